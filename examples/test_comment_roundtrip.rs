@@ -28,12 +28,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Successfully reloaded!");
 
     // Check comment count in reloaded
-    match reloaded.value() {
-        YamlValue::Object(map) => {
-            let comment_count = map.keys().filter(|k| k.starts_with("__comment_")).count();
-            println!("Comments in reloaded: {}", comment_count);
-        }
-        _ => {}
+    if let YamlValue::Object(map) = reloaded.value() {
+        let comment_count = map.keys().filter(|k| k.starts_with("__comment_")).count();
+        println!("Comments in reloaded: {}", comment_count);
     }
 
     Ok(())
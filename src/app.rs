@@ -0,0 +1,352 @@
+//! Top-level application state: the open buffers and the window layout.
+//!
+//! Every file that has been opened with `:e` or `:vsplit` stays in the
+//! buffer list, each as its own [`EditorState`], until the editor exits.
+//! Panes show buffers by index, so switching with `:bn`/`:bp` or closing a
+//! pane never loses unsaved changes.
+//!
+//! Like window commands, buffer commands are queued on the focused buffer's
+//! state by the key handler ([`EditorState::request_buffer_command`]) and
+//! applied here once the key has been handled.
+
+use crate::editor::state::{EditorState, MessageLevel};
+use crate::editor::window::{WindowCommand, Windows};
+use crate::file::loader::load_yaml_file_lazy;
+use anyhow::Result;
+use std::path::Path;
+
+/// A request to change which buffer is shown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BufferCommand {
+    /// Open a file in a new buffer, or switch to it if already open (`:e`)
+    Edit(String),
+    /// Show the next buffer in the focused pane (`:bn`)
+    Next,
+    /// Show the previous buffer in the focused pane (`:bp`)
+    Prev,
+    /// Show buffer N, counting from 1 (`:b N`)
+    Goto(usize),
+    /// List the open buffers (`:ls`)
+    List,
+    /// Open a file and move to a bookmarked node in it (bookmark jumps)
+    Jump {
+        /// File to open
+        file: String,
+        /// Name of the bookmark
+        bookmark: String,
+    },
+    /// Open a file and move to the node at a YAMLPath in it (`:usages`)
+    JumpPath {
+        /// File to open
+        file: String,
+        /// YAMLPath of the node
+        path: String,
+    },
+}
+
+/// The open buffers and the panes showing them.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::app::{App, BufferCommand};
+/// use yamlquill::document::node::{YamlNode, YamlValue};
+/// use yamlquill::document::tree::YamlTree;
+/// use yamlquill::editor::state::EditorState;
+///
+/// let tree = YamlTree::new(YamlNode::new(YamlValue::Null));
+/// let mut app = App::new(EditorState::new_with_default_theme(tree));
+/// assert_eq!(app.buffers().len(), 1);
+///
+/// app.apply_buffer_command(BufferCommand::Next).unwrap();
+/// assert_eq!(app.focused_buffer_index(), 0);
+/// ```
+pub struct App {
+    buffers: Vec<EditorState>,
+    windows: Windows,
+}
+
+impl App {
+    /// Creates an app with a single buffer in a single pane.
+    pub fn new(state: EditorState) -> Self {
+        let mut app = Self {
+            buffers: vec![state],
+            windows: Windows::new(0),
+        };
+        app.sync_buffer_positions();
+        app
+    }
+
+    /// Returns the open buffers, in the order they were opened.
+    pub fn buffers(&self) -> &[EditorState] {
+        &self.buffers
+    }
+
+    /// Returns mutable access to all buffers.
+    pub fn buffers_mut(&mut self) -> &mut [EditorState] {
+        &mut self.buffers
+    }
+
+    /// Returns the window layout.
+    pub fn windows(&self) -> &Windows {
+        &self.windows
+    }
+
+    /// Returns the index of the buffer in the focused pane.
+    pub fn focused_buffer_index(&self) -> usize {
+        self.windows.focused_buffer()
+    }
+
+    /// Returns the buffer in the focused pane.
+    pub fn focused(&self) -> &EditorState {
+        &self.buffers[self.windows.focused_buffer()]
+    }
+
+    /// Returns the buffer in the focused pane mutably.
+    pub fn focused_mut(&mut self) -> &mut EditorState {
+        &mut self.buffers[self.windows.focused_buffer()]
+    }
+
+    /// Consumes the app and returns the buffer in the focused pane.
+    pub fn into_focused(mut self) -> EditorState {
+        let index = self.windows.focused_buffer();
+        self.buffers.swap_remove(index)
+    }
+
+    /// Returns the index of the buffer editing `filename`, loading it if needed.
+    ///
+    /// New buffers inherit the focused buffer's settings.
+    pub fn open_buffer(&mut self, filename: &str) -> Result<usize> {
+        if let Some(index) = self
+            .buffers
+            .iter()
+            .position(|b| b.filename().is_some_and(|f| same_file(f, filename)))
+        {
+            return Ok(index);
+        }
+
+        let current = self.focused();
+        let tree = load_yaml_file_lazy(
+            filename,
+            current.lazy_load_threshold(),
+            current.load_limits(),
+        )?;
+        let mut buffer = EditorState::new(tree, current.current_theme().to_string());
+        buffer.set_filename(filename.to_string());
+        buffer.set_read_only(current.is_read_only());
+        buffer.set_safe_mode(current.is_safe_mode());
+        buffer.apply_config(&current.to_config());
+        buffer.set_trust_store(current.trust_store().clone());
+        buffer.set_bookmark_dir(current.bookmark_dir().map(Path::to_path_buf));
+        buffer.load_buffer_settings();
+        buffer.load_undo_history();
+        buffer.load_notes();
+        buffer.set_message(format!("\"{}\" loaded", filename), MessageLevel::Info);
+
+        self.buffers.push(buffer);
+        self.sync_buffer_positions();
+        Ok(self.buffers.len() - 1)
+    }
+
+    /// Applies a window command.
+    pub fn apply_window_command(&mut self, command: WindowCommand) -> Result<()> {
+        match command {
+            WindowCommand::VSplit(filename) => {
+                let buffer = self.open_buffer(&filename)?;
+                self.windows.split(buffer);
+            }
+            WindowCommand::FocusNext => self.windows.focus_next(),
+            WindowCommand::FocusLeft => self.windows.focus_left(),
+            WindowCommand::FocusRight => self.windows.focus_right(),
+            WindowCommand::Close => {
+                if !self.windows.close_focused() {
+                    anyhow::bail!("Cannot close last window");
+                }
+            }
+            WindowCommand::Only => self.windows.only(),
+        }
+        Ok(())
+    }
+
+    /// Applies a buffer command to the focused pane.
+    pub fn apply_buffer_command(&mut self, command: BufferCommand) -> Result<()> {
+        let count = self.buffers.len();
+        let current = self.windows.focused_buffer();
+        match command {
+            BufferCommand::Edit(filename) => {
+                let buffer = self.open_buffer(&filename)?;
+                self.windows.set_focused_buffer(buffer);
+            }
+            BufferCommand::Next => self.windows.set_focused_buffer((current + 1) % count),
+            BufferCommand::Prev => self
+                .windows
+                .set_focused_buffer((current + count - 1) % count),
+            BufferCommand::Goto(number) => {
+                if number == 0 || number > count {
+                    anyhow::bail!("Buffer {} does not exist", number);
+                }
+                self.windows.set_focused_buffer(number - 1);
+            }
+            BufferCommand::Jump { file, bookmark } => {
+                let buffer = self.open_buffer(&file)?;
+                self.windows.set_focused_buffer(buffer);
+                self.focused_mut().go_to_bookmark(&bookmark)?;
+            }
+            BufferCommand::JumpPath { file, path } => {
+                let buffer = self.open_buffer(&file)?;
+                self.windows.set_focused_buffer(buffer);
+                self.focused_mut().jump_to_yamlpath(&path)?;
+            }
+            BufferCommand::List => {
+                let list = self.buffer_list();
+                self.focused_mut().set_message(list, MessageLevel::Info);
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the window and buffer commands queued by the focused buffer, if any.
+    ///
+    /// Failures are reported in the focused buffer's message area.
+    pub fn handle_pending(&mut self) {
+        let window_command = self.focused_mut().take_pending_window_command();
+        let buffer_command = self.focused_mut().take_pending_buffer_command();
+
+        let result = window_command
+            .map_or(Ok(()), |c| self.apply_window_command(c))
+            .and_then(|_| buffer_command.map_or(Ok(()), |c| self.apply_buffer_command(c)));
+        if let Err(e) = result {
+            self.focused_mut()
+                .set_message(e.to_string(), MessageLevel::Error);
+        }
+    }
+
+    /// Handles a quit (`:q`, `:wq`, `ZZ`) from the focused pane.
+    ///
+    /// In a split this closes just the focused pane. In the last pane it
+    /// returns true to exit, unless another buffer has unsaved changes and
+    /// the quit was not forced with `:q!`; that buffer is shown instead.
+    pub fn quit(&mut self) -> bool {
+        let forced = self.focused_mut().take_force_quit();
+        if self.windows.close_focused() {
+            return false;
+        }
+        if !forced {
+            if let Some(index) = self.buffers.iter().position(|b| b.is_dirty()) {
+                self.windows.set_focused_buffer(index);
+                let message = format!(
+                    "No write since last change for buffer {} (use :q! to force)",
+                    index + 1
+                );
+                self.focused_mut().set_message(message, MessageLevel::Error);
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns the one-line buffer listing shown by `:ls`.
+    ///
+    /// `%` marks the focused buffer and `+` one with unsaved changes.
+    fn buffer_list(&self) -> String {
+        let current = self.windows.focused_buffer();
+        self.buffers
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                format!(
+                    "{}{}{} \"{}\"",
+                    i + 1,
+                    if i == current { "%" } else { "" },
+                    if b.is_dirty() { "+" } else { "" },
+                    b.filename().unwrap_or("[No Name]")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+
+    /// Tells each buffer its position in the list, for the status line.
+    fn sync_buffer_positions(&mut self) {
+        let count = self.buffers.len();
+        for (i, buffer) in self.buffers.iter_mut().enumerate() {
+            buffer.set_buffer_position(i + 1, count);
+        }
+    }
+}
+
+/// Returns true if both names refer to the same file.
+fn same_file(a: &str, b: &str) -> bool {
+    match (Path::new(a).canonicalize(), Path::new(b).canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::node::{YamlNode, YamlValue};
+    use crate::document::tree::YamlTree;
+
+    fn buffer(name: &str) -> EditorState {
+        let mut state =
+            EditorState::new_with_default_theme(YamlTree::new(YamlNode::new(YamlValue::Null)));
+        state.set_filename(name.to_string());
+        state
+    }
+
+    fn app_with(names: &[&str]) -> App {
+        let mut app = App::new(buffer(names[0]));
+        for name in &names[1..] {
+            app.buffers.push(buffer(name));
+        }
+        app.sync_buffer_positions();
+        app
+    }
+
+    #[test]
+    fn test_buffer_cycling() {
+        let mut app = app_with(&["a", "b", "c"]);
+        app.apply_buffer_command(BufferCommand::Next).unwrap();
+        assert_eq!(app.focused().filename(), Some("b"));
+        app.apply_buffer_command(BufferCommand::Prev).unwrap();
+        app.apply_buffer_command(BufferCommand::Prev).unwrap();
+        assert_eq!(app.focused().filename(), Some("c"));
+        app.apply_buffer_command(BufferCommand::Goto(1)).unwrap();
+        assert_eq!(app.focused().filename(), Some("a"));
+        assert!(app.apply_buffer_command(BufferCommand::Goto(4)).is_err());
+    }
+
+    #[test]
+    fn test_buffer_list() {
+        let mut app = app_with(&["a", "b"]);
+        app.buffers[1].mark_dirty();
+        assert_eq!(app.buffer_list(), "1% \"a\"  2+ \"b\"");
+        assert_eq!(app.buffers[1].buffer_position(), Some((2, 2)));
+    }
+
+    #[test]
+    fn test_quit_with_hidden_dirty_buffer() {
+        let mut app = app_with(&["a", "b"]);
+        app.buffers[1].mark_dirty();
+
+        assert!(!app.quit());
+        assert_eq!(app.focused().filename(), Some("b"));
+
+        app.focused_mut().request_force_quit();
+        assert!(app.quit());
+    }
+
+    #[test]
+    fn test_close_keeps_buffer() {
+        let mut app = app_with(&["a", "b"]);
+        app.windows.split(1);
+        app.buffers[1].mark_dirty();
+
+        app.apply_window_command(WindowCommand::Close).unwrap();
+        assert_eq!(app.windows().len(), 1);
+        assert_eq!(app.buffers().len(), 2);
+        assert!(app.apply_window_command(WindowCommand::Close).is_err());
+    }
+}
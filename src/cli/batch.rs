@@ -0,0 +1,209 @@
+//! Non-interactive editing (`--apply`).
+//!
+//! Each `--apply` is a YAMLPath edit:
+//!
+//! - `QUERY=VALUE` sets every scalar the query matches, like `:path-set`
+//! - `del(QUERY)` deletes every node it matches, like `:path-delete`
+//!
+//! Edits run in order against one editor state, through the same code as
+//! the interactive commands, and nothing is written unless all of them
+//! succeed. An edit that matches nothing is an error, so a typo in a path
+//! can't pass silently.
+//!
+//! ```
+//! use yamlquill::cli::batch::{apply, Edit};
+//! use yamlquill::document::parser::parse_yaml_auto;
+//! use yamlquill::document::tree::YamlTree;
+//! use yamlquill::file::saver::yaml_text;
+//!
+//! let tree = YamlTree::new(parse_yaml_auto("spec:\n  replicas: 1\n  paused: true\n").unwrap());
+//! let edits = [
+//!     Edit::parse("$.spec.replicas=3").unwrap(),
+//!     Edit::parse("del($.spec.paused)").unwrap(),
+//! ];
+//! let tree = apply(tree, &edits).unwrap();
+//! assert_eq!(yaml_text(&tree).unwrap().trim_end(), "spec:\n  replicas: 3");
+//! ```
+
+use crate::config::Config;
+use crate::document::tree::YamlTree;
+use crate::editor::state::EditorState;
+use crate::file::exporter::{save_file, to_json, to_toml, Format};
+use crate::file::saver::yaml_text;
+use anyhow::{bail, Context, Result};
+use std::fmt;
+
+/// One `--apply` edit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit {
+    /// Sets every scalar matching `query`; `value` is typed as in `:path-set`.
+    Set { query: String, value: String },
+    /// Deletes every node matching `query`.
+    Delete { query: String },
+}
+
+impl Edit {
+    /// Parses `QUERY=VALUE` or `del(QUERY)`.
+    ///
+    /// The `=` is the first one outside brackets and quotes, so filters like
+    /// `$.items[?(@.name == 'web')].image=nginx` work.
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if let Some(query) = text
+            .strip_prefix("del(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let query = query.trim();
+            if query.is_empty() {
+                bail!("del() needs a path");
+            }
+            return Ok(Edit::Delete {
+                query: query.to_string(),
+            });
+        }
+
+        let (query, value) = split_assignment(text)
+            .with_context(|| format!("Expected QUERY=VALUE or del(QUERY), got '{}'", text))?;
+        let query = query.trim();
+        if query.is_empty() {
+            bail!("Missing path before '=' in '{}'", text);
+        }
+        Ok(Edit::Set {
+            query: query.to_string(),
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Edit::Set { query, value } => write!(f, "{}={}", query, value),
+            Edit::Delete { query } => write!(f, "del({})", query),
+        }
+    }
+}
+
+/// Splits at the first `=` outside brackets and quotes that isn't part of `==`.
+fn split_assignment(text: &str) -> Option<(&str, &str)> {
+    let bytes = text.as_bytes();
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (i, &b) in bytes.iter().enumerate() {
+        match (quote, b) {
+            (Some(q), _) if b == q => quote = None,
+            (Some(_), _) => {}
+            (None, b'\'' | b'"') => quote = Some(b),
+            (None, b'[' | b'(') => depth += 1,
+            (None, b']' | b')') => depth = depth.saturating_sub(1),
+            (None, b'=') if depth == 0 && bytes.get(i + 1) != Some(&b'=') => {
+                return Some((&text[..i], &text[i + 1..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Applies `edits` in order and returns the edited tree.
+///
+/// Fails on the first edit that is invalid or matches nothing.
+pub fn apply(tree: YamlTree, edits: &[Edit]) -> Result<YamlTree> {
+    let mut state = EditorState::new_with_default_theme(tree);
+    for edit in edits {
+        let changed = match edit {
+            Edit::Set { query, value } => state.path_set(query, value),
+            Edit::Delete { query } => state.path_delete(query),
+        }
+        .with_context(|| format!("--apply '{}'", edit))?;
+        if changed == 0 {
+            match edit {
+                Edit::Set { query, .. } => {
+                    bail!("--apply '{}': {} matches no scalar values", edit, query)
+                }
+                Edit::Delete { query } => bail!("--apply '{}': {} matches nothing", edit, query),
+            }
+        }
+    }
+    Ok(state.tree().clone())
+}
+
+/// Runs `--apply` edits on a loaded document.
+///
+/// With `in_place`, the result is saved over `file` the way `:w` would save
+/// it, and `None` is returned. Otherwise the result is returned as text in
+/// the file's format, for printing.
+pub fn run(
+    tree: YamlTree,
+    file: Option<&str>,
+    edits: &[Edit],
+    in_place: bool,
+    config: &Config,
+) -> Result<Option<String>> {
+    let tree = apply(tree, edits)?;
+    if in_place {
+        let file = file.context("--in-place needs a file, not stdin")?;
+        save_file(file, &tree, config)?;
+        return Ok(None);
+    }
+
+    let format = file.map_or(Format::Yaml, Format::from_path);
+    let mut text = match format {
+        Format::Yaml => yaml_text(&tree)?,
+        Format::Json => to_json(&tree)?,
+        Format::Toml => to_toml(&tree)?,
+    };
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    Ok(Some(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_edits() {
+        assert_eq!(
+            Edit::parse("$.spec.replicas=3").unwrap(),
+            Edit::Set {
+                query: "$.spec.replicas".to_string(),
+                value: "3".to_string()
+            }
+        );
+        assert_eq!(
+            Edit::parse("$.items[?(@.name == 'a=b')].image = nginx:1.25").unwrap(),
+            Edit::Set {
+                query: "$.items[?(@.name == 'a=b')].image".to_string(),
+                value: "nginx:1.25".to_string()
+            }
+        );
+        assert_eq!(
+            Edit::parse("$.a='x = y'").unwrap(),
+            Edit::Set {
+                query: "$.a".to_string(),
+                value: "'x = y'".to_string()
+            }
+        );
+        assert_eq!(
+            Edit::parse(" del($.metadata.annotations) ").unwrap(),
+            Edit::Delete {
+                query: "$.metadata.annotations".to_string()
+            }
+        );
+        assert!(Edit::parse("$.a").is_err());
+        assert!(Edit::parse("=3").is_err());
+        assert!(Edit::parse("del()").is_err());
+    }
+
+    #[test]
+    fn test_edit_matching_nothing_is_an_error() {
+        let tree = YamlTree::new(crate::document::parser::parse_yaml_auto("a: 1\n").unwrap());
+        let err = apply(tree, &[Edit::parse("$.b=2").unwrap()]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "--apply '$.b=2': $.b matches no scalar values"
+        );
+    }
+}
@@ -0,0 +1,5 @@
+//! Command-line modes that run without the terminal UI.
+//!
+//! - [`batch`]: YAMLPath edits from `--apply`, printed or written in place
+
+pub mod batch;
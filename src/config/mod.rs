@@ -22,6 +22,12 @@
 //! };
 //! ```
 
+pub mod modeline;
+pub mod profile;
+pub mod project;
+pub mod trust;
+
+use crate::document::limits::LoadLimits;
 use serde::{Deserialize, Serialize};
 
 /// Configuration for the yamlquill application.
@@ -40,9 +46,36 @@ use serde::{Deserialize, Serialize};
 /// * `create_backup` - Create .bak files before saving (default: false)
 /// * `undo_limit` - Maximum number of undo operations to keep (default: 50)
 /// * `sync_unnamed_register` - Sync unnamed register with system clipboard (default: true)
-/// * `lazy_load_threshold` - File size in bytes to trigger lazy loading (default: 100MB)
+/// * `lazy_load_threshold` - YAML files of at least this many bytes are parsed lazily, section by section (default: 100MB)
+/// * `max_file_size_mb` - Largest file opened, in megabytes, 0 for no limit (default: 1024)
+/// * `max_parse_depth` - Deepest nesting a YAML file may have, 0 for no limit (default: 512)
+/// * `max_node_count` - Most nodes a YAML file may have, 0 for no limit (default: 20,000,000)
+/// * `max_alias_expansion` - Most nodes a YAML file's aliases may stand for, 0 for no limit (default: 1,000,000)
 /// * `enable_mouse` - Enable mouse/trackpad scrolling support (default: true)
 /// * `preserve_formatting` - Preserve original formatting for unmodified nodes (default: true)
+/// * `resolve_merge_keys` - Show entries inherited through `<<` merge keys (default: false)
+/// * `persistent_undo` - Keep undo history in a `.file.yaml.undo` sidecar across sessions (default: false)
+/// * `reload_on_change` - Reload unmodified buffers when their file changes on disk (default: false)
+/// * `regex_search` - Treat `/` and `?` patterns as regular expressions (default: false)
+/// * `search_collapsed` - Search inside collapsed nodes, expanding them to show matches (default: true)
+/// * `show_breadcrumbs` - Show the cursor's dotted path in a bar above the tree (default: false)
+/// * `show_type_badges` - Show each value's type (str/int/float/bool/null/map/seq) after its line (default: false)
+/// * `cloudformation` - Highlight CloudFormation intrinsics and complete `!Ref`/`!GetAtt` targets (default: false)
+/// * `decode_secrets` - Show the base64 values under a Secret's `data` decoded (default: false)
+/// * `k8s` - Enable the Kubernetes commands `:containers`, `:images` and `:env` (default: false)
+/// * `prometheus` - Check `expr` fields as PromQL and fold rules to their names (default: false)
+/// * `auto_validate` - Re-validate against the buffer's schema while idle after each edit (default: false)
+/// * `scrolloff` - Lines of context kept above and below the cursor when scrolling (default: 0)
+/// * `max_render_depth` - Levels of nesting shown before deeper subtrees are summarized, 0 for no limit (default: 64)
+/// * `mouse_scroll_lines` - Lines (or columns) scrolled per mouse wheel tick (default: 3)
+/// * `memory_budget_mb` - Estimated memory for a buffer's document, undo history and registers before the editor degrades; 0 for no limit (default: 2048)
+/// * `yaml_style` - How YAML is written: "preserving", "canonical" or "compact" (default: "preserving")
+/// * `json_style` - How JSON is written: "preserving", "canonical" or "compact" (default: "preserving")
+/// * `undo_file_limit_mb` - Largest size of the history in an undo file before compression; the oldest states are left out to fit, and bigger files aren't read, 0 for no limit (default: 64)
+/// * `preview_fields` - Dotted fields shown first in every multi-document record's preview (default: none)
+/// * `statusline` - File details after the filename: `%s` size, `%t` modified time, `%n` a warning when the file on disk is newer (default: "%s %t %n")
+/// * `lint_disabled` - `:lint` rules to skip, e.g. `["long-lines"]` (default: none)
+/// * `lint_line_length` - Longest line `:lint` accepts, 0 for no limit (default: 120)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     /// Color scheme name
@@ -77,10 +110,27 @@ pub struct Config {
     #[serde(default = "default_sync_unnamed_register")]
     pub sync_unnamed_register: bool,
 
-    /// File size in bytes to trigger lazy loading
+    /// File size in bytes from which YAML files are parsed lazily
     #[serde(default = "default_lazy_load_threshold")]
     pub lazy_load_threshold: usize,
 
+    /// Largest file that is opened, in megabytes; 0 for no limit
+    #[serde(default = "default_max_file_size_mb")]
+    pub max_file_size_mb: usize,
+
+    /// Deepest nesting of mappings and lists a YAML file may have; 0 for no limit
+    #[serde(default = "default_max_parse_depth")]
+    pub max_parse_depth: usize,
+
+    /// Most nodes a YAML file may have; 0 for no limit
+    #[serde(default = "default_max_node_count")]
+    pub max_node_count: usize,
+
+    /// Most nodes a YAML file's aliases may stand for, counting an alias of
+    /// a subtree as all of its nodes; 0 for no limit
+    #[serde(default = "default_max_alias_expansion")]
+    pub max_alias_expansion: usize,
+
     /// Enable mouse/trackpad scrolling
     #[serde(default = "default_enable_mouse")]
     pub enable_mouse: bool,
@@ -93,6 +143,106 @@ pub struct Config {
     /// When enabled, unmodified portions of JSON retain exact original formatting
     #[serde(default = "default_preserve_formatting")]
     pub preserve_formatting: bool,
+
+    /// Show entries inherited through YAML merge keys (`<<: *anchor`)
+    #[serde(default)]
+    pub resolve_merge_keys: bool,
+
+    /// Save undo history next to the file and restore it when the file is reopened
+    #[serde(default)]
+    pub persistent_undo: bool,
+
+    /// Reload the buffer without asking when its file changes on disk and there are no unsaved changes
+    #[serde(default)]
+    pub reload_on_change: bool,
+
+    /// Treat `/` and `?` patterns as regular expressions without a `\v` prefix
+    #[serde(default)]
+    pub regex_search: bool,
+
+    /// Search the whole document, expanding collapsed nodes to show matches;
+    /// when false, `/` only searches the lines currently shown
+    #[serde(default = "default_search_collapsed")]
+    pub search_collapsed: bool,
+
+    /// Show the cursor's dotted path in a breadcrumb bar above the tree
+    #[serde(default)]
+    pub show_breadcrumbs: bool,
+
+    /// Show each value's type after its line in the tree view
+    #[serde(default)]
+    pub show_type_badges: bool,
+
+    /// Highlight CloudFormation intrinsics and complete `!Ref`/`!GetAtt` targets
+    #[serde(default)]
+    pub cloudformation: bool,
+
+    /// Show the base64 values under a Kubernetes Secret's `data` decoded
+    #[serde(default)]
+    pub decode_secrets: bool,
+
+    /// Enable the Kubernetes commands `:containers`, `:images` and `:env`
+    #[serde(default)]
+    pub k8s: bool,
+
+    /// Check `expr` fields as PromQL and fold Prometheus rules to their names
+    #[serde(default)]
+    pub prometheus: bool,
+
+    /// Re-validate against the buffer's schema in idle time after each edit
+    #[serde(default)]
+    pub auto_validate: bool,
+
+    /// Lines of context to keep above and below the cursor when scrolling (like vim's scrolloff)
+    #[serde(default)]
+    pub scrolloff: usize,
+
+    /// Levels of nesting shown in the tree; expanded nodes below that are
+    /// summarized until expanded deeper (`zd`). 0 for no limit
+    #[serde(default = "default_max_render_depth")]
+    pub max_render_depth: usize,
+
+    /// Lines scrolled per mouse wheel tick, and columns per horizontal tick
+    #[serde(default = "default_mouse_scroll_lines")]
+    pub mouse_scroll_lines: usize,
+
+    /// Megabytes a buffer's document, undo history and registers may take
+    /// before old undo states are dropped; 0 for no limit
+    #[serde(default = "default_memory_budget_mb")]
+    pub memory_budget_mb: usize,
+
+    /// How YAML files are written: "preserving", "canonical" or "compact"
+    #[serde(default = "default_style")]
+    pub yaml_style: String,
+
+    /// How JSON files are written: "preserving", "canonical" or "compact"
+    #[serde(default = "default_style")]
+    pub json_style: String,
+
+    /// Megabytes the history in an undo file may take before compression;
+    /// the oldest states are left out to fit and bigger files aren't read,
+    /// 0 for no limit
+    #[serde(default = "default_undo_file_limit_mb")]
+    pub undo_file_limit_mb: usize,
+
+    /// Fields (dotted paths like `status.phase`) pinned to the front of each
+    /// multi-document/JSONL record's collapsed preview
+    #[serde(default)]
+    pub preview_fields: Vec<String>,
+
+    /// File details shown after the filename in the status line: `%s` size,
+    /// `%t` last modified time, `%n` a warning when the file on disk is newer
+    /// than the buffer; empty to show none
+    #[serde(default = "default_statusline")]
+    pub statusline: String,
+
+    /// Names of the `:lint` rules to skip, such as `long-lines`
+    #[serde(default)]
+    pub lint_disabled: Vec<String>,
+
+    /// Characters a line may have before `:lint` reports it; 0 for no limit
+    #[serde(default = "default_lint_line_length")]
+    pub lint_line_length: usize,
 }
 
 /// Returns the default theme name.
@@ -130,11 +280,60 @@ fn default_lazy_load_threshold() -> usize {
     104_857_600 // 100MB
 }
 
+fn default_max_file_size_mb() -> usize {
+    LoadLimits::default().max_file_size_mb
+}
+
+fn default_max_parse_depth() -> usize {
+    LoadLimits::default().max_depth
+}
+
+fn default_max_node_count() -> usize {
+    LoadLimits::default().max_nodes
+}
+
+fn default_max_alias_expansion() -> usize {
+    LoadLimits::default().max_alias_expansion
+}
+
 /// Returns the default for enabling mouse support.
 fn default_enable_mouse() -> bool {
     true
 }
 
+/// Returns the default number of lines scrolled per mouse wheel tick.
+fn default_search_collapsed() -> bool {
+    true
+}
+
+fn default_memory_budget_mb() -> usize {
+    2048
+}
+
+fn default_style() -> String {
+    "preserving".to_string()
+}
+
+fn default_undo_file_limit_mb() -> usize {
+    64
+}
+
+fn default_max_render_depth() -> usize {
+    64
+}
+
+fn default_lint_line_length() -> usize {
+    120
+}
+
+fn default_mouse_scroll_lines() -> usize {
+    3
+}
+
+fn default_statusline() -> String {
+    "%s %t %n".to_string()
+}
+
 /// Returns the default for preserving formatting.
 fn default_preserve_formatting() -> bool {
     true // Enabled by default - preserves original formatting for unmodified nodes
@@ -154,8 +353,35 @@ impl Default for Config {
     /// * `undo_limit`: 50
     /// * `sync_unnamed_register`: true
     /// * `lazy_load_threshold`: 104,857,600 (100MB)
+    /// * `max_file_size_mb`: 1024
+    /// * `max_parse_depth`: 512
+    /// * `max_node_count`: 20,000,000
+    /// * `max_alias_expansion`: 1,000,000
     /// * `enable_mouse`: true
     /// * `preserve_formatting`: true
+    /// * `resolve_merge_keys`: false
+    /// * `persistent_undo`: false
+    /// * `reload_on_change`: false
+    /// * `regex_search`: false
+    /// * `search_collapsed`: true
+    /// * `show_breadcrumbs`: false
+    /// * `show_type_badges`: false
+    /// * `cloudformation`: false
+    /// * `decode_secrets`: false
+    /// * `k8s`: false
+    /// * `prometheus`: false
+    /// * `auto_validate`: false
+    /// * `scrolloff`: 0
+    /// * `max_render_depth`: 64
+    /// * `mouse_scroll_lines`: 3
+    /// * `memory_budget_mb`: 2048
+    /// * `yaml_style`: "preserving"
+    /// * `json_style`: "preserving"
+    /// * `undo_file_limit_mb`: 64
+    /// * `preview_fields`: empty
+    /// * `statusline`: "%s %t %n"
+    /// * `lint_disabled`: empty
+    /// * `lint_line_length`: 120
     ///
     /// # Example
     ///
@@ -178,9 +404,36 @@ impl Default for Config {
             undo_limit: default_undo_limit(),
             sync_unnamed_register: true,
             lazy_load_threshold: default_lazy_load_threshold(),
+            max_file_size_mb: default_max_file_size_mb(),
+            max_parse_depth: default_max_parse_depth(),
+            max_node_count: default_max_node_count(),
+            max_alias_expansion: default_max_alias_expansion(),
             enable_mouse: default_enable_mouse(),
             relative_line_numbers: false,
             preserve_formatting: default_preserve_formatting(),
+            resolve_merge_keys: false,
+            persistent_undo: false,
+            reload_on_change: false,
+            regex_search: false,
+            search_collapsed: default_search_collapsed(),
+            show_breadcrumbs: false,
+            show_type_badges: false,
+            cloudformation: false,
+            decode_secrets: false,
+            k8s: false,
+            prometheus: false,
+            auto_validate: false,
+            scrolloff: 0,
+            max_render_depth: default_max_render_depth(),
+            mouse_scroll_lines: default_mouse_scroll_lines(),
+            memory_budget_mb: default_memory_budget_mb(),
+            yaml_style: default_style(),
+            json_style: default_style(),
+            undo_file_limit_mb: default_undo_file_limit_mb(),
+            preview_fields: Vec::new(),
+            statusline: default_statusline(),
+            lint_disabled: Vec::new(),
+            lint_line_length: default_lint_line_length(),
         }
     }
 }
@@ -198,6 +451,16 @@ impl Config {
         })
     }
 
+    /// Returns the limits files are checked against when they are opened.
+    pub fn load_limits(&self) -> LoadLimits {
+        LoadLimits {
+            max_file_size_mb: self.max_file_size_mb,
+            max_depth: self.max_parse_depth,
+            max_nodes: self.max_node_count,
+            max_alias_expansion: self.max_alias_expansion,
+        }
+    }
+
     /// Loads configuration from the default config file.
     ///
     /// Returns the default configuration if the file doesn't exist or can't be read.
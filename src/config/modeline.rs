@@ -0,0 +1,240 @@
+//! Modeline support for per-file settings.
+//!
+//! Like vim modelines, a YAML file can carry editor settings in a comment near
+//! the top or bottom of the file:
+//!
+//! ```yaml
+//! # yamlquill: indent=4 foldlevel=2 schema=./schema.json
+//! ```
+//!
+//! Only the first and last [`MODELINE_SCAN_LINES`] lines are scanned. Options
+//! are checked against a fixed allowlist and anything else is rejected, so a
+//! modeline can only adjust harmless display and formatting settings. Options
+//! apply to the buffer they were found in and are never written back to the
+//! user's config file.
+//!
+//! # Example
+//!
+//! ```
+//! use yamlquill::config::modeline::{parse_modeline, ModelineOption};
+//!
+//! let source = "# yamlquill: indent=4 foldlevel=1\nname: test\n";
+//! let modeline = parse_modeline(source).unwrap();
+//! assert_eq!(
+//!     modeline.options,
+//!     vec![ModelineOption::Indent(4), ModelineOption::FoldLevel(1)]
+//! );
+//! ```
+
+use super::Config;
+
+/// Number of lines at the start and end of a file that are scanned for a modeline.
+pub const MODELINE_SCAN_LINES: usize = 5;
+
+/// Marker that introduces a modeline inside a comment.
+const MODELINE_MARKER: &str = "yamlquill:";
+
+/// Largest indent a modeline may request.
+const MAX_INDENT: usize = 8;
+
+/// A single allowlisted modeline option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModelineOption {
+    /// `indent=N` - spaces per indentation level when saving or formatting
+    Indent(usize),
+    /// `foldlevel=N` - containers nested deeper than N start collapsed
+    FoldLevel(usize),
+    /// `schema=PATH` - JSON Schema associated with the file
+    Schema(String),
+    /// `number` / `nonumber`
+    Number(bool),
+    /// `relativenumber` / `norelativenumber`
+    RelativeNumber(bool),
+    /// `resolvemerge` / `noresolvemerge`
+    ResolveMerge(bool),
+}
+
+/// Settings parsed from a modeline.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Modeline {
+    /// Accepted options, in the order they appeared
+    pub options: Vec<ModelineOption>,
+    /// Options that were not on the allowlist or had invalid values
+    pub rejected: Vec<String>,
+}
+
+impl Modeline {
+    /// Parses the options of a single modeline (the text after `yamlquill:`).
+    pub fn parse_options(text: &str) -> Self {
+        let mut modeline = Modeline::default();
+        for token in text.split_whitespace() {
+            match parse_option(token) {
+                Some(option) => modeline.options.push(option),
+                None => modeline.rejected.push(token.to_string()),
+            }
+        }
+        modeline
+    }
+
+    /// Returns the schema path requested by the modeline, if any.
+    pub fn schema(&self) -> Option<&str> {
+        self.options.iter().rev().find_map(|opt| match opt {
+            ModelineOption::Schema(path) => Some(path.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Returns the fold level requested by the modeline, if any.
+    pub fn fold_level(&self) -> Option<usize> {
+        self.options.iter().rev().find_map(|opt| match opt {
+            ModelineOption::FoldLevel(level) => Some(*level),
+            _ => None,
+        })
+    }
+
+    /// Resets every config setting this modeline overrides back to `defaults`.
+    ///
+    /// Used so that saving settings never persists buffer-local values.
+    pub fn revert(&self, config: &mut Config, defaults: &Config) {
+        for option in &self.options {
+            match option {
+                ModelineOption::Indent(_) => config.indent_size = defaults.indent_size,
+                ModelineOption::Number(_) => config.show_line_numbers = defaults.show_line_numbers,
+                ModelineOption::RelativeNumber(_) => {
+                    config.relative_line_numbers = defaults.relative_line_numbers
+                }
+                ModelineOption::ResolveMerge(_) => {
+                    config.resolve_merge_keys = defaults.resolve_merge_keys
+                }
+                ModelineOption::FoldLevel(_) | ModelineOption::Schema(_) => {}
+            }
+        }
+    }
+}
+
+/// Parses one `name` or `name=value` token against the allowlist.
+fn parse_option(token: &str) -> Option<ModelineOption> {
+    let (name, value) = match token.split_once('=') {
+        Some((name, value)) => (name, Some(value)),
+        None => (token, None),
+    };
+
+    match (name, value) {
+        ("indent" | "sw" | "shiftwidth", Some(v)) => v
+            .parse::<usize>()
+            .ok()
+            .filter(|n| (1..=MAX_INDENT).contains(n))
+            .map(ModelineOption::Indent),
+        ("foldlevel" | "fdl", Some(v)) => v.parse::<usize>().ok().map(ModelineOption::FoldLevel),
+        ("schema", Some(v)) if !v.is_empty() => Some(ModelineOption::Schema(v.to_string())),
+        ("number" | "nu", None) => Some(ModelineOption::Number(true)),
+        ("nonumber" | "nonu", None) => Some(ModelineOption::Number(false)),
+        ("relativenumber" | "rnu", None) => Some(ModelineOption::RelativeNumber(true)),
+        ("norelativenumber" | "nornu", None) => Some(ModelineOption::RelativeNumber(false)),
+        ("resolvemerge", None) => Some(ModelineOption::ResolveMerge(true)),
+        ("noresolvemerge", None) => Some(ModelineOption::ResolveMerge(false)),
+        _ => None,
+    }
+}
+
+/// Extracts the modeline text from a single source line, if it has one.
+fn modeline_text(line: &str) -> Option<&str> {
+    let hash = line.find('#')?;
+    let comment = line[hash + 1..].trim_start();
+    let rest = comment.strip_prefix(MODELINE_MARKER)?;
+    Some(rest.trim())
+}
+
+/// Finds and parses the modeline in a YAML source string.
+///
+/// Scans the first and last [`MODELINE_SCAN_LINES`] lines; the first modeline
+/// found wins. Returns `None` if the file has no modeline.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::config::modeline::parse_modeline;
+///
+/// let source = "a: 1\nb: 2\n# yamlquill: nonumber bogus=1\n";
+/// let modeline = parse_modeline(source).unwrap();
+/// assert_eq!(modeline.options.len(), 1);
+/// assert_eq!(modeline.rejected, vec!["bogus=1".to_string()]);
+///
+/// assert!(parse_modeline("a: 1\n").is_none());
+/// ```
+pub fn parse_modeline(source: &str) -> Option<Modeline> {
+    let lines: Vec<&str> = source.lines().collect();
+    let head = lines.iter().take(MODELINE_SCAN_LINES);
+    let tail_start = lines
+        .len()
+        .saturating_sub(MODELINE_SCAN_LINES)
+        .max(MODELINE_SCAN_LINES);
+    let tail = lines.iter().skip(tail_start);
+
+    head.chain(tail)
+        .find_map(|line| modeline_text(line))
+        .map(Modeline::parse_options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_modeline_first_line() {
+        let modeline = parse_modeline("# yamlquill: indent=4 schema=./s.json\nkey: v\n").unwrap();
+        assert_eq!(
+            modeline.options,
+            vec![
+                ModelineOption::Indent(4),
+                ModelineOption::Schema("./s.json".to_string())
+            ]
+        );
+        assert_eq!(modeline.schema(), Some("./s.json"));
+        assert!(modeline.rejected.is_empty());
+    }
+
+    #[test]
+    fn test_parse_modeline_trailing_comment_on_value_line() {
+        let source = "a: 1\nb: 2  # yamlquill: foldlevel=0\n";
+        let modeline = parse_modeline(source).unwrap();
+        assert_eq!(modeline.fold_level(), Some(0));
+    }
+
+    #[test]
+    fn test_parse_modeline_ignores_middle_of_file() {
+        let mut source = String::new();
+        for i in 0..6 {
+            source.push_str(&format!("k{}: {}\n", i, i));
+        }
+        source.push_str("# yamlquill: nonumber\n");
+        for i in 6..12 {
+            source.push_str(&format!("k{}: {}\n", i, i));
+        }
+        assert!(parse_modeline(&source).is_none());
+    }
+
+    #[test]
+    fn test_parse_modeline_rejects_unknown_and_invalid_options() {
+        let modeline = Modeline::parse_options("indent=99 exec=rm shell foldlevel=x nu");
+        assert_eq!(modeline.options, vec![ModelineOption::Number(true)]);
+        assert_eq!(
+            modeline.rejected,
+            vec!["indent=99", "exec=rm", "shell", "foldlevel=x"]
+        );
+    }
+
+    #[test]
+    fn test_revert_restores_overridden_settings() {
+        let defaults = Config::default();
+        let mut config = Config {
+            indent_size: 4,
+            show_line_numbers: false,
+            ..Config::default()
+        };
+        let modeline = Modeline::parse_options("indent=4 nonumber");
+        modeline.revert(&mut config, &defaults);
+        assert_eq!(config.indent_size, defaults.indent_size);
+        assert_eq!(config.show_line_numbers, defaults.show_line_numbers);
+    }
+}
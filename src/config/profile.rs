@@ -0,0 +1,131 @@
+//! Settings profiles (`:profile export`, `:profile import`).
+//!
+//! A profile is a TOML file with the same keys as `config.toml`: the
+//! options, the theme and the fields pinned to record previews. A team can
+//! export one and check it in so everyone imports the same setup.
+//!
+//! Importing lays the profile's settings over the current ones and reports
+//! what it changed, which of those were the user's own choices rather than
+//! defaults, and which keys it didn't know.
+
+use super::Config;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Renders `config` as a profile.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::config::Config;
+/// use yamlquill::config::profile::{export, Profile};
+///
+/// let text = export(&Config::default()).unwrap();
+/// assert!(text.starts_with("# yamlquill profile"));
+/// assert!(Profile::parse(&text).unwrap().import(&Config::default()).unwrap().changed.is_empty());
+/// ```
+pub fn export(config: &Config) -> Result<String> {
+    Ok(format!(
+        "# yamlquill profile: import with `:profile import <file>`\n\n{}",
+        toml::to_string_pretty(config)?
+    ))
+}
+
+/// A parsed profile file.
+#[derive(Debug, Clone)]
+pub struct Profile {
+    table: toml::Table,
+}
+
+/// What importing a profile does to the current settings.
+#[derive(Debug, Clone)]
+pub struct Import {
+    /// The current settings with the profile's laid over them
+    pub config: Config,
+    /// Settings whose value the profile changes
+    pub changed: Vec<String>,
+    /// Changed settings that weren't at their default, so replace a choice
+    /// the user made
+    pub conflicts: Vec<String>,
+    /// Keys in the profile that aren't settings; they are ignored
+    pub unknown: Vec<String>,
+}
+
+impl Profile {
+    /// Parses a profile from its contents.
+    pub fn parse(contents: &str) -> Result<Self> {
+        Ok(Self {
+            table: toml::from_str(contents)?,
+        })
+    }
+
+    /// Loads the profile at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Lays the profile over `current`, reporting the changes and conflicts.
+    ///
+    /// Returns an error if a setting has a value of the wrong type, so a
+    /// profile is applied whole or not at all.
+    pub fn import(&self, current: &Config) -> Result<Import> {
+        let defaults = toml::Table::try_from(Config::default())?;
+        let mut merged = toml::Table::try_from(current)?;
+        let mut changed = Vec::new();
+        let mut conflicts = Vec::new();
+        let mut unknown = Vec::new();
+        for (key, value) in &self.table {
+            match merged.get(key) {
+                None => unknown.push(key.clone()),
+                Some(old) if old == value => {}
+                Some(old) => {
+                    changed.push(key.clone());
+                    if Some(old) != defaults.get(key) {
+                        conflicts.push(key.clone());
+                    }
+                    merged.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        let config = merged.try_into().context("Invalid setting in profile")?;
+        Ok(Import {
+            config,
+            changed,
+            conflicts,
+            unknown,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_reports_changes_and_conflicts() {
+        let profile = Profile::parse(
+            "theme = \"nord\"\nindent_size = 4\nscrolloff = 3\nkeymap = \"emacs\"\n",
+        )
+        .unwrap();
+        let current = Config {
+            theme: "gruvbox".to_string(),
+            scrolloff: 3,
+            ..Config::default()
+        };
+
+        let import = profile.import(&current).unwrap();
+        assert_eq!(import.config.theme, "nord");
+        assert_eq!(import.config.indent_size, 4);
+        assert_eq!(import.changed, vec!["theme", "indent_size"]);
+        assert_eq!(import.conflicts, vec!["theme"]);
+        assert_eq!(import.unknown, vec!["keymap"]);
+    }
+
+    #[test]
+    fn test_invalid_value_is_an_error() {
+        let profile = Profile::parse("indent_size = \"wide\"\n").unwrap();
+        assert!(profile.import(&Config::default()).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Project-level configuration (`.yamlquill.toml`).
+//!
+//! A `.yamlquill.toml` in the directory of the file being edited, or in any of
+//! its ancestors, overrides the user's config for files in that project. It
+//! uses the same keys as `config.toml`; only the keys it sets are overridden.
+//! Project configs are only applied once their directory has been trusted
+//! (see [`super::trust`]).
+
+use super::Config;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// File name of a project config.
+pub const PROJECT_CONFIG_FILE: &str = ".yamlquill.toml";
+
+/// A project config file and the settings it overrides.
+#[derive(Debug, Clone)]
+pub struct ProjectConfig {
+    /// Path of the `.yamlquill.toml` file
+    pub path: PathBuf,
+    /// Raw file contents; trust is pinned to them, so any change needs `:trust` again
+    pub contents: String,
+    table: toml::Table,
+}
+
+impl ProjectConfig {
+    /// Parses a project config from its file contents.
+    pub fn parse(path: PathBuf, contents: String) -> Result<Self> {
+        let table: toml::Table = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Self {
+            path,
+            contents,
+            table,
+        })
+    }
+
+    /// Loads the project config at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(path.to_path_buf(), contents)
+    }
+
+    /// Finds and loads the nearest project config at or above `start_dir`.
+    pub fn discover(start_dir: &Path) -> Option<Result<Self>> {
+        find_project_config(start_dir).map(|path| Self::load(&path))
+    }
+
+    /// Returns the directory containing the project config.
+    pub fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+
+    /// Returns the names of the settings this project config overrides.
+    pub fn keys(&self) -> Vec<String> {
+        self.table.keys().cloned().collect()
+    }
+
+    /// Returns `base` with this project's settings laid over it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::config::Config;
+    /// use yamlquill::config::project::ProjectConfig;
+    ///
+    /// let project = ProjectConfig::parse(".yamlquill.toml".into(), "indent_size = 4\n".into()).unwrap();
+    /// let merged = project.apply_to(&Config::default()).unwrap();
+    /// assert_eq!(merged.indent_size, 4);
+    /// assert_eq!(merged.theme, "default-dark");
+    /// ```
+    pub fn apply_to(&self, base: &Config) -> Result<Config> {
+        let mut merged = toml::Table::try_from(base)?;
+        for (key, value) in &self.table {
+            merged.insert(key.clone(), value.clone());
+        }
+        merged
+            .try_into()
+            .with_context(|| format!("Invalid setting in {}", self.path.display()))
+    }
+
+    /// Resets the settings this project config overrides back to `defaults`.
+    pub fn revert(&self, config: &mut Config, defaults: &Config) -> Result<()> {
+        let mut current = toml::Table::try_from(&*config)?;
+        let defaults = toml::Table::try_from(defaults)?;
+        for key in self.table.keys() {
+            match defaults.get(key) {
+                Some(value) => current.insert(key.clone(), value.clone()),
+                None => current.remove(key),
+            };
+        }
+        *config = current.try_into()?;
+        Ok(())
+    }
+}
+
+/// Returns the path of the nearest `.yamlquill.toml` at or above `start_dir`.
+pub fn find_project_config(start_dir: &Path) -> Option<PathBuf> {
+    let start = std::fs::canonicalize(start_dir).unwrap_or_else(|_| start_dir.to_path_buf());
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_project_config_in_ancestor() {
+        let dir = TempDir::new().unwrap();
+        let nested = dir.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.path().join(PROJECT_CONFIG_FILE), "indent_size = 4\n").unwrap();
+
+        let found = find_project_config(&nested).unwrap();
+        assert_eq!(
+            found,
+            std::fs::canonicalize(dir.path())
+                .unwrap()
+                .join(PROJECT_CONFIG_FILE)
+        );
+    }
+
+    #[test]
+    fn test_apply_and_revert() {
+        let project = ProjectConfig::parse(
+            PathBuf::from(PROJECT_CONFIG_FILE),
+            "indent_size = 4\nshow_line_numbers = false\n".to_string(),
+        )
+        .unwrap();
+        let user = Config {
+            theme: "nord".to_string(),
+            ..Config::default()
+        };
+
+        let mut merged = project.apply_to(&user).unwrap();
+        assert_eq!(merged.indent_size, 4);
+        assert!(!merged.show_line_numbers);
+        assert_eq!(merged.theme, "nord");
+
+        merged.create_backup = true;
+        project.revert(&mut merged, &user).unwrap();
+        assert_eq!(merged.indent_size, user.indent_size);
+        assert!(merged.show_line_numbers);
+        assert!(merged.create_backup);
+    }
+
+    #[test]
+    fn test_invalid_setting_is_an_error() {
+        let project = ProjectConfig::parse(
+            PathBuf::from(PROJECT_CONFIG_FILE),
+            "indent_size = \"wide\"\n".to_string(),
+        )
+        .unwrap();
+        assert!(project.apply_to(&Config::default()).is_err());
+    }
+}
@@ -0,0 +1,236 @@
+//! Trust store for project-level settings.
+//!
+//! Files can carry settings of their own: a `.yamlquill.toml` project config
+//! next to them, or a `# yamlquill:` modeline inside them. Like direnv, these
+//! are ignored until the user explicitly trusts the directory with `:trust`,
+//! so opening a file from an unknown repository can never change editor
+//! behavior behind the user's back. Anything a project config could use to run
+//! external programs must also be gated on [`TrustStore::is_trusted`].
+//!
+//! A trusted project config is pinned to its contents, kept in the trust
+//! file; editing the file revokes trust until it is granted again. Comparing
+//! the contents themselves, rather than a digest of them, leaves nothing to
+//! forge.
+//!
+//! Trusted locations are stored in `~/.config/yamlquill/trust.toml`.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A directory the user has trusted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustEntry {
+    /// Canonical path of the trusted directory
+    pub path: PathBuf,
+    /// The directory's `.yamlquill.toml` as it was when trusted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrustFile {
+    #[serde(default)]
+    trusted: Vec<TrustEntry>,
+}
+
+/// The set of directories whose project config and modelines may be applied.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::config::trust::TrustStore;
+/// use std::path::Path;
+///
+/// let mut store = TrustStore::new();
+/// assert!(!store.is_trusted(Path::new("/srv/app")));
+///
+/// store.trust(Path::new("/srv"), None);
+/// assert!(store.is_trusted(Path::new("/srv/app")));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TrustStore {
+    entries: Vec<TrustEntry>,
+    /// Where the store is persisted; `None` keeps it in memory only
+    file: Option<PathBuf>,
+}
+
+impl TrustStore {
+    /// Creates an empty, in-memory trust store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the path to the trust file.
+    ///
+    /// Uses `~/.config/yamlquill/trust.toml` on all platforms.
+    pub fn trust_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|mut path| {
+            path.push(".config");
+            path.push("yamlquill");
+            path.push("trust.toml");
+            path
+        })
+    }
+
+    /// Loads the trust store from the default trust file.
+    ///
+    /// Returns an empty store if the file doesn't exist or can't be read.
+    pub fn load() -> Self {
+        match Self::trust_path() {
+            Some(path) => Self::load_from(path),
+            None => Self::new(),
+        }
+    }
+
+    /// Loads the trust store from a specific file.
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
+        let entries = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| toml::from_str::<TrustFile>(&contents).ok())
+            .map(|file| file.trusted)
+            .unwrap_or_default();
+        Self {
+            entries,
+            file: Some(path.to_path_buf()),
+        }
+    }
+
+    /// Writes the trust store to the file it was loaded from.
+    ///
+    /// In-memory stores are not persisted.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.file else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = TrustFile {
+            trusted: self.entries.clone(),
+        };
+        std::fs::write(path, toml::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+
+    /// Returns all trusted entries.
+    pub fn entries(&self) -> &[TrustEntry] {
+        &self.entries
+    }
+
+    /// Returns true if `dir` or one of its ancestors has been trusted.
+    pub fn is_trusted(&self, dir: &Path) -> bool {
+        let dir = normalize(dir);
+        self.entries
+            .iter()
+            .any(|entry| dir.starts_with(&entry.path))
+    }
+
+    /// Returns true if the project config in `dir` with the given contents is trusted.
+    ///
+    /// The directory itself must have been trusted while the config had
+    /// exactly these contents.
+    pub fn is_config_trusted(&self, dir: &Path, contents: &str) -> bool {
+        let dir = normalize(dir);
+        self.entries
+            .iter()
+            .any(|entry| entry.path == dir && entry.config.as_deref() == Some(contents))
+    }
+
+    /// Trusts `dir`, pinning the contents of its project config if there is one.
+    pub fn trust(&mut self, dir: &Path, config_contents: Option<&str>) {
+        let path = normalize(dir);
+        self.entries.retain(|entry| entry.path != path);
+        self.entries.push(TrustEntry {
+            path,
+            config: config_contents.map(str::to_string),
+        });
+    }
+
+    /// Revokes trust for `dir` and everything trusted beneath it.
+    ///
+    /// Returns true if anything was removed.
+    pub fn revoke(&mut self, dir: &Path) -> bool {
+        let dir = normalize(dir);
+        let before = self.entries.len();
+        self.entries.retain(|entry| !entry.path.starts_with(&dir));
+        self.entries.len() != before
+    }
+}
+
+/// Canonicalizes a path when possible so that trust checks aren't fooled by `..` or symlinks.
+fn normalize(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_trust_covers_subdirectories() {
+        let dir = TempDir::new().unwrap();
+        let sub = dir.path().join("nested");
+        std::fs::create_dir(&sub).unwrap();
+
+        let mut store = TrustStore::new();
+        assert!(!store.is_trusted(&sub));
+        store.trust(dir.path(), None);
+        assert!(store.is_trusted(&sub));
+    }
+
+    #[test]
+    fn test_config_trust_is_pinned_to_contents() {
+        let dir = TempDir::new().unwrap();
+        let mut store = TrustStore::new();
+        store.trust(dir.path(), Some("theme = \"nord\"\n"));
+
+        assert!(store.is_config_trusted(dir.path(), "theme = \"nord\"\n"));
+        assert!(!store.is_config_trusted(dir.path(), "theme = \"dracula\"\n"));
+    }
+
+    #[test]
+    fn test_revoke() {
+        let dir = TempDir::new().unwrap();
+        let mut store = TrustStore::new();
+        store.trust(dir.path(), None);
+
+        assert!(store.revoke(dir.path()));
+        assert!(!store.is_trusted(dir.path()));
+        assert!(!store.revoke(dir.path()));
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("trust.toml");
+
+        let mut store = TrustStore::load_from(&file);
+        store.trust(dir.path(), Some("indent_size = 4\n"));
+        store.save().unwrap();
+
+        let reloaded = TrustStore::load_from(&file);
+        assert_eq!(reloaded.entries(), store.entries());
+        assert!(reloaded.is_config_trusted(dir.path(), "indent_size = 4\n"));
+    }
+
+    #[test]
+    fn test_hash_pins_from_older_versions_need_trusting_again() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("trust.toml");
+        let path = normalize(dir.path());
+        std::fs::write(
+            &file,
+            format!(
+                "[[trusted]]\npath = {:?}\nconfig_hash = \"cbf29ce484222325\"\n",
+                path.display().to_string()
+            ),
+        )
+        .unwrap();
+
+        let store = TrustStore::load_from(&file);
+        assert!(store.is_trusted(dir.path()));
+        assert!(!store.is_config_trusted(dir.path(), ""));
+    }
+}
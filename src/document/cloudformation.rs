@@ -0,0 +1,139 @@
+//! CloudFormation intrinsic functions (`:set cloudformation`).
+//!
+//! Templates call intrinsics either with a short-form tag (`!Ref Bucket`,
+//! `!GetAtt Role.Arn`) or with a long-form key (`Ref: Bucket`,
+//! `Fn::GetAtt: [Role, Arn]`). This module recognizes both and lists the
+//! logical IDs a `!Ref` or `!GetAtt` can point at.
+
+use crate::document::node::{YamlNode, YamlValue};
+
+/// Intrinsic function names, as written after `!` in the short form.
+pub const INTRINSICS: &[&str] = &[
+    "And",
+    "Base64",
+    "Cidr",
+    "Condition",
+    "Equals",
+    "FindInMap",
+    "ForEach",
+    "GetAZs",
+    "GetAtt",
+    "If",
+    "ImportValue",
+    "Join",
+    "Length",
+    "Not",
+    "Or",
+    "Ref",
+    "Select",
+    "Split",
+    "Sub",
+    "ToJsonString",
+    "Transform",
+];
+
+/// Parameters every stack has, usable with `!Ref`.
+pub const PSEUDO_PARAMETERS: &[&str] = &[
+    "AWS::AccountId",
+    "AWS::NotificationARNs",
+    "AWS::NoValue",
+    "AWS::Partition",
+    "AWS::Region",
+    "AWS::StackId",
+    "AWS::StackName",
+    "AWS::URLSuffix",
+];
+
+/// Returns true if `tag` is the short form of an intrinsic (`!Ref`, `!Sub`).
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::cloudformation::is_intrinsic_tag;
+///
+/// assert!(is_intrinsic_tag("!GetAtt"));
+/// assert!(!is_intrinsic_tag("!!str"));
+/// ```
+pub fn is_intrinsic_tag(tag: &str) -> bool {
+    tag.strip_prefix('!')
+        .is_some_and(|name| INTRINSICS.contains(&name))
+}
+
+/// Returns true if `key` is the long form of an intrinsic (`Ref`, `Fn::Join`).
+///
+/// `Condition` is left out: as a key it usually names a resource's condition
+/// rather than calling `Fn::Condition`.
+pub fn is_intrinsic_key(key: &str) -> bool {
+    key == "Ref"
+        || key
+            .strip_prefix("Fn::")
+            .is_some_and(|name| INTRINSICS.contains(&name))
+}
+
+/// Returns the values an intrinsic tagged `tag` can refer to that start with
+/// `prefix`, sorted.
+///
+/// `!Ref` refers to parameters, resources and pseudo parameters; `!GetAtt`
+/// to resources. Other tags have no candidates.
+pub fn reference_candidates(root: &YamlNode, tag: &str, prefix: &str) -> Vec<String> {
+    let sections: &[&str] = match tag {
+        "!Ref" => &["Parameters", "Resources"],
+        "!GetAtt" => &["Resources"],
+        _ => return Vec::new(),
+    };
+    let mut candidates: Vec<String> = sections
+        .iter()
+        .flat_map(|section| logical_ids(root, section))
+        .collect();
+    if tag == "!Ref" {
+        candidates.extend(PSEUDO_PARAMETERS.iter().map(|p| p.to_string()));
+    }
+    candidates.retain(|id| id.starts_with(prefix));
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+/// Returns the keys of the top-level `section` mapping (`Resources`,
+/// `Parameters`), in document order.
+pub fn logical_ids(root: &YamlNode, section: &str) -> Vec<String> {
+    let YamlValue::Object(sections) = root.value() else {
+        return Vec::new();
+    };
+    match sections.get(section).map(|node| node.value()) {
+        Some(YamlValue::Object(entries)) => entries.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    #[test]
+    fn test_reference_candidates() {
+        let root = parse_yaml_auto(
+            "Parameters:\n  Env: {Type: String}\nResources:\n  Bucket: {Type: x}\n  BucketPolicy: {Type: y}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            reference_candidates(&root, "!GetAtt", "Buck"),
+            vec!["Bucket", "BucketPolicy"]
+        );
+        assert_eq!(
+            reference_candidates(&root, "!Ref", "E"),
+            vec!["Env".to_string()]
+        );
+        assert!(reference_candidates(&root, "!Ref", "").contains(&"AWS::Region".to_string()));
+        assert!(reference_candidates(&root, "!Sub", "").is_empty());
+    }
+
+    #[test]
+    fn test_intrinsic_keys() {
+        assert!(is_intrinsic_key("Ref"));
+        assert!(is_intrinsic_key("Fn::GetAtt"));
+        assert!(!is_intrinsic_key("Condition"));
+        assert!(!is_intrinsic_key("Fn::Bogus"));
+    }
+}
@@ -0,0 +1,175 @@
+//! Mapping keys written more than once.
+//!
+//! YAML requires the keys of a mapping to be unique, but files with a key
+//! written twice are common (a hand merge, a pasted block), and most tools
+//! read them by silently keeping the last value. The parser keeps every entry
+//! instead: the first under its key and each repeat under a marked key, so
+//! the second `name` becomes `name__duplicate_2__`. The tree view shows a
+//! repeat under the key it was written with, and the saver writes it back
+//! under that key, so the file round-trips until `:dedupe` resolves it.
+
+use crate::document::node::{YamlNode, YamlValue};
+use indexmap::IndexMap;
+
+const MARKER: &str = "__duplicate_";
+
+/// Returns the key the `n`th entry written with `key` is kept under.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::duplicates::{duplicate_key, written_key};
+///
+/// assert_eq!(duplicate_key("name", 2), "name__duplicate_2__");
+/// assert_eq!(written_key("name__duplicate_2__"), "name");
+/// assert_eq!(written_key("name"), "name");
+/// ```
+pub fn duplicate_key(key: &str, n: usize) -> String {
+    format!("{}{}{}__", key, MARKER, n)
+}
+
+/// Returns the key `key` was written with in the file: the key it repeats,
+/// for a marked key, or `key` itself.
+pub fn written_key(key: &str) -> &str {
+    marker_start(key).map_or(key, |start| &key[..start])
+}
+
+/// Returns whether `key` is a marked repeat of another key.
+pub fn is_duplicate_key(key: &str) -> bool {
+    marker_start(key).is_some()
+}
+
+/// Returns where the marker starts in `key`, if it ends with one.
+fn marker_start(key: &str) -> Option<usize> {
+    let start = key.rfind(MARKER)?;
+    let number = key[start + MARKER.len()..].strip_suffix("__")?;
+    (!number.is_empty() && number.bytes().all(|b| b.is_ascii_digit())).then_some(start)
+}
+
+/// Returns the key to keep a new entry written with `key` under in
+/// `entries`: `key` itself, or a marked key if `key` is taken.
+pub fn unique_key(entries: &IndexMap<String, YamlNode>, key: String) -> String {
+    if !entries.contains_key(&key) {
+        return key;
+    }
+    (2..)
+        .map(|n| duplicate_key(&key, n))
+        .find(|marked| !entries.contains_key(marked))
+        .unwrap_or(key)
+}
+
+/// A key written more than once in one mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKey {
+    /// The key as written
+    pub key: String,
+    /// Paths of the entries written with it, in the order they were written
+    pub paths: Vec<Vec<usize>>,
+}
+
+/// Returns every key written more than once, in document order.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::duplicates::find;
+/// use yamlquill::document::parser::parse_yaml_auto;
+///
+/// let root = parse_yaml_auto("name: api\nport: 80\nname: web\n").unwrap();
+/// let duplicates = find(&root);
+/// assert_eq!(duplicates[0].key, "name");
+/// assert_eq!(duplicates[0].paths, [vec![0], vec![2]]);
+/// ```
+pub fn find(root: &YamlNode) -> Vec<DuplicateKey> {
+    let mut found = Vec::new();
+    collect(root, &mut Vec::new(), &mut found);
+    found.sort_by(|a, b| a.paths[0].cmp(&b.paths[0]));
+    found
+}
+
+fn collect(node: &YamlNode, path: &mut Vec<usize>, found: &mut Vec<DuplicateKey>) {
+    match node.value() {
+        YamlValue::Object(entries) => {
+            let mut groups: IndexMap<&str, Vec<usize>> = IndexMap::new();
+            for (i, key) in entries.keys().enumerate() {
+                groups.entry(written_key(key)).or_default().push(i);
+            }
+            for (key, indices) in groups {
+                if indices.len() > 1 {
+                    found.push(DuplicateKey {
+                        key: key.to_string(),
+                        paths: indices.iter().map(|&i| child(path, i)).collect(),
+                    });
+                }
+            }
+            for (i, value) in entries.values().enumerate() {
+                path.push(i);
+                collect(value, path, found);
+                path.pop();
+            }
+        }
+        YamlValue::Array(elements) | YamlValue::MultiDoc(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                path.push(i);
+                collect(element, path, found);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn child(path: &[usize], index: usize) -> Vec<usize> {
+    path.iter().copied().chain(std::iter::once(index)).collect()
+}
+
+/// Replaces marked keys in serialized YAML with the keys they repeat.
+///
+/// Only a marker that ends a key (followed by `:` or a closing quote) is
+/// replaced, so values that happen to contain the marker text are kept.
+pub fn restore_keys(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(MARKER) {
+        let after = &rest[start + MARKER.len()..];
+        let digits = after.bytes().take_while(u8::is_ascii_digit).count();
+        let tail = &after[digits..];
+        let ends_key = digits > 0
+            && tail.starts_with("__")
+            && matches!(tail[2..].chars().next(), Some(':' | '"' | '\''));
+        if ends_key {
+            output.push_str(&rest[..start]);
+            rest = &tail[2..];
+        } else {
+            output.push_str(&rest[..start + MARKER.len()]);
+            rest = after;
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marker_parsing() {
+        assert!(is_duplicate_key("a__duplicate_12__"));
+        assert!(!is_duplicate_key("a__duplicate___"));
+        assert!(!is_duplicate_key("a__duplicate_x__"));
+        assert_eq!(
+            written_key("a__duplicate_2____duplicate_3__"),
+            "a__duplicate_2__"
+        );
+    }
+
+    #[test]
+    fn test_restore_keys() {
+        let text = "name: api\nname__duplicate_2__: web\n\"a b__duplicate_3__\": x\nv: keep__duplicate_2__ me\n";
+        assert_eq!(
+            restore_keys(text),
+            "name: api\nname: web\n\"a b\": x\nv: keep__duplicate_2__ me\n"
+        );
+    }
+}
@@ -0,0 +1,324 @@
+//! Lazy loading for large files (`lazy_load_threshold`).
+//!
+//! Parsing a file builds a node for every value, which takes many times the
+//! file's own size in memory. For files over the threshold only the top
+//! level is parsed: each document of a multi-document stream, and each
+//! block-valued key of a mapping (or multi-line item of a sequence), is kept
+//! as its YAML source in a [`YamlValue::Deferred`] node and parsed the first
+//! time it is expanded.
+//!
+//! Unloaded sections are saved as their original text, so a file can be
+//! browsed and edited in one place without the rest ever being parsed.
+//! Edits of the whole document (`:%s`, `:path-set`, ...) load every section
+//! first.
+
+use crate::document::node::{DeferredSource, YamlNode, YamlValue};
+use crate::document::parser::parse_yaml_auto;
+use anyhow::{Context, Result};
+use std::sync::Arc;
+
+/// A run of source lines that is deferred rather than parsed.
+struct Section {
+    /// Line holding the key (`key:`) or the start of the item (`- `).
+    head: usize,
+    /// Lines after `head` up to, not including, this one are the body.
+    end: usize,
+    /// The key for mappings; `None` for sequence items.
+    key: Option<String>,
+    /// Position of the entry among the top-level entries.
+    index: usize,
+}
+
+/// Parses `source`, deferring everything below the top level.
+///
+/// Files the splitter doesn't understand (flow collections at the top
+/// level, a single scalar) are parsed in full.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::lazy::{load_deferred, parse_yaml_lazy};
+/// use yamlquill::document::node::YamlValue;
+///
+/// let root = parse_yaml_lazy("name: demo\nitems:\n  - a\n  - b\n").unwrap();
+/// let YamlValue::Object(entries) = root.value() else { panic!() };
+/// assert!(!entries["name"].value().is_deferred());
+/// assert!(entries["items"].value().is_deferred());
+///
+/// let YamlValue::Deferred(source) = entries["items"].value() else { panic!() };
+/// assert!(load_deferred(source).unwrap().value().is_container());
+/// ```
+pub fn parse_yaml_lazy(source: &str) -> Result<YamlNode> {
+    let documents = split_documents(source);
+    if documents.len() > 1 {
+        let nodes = documents.into_iter().map(deferred_document).collect();
+        return Ok(YamlNode::new(YamlValue::MultiDoc(nodes)));
+    }
+
+    let lines: Vec<&str> = source.lines().collect();
+    let sections = split_sections(&lines);
+    if sections.is_empty() {
+        return parse_yaml_auto(source);
+    }
+
+    // Parse a skeleton with each section's body blanked out. Blank lines
+    // keep every remaining line (and comment) at its original line number.
+    let mut skeleton: Vec<&str> = lines.clone();
+    for section in &sections {
+        if section.key.is_none() {
+            skeleton[section.head] = "- null";
+        }
+        for line in &mut skeleton[section.head + 1..section.end] {
+            *line = "";
+        }
+    }
+    let mut root = parse_yaml_auto(&skeleton.join("\n"))?;
+
+    match root.value_mut_traverse() {
+        YamlValue::Object(entries) => {
+            for section in &sections {
+                if let Some(node) = section.key.as_ref().and_then(|key| entries.get_mut(key)) {
+                    node.value = YamlValue::Deferred(section_source(&lines, section));
+                }
+            }
+        }
+        YamlValue::Array(elements) => {
+            let mut items: Vec<&mut YamlNode> = elements
+                .iter_mut()
+                .filter(|n| !n.value().is_comment())
+                .collect();
+            for section in &sections {
+                if let Some(node) = items.get_mut(section.index) {
+                    node.value = YamlValue::Deferred(section_source(&lines, section));
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(root)
+}
+
+/// Wraps a document's source in an unmodified deferred node.
+fn deferred_document(source: DeferredSource) -> YamlNode {
+    let mut node = YamlNode::new(YamlValue::Deferred(source));
+    node.metadata.modified = false;
+    node
+}
+
+/// Parses the source of a deferred node.
+///
+/// Comments keep the line numbers they have in the whole file, which the
+/// saver uses to write comment edits back in place.
+pub fn load_deferred(source: &DeferredSource) -> Result<YamlNode> {
+    let mut node = parse_yaml_auto(&source.text).context("Failed to parse section")?;
+    shift_comment_lines(&mut node, source.line_offset);
+    Ok(node)
+}
+
+fn shift_comment_lines(node: &mut YamlNode, offset: usize) {
+    match &mut node.value {
+        YamlValue::Comment(comment) => {
+            if let Some(line) = comment.source_line.as_mut() {
+                *line += offset;
+            }
+        }
+        YamlValue::Object(entries) => entries
+            .values_mut()
+            .for_each(|child| shift_comment_lines(child, offset)),
+        YamlValue::Array(items) | YamlValue::MultiDoc(items) => items
+            .iter_mut()
+            .for_each(|child| shift_comment_lines(child, offset)),
+        _ => {}
+    }
+}
+
+/// Splits a multi-document stream at its `---` markers.
+fn split_documents(source: &str) -> Vec<DeferredSource> {
+    let mut documents = Vec::new();
+    let mut text = String::new();
+    let mut line_offset = 0;
+    for (i, line) in source.lines().enumerate() {
+        if line == "---" || line.starts_with("--- ") {
+            if !text.trim().is_empty() {
+                documents.push(DeferredSource {
+                    text: Arc::from(std::mem::take(&mut text)),
+                    line_offset,
+                });
+            }
+            text.clear();
+            line_offset = i + 1;
+            continue;
+        }
+        text.push_str(line);
+        text.push('\n');
+    }
+    if !text.trim().is_empty() {
+        documents.push(DeferredSource {
+            text: Arc::from(text),
+            line_offset,
+        });
+    }
+    documents
+}
+
+/// Finds the top-level keys and sequence items whose value is an indented
+/// block that can be parsed on its own.
+fn split_sections(lines: &[&str]) -> Vec<Section> {
+    let sequence = lines
+        .iter()
+        .find(|line| !is_gap(line) && !line.starts_with("---") && !line.starts_with('%'))
+        .is_some_and(|line| is_item_start(line));
+    let starts: Vec<usize> = (0..lines.len())
+        .filter(|&i| is_top_level_start(lines[i], sequence))
+        .collect();
+    let mut sections = Vec::new();
+    for (n, &head) in starts.iter().enumerate() {
+        let next = starts.get(n + 1).copied().unwrap_or(lines.len());
+        // Blank lines and top-level comments before the next start stay in
+        // the skeleton, where the saver expects them.
+        let mut end = next;
+        while end > head + 1 && is_gap(lines[end - 1]) {
+            end -= 1;
+        }
+        if end == head + 1 {
+            continue;
+        }
+        let line = lines[head].trim_end();
+        if sequence {
+            sections.push(Section {
+                head,
+                end,
+                key: None,
+                index: n,
+            });
+        } else if let Some(key) = block_key(line) {
+            sections.push(Section {
+                head,
+                end,
+                key: Some(key),
+                index: n,
+            });
+        }
+    }
+    sections
+}
+
+/// Returns true for a line that starts a top-level entry: an item of a
+/// sequence document, or anything at column 0 in a mapping (whose `- `
+/// lines belong to an unindented sequence under a key).
+fn is_top_level_start(line: &str, sequence: bool) -> bool {
+    if sequence {
+        return is_item_start(line);
+    }
+    !line.is_empty() && !line.starts_with([' ', '\t', '#', '-'])
+}
+
+/// Returns true for a line that starts a top-level sequence item.
+fn is_item_start(line: &str) -> bool {
+    line.trim_end() == "-" || line.starts_with("- ")
+}
+
+/// Returns true for a blank line or a comment at column 0.
+fn is_gap(line: &str) -> bool {
+    line.trim().is_empty() || line.starts_with('#')
+}
+
+/// Returns the key of a `key:` line with nothing after the colon.
+///
+/// Keys with anchors, tags or inline values are left to the skeleton.
+fn block_key(line: &str) -> Option<String> {
+    let key = line.strip_suffix(':')?;
+    if key.is_empty() || key.starts_with(['&', '*', '!', '?', '{', '[', '%', '|', '>']) {
+        return None;
+    }
+    if key.starts_with(['"', '\'']) {
+        return serde_yaml::from_str::<String>(key).ok();
+    }
+    (!key.contains(": ") && !key.contains(" #")).then(|| key.to_string())
+}
+
+/// Returns a section's body as a standalone document.
+///
+/// A sequence item's first line is the rest of its `- ` line; the body is
+/// dedented so it parses at the top level.
+fn section_source(lines: &[&str], section: &Section) -> DeferredSource {
+    let mut body: Vec<&str> = Vec::new();
+    let first;
+    if section.key.is_none() {
+        first = format!(" {}", &lines[section.head][1..]);
+        body.push(&first);
+    }
+    body.extend(&lines[section.head + 1..section.end]);
+
+    let indent = body
+        .iter()
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|line| line.len() - line.trim_start_matches(' ').len())
+        .min()
+        .unwrap_or(0);
+    let mut text = String::new();
+    for line in body {
+        let strip = indent.min(line.len() - line.trim_start_matches(' ').len());
+        text.push_str(&line[strip..]);
+        text.push('\n');
+    }
+    DeferredSource {
+        text: Arc::from(text),
+        line_offset: match section.key {
+            Some(_) => section.head + 1,
+            None => section.head,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defers_block_sections() {
+        let source = "# header\nname: demo\nspec:\n  replicas: 2\n  ports:\n    - 80\n\n# trailer\nlist:\n- a\n- b\n\"quoted key\":\n  x: 1\n";
+        let root = parse_yaml_lazy(source).unwrap();
+        let YamlValue::Object(entries) = root.value() else {
+            panic!("expected a mapping");
+        };
+        assert!(!entries["name"].value().is_deferred());
+        let YamlValue::Deferred(spec) = entries["spec"].value() else {
+            panic!("spec should be deferred");
+        };
+        assert_eq!(&*spec.text, "replicas: 2\nports:\n  - 80\n");
+        assert_eq!(spec.line_offset, 3);
+        assert!(entries["list"].value().is_deferred());
+        assert!(entries["quoted key"].value().is_deferred());
+        assert!(!entries["spec"].is_modified());
+
+        let spec = load_deferred(spec).unwrap();
+        let YamlValue::Object(spec) = spec.value() else {
+            panic!("expected a mapping");
+        };
+        assert_eq!(spec.len(), 2);
+    }
+
+    #[test]
+    fn test_defers_sequence_items_and_documents() {
+        let root =
+            parse_yaml_lazy("- name: a\n  port: 1\n- plain\n- name: b\n  port: 2\n").unwrap();
+        let YamlValue::Array(items) = root.value() else {
+            panic!("expected a sequence");
+        };
+        assert_eq!(items.len(), 3);
+        let YamlValue::Deferred(first) = items[0].value() else {
+            panic!("item should be deferred");
+        };
+        assert_eq!(&*first.text, "name: a\nport: 1\n");
+        assert_eq!(first.line_offset, 0);
+        assert!(!items[1].value().is_deferred());
+
+        let root = parse_yaml_lazy("---\na: 1\n---\nb: 2\n").unwrap();
+        let YamlValue::MultiDoc(documents) = root.value() else {
+            panic!("expected documents");
+        };
+        assert_eq!(documents.len(), 2);
+        assert!(documents.iter().all(|d| d.value().is_deferred()));
+    }
+}
@@ -0,0 +1,227 @@
+//! Resource limits checked before a file is parsed.
+//!
+//! A few hundred bytes of YAML can describe far more data than fits in
+//! memory: each alias of an anchor repeats the anchored subtree, so nine
+//! levels of anchors that each alias the one before ten times (the "billion
+//! laughs") stand for a billion strings. Deep nesting and huge node counts
+//! can likewise exhaust the stack or memory long after the parse succeeds.
+//!
+//! [`LoadLimits::check`] makes one pass over the parser's events, without
+//! building a tree, and stops at the first limit passed with an error naming
+//! the config setting that raises it.
+
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use yaml_rust2::parser::{Event, Parser};
+
+/// Limits on what a file may contain. A limit of 0 means no limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadLimits {
+    /// Largest file, in megabytes (`max_file_size_mb`)
+    pub max_file_size_mb: usize,
+    /// Deepest nesting of mappings and sequences (`max_parse_depth`)
+    pub max_depth: usize,
+    /// Most nodes written in the file (`max_node_count`)
+    pub max_nodes: usize,
+    /// Most nodes that aliases may stand for, counting an alias of a
+    /// subtree as every node in it (`max_alias_expansion`)
+    pub max_alias_expansion: usize,
+}
+
+impl Default for LoadLimits {
+    /// The config defaults: 1024 MB, 512 levels, 20 million nodes and a
+    /// million nodes through aliases.
+    fn default() -> Self {
+        Self {
+            max_file_size_mb: 1024,
+            max_depth: 512,
+            max_nodes: 20_000_000,
+            max_alias_expansion: 1_000_000,
+        }
+    }
+}
+
+impl LoadLimits {
+    /// No limits at all.
+    pub const NONE: LoadLimits = LoadLimits {
+        max_file_size_mb: 0,
+        max_depth: 0,
+        max_nodes: 0,
+        max_alias_expansion: 0,
+    };
+
+    /// Checks a file's size in bytes, before it is read.
+    pub fn check_size(&self, bytes: u64) -> Result<()> {
+        let limit = self.max_file_size_mb as u64 * 1024 * 1024;
+        if self.max_file_size_mb > 0 && bytes > limit {
+            bail!(
+                "the file is {} MB, over the {} MB limit (max_file_size_mb)",
+                bytes.div_ceil(1024 * 1024),
+                self.max_file_size_mb
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks YAML source against every limit.
+    ///
+    /// Syntax errors aren't reported here; the parse that follows reports
+    /// them with more detail.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::document::limits::LoadLimits;
+    ///
+    /// let bomb = "a: &a [x, x, x, x, x, x, x, x, x, x]\n\
+    ///             b: &b [*a, *a, *a, *a, *a, *a, *a, *a, *a, *a]\n\
+    ///             c: &c [*b, *b, *b, *b, *b, *b, *b, *b, *b, *b]\n\
+    ///             d: [*c, *c, *c, *c, *c, *c, *c, *c, *c, *c]\n";
+    /// let limits = LoadLimits {
+    ///     max_alias_expansion: 1000,
+    ///     ..LoadLimits::default()
+    /// };
+    /// let error = limits.check(bomb).unwrap_err().to_string();
+    /// assert!(error.starts_with("aliases expand to more than 1000 nodes"));
+    ///
+    /// assert!(LoadLimits::default().check(bomb).is_ok());
+    /// ```
+    pub fn check(&self, source: &str) -> Result<()> {
+        self.check_size(source.len() as u64)?;
+
+        // Nodes each anchor stands for, with its own aliases expanded
+        let mut anchored: HashMap<usize, u64> = HashMap::new();
+        // Open collections: their anchor and nodes so far
+        let mut open: Vec<(usize, u64)> = Vec::new();
+        let mut nodes = 0usize;
+        let mut expanded = 0u64;
+
+        let mut parser = Parser::new(source.chars());
+        loop {
+            let Ok((event, mark)) = parser.next_token() else {
+                return Ok(());
+            };
+            let size = match event {
+                Event::StreamEnd => return Ok(()),
+                Event::Scalar(_, _, anchor, _) => {
+                    if anchor > 0 {
+                        anchored.insert(anchor, 1);
+                    }
+                    1
+                }
+                Event::Alias(anchor) => {
+                    let size = anchored.get(&anchor).copied().unwrap_or(1);
+                    expanded = expanded.saturating_add(size);
+                    if self.max_alias_expansion > 0 && expanded > self.max_alias_expansion as u64 {
+                        bail!(
+                            "aliases expand to more than {} nodes by line {}, as in a \"billion laughs\" attack (max_alias_expansion)",
+                            self.max_alias_expansion,
+                            mark.line()
+                        );
+                    }
+                    size
+                }
+                Event::SequenceStart(anchor, _) | Event::MappingStart(anchor, _) => {
+                    open.push((anchor, 1));
+                    if self.max_depth > 0 && open.len() > self.max_depth {
+                        bail!(
+                            "nesting is deeper than {} levels at line {} (max_parse_depth)",
+                            self.max_depth,
+                            mark.line()
+                        );
+                    }
+                    nodes += 1;
+                    continue;
+                }
+                Event::SequenceEnd | Event::MappingEnd => {
+                    let Some((anchor, size)) = open.pop() else {
+                        continue;
+                    };
+                    if anchor > 0 {
+                        anchored.insert(anchor, size);
+                    }
+                    if let Some((_, parent)) = open.last_mut() {
+                        *parent = parent.saturating_add(size);
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            nodes += 1;
+            if self.max_nodes > 0 && nodes > self.max_nodes {
+                bail!(
+                    "the file has more than {} nodes (max_node_count)",
+                    self.max_nodes
+                );
+            }
+            if let Some((_, parent)) = open.last_mut() {
+                *parent = parent.saturating_add(size);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_depth_and_node_count() {
+        let limits = LoadLimits {
+            max_depth: 3,
+            ..LoadLimits::NONE
+        };
+        assert!(limits.check("a:\n  b:\n    c: 1\n").is_ok());
+        assert_eq!(
+            limits
+                .check("a:\n  b:\n    c:\n      d: 1\n")
+                .unwrap_err()
+                .to_string(),
+            "nesting is deeper than 3 levels at line 4 (max_parse_depth)"
+        );
+
+        let limits = LoadLimits {
+            max_nodes: 6,
+            ..LoadLimits::NONE
+        };
+        assert!(limits.check("[1, 2, 3, 4, 5]").is_ok());
+        assert_eq!(
+            limits.check("[1, 2, 3, 4, 5, 6]").unwrap_err().to_string(),
+            "the file has more than 6 nodes (max_node_count)"
+        );
+    }
+
+    #[test]
+    fn test_alias_expansion_counts_nested_aliases() {
+        let limits = LoadLimits {
+            max_alias_expansion: 30,
+            ..LoadLimits::NONE
+        };
+        // Each *a is 4 nodes, each *b the 9 of b's list with its aliases
+        let yaml = "a: &a [1, 2, 3]\nb: &b [*a, *a]\nc: [*b, *b]\n";
+        assert!(limits.check(yaml).is_ok());
+        let yaml = "a: &a [1, 2, 3]\nb: &b [*a, *a]\nc: [*b, *b, *b]\n";
+        assert!(limits
+            .check(yaml)
+            .unwrap_err()
+            .to_string()
+            .starts_with("aliases expand to more than 30 nodes by line 3"));
+    }
+
+    #[test]
+    fn test_file_size() {
+        let limits = LoadLimits {
+            max_file_size_mb: 1,
+            ..LoadLimits::NONE
+        };
+        assert!(limits.check_size(1024 * 1024).is_ok());
+        assert_eq!(
+            limits
+                .check_size(3 * 1024 * 1024 + 1)
+                .unwrap_err()
+                .to_string(),
+            "the file is 4 MB, over the 1 MB limit (max_file_size_mb)"
+        );
+        assert!(LoadLimits::NONE.check_size(u64::MAX).is_ok());
+    }
+}
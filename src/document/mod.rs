@@ -1,3 +1,9 @@
+pub mod cloudformation;
+pub mod duplicates;
+pub mod lazy;
+pub mod limits;
 pub mod node;
+pub mod ops;
 pub mod parser;
+pub mod prometheus;
 pub mod tree;
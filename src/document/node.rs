@@ -28,12 +28,33 @@
 //! ```
 
 use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// A node's identity for the rest of the session.
+///
+/// Every node gets a new ID when it is created or parsed, and keeps it when
+/// cloned, so the same node has the same ID in undo snapshots and after an
+/// edit shifts its index path. Copies that become separate nodes, like
+/// pasted ones, get new IDs from [`YamlNode::duplicate`]. IDs are not saved
+/// and don't take part in node equality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u64);
+
+impl NodeId {
+    /// Returns an ID no other node has had this session.
+    pub fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        NodeId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
 
 /// A byte range in the original YAML source.
 ///
 /// TextSpan tracks the position of a node's text in the original YAML string,
 /// enabling exact format preservation for unmodified nodes.
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
 pub struct TextSpan {
     /// Start byte offset in original YAML
     pub start: usize,
@@ -42,7 +63,7 @@ pub struct TextSpan {
 }
 
 /// Represents different YAML string styles
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum YamlString {
     Plain(String),
     Literal(String),
@@ -75,7 +96,7 @@ impl YamlString {
 }
 
 /// Represents YAML numbers (integer or float)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum YamlNumber {
     Integer(i64),
     Float(f64),
@@ -107,8 +128,19 @@ impl YamlNumber {
     }
 }
 
+/// How a mapping or sequence is written: indented lines (block) or inline
+/// brackets (flow, `{a: 1}` / `[1, 2]`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CollectionStyle {
+    /// One entry per line, nested by indentation
+    #[default]
+    Block,
+    /// On one line in braces or brackets
+    Flow,
+}
+
 /// Position of a comment relative to YAML nodes
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CommentPosition {
     /// Comment line(s) before a value
     Above,
@@ -121,7 +153,7 @@ pub enum CommentPosition {
 }
 
 /// A YAML comment node
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CommentNode {
     /// Comment text without the '#' prefix
     pub content: String,
@@ -166,12 +198,33 @@ impl CommentNode {
     }
 }
 
+/// The YAML source of a subtree that is parsed the first time it is expanded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeferredSource {
+    /// The subtree's text, dedented so it parses on its own; shared so
+    /// that undo snapshots don't copy it
+    pub text: Arc<str>,
+    /// Number of lines in the file before the subtree's first line
+    pub line_offset: usize,
+}
+
+impl DeferredSource {
+    /// Returns true if the subtree is a sequence rather than a mapping.
+    pub fn is_sequence(&self) -> bool {
+        self.text
+            .lines()
+            .map(str::trim_start)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .is_some_and(|line| line == "-" || line.starts_with("- ") || line.starts_with('['))
+    }
+}
+
 /// A YAML value without metadata.
 ///
 /// This enum represents the core YAML types: objects, arrays, strings, numbers,
 /// booleans, and null. Objects and arrays contain `YamlNode` instances to preserve
 /// metadata throughout the tree structure.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum YamlValue {
     /// A YAML object containing key-value pairs
     Object(IndexMap<String, YamlNode>),
@@ -191,6 +244,9 @@ pub enum YamlValue {
     MultiDoc(Vec<YamlNode>),
     /// A YAML comment
     Comment(CommentNode),
+    /// A subtree of a lazily loaded file, kept as its YAML source until it
+    /// is first expanded (see [`crate::document::lazy`])
+    Deferred(DeferredSource),
 }
 
 /// A YAML value wrapped with metadata for tracking changes and formatting.
@@ -198,13 +254,28 @@ pub enum YamlValue {
 /// `YamlNode` is the primary type used throughout yamlquill to represent YAML data.
 /// It wraps a `YamlValue` with `NodeMetadata` to track whether the node has been
 /// modified and preserve original formatting information for format-preserving edits.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct YamlNode {
     pub(crate) value: YamlValue,
     pub(crate) metadata: NodeMetadata,
     pub(crate) anchor: Option<String>,
     pub(crate) alias_target: Option<String>,
+    #[serde(default)]
+    pub(crate) tag: Option<String>,
     pub(crate) original_formatting: Option<String>,
+    #[serde(skip, default = "NodeId::next")]
+    pub(crate) id: NodeId,
+}
+
+impl PartialEq for YamlNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+            && self.metadata == other.metadata
+            && self.anchor == other.anchor
+            && self.alias_target == other.alias_target
+            && self.tag == other.tag
+            && self.original_formatting == other.original_formatting
+    }
 }
 
 /// Metadata associated with a YAML node.
@@ -212,12 +283,15 @@ pub struct YamlNode {
 /// This structure tracks information about a node beyond its value, including
 /// whether it has been modified since loading and its byte position in the
 /// original source for format preservation.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NodeMetadata {
     /// Byte range in the original YAML string (for unmodified nodes)
     pub text_span: Option<TextSpan>,
     /// Whether this node has been modified
     pub modified: bool,
+    /// Block or flow style (only meaningful for mappings and sequences)
+    #[serde(default)]
+    pub style: CollectionStyle,
 }
 
 impl YamlValue {
@@ -280,6 +354,11 @@ impl YamlValue {
         )
     }
 
+    /// Returns true if this value is a subtree that hasn't been parsed yet.
+    pub fn is_deferred(&self) -> bool {
+        matches!(self, YamlValue::Deferred(_))
+    }
+
     /// Returns true if this value is a comment.
     pub fn is_comment(&self) -> bool {
         matches!(self, YamlValue::Comment(_))
@@ -380,6 +459,7 @@ impl std::fmt::Display for YamlValue {
             YamlValue::Alias(a) => write!(f, "*{}", a),
             YamlValue::MultiDoc(_) => write!(f, "[multi-doc]"),
             YamlValue::Comment(c) => write!(f, "# {}", c.content),
+            YamlValue::Deferred(_) => write!(f, "[not loaded]"),
         }
     }
 }
@@ -404,10 +484,36 @@ impl YamlNode {
             metadata: NodeMetadata {
                 text_span: None,
                 modified: true,
+                style: CollectionStyle::Block,
             },
             anchor: None,
             alias_target: None,
+            tag: None,
             original_formatting: None,
+            id: NodeId::next(),
+        }
+    }
+
+    /// Returns the node's session-stable identity.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Returns a copy of this subtree whose nodes all have new IDs.
+    pub fn duplicate(&self) -> YamlNode {
+        let mut copy = self.clone();
+        copy.refresh_ids();
+        copy
+    }
+
+    fn refresh_ids(&mut self) {
+        self.id = NodeId::next();
+        match &mut self.value {
+            YamlValue::Object(entries) => entries.values_mut().for_each(Self::refresh_ids),
+            YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+                items.iter_mut().for_each(Self::refresh_ids)
+            }
+            _ => {}
         }
     }
 
@@ -433,6 +539,50 @@ impl YamlNode {
         self.metadata.modified = true;
     }
 
+    /// Returns the node's explicit tag as written (`!!str`, `!Ref`), if any.
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// Sets or removes the node's explicit tag.
+    ///
+    /// The tag is written as given, so it must include its `!` or `!!`.
+    pub fn set_tag(&mut self, tag: Option<String>) {
+        self.tag = tag;
+        self.metadata.modified = true;
+    }
+
+    /// Returns whether this mapping or sequence is written in block or flow style.
+    pub fn style(&self) -> CollectionStyle {
+        self.metadata.style
+    }
+
+    /// Sets block or flow style for this mapping or sequence.
+    ///
+    /// The node's text changes as a whole, so it and everything in it are
+    /// marked modified and will be rewritten on save.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::document::node::{CollectionStyle, YamlValue};
+    /// use yamlquill::document::parser::parse_yaml_auto;
+    ///
+    /// let mut node = parse_yaml_auto("ports: [80, 443]\n").unwrap();
+    /// if let YamlValue::Object(entries) = node.value() {
+    ///     assert_eq!(entries["ports"].style(), CollectionStyle::Flow);
+    /// }
+    ///
+    /// assert_eq!(node.style(), CollectionStyle::Block);
+    /// node.set_style(CollectionStyle::Flow);
+    /// assert_eq!(node.style(), CollectionStyle::Flow);
+    /// assert!(node.is_modified());
+    /// ```
+    pub fn set_style(&mut self, style: CollectionStyle) {
+        self.metadata.style = style;
+        self.mark_all_modified();
+    }
+
     /// Returns the original formatting if preserved.
     pub fn original_formatting(&self) -> Option<&str> {
         self.original_formatting.as_deref()
@@ -497,6 +647,96 @@ impl YamlNode {
         self.metadata.modified
     }
 
+    /// Marks this node and all of its descendants as modified, dropping
+    /// their source spans.
+    ///
+    /// Use this on a node taken from another document (another file or
+    /// revision) before inserting it, so the saver serializes it instead of
+    /// copying text from the wrong source.
+    pub fn mark_all_modified(&mut self) {
+        self.metadata.modified = true;
+        self.metadata.text_span = None;
+        match &mut self.value {
+            YamlValue::Object(entries) => entries.values_mut().for_each(Self::mark_all_modified),
+            YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+                items.iter_mut().for_each(Self::mark_all_modified)
+            }
+            _ => {}
+        }
+    }
+
+    /// Sorts the keys of this mapping alphabetically, ignoring case.
+    ///
+    /// Comments move with the key that follows them; comments after the last
+    /// key stay at the end. With `recursive`, mappings nested anywhere below
+    /// (including inside sequences) are sorted too.
+    ///
+    /// Returns the number of mappings whose order changed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::document::node::YamlValue;
+    /// use yamlquill::document::parser::parse_yaml_auto;
+    ///
+    /// let mut node = parse_yaml_auto("b: 1\nA: 2\nc: 3\n").unwrap();
+    /// assert_eq!(node.sort_keys(false, false), 1);
+    /// if let YamlValue::Object(entries) = node.value() {
+    ///     assert_eq!(entries.keys().collect::<Vec<_>>(), ["A", "b", "c"]);
+    /// }
+    /// ```
+    pub fn sort_keys(&mut self, reverse: bool, recursive: bool) -> usize {
+        let mut sorted = 0;
+        if recursive {
+            match self.value_mut_traverse() {
+                YamlValue::Object(entries) => {
+                    for child in entries.values_mut() {
+                        sorted += child.sort_keys(reverse, true);
+                    }
+                }
+                YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+                    for child in items.iter_mut() {
+                        sorted += child.sort_keys(reverse, true);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let YamlValue::Object(entries) = &self.value else {
+            return sorted;
+        };
+        // Group each key with the comments above it, as ranges of indices
+        let mut groups = Vec::new();
+        let mut start = 0;
+        for (i, (key, child)) in entries.iter().enumerate() {
+            if !child.is_comment() {
+                groups.push(((key.to_lowercase(), key.clone()), start..i + 1));
+                start = i + 1;
+            }
+        }
+        let before: Vec<_> = groups.iter().map(|(_, range)| range.start).collect();
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+        if reverse {
+            groups.reverse();
+        }
+        if groups.iter().map(|(_, range)| range.start).eq(before) {
+            return sorted;
+        }
+
+        if let YamlValue::Object(entries) = self.value_mut() {
+            let mut old: Vec<_> = std::mem::take(entries).into_iter().map(Some).collect();
+            let trailing = start..old.len();
+            *entries = groups
+                .into_iter()
+                .flat_map(|(_, range)| range)
+                .chain(trailing)
+                .filter_map(|i| old[i].take())
+                .collect();
+        }
+        sorted + 1
+    }
+
     /// Returns true if this node is a comment.
     pub fn is_comment(&self) -> bool {
         self.value.is_comment()
@@ -537,6 +777,7 @@ mod text_span_tests {
         let metadata = NodeMetadata {
             text_span: Some(TextSpan { start: 0, end: 10 }),
             modified: false,
+            style: CollectionStyle::Block,
         };
 
         assert!(metadata.text_span.is_some());
@@ -550,6 +791,7 @@ mod text_span_tests {
         let metadata = NodeMetadata {
             text_span: None,
             modified: true,
+            style: CollectionStyle::Block,
         };
 
         assert!(metadata.text_span.is_none());
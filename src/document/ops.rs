@@ -0,0 +1,682 @@
+//! Edit operations on a [`YamlTree`].
+//!
+//! Every structural change can be described as an [`Op`]: insert, delete,
+//! replace, move, or rename a key. Applying an op returns its inverse, so a
+//! caller can keep an undo log of small ops instead of whole-tree snapshots,
+//! and [`apply_all`] applies a batch transactionally: if any op fails, the
+//! tree is left exactly as it was.
+//!
+//! Paths are the same index paths as [`YamlTree::get_node`]: for mappings the
+//! index is the entry's position, comment entries included. Because an edit
+//! shifts the paths after it, ops made concurrently against the same tree
+//! (two sessions, or the buffer and a reload from disk) are combined with
+//! [`transform_pair`], which rewrites each side's paths as if the other side
+//! had gone first. To follow one node across edits, keep its
+//! [`NodeId`](super::node::NodeId) and look it up with [`YamlTree::path_of`].
+//!
+//! ```
+//! use yamlquill::document::ops::{apply_all, Op};
+//! use yamlquill::document::parser::parse_yaml_auto;
+//! use yamlquill::document::tree::YamlTree;
+//! use yamlquill::file::saver::yaml_text;
+//!
+//! let mut tree = YamlTree::new(parse_yaml_auto("a: 1\nb: 2\n").unwrap());
+//! let undo = apply_all(
+//!     &mut tree,
+//!     &[
+//!         Op::RenameKey { path: vec![0], key: "first".to_string() },
+//!         Op::Delete { path: vec![1] },
+//!     ],
+//! )
+//! .unwrap();
+//! assert_eq!(yaml_text(&tree).unwrap().trim_end(), "first: 1");
+//!
+//! apply_all(&mut tree, &undo).unwrap();
+//! assert_eq!(yaml_text(&tree).unwrap().trim_end(), "a: 1\nb: 2");
+//! ```
+
+use super::node::{YamlNode, YamlValue};
+use super::tree::YamlTree;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// One edit to a tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Op {
+    /// Inserts `node` so that it ends up at `path`.
+    ///
+    /// `key` names the entry when the parent is a mapping and must be `None`
+    /// in a sequence.
+    Insert {
+        path: Vec<usize>,
+        key: Option<String>,
+        node: YamlNode,
+    },
+    /// Removes the node at `path`.
+    Delete { path: Vec<usize> },
+    /// Puts `node` in place of the node at `path` (the root if empty).
+    Replace { path: Vec<usize>, node: YamlNode },
+    /// Moves the node at `from` so that it ends up at `to`.
+    ///
+    /// `to` is a path in the tree as it is once the node has been taken out.
+    /// A mapping entry keeps its key; a sequence item can only move into
+    /// another sequence.
+    Move { from: Vec<usize>, to: Vec<usize> },
+    /// Changes the key of the mapping entry at `path`.
+    RenameKey { path: Vec<usize>, key: String },
+}
+
+impl Op {
+    /// Returns the path the op acts on (the source, for a move).
+    pub fn path(&self) -> &[usize] {
+        match self {
+            Op::Insert { path, .. }
+            | Op::Delete { path }
+            | Op::Replace { path, .. }
+            | Op::RenameKey { path, .. } => path,
+            Op::Move { from, .. } => from,
+        }
+    }
+
+    /// Returns the op that undoes this one, given the tree before it is applied.
+    ///
+    /// Fails if this op can't be applied to `tree`.
+    pub fn invert(&self, tree: &YamlTree) -> Result<Op> {
+        match self {
+            Op::Insert { path, key, .. } => {
+                let (parent, index) = parent_of(tree, path)?;
+                match (parent.value(), key) {
+                    (YamlValue::Object(entries), Some(key)) => {
+                        if entries.contains_key(key) {
+                            bail!("Key '{}' already exists", key);
+                        }
+                        check_index(index, entries.len() + 1)?;
+                    }
+                    (YamlValue::Object(_), None) => bail!("Inserting into a mapping needs a key"),
+                    (YamlValue::Array(items) | YamlValue::MultiDoc(items), None) => {
+                        check_index(index, items.len() + 1)?;
+                    }
+                    (YamlValue::Array(_) | YamlValue::MultiDoc(_), Some(_)) => {
+                        bail!("Sequence items have no key")
+                    }
+                    _ => bail!("Parent is not a container"),
+                }
+                Ok(Op::Delete { path: path.clone() })
+            }
+            Op::Delete { path } => {
+                let (parent, index) = parent_of(tree, path)?;
+                let node = tree
+                    .get_node(path)
+                    .with_context(|| format!("No node at {:?}", path))?;
+                Ok(Op::Insert {
+                    path: path.clone(),
+                    key: key_at(parent, index),
+                    node: node.clone(),
+                })
+            }
+            Op::Replace { path, .. } => {
+                let node = tree
+                    .get_node(path)
+                    .with_context(|| format!("No node at {:?}", path))?;
+                Ok(Op::Replace {
+                    path: path.clone(),
+                    node: node.clone(),
+                })
+            }
+            Op::Move { from, to } => {
+                parent_of(tree, from)?;
+                tree.get_node(from)
+                    .with_context(|| format!("No node at {:?}", from))?;
+                Ok(Op::Move {
+                    from: to.clone(),
+                    to: from.clone(),
+                })
+            }
+            Op::RenameKey { path, key } => {
+                let (parent, index) = parent_of(tree, path)?;
+                let YamlValue::Object(entries) = parent.value() else {
+                    bail!("Only mapping entries have keys");
+                };
+                let (old, _) = entries
+                    .get_index(index)
+                    .with_context(|| format!("No node at {:?}", path))?;
+                if old != key && entries.contains_key(key) {
+                    bail!("Key '{}' already exists", key);
+                }
+                Ok(Op::RenameKey {
+                    path: path.clone(),
+                    key: old.clone(),
+                })
+            }
+        }
+    }
+
+    /// Applies the op and returns its inverse.
+    ///
+    /// A failing op leaves the tree unchanged. The anchor registry is not
+    /// updated; [`apply_all`] rebuilds it once for a whole batch.
+    pub fn apply(&self, tree: &mut YamlTree) -> Result<Op> {
+        let inverse = self.invert(tree)?;
+        match self {
+            Op::Insert { path, key, node } => insert(tree, path, key.clone(), node.clone())?,
+            Op::Delete { path } => {
+                tree.delete_node(path)?;
+            }
+            Op::Replace { path, node } => {
+                let target = tree
+                    .get_node_mut(path)
+                    .with_context(|| format!("No node at {:?}", path))?;
+                *target = node.clone();
+            }
+            Op::Move { from, to } => {
+                let (parent, index) = parent_of(tree, from)?;
+                let key = key_at(parent, index);
+                let node = tree
+                    .get_node(from)
+                    .with_context(|| format!("No node at {:?}", from))?
+                    .clone();
+                tree.delete_node(from)?;
+                if let Err(e) = insert(tree, to, key.clone(), node.clone()) {
+                    // Put it back where it was
+                    insert(tree, from, key, node)?;
+                    return Err(e);
+                }
+            }
+            Op::RenameKey { path, key } => {
+                let (&index, parent_path) = path.split_last().context("The root has no key")?;
+                let parent = tree
+                    .get_node_mut(parent_path)
+                    .context("Parent node not found")?;
+                let YamlValue::Object(entries) = parent.value_mut() else {
+                    bail!("Only mapping entries have keys");
+                };
+                let (_, value) = entries
+                    .shift_remove_index(index)
+                    .context("Invalid object index")?;
+                entries.shift_insert(index, key.clone(), value);
+            }
+        }
+        Ok(inverse)
+    }
+}
+
+/// Applies `ops` in order, all or nothing.
+///
+/// Returns the ops that undo the batch, in the order to apply them. If any
+/// op fails, the tree is restored exactly, modification flags included, and
+/// the error says which op it was.
+pub fn apply_all(tree: &mut YamlTree, ops: &[Op]) -> Result<Vec<Op>> {
+    let before = tree.clone();
+    let mut inverses = Vec::with_capacity(ops.len());
+    for (i, op) in ops.iter().enumerate() {
+        match op.apply(tree) {
+            Ok(inverse) => inverses.push(inverse),
+            Err(e) => {
+                *tree = before;
+                return Err(e.context(format!(
+                    "Operation {} of {} failed; nothing was changed",
+                    i + 1,
+                    ops.len()
+                )));
+            }
+        }
+    }
+    tree.build_anchor_registry();
+    inverses.reverse();
+    Ok(inverses)
+}
+
+/// Rewrites two op sequences made against the same tree so each can follow the other.
+///
+/// Returns `(ours, theirs)` where the new `ours` applies after the original
+/// `theirs` and the new `theirs` after the original `ours`; both orders give
+/// the same tree. Ops whose target the other side deleted or replaced are
+/// dropped. When both sides insert at the same position, `ours` ends up
+/// after; when both replace or rename the same node, `ours` wins.
+///
+/// Move destinations are shifted like insert positions, which is exact as
+/// long as the other side doesn't edit the moved node's old parent.
+pub fn transform_pair(ours: &[Op], theirs: &[Op]) -> (Vec<Op>, Vec<Op>) {
+    let mut theirs = theirs.to_vec();
+    let mut rebased = Vec::with_capacity(ours.len());
+    for op in ours {
+        let mut op = Some(op.clone());
+        let mut next = Vec::with_capacity(theirs.len());
+        for other in theirs {
+            match &op {
+                Some(current) => {
+                    next.extend(transform(&other, current, false));
+                    op = transform(current, &other, true);
+                }
+                None => next.push(other),
+            }
+        }
+        theirs = next;
+        rebased.extend(op);
+    }
+    (rebased, theirs)
+}
+
+/// Rewrites `ours` to apply after `theirs`; see [`transform_pair`].
+pub fn rebase(ours: &[Op], theirs: &[Op]) -> Vec<Op> {
+    transform_pair(ours, theirs).0
+}
+
+/// Rewrites `op` to apply after `past`, both made against the same tree.
+///
+/// `wins` breaks ties: the winning insert goes after, the winning replace or
+/// rename is kept.
+fn transform(op: &Op, past: &Op, wins: bool) -> Option<Op> {
+    let same_target = |path: &[usize]| match past {
+        Op::Replace { path: other, .. } => {
+            matches!(op, Op::Replace { .. }) && path == other.as_slice()
+        }
+        Op::RenameKey { path: other, .. } => {
+            matches!(op, Op::RenameKey { .. }) && path == other.as_slice()
+        }
+        _ => false,
+    };
+    Some(match op {
+        Op::Insert { path, key, node } => Op::Insert {
+            path: shift(path, past, true, wins)?,
+            key: key.clone(),
+            node: node.clone(),
+        },
+        Op::Delete { path } => Op::Delete {
+            path: shift(path, past, false, wins)?,
+        },
+        Op::Replace { path, node } => {
+            if same_target(path) && !wins {
+                return None;
+            }
+            Op::Replace {
+                path: shift(path, past, false, wins)?,
+                node: node.clone(),
+            }
+        }
+        Op::Move { from, to } => Op::Move {
+            from: shift(from, past, false, wins)?,
+            to: shift(to, past, true, wins)?,
+        },
+        Op::RenameKey { path, key } => {
+            if same_target(path) && !wins {
+                return None;
+            }
+            Op::RenameKey {
+                path: shift(path, past, false, wins)?,
+                key: key.clone(),
+            }
+        }
+    })
+}
+
+/// Returns where `path` is after `past` has been applied, or `None` if
+/// `past` removed it.
+///
+/// A `position` is a place to insert rather than a node, so a deletion at
+/// exactly that place leaves it where it is and `wins` decides whether it
+/// goes after a concurrent insert there.
+fn shift(path: &[usize], past: &Op, position: bool, wins: bool) -> Option<Vec<usize>> {
+    match past {
+        Op::Insert { path: at, .. } => Some(shift_for_insert(path, at, position, wins)),
+        Op::Delete { path: at } => shift_for_delete(path, at, position),
+        Op::Replace { path: at, .. } => {
+            // Anything inside the replaced node is gone with it
+            if path.len() > at.len() && path.starts_with(at) {
+                None
+            } else {
+                Some(path.to_vec())
+            }
+        }
+        Op::Move { from, to } => {
+            if path.len() >= from.len() && path.starts_with(from) && !from.is_empty() {
+                // Inside the moved node: follow it
+                let mut moved = to.clone();
+                moved.extend_from_slice(&path[from.len()..]);
+                return Some(moved);
+            }
+            let removed = shift_for_delete(path, from, position)?;
+            Some(shift_for_insert(&removed, to, position, wins))
+        }
+        Op::RenameKey { .. } => Some(path.to_vec()),
+    }
+}
+
+fn shift_for_insert(path: &[usize], at: &[usize], position: bool, wins: bool) -> Vec<usize> {
+    let mut path = path.to_vec();
+    let Some((&index, parent)) = at.split_last() else {
+        return path;
+    };
+    let depth = parent.len();
+    if path.len() > depth && path.starts_with(parent) {
+        let tie_goes_after = !position || path.len() > at.len() || wins;
+        if path[depth] > index || (path[depth] == index && tie_goes_after) {
+            path[depth] += 1;
+        }
+    }
+    path
+}
+
+fn shift_for_delete(path: &[usize], at: &[usize], position: bool) -> Option<Vec<usize>> {
+    let mut path = path.to_vec();
+    let Some((&index, parent)) = at.split_last() else {
+        return Some(path);
+    };
+    let depth = parent.len();
+    if path.len() > depth && path.starts_with(parent) {
+        if path[depth] == index {
+            if !(position && path.len() == at.len()) {
+                return None;
+            }
+        } else if path[depth] > index {
+            path[depth] -= 1;
+        }
+    }
+    Some(path)
+}
+
+/// Returns the parent of the node at `path` and the node's index in it.
+fn parent_of<'a>(tree: &'a YamlTree, path: &[usize]) -> Result<(&'a YamlNode, usize)> {
+    let (&index, parent_path) = path
+        .split_last()
+        .ok_or_else(|| anyhow!("The root has no parent"))?;
+    let parent = tree
+        .get_node(parent_path)
+        .with_context(|| format!("No node at {:?}", parent_path))?;
+    Ok((parent, index))
+}
+
+fn key_at(parent: &YamlNode, index: usize) -> Option<String> {
+    match parent.value() {
+        YamlValue::Object(entries) => entries.get_index(index).map(|(k, _)| k.clone()),
+        _ => None,
+    }
+}
+
+fn check_index(index: usize, len: usize) -> Result<()> {
+    if index >= len {
+        bail!("Index {} out of bounds", index);
+    }
+    Ok(())
+}
+
+fn insert(tree: &mut YamlTree, path: &[usize], key: Option<String>, node: YamlNode) -> Result<()> {
+    let (parent, _) = parent_of(tree, path)?;
+    match (parent.value(), key) {
+        (YamlValue::Object(entries), Some(key)) => {
+            if entries.contains_key(&key) {
+                bail!("Key '{}' already exists", key);
+            }
+            tree.insert_node_in_object(path, key, node)
+        }
+        (YamlValue::Object(_), None) => bail!("Moving into a mapping needs a key"),
+        (YamlValue::Array(_) | YamlValue::MultiDoc(_), _) => tree.insert_node_in_array(path, node),
+        _ => bail!("Parent is not a container"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    fn tree(yaml: &str) -> YamlTree {
+        YamlTree::new(parse_yaml_auto(yaml).unwrap())
+    }
+
+    fn text(tree: &YamlTree) -> String {
+        flow(tree.root())
+    }
+
+    fn flow(node: &YamlNode) -> String {
+        match node.value() {
+            YamlValue::Object(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(k, v)| format!("{}: {}", k, flow(v)))
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+            YamlValue::Array(items) => {
+                let items: Vec<String> = items.iter().map(flow).collect();
+                format!("[{}]", items.join(", "))
+            }
+            value => value.to_string(),
+        }
+    }
+
+    fn scalar(yaml: &str) -> YamlNode {
+        parse_yaml_auto(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_each_op_and_its_inverse() {
+        let original = tree("a: 1\nlist: [x, y]\nnested:\n  b: 2\n");
+        let ops = [
+            Op::Insert {
+                path: vec![1],
+                key: Some("new".to_string()),
+                node: scalar("3"),
+            },
+            Op::Insert {
+                path: vec![1, 2],
+                key: None,
+                node: scalar("z"),
+            },
+            Op::Delete { path: vec![0] },
+            Op::Replace {
+                path: vec![2, 0],
+                node: scalar("[1, 2]"),
+            },
+            Op::Move {
+                from: vec![0],
+                to: vec![1, 1],
+            },
+            Op::Move {
+                from: vec![1, 0],
+                to: vec![1, 1],
+            },
+            Op::RenameKey {
+                path: vec![2],
+                key: "inner".to_string(),
+            },
+        ];
+        let expected = [
+            "{a: 1, new: 3, list: [x, y], nested: {b: 2}}",
+            "{a: 1, list: [x, y, z], nested: {b: 2}}",
+            "{list: [x, y], nested: {b: 2}}",
+            "{a: 1, list: [x, y], nested: {b: [1, 2]}}",
+            "{list: [x, y], nested: {b: 2, a: 1}}",
+            "{a: 1, list: [y, x], nested: {b: 2}}",
+            "{a: 1, list: [x, y], inner: {b: 2}}",
+        ];
+        for (op, expected) in ops.iter().zip(expected) {
+            let mut tree = original.clone();
+            let inverse = op.apply(&mut tree).unwrap();
+            assert_eq!(text(&tree), expected, "{:?}", op);
+            inverse.apply(&mut tree).unwrap();
+            assert_eq!(text(&tree), text(&original), "inverse of {:?}", op);
+        }
+    }
+
+    #[test]
+    fn test_invalid_ops_fail_without_changes() {
+        let original = tree("a: 1\nb: [x]\n");
+        let ops = [
+            Op::Insert {
+                path: vec![0],
+                key: Some("b".to_string()),
+                node: scalar("2"),
+            },
+            Op::Insert {
+                path: vec![1, 0],
+                key: Some("k".to_string()),
+                node: scalar("2"),
+            },
+            Op::Insert {
+                path: vec![5],
+                key: Some("c".to_string()),
+                node: scalar("2"),
+            },
+            Op::Delete { path: vec![] },
+            Op::Delete { path: vec![9] },
+            Op::Move {
+                from: vec![1],
+                to: vec![1, 0],
+            },
+            // A sequence item has no key to take into a mapping
+            Op::Move {
+                from: vec![1, 0],
+                to: vec![0],
+            },
+            Op::RenameKey {
+                path: vec![0],
+                key: "b".to_string(),
+            },
+            Op::RenameKey {
+                path: vec![1, 0],
+                key: "c".to_string(),
+            },
+        ];
+        for op in &ops {
+            let mut tree = original.clone();
+            assert!(op.apply(&mut tree).is_err(), "{:?}", op);
+            assert_eq!(text(&tree), text(&original), "{:?}", op);
+        }
+    }
+
+    #[test]
+    fn test_failed_batch_leaves_tree_untouched() {
+        let mut tree = tree("a: 1\nb: 2\n");
+        let before = tree.clone();
+        let err = apply_all(
+            &mut tree,
+            &[
+                Op::Delete { path: vec![0] },
+                Op::RenameKey {
+                    path: vec![0],
+                    key: "c".to_string(),
+                },
+                Op::Delete { path: vec![5] },
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Operation 3 of 3 failed; nothing was changed"
+        );
+        assert_eq!(tree.root(), before.root());
+    }
+
+    /// Applies both sides in both orders and checks they agree.
+    fn merged(base: &str, ours: &[Op], theirs: &[Op]) -> String {
+        let (ours_after, theirs_after) = transform_pair(ours, theirs);
+
+        let mut ours_first = tree(base);
+        apply_all(&mut ours_first, ours).unwrap();
+        apply_all(&mut ours_first, &theirs_after).unwrap();
+
+        let mut theirs_first = tree(base);
+        apply_all(&mut theirs_first, theirs).unwrap();
+        apply_all(&mut theirs_first, &ours_after).unwrap();
+
+        assert_eq!(text(&ours_first), text(&theirs_first));
+        text(&ours_first)
+    }
+
+    #[test]
+    fn test_concurrent_edits_converge() {
+        let base = "list: [a, b, c]\nmeta:\n  name: web\n  tier: front\n";
+        let insert = |i, s: &str| Op::Insert {
+            path: vec![0, i],
+            key: None,
+            node: scalar(s),
+        };
+
+        // Both insert at the same place: ours goes after
+        assert_eq!(
+            merged(base, &[insert(1, "x")], &[insert(1, "y")]),
+            "{list: [a, y, x, b, c], meta: {name: web, tier: front}}"
+        );
+
+        // A deletion before an edit shifts the edit
+        assert_eq!(
+            merged(
+                base,
+                &[Op::Replace {
+                    path: vec![0, 2],
+                    node: scalar("C"),
+                }],
+                &[Op::Delete { path: vec![0, 0] }, insert(0, "z")],
+            ),
+            "{list: [z, b, C], meta: {name: web, tier: front}}"
+        );
+
+        // Edits inside a deleted node are dropped
+        assert_eq!(
+            merged(
+                base,
+                &[Op::RenameKey {
+                    path: vec![1, 1],
+                    key: "layer".to_string(),
+                }],
+                &[Op::Delete { path: vec![1] }],
+            ),
+            "{list: [a, b, c]}"
+        );
+
+        // Replacing the same node: ours wins
+        assert_eq!(
+            merged(
+                base,
+                &[Op::Replace {
+                    path: vec![1, 0],
+                    node: scalar("api"),
+                }],
+                &[Op::Replace {
+                    path: vec![1, 0],
+                    node: scalar("www"),
+                }],
+            ),
+            "{list: [a, b, c], meta: {name: api, tier: front}}"
+        );
+
+        // Edits inside a moved node follow it
+        assert_eq!(
+            merged(
+                base,
+                &[Op::Move {
+                    from: vec![0],
+                    to: vec![0, 2],
+                }],
+                &[insert(3, "d")],
+            ),
+            "{meta: {name: web, tier: front, list: [a, b, c, d]}}"
+        );
+    }
+
+    #[test]
+    fn test_node_ids_follow_nodes() {
+        let mut tree = tree("a: 1\nb: [x, y]\n");
+        let y = tree.get_node(&[1, 1]).unwrap().id();
+        Op::Delete { path: vec![0] }.apply(&mut tree).unwrap();
+        Op::Insert {
+            path: vec![0, 0],
+            key: None,
+            node: scalar("w"),
+        }
+        .apply(&mut tree)
+        .unwrap();
+        assert_eq!(tree.path_of(y), Some(vec![0, 2]));
+
+        // Undoing a delete brings back the same node
+        let undo = Op::Delete { path: vec![0, 2] }.apply(&mut tree).unwrap();
+        assert_eq!(tree.path_of(y), None);
+        undo.apply(&mut tree).unwrap();
+        assert_eq!(tree.path_of(y), Some(vec![0, 2]));
+
+        let copy = tree.get_node(&[0, 2]).unwrap().duplicate();
+        assert_ne!(copy.id(), y);
+        assert_eq!(&copy, tree.get_node(&[0, 2]).unwrap());
+    }
+}
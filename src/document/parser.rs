@@ -19,15 +19,17 @@
 //! let node = parse_yaml(yaml).unwrap();
 //! ```
 
+use crate::document::duplicates::{unique_key, written_key};
 use crate::document::node::{
-    CommentNode, CommentPosition, YamlNode, YamlNumber, YamlString, YamlValue,
+    CollectionStyle, CommentNode, CommentPosition, NodeId, YamlNode, YamlNumber, YamlString,
+    YamlValue,
 };
 use anyhow::{bail, Context, Result};
 use indexmap::IndexMap;
 use serde_yaml::{self, Value};
-use std::collections::HashMap;
-use yaml_rust2::parser::{Event, EventReceiver, Parser};
-use yaml_rust2::scanner::{Scanner, TokenType};
+use std::collections::{HashMap, HashSet};
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser, Tag};
+use yaml_rust2::scanner::{Marker, Scanner, TScalarStyle, TokenType};
 
 /// Represents a comment found in YAML source.
 ///
@@ -211,6 +213,9 @@ struct TreeBuilder {
     documents: Vec<YamlNode>,
     /// Comments extracted from source (to be injected after parsing)
     comments: Vec<ExtractedComment>,
+    /// Character indices of `{` and `[` in the source, where flow
+    /// collections start
+    flow_starts: HashSet<usize>,
 }
 
 /// Represents a node being built (may be incomplete)
@@ -220,21 +225,44 @@ enum BuildNode {
         entries: IndexMap<String, YamlNode>,
         anchor: Option<String>,
         current_key: Option<String>, // Key waiting for its value
+        style: CollectionStyle,
+        tag: Option<String>,
     },
     /// A sequence being constructed
     Sequence {
         elements: Vec<YamlNode>,
         anchor: Option<String>,
+        style: CollectionStyle,
+        tag: Option<String>,
     },
 }
 
 impl TreeBuilder {
-    fn new(anchor_map: AnchorMap, comments: Vec<ExtractedComment>) -> Self {
+    fn new(anchor_map: AnchorMap, comments: Vec<ExtractedComment>, yaml_str: &str) -> Self {
+        let flow_starts = yaml_str
+            .chars()
+            .enumerate()
+            .filter(|(_, c)| matches!(c, '{' | '['))
+            .map(|(i, _)| i)
+            .collect();
         Self {
             stack: Vec::new(),
             anchor_map,
             documents: Vec::new(),
             comments,
+            flow_starts,
+        }
+    }
+
+    /// Returns the style of a collection starting at `mark`.
+    ///
+    /// Flow collections start at their opening bracket; block collections
+    /// start at their first key or `-`.
+    fn style_at(&self, mark: Marker) -> CollectionStyle {
+        if self.flow_starts.contains(&mark.index()) {
+            CollectionStyle::Flow
+        } else {
+            CollectionStyle::Block
         }
     }
 
@@ -248,7 +276,8 @@ impl TreeBuilder {
                     ..
                 } => {
                     if let Some(key) = current_key.take() {
-                        entries.insert(key, node);
+                        // A repeated key keeps its entry under a marked key
+                        entries.insert(unique_key(entries, key), node);
                     }
                 }
                 BuildNode::Sequence { elements, .. } => {
@@ -271,8 +300,8 @@ impl TreeBuilder {
     }
 }
 
-impl EventReceiver for TreeBuilder {
-    fn on_event(&mut self, ev: Event) {
+impl MarkedEventReceiver for TreeBuilder {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
         match ev {
             Event::Nothing | Event::StreamStart | Event::StreamEnd => {
                 // Ignore structural events
@@ -299,16 +328,19 @@ impl EventReceiver for TreeBuilder {
                     metadata: crate::document::node::NodeMetadata {
                         text_span: None,
                         modified: false,
+                        style: CollectionStyle::Block,
                     },
                     anchor: None,
                     alias_target: Some(anchor_name),
+                    tag: None,
                     original_formatting: None,
+                    id: NodeId::next(),
                 };
 
                 self.push_value(node);
             }
 
-            Event::Scalar(value, _style, anchor_id, _tag) => {
+            Event::Scalar(value, style, anchor_id, tag) => {
                 // In a mapping context, scalars alternate between keys and values
                 if let Some(BuildNode::Mapping { current_key, .. }) = self.stack.last_mut() {
                     if current_key.is_none() {
@@ -318,8 +350,20 @@ impl EventReceiver for TreeBuilder {
                     }
                 }
 
-                // This is a value (or we're not in a mapping)
-                let yaml_value = parse_scalar_value(&value);
+                // This is a value (or we're not in a mapping); block and quoted
+                // scalars, and plain ones tagged `!!str`, are always strings
+                let tag = tag.map(tag_text);
+                let yaml_value = match style {
+                    TScalarStyle::Literal => YamlValue::String(YamlString::Literal(value)),
+                    TScalarStyle::Folded => YamlValue::String(YamlString::Folded(value)),
+                    TScalarStyle::SingleQuoted | TScalarStyle::DoubleQuoted => {
+                        YamlValue::String(YamlString::Plain(value))
+                    }
+                    _ if tag.as_deref() == Some("!!str") => {
+                        YamlValue::String(YamlString::Plain(value))
+                    }
+                    _ => parse_scalar_value(&value),
+                };
                 let anchor_name = self.get_anchor_name(anchor_id);
 
                 let node = YamlNode {
@@ -327,45 +371,61 @@ impl EventReceiver for TreeBuilder {
                     metadata: crate::document::node::NodeMetadata {
                         text_span: None,
                         modified: false,
+                        style: CollectionStyle::Block,
                     },
                     anchor: anchor_name,
                     alias_target: None,
+                    tag,
                     original_formatting: None,
+                    id: NodeId::next(),
                 };
 
                 self.push_value(node);
             }
 
-            Event::SequenceStart(anchor_id, _tag) => {
+            Event::SequenceStart(anchor_id, tag) => {
                 let anchor_name = self.get_anchor_name(anchor_id);
                 self.stack.push(BuildNode::Sequence {
                     elements: Vec::new(),
                     anchor: anchor_name,
+                    style: self.style_at(mark),
+                    tag: tag.map(tag_text),
                 });
             }
 
             Event::SequenceEnd => {
-                if let Some(BuildNode::Sequence { elements, anchor }) = self.stack.pop() {
+                if let Some(BuildNode::Sequence {
+                    elements,
+                    anchor,
+                    style,
+                    tag,
+                }) = self.stack.pop()
+                {
                     let node = YamlNode {
                         value: YamlValue::Array(elements),
                         metadata: crate::document::node::NodeMetadata {
                             text_span: None,
                             modified: false,
+                            style,
                         },
                         anchor,
                         alias_target: None,
+                        tag,
                         original_formatting: None,
+                        id: NodeId::next(),
                     };
                     self.push_value(node);
                 }
             }
 
-            Event::MappingStart(anchor_id, _tag) => {
+            Event::MappingStart(anchor_id, tag) => {
                 let anchor_name = self.get_anchor_name(anchor_id);
                 self.stack.push(BuildNode::Mapping {
                     entries: IndexMap::new(),
                     anchor: anchor_name,
                     current_key: None,
+                    style: self.style_at(mark),
+                    tag: tag.map(tag_text),
                 });
             }
 
@@ -374,6 +434,8 @@ impl EventReceiver for TreeBuilder {
                     entries,
                     anchor,
                     current_key: _,
+                    style,
+                    tag,
                 }) = self.stack.pop()
                 {
                     let node = YamlNode {
@@ -381,10 +443,13 @@ impl EventReceiver for TreeBuilder {
                         metadata: crate::document::node::NodeMetadata {
                             text_span: None,
                             modified: false,
+                            style,
                         },
                         anchor,
                         alias_target: None,
+                        tag,
                         original_formatting: None,
+                        id: NodeId::next(),
                     };
                     self.push_value(node);
                 }
@@ -393,6 +458,15 @@ impl EventReceiver for TreeBuilder {
     }
 }
 
+/// Returns a tag as written in the source: the `tag:yaml.org,2002:` prefix
+/// that `!!` stands for is shortened back to `!!`.
+fn tag_text(tag: Tag) -> String {
+    match tag.handle.as_str() {
+        "tag:yaml.org,2002:" => format!("!!{}", tag.suffix),
+        _ => format!("{}{}", tag.handle, tag.suffix),
+    }
+}
+
 /// Parse a scalar string value into a YamlValue
 fn parse_scalar_value(s: &str) -> YamlValue {
     // Try to parse as various types
@@ -542,10 +616,13 @@ fn convert_value(value: Value) -> Result<YamlNode> {
         metadata: crate::document::node::NodeMetadata {
             text_span: None,
             modified: false,
+            style: CollectionStyle::Block,
         },
         anchor: None,
         alias_target: None,
+        tag: None,
         original_formatting: None,
+        id: NodeId::next(),
     })
 }
 
@@ -739,7 +816,9 @@ fn inject_comments_by_path(
                 let mut key_lines: Vec<usize> = Vec::new();
                 let mut search_from = 0;
                 for (key, _) in &processed_entries {
-                    if let Some(line) = find_key_line_in_yaml(key, yaml_lines, search_from) {
+                    if let Some(line) =
+                        find_key_line_in_yaml(written_key(key), yaml_lines, search_from)
+                    {
                         key_lines.push(line);
                         search_from = line + 1;
                     } else {
@@ -932,7 +1011,7 @@ pub fn parse_yaml_auto(yaml_str: &str) -> Result<YamlNode> {
 
     // Pass 3: Parse with Parser + TreeBuilder
     let mut parser = Parser::new(yaml_str.chars());
-    let mut builder = TreeBuilder::new(anchor_map, comments);
+    let mut builder = TreeBuilder::new(anchor_map, comments, yaml_str);
 
     parser
         .load(&mut builder, true)
@@ -975,10 +1054,13 @@ pub fn parse_value(value: &Value) -> YamlNode {
         metadata: crate::document::node::NodeMetadata {
             text_span: None,
             modified: false,
+            style: CollectionStyle::Block,
         },
         anchor: None,
         alias_target: None,
+        tag: None,
         original_formatting: None,
+        id: NodeId::next(),
     })
 }
 
@@ -1008,6 +1090,20 @@ mod tests {
         assert!(matches!(node.value(), YamlValue::Boolean(false)));
     }
 
+    #[test]
+    fn test_quoted_scalars_are_strings() {
+        let node = parse_yaml_auto("port: \"80\"\nempty: ''\nflag: 'true'\n").unwrap();
+        let YamlValue::Object(entries) = node.value() else {
+            panic!("expected a mapping");
+        };
+        for (key, text) in [("port", "80"), ("empty", ""), ("flag", "true")] {
+            assert_eq!(
+                entries[key].value(),
+                &YamlValue::String(YamlString::Plain(text.to_string()))
+            );
+        }
+    }
+
     #[test]
     fn test_parse_integer() {
         let yaml = "42";
@@ -1030,10 +1126,10 @@ mod tests {
 
     #[test]
     fn test_parse_float() {
-        let yaml = "3.14";
+        let yaml = "2.75";
         let node = parse_yaml(yaml).unwrap();
         match node.value() {
-            YamlValue::Number(YamlNumber::Float(f)) => assert_eq!(*f, 3.14),
+            YamlValue::Number(YamlNumber::Float(f)) => assert_eq!(*f, 2.75),
             _ => panic!("Expected float"),
         }
     }
@@ -1231,10 +1327,10 @@ user:
 
     #[test]
     fn test_convert_value_float() {
-        let value = Value::Number(serde_yaml::Number::from(3.14));
+        let value = Value::Number(serde_yaml::Number::from(2.75));
         let node = convert_value(value).unwrap();
         match node.value() {
-            YamlValue::Number(YamlNumber::Float(f)) => assert!((f - 3.14).abs() < 0.001),
+            YamlValue::Number(YamlNumber::Float(f)) => assert!((f - 2.75).abs() < 0.001),
             _ => panic!("Expected float"),
         }
     }
@@ -0,0 +1,792 @@
+//! Prometheus rule files (`:set prometheus`).
+//!
+//! Rule files hold `groups`, each with a list of `rules`; a rule is either
+//! an alert (`alert: HighErrorRate`) or a recording rule
+//! (`record: job:errors:rate5m`), and both compute an `expr` in PromQL. The
+//! same layout appears under `spec` in a `PrometheusRule` resource.
+//!
+//! [`check`] is a syntax checker for PromQL: it parses an expression the
+//! way Prometheus does (selectors, ranges and subqueries, functions,
+//! aggregations and binary operators with their modifiers) and reports the
+//! first problem, without evaluating anything.
+
+use crate::document::node::{YamlNode, YamlValue};
+
+/// Source name for the diagnostics PromQL checking reports.
+pub const PROMQL_SOURCE: &str = "promql";
+
+/// Aggregation operators, with the number of arguments each takes.
+const AGGREGATIONS: &[(&str, usize)] = &[
+    ("sum", 1),
+    ("min", 1),
+    ("max", 1),
+    ("avg", 1),
+    ("group", 1),
+    ("stddev", 1),
+    ("stdvar", 1),
+    ("count", 1),
+    ("count_values", 2),
+    ("bottomk", 2),
+    ("topk", 2),
+    ("quantile", 2),
+    ("limitk", 2),
+    ("limit_ratio", 2),
+];
+
+/// PromQL functions.
+pub const FUNCTIONS: &[&str] = &[
+    "abs",
+    "absent",
+    "absent_over_time",
+    "acos",
+    "acosh",
+    "asin",
+    "asinh",
+    "atan",
+    "atanh",
+    "avg_over_time",
+    "ceil",
+    "changes",
+    "clamp",
+    "clamp_max",
+    "clamp_min",
+    "cos",
+    "cosh",
+    "count_over_time",
+    "day_of_month",
+    "day_of_week",
+    "day_of_year",
+    "days_in_month",
+    "deg",
+    "delta",
+    "deriv",
+    "double_exponential_smoothing",
+    "exp",
+    "floor",
+    "histogram_avg",
+    "histogram_count",
+    "histogram_fraction",
+    "histogram_quantile",
+    "histogram_stddev",
+    "histogram_stdvar",
+    "histogram_sum",
+    "holt_winters",
+    "hour",
+    "idelta",
+    "increase",
+    "info",
+    "irate",
+    "label_join",
+    "label_replace",
+    "last_over_time",
+    "ln",
+    "log10",
+    "log2",
+    "mad_over_time",
+    "max_over_time",
+    "min_over_time",
+    "minute",
+    "month",
+    "pi",
+    "predict_linear",
+    "present_over_time",
+    "quantile_over_time",
+    "rad",
+    "rate",
+    "resets",
+    "round",
+    "scalar",
+    "sgn",
+    "sin",
+    "sinh",
+    "sort",
+    "sort_by_label",
+    "sort_by_label_desc",
+    "sort_desc",
+    "sqrt",
+    "stddev_over_time",
+    "stdvar_over_time",
+    "sum_over_time",
+    "tan",
+    "tanh",
+    "time",
+    "timestamp",
+    "vector",
+    "year",
+];
+
+/// Binary operators and their precedence, loosest first.
+const OPERATORS: &[(&str, u8)] = &[
+    ("or", 1),
+    ("and", 2),
+    ("unless", 2),
+    ("==", 3),
+    ("!=", 3),
+    ("<=", 3),
+    (">=", 3),
+    ("<", 3),
+    (">", 3),
+    ("+", 4),
+    ("-", 4),
+    ("*", 5),
+    ("/", 5),
+    ("%", 5),
+    ("atan2", 5),
+    ("^", 6),
+];
+
+/// Checks the syntax of a PromQL expression.
+///
+/// Returns a description of the first problem, with its 1-based column.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::prometheus::check;
+///
+/// assert!(check("sum by (job) (rate(http_requests_total{code=~\"5..\"}[5m])) > 0.1").is_ok());
+/// assert_eq!(check("rate(errors[5m]").unwrap_err(), "expected ')' at column 16");
+/// ```
+pub fn check(expr: &str) -> Result<(), String> {
+    let mut parser = Parser {
+        chars: expr.chars().collect(),
+        pos: 0,
+    };
+    parser.expr(0)?;
+    parser.skip_space();
+    if parser.pos < parser.chars.len() {
+        return Err(parser.unexpected());
+    }
+    Ok(())
+}
+
+/// Returns the paths of every string `expr` value under `root`.
+pub fn expr_paths(root: &YamlNode) -> Vec<Vec<usize>> {
+    let mut found = Vec::new();
+    collect_exprs(root, &mut Vec::new(), &mut found);
+    found
+}
+
+fn collect_exprs(node: &YamlNode, path: &mut Vec<usize>, found: &mut Vec<Vec<usize>>) {
+    match node.value() {
+        YamlValue::Object(entries) => {
+            for (index, (key, child)) in entries.iter().enumerate() {
+                path.push(index);
+                if key == "expr" && matches!(child.value(), YamlValue::String(_)) {
+                    found.push(path.clone());
+                } else {
+                    collect_exprs(child, path, found);
+                }
+                path.pop();
+            }
+        }
+        YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+            for (index, item) in items.iter().enumerate() {
+                path.push(index);
+                collect_exprs(item, path, found);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the kind (`alert` or `record`) and name of a rule.
+pub fn rule_name(node: &YamlNode) -> Option<(&'static str, &str)> {
+    let YamlValue::Object(entries) = node.value() else {
+        return None;
+    };
+    ["alert", "record"]
+        .into_iter()
+        .find_map(|kind| match entries.get(kind).map(|n| n.value()) {
+            Some(YamlValue::String(name)) => Some((kind, name.as_str())),
+            _ => None,
+        })
+}
+
+/// Returns the paths of every rule (an element of a `rules` list with an
+/// `alert` or `record` name) under `root`.
+pub fn rule_paths(root: &YamlNode) -> Vec<Vec<usize>> {
+    let mut found = Vec::new();
+    collect_rules(root, &mut Vec::new(), &mut found);
+    found
+}
+
+fn collect_rules(node: &YamlNode, path: &mut Vec<usize>, found: &mut Vec<Vec<usize>>) {
+    match node.value() {
+        YamlValue::Object(entries) => {
+            for (index, (key, child)) in entries.iter().enumerate() {
+                path.push(index);
+                match child.value() {
+                    YamlValue::Array(rules) if key == "rules" => {
+                        for (rule_index, rule) in rules.iter().enumerate() {
+                            if rule_name(rule).is_some() {
+                                path.push(rule_index);
+                                found.push(path.clone());
+                                path.pop();
+                            }
+                        }
+                    }
+                    _ => collect_rules(child, path, found),
+                }
+                path.pop();
+            }
+        }
+        YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+            for (index, item) in items.iter().enumerate() {
+                path.push(index);
+                collect_rules(item, path, found);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_space(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '#' {
+                // Comments run to the end of the line
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+            } else if c.is_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at column {}", message, self.pos + 1)
+    }
+
+    fn unexpected(&self) -> String {
+        match self.peek() {
+            Some(c) => self.error(&format!("unexpected '{}'", c)),
+            None => self.error("unexpected end of expression"),
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_space();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    /// Reads an identifier (`rate`, `job`, `node:cpu:rate5m`) without
+    /// consuming it when `consume` is false.
+    fn ident(&mut self, consume: bool) -> Option<String> {
+        self.skip_space();
+        let start = self.pos;
+        let mut end = start;
+        while let Some(&c) = self.chars.get(end) {
+            let ok = c.is_ascii_alphabetic()
+                || c == '_'
+                || c == ':'
+                || (end > start && c.is_ascii_digit());
+            if !ok {
+                break;
+            }
+            end += 1;
+        }
+        if end == start {
+            return None;
+        }
+        if consume {
+            self.pos = end;
+        }
+        Some(self.chars[start..end].iter().collect())
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        match self.ident(false) {
+            Some(found) if found.eq_ignore_ascii_case(word) => {
+                self.ident(true);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Reads the binary operator at the cursor, if any, without consuming it.
+    fn operator(&mut self) -> Option<(&'static str, u8)> {
+        self.skip_space();
+        let word = self.ident(false).map(|w| w.to_ascii_lowercase());
+        OPERATORS.iter().copied().find(|(op, _)| {
+            if op.chars().all(|c| c.is_ascii_alphabetic()) {
+                word.as_deref() == Some(*op)
+            } else {
+                let text: String = self.chars[self.pos..].iter().take(op.len()).collect();
+                // `=~` and `!~` only appear in matchers
+                text == *op && !(*op == "!=" && self.chars.get(self.pos + 2) == Some(&'~'))
+            }
+        })
+    }
+
+    fn expr(&mut self, min_precedence: u8) -> Result<(), String> {
+        self.unary()?;
+        while let Some((op, precedence)) = self.operator() {
+            if precedence < min_precedence {
+                break;
+            }
+            self.pos += op.chars().count();
+            if precedence == 3 {
+                self.keyword("bool");
+            }
+            if self.keyword("on") || self.keyword("ignoring") {
+                self.labels()?;
+                if self.keyword("group_left") || self.keyword("group_right") {
+                    self.skip_space();
+                    if self.peek() == Some('(') {
+                        self.labels()?;
+                    }
+                }
+            }
+            // `^` is right-associative, everything else left-associative
+            let next = if op == "^" {
+                precedence
+            } else {
+                precedence + 1
+            };
+            self.expr(next)?;
+        }
+        Ok(())
+    }
+
+    fn unary(&mut self) -> Result<(), String> {
+        if self.eat('-') || self.eat('+') {
+            return self.unary();
+        }
+        self.primary()?;
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<(), String> {
+        loop {
+            if self.eat('[') {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c != ']') {
+                    self.pos += 1;
+                }
+                let range: String = self.chars[start..self.pos].iter().collect();
+                self.pos = start;
+                self.range(&range)?;
+                self.pos = start + range.chars().count();
+                self.expect(']')?;
+            } else if self.keyword("offset") {
+                self.eat('-');
+                self.duration_token()?;
+            } else if self.eat('@') {
+                if self.keyword("start") || self.keyword("end") {
+                    self.expect('(')?;
+                    self.expect(')')?;
+                } else {
+                    self.eat('-');
+                    self.number()?;
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Checks the inside of `[5m]` or a subquery's `[1h:5m]`.
+    fn range(&self, range: &str) -> Result<(), String> {
+        let (window, step) = match range.split_once(':') {
+            Some((window, step)) => (window, Some(step)),
+            None => (range, None),
+        };
+        if !is_duration(window.trim()) {
+            return Err(self.error("expected a duration like 5m"));
+        }
+        match step.map(str::trim) {
+            Some(step) if !step.is_empty() && !is_duration(step) => {
+                Err(self.error("expected a subquery step like 1m"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn duration_token(&mut self) -> Result<(), String> {
+        self.skip_space();
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if is_duration(&text) {
+            Ok(())
+        } else {
+            self.pos = start;
+            Err(self.error("expected a duration like 5m"))
+        }
+    }
+
+    fn number(&mut self) -> Result<(), String> {
+        self.skip_space();
+        let start = self.pos;
+        if let Some(word) = self.ident(false) {
+            if word.eq_ignore_ascii_case("inf") || word.eq_ignore_ascii_case("nan") {
+                self.ident(true);
+                return Ok(());
+            }
+        }
+        if self.chars[self.pos..].starts_with(&['0', 'x']) {
+            self.pos += 2;
+            while self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                self.pos += 1;
+            }
+        } else {
+            while self
+                .peek()
+                .is_some_and(|c| c.is_ascii_digit() || c == '.' || c == '_')
+            {
+                self.pos += 1;
+            }
+            if self.pos > start && matches!(self.peek(), Some('e' | 'E')) {
+                self.pos += 1;
+                if matches!(self.peek(), Some('+' | '-')) {
+                    self.pos += 1;
+                }
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+            }
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if text.replace('_', "").parse::<f64>().is_ok()
+            || (text.starts_with("0x") && text.len() > 2)
+        {
+            Ok(())
+        } else {
+            self.pos = start;
+            Err(self.error("expected a number"))
+        }
+    }
+
+    fn string(&mut self) -> Result<(), String> {
+        self.skip_space();
+        let Some(quote @ ('"' | '\'' | '`')) = self.peek() else {
+            return Err(self.error("expected a string"));
+        };
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == quote {
+                return Ok(());
+            }
+            if c == '\\' && quote != '`' {
+                self.pos += 1;
+            }
+        }
+        self.pos = start;
+        Err(self.error("unterminated string"))
+    }
+
+    fn primary(&mut self) -> Result<(), String> {
+        self.skip_space();
+        match self.peek() {
+            None => Err(self.unexpected()),
+            Some('(') => {
+                self.pos += 1;
+                self.expr(0)?;
+                self.expect(')')
+            }
+            Some('"' | '\'' | '`') => self.string(),
+            Some('{') => self.matchers(false),
+            Some(c) if c.is_ascii_digit() || c == '.' => self.duration_or_number(),
+            Some(_) => {
+                let Some(name) = self.ident(false) else {
+                    return Err(self.unexpected());
+                };
+                let lower = name.to_ascii_lowercase();
+                if lower == "inf" || lower == "nan" {
+                    return self.number();
+                }
+                if let Some(&(_, arity)) = AGGREGATIONS.iter().find(|(op, _)| *op == lower) {
+                    self.ident(true);
+                    return self.aggregation(&name, arity);
+                }
+                let start = self.pos;
+                self.ident(true);
+                self.skip_space();
+                if self.peek() == Some('(') {
+                    if !FUNCTIONS.contains(&name.as_str()) {
+                        self.pos = start;
+                        return Err(self.error(&format!("unknown function '{}'", name)));
+                    }
+                    return self.call();
+                }
+                if OPERATORS.iter().any(|(op, _)| *op == lower)
+                    || [
+                        "by",
+                        "without",
+                        "on",
+                        "ignoring",
+                        "group_left",
+                        "group_right",
+                        "bool",
+                        "offset",
+                    ]
+                    .contains(&lower.as_str())
+                {
+                    self.pos = start;
+                    return Err(self.error(&format!("unexpected keyword '{}'", name)));
+                }
+                if self.peek() == Some('{') {
+                    self.matchers(true)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Numbers may also be durations (`5m` as a number of seconds).
+    fn duration_or_number(&mut self) -> Result<(), String> {
+        let start = self.pos;
+        if self.duration_token().is_ok() {
+            return Ok(());
+        }
+        self.pos = start;
+        self.number()
+    }
+
+    fn call(&mut self) -> Result<(), String> {
+        self.expect('(')?;
+        if self.eat(')') {
+            return Ok(());
+        }
+        loop {
+            self.expr(0)?;
+            if self.eat(')') {
+                return Ok(());
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected ')'"));
+            }
+        }
+    }
+
+    fn aggregation(&mut self, name: &str, arity: usize) -> Result<(), String> {
+        let grouped = self.grouping()?;
+        self.expect('(')?;
+        let mut arguments = 0;
+        loop {
+            self.expr(0)?;
+            arguments += 1;
+            if self.eat(')') {
+                break;
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected ')'"));
+            }
+        }
+        if arguments != arity {
+            return Err(self.error(&format!(
+                "{} expects {} argument{}, got {}",
+                name,
+                arity,
+                if arity == 1 { "" } else { "s" },
+                arguments
+            )));
+        }
+        if !grouped {
+            self.grouping()?;
+        }
+        Ok(())
+    }
+
+    /// Reads an optional `by (...)` or `without (...)`.
+    fn grouping(&mut self) -> Result<bool, String> {
+        if self.keyword("by") || self.keyword("without") {
+            self.labels()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Reads a label list like `(job, instance)`.
+    fn labels(&mut self) -> Result<(), String> {
+        self.expect('(')?;
+        if self.eat(')') {
+            return Ok(());
+        }
+        loop {
+            self.skip_space();
+            if matches!(self.peek(), Some('"' | '\'')) {
+                self.string()?;
+            } else if self.ident(true).is_none() {
+                return Err(self.error("expected a label name"));
+            }
+            if self.eat(')') {
+                return Ok(());
+            }
+            self.expect(',')?;
+            if self.eat(')') {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Reads `{job="api", code=~"5.."}`.
+    fn matchers(&mut self, has_name: bool) -> Result<(), String> {
+        let open = self.pos;
+        self.expect('{')?;
+        let mut any = has_name;
+        loop {
+            if self.eat('}') {
+                break;
+            }
+            self.skip_space();
+            if matches!(self.peek(), Some('"' | '\'')) {
+                // A quoted metric name, or a quoted label name
+                self.string()?;
+            } else if self.ident(true).is_none() {
+                return Err(self.error("expected a label matcher"));
+            }
+            self.skip_space();
+            let rest: String = self.chars[self.pos..].iter().take(2).collect();
+            let op = ["=~", "!~", "!=", "="]
+                .into_iter()
+                .find(|op| rest.starts_with(op));
+            if let Some(op) = op {
+                self.pos += op.len();
+                self.string()?;
+            }
+            any = true;
+            if self.eat('}') {
+                break;
+            }
+            self.expect(',')?;
+        }
+        if !any {
+            self.pos = open;
+            return Err(self.error("a vector selector needs a metric name or a matcher"));
+        }
+        Ok(())
+    }
+}
+
+/// Returns true for durations like `5m`, `1h30m` or `250ms`.
+fn is_duration(text: &str) -> bool {
+    let mut rest = text;
+    if rest.is_empty() {
+        return false;
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            return false;
+        }
+        rest = &rest[digits..];
+        let unit = ["ms", "s", "m", "h", "d", "w", "y"]
+            .into_iter()
+            .find(|unit| rest.starts_with(unit));
+        match unit {
+            Some(unit) => rest = &rest[unit.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+    use crate::document::tree::YamlTree;
+
+    #[test]
+    fn test_valid_expressions() {
+        for expr in [
+            "up == 0",
+            "rate(http_requests_total[5m])",
+            "sum by (job) (rate(http_requests_total{code=~\"5..\", job!=\"\"}[5m])) / sum by (job) (rate(http_requests_total[5m])) > 0.05",
+            "histogram_quantile(0.99, sum without (instance) (rate(latency_bucket[10m])))",
+            "topk(3, node:cpu:rate5m) offset 1h",
+            "max_over_time(deriv(rate(x[1m])[30m:1m])[1h:])",
+            "a * on (instance) group_left (node) b",
+            "absent(up{job=\"api\"}) or vector(1)",
+            "-1 ^ 2 ^ 3 % 4",
+            "time() - process_start_time_seconds > 1e3",
+            "count(up) by (job) @ end()",
+            "{__name__=~\"job:.*\"}",
+            "# a comment\nup",
+            "1h30m",
+        ] {
+            assert_eq!(check(expr), Ok(()), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        for (expr, error) in [
+            ("rate(x[5m]", "expected ')' at column 11"),
+            ("rate(x[5x])", "expected a duration like 5m at column 8"),
+            ("sum(rate(x[5m])))", "unexpected ')' at column 17"),
+            ("up ==", "unexpected end of expression at column 6"),
+            ("rat(x[5m])", "unknown function 'rat' at column 1"),
+            ("topk(x)", "topk expects 2 arguments, got 1 at column 8"),
+            ("up{job=api}", "expected a string at column 8"),
+            (
+                "{}",
+                "a vector selector needs a metric name or a matcher at column 1",
+            ),
+            ("up{job=\"x}", "unterminated string at column 8"),
+            ("sum by job (x)", "expected '(' at column 8"),
+        ] {
+            assert_eq!(check(expr), Err(error.to_string()), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn test_rules_and_exprs_are_found() {
+        let root = parse_yaml_auto(
+            "\
+groups:
+  - name: api
+    rules:
+      - alert: HighErrorRate
+        expr: rate(errors[5m]) > 1
+      - record: job:errors:rate5m
+        expr: sum by (job) (rate(errors[5m]))
+",
+        )
+        .unwrap();
+        assert_eq!(rule_paths(&root), vec![vec![0, 0, 1, 0], vec![0, 0, 1, 1]]);
+        assert_eq!(
+            expr_paths(&root),
+            vec![vec![0, 0, 1, 0, 1], vec![0, 0, 1, 1, 1]]
+        );
+        let tree = YamlTree::new(root);
+        let rule = tree.get_node(&[0, 0, 1, 1]).unwrap();
+        assert_eq!(rule_name(rule), Some(("record", "job:errors:rate5m")));
+    }
+}
@@ -29,8 +29,13 @@
 //! }
 //! ```
 
-use super::node::{YamlNode, YamlValue};
-use std::collections::HashMap;
+use super::lazy::load_deferred;
+use super::node::{NodeId, YamlNode, YamlValue};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// The YAML merge key used to inherit entries from an anchored mapping.
+pub const MERGE_KEY: &str = "<<";
 
 /// Tracks anchor definitions and alias references within a YAML tree.
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -94,8 +99,9 @@ impl AnchorRegistry {
 #[derive(Debug, Clone, PartialEq)]
 pub struct YamlTree {
     root: YamlNode,
-    /// The original YAML string (preserved for unmodified nodes)
-    original_source: Option<String>,
+    /// The original YAML string (preserved for unmodified nodes), shared
+    /// between the undo snapshots of a document
+    original_source: Option<Arc<str>>,
     /// Tracks anchor definitions and alias references
     anchor_registry: AnchorRegistry,
 }
@@ -131,7 +137,7 @@ impl YamlTree {
     pub fn with_source(root: YamlNode, original_source: Option<String>) -> Self {
         let mut tree = Self {
             root,
-            original_source,
+            original_source: original_source.map(Arc::from),
             anchor_registry: AnchorRegistry::new(),
         };
         tree.build_anchor_registry();
@@ -143,6 +149,16 @@ impl YamlTree {
         self.original_source.as_deref()
     }
 
+    /// Returns the original source as shared with the tree's undo states.
+    pub(crate) fn shared_source(&self) -> Option<Arc<str>> {
+        self.original_source.clone()
+    }
+
+    /// Replaces the original source with one shared with other trees.
+    pub(crate) fn set_shared_source(&mut self, source: Option<Arc<str>>) {
+        self.original_source = source;
+    }
+
     /// Returns a reference to the anchor registry.
     pub fn anchor_registry(&self) -> &AnchorRegistry {
         &self.anchor_registry
@@ -239,6 +255,68 @@ impl YamlTree {
         Some(current)
     }
 
+    /// Returns the current path of the node with the given [`NodeId`].
+    ///
+    /// Unlike a path, an ID keeps naming the same node while edits elsewhere
+    /// shift its index. Returns `None` once the node has been deleted.
+    pub fn path_of(&self, id: NodeId) -> Option<Vec<usize>> {
+        fn find(node: &YamlNode, id: NodeId, path: &mut Vec<usize>) -> bool {
+            if node.id() == id {
+                return true;
+            }
+            let children: Box<dyn Iterator<Item = &YamlNode>> = match node.value() {
+                YamlValue::Object(entries) => Box::new(entries.values()),
+                YamlValue::Array(items) | YamlValue::MultiDoc(items) => Box::new(items.iter()),
+                _ => return false,
+            };
+            for (i, child) in children.enumerate() {
+                path.push(i);
+                if find(child, id, path) {
+                    return true;
+                }
+                path.pop();
+            }
+            false
+        }
+
+        let mut path = Vec::new();
+        find(&self.root, id, &mut path).then_some(path)
+    }
+
+    /// Returns the current paths of the nodes with the given IDs.
+    ///
+    /// Like [`path_of`](Self::path_of) for many nodes in one pass; deleted
+    /// nodes are left out.
+    pub fn paths_of(&self, ids: &HashSet<NodeId>) -> HashMap<NodeId, Vec<usize>> {
+        fn walk(
+            node: &YamlNode,
+            ids: &HashSet<NodeId>,
+            path: &mut Vec<usize>,
+            found: &mut HashMap<NodeId, Vec<usize>>,
+        ) {
+            if found.len() == ids.len() {
+                return;
+            }
+            if ids.contains(&node.id()) {
+                found.insert(node.id(), path.clone());
+            }
+            let children: Box<dyn Iterator<Item = &YamlNode>> = match node.value() {
+                YamlValue::Object(entries) => Box::new(entries.values()),
+                YamlValue::Array(items) | YamlValue::MultiDoc(items) => Box::new(items.iter()),
+                _ => return,
+            };
+            for (i, child) in children.enumerate() {
+                path.push(i);
+                walk(child, ids, path, found);
+                path.pop();
+            }
+        }
+
+        let mut found = HashMap::new();
+        walk(&self.root, ids, &mut Vec::new(), &mut found);
+        found
+    }
+
     /// Gets a mutable reference to a node at the specified path.
     ///
     /// This method follows the same path resolution rules as `get_node`,
@@ -291,6 +369,27 @@ impl YamlTree {
         Some(current)
     }
 
+    /// Parses the deferred section at `path` in place (see
+    /// [`crate::document::lazy`]).
+    ///
+    /// Returns true if a section was loaded, false if the node wasn't
+    /// deferred. Loading isn't an edit: the new nodes are unmodified, so the
+    /// section still saves as its original text.
+    pub fn load_deferred(&mut self, path: &[usize]) -> anyhow::Result<bool> {
+        let Some(node) = self.get_node_mut(path) else {
+            return Ok(false);
+        };
+        let YamlValue::Deferred(source) = node.value() else {
+            return Ok(false);
+        };
+        let loaded = load_deferred(source)?;
+        node.value = loaded.value;
+        node.anchor = node.anchor.take().or(loaded.anchor);
+        node.tag = node.tag.take().or(loaded.tag);
+        self.build_anchor_registry();
+        Ok(true)
+    }
+
     /// Deletes the node at the given path.
     /// Returns an error if the path is empty (cannot delete root) or invalid.
     pub fn delete_node(&mut self, path: &[usize]) -> anyhow::Result<()> {
@@ -463,6 +562,96 @@ impl YamlTree {
         path.chars().filter(|c| *c == '.' || *c == '[').count()
     }
 
+    /// Returns the entries a mapping inherits through YAML merge keys (`<<`).
+    ///
+    /// Supports both `<<: *anchor` and `<<: [*a, *b]`. Keys defined directly on
+    /// the mapping take precedence, and earlier merge sources win over later
+    /// ones, matching the YAML 1.1 merge key semantics. Merge sources are
+    /// resolved recursively, so an anchor that itself merges another anchor
+    /// contributes both sets of keys. The original `<<` entry is left in place;
+    /// this only computes the additional entries for display.
+    ///
+    /// Returns an empty vector for non-mappings or mappings without merge keys.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::document::parser::parse_yaml_auto;
+    /// use yamlquill::document::tree::YamlTree;
+    ///
+    /// let root = parse_yaml_auto("base: &base\n  a: 1\n  b: 2\nchild:\n  <<: *base\n  b: 3\n").unwrap();
+    /// let tree = YamlTree::new(root);
+    /// let child = tree.get_node(&[1]).unwrap();
+    /// let merged = tree.merged_entries(child);
+    /// assert_eq!(merged.len(), 1);
+    /// assert_eq!(merged[0].0, "a");
+    /// ```
+    pub fn merged_entries(&self, node: &YamlNode) -> Vec<(String, YamlNode)> {
+        let mut visited = Vec::new();
+        self.collect_merged_entries(node, &mut visited)
+    }
+
+    fn collect_merged_entries(
+        &self,
+        node: &YamlNode,
+        visited: &mut Vec<String>,
+    ) -> Vec<(String, YamlNode)> {
+        let entries = match node.value() {
+            YamlValue::Object(entries) => entries,
+            _ => return Vec::new(),
+        };
+
+        let sources: Vec<&str> = match entries.get(MERGE_KEY).map(|n| n.value()) {
+            Some(YamlValue::Alias(name)) => vec![name.as_str()],
+            Some(YamlValue::Array(items)) => items
+                .iter()
+                .filter_map(|item| match item.value() {
+                    YamlValue::Alias(name) => Some(name.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            _ => return Vec::new(),
+        };
+
+        let mut merged: Vec<(String, YamlNode)> = Vec::new();
+        for name in sources {
+            // Guard against anchors that (indirectly) merge themselves
+            if visited.iter().any(|v| v == name) {
+                continue;
+            }
+            let Some(source) = self
+                .anchor_registry
+                .get_anchor_path(name)
+                .and_then(|path| self.get_node(path))
+            else {
+                continue;
+            };
+            let YamlValue::Object(source_entries) = source.value() else {
+                continue;
+            };
+
+            visited.push(name.to_string());
+            let inherited = self.collect_merged_entries(source, visited);
+            visited.pop();
+
+            let candidates = source_entries
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .chain(inherited);
+            for (key, value) in candidates {
+                if key == MERGE_KEY || key.starts_with("__comment_") {
+                    continue;
+                }
+                if entries.contains_key(&key) || merged.iter().any(|(k, _)| *k == key) {
+                    continue;
+                }
+                merged.push((key, value));
+            }
+        }
+
+        merged
+    }
+
     /// Builds the anchor registry by walking the tree and registering all anchors and aliases.
     pub fn build_anchor_registry(&mut self) {
         self.anchor_registry = AnchorRegistry::new();
@@ -558,4 +747,17 @@ mod tests {
         assert_eq!(tree.get_depth("config.timeout"), 1);
         assert_eq!(tree.get_depth("users[0].name"), 2);
     }
+
+    #[test]
+    fn test_paths_of() {
+        let mut tree =
+            YamlTree::new(crate::document::parser::parse_yaml_auto("[a, b, c]").unwrap());
+        let b = tree.get_node(&[1]).unwrap().id();
+        let c = tree.get_node(&[2]).unwrap().id();
+        tree.delete_node(&[1]).unwrap();
+
+        let found = tree.paths_of(&HashSet::from([b, c]));
+        assert_eq!(found, HashMap::from([(c, vec![1])]));
+        assert_eq!(tree.path_of(c), Some(vec![1]));
+    }
 }
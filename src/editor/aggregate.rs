@@ -0,0 +1,198 @@
+//! Aggregate functions over numbers in the document (`:sum`, `:count`,
+//! `:min`, `:max`, `:avg`).
+//!
+//! The inputs are nodes (a visual selection or YAMLPath matches). Every
+//! number inside them counts, so selecting a mapping sums all of its numeric
+//! leaves. Other scalars are skipped.
+
+use crate::document::node::{YamlNode, YamlNumber, YamlValue};
+use anyhow::{bail, Result};
+
+/// One of the aggregate commands.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::node::{YamlNode, YamlNumber, YamlValue};
+/// use yamlquill::editor::aggregate::Aggregate;
+///
+/// let nodes: Vec<YamlNode> = [40, 35, 25]
+///     .into_iter()
+///     .map(|n| YamlNode::new(YamlValue::Number(YamlNumber::Integer(n))))
+///     .collect();
+/// let numbers = Aggregate::numbers(nodes.iter());
+/// assert_eq!(Aggregate::Sum.apply(&numbers).unwrap(), YamlNumber::Integer(100));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Count,
+    Min,
+    Max,
+    Avg,
+}
+
+impl Aggregate {
+    /// Returns the aggregate named by a command (`sum`, `count`, `min`,
+    /// `max` or `avg`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sum" => Some(Self::Sum),
+            "count" => Some(Self::Count),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "avg" => Some(Self::Avg),
+            _ => None,
+        }
+    }
+
+    /// Returns the command name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sum => "sum",
+            Self::Count => "count",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Avg => "avg",
+        }
+    }
+
+    /// Collects every number in `nodes` and their descendants, in document
+    /// order.
+    pub fn numbers<'a>(nodes: impl IntoIterator<Item = &'a YamlNode>) -> Vec<YamlNumber> {
+        fn walk(node: &YamlNode, out: &mut Vec<YamlNumber>) {
+            match node.value() {
+                YamlValue::Number(n) => out.push(n.clone()),
+                YamlValue::Object(fields) => fields.values().for_each(|v| walk(v, out)),
+                YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+                    items.iter().for_each(|v| walk(v, out))
+                }
+                _ => {}
+            }
+        }
+        let mut out = Vec::new();
+        for node in nodes {
+            walk(node, &mut out);
+        }
+        out
+    }
+
+    /// Computes the aggregate. Sums of integers stay integers unless they
+    /// overflow; averages are always floats.
+    pub fn apply(self, numbers: &[YamlNumber]) -> Result<YamlNumber> {
+        if self == Self::Count {
+            return Ok(YamlNumber::Integer(numbers.len() as i64));
+        }
+        if numbers.is_empty() {
+            bail!("No numbers to {}", self.name());
+        }
+        let as_f64 = |n: &YamlNumber| match n {
+            YamlNumber::Integer(i) => *i as f64,
+            YamlNumber::Float(f) => *f,
+        };
+        Ok(match self {
+            Self::Sum => sum(numbers),
+            Self::Avg => {
+                YamlNumber::Float(numbers.iter().map(as_f64).sum::<f64>() / numbers.len() as f64)
+            }
+            Self::Min => numbers
+                .iter()
+                .min_by(|a, b| as_f64(a).total_cmp(&as_f64(b)))
+                .cloned()
+                .expect("not empty"),
+            Self::Max => numbers
+                .iter()
+                .max_by(|a, b| as_f64(a).total_cmp(&as_f64(b)))
+                .cloned()
+                .expect("not empty"),
+            Self::Count => unreachable!(),
+        })
+    }
+}
+
+fn sum(numbers: &[YamlNumber]) -> YamlNumber {
+    let integers: Option<i64> = numbers.iter().try_fold(0i64, |total, n| match n {
+        YamlNumber::Integer(i) => total.checked_add(*i),
+        YamlNumber::Float(_) => None,
+    });
+    match integers {
+        Some(total) => YamlNumber::Integer(total),
+        None => YamlNumber::Float(
+            numbers
+                .iter()
+                .map(|n| match n {
+                    YamlNumber::Integer(i) => *i as f64,
+                    YamlNumber::Float(f) => *f,
+                })
+                .sum(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    fn numbers(yaml: &str) -> Vec<YamlNumber> {
+        Aggregate::numbers([&parse_yaml_auto(yaml).unwrap()])
+    }
+
+    #[test]
+    fn test_numbers_descend_and_skip_other_scalars() {
+        let found = numbers("a: 1\nb: [2, x, 3.5]\nc: {d: 4, e: true}\n");
+        assert_eq!(
+            found,
+            vec![
+                YamlNumber::Integer(1),
+                YamlNumber::Integer(2),
+                YamlNumber::Float(3.5),
+                YamlNumber::Integer(4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let found = numbers("[3, 1.5, -2]");
+        assert_eq!(
+            Aggregate::Sum.apply(&found).unwrap(),
+            YamlNumber::Float(2.5)
+        );
+        assert_eq!(
+            Aggregate::Count.apply(&found).unwrap(),
+            YamlNumber::Integer(3)
+        );
+        assert_eq!(
+            Aggregate::Min.apply(&found).unwrap(),
+            YamlNumber::Integer(-2)
+        );
+        assert_eq!(
+            Aggregate::Max.apply(&found).unwrap(),
+            YamlNumber::Integer(3)
+        );
+        let avg = Aggregate::Avg.apply(&found).unwrap();
+        assert_eq!(avg, YamlNumber::Float(2.5 / 3.0));
+    }
+
+    #[test]
+    fn test_integer_sum_stays_integer_until_overflow() {
+        let found = numbers("[1, 2]");
+        assert_eq!(
+            Aggregate::Sum.apply(&found).unwrap(),
+            YamlNumber::Integer(3)
+        );
+        let big = vec![YamlNumber::Integer(i64::MAX), YamlNumber::Integer(1)];
+        assert!(matches!(
+            Aggregate::Sum.apply(&big).unwrap(),
+            YamlNumber::Float(_)
+        ));
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert_eq!(Aggregate::Count.apply(&[]).unwrap(), YamlNumber::Integer(0));
+        let err = Aggregate::Max.apply(&[]).unwrap_err();
+        assert_eq!(err.to_string(), "No numbers to max");
+    }
+}
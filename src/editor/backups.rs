@@ -0,0 +1,118 @@
+//! Backup browser (`:backups`).
+//!
+//! Lists the backups of the current file with when each was written and how
+//! it differs from the buffer. Restoring one replaces the buffer's document
+//! with it as a single undoable change; nothing is written until the buffer
+//! is saved.
+//!
+//! Backups are the `file.yaml.bak` copies made before each save when
+//! `create_backup` is on.
+
+use super::review::{diff, Change};
+use crate::document::node::YamlNode;
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+
+/// A backup as the `:backups` browser shows it.
+#[derive(Debug, Clone)]
+pub struct BackupEntry {
+    /// Where the backup is
+    pub path: PathBuf,
+    /// When it was written, in Unix seconds
+    pub modified: i64,
+    /// Size in bytes
+    pub size: u64,
+    /// The backup's document
+    pub root: YamlNode,
+    /// How the buffer differs from the backup
+    pub changes: Vec<Change>,
+}
+
+/// Returns the backup files of `path` that exist.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::backups::find_backups;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let file = dir.path().join("app.yaml");
+/// assert!(find_backups(&file).is_empty());
+///
+/// std::fs::write(dir.path().join("app.yaml.bak"), "a: 1\n").unwrap();
+/// assert_eq!(find_backups(&file), vec![dir.path().join("app.yaml.bak")]);
+/// ```
+pub fn find_backups(path: &Path) -> Vec<PathBuf> {
+    let Some(name) = path.file_name() else {
+        return Vec::new();
+    };
+    let mut backup = path.to_path_buf();
+    backup.set_file_name(format!("{}.bak", name.to_string_lossy()));
+    if backup.is_file() {
+        vec![backup]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Loads a backup and diffs the buffer's document against it.
+pub fn load_backup(path: &Path, current: &YamlNode) -> Result<BackupEntry> {
+    let metadata =
+        std::fs::metadata(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let tree = crate::file::loader::load_yaml_file(path)
+        .map_err(|e| anyhow!("Failed to load {}: {}", path.display(), e))?;
+    let root = tree.root().clone();
+    let changes = diff(&root, current);
+    Ok(BackupEntry {
+        path: path.to_path_buf(),
+        modified,
+        size: metadata.len(),
+        root,
+        changes,
+    })
+}
+
+/// Selection state for the `:backups` browser.
+#[derive(Debug, Clone)]
+pub struct BackupPickerState {
+    /// Backups of the current file
+    pub backups: Vec<BackupEntry>,
+    /// Index of the highlighted backup
+    pub selected_index: usize,
+}
+
+impl BackupPickerState {
+    /// Creates a browser over `backups` with the first one selected.
+    pub fn new(backups: Vec<BackupEntry>) -> Self {
+        Self {
+            backups,
+            selected_index: 0,
+        }
+    }
+
+    /// Moves the selection up, wrapping to the bottom.
+    pub fn previous(&mut self) {
+        if !self.backups.is_empty() {
+            self.selected_index =
+                (self.selected_index + self.backups.len() - 1) % self.backups.len();
+        }
+    }
+
+    /// Moves the selection down, wrapping to the top.
+    pub fn next(&mut self) {
+        if !self.backups.is_empty() {
+            self.selected_index = (self.selected_index + 1) % self.backups.len();
+        }
+    }
+
+    /// Returns the highlighted backup.
+    pub fn selected(&self) -> Option<&BackupEntry> {
+        self.backups.get(self.selected_index)
+    }
+}
@@ -0,0 +1,170 @@
+//! Base64 encoding of string values (`:b64 encode`, `:b64 decode`, `gb`),
+//! and finding the values of a Kubernetes Secret's `data` that the tree
+//! shows decoded under `:set decodesecrets`.
+//!
+//! Values use the standard alphabet with `=` padding, as Secrets do.
+//! Decoding also accepts missing padding and ignores whitespace, so a value
+//! wrapped over several lines decodes too.
+
+use crate::document::node::{YamlNode, YamlValue};
+use anyhow::{bail, Result};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `bytes` as padded base64.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::base64::encode;
+///
+/// assert_eq!(encode(b"admin"), "YWRtaW4=");
+/// assert_eq!(encode(b""), "");
+/// ```
+pub fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes base64 text, ignoring whitespace and accepting missing padding.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::base64::decode;
+///
+/// assert_eq!(decode("YWRtaW4=").unwrap(), b"admin");
+/// assert_eq!(decode("YWRt\naW4").unwrap(), b"admin");
+/// assert!(decode("not base64!").is_err());
+/// ```
+pub fn decode(text: &str) -> Result<Vec<u8>> {
+    let digits: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    let data = digits
+        .strip_suffix(b"==")
+        .or_else(|| digits.strip_suffix(b"="));
+    let padded = data.is_some();
+    let data = data.unwrap_or(&digits);
+    if padded && !digits.len().is_multiple_of(4) {
+        bail!("wrong padding");
+    }
+    if data.len() % 4 == 1 {
+        bail!("wrong length");
+    }
+
+    let mut out = Vec::with_capacity(data.len() / 4 * 3 + 2);
+    for chunk in data.chunks(4) {
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let Some(value) = ALPHABET.iter().position(|&a| a == c) else {
+                bail!("'{}' is not a base64 character", c as char);
+            };
+            n |= (value as u32) << (18 - 6 * i);
+        }
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        out.extend_from_slice(&bytes[..chunk.len() - 1]);
+    }
+    Ok(out)
+}
+
+/// Decodes base64 text that holds UTF-8 text, as most Secret values do.
+///
+/// Returns `None` if the text isn't base64 or decodes to binary data.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::base64::decode_text;
+///
+/// assert_eq!(decode_text("cGFzc3dvcmQ=").as_deref(), Some("password"));
+/// assert_eq!(decode_text("password"), None);
+/// assert_eq!(decode_text("/w=="), None);
+/// ```
+pub fn decode_text(text: &str) -> Option<String> {
+    let bytes = decode(text).ok()?;
+    let text = String::from_utf8(bytes).ok()?;
+    let printable = text
+        .chars()
+        .all(|c| !c.is_control() || c == '\n' || c == '\t' || c == '\r');
+    printable.then_some(text)
+}
+
+/// Returns true if `path` is the `data` mapping of a Kubernetes Secret
+/// (a mapping with `kind: Secret`), whose values are base64.
+pub fn is_secret_data(root: &YamlNode, path: &[usize]) -> bool {
+    let Some((&index, parent_path)) = path.split_last() else {
+        return false;
+    };
+    let mut parent = root;
+    for &i in parent_path {
+        let next = match parent.value() {
+            YamlValue::Object(fields) => fields.get_index(i).map(|(_, v)| v),
+            YamlValue::Array(items) | YamlValue::MultiDoc(items) => items.get(i),
+            _ => None,
+        };
+        match next {
+            Some(node) => parent = node,
+            None => return false,
+        }
+    }
+    let YamlValue::Object(fields) = parent.value() else {
+        return false;
+    };
+    let is_secret = matches!(
+        fields.get("kind").map(|n| n.value()),
+        Some(YamlValue::String(kind)) if kind.as_str() == "Secret"
+    );
+    is_secret
+        && fields
+            .get_index(index)
+            .is_some_and(|(key, value)| key == "data" && value.value().is_object())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    #[test]
+    fn test_round_trip() {
+        for text in ["", "a", "ab", "abc", "abcd", "héllo wörld\n"] {
+            let encoded = encode(text.as_bytes());
+            assert_eq!(encoded.len() % 4, 0);
+            assert_eq!(decode(&encoded).unwrap(), text.as_bytes());
+        }
+        assert_eq!(encode(&[0xff, 0xfe]), "//4=");
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_input() {
+        assert!(decode("YWRtaW4==").is_err());
+        assert!(decode("YWRta").is_err());
+        assert!(decode("YW=taW4=").is_err());
+    }
+
+    #[test]
+    fn test_is_secret_data() {
+        let root = parse_yaml_auto(
+            "kind: Secret\ndata:\n  password: cGFzc3dvcmQ=\n---\nkind: ConfigMap\ndata:\n  a: b\n",
+        )
+        .unwrap();
+        assert!(is_secret_data(&root, &[0, 1]));
+        assert!(!is_secret_data(&root, &[0, 0]));
+        assert!(!is_secret_data(&root, &[1, 1]));
+        assert!(!is_secret_data(&root, &[]));
+    }
+}
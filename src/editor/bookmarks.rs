@@ -0,0 +1,348 @@
+//! Named bookmarks that persist per project.
+//!
+//! Marks (`ma`, `'a`) are limited to 26 letters and vanish with the session.
+//! Bookmarks have names (`:bookmark add prod-db-url`), are written to disk as
+//! soon as they change, and are shared by every file in the same project, so
+//! they survive restarts and can point into other buffers.
+//!
+//! A project is the nearest ancestor directory holding a `.yamlquill.toml`
+//! or a `.git` directory, falling back to the file's own directory. Each
+//! project gets one file under `~/.local/state/yamlquill/bookmarks/`, named
+//! after the project path with `/` replaced by `%` (like Vim's `undodir`).
+//!
+//! Locations are stored as bracket-notation YAMLPaths
+//! (`$["spec"]["containers"][0]`) rather than tree indices, so a bookmark
+//! still finds its node after keys are added above it. A path that stops
+//! resolving after bigger changes is re-resolved by its trailing keys (see
+//! [`resolve`]).
+
+use crate::document::node::YamlNode;
+use crate::yamlpath::ast::PathSegment;
+use crate::yamlpath::{Evaluator, Parser};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A named location in a file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Name given with `:bookmark add`
+    pub name: String,
+    /// File path relative to the project root
+    pub file: String,
+    /// Bracket-notation YAMLPath of the node
+    pub path: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BookmarkFile {
+    #[serde(default, rename = "bookmark")]
+    bookmarks: Vec<Bookmark>,
+}
+
+/// The bookmarks of one project.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::bookmarks::{Bookmark, BookmarkStore};
+/// use std::path::Path;
+///
+/// let mut store = BookmarkStore::in_memory(Path::new("/srv/app"));
+/// store.add(Bookmark {
+///     name: "db".to_string(),
+///     file: "values.yaml".to_string(),
+///     path: "$[\"database\"][\"url\"]".to_string(),
+/// });
+///
+/// assert_eq!(store.get("db").unwrap().file, "values.yaml");
+/// assert_eq!(store.file_path(store.get("db").unwrap()), Path::new("/srv/app/values.yaml"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BookmarkStore {
+    /// Project root that bookmark file paths are relative to
+    root: PathBuf,
+    bookmarks: Vec<Bookmark>,
+    /// Where the store is persisted; `None` keeps it in memory only
+    file: Option<PathBuf>,
+}
+
+impl BookmarkStore {
+    /// Creates an empty store for `root` that is never written to disk.
+    pub fn in_memory(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            bookmarks: Vec::new(),
+            file: None,
+        }
+    }
+
+    /// Loads the bookmarks of the project at `root` from `store_dir`.
+    ///
+    /// A missing or unreadable bookmark file gives an empty store.
+    pub fn for_project(root: &Path, store_dir: &Path) -> Self {
+        Self::load_from(root, store_dir.join(store_file_name(root)))
+    }
+
+    /// Loads the bookmarks for `root` from `path`, which is also where they are saved.
+    pub fn load_from(root: &Path, path: PathBuf) -> Self {
+        let mut bookmarks = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| toml::from_str::<BookmarkFile>(&contents).ok())
+            .map(|file| file.bookmarks)
+            .unwrap_or_default();
+        bookmarks.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            root: root.to_path_buf(),
+            bookmarks,
+            file: Some(path),
+        }
+    }
+
+    /// Returns the directory holding every project's bookmark file.
+    ///
+    /// Uses `~/.local/state/yamlquill/bookmarks/` on all platforms.
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::home_dir().map(|mut path| {
+            path.push(".local");
+            path.push("state");
+            path.push("yamlquill");
+            path.push("bookmarks");
+            path
+        })
+    }
+
+    /// Returns the project root.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns all bookmarks, sorted by name.
+    pub fn list(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    /// Returns the bookmark called `name`.
+    pub fn get(&self, name: &str) -> Option<&Bookmark> {
+        self.bookmarks.iter().find(|b| b.name == name)
+    }
+
+    /// Adds a bookmark, replacing any existing one with the same name.
+    pub fn add(&mut self, bookmark: Bookmark) {
+        self.bookmarks.retain(|b| b.name != bookmark.name);
+        self.bookmarks.push(bookmark);
+        self.bookmarks.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+
+    /// Removes the bookmark called `name`. Returns false if there was none.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.bookmarks.len();
+        self.bookmarks.retain(|b| b.name != name);
+        self.bookmarks.len() != before
+    }
+
+    /// Returns the absolute path of the file a bookmark points into.
+    pub fn file_path(&self, bookmark: &Bookmark) -> PathBuf {
+        self.root.join(&bookmark.file)
+    }
+
+    /// Returns `file` relative to the project root, if it is inside the project.
+    pub fn relative_file(&self, file: &Path) -> Option<String> {
+        file.strip_prefix(&self.root)
+            .ok()
+            .map(|p| p.to_string_lossy().to_string())
+    }
+
+    /// Writes the store to disk (no-op for in-memory stores).
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.file else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create bookmark directory: {}", parent.display())
+            })?;
+        }
+        let file = BookmarkFile {
+            bookmarks: self.bookmarks.clone(),
+        };
+        let contents = toml::to_string_pretty(&file).context("Failed to serialize bookmarks")?;
+        fs::write(path, contents)
+            .with_context(|| format!("Failed to write bookmarks: {}", path.display()))
+    }
+}
+
+/// Finds the node a bookmark's `path` points at under `root`.
+///
+/// When the exact path no longer resolves, because a parent key was renamed
+/// or the node moved under another one, it is re-resolved as a recursive
+/// query on its trailing keys: `$["db"]["primary"]["url"]` is retried as
+/// `$..["primary"]["url"]`, then `$..["url"]`. The longest such query that
+/// matches exactly one node wins.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::editor::bookmarks::resolve;
+///
+/// let root = parse_yaml_auto("database:\n  primary:\n    url: x\n").unwrap();
+/// assert_eq!(resolve(&root, "$[\"db\"][\"primary\"][\"url\"]").unwrap(), vec![0, 0, 0]);
+/// ```
+pub fn resolve(root: &YamlNode, path: &str) -> Result<Vec<usize>> {
+    let query = Parser::parse(path).map_err(|e| anyhow::anyhow!("{}", e))?;
+    let evaluator = Evaluator::new(root);
+    if let Some(found) = evaluator.evaluate_paths(&query.segments).into_iter().next() {
+        return Ok(found);
+    }
+
+    let steps = query
+        .segments
+        .iter()
+        .skip_while(|segment| **segment == PathSegment::Root)
+        .collect::<Vec<_>>();
+    for start in 1..steps.len() {
+        let PathSegment::Child(key) = steps[start] else {
+            continue;
+        };
+        let mut segments = vec![
+            PathSegment::Root,
+            PathSegment::RecursiveDescent(Some(key.clone())),
+        ];
+        segments.extend(steps[start + 1..].iter().map(|&s| s.clone()));
+        let matches = evaluator.evaluate_paths(&segments);
+        if let [found] = matches.as_slice() {
+            return Ok(found.clone());
+        }
+    }
+    bail!("Not found: {}", path)
+}
+
+/// Returns the name of the bookmark file for the project at `root`.
+fn store_file_name(root: &Path) -> String {
+    format!("{}.toml", root.to_string_lossy().replace(['/', '\\'], "%"))
+}
+
+/// Returns the project root for a file in `file_dir`.
+///
+/// The nearest ancestor with a `.yamlquill.toml` or `.git` wins; otherwise
+/// `file_dir` itself.
+pub fn project_root(file_dir: &Path) -> PathBuf {
+    file_dir
+        .ancestors()
+        .find(|dir| dir.join(".yamlquill.toml").is_file() || dir.join(".git").exists())
+        .unwrap_or(file_dir)
+        .to_path_buf()
+}
+
+/// Selection state for the `:bookmarks` picker.
+#[derive(Debug, Clone)]
+pub struct BookmarkPickerState {
+    /// Bookmarks of the current project, sorted by name
+    pub bookmarks: Vec<Bookmark>,
+    /// Index of the highlighted bookmark
+    pub selected_index: usize,
+}
+
+impl BookmarkPickerState {
+    /// Creates a picker over `bookmarks` with the first one selected.
+    pub fn new(bookmarks: Vec<Bookmark>) -> Self {
+        Self {
+            bookmarks,
+            selected_index: 0,
+        }
+    }
+
+    /// Moves the selection up, wrapping to the bottom.
+    pub fn previous(&mut self) {
+        if !self.bookmarks.is_empty() {
+            self.selected_index =
+                (self.selected_index + self.bookmarks.len() - 1) % self.bookmarks.len();
+        }
+    }
+
+    /// Moves the selection down, wrapping to the top.
+    pub fn next(&mut self) {
+        if !self.bookmarks.is_empty() {
+            self.selected_index = (self.selected_index + 1) % self.bookmarks.len();
+        }
+    }
+
+    /// Returns the highlighted bookmark.
+    pub fn selected(&self) -> Option<&Bookmark> {
+        self.bookmarks.get(self.selected_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn bookmark(name: &str) -> Bookmark {
+        Bookmark {
+            name: name.to_string(),
+            file: "values.yaml".to_string(),
+            path: "$[\"a\"]".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_add_replaces_and_sorts() {
+        let mut store = BookmarkStore::in_memory(Path::new("/p"));
+        store.add(bookmark("zeta"));
+        store.add(bookmark("alpha"));
+        store.add(Bookmark {
+            path: "$[\"b\"]".to_string(),
+            ..bookmark("zeta")
+        });
+
+        let names: Vec<_> = store.list().iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, ["alpha", "zeta"]);
+        assert_eq!(store.get("zeta").unwrap().path, "$[\"b\"]");
+        assert!(store.remove("alpha"));
+        assert!(!store.remove("alpha"));
+    }
+
+    #[test]
+    fn test_save_and_reload() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("state").join("p.toml");
+
+        let mut store = BookmarkStore::load_from(Path::new("/p"), path.clone());
+        store.add(bookmark("db"));
+        store.save().unwrap();
+
+        let reloaded = BookmarkStore::load_from(Path::new("/p"), path);
+        assert_eq!(reloaded.list(), [bookmark("db")]);
+    }
+
+    #[test]
+    fn test_project_root() {
+        let dir = TempDir::new().unwrap();
+        let nested = dir.path().join("charts").join("web");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(project_root(&nested), nested);
+
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        assert_eq!(project_root(&nested), dir.path());
+    }
+
+    #[test]
+    fn test_store_file_is_per_project() {
+        let a = store_file_name(Path::new("/srv/a"));
+        let b = store_file_name(Path::new("/srv/b"));
+        assert_ne!(a, b);
+        assert_eq!(a, "%srv%a.toml");
+    }
+
+    #[test]
+    fn test_picker_wraps() {
+        let mut picker = BookmarkPickerState::new(vec![bookmark("a"), bookmark("b")]);
+        picker.previous();
+        assert_eq!(picker.selected().unwrap().name, "b");
+        picker.next();
+        assert_eq!(picker.selected().unwrap().name, "a");
+    }
+}
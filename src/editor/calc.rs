@@ -0,0 +1,279 @@
+//! Arithmetic on numeric edits.
+//!
+//! When a number is being edited, text starting with an operator is worked
+//! out against the current value: `*2` doubles it and `+10` adds ten. Text
+//! starting with `=` is a standalone expression such as `=1024*1024`.
+//! Anything else is left for the usual number parsing, so `-5` is still a
+//! negative number rather than a subtraction (`+-5` subtracts).
+//!
+//! Expressions support `+ - * / %`, parentheses, unary minus, and `_`
+//! digit separators. Integers stay integers unless a float is involved or
+//! a division isn't exact.
+
+use crate::document::node::YamlNumber;
+use anyhow::{anyhow, Result};
+
+/// Evaluates `input` as an expression relative to `current`.
+///
+/// Returns `Ok(None)` when the input isn't an expression.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::node::YamlNumber;
+/// use yamlquill::editor::calc::evaluate;
+///
+/// let three = YamlNumber::Integer(3);
+/// assert_eq!(evaluate("*2", &three).unwrap(), Some(YamlNumber::Integer(6)));
+/// assert_eq!(evaluate("/2", &three).unwrap(), Some(YamlNumber::Float(1.5)));
+/// assert_eq!(evaluate("=1024*1024", &three).unwrap(), Some(YamlNumber::Integer(1048576)));
+/// assert_eq!(evaluate("-5", &three).unwrap(), None);
+/// assert!(evaluate("*x", &three).is_err());
+/// ```
+pub fn evaluate(input: &str, current: &YamlNumber) -> Result<Option<YamlNumber>> {
+    let trimmed = input.trim();
+    let (mut tokens, source) = if let Some(rest) = trimmed.strip_prefix('=') {
+        (Vec::new(), rest)
+    } else if trimmed.starts_with(['+', '*', '/', '%']) {
+        (vec![Token::Number(current.clone())], trimmed)
+    } else {
+        return Ok(None);
+    };
+    let fail = |reason: String| anyhow!("Invalid expression '{}': {}", trimmed, reason);
+
+    tokens.extend(tokenize(source).map_err(fail)?);
+    let mut parser = Parser { tokens, pos: 0 };
+    let value = parser.expression().map_err(fail)?;
+    if let Some(token) = parser.peek() {
+        return Err(fail(format!("unexpected '{}'", token)));
+    }
+    if let YamlNumber::Float(f) = value {
+        if !f.is_finite() {
+            return Err(fail("the result is not a finite number".to_string()));
+        }
+    }
+    Ok(Some(value))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(YamlNumber),
+    Op(char),
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Op(c) => write!(f, "{}", c),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> std::result::Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' | '\t' => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' | '%' => {
+                tokens.push(Token::Op(c));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                chars.next();
+            }
+            '0'..='9' | '.' => {
+                let mut text = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_digit() || c == '.' || c == '_' {
+                        text.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let digits = text.replace('_', "");
+                let number = if let Ok(i) = digits.parse::<i64>() {
+                    YamlNumber::Integer(i)
+                } else if let Ok(f) = digits.parse::<f64>() {
+                    YamlNumber::Float(f)
+                } else {
+                    return Err(format!("'{}' is not a number", text));
+                };
+                tokens.push(Token::Number(number));
+            }
+            _ => return Err(format!("unexpected '{}'", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next_op(&mut self, ops: &[char]) -> Option<char> {
+        match self.peek() {
+            Some(Token::Op(c)) if ops.contains(c) => {
+                let c = *c;
+                self.pos += 1;
+                Some(c)
+            }
+            _ => None,
+        }
+    }
+
+    fn expression(&mut self) -> std::result::Result<YamlNumber, String> {
+        let mut value = self.term()?;
+        while let Some(op) = self.next_op(&['+', '-']) {
+            let rhs = self.term()?;
+            value = apply(op, value, rhs)?;
+        }
+        Ok(value)
+    }
+
+    fn term(&mut self) -> std::result::Result<YamlNumber, String> {
+        let mut value = self.unary()?;
+        while let Some(op) = self.next_op(&['*', '/', '%']) {
+            let rhs = self.unary()?;
+            value = apply(op, value, rhs)?;
+        }
+        Ok(value)
+    }
+
+    fn unary(&mut self) -> std::result::Result<YamlNumber, String> {
+        match self.next_op(&['+', '-']) {
+            Some('-') => {
+                let value = self.unary()?;
+                apply('-', YamlNumber::Integer(0), value)
+            }
+            Some(_) => self.unary(),
+            None => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> std::result::Result<YamlNumber, String> {
+        let token = self.peek().cloned();
+        self.pos += 1;
+        match token {
+            Some(Token::Number(n)) => Ok(n),
+            Some(Token::Open) => {
+                let value = self.expression()?;
+                match self.peek() {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(value)
+                    }
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(token) => Err(format!("expected a number before '{}'", token)),
+            None => Err("expected a number at the end".to_string()),
+        }
+    }
+}
+
+fn apply(op: char, lhs: YamlNumber, rhs: YamlNumber) -> std::result::Result<YamlNumber, String> {
+    if matches!(op, '/' | '%') && rhs.as_f64() == 0.0 {
+        return Err("division by zero".to_string());
+    }
+    if let (YamlNumber::Integer(a), YamlNumber::Integer(b)) = (&lhs, &rhs) {
+        let (a, b) = (*a, *b);
+        let result = match op {
+            '+' => a.checked_add(b),
+            '-' => a.checked_sub(b),
+            '*' => a.checked_mul(b),
+            '/' if a.checked_rem(b).is_some_and(|r| r != 0) => {
+                return Ok(YamlNumber::Float(a as f64 / b as f64))
+            }
+            '/' => a.checked_div(b),
+            _ => a.checked_rem(b),
+        };
+        return result
+            .map(YamlNumber::Integer)
+            .ok_or_else(|| "the result doesn't fit in a 64-bit integer".to_string());
+    }
+    let (a, b) = (lhs.as_f64(), rhs.as_f64());
+    Ok(YamlNumber::Float(match op {
+        '+' => a + b,
+        '-' => a - b,
+        '*' => a * b,
+        '/' => a / b,
+        _ => a % b,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(input: &str, current: i64) -> YamlNumber {
+        evaluate(input, &YamlNumber::Integer(current))
+            .unwrap()
+            .unwrap()
+    }
+
+    fn error(input: &str) -> String {
+        evaluate(input, &YamlNumber::Integer(3))
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn test_relative_and_absolute() {
+        assert_eq!(eval("+10", 3), YamlNumber::Integer(13));
+        assert_eq!(eval("+-5", 3), YamlNumber::Integer(-2));
+        assert_eq!(eval("*2+1", 3), YamlNumber::Integer(7));
+        assert_eq!(eval("% 2", 3), YamlNumber::Integer(1));
+        assert_eq!(eval("=(1 + 2) * -4", 3), YamlNumber::Integer(-12));
+        assert_eq!(eval("=1_000 / 8", 3), YamlNumber::Integer(125));
+        assert_eq!(eval("*1.5", 2), YamlNumber::Float(3.0));
+        assert_eq!(
+            evaluate("*2", &YamlNumber::Float(0.25)).unwrap(),
+            Some(YamlNumber::Float(0.5))
+        );
+        assert_eq!(evaluate("42", &YamlNumber::Integer(3)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            error("*"),
+            "Invalid expression '*': expected a number at the end"
+        );
+        assert_eq!(error("=2*x"), "Invalid expression '=2*x': unexpected 'x'");
+        assert_eq!(error("=(1+2"), "Invalid expression '=(1+2': missing ')'");
+        assert_eq!(error("=2 3"), "Invalid expression '=2 3': unexpected '3'");
+        assert_eq!(
+            error("=1.2.3"),
+            "Invalid expression '=1.2.3': '1.2.3' is not a number"
+        );
+        assert_eq!(error("/0"), "Invalid expression '/0': division by zero");
+        assert_eq!(
+            error("*9223372036854775807"),
+            "Invalid expression '*9223372036854775807': the result doesn't fit in a 64-bit integer"
+        );
+        assert_eq!(
+            error("=*2"),
+            "Invalid expression '=*2': expected a number before '*'"
+        );
+    }
+}
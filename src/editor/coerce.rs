@@ -0,0 +1,197 @@
+//! Changing the type of a scalar (`gt` followed by a type key, `:type`).
+//!
+//! Values read from a file keep the type YAML gave them, so a port written
+//! as `"80"` stays a string. Coercion reinterprets the scalar's text as
+//! another type, and refuses when the text doesn't fit: `"abc"` can't
+//! become an int.
+
+use crate::document::node::{YamlNumber, YamlString, YamlValue};
+use anyhow::{bail, Result};
+
+/// A scalar type a value can be coerced to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarType {
+    Str,
+    Int,
+    Float,
+    Bool,
+    Null,
+}
+
+impl ScalarType {
+    /// Every scalar type, in the order the prompt lists them.
+    pub const ALL: [ScalarType; 5] = [Self::Str, Self::Int, Self::Float, Self::Bool, Self::Null];
+
+    /// Returns the short name shown in type badges and messages.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Str => "str",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::Bool => "bool",
+            Self::Null => "null",
+        }
+    }
+
+    /// Returns the key that picks the type after `gt`.
+    pub fn key(self) -> char {
+        self.name().chars().next().unwrap_or('s')
+    }
+
+    /// Parses a type name as typed in `:type`, or its one-letter key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::editor::coerce::ScalarType;
+    ///
+    /// assert_eq!(ScalarType::parse("integer"), Some(ScalarType::Int));
+    /// assert_eq!(ScalarType::parse("b"), Some(ScalarType::Bool));
+    /// assert_eq!(ScalarType::parse("map"), None);
+    /// ```
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "s" | "str" | "string" => Some(Self::Str),
+            "i" | "int" | "integer" => Some(Self::Int),
+            "f" | "float" => Some(Self::Float),
+            "b" | "bool" | "boolean" => Some(Self::Bool),
+            "n" | "null" => Some(Self::Null),
+            _ => None,
+        }
+    }
+
+    /// Returns the type of a scalar value, or `None` for collections,
+    /// aliases and comments.
+    pub fn of(value: &YamlValue) -> Option<Self> {
+        match value {
+            YamlValue::String(_) => Some(Self::Str),
+            YamlValue::Number(YamlNumber::Integer(_)) => Some(Self::Int),
+            YamlValue::Number(YamlNumber::Float(_)) => Some(Self::Float),
+            YamlValue::Boolean(_) => Some(Self::Bool),
+            YamlValue::Null => Some(Self::Null),
+            _ => None,
+        }
+    }
+}
+
+/// Reinterprets a scalar as `target`.
+///
+/// Strings are parsed (`"80"` → `80`, `"yes"` → `true`); other scalars are
+/// converted through their text. Null only accepts empty text, `null` and
+/// `~`, so no value is dropped by accident.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::node::{YamlNumber, YamlString, YamlValue};
+/// use yamlquill::editor::coerce::{coerce, ScalarType};
+///
+/// let port = YamlValue::String(YamlString::Plain("80".to_string()));
+/// assert_eq!(
+///     coerce(&port, ScalarType::Int).unwrap(),
+///     YamlValue::Number(YamlNumber::Integer(80))
+/// );
+/// assert!(coerce(&port, ScalarType::Bool).is_err());
+/// ```
+pub fn coerce(value: &YamlValue, target: ScalarType) -> Result<YamlValue> {
+    let text = match value {
+        YamlValue::String(s) => s.as_str().to_string(),
+        YamlValue::Number(n) => n.to_string(),
+        YamlValue::Boolean(b) => b.to_string(),
+        YamlValue::Null => String::new(),
+        _ => bail!("Only scalar values can change type"),
+    };
+    let trimmed = text.trim();
+    let invalid = || anyhow::anyhow!("Can't convert '{}' to {}", text, target.name());
+    Ok(match target {
+        ScalarType::Str => match value {
+            YamlValue::String(s) => YamlValue::String(s.clone()),
+            _ => YamlValue::String(YamlString::Plain(text.clone())),
+        },
+        ScalarType::Int => match value {
+            YamlValue::Boolean(b) => YamlValue::Number(YamlNumber::Integer(*b as i64)),
+            _ => {
+                let int = trimmed
+                    .parse::<i64>()
+                    .ok()
+                    .or_else(|| {
+                        // 80.0 is a whole number; 80.5 isn't
+                        let float = trimmed.parse::<f64>().ok()?;
+                        (float.fract() == 0.0 && float.abs() < i64::MAX as f64)
+                            .then_some(float as i64)
+                    })
+                    .ok_or_else(invalid)?;
+                YamlValue::Number(YamlNumber::Integer(int))
+            }
+        },
+        ScalarType::Float => {
+            let float = trimmed.parse::<f64>().map_err(|_| invalid())?;
+            YamlValue::Number(YamlNumber::Float(float))
+        }
+        ScalarType::Bool => match trimmed.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => YamlValue::Boolean(true),
+            "false" | "no" | "off" | "0" => YamlValue::Boolean(false),
+            _ => return Err(invalid()),
+        },
+        ScalarType::Null => match trimmed {
+            "" | "~" | "null" | "Null" | "NULL" => YamlValue::Null,
+            _ => return Err(invalid()),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> YamlValue {
+        YamlValue::String(YamlString::Plain(s.to_string()))
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(
+            coerce(&string(" 80.0 "), ScalarType::Int).unwrap(),
+            YamlValue::Number(YamlNumber::Integer(80))
+        );
+        assert!(coerce(&string("80.5"), ScalarType::Int).is_err());
+        assert_eq!(
+            coerce(
+                &YamlValue::Number(YamlNumber::Integer(3)),
+                ScalarType::Float
+            )
+            .unwrap(),
+            YamlValue::Number(YamlNumber::Float(3.0))
+        );
+        assert_eq!(
+            coerce(&YamlValue::Number(YamlNumber::Integer(3)), ScalarType::Str).unwrap(),
+            string("3")
+        );
+    }
+
+    #[test]
+    fn test_bool_and_null() {
+        assert_eq!(
+            coerce(&string("Yes"), ScalarType::Bool).unwrap(),
+            YamlValue::Boolean(true)
+        );
+        assert_eq!(
+            coerce(&YamlValue::Boolean(true), ScalarType::Int).unwrap(),
+            YamlValue::Number(YamlNumber::Integer(1))
+        );
+        assert_eq!(
+            coerce(&string("~"), ScalarType::Null).unwrap(),
+            YamlValue::Null
+        );
+        assert_eq!(
+            coerce(&string("x"), ScalarType::Null)
+                .unwrap_err()
+                .to_string(),
+            "Can't convert 'x' to null"
+        );
+        assert_eq!(
+            coerce(&YamlValue::Null, ScalarType::Str).unwrap(),
+            string("")
+        );
+    }
+}
@@ -0,0 +1,457 @@
+//! Cron schedules (`K` on a `schedule: "0 3 * * 1"` value).
+//!
+//! Parses the five-field syntax of crontab, Kubernetes CronJobs and GitHub
+//! Actions (`minute hour day-of-month month day-of-week`, with `*`, lists,
+//! ranges, `/` steps and `JAN`/`MON` names) plus the `@daily`-style macros,
+//! describes a schedule in words and finds its next runs.
+//!
+//! Run times are computed in UTC, the time zone GitHub Actions uses and the
+//! default of most clusters.
+
+/// A field's name, range and value names.
+struct Field {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &'static [&'static str],
+}
+
+const FIELDS: [Field; 5] = [
+    Field {
+        name: "minute",
+        min: 0,
+        max: 59,
+        names: &[],
+    },
+    Field {
+        name: "hour",
+        min: 0,
+        max: 23,
+        names: &[],
+    },
+    Field {
+        name: "day-of-month",
+        min: 1,
+        max: 31,
+        names: &[],
+    },
+    Field {
+        name: "month",
+        min: 1,
+        max: 12,
+        names: &[
+            "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+        ],
+    },
+    Field {
+        name: "day-of-week",
+        min: 0,
+        max: 7,
+        names: &["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"],
+    },
+];
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// One comma-separated part of a field: `5`, `1-5`, `*/15` or `10-50/20`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Any { step: u32 },
+    Range { from: u32, to: u32, step: u32 },
+}
+
+/// A parsed schedule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    fields: [Vec<Part>; 5],
+}
+
+impl Schedule {
+    /// Parses a five-field expression or a macro such as `@daily`.
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expr = expr.trim();
+        let expanded = match expr.to_ascii_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            macro_name if macro_name.starts_with('@') => {
+                return Err(format!("{} has no fixed schedule", expr));
+            }
+            _ => expr,
+        };
+        let texts: Vec<&str> = expanded.split_whitespace().collect();
+        if texts.len() != 5 {
+            return Err(format!("expected 5 fields, found {}", texts.len()));
+        }
+        let mut fields: [Vec<Part>; 5] = Default::default();
+        for (index, text) in texts.iter().enumerate() {
+            fields[index] = parse_field(text, &FIELDS[index])?;
+        }
+        Ok(Self { fields })
+    }
+
+    /// Returns true if the field allows every value (`*` or `?`).
+    fn is_any(&self, field: usize) -> bool {
+        self.fields[field] == [Part::Any { step: 1 }]
+    }
+
+    fn matches(&self, field: usize, value: u32) -> bool {
+        let info = &FIELDS[field];
+        self.fields[field].iter().any(|part| match *part {
+            Part::Any { step } => (value - info.min).is_multiple_of(step),
+            Part::Range { from, to, step } => {
+                // Sunday is both 0 and 7
+                let value = if field == 4 && value == 0 && to == 7 {
+                    7
+                } else {
+                    value
+                };
+                (from..=to).contains(&value) && (value - from).is_multiple_of(step)
+            }
+        })
+    }
+
+    /// Returns true if the schedule runs on the given day.
+    ///
+    /// As in cron, when both day-of-month and day-of-week are restricted a
+    /// day matching either one counts.
+    fn runs_on(&self, day: u32, month: u32, weekday: u32) -> bool {
+        if !self.matches(3, month) {
+            return false;
+        }
+        match (self.is_any(2), self.is_any(4)) {
+            (true, true) => true,
+            (false, true) => self.matches(2, day),
+            (true, false) => self.matches(4, weekday),
+            (false, false) => self.matches(2, day) || self.matches(4, weekday),
+        }
+    }
+
+    /// Returns the next `count` run times after `after` (Unix seconds),
+    /// looking up to five years ahead.
+    pub fn next_runs(&self, after: i64, count: usize) -> Vec<i64> {
+        let mut runs = Vec::new();
+        let first_day = after.div_euclid(86_400);
+        for day in first_day..first_day + 5 * 366 {
+            let (_, month, date) = civil_from_days(day);
+            let weekday = (day + 4).rem_euclid(7) as u32;
+            if !self.runs_on(date, month, weekday) {
+                continue;
+            }
+            for hour in (0..24).filter(|h| self.matches(1, *h)) {
+                for minute in (0..60).filter(|m| self.matches(0, *m)) {
+                    let time = day * 86_400 + i64::from(hour * 3600 + minute * 60);
+                    if time > after {
+                        runs.push(time);
+                        if runs.len() == count {
+                            return runs;
+                        }
+                    }
+                }
+            }
+        }
+        runs
+    }
+
+    /// Describes the schedule, e.g. `At 03:00 on Monday.`
+    pub fn describe(&self) -> String {
+        let mut text = match self.clock_times() {
+            Some(times) => format!("At {}", join(&times)),
+            None => {
+                let mut text = format!("At {}", self.describe_field(0));
+                if !self.is_any(1) {
+                    text.push_str(&format!(" past {}", self.describe_field(1)));
+                }
+                text
+            }
+        };
+        match (self.is_any(2), self.is_any(4)) {
+            (false, false) => text.push_str(&format!(
+                " on {} or on {}",
+                self.describe_field(2),
+                self.describe_field(4)
+            )),
+            (false, true) => text.push_str(&format!(" on {}", self.describe_field(2))),
+            (true, false) => text.push_str(&format!(" on {}", self.describe_field(4))),
+            (true, true) => {}
+        }
+        if !self.is_any(3) {
+            text.push_str(&format!(" in {}", self.describe_field(3)));
+        }
+        text.push('.');
+        text
+    }
+
+    /// Returns `03:00`-style times when the minutes and hours are plain
+    /// values, and there are at most four of them.
+    fn clock_times(&self) -> Option<Vec<String>> {
+        let values = |field: usize| -> Option<Vec<u32>> {
+            self.fields[field]
+                .iter()
+                .map(|part| match *part {
+                    Part::Range { from, to, .. } if from == to => Some(from),
+                    _ => None,
+                })
+                .collect()
+        };
+        let (minutes, hours) = (values(0)?, values(1)?);
+        if minutes.len() * hours.len() > 4 {
+            return None;
+        }
+        Some(
+            hours
+                .iter()
+                .flat_map(|h| minutes.iter().map(move |m| format!("{:02}:{:02}", h, m)))
+                .collect(),
+        )
+    }
+
+    fn describe_field(&self, field: usize) -> String {
+        let info = &FIELDS[field];
+        let value = |v: u32| match field {
+            3 => MONTHS[(v - 1) as usize].to_string(),
+            4 => WEEKDAYS[(v % 7) as usize].to_string(),
+            _ => v.to_string(),
+        };
+        // Named values read without the field's name
+        let prefix = match field {
+            3 | 4 => String::new(),
+            _ => format!("{} ", info.name),
+        };
+        let parts: Vec<String> = self.fields[field]
+            .iter()
+            .map(|part| match *part {
+                Part::Any { step: 1 } => format!("every {}", info.name),
+                Part::Any { step } => format!("every {} {}", ordinal(step), info.name),
+                Part::Range { from, to, .. } if from == to => format!("{}{}", prefix, value(from)),
+                Part::Range { from, to, step: 1 } => format!(
+                    "every {} from {} through {}",
+                    info.name,
+                    value(from),
+                    value(to)
+                ),
+                Part::Range { from, to, step } => format!(
+                    "every {} {} from {} through {}",
+                    ordinal(step),
+                    info.name,
+                    value(from),
+                    value(to)
+                ),
+            })
+            .collect();
+        join(&parts)
+    }
+}
+
+fn parse_field(text: &str, field: &Field) -> Result<Vec<Part>, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let upper = s.to_ascii_uppercase();
+        let parsed = match field.names.iter().position(|n| *n == upper) {
+            Some(index) => index as u32 + field.min,
+            None => s
+                .parse::<u32>()
+                .map_err(|_| format!("{} {:?} is not a number", field.name, s))?,
+        };
+        if parsed < field.min || parsed > field.max {
+            return Err(format!(
+                "{} {} is outside {}-{}",
+                field.name, parsed, field.min, field.max
+            ));
+        }
+        Ok(parsed)
+    };
+
+    text.split(',')
+        .map(|item| {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => {
+                    let step: u32 =
+                        step.parse().ok().filter(|s| *s > 0).ok_or_else(|| {
+                            format!("{} step {:?} is not valid", field.name, step)
+                        })?;
+                    (range, step)
+                }
+                None => (item, 1),
+            };
+            if range == "*" || range == "?" {
+                return Ok(Part::Any { step });
+            }
+            let (from, to) = match range.split_once('-') {
+                Some((from, to)) => (value(from)?, value(to)?),
+                // `5/15` runs from 5 to the end of the range
+                None if step > 1 => (value(range)?, field.max),
+                None => {
+                    let v = value(range)?;
+                    (v, v)
+                }
+            };
+            if from > to {
+                return Err(format!("{} range {} is backwards", field.name, range));
+            }
+            Ok(Part::Range { from, to, step })
+        })
+        .collect()
+}
+
+fn ordinal(n: u32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+/// Joins `a`, `a and b`, or `a, b, and c`.
+fn join(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [one] => one.clone(),
+        [a, b] => format!("{} and {}", a, b),
+        [rest @ .., last] => format!("{}, and {}", rest.join(", "), last),
+    }
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) date.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Formats Unix seconds as `Mon 2026-10-19 03:00 UTC`.
+pub fn format_time(time: i64) -> String {
+    let day = time.div_euclid(86_400);
+    let seconds = time.rem_euclid(86_400);
+    let (year, month, date) = civil_from_days(day);
+    let weekday = WEEKDAYS[(day + 4).rem_euclid(7) as usize];
+    format!(
+        "{} {:04}-{:02}-{:02} {:02}:{:02} UTC",
+        &weekday[..3],
+        year,
+        month,
+        date,
+        seconds / 3600,
+        seconds % 3600 / 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(expr: &str) -> String {
+        Schedule::parse(expr).unwrap().describe()
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe("0 3 * * 1"), "At 03:00 on Monday.");
+        assert_eq!(
+            describe("30 9,17 * * MON-FRI"),
+            "At 09:30 and 17:30 on every day-of-week from Monday through Friday."
+        );
+        assert_eq!(describe("*/15 * * * *"), "At every 15th minute.");
+        assert_eq!(
+            describe("5 */2 1 JAN,JUL *"),
+            "At minute 5 past every 2nd hour on day-of-month 1 in January and July."
+        );
+        assert_eq!(
+            describe("0 0 1 * 0"),
+            "At 00:00 on day-of-month 1 or on Sunday."
+        );
+        assert_eq!(describe("@hourly"), "At minute 0.");
+        assert_eq!(
+            describe("0-10/5 * * * *"),
+            "At every 5th minute from 0 through 10."
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Schedule::parse("* * * *").unwrap_err(),
+            "expected 5 fields, found 4"
+        );
+        assert_eq!(
+            Schedule::parse("60 * * * *").unwrap_err(),
+            "minute 60 is outside 0-59"
+        );
+        assert_eq!(
+            Schedule::parse("* * * FOO *").unwrap_err(),
+            "month \"FOO\" is not a number"
+        );
+        assert_eq!(
+            Schedule::parse("*/0 * * * *").unwrap_err(),
+            "minute step \"0\" is not valid"
+        );
+        assert_eq!(
+            Schedule::parse("@reboot").unwrap_err(),
+            "@reboot has no fixed schedule"
+        );
+    }
+
+    #[test]
+    fn test_next_runs() {
+        // Thu 2026-10-15 12:00 UTC
+        let now = 1_792_065_600;
+        assert_eq!(format_time(now), "Thu 2026-10-15 12:00 UTC");
+
+        let runs: Vec<String> = Schedule::parse("0 3 * * 1")
+            .unwrap()
+            .next_runs(now, 3)
+            .into_iter()
+            .map(format_time)
+            .collect();
+        assert_eq!(
+            runs,
+            [
+                "Mon 2026-10-19 03:00 UTC",
+                "Mon 2026-10-26 03:00 UTC",
+                "Mon 2026-11-02 03:00 UTC"
+            ]
+        );
+
+        // Sunday written as 7, and a day that never comes
+        let sunday = Schedule::parse("0 0 * * 7").unwrap().next_runs(now, 1);
+        assert_eq!(format_time(sunday[0]), "Sun 2026-10-18 00:00 UTC");
+        assert!(Schedule::parse("0 0 30 2 *")
+            .unwrap()
+            .next_runs(now, 1)
+            .is_empty());
+    }
+}
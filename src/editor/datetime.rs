@@ -0,0 +1,497 @@
+//! Timestamps in values (`2026-10-15T09:30:00Z`, `2026-10-15`): `:now`,
+//! and shifting or re-formatting with `:time` (`gt+`, `gt-`, `gt=`).
+//!
+//! Timestamps are the ISO 8601 forms YAML itself recognizes: a date, or a
+//! date and time separated by `T` or a space, with optional fractional
+//! seconds and a `Z` or `+HH:MM` zone. Shifting keeps the form as written,
+//! so a date stays a date and an offset stays the same offset.
+
+use super::cron::civil_from_days;
+
+/// The time zone written after a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zone {
+    /// No zone: a local time, or a date alone
+    Local,
+    /// `Z`
+    Utc,
+    /// An offset from UTC in minutes (`+05:30` is 330)
+    Offset(i32),
+}
+
+/// A parsed timestamp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateTime {
+    /// Seconds since 1970-01-01 00:00 in the timestamp's own zone
+    pub seconds: i64,
+    /// Digits of the fractional seconds as written (`10` for `.10`)
+    pub fraction: String,
+    /// The zone written after the time
+    pub zone: Zone,
+    /// Whether only a date was written
+    pub date_only: bool,
+    /// What separates the date from the time: `T` or a space
+    pub separator: char,
+}
+
+/// What `:time` re-formats a timestamp as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// `2026-10-15T09:30:00+02:00`, keeping the zone
+    Iso,
+    /// `2026-10-15T07:30:00Z`
+    Utc,
+    /// `2026-10-15`
+    Date,
+    /// Seconds since 1970-01-01 UTC, as an integer
+    Epoch,
+}
+
+impl Format {
+    /// Parses `iso`, `utc`, `date` or `epoch`.
+    pub fn parse(name: &str) -> Option<Format> {
+        match name {
+            "iso" => Some(Format::Iso),
+            "utc" => Some(Format::Utc),
+            "date" => Some(Format::Date),
+            "epoch" => Some(Format::Epoch),
+            _ => None,
+        }
+    }
+}
+
+impl DateTime {
+    /// Parses a timestamp, explaining what's wrong with one that isn't valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::editor::datetime::DateTime;
+    ///
+    /// let time = DateTime::parse("2026-01-31T09:30:00+02:00").unwrap();
+    /// assert_eq!(time.shift("+1mo").unwrap().to_string(), "2026-02-28T09:30:00+02:00");
+    /// assert_eq!(time.to_utc().to_string(), "2026-01-31T07:30:00Z");
+    /// assert_eq!(
+    ///     DateTime::parse("2026-02-30").unwrap_err(),
+    ///     "February 2026 has no day 30"
+    /// );
+    /// ```
+    pub fn parse(text: &str) -> Result<DateTime, String> {
+        let text = text.trim();
+        let date = text.get(..10).ok_or("expected a date like 2026-10-15")?;
+        let (year, month, day) = parse_date(date)?;
+        let days = days_from_civil(year, month, day);
+        let rest = &text[10..];
+        if rest.is_empty() {
+            return Ok(DateTime {
+                seconds: days * 86_400,
+                fraction: String::new(),
+                zone: Zone::Local,
+                date_only: true,
+                separator: 'T',
+            });
+        }
+
+        let mut chars = rest.chars();
+        let separator = match chars.next() {
+            Some('T') | Some('t') => 'T',
+            Some(' ') => ' ',
+            _ => return Err(format!("unexpected '{}' after the date", rest)),
+        };
+        let rest = rest[1..].trim_start();
+        let time = rest.get(..8).ok_or("expected a time like 09:30:00")?;
+        let (hour, minute, second) = parse_time(time)?;
+        let mut rest = &rest[8..];
+
+        let mut fraction = String::new();
+        if let Some(after) = rest.strip_prefix('.') {
+            fraction = after.chars().take_while(|c| c.is_ascii_digit()).collect();
+            if fraction.is_empty() {
+                return Err("expected digits after '.'".to_string());
+            }
+            rest = &after[fraction.len()..];
+        }
+        let zone = parse_zone(rest.trim_start())?;
+
+        Ok(DateTime {
+            seconds: days * 86_400 + hour * 3600 + minute * 60 + second,
+            fraction,
+            zone,
+            date_only: false,
+            separator,
+        })
+    }
+
+    /// Returns the timestamp for Unix seconds, in UTC.
+    pub fn from_unix(seconds: i64) -> DateTime {
+        DateTime {
+            seconds,
+            fraction: String::new(),
+            zone: Zone::Utc,
+            date_only: false,
+            separator: 'T',
+        }
+    }
+
+    /// Returns the timestamp as Unix seconds; a local time counts as UTC.
+    pub fn unix(&self) -> i64 {
+        match self.zone {
+            Zone::Offset(minutes) => self.seconds - i64::from(minutes) * 60,
+            Zone::Local | Zone::Utc => self.seconds,
+        }
+    }
+
+    /// Returns the same moment in UTC.
+    pub fn to_utc(&self) -> DateTime {
+        DateTime {
+            seconds: self.unix(),
+            zone: Zone::Utc,
+            date_only: false,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the timestamp with only its date.
+    pub fn to_date(&self) -> DateTime {
+        DateTime {
+            seconds: self.seconds.div_euclid(86_400) * 86_400,
+            fraction: String::new(),
+            zone: Zone::Local,
+            date_only: true,
+            ..self.clone()
+        }
+    }
+
+    /// Shifts the timestamp by an amount like `+1d`, `-2h30m` or `+1y6mo`.
+    ///
+    /// Units are `y` (years), `mo` (months), `w`, `d`, `h`, `m` (minutes)
+    /// and `s`. Moving by months or years keeps the day of the month where
+    /// it can and otherwise uses the month's last day. A date can only move
+    /// by whole days.
+    pub fn shift(&self, amount: &str) -> Result<DateTime, String> {
+        let amount = amount.trim();
+        let (sign, mut rest) = match amount.chars().next() {
+            Some('+') => (1, &amount[1..]),
+            Some('-') => (-1, &amount[1..]),
+            _ => {
+                return Err(format!(
+                    "'{}' doesn't start with + or - (like +1d or -2h)",
+                    amount
+                ))
+            }
+        };
+        if rest.is_empty() {
+            return Err("expected an amount like +1d or -2h".to_string());
+        }
+
+        let mut shifted = self.clone();
+        while !rest.is_empty() {
+            let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+            if digits == 0 {
+                return Err(format!("expected a number before '{}'", rest));
+            }
+            let count: i64 = rest[..digits]
+                .parse()
+                .map_err(|_| format!("{} is too large", &rest[..digits]))?;
+            rest = &rest[digits..];
+            let unit_len = if rest.starts_with("mo") {
+                2
+            } else {
+                rest.chars().next().map_or(0, char::len_utf8)
+            };
+            let unit = &rest[..unit_len];
+            rest = &rest[unit_len..];
+
+            let count = sign * count;
+            let seconds = match unit {
+                "y" => {
+                    shifted.seconds = add_months(shifted.seconds, count * 12);
+                    continue;
+                }
+                "mo" => {
+                    shifted.seconds = add_months(shifted.seconds, count);
+                    continue;
+                }
+                "w" => 7 * 86_400,
+                "d" => 86_400,
+                "h" | "m" | "s" if self.date_only => {
+                    return Err(format!(
+                        "a date has no time of day to move by '{}' (use y, mo, w or d)",
+                        unit
+                    ))
+                }
+                "h" => 3600,
+                "m" => 60,
+                "s" => 1,
+                "" => {
+                    return Err(format!(
+                        "{} needs a unit: y, mo, w, d, h, m or s",
+                        count.abs()
+                    ))
+                }
+                _ => {
+                    return Err(format!(
+                        "unknown unit '{}' (use y, mo, w, d, h, m or s)",
+                        unit
+                    ))
+                }
+            };
+            shifted.seconds = count
+                .checked_mul(seconds)
+                .and_then(|s| shifted.seconds.checked_add(s))
+                .ok_or("the result is out of range")?;
+        }
+        Ok(shifted)
+    }
+}
+
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (year, month, day) = civil_from_days(self.seconds.div_euclid(86_400));
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)?;
+        if self.date_only {
+            return Ok(());
+        }
+        let time = self.seconds.rem_euclid(86_400);
+        write!(
+            f,
+            "{}{:02}:{:02}:{:02}",
+            self.separator,
+            time / 3600,
+            time % 3600 / 60,
+            time % 60
+        )?;
+        if !self.fraction.is_empty() {
+            write!(f, ".{}", self.fraction)?;
+        }
+        match self.zone {
+            Zone::Local => Ok(()),
+            Zone::Utc => write!(f, "Z"),
+            Zone::Offset(minutes) => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.abs();
+                write!(f, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+            }
+        }
+    }
+}
+
+/// Returns true if `text` is worth treating as a timestamp: it starts like
+/// `2026-10-15`.
+pub fn looks_like_timestamp(text: &str) -> bool {
+    let bytes = text.trim().as_bytes();
+    bytes.len() >= 10
+        && bytes[..10].iter().enumerate().all(|(i, b)| {
+            if i == 4 || i == 7 {
+                *b == b'-'
+            } else {
+                b.is_ascii_digit()
+            }
+        })
+}
+
+/// Converts a (year, month, day) date to days since 1970-01-01.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (i64::from(month) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+fn parse_date(date: &str) -> Result<(i64, u32, u32), String> {
+    let number = |text: &str| -> Result<u32, String> {
+        if !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) {
+            text.parse()
+                .map_err(|_| format!("'{}' is not a number", text))
+        } else {
+            Err("expected a date like 2026-10-15".to_string())
+        }
+    };
+    if date.as_bytes()[4] != b'-' || date.as_bytes()[7] != b'-' {
+        return Err("expected a date like 2026-10-15".to_string());
+    }
+    let year = i64::from(number(&date[..4])?);
+    let month = number(&date[5..7])?;
+    let day = number(&date[8..10])?;
+    if !(1..=12).contains(&month) {
+        return Err(format!("{} is not a month", month));
+    }
+    if day == 0 || day > days_in_month(year, month) {
+        return Err(format!(
+            "{} {} has no day {}",
+            MONTHS[month as usize - 1],
+            year,
+            day
+        ));
+    }
+    Ok((year, month, day))
+}
+
+fn parse_time(time: &str) -> Result<(i64, i64, i64), String> {
+    let parts: Vec<&str> = time.split(':').collect();
+    let valid = parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| p.len() == 2 && p.chars().all(|c| c.is_ascii_digit()));
+    if !valid {
+        return Err(format!("expected a time like 09:30:00, found '{}'", time));
+    }
+    let [hour, minute, second] = [0, 1, 2].map(|i| parts[i].parse::<i64>().unwrap_or(0));
+    if hour > 23 || minute > 59 || second > 60 {
+        return Err(format!("{} is not a time of day", time));
+    }
+    Ok((hour, minute, second.min(59)))
+}
+
+fn parse_zone(zone: &str) -> Result<Zone, String> {
+    match zone {
+        "" => return Ok(Zone::Local),
+        "Z" | "z" => return Ok(Zone::Utc),
+        _ => {}
+    }
+    let sign = match zone.chars().next() {
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return Err(format!("unexpected '{}' after the time", zone)),
+    };
+    let digits: String = zone[1..].chars().filter(|c| *c != ':').collect();
+    let (hours, minutes) = match digits.len() {
+        1 | 2 => (digits.as_str(), "0"),
+        4 => digits.split_at(2),
+        _ => return Err(format!("'{}' is not a time zone offset", zone)),
+    };
+    match (hours.parse::<i32>(), minutes.parse::<i32>()) {
+        (Ok(h), Ok(m)) if h <= 23 && m <= 59 && digits.chars().all(|c| c.is_ascii_digit()) => {
+            Ok(Zone::Offset(sign * (h * 60 + m)))
+        }
+        _ => Err(format!("'{}' is not a time zone offset", zone)),
+    }
+}
+
+/// Moves a wall-clock time by whole months, clamping the day to the month.
+fn add_months(seconds: i64, months: i64) -> i64 {
+    let days = seconds.div_euclid(86_400);
+    let time = seconds.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    let index = year * 12 + i64::from(month) - 1 + months;
+    let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+    let day = day.min(days_in_month(year, month));
+    days_from_civil(year, month, day) * 86_400 + time
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shift(text: &str, amount: &str) -> String {
+        DateTime::parse(text)
+            .unwrap()
+            .shift(amount)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        for text in [
+            "2026-10-15",
+            "2026-10-15T09:30:00Z",
+            "2026-10-15T09:30:00",
+            "2001-12-14 21:59:43.10-05:00",
+            "1969-07-20T20:17:40.5+00:00",
+        ] {
+            assert_eq!(DateTime::parse(text).unwrap().to_string(), text);
+        }
+        assert_eq!(
+            DateTime::parse("2001-12-14t21:59:43-5")
+                .unwrap()
+                .to_string(),
+            "2001-12-14T21:59:43-05:00"
+        );
+        assert_eq!(
+            DateTime::parse("2001-12-14 21:59:43.10 -5")
+                .unwrap()
+                .to_string(),
+            "2001-12-14 21:59:43.10-05:00"
+        );
+        assert_eq!(DateTime::parse("1970-01-02").unwrap().unix(), 86_400);
+        assert_eq!(
+            DateTime::from_unix(1_760_000_000).to_string(),
+            "2025-10-09T08:53:20Z"
+        );
+    }
+
+    #[test]
+    fn test_shift() {
+        assert_eq!(shift("2026-12-31T23:30:00Z", "+1h"), "2027-01-01T00:30:00Z");
+        assert_eq!(shift("2026-03-01", "-1d"), "2026-02-28");
+        assert_eq!(shift("2024-02-29", "+1y"), "2025-02-28");
+        assert_eq!(
+            shift("2026-10-15T09:30:00Z", "-1d2h30m"),
+            "2026-10-14T07:00:00Z"
+        );
+        assert_eq!(shift("2026-10-15", "+2w"), "2026-10-29");
+        assert_eq!(shift("2026-10-15", "+14mo"), "2027-12-15");
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text: &str| DateTime::parse(text).unwrap_err();
+        assert_eq!(error("2026-13-01"), "13 is not a month");
+        assert_eq!(error("2026-10"), "expected a date like 2026-10-15");
+        assert_eq!(
+            error("2026-10-15T25:00:00"),
+            "25:00:00 is not a time of day"
+        );
+        assert_eq!(error("2026-10-15T09:30"), "expected a time like 09:30:00");
+        assert_eq!(
+            error("2026-10-15T09:30:00 UTC"),
+            "unexpected 'UTC' after the time"
+        );
+
+        let date = DateTime::parse("2026-10-15").unwrap();
+        assert_eq!(
+            date.shift("+3h").unwrap_err(),
+            "a date has no time of day to move by 'h' (use y, mo, w or d)"
+        );
+        assert_eq!(
+            date.shift("1d").unwrap_err(),
+            "'1d' doesn't start with + or - (like +1d or -2h)"
+        );
+        assert_eq!(
+            date.shift("+1x").unwrap_err(),
+            "unknown unit 'x' (use y, mo, w, d, h, m or s)"
+        );
+        assert_eq!(
+            date.shift("+5").unwrap_err(),
+            "5 needs a unit: y, mo, w, d, h, m or s"
+        );
+    }
+}
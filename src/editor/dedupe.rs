@@ -0,0 +1,190 @@
+//! Keys written more than once in a mapping, and `:dedupe` to resolve them.
+//!
+//! The parser keeps every entry of a repeated key (see
+//! [`crate::document::duplicates`]), where other YAML readers silently keep
+//! only the last. Each repeat is flagged in the `duplicates` diagnostics
+//! until it is resolved. `:dedupe` steps through the repeated keys in
+//! document order and asks, for each, whether to keep the first entry, the
+//! last one (what other readers see) or all of them under new keys.
+
+use super::diagnostics::{Severity, ValueProblem};
+use crate::document::duplicates::{find, DuplicateKey};
+use crate::document::node::{YamlNode, YamlValue};
+use crate::document::ops::Op;
+
+/// Source name for the diagnostics of repeated keys.
+pub const DUPLICATES_SOURCE: &str = "duplicates";
+
+/// Returns a warning for each entry that repeats a key of its mapping, in
+/// document order.
+pub fn check_keys(root: &YamlNode) -> Vec<ValueProblem> {
+    let mut problems: Vec<_> = find(root)
+        .into_iter()
+        .flat_map(|duplicate| {
+            let count = duplicate.paths.len();
+            let key = duplicate.key;
+            duplicate
+                .paths
+                .into_iter()
+                .skip(1)
+                .map(move |path| ValueProblem {
+                    path,
+                    severity: Severity::Warning,
+                    message: format!(
+                    "Key `{}` is written {} times; other YAML readers keep only the last (:dedupe)",
+                    key, count
+                ),
+                })
+        })
+        .collect();
+    problems.sort_by(|a, b| a.path.cmp(&b.path));
+    problems
+}
+
+/// How to resolve a repeated key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Keep the first entry, deleting the repeats
+    KeepFirst,
+    /// Keep the last entry under the key, deleting the others
+    KeepLast,
+    /// Keep every entry, giving each repeat a new key (`name_2`, ...)
+    Rename,
+}
+
+impl Resolution {
+    /// Returns the resolution answered with `c` at the `:dedupe` prompt.
+    pub fn from_answer(c: char) -> Option<Self> {
+        match c {
+            'f' => Some(Self::KeepFirst),
+            'l' => Some(Self::KeepLast),
+            'r' => Some(Self::Rename),
+            _ => None,
+        }
+    }
+
+    /// Returns the ops that resolve `duplicate` and the path of the entry
+    /// left under its key afterwards.
+    ///
+    /// `parent` is the mapping holding the entries; new keys are chosen so
+    /// they don't clash with its other keys.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::document::duplicates::find;
+    /// use yamlquill::document::ops::Op;
+    /// use yamlquill::document::parser::parse_yaml_auto;
+    /// use yamlquill::editor::dedupe::Resolution;
+    ///
+    /// let root = parse_yaml_auto("name: api\nport: 80\nname: web\n").unwrap();
+    /// let duplicate = &find(&root)[0];
+    /// let (ops, kept) = Resolution::KeepLast.ops(duplicate, &root);
+    /// assert_eq!(kept, [1]);
+    /// assert_eq!(
+    ///     ops,
+    ///     [
+    ///         Op::Delete { path: vec![0] },
+    ///         Op::RenameKey { path: vec![1], key: "name".to_string() },
+    ///     ]
+    /// );
+    /// ```
+    pub fn ops(self, duplicate: &DuplicateKey, parent: &YamlNode) -> (Vec<Op>, Vec<usize>) {
+        let paths = &duplicate.paths;
+        let delete = |paths: &[Vec<usize>]| {
+            paths
+                .iter()
+                .rev()
+                .map(|path| Op::Delete { path: path.clone() })
+                .collect::<Vec<_>>()
+        };
+        match self {
+            Self::KeepFirst => (delete(&paths[1..]), paths[0].clone()),
+            Self::KeepLast => {
+                let (last, earlier) = paths.split_last().expect("a repeated key has entries");
+                // The last entry moves up by one for each entry deleted before it
+                let mut kept = last.clone();
+                if let Some(index) = kept.last_mut() {
+                    *index -= earlier.len();
+                }
+                let mut ops = delete(earlier);
+                ops.push(Op::RenameKey {
+                    path: kept.clone(),
+                    key: duplicate.key.clone(),
+                });
+                (ops, kept)
+            }
+            Self::Rename => {
+                let taken = |key: &str| match parent.value() {
+                    YamlValue::Object(entries) => entries.contains_key(key),
+                    _ => false,
+                };
+                let mut n = 1;
+                let ops = paths[1..]
+                    .iter()
+                    .map(|path| {
+                        let key = loop {
+                            n += 1;
+                            let key = format!("{}_{}", duplicate.key, n);
+                            if !taken(&key) {
+                                break key;
+                            }
+                        };
+                        Op::RenameKey {
+                            path: path.clone(),
+                            key,
+                        }
+                    })
+                    .collect();
+                (ops, paths[0].clone())
+            }
+        }
+    }
+}
+
+/// Progress through the repeated keys while the `:dedupe` prompt is open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DedupeSession {
+    /// Repeated keys skipped so far; they come first in document order
+    pub skipped: usize,
+    /// Repeated keys resolved so far
+    pub resolved: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    #[test]
+    fn test_check_keys_flags_each_repeat() {
+        let root = parse_yaml_auto("a: 1\nb:\n  c: 1\n  c: 2\n  c: 3\na: 2\n").unwrap();
+        let problems = check_keys(&root);
+        let paths: Vec<_> = problems.iter().map(|p| p.path.clone()).collect();
+        assert_eq!(paths, [vec![1, 1], vec![1, 2], vec![2]]);
+        assert_eq!(
+            problems[0].message,
+            "Key `c` is written 3 times; other YAML readers keep only the last (:dedupe)"
+        );
+    }
+
+    #[test]
+    fn test_rename_skips_taken_keys() {
+        let root = parse_yaml_auto("a: 1\na_2: x\na: 2\na: 3\n").unwrap();
+        let (ops, kept) = Resolution::Rename.ops(&find(&root)[0], &root);
+        assert_eq!(kept, [0]);
+        assert_eq!(
+            ops,
+            [
+                Op::RenameKey {
+                    path: vec![2],
+                    key: "a_3".to_string()
+                },
+                Op::RenameKey {
+                    path: vec![3],
+                    key: "a_4".to_string()
+                },
+            ]
+        );
+    }
+}
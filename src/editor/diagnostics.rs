@@ -0,0 +1,271 @@
+//! Diagnostics reported against nodes in the document.
+//!
+//! Validators (such as JSON Schema validation) produce `Diagnostic`s that point
+//! at a node path. The editor keeps them in a `DiagnosticList`, grouped by the
+//! source that produced them, so that re-running one validator replaces only
+//! its own results.
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// How serious a diagnostic is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Informational note
+    Info,
+    /// Suspicious but not invalid
+    Warning,
+    /// The document violates a rule
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single problem reported at a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Path to the offending node (indices at each level)
+    pub path: Vec<usize>,
+    /// Human-readable location, e.g. `$.spec.replicas`
+    pub location: String,
+    /// Description of the problem
+    pub message: String,
+    /// Severity of the problem
+    pub severity: Severity,
+    /// Name of the validator that produced it, e.g. `schema`
+    pub source: String,
+}
+
+impl Diagnostic {
+    /// Creates a new error diagnostic.
+    pub fn error(source: &str, path: Vec<usize>, location: String, message: String) -> Self {
+        Self {
+            path,
+            location,
+            message,
+            severity: Severity::Error,
+            source: source.to_string(),
+        }
+    }
+
+    /// Creates a new warning diagnostic.
+    pub fn warning(source: &str, path: Vec<usize>, location: String, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(source, path, location, message)
+        }
+    }
+}
+
+/// A problem a checker found at a node, before the editor turns it into a
+/// [`Diagnostic`] with a location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueProblem {
+    /// Path of the value
+    pub path: Vec<usize>,
+    /// `Error` for invalid values, `Warning` for likely mistakes
+    pub severity: Severity,
+    /// Description of the problem, with a suggestion where there is one
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// All diagnostics currently reported for the document.
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticList {
+    items: Vec<Diagnostic>,
+}
+
+impl DiagnosticList {
+    /// Creates an empty diagnostic list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces all diagnostics from `source` with `diagnostics`.
+    pub fn replace_source(&mut self, source: &str, diagnostics: Vec<Diagnostic>) {
+        self.items.retain(|d| d.source != source);
+        self.items.extend(diagnostics);
+    }
+
+    /// Replaces the diagnostics from `source` at `path` with `diagnostics`.
+    ///
+    /// With `subtree`, diagnostics anywhere below `path` are replaced too.
+    /// Used to re-validate one part of the document at a time.
+    pub fn replace_at(
+        &mut self,
+        source: &str,
+        path: &[usize],
+        subtree: bool,
+        diagnostics: Vec<Diagnostic>,
+    ) {
+        self.items.retain(|d| {
+            let covered = if subtree {
+                d.path.starts_with(path)
+            } else {
+                d.path == path
+            };
+            d.source != source || !covered
+        });
+        self.items.extend(diagnostics);
+    }
+
+    /// Removes every diagnostic.
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Replaces every diagnostic's path with `f(path)`, dropping those it maps to `None`.
+    pub fn remap_paths(&mut self, mut f: impl FnMut(&[usize]) -> Option<Vec<usize>>) {
+        self.items.retain_mut(|d| match f(&d.path) {
+            Some(path) => {
+                d.path = path;
+                true
+            }
+            None => false,
+        });
+    }
+
+    /// Returns all diagnostics.
+    pub fn items(&self) -> &[Diagnostic] {
+        &self.items
+    }
+
+    /// Returns true if there are no diagnostics.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns the number of diagnostics.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Counts diagnostics with the given severity.
+    pub fn count(&self, severity: Severity) -> usize {
+        self.items.iter().filter(|d| d.severity == severity).count()
+    }
+
+    /// Returns the most severe diagnostic level for each reported path.
+    ///
+    /// Used by the tree view to draw gutter markers.
+    pub fn markers(&self) -> HashMap<Vec<usize>, Severity> {
+        let mut markers: HashMap<Vec<usize>, Severity> = HashMap::new();
+        for d in &self.items {
+            let entry = markers.entry(d.path.clone()).or_insert(d.severity);
+            if d.severity > *entry {
+                *entry = d.severity;
+            }
+        }
+        markers
+    }
+
+    /// Returns a one-line summary such as `2 errors, 1 warning`.
+    pub fn summary(&self) -> String {
+        let errors = self.count(Severity::Error);
+        let warnings = self.count(Severity::Warning);
+        let plural = |n: usize, word: &str| {
+            if n == 1 {
+                format!("{} {}", n, word)
+            } else {
+                format!("{} {}s", n, word)
+            }
+        };
+        match (errors, warnings) {
+            (0, 0) => "no problems".to_string(),
+            (e, 0) => plural(e, "error"),
+            (0, w) => plural(w, "warning"),
+            (e, w) => format!("{}, {}", plural(e, "error"), plural(w, "warning")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diag(source: &str, path: Vec<usize>, severity: Severity) -> Diagnostic {
+        Diagnostic {
+            path,
+            location: "$".to_string(),
+            message: "problem".to_string(),
+            severity,
+            source: source.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_replace_source_keeps_other_sources() {
+        let mut list = DiagnosticList::new();
+        list.replace_source("schema", vec![diag("schema", vec![0], Severity::Error)]);
+        list.replace_source("lint", vec![diag("lint", vec![1], Severity::Warning)]);
+        list.replace_source("schema", vec![]);
+
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.items()[0].source, "lint");
+    }
+
+    #[test]
+    fn test_replace_at_node_or_subtree() {
+        let mut list = DiagnosticList::new();
+        list.replace_source(
+            "schema",
+            vec![
+                diag("schema", vec![], Severity::Error),
+                diag("schema", vec![0], Severity::Error),
+                diag("schema", vec![0, 2], Severity::Error),
+                diag("schema", vec![1], Severity::Error),
+            ],
+        );
+        list.replace_source("lint", vec![diag("lint", vec![0], Severity::Warning)]);
+
+        list.replace_at("schema", &[0], true, vec![]);
+        let paths: Vec<&[usize]> = list.items().iter().map(|d| d.path.as_slice()).collect();
+        assert_eq!(paths, vec![&[][..], &[1], &[0]]);
+
+        list.replace_at("schema", &[], false, vec![]);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn test_markers_use_highest_severity() {
+        let mut list = DiagnosticList::new();
+        list.replace_source(
+            "schema",
+            vec![
+                diag("schema", vec![0], Severity::Warning),
+                diag("schema", vec![0], Severity::Error),
+            ],
+        );
+
+        assert_eq!(list.markers().get(&vec![0]), Some(&Severity::Error));
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut list = DiagnosticList::new();
+        assert_eq!(list.summary(), "no problems");
+        list.replace_source(
+            "schema",
+            vec![
+                diag("schema", vec![0], Severity::Error),
+                diag("schema", vec![1], Severity::Error),
+                diag("schema", vec![2], Severity::Warning),
+            ],
+        );
+        assert_eq!(list.summary(), "2 errors, 1 warning");
+    }
+}
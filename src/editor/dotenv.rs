@@ -0,0 +1,144 @@
+//! Reading key/value pairs for `:import env`.
+//!
+//! Pairs come either from the editor's own environment or from a `.env`
+//! file. The file syntax is the common one shared by docker compose and
+//! the dotenv libraries:
+//!
+//! - `KEY=value`, optionally prefixed with `export `
+//! - blank lines and lines starting with `#` are skipped
+//! - unquoted values end at ` #` (an inline comment) and are trimmed
+//! - `'single quoted'` values are taken literally
+//! - `"double quoted"` values understand `\n`, `\t`, `\"` and `\\`
+
+use super::regex::Regex;
+use anyhow::{bail, Result};
+
+/// Parses the contents of a `.env` file into key/value pairs, in file order.
+///
+/// A key set twice keeps its last value, at the position it first appeared.
+pub fn parse(text: &str) -> Result<Vec<(String, String)>> {
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("Line {}: expected KEY=value", index + 1);
+        };
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            bail!("Line {}: invalid key '{}'", index + 1, key);
+        }
+        let value =
+            parse_value(value.trim()).map_err(|e| anyhow::anyhow!("Line {}: {}", index + 1, e))?;
+        match pairs.iter_mut().find(|(k, _)| k == key) {
+            Some(pair) => pair.1 = value,
+            None => pairs.push((key.to_string(), value)),
+        }
+    }
+    Ok(pairs)
+}
+
+fn parse_value(raw: &str) -> Result<String> {
+    if let Some(rest) = raw.strip_prefix('\'') {
+        let Some(end) = rest.find('\'') else {
+            bail!("unterminated ' quote");
+        };
+        return Ok(rest[..end].to_string());
+    }
+    if let Some(rest) = raw.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Ok(value),
+                '\\' => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(other) => value.push(other),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        bail!("unterminated \" quote");
+    }
+    let value = match raw.find(" #") {
+        Some(comment) => &raw[..comment],
+        None => raw,
+    };
+    Ok(value.trim_end().to_string())
+}
+
+/// Returns the editor's environment variables, sorted by name.
+pub fn environment() -> Vec<(String, String)> {
+    let mut pairs: Vec<(String, String)> = std::env::vars().collect();
+    pairs.sort();
+    pairs
+}
+
+/// Keeps the pairs whose key matches `pattern`, a regex (`^APP_`, `PORT$`).
+pub fn filter(
+    pairs: Vec<(String, String)>,
+    pattern: Option<&str>,
+) -> Result<Vec<(String, String)>> {
+    let Some(pattern) = pattern else {
+        return Ok(pairs);
+    };
+    let regex = Regex::new(pattern)?;
+    Ok(pairs
+        .into_iter()
+        .filter(|(key, _)| regex.is_match(key))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dotenv_syntax() {
+        let text = "\
+# database settings
+export DB_HOST=localhost
+DB_PORT = 5432  # default port
+
+GREETING=\"hello\\n\\\"world\\\"\"
+RAW='no $expansion # here'
+EMPTY=
+DB_HOST=db.internal
+";
+        let pairs = parse(text).unwrap();
+        let get = |key: &str| {
+            pairs
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(pairs.len(), 5);
+        assert_eq!(pairs[0].0, "DB_HOST");
+        assert_eq!(get("DB_HOST"), Some("db.internal"));
+        assert_eq!(get("DB_PORT"), Some("5432"));
+        assert_eq!(get("GREETING"), Some("hello\n\"world\""));
+        assert_eq!(get("RAW"), Some("no $expansion # here"));
+        assert_eq!(get("EMPTY"), Some(""));
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let err = parse("A=1\nnot a pair\n").unwrap_err();
+        assert_eq!(err.to_string(), "Line 2: expected KEY=value");
+        let err = parse("A=\"open\n").unwrap_err();
+        assert_eq!(err.to_string(), "Line 1: unterminated \" quote");
+    }
+
+    #[test]
+    fn test_filter_matches_keys() {
+        let pairs = parse("APP_NAME=x\nAPP_PORT=1\nHOME=/root\n").unwrap();
+        let kept = filter(pairs, Some("^APP_")).unwrap();
+        let keys: Vec<_> = kept.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(keys, ["APP_NAME", "APP_PORT"]);
+    }
+}
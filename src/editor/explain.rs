@@ -0,0 +1,135 @@
+//! Explanations of the value under the cursor (`K`).
+//!
+//! Each kind of value that can be explained (cron schedules, IP addresses,
+//! CIDR ranges, ports and semantic versions) recognises its own syntax; the first one that
+//! does produces the popup's text.
+
+use super::cron::{format_time, Schedule};
+use super::{network, semver};
+use crate::document::node::YamlValue;
+
+/// How many upcoming runs a cron explanation lists.
+const CRON_RUNS: usize = 3;
+
+/// The text of the `K` popup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    /// What the value is, e.g. `Cron schedule`
+    pub title: String,
+    /// The explanation, one line per entry
+    pub lines: Vec<String>,
+}
+
+/// Where the value being explained sits.
+#[derive(Debug, Clone, Default)]
+pub struct Context<'a> {
+    /// The value's key, or for a list item the key of the list
+    pub key: Option<&'a str>,
+    /// The other values in the same mapping or list, labelled by key or
+    /// as `item N`
+    pub siblings: Vec<(String, &'a YamlValue)>,
+    /// The current time, in Unix seconds
+    pub now: i64,
+}
+
+/// Explains `value`.
+///
+/// Returns `Ok(None)` for a value nothing recognises, and an error for one
+/// that looks like a known syntax but isn't valid.
+pub fn explain(value: &YamlValue, context: &Context) -> Result<Option<Explanation>, String> {
+    let recognised = network::explain(context.key, value, &context.siblings)
+        .or_else(|| semver::explain(context.key, value));
+    if let Some(result) = recognised {
+        return result.map(|(title, lines)| Some(Explanation { title, lines }));
+    }
+    let YamlValue::String(text) = value else {
+        return Ok(None);
+    };
+    let text = text.as_str().trim();
+    if looks_like_cron(text) {
+        return explain_cron(text, context.now).map(Some);
+    }
+    Ok(None)
+}
+
+/// Returns true for five fields of cron characters, or an `@` macro.
+fn looks_like_cron(text: &str) -> bool {
+    if let Some(name) = text.strip_prefix('@') {
+        return !name.is_empty() && name.chars().all(|c| c.is_ascii_alphabetic());
+    }
+    let fields: Vec<&str> = text.split_whitespace().collect();
+    fields.len() == 5
+        && fields.iter().all(|field| {
+            field
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "*?,/-".contains(c))
+        })
+        && fields.iter().any(|field| field.contains(['*', '?']))
+}
+
+fn explain_cron(text: &str, now: i64) -> Result<Explanation, String> {
+    let schedule =
+        Schedule::parse(text).map_err(|e| format!("Not a valid cron schedule: {}", e))?;
+    let mut lines = vec![schedule.describe(), String::new()];
+    let runs = schedule.next_runs(now, CRON_RUNS);
+    if runs.is_empty() {
+        lines.push("Never runs".to_string());
+    } else {
+        lines.push("Next runs:".to_string());
+        lines.extend(
+            runs.into_iter()
+                .map(|run| format!("  {}", format_time(run))),
+        );
+    }
+    Ok(Explanation {
+        title: format!("Cron schedule {}", text),
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::node::YamlString;
+
+    fn string(s: &str) -> YamlValue {
+        YamlValue::String(YamlString::Plain(s.to_string()))
+    }
+
+    fn at(now: i64) -> Context<'static> {
+        Context {
+            now,
+            ..Context::default()
+        }
+    }
+
+    #[test]
+    fn test_explain_cron() {
+        let explanation = explain(&string("0 3 * * 1"), &at(1_792_065_600))
+            .unwrap()
+            .unwrap();
+        assert_eq!(explanation.title, "Cron schedule 0 3 * * 1");
+        assert_eq!(
+            explanation.lines,
+            [
+                "At 03:00 on Monday.",
+                "",
+                "Next runs:",
+                "  Mon 2026-10-19 03:00 UTC",
+                "  Mon 2026-10-26 03:00 UTC",
+                "  Mon 2026-11-02 03:00 UTC",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_only_cron_looking_values() {
+        assert_eq!(explain(&string("hello world"), &at(0)), Ok(None));
+        assert_eq!(explain(&string("1 2 3 4 5 6"), &at(0)), Ok(None));
+        assert_eq!(explain(&YamlValue::Null, &at(0)), Ok(None));
+        assert_eq!(
+            explain(&string("0 3 * * 9"), &at(0)).unwrap_err(),
+            "Not a valid cron schedule: day-of-week 9 is outside 0-7"
+        );
+    }
+}
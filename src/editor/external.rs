@@ -0,0 +1,160 @@
+//! Editing a subtree in an external editor (`ge`, `:edit-external`).
+//!
+//! The subtree under the cursor is written to a temporary `.yaml` file and
+//! `$VISUAL` (or `$EDITOR`, or `vi`) is run on it while the TUI is
+//! suspended. The event loop in `main.rs` owns the terminal, so the key
+//! handler only queues the request ([`EditorState::request_external_edit`])
+//! and the loop calls [`edit_text`] between suspending and restoring it.
+//!
+//! Shell filters (`:!cmd`, `:%!cmd`) are queued the same way and run with
+//! [`run_filter`]: the subtree (or document) is piped through the command
+//! and its output replaces it.
+//!
+//! [`EditorState::request_external_edit`]: crate::editor::state::EditorState::request_external_edit
+
+use anyhow::{bail, Context, Result};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// The text an external editor left in its temporary file.
+#[derive(Debug)]
+pub struct ExternalEdit {
+    /// The temporary file, kept until the edit has been applied
+    pub path: PathBuf,
+    /// The file's contents after the editor exited
+    pub text: String,
+}
+
+/// A subtree queued to be piped through a shell command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterRequest {
+    /// The shell command line
+    pub command: String,
+    /// The YAML text fed to the command
+    pub input: String,
+    /// Path of the node the output replaces (empty for the whole document)
+    pub path: Vec<usize>,
+}
+
+/// Returns the command line of the user's editor: `$VISUAL`, `$EDITOR`, or `vi`.
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Writes `text` to a temporary file, runs the editor on it, and returns what it saved.
+///
+/// The editor command may carry arguments (`code --wait`). It talks to the
+/// terminal directly, so this works when stdin is a pipe. The file is
+/// removed if the editor fails.
+pub fn edit_text(text: &str) -> Result<ExternalEdit> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+        "yamlquill-{}-{}.yaml",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    let result = run_editor(&editor_command(), &path).and_then(|_| {
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))
+    });
+    match result {
+        Ok(text) => Ok(ExternalEdit { path, text }),
+        Err(e) => {
+            let _ = fs::remove_file(&path);
+            Err(e)
+        }
+    }
+}
+
+fn run_editor(command: &str, path: &Path) -> Result<()> {
+    let mut words = command.split_whitespace();
+    let program = words.next().context("No editor set")?;
+    let mut process = Command::new(program);
+    process.args(words).arg(path);
+    if let (Ok(input), Ok(output)) = (File::open("/dev/tty"), File::create("/dev/tty")) {
+        process
+            .stdin(Stdio::from(input))
+            .stdout(Stdio::from(output));
+    }
+
+    let status = process
+        .status()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !status.success() {
+        bail!("{} exited with {}; edit discarded", program, status);
+    }
+    Ok(())
+}
+
+/// Runs `command` with `sh -c`, feeding it `input`, and returns its output.
+///
+/// A command that fails reports its exit status and the last line it wrote
+/// to stderr, which is usually the one that says what went wrong.
+pub fn run_filter(command: &str, input: &str) -> Result<String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", command))?;
+
+    // Written from another thread so a command that doesn't read all of its
+    // input before writing can't deadlock against us
+    let mut stdin = child.stdin.take().context("No stdin for filter")?;
+    let input = input.to_string();
+    let writer = thread::spawn(move || {
+        // A command may exit without reading its input (`echo x`)
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("Failed to run {}", command))?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        match stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+            Some(line) => bail!("{} exited with {}: {}", command, output.status, line.trim()),
+            None => bail!("{} exited with {}", command, output.status),
+        }
+    }
+    String::from_utf8(output.stdout).with_context(|| format!("{} wrote invalid UTF-8", command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_editor_reports_failure() {
+        let path = std::env::temp_dir().join("yamlquill-external-test.yaml");
+        assert!(run_editor("true", &path).is_ok());
+        let err = run_editor("false", &path).unwrap_err();
+        assert!(err.to_string().contains("edit discarded"));
+        assert!(run_editor("/nonexistent/editor", &path).is_err());
+        assert!(run_editor("  ", &path).is_err());
+    }
+
+    #[test]
+    fn test_run_filter() {
+        assert_eq!(run_filter("tr a-z A-Z", "a: b\n").unwrap(), "A: B\n");
+        assert_eq!(run_filter("echo ignored", "x\n").unwrap(), "ignored\n");
+
+        let err = run_filter("echo first >&2; echo 'bad input' >&2; exit 3", "")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("exit status: 3"), "{}", err);
+        assert!(err.ends_with("bad input"), "{}", err);
+    }
+}
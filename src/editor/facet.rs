@@ -0,0 +1,155 @@
+//! Value counts of one field across multi-document records (`:facet`).
+//!
+//! Triage of logs and record dumps is mostly "what values does this field
+//! take, and which records have the odd one?". `:facet status.phase` groups
+//! the records of a multi-document or JSONL file by the value of the field
+//! and lists the groups, most common first, in a panel where `Enter` narrows
+//! the view to the records of the selected value.
+
+use crate::document::node::{YamlNode, YamlValue};
+use crate::ui::tree_view::{format_collapsed_preview, lookup_dotted};
+
+/// One distinct value of the faceted field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FacetValue {
+    /// The value as shown in the panel, or `None` for records without the field
+    pub value: Option<String>,
+    /// Indexes of the records with this value, in document order
+    pub records: Vec<usize>,
+}
+
+impl FacetValue {
+    /// Returns the value as shown in the panel and status line.
+    pub fn label(&self) -> &str {
+        self.value.as_deref().unwrap_or("(missing)")
+    }
+}
+
+/// Groups `records` by the value of the dotted `field`, most common value
+/// first (ties in order of first appearance). Records without the field are
+/// grouped last.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::editor::facet::compute_facets;
+///
+/// let records: Vec<_> = ["level: info", "level: warn", "level: info", "msg: hi"]
+///     .iter()
+///     .map(|r| parse_yaml_auto(r).unwrap())
+///     .collect();
+/// let facets = compute_facets(&records, "level");
+/// let counts: Vec<_> = facets.iter().map(|f| (f.label(), f.records.len())).collect();
+/// assert_eq!(counts, [("info", 2), ("warn", 1), ("(missing)", 1)]);
+/// ```
+pub fn compute_facets(records: &[YamlNode], field: &str) -> Vec<FacetValue> {
+    let mut facets: Vec<FacetValue> = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        let value = lookup_dotted(record, field).map(facet_text);
+        match facets.iter_mut().find(|f| f.value == value) {
+            Some(facet) => facet.records.push(index),
+            None => facets.push(FacetValue {
+                value,
+                records: vec![index],
+            }),
+        }
+    }
+    // Stable sort keeps first-appearance order among equal counts
+    facets.sort_by_key(|f| (f.value.is_none(), std::cmp::Reverse(f.records.len())));
+    facets
+}
+
+fn facet_text(node: &YamlNode) -> String {
+    match node.value() {
+        YamlValue::String(s) => s.as_str().to_string(),
+        _ => format_collapsed_preview(node, 40),
+    }
+}
+
+/// Selection state for the `:facet` panel.
+#[derive(Debug, Clone)]
+pub struct FacetPanelState {
+    /// The dotted field being faceted
+    pub field: String,
+    /// Distinct values, most common first
+    pub values: Vec<FacetValue>,
+    /// Total number of records
+    pub total: usize,
+    /// Index of the highlighted value
+    pub selected_index: usize,
+}
+
+impl FacetPanelState {
+    /// Creates a panel over `values` with the first one selected.
+    pub fn new(field: String, values: Vec<FacetValue>, total: usize) -> Self {
+        Self {
+            field,
+            values,
+            total,
+            selected_index: 0,
+        }
+    }
+
+    /// Moves the selection up, wrapping to the bottom.
+    pub fn previous(&mut self) {
+        if !self.values.is_empty() {
+            self.selected_index = (self.selected_index + self.values.len() - 1) % self.values.len();
+        }
+    }
+
+    /// Moves the selection down, wrapping to the top.
+    pub fn next(&mut self) {
+        if !self.values.is_empty() {
+            self.selected_index = (self.selected_index + 1) % self.values.len();
+        }
+    }
+
+    /// Returns the highlighted value.
+    pub fn selected(&self) -> Option<&FacetValue> {
+        self.values.get(self.selected_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    fn records(docs: &[&str]) -> Vec<YamlNode> {
+        docs.iter().map(|d| parse_yaml_auto(d).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_nested_fields_and_non_string_values() {
+        let records = records(&[
+            "status: {code: 200}",
+            "status: {code: 500}",
+            "status: {code: 200}",
+        ]);
+        let facets = compute_facets(&records, "status.code");
+        assert_eq!(
+            facets,
+            [
+                FacetValue {
+                    value: Some("200".to_string()),
+                    records: vec![0, 2],
+                },
+                FacetValue {
+                    value: Some("500".to_string()),
+                    records: vec![1],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_panel_wraps() {
+        let records = records(&["a: 1", "a: 2"]);
+        let mut panel = FacetPanelState::new("a".to_string(), compute_facets(&records, "a"), 2);
+        panel.previous();
+        assert_eq!(panel.selected().unwrap().label(), "2");
+        panel.next();
+        assert_eq!(panel.selected().unwrap().label(), "1");
+    }
+}
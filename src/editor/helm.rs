@@ -0,0 +1,254 @@
+//! Helm chart awareness for `values.yaml` buffers (`:usages`).
+//!
+//! Templates read values through `.Values.a.b` (or `$.Values.a.b`, or
+//! `index .Values "a" "b"`). Scanning every file under the chart's
+//! `templates/` directory for those references tells which templates use a
+//! values key, and which keys nothing uses at all.
+//!
+//! A reference covers everything below it (`toYaml .Values.resources` uses
+//! `resources.limits.cpu`) and everything above it, so a key counts as used
+//! when its path and a reference's path start the same way.
+
+use crate::document::node::{YamlNode, YamlValue};
+use anyhow::{bail, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Source name for the diagnostics `:usages unused` reports.
+pub const HELM_SOURCE: &str = "helm";
+
+/// A `.Values` reference in a template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// Template path relative to the chart directory
+    pub file: String,
+    /// 1-based line number
+    pub line: usize,
+    /// Keys after `.Values`; empty for `.Values` itself
+    pub path: Vec<String>,
+}
+
+impl Reference {
+    /// Returns true if the reference reads the value at `key_path`, or a
+    /// mapping containing it, or something inside it.
+    pub fn covers(&self, key_path: &[String]) -> bool {
+        self.path
+            .iter()
+            .zip(key_path)
+            .all(|(reference, key)| reference == key)
+    }
+}
+
+/// The `.Values` references of every template in a chart.
+#[derive(Debug, Clone, Default)]
+pub struct HelmIndex {
+    references: Vec<Reference>,
+}
+
+impl HelmIndex {
+    /// Indexes the templates of the chart at `chart_dir`.
+    pub fn build(chart_dir: &Path) -> Result<Self> {
+        let templates = chart_dir.join("templates");
+        if !templates.is_dir() {
+            bail!(
+                "No templates directory in {} (use :usages <chart-dir>)",
+                chart_dir.display()
+            );
+        }
+        let mut files = Vec::new();
+        collect_files(&templates, &mut files)?;
+        files.sort();
+
+        let mut references = Vec::new();
+        for file in files {
+            // Binary files and the like can't hold template references
+            let Ok(text) = fs::read_to_string(&file) else {
+                continue;
+            };
+            let name = file
+                .strip_prefix(chart_dir)
+                .unwrap_or(&file)
+                .to_string_lossy()
+                .to_string();
+            references.extend(scan(&text).into_iter().map(|(line, path)| Reference {
+                file: name.clone(),
+                line,
+                path,
+            }));
+        }
+        Ok(Self { references })
+    }
+
+    /// Returns every reference.
+    pub fn references(&self) -> &[Reference] {
+        &self.references
+    }
+
+    /// Returns the references that read the value at `key_path`.
+    pub fn usages(&self, key_path: &[String]) -> Vec<&Reference> {
+        self.references
+            .iter()
+            .filter(|r| r.covers(key_path))
+            .collect()
+    }
+
+    /// Returns the tree paths and dotted names of the values keys no
+    /// template references, outermost first: below an unused mapping, only
+    /// the mapping itself is listed.
+    pub fn unused(&self, values: &YamlNode) -> Vec<(Vec<usize>, String)> {
+        let mut unused = Vec::new();
+        self.collect_unused(values, &mut Vec::new(), &mut Vec::new(), &mut unused);
+        unused
+    }
+
+    fn collect_unused(
+        &self,
+        node: &YamlNode,
+        path: &mut Vec<usize>,
+        keys: &mut Vec<String>,
+        unused: &mut Vec<(Vec<usize>, String)>,
+    ) {
+        let YamlValue::Object(entries) = node.value() else {
+            return;
+        };
+        for (index, (key, child)) in entries.iter().enumerate() {
+            if matches!(child.value(), YamlValue::Comment(_)) {
+                continue;
+            }
+            path.push(index);
+            keys.push(key.clone());
+            let covering: Vec<&Reference> = self.usages(keys);
+            if covering.is_empty() {
+                unused.push((path.clone(), keys.join(".")));
+            } else if covering.iter().all(|r| r.path.len() > keys.len()) {
+                // Only deeper keys are read: check each of them
+                self.collect_unused(child, path, keys, unused);
+            }
+            keys.pop();
+            path.pop();
+        }
+    }
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Finds the `.Values` references in template text, with their line numbers.
+pub fn scan(text: &str) -> Vec<(usize, Vec<String>)> {
+    let is_key_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut found = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let mut rest = line;
+        while let Some(start) = rest.find(".Values") {
+            let before = &rest[..start];
+            let mut after = &rest[start + ".Values".len()..];
+            rest = after;
+            // `.ValuesFile` or `foo.Values` are something else
+            if after.starts_with(is_key_char)
+                || before.ends_with(|c: char| is_key_char(c) || c == '.')
+            {
+                continue;
+            }
+
+            let mut path = Vec::new();
+            while let Some(tail) = after.strip_prefix('.') {
+                let end = tail.find(|c: char| !is_key_char(c)).unwrap_or(tail.len());
+                if end == 0 {
+                    break;
+                }
+                path.push(tail[..end].to_string());
+                after = &tail[end..];
+            }
+            // `index .Values "a" "b"` names keys as quoted arguments
+            if before.trim_end_matches('$').trim_end().ends_with("index") {
+                let mut args = after.trim_start();
+                while let Some(quoted) = args.strip_prefix('"') {
+                    let Some(end) = quoted.find('"') else {
+                        break;
+                    };
+                    path.push(quoted[..end].to_string());
+                    args = quoted[end + 1..].trim_start();
+                }
+            }
+            found.push((number + 1, path));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    fn keys(path: &[&str]) -> Vec<String> {
+        path.iter().map(|k| k.to_string()).collect()
+    }
+
+    #[test]
+    fn test_scan_finds_references() {
+        let text = "\
+image: {{ .Values.image.repository }}:{{ .Values.image.tag | default .Chart.AppVersion }}
+{{- with $.Values.podAnnotations }}
+resources: {{ toYaml .Values | nindent 2 }}
+host: {{ index .Values \"ingress\" \"host-name\" }} {{ index $.Values \"tls\" }}
+file: {{ .ValuesFile }} {{ .Release.Values.x }}
+";
+        let found = scan(text);
+        assert_eq!(
+            found,
+            vec![
+                (1, keys(&["image", "repository"])),
+                (1, keys(&["image", "tag"])),
+                (2, keys(&["podAnnotations"])),
+                (3, vec![]),
+                (4, keys(&["ingress", "host-name"])),
+                (4, keys(&["tls"])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_usages_and_unused() {
+        let index = HelmIndex {
+            references: vec![
+                Reference {
+                    file: "templates/deployment.yaml".to_string(),
+                    line: 3,
+                    path: keys(&["image", "tag"]),
+                },
+                Reference {
+                    file: "templates/deployment.yaml".to_string(),
+                    line: 9,
+                    path: keys(&["resources"]),
+                },
+            ],
+        };
+        assert_eq!(index.usages(&keys(&["image"])).len(), 1);
+        assert_eq!(
+            index.usages(&keys(&["resources", "limits", "cpu"])).len(),
+            1
+        );
+        assert!(index.usages(&keys(&["image", "pullPolicy"])).is_empty());
+
+        let values = parse_yaml_auto(
+            "image:\n  tag: v1\n  pullPolicy: Always\nresources:\n  limits: {}\nlegacy:\n  enabled: true\n",
+        )
+        .unwrap();
+        let unused: Vec<String> = index
+            .unused(&values)
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+        assert_eq!(unused, ["image.pullPolicy", "legacy"]);
+    }
+}
@@ -0,0 +1,83 @@
+//! Hint mode (`s`): a label on every line on screen, typed to jump there.
+//!
+//! Lines nearest the cursor get the single-letter labels, so short hops stay
+//! one keystroke. When the screen holds more lines than there are letters,
+//! the last letters stop being labels of their own and start two-letter
+//! labels instead; no label is the start of another, so typing the first
+//! letter of a two-letter label narrows the hints to the lines it starts.
+
+use super::keyjump::LABEL_LETTERS;
+
+/// Hint mode in progress.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hints {
+    /// The letters typed so far
+    pub typed: String,
+    /// Lines on screen, nearest the cursor first, with their labels
+    pub targets: Vec<(Vec<usize>, String)>,
+}
+
+impl Hints {
+    /// Returns the targets whose labels start with the letters typed so far.
+    pub fn remaining(&self) -> impl Iterator<Item = &(Vec<usize>, String)> {
+        self.targets
+            .iter()
+            .filter(|(_, label)| label.starts_with(&self.typed))
+    }
+}
+
+/// Returns `count` labels, shortest first, none the start of another.
+///
+/// Past the number of label letters, the fewest letters needed turn into
+/// prefixes of two-letter labels, taken from the end of [`LABEL_LETTERS`] so
+/// the home row stays single. Labels run out at two letters each.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::hints::labels;
+///
+/// assert_eq!(labels(3), ["a", "s", "d"]);
+///
+/// let labels = labels(30);
+/// assert_eq!(labels[24], "n");
+/// assert_eq!(&labels[25..], ["ma", "ms", "md", "mf", "mg"]);
+/// ```
+pub fn labels(count: usize) -> Vec<String> {
+    let letters: Vec<char> = LABEL_LETTERS.chars().collect();
+    let n = letters.len();
+    let prefixes = (0..=n).find(|&p| (n - p) + p * n >= count).unwrap_or(n);
+    let (singles, prefix_letters) = letters.split_at(n - prefixes);
+    singles
+        .iter()
+        .map(|c| c.to_string())
+        .chain(prefix_letters.iter().rev().flat_map(|prefix| {
+            letters
+                .iter()
+                .map(move |c| [*prefix, *c].iter().collect::<String>())
+        }))
+        .take(count)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_labels_are_prefix_free() {
+        for count in [1, 26, 27, 90, 676] {
+            let labels = labels(count);
+            assert_eq!(labels.len(), count);
+            let unique: HashSet<_> = labels.iter().collect();
+            assert_eq!(unique.len(), count);
+            for a in &labels {
+                for b in &labels {
+                    assert!(a == b || !b.starts_with(a.as_str()), "{} {}", a, b);
+                }
+            }
+        }
+        assert_eq!(labels(1000).len(), 676);
+    }
+}
@@ -0,0 +1,201 @@
+//! Variable interpolation for the `:set resolve-env` preview.
+//!
+//! Values are interpolated the way docker compose does it:
+//!
+//! - `$VAR` and `${VAR}` are replaced by the variable, empty when unset
+//! - `${VAR:-default}` uses `default` when `VAR` is unset or empty,
+//!   `${VAR-default}` only when it is unset
+//! - `${VAR:+other}` uses `other` when `VAR` is set and not empty,
+//!   `${VAR+other}` whenever it is set
+//! - `${VAR:?message}` and `${VAR?message}` require `VAR`; here they leave
+//!   it empty and report it missing
+//! - `$$` is a literal `$`
+//!
+//! Defaults may themselves contain placeholders (`${A:-${B}}`).
+
+use std::collections::HashMap;
+
+/// The result of interpolating a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpolated {
+    /// The value with its placeholders replaced
+    pub text: String,
+    /// Variables that were needed but unset, in the order they appear
+    pub missing: Vec<String>,
+}
+
+/// Replaces the placeholders in `text` with values from `vars`.
+///
+/// Returns `None` if `text` has no placeholders, so values that don't use
+/// interpolation can be told apart from those that resolve to themselves.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use yamlquill::editor::interpolate::interpolate;
+///
+/// let vars = HashMap::from([("TAG".to_string(), "1.27".to_string())]);
+/// let resolved = interpolate("nginx:${TAG}", &vars).unwrap();
+/// assert_eq!(resolved.text, "nginx:1.27");
+///
+/// let resolved = interpolate("${PORT:-8080}", &vars).unwrap();
+/// assert_eq!(resolved.text, "8080");
+///
+/// let resolved = interpolate("$HOST:80", &vars).unwrap();
+/// assert_eq!(resolved.text, ":80");
+/// assert_eq!(resolved.missing, vec!["HOST"]);
+///
+/// assert!(interpolate("plain", &vars).is_none());
+/// ```
+pub fn interpolate(text: &str, vars: &HashMap<String, String>) -> Option<Interpolated> {
+    if !text.contains('$') {
+        return None;
+    }
+    let mut missing = Vec::new();
+    let (resolved, found) = expand(text, vars, &mut missing);
+    found.then_some(Interpolated {
+        text: resolved,
+        missing,
+    })
+}
+
+/// Expands the placeholders in `text`; the flag is true if there were any.
+fn expand(text: &str, vars: &HashMap<String, String>, missing: &mut Vec<String>) -> (String, bool) {
+    let mut out = String::new();
+    let mut found = false;
+    let mut rest = text;
+    while let Some(dollar) = rest.find('$') {
+        out.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        if let Some(tail) = after.strip_prefix('$') {
+            out.push('$');
+            found = true;
+            rest = tail;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let Some(end) = closing_brace(body) else {
+                // An unclosed brace isn't a placeholder
+                out.push_str(&rest[dollar..]);
+                return (out, found);
+            };
+            out.push_str(&braced(&body[..end], vars, missing));
+            found = true;
+            rest = &body[end + 1..];
+        } else {
+            let name_len = name_length(after);
+            if name_len == 0 {
+                out.push('$');
+            } else {
+                out.push_str(&lookup(&after[..name_len], vars, missing));
+                found = true;
+            }
+            rest = &after[name_len..];
+        }
+    }
+    out.push_str(rest);
+    (out, found)
+}
+
+/// Resolves the inside of a `${...}` placeholder.
+fn braced(body: &str, vars: &HashMap<String, String>, missing: &mut Vec<String>) -> String {
+    let name_len = name_length(body);
+    let (name, modifier) = body.split_at(name_len);
+    let value = vars.get(name);
+    let set = value.is_some();
+    let set_and_not_empty = value.is_some_and(|v| !v.is_empty());
+    // Which operand a modifier uses: the variable, its word (after the
+    // modifier's `:-`, `-`, `:+` or `+`) or nothing
+    let (word_len, use_word, use_value) = match modifier.get(..2) {
+        _ if modifier.is_empty() => return lookup(name, vars, missing),
+        Some(":-") => (2, !set_and_not_empty, set_and_not_empty),
+        Some(":+") => (2, set_and_not_empty, false),
+        _ if modifier.starts_with('-') => (1, !set, set),
+        _ if modifier.starts_with('+') => (1, set, false),
+        _ => return lookup(name, vars, missing),
+    };
+    if use_word {
+        expand(&modifier[word_len..], vars, missing).0
+    } else if use_value {
+        value.cloned().unwrap_or_default()
+    } else {
+        String::new()
+    }
+}
+
+/// Returns a variable's value, noting it as missing when unset.
+fn lookup(name: &str, vars: &HashMap<String, String>, missing: &mut Vec<String>) -> String {
+    match vars.get(name) {
+        Some(value) => value.clone(),
+        None => {
+            if !missing.iter().any(|m| m == name) {
+                missing.push(name.to_string());
+            }
+            String::new()
+        }
+    }
+}
+
+/// Returns the length of the variable name at the start of `text`.
+fn name_length(text: &str) -> usize {
+    let mut chars = text.char_indices();
+    match chars.next() {
+        Some((_, c)) if c == '_' || c.is_ascii_alphabetic() => {}
+        _ => return 0,
+    }
+    chars
+        .find(|(_, c)| *c != '_' && !c.is_ascii_alphanumeric())
+        .map_or(text.len(), |(i, _)| i)
+}
+
+/// Returns the index of the `}` closing a placeholder, skipping nested ones.
+fn closing_brace(body: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in body.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(i),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> HashMap<String, String> {
+        HashMap::from([
+            ("TAG".to_string(), "1.27".to_string()),
+            ("EMPTY".to_string(), String::new()),
+        ])
+    }
+
+    fn resolve(text: &str) -> String {
+        interpolate(text, &vars()).unwrap().text
+    }
+
+    #[test]
+    fn test_defaults_and_alternatives() {
+        assert_eq!(resolve("${EMPTY:-x}"), "x");
+        assert_eq!(resolve("${EMPTY-x}"), "");
+        assert_eq!(resolve("${UNSET-x}"), "x");
+        assert_eq!(resolve("${TAG:+set}"), "set");
+        assert_eq!(resolve("${EMPTY:+set}"), "");
+        assert_eq!(resolve("${EMPTY+set}"), "set");
+        assert_eq!(resolve("${UNSET:-${TAG}}"), "1.27");
+    }
+
+    #[test]
+    fn test_escapes_and_missing() {
+        assert_eq!(resolve("cost: $$5"), "cost: $5");
+        assert_eq!(resolve("$ $TAG"), "$ 1.27");
+        assert!(interpolate("a $ b", &vars()).is_none());
+        assert!(interpolate("${open", &vars()).is_none());
+
+        let resolved = interpolate("${DB:?set DB} $DB ${TAG}", &vars()).unwrap();
+        assert_eq!(resolved.text, "  1.27");
+        assert_eq!(resolved.missing, vec!["DB"]);
+    }
+}
@@ -93,4 +93,26 @@ impl JumpList {
     pub fn current_position(&self) -> usize {
         self.current
     }
+
+    /// Replaces every stored path with `f(path)`, dropping jumps it maps to `None`.
+    pub fn remap_paths(&mut self, mut f: impl FnMut(&[usize]) -> Option<Vec<usize>>) {
+        let mut index = 0;
+        self.jumps.retain_mut(|path| {
+            let kept = match f(path) {
+                Some(new_path) => {
+                    *path = new_path;
+                    true
+                }
+                None => {
+                    if index < self.current {
+                        self.current -= 1;
+                    }
+                    false
+                }
+            };
+            index += 1;
+            kept
+        });
+        self.current = self.current.min(self.jumps.len().saturating_sub(1));
+    }
 }
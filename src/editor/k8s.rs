@@ -0,0 +1,578 @@
+//! Kubernetes helpers (`:set k8s`): `:containers`, `:images` and `:env`,
+//! and the checks of resource quantities and durations.
+//!
+//! A container is any mapping in a list under a `containers`,
+//! `initContainers` or `ephemeralContainers` key, wherever that list sits:
+//! a Pod's `spec`, a Deployment's `spec.template.spec`, a CronJob's
+//! `spec.jobTemplate.spec.template.spec`, or an item of a `List`.
+
+use crate::document::node::{YamlNode, YamlValue};
+use crate::editor::diagnostics::{Severity, ValueProblem};
+
+/// Keys whose list elements are containers.
+pub const CONTAINER_KEYS: &[&str] = &["containers", "initContainers", "ephemeralContainers"];
+
+/// A container found in the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Container {
+    /// Path of the container's mapping
+    pub path: Vec<usize>,
+    /// The container's `name`, or empty
+    pub name: String,
+    /// The container's `image`, or empty
+    pub image: String,
+}
+
+/// Returns every container under `root`, in document order.
+pub fn containers(root: &YamlNode) -> Vec<Container> {
+    let mut found = Vec::new();
+    collect(root, &mut Vec::new(), &mut found);
+    found
+}
+
+fn collect(node: &YamlNode, path: &mut Vec<usize>, found: &mut Vec<Container>) {
+    match node.value() {
+        YamlValue::Object(fields) => {
+            for (index, (key, child)) in fields.iter().enumerate() {
+                path.push(index);
+                match child.value() {
+                    YamlValue::Array(items) if CONTAINER_KEYS.contains(&key.as_str()) => {
+                        for (item_index, item) in items.iter().enumerate() {
+                            if let YamlValue::Object(container) = item.value() {
+                                let text =
+                                    |field: &str| match container.get(field).map(|n| n.value()) {
+                                        Some(YamlValue::String(s)) => s.as_str().to_string(),
+                                        _ => String::new(),
+                                    };
+                                path.push(item_index);
+                                found.push(Container {
+                                    path: path.clone(),
+                                    name: text("name"),
+                                    image: text("image"),
+                                });
+                                path.pop();
+                            }
+                        }
+                    }
+                    _ => collect(child, path, found),
+                }
+                path.pop();
+            }
+        }
+        YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+            for (index, item) in items.iter().enumerate() {
+                path.push(index);
+                collect(item, path, found);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns the path of the container containing `path` (or at `path`).
+pub fn enclosing_container(root: &YamlNode, path: &[usize]) -> Option<Vec<usize>> {
+    containers(root)
+        .into_iter()
+        .map(|c| c.path)
+        .find(|container| path.starts_with(container))
+}
+
+/// Splits an image reference into its repository and tag.
+///
+/// A digest (`@sha256:...`) counts as the tag, and an image without one
+/// has an empty tag. A `:` before the last `/` belongs to a registry port.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::k8s::split_image;
+///
+/// assert_eq!(split_image("nginx:1.25"), ("nginx", ":1.25"));
+/// assert_eq!(split_image("localhost:5000/app"), ("localhost:5000/app", ""));
+/// ```
+pub fn split_image(image: &str) -> (&str, &str) {
+    let name_start = image.rfind('/').map_or(0, |slash| slash + 1);
+    let tag_start = image
+        .find('@')
+        .or_else(|| {
+            image[name_start..]
+                .find(':')
+                .map(|colon| name_start + colon)
+        })
+        .unwrap_or(image.len());
+    image.split_at(tag_start)
+}
+
+/// Returns true if `filter` names the image's repository: either all of it
+/// (`docker.io/library/nginx`) or its last segment (`nginx`).
+pub fn image_matches(image: &str, filter: &str) -> bool {
+    let (repository, _) = split_image(image);
+    repository == filter || repository.rsplit('/').next() == Some(filter)
+}
+
+/// Source name for the diagnostics quantity and duration checks report.
+pub const K8S_SOURCE: &str = "k8s";
+
+/// Keys of mappings whose values are resource quantities, as in
+/// `resources.requests` or a ResourceQuota's `hard`.
+const QUANTITY_MAPS: &[&str] = &[
+    "requests",
+    "limits",
+    "hard",
+    "capacity",
+    "allocatable",
+    "overhead",
+];
+
+/// Fields that hold a quantity themselves.
+const QUANTITY_FIELDS: &[&str] = &["sizeLimit"];
+
+/// Fields that hold a Go duration string (`30s`, `1h30m`), as used by
+/// Flux, cert-manager and Argo resources.
+const DURATION_FIELDS: &[&str] = &[
+    "interval",
+    "timeout",
+    "retryInterval",
+    "duration",
+    "renewBefore",
+    "maxDuration",
+];
+
+/// Suffixes of binary quantities, in increasing order.
+const BINARY_SUFFIXES: &[&str] = &["Ki", "Mi", "Gi", "Ti", "Pi", "Ei"];
+
+/// Suffixes of decimal quantities.
+const DECIMAL_SUFFIXES: &[&str] = &["n", "u", "m", "", "k", "M", "G", "T", "P", "E"];
+
+/// Checks resource quantities and durations in known fields under `root`.
+///
+/// Quantities are the values of `requests`, `limits` and similar mappings;
+/// an invalid one (`2GB`) is an error, and a valid one that is probably a
+/// typo is a warning: a decimal unit for memory (`2G` instead of `2Gi`),
+/// millis of memory (`500m`) or mega-CPUs (`500M`). Duration fields must be
+/// Go durations (`5m`, not `5min`), and `*Seconds` fields plain numbers.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::editor::k8s::check_values;
+///
+/// let root = parse_yaml_auto("resources:\n  limits:\n    memory: 2G\n").unwrap();
+/// let problems = check_values(&root);
+/// assert_eq!(problems[0].message, "2G is 2×10^9 bytes; did you mean 2Gi?");
+/// ```
+pub fn check_values(root: &YamlNode) -> Vec<ValueProblem> {
+    let mut problems = Vec::new();
+    check_node(root, &mut Vec::new(), &mut problems);
+    problems
+}
+
+fn check_node(node: &YamlNode, path: &mut Vec<usize>, problems: &mut Vec<ValueProblem>) {
+    match node.value() {
+        YamlValue::Object(fields) => {
+            for (index, (key, child)) in fields.iter().enumerate() {
+                path.push(index);
+                match child.value() {
+                    YamlValue::Object(resources) if QUANTITY_MAPS.contains(&key.as_str()) => {
+                        for (index, (resource, value)) in resources.iter().enumerate() {
+                            if let YamlValue::String(text) = value.value() {
+                                path.push(index);
+                                if let Some((severity, message)) =
+                                    check_quantity(resource, text.as_str())
+                                {
+                                    problems.push(ValueProblem {
+                                        path: path.clone(),
+                                        severity,
+                                        message,
+                                    });
+                                }
+                                path.pop();
+                            }
+                        }
+                    }
+                    YamlValue::String(text) => {
+                        let text = text.as_str();
+                        let problem = if QUANTITY_FIELDS.contains(&key.as_str()) {
+                            check_quantity("memory", text)
+                        } else if DURATION_FIELDS.contains(&key.as_str()) {
+                            check_duration(text)
+                        } else if key.ends_with("Seconds") {
+                            Some(seconds_problem(key, text))
+                        } else {
+                            None
+                        };
+                        if let Some((severity, message)) = problem {
+                            problems.push(ValueProblem {
+                                path: path.clone(),
+                                severity,
+                                message,
+                            });
+                        }
+                    }
+                    _ => check_node(child, path, problems),
+                }
+                path.pop();
+            }
+        }
+        YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+            for (index, item) in items.iter().enumerate() {
+                path.push(index);
+                check_node(item, path, problems);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Splits a quantity into its number and suffix, if it is one.
+///
+/// The number is an optionally signed decimal; the suffix is a binary or
+/// decimal unit or an exponent (`1e3`).
+fn split_quantity(text: &str) -> Option<(&str, &str)> {
+    let unsigned = text.strip_prefix(['+', '-']).unwrap_or(text);
+    let digits = unsigned
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(unsigned.len());
+    let number = &unsigned[..digits];
+    let suffix = &unsigned[digits..];
+    let valid_number =
+        number.matches('.').count() <= 1 && number.chars().any(|c| c.is_ascii_digit());
+    let exponent = suffix
+        .strip_prefix(['e', 'E'])
+        .map(|exp| exp.strip_prefix(['+', '-']).unwrap_or(exp))
+        .is_some_and(|exp| !exp.is_empty() && exp.chars().all(|c| c.is_ascii_digit()));
+    let valid_suffix =
+        BINARY_SUFFIXES.contains(&suffix) || DECIMAL_SUFFIXES.contains(&suffix) || exponent;
+    (valid_number && valid_suffix).then_some((number, suffix))
+}
+
+/// Checks the quantity `text` given for `resource` (`cpu`, `memory`, ...).
+fn check_quantity(resource: &str, text: &str) -> Option<(Severity, String)> {
+    let bytes = ["memory", "storage", "hugepages"]
+        .iter()
+        .any(|unit| resource.contains(unit));
+    let Some((number, suffix)) = split_quantity(text.trim()) else {
+        // Suggest the binary unit for sizes like `2GB` or `2gi`
+        let number_end = text.find(|c: char| !c.is_ascii_digit() && c != '.');
+        let suggestion = number_end.and_then(|end| {
+            let unit = text[end..]
+                .trim_end_matches(['b', 'B'])
+                .trim_end_matches(['i', 'I']);
+            let index = "kmgtpe".find(unit.to_ascii_lowercase().as_str())?;
+            (unit.len() == 1 && bytes)
+                .then(|| format!("{}{}", &text[..end], BINARY_SUFFIXES[index]))
+        });
+        return Some((
+            Severity::Error,
+            match suggestion {
+                Some(suggestion) => {
+                    format!("{} is not a quantity; did you mean {}?", text, suggestion)
+                }
+                None => format!("{} is not a quantity like 500m, 2 or 2Gi", text),
+            },
+        ));
+    };
+
+    if bytes {
+        if let Some(index) = ["k", "M", "G", "T", "P", "E"]
+            .iter()
+            .position(|s| *s == suffix)
+        {
+            return Some((
+                Severity::Warning,
+                format!(
+                    "{} is {}×10^{} bytes; did you mean {}{}?",
+                    text,
+                    number,
+                    3 * (index + 1),
+                    number,
+                    BINARY_SUFFIXES[index]
+                ),
+            ));
+        }
+        if suffix == "m" {
+            return Some((
+                Severity::Warning,
+                format!(
+                    "{} is thousandths of a byte; did you mean {}Mi?",
+                    text, number
+                ),
+            ));
+        }
+    } else if resource.contains("cpu") && suffix == "M" {
+        return Some((
+            Severity::Warning,
+            format!(
+                "{} is {} million CPUs; did you mean {}m?",
+                text, number, number
+            ),
+        ));
+    }
+    None
+}
+
+/// Parses a Go duration (`300ms`, `1h30m`, `-1.5h`) into seconds.
+fn duration_seconds(text: &str) -> Option<f64> {
+    let unsigned = text.strip_prefix(['+', '-']).unwrap_or(text);
+    if unsigned == "0" {
+        return Some(0.0);
+    }
+    let mut rest = unsigned;
+    let mut seconds = 0.0;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_end] {
+            "ns" => 1e-9,
+            "us" | "µs" | "μs" => 1e-6,
+            "ms" => 1e-3,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return None,
+        };
+        seconds += number * scale;
+        rest = &rest[unit_end..];
+    }
+    Some(if text.starts_with('-') {
+        -seconds
+    } else {
+        seconds
+    })
+}
+
+/// Rewrites a duration written with other units (`5min`, `1d`, `30`) as a
+/// Go duration.
+fn suggest_duration(text: &str) -> Option<String> {
+    let mut suggestion = String::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let number: u64 = rest[..digits].parse().ok()?;
+        rest = rest[digits..].trim_start();
+        let unit_end = rest
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(rest.len());
+        let (number, unit) = match rest[..unit_end].to_lowercase().as_str() {
+            "d" | "day" | "days" => (number * 24, "h"),
+            "h" | "hr" | "hrs" | "hour" | "hours" => (number, "h"),
+            "m" | "min" | "mins" | "minute" | "minutes" => (number, "m"),
+            "" | "s" | "sec" | "secs" | "second" | "seconds" => (number, "s"),
+            "ms" => (number, "ms"),
+            _ => return None,
+        };
+        suggestion.push_str(&format!("{}{}", number, unit));
+        rest = rest[unit_end..].trim_start();
+    }
+    Some(suggestion)
+}
+
+/// Checks a Go duration string.
+fn check_duration(text: &str) -> Option<(Severity, String)> {
+    if duration_seconds(text).is_some() {
+        return None;
+    }
+    Some((
+        Severity::Error,
+        match suggest_duration(text) {
+            Some(suggestion) => format!("{} is not a duration; did you mean {}?", text, suggestion),
+            None => format!("{} is not a duration like 30s, 5m or 1h30m", text),
+        },
+    ))
+}
+
+/// Describes a string given for a `*Seconds` field, which takes a number.
+fn seconds_problem(key: &str, text: &str) -> (Severity, String) {
+    let seconds = text.trim().parse::<u64>().ok().or_else(|| {
+        duration_seconds(text.trim())
+            .filter(|s| *s >= 0.0 && s.fract() == 0.0)
+            .map(|s| s as u64)
+    });
+    (
+        Severity::Error,
+        match seconds {
+            Some(seconds) => format!(
+                "{} takes a number of seconds; did you mean {}?",
+                key, seconds
+            ),
+            None => format!("{} takes a number of seconds", key),
+        },
+    )
+}
+
+/// Selection state for the `:containers` picker.
+#[derive(Debug, Clone)]
+pub struct ContainerPickerState {
+    /// Containers of the document, in document order
+    pub containers: Vec<Container>,
+    /// Index of the highlighted container
+    pub selected_index: usize,
+}
+
+impl ContainerPickerState {
+    /// Creates a picker over `containers` with the first one selected.
+    pub fn new(containers: Vec<Container>) -> Self {
+        Self {
+            containers,
+            selected_index: 0,
+        }
+    }
+
+    /// Moves the selection up, wrapping to the bottom.
+    pub fn previous(&mut self) {
+        if !self.containers.is_empty() {
+            self.selected_index =
+                (self.selected_index + self.containers.len() - 1) % self.containers.len();
+        }
+    }
+
+    /// Moves the selection down, wrapping to the top.
+    pub fn next(&mut self) {
+        if !self.containers.is_empty() {
+            self.selected_index = (self.selected_index + 1) % self.containers.len();
+        }
+    }
+
+    /// Returns the highlighted container.
+    pub fn selected(&self) -> Option<&Container> {
+        self.containers.get(self.selected_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    #[test]
+    fn test_containers_found_at_any_depth() {
+        let yaml = "\
+kind: Deployment
+spec:
+  template:
+    spec:
+      initContainers:
+        - name: migrate
+          image: app:1.0
+      containers:
+        - name: app
+          image: registry:5000/team/app:1.0
+        - name: sidecar
+          image: envoy@sha256:abc
+";
+        let root = parse_yaml_auto(yaml).unwrap();
+        let found = containers(&root);
+        let names: Vec<_> = found.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["migrate", "app", "sidecar"]);
+        assert_eq!(found[1].path, vec![1, 0, 0, 1, 0]);
+        assert_eq!(
+            enclosing_container(&root, &[1, 0, 0, 1, 1, 0]),
+            Some(vec![1, 0, 0, 1, 1])
+        );
+        assert_eq!(enclosing_container(&root, &[0]), None);
+    }
+
+    #[test]
+    fn test_split_and_match_images() {
+        assert_eq!(
+            split_image("registry:5000/team/app:1.0"),
+            ("registry:5000/team/app", ":1.0")
+        );
+        assert_eq!(split_image("envoy@sha256:abc"), ("envoy", "@sha256:abc"));
+        assert!(image_matches("registry:5000/team/app:1.0", "app"));
+        assert!(image_matches(
+            "registry:5000/team/app:1.0",
+            "registry:5000/team/app"
+        ));
+        assert!(!image_matches("registry:5000/team/app:1.0", "team"));
+    }
+
+    #[test]
+    fn test_quantities_and_durations() {
+        let yaml = "\
+resources:
+  requests:
+    cpu: 500M
+    memory: 500m
+  limits:
+    cpu: 250m
+    memory: 2GB
+    ephemeral-storage: 1.5Gi
+spec:
+  interval: 5min
+  timeout: 1h30m
+  periodSeconds: 30s
+";
+        let root = parse_yaml_auto(yaml).unwrap();
+        let problems: Vec<_> = check_values(&root)
+            .into_iter()
+            .map(|p| (p.path, p.severity, p.message))
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                (
+                    vec![0, 0, 0],
+                    Severity::Warning,
+                    "500M is 500 million CPUs; did you mean 500m?".to_string()
+                ),
+                (
+                    vec![0, 0, 1],
+                    Severity::Warning,
+                    "500m is thousandths of a byte; did you mean 500Mi?".to_string()
+                ),
+                (
+                    vec![0, 1, 1],
+                    Severity::Error,
+                    "2GB is not a quantity; did you mean 2Gi?".to_string()
+                ),
+                (
+                    vec![1, 0],
+                    Severity::Error,
+                    "5min is not a duration; did you mean 5m?".to_string()
+                ),
+                (
+                    vec![1, 2],
+                    Severity::Error,
+                    "periodSeconds takes a number of seconds; did you mean 30?".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duration_suggestions() {
+        assert_eq!(duration_seconds("1h30m"), Some(5400.0));
+        assert_eq!(duration_seconds("300ms"), Some(0.3));
+        assert_eq!(duration_seconds("30"), None);
+        assert_eq!(suggest_duration("1d").as_deref(), Some("24h"));
+        assert_eq!(suggest_duration("30").as_deref(), Some("30s"));
+        assert_eq!(suggest_duration("2 hours").as_deref(), Some("2h"));
+        assert_eq!(suggest_duration("soon"), None);
+    }
+
+    #[test]
+    fn test_picker_wraps() {
+        let container = |name: &str| Container {
+            path: vec![0],
+            name: name.to_string(),
+            image: String::new(),
+        };
+        let mut picker = ContainerPickerState::new(vec![container("a"), container("b")]);
+        picker.previous();
+        assert_eq!(picker.selected().unwrap().name, "b");
+        picker.next();
+        assert_eq!(picker.selected().unwrap().name, "a");
+    }
+}
@@ -0,0 +1,103 @@
+//! Jumping to a key on screen by typing its first letters (`f`).
+//!
+//! After `f`, each letter typed narrows the mapping keys on screen to those
+//! starting with what has been typed, ignoring case unless an uppercase
+//! letter is typed. A single match is jumped to at once; while there are
+//! several, each gets a one-letter label in the gutter, and typing a label
+//! jumps to its key. Labels never use a letter that would extend a match, so
+//! typing keeps narrowing for as long as it can. `Enter` jumps to the first
+//! match after the cursor.
+
+use std::collections::HashSet;
+
+/// Letters used for labels, home row first.
+pub const LABEL_LETTERS: &str = "asdfghjklqwertyuiopzxcvbnm";
+
+/// A key jump in progress.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyJump {
+    /// The letters typed so far
+    pub query: String,
+    /// Matching nodes, first match after the cursor first, with their labels
+    pub targets: Vec<(Vec<usize>, Option<char>)>,
+}
+
+impl KeyJump {
+    /// Returns the target labelled `label`, if any.
+    pub fn labelled(&self, label: char) -> Option<&[usize]> {
+        self.targets
+            .iter()
+            .find(|(_, l)| *l == Some(label))
+            .map(|(path, _)| path.as_slice())
+    }
+}
+
+/// Returns whether `key` starts with `query`, ignoring case unless `query`
+/// has an uppercase letter.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::keyjump::key_matches;
+///
+/// assert!(key_matches("Replicas", "rep"));
+/// assert!(!key_matches("replicas", "Rep"));
+/// ```
+pub fn key_matches(key: &str, query: &str) -> bool {
+    if query.chars().any(char::is_uppercase) {
+        key.starts_with(query)
+    } else {
+        key.to_lowercase().starts_with(query)
+    }
+}
+
+/// Returns the letters that would extend `query` for one of `keys`, as they
+/// would be typed.
+pub fn next_letters<'a>(keys: impl IntoIterator<Item = &'a str>, query: &str) -> HashSet<char> {
+    let smart_case = query.chars().any(char::is_uppercase);
+    let count = query.chars().count();
+    keys.into_iter()
+        .filter(|key| key_matches(key, query))
+        .filter_map(|key| key.chars().nth(count))
+        .flat_map(|c| {
+            if smart_case {
+                vec![c]
+            } else {
+                c.to_lowercase().chain(c.to_uppercase()).collect()
+            }
+        })
+        .collect()
+}
+
+/// Returns up to `count` labels from [`LABEL_LETTERS`], skipping `reserved`.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashSet;
+/// use yamlquill::editor::keyjump::labels;
+///
+/// assert_eq!(labels(3, &HashSet::from(['s'])), ['a', 'd', 'f']);
+/// ```
+pub fn labels(count: usize, reserved: &HashSet<char>) -> Vec<char> {
+    LABEL_LETTERS
+        .chars()
+        .filter(|c| !reserved.contains(c))
+        .take(count)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_letters_follow_smart_case() {
+        let keys = ["replicas", "resources", "Restart"];
+        assert_eq!(
+            next_letters(keys, "re"),
+            HashSet::from(['p', 'P', 's', 'S'])
+        );
+        assert_eq!(next_letters(keys, "Re"), HashSet::from(['s']));
+    }
+}
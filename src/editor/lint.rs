@@ -0,0 +1,537 @@
+//! Structural lint rules for YAML text (`:lint`).
+//!
+//! The rules look at the file as loaded, or as `:w` would write it once it
+//! has been edited, so they catch problems in how it is spelled rather than
+//! in what it means:
+//!
+//! - `duplicate-keys`: a key appears twice in one mapping; the last value wins
+//! - `empty-values`: a key or list item has no value, which reads as null
+//! - `trailing-whitespace`: a quoted or block scalar ends a line in spaces or tabs
+//! - `inconsistent-indent`: a line is indented by a different step than the
+//!   rest of the file
+//! - `non-portable-booleans`: an unquoted `yes`/`no`/`on`/`off` (and `y`/`n`),
+//!   booleans in YAML 1.1 but strings in YAML 1.2
+//! - `long-lines`: a line is longer than the configured limit
+//!
+//! Problems are reported against the node they were found at, with their
+//! line number, and fill the quickfix list ([`super::quickfix`]).
+
+use super::diagnostics::{Severity, ValueProblem};
+use crate::document::duplicates::duplicate_key;
+use crate::document::node::{YamlNode, YamlValue};
+use std::collections::HashSet;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::{Marker, TScalarStyle};
+
+/// Name of the lint diagnostics source.
+pub const LINT_SOURCE: &str = "lint";
+
+/// A lint rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// A key appears twice in one mapping
+    DuplicateKeys,
+    /// A key or list item has no value
+    EmptyValues,
+    /// A scalar has whitespace at the end of a line
+    TrailingWhitespace,
+    /// A line is indented by a different step than the rest of the file
+    InconsistentIndent,
+    /// An unquoted YAML 1.1 boolean such as `yes` or `off`
+    NonPortableBooleans,
+    /// A line is longer than the limit
+    LongLines,
+}
+
+impl Rule {
+    /// Every rule, in the order they are listed.
+    pub const ALL: [Rule; 6] = [
+        Rule::DuplicateKeys,
+        Rule::EmptyValues,
+        Rule::TrailingWhitespace,
+        Rule::InconsistentIndent,
+        Rule::NonPortableBooleans,
+        Rule::LongLines,
+    ];
+
+    /// Returns the rule's name, as used in the config and `:lint enable`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::DuplicateKeys => "duplicate-keys",
+            Rule::EmptyValues => "empty-values",
+            Rule::TrailingWhitespace => "trailing-whitespace",
+            Rule::InconsistentIndent => "inconsistent-indent",
+            Rule::NonPortableBooleans => "non-portable-booleans",
+            Rule::LongLines => "long-lines",
+        }
+    }
+
+    /// Returns the rule with the given name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::editor::lint::Rule;
+    ///
+    /// assert_eq!(Rule::from_name("long-lines"), Some(Rule::LongLines));
+    /// assert_eq!(Rule::from_name("tabs"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.name() == name)
+    }
+
+    fn severity(self) -> Severity {
+        match self {
+            Rule::DuplicateKeys => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+/// Unquoted words YAML 1.1 reads as booleans.
+const YAML11_BOOLEANS: [&str; 16] = [
+    "y", "Y", "yes", "Yes", "YES", "n", "N", "no", "No", "NO", "on", "On", "ON", "off", "Off",
+    "OFF",
+];
+
+/// Lints `text`, the source of the document under `root`.
+///
+/// Only the rules in `rules` are checked; lines longer than `max_line_length`
+/// characters are reported by `long-lines` (0 for no limit). Problems come
+/// back in line order, each message ending with its line and rule.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::editor::lint::{lint, Rule};
+///
+/// let text = "name: api\nname: web\nenabled: yes\n";
+/// let root = parse_yaml_auto(text).unwrap();
+/// let problems = lint(text, &root, &Rule::ALL, 120).unwrap();
+/// assert_eq!(problems[0].message, "Duplicate key `name` (line 2, duplicate-keys)");
+/// assert_eq!(problems[0].path, vec![1]);
+/// assert_eq!(problems[1].path, vec![2]);
+/// ```
+pub fn lint(
+    text: &str,
+    root: &YamlNode,
+    rules: &[Rule],
+    max_line_length: usize,
+) -> anyhow::Result<Vec<ValueProblem>> {
+    let mut walker = Walker {
+        rules,
+        ..Walker::default()
+    };
+    Parser::new(text.chars())
+        .load(&mut walker, true)
+        .map_err(|e| anyhow::anyhow!("Can't lint: {}", e))?;
+
+    let mut findings = walker.findings;
+    if rules.contains(&Rule::InconsistentIndent) {
+        findings.extend(check_indent(text, &walker.lines));
+    }
+    if rules.contains(&Rule::LongLines) && max_line_length > 0 {
+        for (i, line) in text.lines().enumerate() {
+            let length = line.chars().count();
+            if length > max_line_length {
+                findings.push(Finding {
+                    line: i + 1,
+                    segments: node_at_line(&walker.lines, i + 1),
+                    rule: Rule::LongLines,
+                    message: format!(
+                        "Line is {} characters long (limit {})",
+                        length, max_line_length
+                    ),
+                });
+            }
+        }
+    }
+    findings.sort_by_key(|finding| finding.line);
+
+    Ok(findings
+        .into_iter()
+        .map(|finding| ValueProblem {
+            path: resolve(root, &finding.segments),
+            severity: finding.rule.severity(),
+            message: format!(
+                "{} (line {}, {})",
+                finding.message,
+                finding.line,
+                finding.rule.name()
+            ),
+        })
+        .collect())
+}
+
+/// A step from a collection to one of its children, by key or by position
+/// among the non-comment items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug)]
+struct Finding {
+    line: usize,
+    segments: Vec<Segment>,
+    rule: Rule,
+    message: String,
+}
+
+#[derive(Debug)]
+enum Frame {
+    Sequence {
+        segments: Vec<Segment>,
+        next: usize,
+    },
+    Mapping {
+        segments: Vec<Segment>,
+        keys: HashSet<String>,
+        /// The key waiting for its value, and its line
+        key: Option<(String, usize)>,
+    },
+}
+
+/// Collects the event-based findings and the line each node starts on.
+#[derive(Debug, Default)]
+struct Walker<'a> {
+    rules: &'a [Rule],
+    stack: Vec<Frame>,
+    documents: usize,
+    /// Start line and path of every node, in document order
+    lines: Vec<(usize, Vec<Segment>)>,
+    findings: Vec<Finding>,
+}
+
+impl Walker<'_> {
+    fn report(&mut self, line: usize, segments: &[Segment], rule: Rule, message: String) {
+        if self.rules.contains(&rule) {
+            self.findings.push(Finding {
+                line,
+                segments: segments.to_vec(),
+                rule,
+                message,
+            });
+        }
+    }
+
+    /// Works out the path of the node at `mark` and the line it is reported
+    /// on: a mapping value's is its key's, as the parser marks empty and
+    /// block scalars where they end. Returns `None` for mapping keys, after
+    /// checking them for duplicates.
+    fn place(&mut self, mark: Marker, key: Option<&str>) -> Option<(Vec<Segment>, usize)> {
+        let (segments, line) = match self.stack.last_mut() {
+            None => {
+                self.documents += 1;
+                (vec![Segment::Index(self.documents - 1)], mark.line())
+            }
+            Some(Frame::Sequence { segments, next }) => {
+                *next += 1;
+                (
+                    [segments.as_slice(), &[Segment::Index(*next - 1)]].concat(),
+                    mark.line(),
+                )
+            }
+            Some(Frame::Mapping {
+                segments,
+                keys,
+                key: pending,
+            }) => match pending.take() {
+                Some((pending, line)) => (
+                    [segments.as_slice(), &[Segment::Key(pending)]].concat(),
+                    line,
+                ),
+                None => {
+                    // Complex keys (collections) are left unnamed
+                    let written = key.unwrap_or("?").to_string();
+                    let duplicate = key.is_some() && keys.contains(&written);
+                    // The tree keeps a repeat under a marked key, as the parser does
+                    let name = if duplicate {
+                        (2..)
+                            .map(|n| duplicate_key(&written, n))
+                            .find(|marked| !keys.contains(marked))
+                            .unwrap_or_default()
+                    } else {
+                        written.clone()
+                    };
+                    keys.insert(name.clone());
+                    let path = [segments.as_slice(), &[Segment::Key(name.clone())]].concat();
+                    *pending = Some((name, mark.line()));
+                    self.lines.push((mark.line(), path.clone()));
+                    if duplicate {
+                        self.report(
+                            mark.line(),
+                            &path,
+                            Rule::DuplicateKeys,
+                            format!("Duplicate key `{}`", written),
+                        );
+                    }
+                    return None;
+                }
+            },
+        };
+        self.lines.push((line, segments.clone()));
+        Some((segments, line))
+    }
+
+    fn check_scalar(
+        &mut self,
+        line: usize,
+        segments: &[Segment],
+        value: &str,
+        style: TScalarStyle,
+    ) {
+        let plain = style == TScalarStyle::Plain;
+        if plain && value.is_empty() {
+            self.report(
+                line,
+                segments,
+                Rule::EmptyValues,
+                "Empty value, read as null".to_string(),
+            );
+        }
+        if plain && YAML11_BOOLEANS.contains(&value) {
+            self.report(
+                line,
+                segments,
+                Rule::NonPortableBooleans,
+                format!(
+                    "`{}` is a boolean in YAML 1.1 but a string in YAML 1.2; quote it or use true/false",
+                    value
+                ),
+            );
+        }
+        if value.split('\n').any(|line| line.ends_with([' ', '\t'])) {
+            self.report(
+                line,
+                segments,
+                Rule::TrailingWhitespace,
+                "Trailing whitespace in the value".to_string(),
+            );
+        }
+    }
+}
+
+impl MarkedEventReceiver for Walker<'_> {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        match ev {
+            Event::Scalar(value, style, _, _) => {
+                if let Some((segments, line)) = self.place(mark, Some(&value)) {
+                    self.check_scalar(line, &segments, &value, style);
+                }
+            }
+            Event::Alias(_) => {
+                self.place(mark, Some("*"));
+            }
+            Event::SequenceStart(_, _) => {
+                let (segments, _) = self.place(mark, None).unwrap_or_default();
+                self.stack.push(Frame::Sequence { segments, next: 0 });
+            }
+            Event::MappingStart(_, _) => {
+                let (segments, _) = self.place(mark, None).unwrap_or_default();
+                self.stack.push(Frame::Mapping {
+                    segments,
+                    keys: HashSet::new(),
+                    key: None,
+                });
+            }
+            Event::SequenceEnd | Event::MappingEnd => {
+                self.stack.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns the path of the last node starting on or before `line`.
+fn node_at_line(lines: &[(usize, Vec<Segment>)], line: usize) -> Vec<Segment> {
+    let end = lines.partition_point(|(start, _)| *start <= line);
+    end.checked_sub(1)
+        .map(|i| lines[i].1.clone())
+        .unwrap_or_default()
+}
+
+/// Checks that every step into a nested block uses the same indent as the
+/// first one in the file. Block scalar contents, comments and blank lines
+/// are skipped; the text after a `- ` counts as indented by the dash.
+fn check_indent(text: &str, lines: &[(usize, Vec<Segment>)]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut unit = None;
+    let mut levels: Vec<usize> = vec![0];
+    let mut block_scalar: Option<usize> = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let content = line.trim_start_matches(' ');
+        let indent = line.len() - content.len();
+        if let Some(parent) = block_scalar {
+            if content.is_empty() || indent > parent {
+                continue;
+            }
+            block_scalar = None;
+        }
+        if content.is_empty() || content.starts_with('#') || line.starts_with("---") {
+            continue;
+        }
+
+        while levels.last().is_some_and(|level| *level > indent) {
+            levels.pop();
+        }
+        let top = levels.last().copied().unwrap_or(0);
+        if indent > top {
+            let step = indent - top;
+            let expected = *unit.get_or_insert(step);
+            if step != expected {
+                findings.push(Finding {
+                    line: i + 1,
+                    segments: node_at_line(lines, i + 1),
+                    rule: Rule::InconsistentIndent,
+                    message: format!(
+                        "Indented by {} spaces where the file uses {}",
+                        step, expected
+                    ),
+                });
+            }
+            levels.push(indent);
+        }
+
+        // The entry after a dash sets the indent for the rest of the item
+        let mut item = content;
+        let mut item_indent = indent;
+        while let Some(rest) = item.strip_prefix('-') {
+            let after = rest.trim_start_matches(' ');
+            if after.len() == rest.len() && !rest.is_empty() {
+                break;
+            }
+            item_indent += 1 + rest.len() - after.len();
+            item = after;
+            if !after.is_empty() {
+                levels.push(item_indent);
+            }
+        }
+
+        if starts_block_scalar(content) {
+            block_scalar = Some(indent);
+        }
+    }
+    findings
+}
+
+/// Returns true if the line ends with a block scalar indicator (`|`, `>-`, ...).
+fn starts_block_scalar(content: &str) -> bool {
+    let content = match content.find(" #") {
+        Some(comment) => &content[..comment],
+        None => content,
+    };
+    content.split_whitespace().last().is_some_and(|token| {
+        token.starts_with(['|', '>'])
+            && token[1..]
+                .chars()
+                .all(|c| matches!(c, '+' | '-' | '1'..='9'))
+    })
+}
+
+/// Turns segments into a tree path, stopping at the deepest node found.
+fn resolve(root: &YamlNode, segments: &[Segment]) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut node = root;
+    let mut segments = segments;
+    // The document index only counts when the file has several
+    if !matches!(root.value(), YamlValue::MultiDoc(_)) {
+        segments = segments.get(1..).unwrap_or_default();
+    }
+    for segment in segments {
+        let found = match (node.value(), segment) {
+            (YamlValue::Object(entries), Segment::Key(key)) => {
+                entries.get_index_of(key.as_str()).map(|i| (i, &entries[i]))
+            }
+            (YamlValue::Array(items) | YamlValue::MultiDoc(items), Segment::Index(n)) => items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| !item.is_comment())
+                .nth(*n),
+            _ => None,
+        };
+        let Some((index, child)) = found else {
+            break;
+        };
+        path.push(index);
+        node = child;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    fn messages(text: &str, rules: &[Rule]) -> Vec<String> {
+        let root = parse_yaml_auto(text).unwrap();
+        lint(text, &root, rules, 20)
+            .unwrap()
+            .into_iter()
+            .map(|problem| problem.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_indent_follows_the_first_step() {
+        let text = "a:\n  b: 1\n  c:\n     d: 2\nitems:\n  - name: x\n    image: y\n";
+        assert_eq!(
+            messages(text, &[Rule::InconsistentIndent]),
+            ["Indented by 3 spaces where the file uses 2 (line 4, inconsistent-indent)"]
+        );
+
+        let text = "run: |\n    echo hi\n      indented\nnext:\n    - x\n";
+        assert!(messages(text, &[Rule::InconsistentIndent]).is_empty());
+    }
+
+    #[test]
+    fn test_scalar_rules() {
+        let text = "a:\nb: \"x \"\nc: 'on'\nd: Off\ne: |\n  trailing  \n";
+        assert_eq!(
+            messages(
+                text,
+                &[
+                    Rule::EmptyValues,
+                    Rule::NonPortableBooleans,
+                    Rule::TrailingWhitespace
+                ]
+            ),
+            [
+                "Empty value, read as null (line 1, empty-values)",
+                "Trailing whitespace in the value (line 2, trailing-whitespace)",
+                "`Off` is a boolean in YAML 1.1 but a string in YAML 1.2; quote it or use true/false (line 4, non-portable-booleans)",
+                "Trailing whitespace in the value (line 5, trailing-whitespace)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paths_skip_comments_and_count_documents() {
+        let text = "items:\n  # first\n  - a\n  - name: b\n    name: c\n---\nx: yes\n";
+        let root = parse_yaml_auto(text).unwrap();
+        let problems = lint(text, &root, &Rule::ALL, 0).unwrap();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].severity, Severity::Error);
+
+        // The duplicate `name` is in the second list item, after the comment
+        let YamlValue::MultiDoc(docs) = root.value() else {
+            panic!("expected two documents");
+        };
+        let YamlValue::Object(entries) = docs[0].value() else {
+            panic!("expected a mapping");
+        };
+        let YamlValue::Array(items) = entries["items"].value() else {
+            panic!("expected a list");
+        };
+        let path = &problems[0].path;
+        assert_eq!(path[..2], [0, entries.get_index_of("items").unwrap()]);
+        assert!(items[..path[2]].iter().any(|item| item.is_comment()));
+        assert!(items[path[2]].value().is_object());
+
+        assert_eq!(problems[1].path[0], 1);
+    }
+}
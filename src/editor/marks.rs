@@ -43,12 +43,89 @@ impl MarkSet {
         self.marks.clear();
     }
 
+    /// Replaces every mark's path with `f(path)`, deleting marks it maps to `None`.
+    pub fn remap_paths(&mut self, mut f: impl FnMut(&[usize]) -> Option<Vec<usize>>) {
+        self.marks.retain(|_, path| match f(path) {
+            Some(new_path) => {
+                *path = new_path;
+                true
+            }
+            None => false,
+        });
+    }
+
     /// Lists all set marks as (name, path) pairs.
     pub fn list(&self) -> Vec<(char, &Vec<usize>)> {
         let mut result: Vec<_> = self.marks.iter().map(|(&c, p)| (c, p)).collect();
         result.sort_by_key(|(c, _)| *c);
         result
     }
+
+    /// Iterates over the set marks in name order.
+    pub fn iter(&self) -> impl Iterator<Item = (char, &[usize])> {
+        self.list().into_iter().map(|(c, p)| (c, p.as_slice()))
+    }
+
+    /// Returns the number of set marks.
+    pub fn len(&self) -> usize {
+        self.marks.len()
+    }
+
+    /// Returns true if no mark is set.
+    pub fn is_empty(&self) -> bool {
+        self.marks.is_empty()
+    }
+}
+
+/// A mark as the `:marks` picker shows it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarkEntry {
+    /// Mark name (a-z)
+    pub name: char,
+    /// Path of the marked node
+    pub path: Vec<usize>,
+    /// The path in dot notation (`spec.ports[0]`)
+    pub location: String,
+    /// Preview of the marked node's value
+    pub preview: String,
+}
+
+/// Selection state for the `:marks` picker.
+#[derive(Debug, Clone)]
+pub struct MarkPickerState {
+    /// Set marks, in name order
+    pub marks: Vec<MarkEntry>,
+    /// Index of the highlighted mark
+    pub selected_index: usize,
+}
+
+impl MarkPickerState {
+    /// Creates a picker over `marks` with the first one selected.
+    pub fn new(marks: Vec<MarkEntry>) -> Self {
+        Self {
+            marks,
+            selected_index: 0,
+        }
+    }
+
+    /// Moves the selection up, wrapping to the bottom.
+    pub fn previous(&mut self) {
+        if !self.marks.is_empty() {
+            self.selected_index = (self.selected_index + self.marks.len() - 1) % self.marks.len();
+        }
+    }
+
+    /// Moves the selection down, wrapping to the top.
+    pub fn next(&mut self) {
+        if !self.marks.is_empty() {
+            self.selected_index = (self.selected_index + 1) % self.marks.len();
+        }
+    }
+
+    /// Returns the highlighted mark.
+    pub fn selected(&self) -> Option<&MarkEntry> {
+        self.marks.get(self.selected_index)
+    }
 }
 
 impl Default for MarkSet {
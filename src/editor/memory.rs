@@ -0,0 +1,142 @@
+//! Memory guardrails (`memory_budget_mb`).
+//!
+//! Undo checkpoints store only the nodes an edit changed, but edits that
+//! rewrite most of a large file store most of it again, so a long session
+//! can still use many times the file's size. The editor keeps a
+//! rough count of what the document, the undo history and the registers
+//! take, and once the total passes `memory_budget_mb` it warns and degrades
+//! rather than running out of memory mid-edit:
+//!
+//! - the oldest undo states are dropped until the total fits again
+//! - yanks over [`CLIPBOARD_MIRROR_LIMIT`] are kept in the register but not
+//!   copied to the system clipboard
+//! - expanding a whole subtree (`E`) is refused
+//!
+//! The estimates count string contents and per-node overhead, not the
+//! allocator's own bookkeeping, so they are a floor rather than exact.
+
+use crate::document::node::{YamlNode, YamlValue};
+use std::mem::size_of;
+
+/// Largest yank still copied to the system clipboard while over budget.
+pub const CLIPBOARD_MIRROR_LIMIT: usize = 1 << 20;
+
+/// Returns the estimated size in bytes of `node` and its descendants.
+///
+/// Unloaded sections of a lazily loaded file share their text between the
+/// document and its undo states, so only the reference is counted here.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::editor::memory::node_size;
+///
+/// let small = parse_yaml_auto("a: 1\n").unwrap();
+/// let large = parse_yaml_auto(&format!("a: {}\n", "x".repeat(10_000))).unwrap();
+/// assert!(node_size(&small) < 1_000);
+/// assert!(node_size(&large) > 10_000);
+/// ```
+pub fn node_size(node: &YamlNode) -> usize {
+    let own = size_of::<YamlNode>()
+        + node.anchor.as_ref().map_or(0, String::capacity)
+        + node.alias_target.as_ref().map_or(0, String::capacity)
+        + node.tag.as_ref().map_or(0, String::capacity)
+        + node
+            .original_formatting
+            .as_ref()
+            .map_or(0, String::capacity);
+    let children = match &node.value {
+        YamlValue::Object(entries) => entries
+            .iter()
+            .map(|(key, child)| key.capacity() + size_of::<usize>() * 2 + node_size(child))
+            .sum(),
+        YamlValue::Array(items) | YamlValue::MultiDoc(items) => items.iter().map(node_size).sum(),
+        YamlValue::String(s) => s.as_str().len(),
+        YamlValue::Alias(name) => name.capacity(),
+        YamlValue::Comment(comment) => comment.content.capacity(),
+        YamlValue::Number(_) | YamlValue::Boolean(_) | YamlValue::Null => 0,
+        YamlValue::Deferred(_) => 0,
+    };
+    own + children
+}
+
+/// Estimated memory held by one buffer, in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// The document, including the source text kept for saving
+    pub tree: usize,
+    /// All undo states
+    pub undo: usize,
+    /// Number of undo states
+    pub undo_states: usize,
+    /// All registers
+    pub registers: usize,
+}
+
+impl MemoryUsage {
+    /// Returns the total estimate.
+    pub fn total(&self) -> usize {
+        self.tree + self.undo + self.registers
+    }
+
+    /// Returns a one-line breakdown such as
+    /// `document 12.0 MB, undo 48.0 MB (4 states), registers 1.0 KB`.
+    pub fn summary(&self) -> String {
+        format!(
+            "document {}, undo {} ({} state{}), registers {}",
+            format_size(self.tree),
+            format_size(self.undo),
+            self.undo_states,
+            if self.undo_states == 1 { "" } else { "s" },
+            format_size(self.registers)
+        )
+    }
+}
+
+/// Formats a byte count as KB, MB or GB with one decimal.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::memory::format_size;
+///
+/// assert_eq!(format_size(1536), "1.5 KB");
+/// assert_eq!(format_size(3 << 20), "3.0 MB");
+/// assert_eq!(format_size(5 << 30), "5.0 GB");
+/// ```
+pub fn format_size(bytes: usize) -> String {
+    if bytes >= 1 << 30 {
+        format!("{:.1} GB", bytes as f64 / (1u64 << 30) as f64)
+    } else if bytes >= 1 << 20 {
+        format!("{:.1} MB", bytes as f64 / (1 << 20) as f64)
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    #[test]
+    fn test_node_size_grows_with_content() {
+        let one = node_size(&parse_yaml_auto("- a\n").unwrap());
+        let two = node_size(&parse_yaml_auto("- a\n- b\n").unwrap());
+        assert!(two > one);
+        assert!(two - one >= size_of::<YamlNode>());
+
+        let usage = MemoryUsage {
+            tree: 2 << 20,
+            undo: 6 << 20,
+            undo_states: 3,
+            registers: 512,
+        };
+        assert_eq!(usage.total(), (8 << 20) + 512);
+        assert_eq!(
+            usage.summary(),
+            "document 2.0 MB, undo 6.0 MB (3 states), registers 0.5 KB"
+        );
+    }
+}
@@ -9,6 +9,7 @@
 //! - `mode`: Editor mode enumeration and transitions
 //! - `cursor`: Cursor position tracking in the JSON tree
 //! - `state`: Editor state management (tree, mode, cursor, etc.)
+//! - `window`: Split panes, each with its own editor state
 //!
 //! # Example
 //!
@@ -20,11 +21,49 @@
 //! assert_eq!(mode, EditorMode::Normal);
 //! ```
 
+pub mod aggregate;
+pub mod backups;
+pub mod base64;
+pub mod bookmarks;
+pub mod calc;
+pub mod coerce;
+pub mod cron;
 pub mod cursor;
+pub mod datetime;
+pub mod dedupe;
+pub mod diagnostics;
+pub mod dotenv;
+pub mod explain;
+pub mod external;
+pub mod facet;
+pub mod helm;
+pub mod hints;
+pub mod interpolate;
 pub mod jumplist;
+pub mod k8s;
+pub mod keyjump;
+pub mod lint;
 pub mod marks;
+pub mod memory;
 pub mod mode;
+pub mod multiline;
+pub mod network;
+pub mod notes;
+pub mod pipeline;
+pub mod quickfix;
+pub mod regex;
 pub mod registers;
 pub mod repeat;
+pub mod repl;
+pub mod revalidate;
+pub mod review;
+pub mod sample;
+pub mod search;
+pub mod semver;
 pub mod state;
+pub mod substitute;
+pub mod tasks;
 pub mod undo;
+pub mod usages;
+pub mod watch;
+pub mod window;
@@ -65,6 +65,10 @@ pub enum EditorMode {
     Search,
     /// Visual mode for selecting multiple nodes.
     Visual,
+    /// Query REPL pane has focus.
+    Repl,
+    /// Stepping through changes with `:review`.
+    Review,
 }
 
 impl fmt::Display for EditorMode {
@@ -86,6 +90,8 @@ impl fmt::Display for EditorMode {
             EditorMode::Command => write!(f, "COMMAND"),
             EditorMode::Search => write!(f, "SEARCH"),
             EditorMode::Visual => write!(f, "VISUAL"),
+            EditorMode::Repl => write!(f, "REPL"),
+            EditorMode::Review => write!(f, "REVIEW"),
         }
     }
 }
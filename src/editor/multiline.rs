@@ -0,0 +1,225 @@
+//! Multi-line edit buffer for block scalars.
+//!
+//! Literal (`|`) and folded (`>`) strings can't be edited sensibly on the
+//! single-line edit prompt, so entering insert mode on a string that spans
+//! lines opens this buffer in an overlay instead. The text is kept as a list
+//! of lines with a row/column cursor; the column is counted in characters.
+
+/// Text and cursor of the multi-line editor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultilineBuffer {
+    /// Lines of text, without their line breaks (never empty)
+    lines: Vec<String>,
+    /// Cursor line
+    row: usize,
+    /// Cursor position within the line, in characters
+    col: usize,
+    /// Whether the original text ended with a line break
+    trailing_newline: bool,
+}
+
+impl MultilineBuffer {
+    /// Opens `text` for editing with the cursor at the end.
+    ///
+    /// A single trailing line break (the usual `|` / `>` chomping) is hidden
+    /// while editing and put back by [`MultilineBuffer::text`].
+    pub fn new(text: &str) -> Self {
+        let trailing_newline = text.ends_with('\n');
+        let body = text.strip_suffix('\n').unwrap_or(text);
+        let lines: Vec<String> = body.split('\n').map(str::to_string).collect();
+        let row = lines.len() - 1;
+        let col = lines[row].chars().count();
+        Self {
+            lines,
+            row,
+            col,
+            trailing_newline,
+        }
+    }
+
+    /// Returns the edited text.
+    pub fn text(&self) -> String {
+        let mut text = self.lines.join("\n");
+        if self.trailing_newline {
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Returns the lines being edited.
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// Returns the cursor as (line, character).
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.row, self.col)
+    }
+
+    /// Inserts a character at the cursor.
+    pub fn insert_char(&mut self, c: char) {
+        if c == '\n' {
+            self.newline();
+            return;
+        }
+        let byte = self.byte_index(self.col);
+        self.lines[self.row].insert(byte, c);
+        self.col += 1;
+    }
+
+    /// Splits the line at the cursor.
+    pub fn newline(&mut self) {
+        let byte = self.byte_index(self.col);
+        let rest = self.lines[self.row].split_off(byte);
+        self.row += 1;
+        self.lines.insert(self.row, rest);
+        self.col = 0;
+    }
+
+    /// Deletes the character before the cursor, joining lines at the start of one.
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let byte = self.byte_index(self.col);
+            self.lines[self.row].remove(byte);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len();
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    /// Deletes the character at the cursor, joining the next line at the end of one.
+    pub fn delete(&mut self) {
+        if self.col < self.line_len() {
+            let byte = self.byte_index(self.col);
+            self.lines[self.row].remove(byte);
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].push_str(&next);
+        }
+    }
+
+    /// Deletes from the cursor to the end of the line.
+    pub fn kill_to_end(&mut self) {
+        let byte = self.byte_index(self.col);
+        self.lines[self.row].truncate(byte);
+    }
+
+    /// Moves the cursor one character left, wrapping to the previous line.
+    pub fn move_left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len();
+        }
+    }
+
+    /// Moves the cursor one character right, wrapping to the next line.
+    pub fn move_right(&mut self) {
+        if self.col < self.line_len() {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    /// Moves the cursor up a line, keeping the column where the line allows.
+    pub fn move_up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.col = self.col.min(self.line_len());
+        }
+    }
+
+    /// Moves the cursor down a line, keeping the column where the line allows.
+    pub fn move_down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = self.col.min(self.line_len());
+        }
+    }
+
+    /// Moves the cursor to the start of the line.
+    pub fn home(&mut self) {
+        self.col = 0;
+    }
+
+    /// Moves the cursor to the end of the line.
+    pub fn end(&mut self) {
+        self.col = self.line_len();
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(char_index)
+            .map(|(i, _)| i)
+            .unwrap_or(line.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trailing_newline_round_trip() {
+        let buffer = MultilineBuffer::new("first\nsecond\n");
+        assert_eq!(buffer.lines(), ["first", "second"]);
+        assert_eq!(buffer.cursor(), (1, 6));
+        assert_eq!(buffer.text(), "first\nsecond\n");
+
+        assert_eq!(MultilineBuffer::new("a\nb").text(), "a\nb");
+        assert_eq!(MultilineBuffer::new("").lines(), [""]);
+    }
+
+    #[test]
+    fn test_split_and_join_lines() {
+        let mut buffer = MultilineBuffer::new("héllo\n");
+        buffer.move_left();
+        buffer.move_left();
+        buffer.newline();
+        assert_eq!(buffer.lines(), ["hél", "lo"]);
+        assert_eq!(buffer.cursor(), (1, 0));
+
+        buffer.backspace();
+        assert_eq!(buffer.lines(), ["héllo"]);
+        assert_eq!(buffer.cursor(), (0, 3));
+
+        buffer.end();
+        buffer.insert_char('\n');
+        buffer.insert_char('x');
+        buffer.move_up();
+        buffer.end();
+        buffer.delete();
+        assert_eq!(buffer.text(), "héllox\n");
+    }
+
+    #[test]
+    fn test_vertical_movement_clamps_column() {
+        let mut buffer = MultilineBuffer::new("long line\nab\nanother");
+        buffer.move_up();
+        assert_eq!(buffer.cursor(), (1, 2));
+        buffer.move_up();
+        assert_eq!(buffer.cursor(), (0, 2));
+        buffer.move_down();
+        buffer.move_down();
+        buffer.move_down();
+        assert_eq!(buffer.cursor(), (2, 2));
+
+        buffer.move_right();
+        buffer.kill_to_end();
+        assert_eq!(buffer.lines()[2], "ano");
+        buffer.home();
+        buffer.move_left();
+        assert_eq!(buffer.cursor(), (1, 2));
+    }
+}
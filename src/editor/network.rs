@@ -0,0 +1,602 @@
+//! Checks and explanations of network values: IP addresses, CIDR ranges and
+//! ports.
+//!
+//! Network settings fail silently when mistyped: `10.0.0.1/24` routes the
+//! whole `/24`, two subnets that overlap only break once both are in use,
+//! and a port above 65535 is truncated or rejected far from the YAML. The
+//! checks here run after every change and report through the `network`
+//! diagnostics; `K` on a value explains it (see [`super::explain`]).
+//!
+//! Bare addresses are only checked under keys that name network settings
+//! (`clusterIP`, `podSubnet`, `dnsServers`, ...), since dotted numbers like
+//! `1.2.3.4` are also version strings. CIDR ranges are recognised anywhere.
+
+use crate::document::node::{YamlNode, YamlNumber, YamlValue};
+use crate::editor::diagnostics::{Severity, ValueProblem};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Source name for the diagnostics network checks report.
+pub const NETWORK_SOURCE: &str = "network";
+
+/// Fragments of keys whose values are addresses.
+const NETWORK_KEYS: &[&str] = &[
+    "ip", "cidr", "addr", "subnet", "network", "range", "host", "gateway", "dns", "server",
+    "except",
+];
+
+/// Well-known ports and what usually listens on them.
+const SERVICES: &[(i64, &str)] = &[
+    (22, "SSH"),
+    (25, "SMTP"),
+    (53, "DNS"),
+    (80, "HTTP"),
+    (443, "HTTPS"),
+    (2379, "etcd"),
+    (3306, "MySQL"),
+    (5432, "PostgreSQL"),
+    (5672, "AMQP (RabbitMQ)"),
+    (6379, "Redis"),
+    (6443, "Kubernetes API server"),
+    (8080, "HTTP (alternate)"),
+    (8443, "HTTPS (alternate)"),
+    (9090, "Prometheus"),
+    (9200, "Elasticsearch"),
+    (10250, "kubelet"),
+    (11211, "memcached"),
+    (27017, "MongoDB"),
+];
+
+/// A CIDR range such as `10.0.0.0/16` or `fd00::/8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    /// The address before the `/`
+    pub addr: IpAddr,
+    /// The prefix length
+    pub prefix: u32,
+}
+
+impl Cidr {
+    /// Parses a CIDR range.
+    ///
+    /// Returns `None` for text that isn't shaped like one, and an error for
+    /// a range that is but isn't valid (`10.0.0.300/8`, `10.0.0.0/33`).
+    pub fn parse(text: &str) -> Option<Result<Cidr, String>> {
+        let (addr, prefix) = text.split_once('/')?;
+        if !prefix.chars().all(|c| c.is_ascii_digit()) || prefix.is_empty() {
+            return None;
+        }
+        let addr = if looks_like_ipv4(addr) {
+            match parse_ipv4(addr) {
+                Ok(addr) => IpAddr::V4(addr),
+                Err(e) => return Some(Err(e)),
+            }
+        } else {
+            IpAddr::V6(addr.parse::<Ipv6Addr>().ok()?)
+        };
+        let width = width(addr);
+        Some(match prefix.parse::<u32>() {
+            Ok(prefix) if prefix <= width => Ok(Cidr { addr, prefix }),
+            _ => Err(format!(
+                "{} is not a CIDR range: the prefix is longer than {} bits",
+                text, width
+            )),
+        })
+    }
+
+    /// Returns the first address of the range.
+    pub fn network(&self) -> IpAddr {
+        from_bits(self.addr, bits(self.addr) & self.mask())
+    }
+
+    /// Returns the last address of the range.
+    pub fn last(&self) -> IpAddr {
+        from_bits(
+            self.addr,
+            bits(self.addr) | (!self.mask() & full(self.addr)),
+        )
+    }
+
+    /// Returns true if the two ranges share an address.
+    pub fn overlaps(&self, other: &Cidr) -> bool {
+        if width(self.addr) != width(other.addr) {
+            return false;
+        }
+        let shorter = if self.prefix < other.prefix {
+            self
+        } else {
+            other
+        };
+        bits(self.addr) & shorter.mask() == bits(other.addr) & shorter.mask()
+    }
+
+    fn mask(&self) -> u128 {
+        let host_bits = width(self.addr) - self.prefix;
+        if host_bits >= 128 {
+            0
+        } else {
+            (u128::MAX << host_bits) & full(self.addr)
+        }
+    }
+}
+
+impl std::fmt::Display for Cidr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+fn width(addr: IpAddr) -> u32 {
+    match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+fn full(addr: IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(_) => u32::MAX as u128,
+        IpAddr::V6(_) => u128::MAX,
+    }
+}
+
+fn bits(addr: IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(addr) => u32::from(addr) as u128,
+        IpAddr::V6(addr) => u128::from(addr),
+    }
+}
+
+fn from_bits(like: IpAddr, bits: u128) -> IpAddr {
+    match like {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from(bits as u32)),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(bits)),
+    }
+}
+
+/// Returns true for four dot-separated numbers.
+pub fn looks_like_ipv4(text: &str) -> bool {
+    let parts: Vec<&str> = text.split('.').collect();
+    parts.len() == 4
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Parses a dotted-quad IPv4 address, explaining what's wrong with it.
+fn parse_ipv4(text: &str) -> Result<Ipv4Addr, String> {
+    let mut octets = [0u8; 4];
+    for (octet, part) in octets.iter_mut().zip(text.split('.')) {
+        if part.len() > 1 && part.starts_with('0') {
+            return Err(format!(
+                "{} is not an IPv4 address: {} has a leading zero",
+                text, part
+            ));
+        }
+        *octet = part
+            .parse()
+            .map_err(|_| format!("{} is not an IPv4 address: {} is over 255", text, part))?;
+    }
+    Ok(Ipv4Addr::from(octets))
+}
+
+/// Returns true if `key` names a port (`port`, `containerPort`, `ports`).
+pub fn is_port_key(key: &str) -> bool {
+    let lower = key.to_ascii_lowercase();
+    ["port", "ports"].contains(&lower.as_str())
+        || key.ends_with("Port")
+        || key.ends_with("Ports")
+        || lower.ends_with("_port")
+        || lower.ends_with("-port")
+}
+
+fn is_network_key(key: &str) -> bool {
+    let lower = key.to_ascii_lowercase();
+    NETWORK_KEYS.iter().any(|fragment| lower.contains(fragment))
+}
+
+/// Returns the port a value under a port key gives, if it is a number.
+fn port_number(value: &YamlValue) -> Option<i64> {
+    match value {
+        YamlValue::Number(YamlNumber::Integer(port)) => Some(*port),
+        YamlValue::String(text) if text.as_str().chars().all(|c| c.is_ascii_digit()) => {
+            text.as_str().parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Checks addresses, ranges and ports under `root`.
+///
+/// Invalid addresses and ranges, and ports outside 0-65535, are errors. A
+/// range with host bits set (`10.0.0.1/24`) and a range overlapping an
+/// earlier one in the same mapping or list are warnings.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::editor::network::check_values;
+///
+/// let root = parse_yaml_auto("podSubnet: 10.0.0.0/16\nserviceSubnet: 10.0.128.0/20\n").unwrap();
+/// let problems = check_values(&root);
+/// assert_eq!(
+///     problems[0].message,
+///     "10.0.128.0/20 overlaps podSubnet (10.0.0.0/16)"
+/// );
+/// ```
+pub fn check_values(root: &YamlNode) -> Vec<ValueProblem> {
+    let mut problems = Vec::new();
+    check_node(root, None, &mut Vec::new(), &mut problems);
+    problems
+}
+
+fn check_node(
+    node: &YamlNode,
+    key: Option<&str>,
+    path: &mut Vec<usize>,
+    problems: &mut Vec<ValueProblem>,
+) {
+    let children: Vec<(Option<&str>, &YamlNode)> = match node.value() {
+        YamlValue::Object(fields) => fields.iter().map(|(k, v)| (Some(k.as_str()), v)).collect(),
+        YamlValue::Array(items) => items.iter().map(|item| (key, item)).collect(),
+        YamlValue::MultiDoc(items) => items.iter().map(|item| (None, item)).collect(),
+        _ => return,
+    };
+    let is_object = matches!(node.value(), YamlValue::Object(_));
+
+    let mut ranges: Vec<(String, Cidr)> = Vec::new();
+    for (index, (child_key, child)) in children.into_iter().enumerate() {
+        path.push(index);
+        if child.value().is_container() {
+            check_node(child, child_key, path, problems);
+        } else if let Some((severity, message)) = check_scalar(child_key, child.value()) {
+            problems.push(ValueProblem {
+                path: path.clone(),
+                severity,
+                message,
+            });
+        }
+
+        if let Some(Ok(cidr)) = cidr_value(child.value()) {
+            let label = match child_key {
+                Some(key) if is_object => key.to_string(),
+                _ => format!("item {}", index),
+            };
+            if let Some((other, other_cidr)) = ranges.iter().find(|(_, c)| c.overlaps(&cidr)) {
+                problems.push(ValueProblem {
+                    path: path.clone(),
+                    severity: Severity::Warning,
+                    message: format!("{} overlaps {} ({})", cidr, other, other_cidr),
+                });
+            }
+            ranges.push((label, cidr));
+        }
+        path.pop();
+    }
+}
+
+fn cidr_value(value: &YamlValue) -> Option<Result<Cidr, String>> {
+    match value {
+        YamlValue::String(text) => Cidr::parse(text.as_str().trim()),
+        _ => None,
+    }
+}
+
+fn check_scalar(key: Option<&str>, value: &YamlValue) -> Option<(Severity, String)> {
+    if let Some(port) = key
+        .filter(|k| is_port_key(k))
+        .and_then(|_| port_number(value))
+    {
+        return (!(0..=65535).contains(&port))
+            .then(|| (Severity::Error, format!("Port {} is outside 0-65535", port)));
+    }
+    match cidr_value(value) {
+        Some(Err(e)) => return Some((Severity::Error, e)),
+        Some(Ok(cidr)) if cidr.addr != cidr.network() => {
+            return Some((
+                Severity::Warning,
+                format!(
+                    "{} has host bits set; the network is {}/{}",
+                    cidr,
+                    cidr.network(),
+                    cidr.prefix
+                ),
+            ))
+        }
+        Some(Ok(_)) => return None,
+        None => {}
+    }
+    let YamlValue::String(text) = value else {
+        return None;
+    };
+    let text = text.as_str().trim();
+    if key.is_some_and(is_network_key) && looks_like_ipv4(text) {
+        return parse_ipv4(text).err().map(|e| (Severity::Error, e));
+    }
+    None
+}
+
+/// Describes what kind of address `addr` is (private, loopback, ...).
+fn address_kind(addr: IpAddr) -> &'static str {
+    match addr {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            if v4.is_unspecified() {
+                "Unspecified (any address)"
+            } else if v4.is_loopback() {
+                "Loopback"
+            } else if v4.is_private() {
+                "Private (RFC 1918)"
+            } else if a == 100 && (64..128).contains(&b) {
+                "Shared address space (RFC 6598, carrier-grade NAT)"
+            } else if v4.is_link_local() {
+                "Link-local"
+            } else if v4.is_multicast() {
+                "Multicast"
+            } else if v4.is_broadcast() {
+                "Broadcast"
+            } else if v4.is_documentation() {
+                "Documentation (RFC 5737)"
+            } else {
+                "Public"
+            }
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            if v6.is_unspecified() {
+                "Unspecified (any address)"
+            } else if v6.is_loopback() {
+                "Loopback"
+            } else if v6.is_multicast() {
+                "Multicast"
+            } else if first & 0xfe00 == 0xfc00 {
+                "Unique local (private)"
+            } else if first & 0xffc0 == 0xfe80 {
+                "Link-local"
+            } else if first == 0x2001 && v6.segments()[1] == 0x0db8 {
+                "Documentation (RFC 3849)"
+            } else {
+                "Global"
+            }
+        }
+    }
+}
+
+/// Formats a number with thousands separators.
+fn grouped(n: u128) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Explains an address, a CIDR range or a port under a port key.
+///
+/// `siblings` are the other values in the same mapping or list, labelled by
+/// key or as `item N`; a range lists the ones it overlaps. Returns `None` for
+/// anything else and an error for an invalid address or range.
+pub fn explain(
+    key: Option<&str>,
+    value: &YamlValue,
+    siblings: &[(String, &YamlValue)],
+) -> Option<Result<(String, Vec<String>), String>> {
+    if let Some(port) = key
+        .filter(|k| is_port_key(k))
+        .and_then(|_| port_number(value))
+    {
+        return Some(explain_port(port));
+    }
+    let YamlValue::String(text) = value else {
+        return None;
+    };
+    let text = text.as_str().trim();
+    if let Some(cidr) = Cidr::parse(text) {
+        return Some(cidr.map(|cidr| explain_cidr(cidr, siblings)));
+    }
+    if looks_like_ipv4(text) {
+        return Some(parse_ipv4(text).map(|addr| {
+            (
+                format!("IPv4 address {}", addr),
+                vec![address_kind(IpAddr::V4(addr)).to_string()],
+            )
+        }));
+    }
+    if text.contains(':') {
+        if let Ok(addr) = text.parse::<Ipv6Addr>() {
+            return Some(Ok((
+                format!("IPv6 address {}", addr),
+                vec![address_kind(IpAddr::V6(addr)).to_string()],
+            )));
+        }
+    }
+    None
+}
+
+fn explain_cidr(cidr: Cidr, siblings: &[(String, &YamlValue)]) -> (String, Vec<String>) {
+    let host_bits = width(cidr.addr) - cidr.prefix;
+    let mut lines = vec![format!("Network:   {}", cidr.network())];
+    if cidr.addr.is_ipv4() {
+        lines.push(format!("Netmask:   {}", from_bits(cidr.addr, cidr.mask())));
+    }
+    lines.push(format!("Range:     {} - {}", cidr.network(), cidr.last()));
+    lines.push(match (cidr.addr, host_bits) {
+        (IpAddr::V4(_), 0) => "Addresses: 1".to_string(),
+        (IpAddr::V4(_), 1) => "Addresses: 2 (point-to-point link)".to_string(),
+        (IpAddr::V4(_), _) => format!(
+            "Addresses: {} ({} usable hosts)",
+            grouped(1 << host_bits),
+            grouped((1 << host_bits) - 2)
+        ),
+        (IpAddr::V6(_), bits) if bits <= 32 => format!("Addresses: {}", grouped(1 << bits)),
+        (IpAddr::V6(_), bits) => format!("Addresses: 2^{}", bits),
+    });
+    lines.push(format!("Kind:      {}", address_kind(cidr.network())));
+    if cidr.addr != cidr.network() {
+        lines.push(format!(
+            "Host bits are set; the network is {}/{}",
+            cidr.network(),
+            cidr.prefix
+        ));
+    }
+
+    lines.push(String::new());
+    let mut overlaps = Vec::new();
+    for (label, value) in siblings {
+        if let Some(Ok(other)) = cidr_value(value) {
+            if other.overlaps(&cidr) {
+                overlaps.push(format!("Overlaps {} ({})", label, other));
+            }
+        }
+    }
+    if overlaps.is_empty() {
+        lines.push("No overlap with sibling entries".to_string());
+    } else {
+        lines.extend(overlaps);
+    }
+    (format!("CIDR range {}", cidr), lines)
+}
+
+fn explain_port(port: i64) -> Result<(String, Vec<String>), String> {
+    let class = match port {
+        0 => "Port 0 lets the system pick any free port",
+        1..=1023 => "Well-known port; binding it needs privileges",
+        1024..=49151 => "Registered port",
+        49152..=65535 => "Dynamic port range; may clash with outgoing connections",
+        _ => return Err(format!("Port {} is outside 0-65535", port)),
+    };
+    let mut lines = vec![class.to_string()];
+    if let Some((_, service)) = SERVICES.iter().find(|(p, _)| *p == port) {
+        lines.push(format!("Usually {}", service));
+    }
+    Ok((format!("Port {}", port), lines))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    fn cidr(text: &str) -> Cidr {
+        Cidr::parse(text).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_parse_cidr() {
+        assert_eq!(cidr("10.0.0.0/8").prefix, 8);
+        assert_eq!(Cidr::parse("1.2.3"), None);
+        assert_eq!(Cidr::parse("a/b"), None);
+        assert_eq!(
+            Cidr::parse("10.0.0.256/8"),
+            Some(Err(
+                "10.0.0.256 is not an IPv4 address: 256 is over 255".to_string()
+            ))
+        );
+        assert_eq!(
+            Cidr::parse("10.0.0.0/33"),
+            Some(Err(
+                "10.0.0.0/33 is not a CIDR range: the prefix is longer than 32 bits".to_string()
+            ))
+        );
+        assert_eq!(
+            cidr("fd00::/8").last().to_string(),
+            "fdff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"
+        );
+        assert_eq!(cidr("10.1.2.3/16").network().to_string(), "10.1.0.0");
+        assert!(cidr("10.0.0.0/8").overlaps(&cidr("10.200.0.0/16")));
+        assert!(!cidr("10.0.0.0/16").overlaps(&cidr("10.1.0.0/16")));
+        assert!(!cidr("10.0.0.0/8").overlaps(&cidr("fd00::/8")));
+    }
+
+    #[test]
+    fn test_check_values() {
+        let yaml = "\
+clusterIP: 10.96.0.300
+version: 1.2.3.400
+ports:
+  - containerPort: 70000
+  - containerPort: 8080
+allowed:
+  - 192.168.1.0/24
+  - 192.168.1.7/24
+  - 192.168.0.0/16
+";
+        let root = parse_yaml_auto(yaml).unwrap();
+        let problems: Vec<_> = check_values(&root)
+            .into_iter()
+            .map(|p| (p.path, p.severity, p.message))
+            .collect();
+        assert_eq!(
+            problems,
+            [
+                (
+                    vec![0],
+                    Severity::Error,
+                    "10.96.0.300 is not an IPv4 address: 300 is over 255".to_string()
+                ),
+                (
+                    vec![2, 0, 0],
+                    Severity::Error,
+                    "Port 70000 is outside 0-65535".to_string()
+                ),
+                (
+                    vec![3, 1],
+                    Severity::Warning,
+                    "192.168.1.7/24 has host bits set; the network is 192.168.1.0/24".to_string()
+                ),
+                (
+                    vec![3, 1],
+                    Severity::Warning,
+                    "192.168.1.7/24 overlaps item 0 (192.168.1.0/24)".to_string()
+                ),
+                (
+                    vec![3, 2],
+                    Severity::Warning,
+                    "192.168.0.0/16 overlaps item 0 (192.168.1.0/24)".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_explain_cidr_and_port() {
+        let text = |s: &str| YamlValue::String(crate::document::node::YamlString::Plain(s.into()));
+        let sibling = text("10.0.4.0/22");
+        let (title, lines) = explain(
+            Some("podSubnet"),
+            &text("10.0.0.0/16"),
+            &[("serviceSubnet".to_string(), &sibling)],
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(title, "CIDR range 10.0.0.0/16");
+        assert_eq!(
+            lines,
+            [
+                "Network:   10.0.0.0",
+                "Netmask:   255.255.0.0",
+                "Range:     10.0.0.0 - 10.0.255.255",
+                "Addresses: 65,536 (65,534 usable hosts)",
+                "Kind:      Private (RFC 1918)",
+                "",
+                "Overlaps serviceSubnet (10.0.4.0/22)",
+            ]
+        );
+
+        let port = YamlValue::Number(YamlNumber::Integer(443));
+        let (title, lines) = explain(Some("port"), &port, &[]).unwrap().unwrap();
+        assert_eq!(title, "Port 443");
+        assert_eq!(
+            lines,
+            [
+                "Well-known port; binding it needs privileges",
+                "Usually HTTPS"
+            ]
+        );
+        assert_eq!(explain(Some("replicas"), &port, &[]), None);
+    }
+}
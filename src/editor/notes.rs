@@ -0,0 +1,168 @@
+//! Notes attached to nodes, kept in a sidecar file.
+//!
+//! `:note <text>` attaches free text to the node under the cursor without
+//! touching the document itself, which makes notes suitable for review
+//! comments on config files that must not change. Notes for `app.yaml` are
+//! stored next to it in `app.yaml.notes.toml`, keyed by the node's
+//! bracket-notation YAMLPath (`$["spec"]["replicas"]`) so they follow the
+//! node rather than its position in the tree.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A note on one node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note {
+    /// Bracket-notation YAMLPath of the node
+    pub path: String,
+    /// The note itself
+    pub text: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct NoteFile {
+    #[serde(default, rename = "note")]
+    notes: Vec<Note>,
+}
+
+/// The notes of one file, in the order they were added.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::notes::NoteStore;
+///
+/// let mut notes = NoteStore::default();
+/// notes.set("$[\"replicas\"]", "Why 5? Was 3 in staging");
+///
+/// assert_eq!(notes.get("$[\"replicas\"]"), Some("Why 5? Was 3 in staging"));
+/// assert!(notes.remove("$[\"replicas\"]"));
+/// assert!(notes.is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct NoteStore {
+    notes: Vec<Note>,
+}
+
+impl NoteStore {
+    /// Loads notes from a sidecar file. A missing file gives an empty store.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read notes: {}", path.display()))?;
+        let file: NoteFile = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse notes: {}", path.display()))?;
+        Ok(Self { notes: file.notes })
+    }
+
+    /// Writes the notes to a sidecar file, removing the file once no notes are left.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if self.notes.is_empty() {
+            if path.exists() {
+                fs::remove_file(path)
+                    .with_context(|| format!("Failed to remove notes: {}", path.display()))?;
+            }
+            return Ok(());
+        }
+        let file = NoteFile {
+            notes: self.notes.clone(),
+        };
+        let contents = toml::to_string_pretty(&file).context("Failed to serialize notes")?;
+        fs::write(path, contents)
+            .with_context(|| format!("Failed to write notes: {}", path.display()))
+    }
+
+    /// Returns all notes.
+    pub fn list(&self) -> &[Note] {
+        &self.notes
+    }
+
+    /// Returns true if there are no notes.
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    /// Returns the note on the node at `path`.
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.notes
+            .iter()
+            .find(|n| n.path == path)
+            .map(|n| n.text.as_str())
+    }
+
+    /// Attaches a note to the node at `path`, replacing any note already there.
+    pub fn set(&mut self, path: &str, text: &str) {
+        match self.notes.iter_mut().find(|n| n.path == path) {
+            Some(note) => note.text = text.to_string(),
+            None => self.notes.push(Note {
+                path: path.to_string(),
+                text: text.to_string(),
+            }),
+        }
+    }
+
+    /// Removes the note on the node at `path`. Returns false if there was none.
+    pub fn remove(&mut self, path: &str) -> bool {
+        let before = self.notes.len();
+        self.notes.retain(|n| n.path != path);
+        self.notes.len() != before
+    }
+}
+
+/// Returns the sidecar path used to store notes for `path`.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::notes::notes_file_path;
+/// use std::path::{Path, PathBuf};
+///
+/// assert_eq!(
+///     notes_file_path(Path::new("conf/app.yaml")),
+///     PathBuf::from("conf/app.yaml.notes.toml")
+/// );
+/// ```
+pub fn notes_file_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.notes.toml", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_set_replaces() {
+        let mut notes = NoteStore::default();
+        notes.set("$[\"a\"]", "first");
+        notes.set("$[\"b\"]", "other");
+        notes.set("$[\"a\"]", "second");
+        assert_eq!(notes.list().len(), 2);
+        assert_eq!(notes.get("$[\"a\"]"), Some("second"));
+        assert!(!notes.remove("$[\"c\"]"));
+    }
+
+    #[test]
+    fn test_save_load_and_cleanup() {
+        let dir = TempDir::new().unwrap();
+        let path = notes_file_path(&dir.path().join("app.yaml"));
+
+        let mut notes = NoteStore::default();
+        notes.set("$[\"a\"]", "check this");
+        notes.save_to(&path).unwrap();
+        let reloaded = NoteStore::load_from(&path).unwrap();
+        assert_eq!(reloaded.get("$[\"a\"]"), Some("check this"));
+
+        notes.remove("$[\"a\"]");
+        notes.save_to(&path).unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,443 @@
+//! CI pipeline graphs (`:graph`).
+//!
+//! Two layouts are recognised. A GitHub Actions workflow has a `jobs`
+//! mapping whose jobs list the jobs they wait for under `needs`. A GitLab
+//! CI file has jobs as top-level keys (anything with a `script`, `trigger`
+//! or `extends`), each in a `stage` from the `stages` list; a job waits for
+//! every job of the earlier stages unless it lists `needs`.
+//!
+//! The graph is drawn as text, one stage per block, each job with the jobs
+//! it waits for.
+
+use crate::document::node::{YamlNode, YamlValue};
+
+/// Top-level GitLab keys that configure the pipeline rather than name a job.
+const GITLAB_KEYWORDS: &[&str] = &[
+    "after_script",
+    "before_script",
+    "cache",
+    "default",
+    "image",
+    "include",
+    "services",
+    "stages",
+    "variables",
+    "workflow",
+];
+
+/// GitLab's stages when the file doesn't list its own.
+const GITLAB_DEFAULT_STAGES: &[&str] = &[".pre", "build", "test", "deploy", ".post"];
+
+/// A job of the pipeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Job {
+    /// The job's name (its key)
+    pub name: String,
+    /// Path of the job's definition
+    pub path: Vec<usize>,
+    /// Names of the jobs listed under `needs`
+    pub needs: Vec<String>,
+    /// The stage the job runs in
+    pub stage: usize,
+}
+
+/// A pipeline: its stage names and its jobs, in stage order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pipeline {
+    pub stages: Vec<String>,
+    pub jobs: Vec<Job>,
+}
+
+/// Finds the pipeline in a document, or in the first document of a stream
+/// that has one.
+pub fn pipeline(root: &YamlNode) -> Option<Pipeline> {
+    match root.value() {
+        YamlValue::MultiDoc(docs) => docs.iter().enumerate().find_map(|(index, doc)| {
+            let mut found = pipeline(doc)?;
+            for job in &mut found.jobs {
+                job.path.insert(0, index);
+            }
+            Some(found)
+        }),
+        YamlValue::Object(entries) => match entries.get("jobs").map(|n| n.value()) {
+            Some(YamlValue::Object(_)) => github(root),
+            _ => gitlab(root),
+        },
+        _ => None,
+    }
+}
+
+/// Returns the names a `needs` value lists: a name, a list of names, or
+/// GitLab's `- job: name` entries.
+fn needs(job: &YamlNode) -> Vec<String> {
+    let YamlValue::Object(fields) = job.value() else {
+        return Vec::new();
+    };
+    let name = |node: &YamlNode| match node.value() {
+        YamlValue::String(s) => Some(s.as_str().to_string()),
+        YamlValue::Object(entry) => match entry.get("job").map(|n| n.value()) {
+            Some(YamlValue::String(s)) => Some(s.as_str().to_string()),
+            _ => None,
+        },
+        _ => None,
+    };
+    match fields.get("needs").map(|n| (n, n.value())) {
+        Some((_, YamlValue::Array(items))) => items.iter().filter_map(name).collect(),
+        Some((node, _)) => name(node).into_iter().collect(),
+        None => Vec::new(),
+    }
+}
+
+fn github(root: &YamlNode) -> Option<Pipeline> {
+    let YamlValue::Object(entries) = root.value() else {
+        return None;
+    };
+    let jobs_index = entries.get_index_of("jobs")?;
+    let Some(YamlValue::Object(jobs)) = entries.get("jobs").map(|n| n.value()) else {
+        return None;
+    };
+    let mut found: Vec<Job> = jobs
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, job))| matches!(job.value(), YamlValue::Object(_)))
+        .map(|(index, (name, job))| Job {
+            name: name.clone(),
+            path: vec![jobs_index, index],
+            needs: needs(job),
+            stage: 0,
+        })
+        .collect();
+    if found.is_empty() {
+        return None;
+    }
+
+    // A job's stage is one past the latest stage of what it needs; jobs in
+    // a cycle end up after everything else
+    let mut placed = vec![false; found.len()];
+    let mut stage = 0;
+    while placed.iter().any(|p| !p) {
+        let ready: Vec<usize> = (0..found.len())
+            .filter(|&i| !placed[i])
+            .filter(|&i| {
+                found[i].needs.iter().all(|need| {
+                    found
+                        .iter()
+                        .position(|job| &job.name == need)
+                        .is_none_or(|j| placed[j] && found[j].stage < stage)
+                })
+            })
+            .collect();
+        let ready = if ready.is_empty() {
+            (0..found.len()).filter(|&i| !placed[i]).collect()
+        } else {
+            ready
+        };
+        for i in ready {
+            placed[i] = true;
+            found[i].stage = stage;
+        }
+        stage += 1;
+    }
+    found.sort_by_key(|job| job.stage);
+    let stages = (1..=stage).map(|n| format!("stage {}", n)).collect();
+    Some(Pipeline {
+        stages,
+        jobs: found,
+    })
+}
+
+fn gitlab(root: &YamlNode) -> Option<Pipeline> {
+    let YamlValue::Object(entries) = root.value() else {
+        return None;
+    };
+    let mut stages: Vec<String> = match entries.get("stages").map(|n| n.value()) {
+        Some(YamlValue::Array(items)) => items
+            .iter()
+            .filter_map(|item| match item.value() {
+                YamlValue::String(s) => Some(s.as_str().to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => GITLAB_DEFAULT_STAGES
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+    };
+    for edge in [".pre", ".post"] {
+        if !stages.iter().any(|s| s == edge) {
+            if edge == ".pre" {
+                stages.insert(0, edge.to_string());
+            } else {
+                stages.push(edge.to_string());
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    for (index, (name, job)) in entries.iter().enumerate() {
+        let YamlValue::Object(fields) = job.value() else {
+            continue;
+        };
+        // Hidden jobs (`.template`) are only for `extends`
+        if name.starts_with('.') || GITLAB_KEYWORDS.contains(&name.as_str()) {
+            continue;
+        }
+        if !["script", "trigger", "extends"]
+            .iter()
+            .any(|key| fields.contains_key(*key))
+        {
+            continue;
+        }
+        let stage_name = match fields.get("stage").map(|n| n.value()) {
+            Some(YamlValue::String(s)) => s.as_str().to_string(),
+            _ => "test".to_string(),
+        };
+        let stage = match stages.iter().position(|s| *s == stage_name) {
+            Some(stage) => stage,
+            None => {
+                stages.push(stage_name);
+                stages.len() - 1
+            }
+        };
+        found.push(Job {
+            name: name.clone(),
+            path: vec![index],
+            needs: needs(job),
+            stage,
+        });
+    }
+    if found.is_empty() {
+        return None;
+    }
+    found.sort_by_key(|job| job.stage);
+
+    // Leave out the stages no job runs in, renumbering the rest
+    let used: Vec<usize> = (0..stages.len())
+        .filter(|stage| found.iter().any(|job| job.stage == *stage))
+        .collect();
+    for job in &mut found {
+        job.stage = used.iter().position(|s| *s == job.stage).unwrap_or(0);
+    }
+    let stages = used.into_iter().map(|s| stages[s].clone()).collect();
+    Some(Pipeline {
+        stages,
+        jobs: found,
+    })
+}
+
+/// A line of the drawn graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphRow {
+    pub text: String,
+    /// Index into [`Pipeline::jobs`] for a job's line
+    pub job: Option<usize>,
+}
+
+/// Draws the pipeline: a heading per stage, an arrow between stages, and a
+/// line per job with the jobs it needs (`<- build, lint`). Needs naming a
+/// job that doesn't exist are marked `?`.
+pub fn diagram(pipeline: &Pipeline) -> Vec<GraphRow> {
+    let mut rows = Vec::new();
+    let text = |text: String| GraphRow { text, job: None };
+    let width = pipeline
+        .jobs
+        .iter()
+        .map(|job| job.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (stage_index, stage) in pipeline.stages.iter().enumerate() {
+        let jobs: Vec<(usize, &Job)> = pipeline
+            .jobs
+            .iter()
+            .enumerate()
+            .filter(|(_, job)| job.stage == stage_index)
+            .collect();
+        if stage_index > 0 {
+            rows.push(text("     |".to_string()));
+            rows.push(text("     v".to_string()));
+        }
+        rows.push(text(format!("[{}]", stage)));
+        for (index, (job_index, job)) in jobs.iter().enumerate() {
+            let branch = if index + 1 == jobs.len() {
+                "`--"
+            } else {
+                "|--"
+            };
+            let mut line = format!("  {} {}", branch, job.name);
+            if !job.needs.is_empty() {
+                let needs: Vec<String> = job
+                    .needs
+                    .iter()
+                    .map(|need| {
+                        if pipeline.jobs.iter().any(|j| &j.name == need) {
+                            need.clone()
+                        } else {
+                            format!("{}?", need)
+                        }
+                    })
+                    .collect();
+                line = format!("{:<pad$}  <- {}", line, needs.join(", "), pad = width + 6);
+            }
+            rows.push(GraphRow {
+                text: line,
+                job: Some(*job_index),
+            });
+        }
+    }
+    rows
+}
+
+/// Selection state for the `:graph` overlay.
+#[derive(Debug, Clone)]
+pub struct GraphState {
+    pub pipeline: Pipeline,
+    pub rows: Vec<GraphRow>,
+    /// Index into `rows` of the highlighted job
+    pub selected_row: usize,
+}
+
+impl GraphState {
+    /// Draws `pipeline` with its first job selected.
+    pub fn new(pipeline: Pipeline) -> Self {
+        let rows = diagram(&pipeline);
+        let selected_row = rows.iter().position(|r| r.job.is_some()).unwrap_or(0);
+        Self {
+            pipeline,
+            rows,
+            selected_row,
+        }
+    }
+
+    /// Moves the selection to the previous job, wrapping to the last.
+    pub fn previous(&mut self) {
+        let jobs = self.job_rows();
+        if let Some(at) = jobs.iter().position(|&r| r == self.selected_row) {
+            self.selected_row = jobs[(at + jobs.len() - 1) % jobs.len()];
+        }
+    }
+
+    /// Moves the selection to the next job, wrapping to the first.
+    pub fn next(&mut self) {
+        let jobs = self.job_rows();
+        if let Some(at) = jobs.iter().position(|&r| r == self.selected_row) {
+            self.selected_row = jobs[(at + 1) % jobs.len()];
+        }
+    }
+
+    /// Returns the highlighted job.
+    pub fn selected(&self) -> Option<&Job> {
+        let job = self.rows.get(self.selected_row)?.job?;
+        self.pipeline.jobs.get(job)
+    }
+
+    fn job_rows(&self) -> Vec<usize> {
+        (0..self.rows.len())
+            .filter(|&r| self.rows[r].job.is_some())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    fn names(pipeline: &Pipeline, stage: usize) -> Vec<&str> {
+        pipeline
+            .jobs
+            .iter()
+            .filter(|job| job.stage == stage)
+            .map(|job| job.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_github_stages_follow_needs() {
+        let root = parse_yaml_auto(
+            "\
+on: push
+jobs:
+  deploy:
+    needs: [test, lint]
+    runs-on: ubuntu-latest
+  build:
+    runs-on: ubuntu-latest
+  test:
+    needs: build
+  lint:
+    needs: build
+",
+        )
+        .unwrap();
+        let pipeline = pipeline(&root).unwrap();
+        assert_eq!(pipeline.stages.len(), 3);
+        assert_eq!(names(&pipeline, 0), ["build"]);
+        assert_eq!(names(&pipeline, 1), ["test", "lint"]);
+        assert_eq!(names(&pipeline, 2), ["deploy"]);
+        assert_eq!(pipeline.jobs[3].path, vec![1, 0]);
+
+        let text: Vec<String> = diagram(&pipeline).into_iter().map(|r| r.text).collect();
+        assert_eq!(
+            text,
+            [
+                "[stage 1]",
+                "  `-- build",
+                "     |",
+                "     v",
+                "[stage 2]",
+                "  |-- test    <- build",
+                "  `-- lint    <- build",
+                "     |",
+                "     v",
+                "[stage 3]",
+                "  `-- deploy  <- test, lint",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gitlab_jobs_by_stage() {
+        let root = parse_yaml_auto(
+            "\
+stages: [build, test, review, deploy]
+variables:
+  A: b
+.base:
+  script: echo
+compile:
+  stage: build
+  script: make
+unit:
+  extends: .base
+lint:
+  stage: test
+  script: lint
+  needs: [compile, missing]
+release:
+  stage: deploy
+  script: ship
+",
+        )
+        .unwrap();
+        let pipeline = pipeline(&root).unwrap();
+        assert_eq!(pipeline.stages, ["build", "test", "deploy"]);
+        assert_eq!(names(&pipeline, 1), ["unit", "lint"]);
+        let rows = diagram(&pipeline);
+        assert!(rows
+            .iter()
+            .any(|r| r.text.ends_with("<- compile, missing?")));
+    }
+
+    #[test]
+    fn test_selection_skips_headings_and_wraps() {
+        let root = parse_yaml_auto("jobs:\n  a: {}\n  b:\n    needs: a\n").unwrap();
+        let mut state = GraphState::new(pipeline(&root).unwrap());
+        assert_eq!(state.selected().unwrap().name, "a");
+        state.next();
+        assert_eq!(state.selected().unwrap().name, "b");
+        state.next();
+        assert_eq!(state.selected().unwrap().name, "a");
+        state.previous();
+        assert_eq!(state.selected().unwrap().name, "b");
+    }
+}
@@ -0,0 +1,133 @@
+//! The quickfix list: a list of places in the document to step through.
+//!
+//! Anything that finds problems or matches at nodes can fill it: `:lint`
+//! fills it with its problems, `:copen diagnostics` with every diagnostic
+//! (schema validation, value checks, lint) and `:copen search` with the
+//! matches of the last search. The list is shown in a panel where `Enter` jumps
+//! to an entry, and `]q`/`[q` (`:cnext`/`:cprev`) step through it without
+//! the panel. A new list replaces the old one.
+
+use super::diagnostics::{Diagnostic, Severity};
+
+/// One place in the quickfix list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuickfixEntry {
+    /// Path of the node
+    pub path: Vec<usize>,
+    /// Human-readable location, e.g. `$["spec"]["replicas"]`
+    pub location: String,
+    /// How serious the entry is; search matches are `Info`
+    pub severity: Severity,
+    /// What is at the node
+    pub message: String,
+}
+
+impl From<Diagnostic> for QuickfixEntry {
+    fn from(diagnostic: Diagnostic) -> Self {
+        Self {
+            path: diagnostic.path,
+            location: diagnostic.location,
+            severity: diagnostic.severity,
+            message: diagnostic.message,
+        }
+    }
+}
+
+/// A quickfix list with its panel selection.
+#[derive(Debug, Clone)]
+pub struct QuickfixList {
+    /// What filled the list, shown as the panel title (`Lint`, `Search`, ...)
+    pub title: String,
+    /// Entries in document order
+    pub entries: Vec<QuickfixEntry>,
+    /// Index of the entry highlighted in the panel
+    pub selected_index: usize,
+    /// Index of the entry last jumped to, if any
+    pub current: Option<usize>,
+}
+
+impl QuickfixList {
+    /// Creates a list over `entries`, sorted into document order, that
+    /// hasn't been jumped into yet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::editor::diagnostics::Severity;
+    /// use yamlquill::editor::quickfix::{QuickfixEntry, QuickfixList};
+    ///
+    /// let entry = |path: Vec<usize>| QuickfixEntry {
+    ///     path,
+    ///     location: String::new(),
+    ///     severity: Severity::Info,
+    ///     message: String::new(),
+    /// };
+    /// let mut list = QuickfixList::new("Search", vec![entry(vec![2]), entry(vec![0, 1])]);
+    /// assert_eq!(list.step(true).unwrap().path, [0, 1]);
+    /// assert_eq!(list.step(true).unwrap().path, [2]);
+    /// assert_eq!(list.step(true).unwrap().path, [0, 1]);
+    /// ```
+    pub fn new(title: &str, mut entries: Vec<QuickfixEntry>) -> Self {
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Self {
+            title: title.to_string(),
+            entries,
+            selected_index: 0,
+            current: None,
+        }
+    }
+
+    /// Moves the panel selection up, wrapping to the bottom.
+    pub fn previous(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected_index =
+                (self.selected_index + self.entries.len() - 1) % self.entries.len();
+        }
+    }
+
+    /// Moves the panel selection down, wrapping to the top.
+    pub fn next(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected_index = (self.selected_index + 1) % self.entries.len();
+        }
+    }
+
+    /// Returns the highlighted entry.
+    pub fn selected(&self) -> Option<&QuickfixEntry> {
+        self.entries.get(self.selected_index)
+    }
+
+    /// Makes the highlighted entry the current one and returns it.
+    pub fn choose(&mut self) -> Option<&QuickfixEntry> {
+        self.current = Some(self.selected_index);
+        self.entries.get(self.selected_index)
+    }
+
+    /// Makes the entry after the current one current (before it, unless
+    /// `forward`), wrapping around, and returns it. The first step goes to
+    /// the first entry (or the last, backwards).
+    pub fn step(&mut self, forward: bool) -> Option<&QuickfixEntry> {
+        let len = self.entries.len();
+        if len == 0 {
+            return None;
+        }
+        let index = match (self.current, forward) {
+            (None, true) => 0,
+            (None, false) => len - 1,
+            (Some(i), true) => (i + 1) % len,
+            (Some(i), false) => (i + len - 1) % len,
+        };
+        self.current = Some(index);
+        self.selected_index = index;
+        self.entries.get(index)
+    }
+
+    /// Returns the position of the current entry as `(2/5)`.
+    pub fn position(&self) -> String {
+        format!(
+            "({}/{})",
+            self.current.map_or(0, |i| i + 1),
+            self.entries.len()
+        )
+    }
+}
@@ -0,0 +1,589 @@
+//! A small backtracking regular expression engine.
+//!
+//! Used by `:%s` (and anything else that needs to match text patterns)
+//! without pulling in a regex dependency. The syntax is the familiar
+//! extended one, like Vim's "very magic" mode:
+//!
+//! - `.` any character except newline, `^` / `$` start / end of the text
+//! - `[abc]`, `[a-z]`, `[^0-9]` character classes
+//! - `\d` `\w` `\s` and their negations `\D` `\W` `\S`
+//! - `*` `+` `?` `{n}` `{n,}` `{n,m}`, each optionally followed by `?` to be lazy
+//! - `(...)` capturing groups, `(?:...)` non-capturing groups, `a|b` alternation
+//! - `\` escapes any other special character
+//!
+//! Patterns are matched by backtracking over the text's characters, which is
+//! plenty fast for the short scalar values found in YAML documents.
+
+use anyhow::{bail, Result};
+
+#[derive(Debug, Clone)]
+enum Node {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+    Start,
+    End,
+    Group {
+        inner: Box<Node>,
+        index: Option<usize>,
+    },
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat {
+        inner: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+/// Byte ranges of capture groups in one match; index 0 is the whole match.
+type Captures = Vec<Option<(usize, usize)>>;
+
+/// A compiled regular expression.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::regex::Regex;
+///
+/// let re = Regex::new(r"v(\d+)\.(\d+)").unwrap();
+/// assert_eq!(re.find("image: app:v1.24"), Some((11, 16)));
+/// assert_eq!(re.replace("v1.24", r"v\2.\1", false), ("v24.1".to_string(), 1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Regex {
+    root: Node,
+    groups: usize,
+    ignore_case: bool,
+}
+
+impl Regex {
+    /// Compiles a case-sensitive pattern.
+    pub fn new(pattern: &str) -> Result<Self> {
+        Self::with_case(pattern, false)
+    }
+
+    /// Compiles a pattern, optionally ignoring case.
+    pub fn with_case(pattern: &str, ignore_case: bool) -> Result<Self> {
+        let mut parser = RegexParser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+            groups: 0,
+        };
+        let root = parser.parse_alt()?;
+        if parser.pos < parser.chars.len() {
+            bail!("Invalid regex: unmatched )");
+        }
+        Ok(Self {
+            root,
+            groups: parser.groups,
+            ignore_case,
+        })
+    }
+
+    /// Returns true if the pattern matches anywhere in `text`.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+
+    /// Returns the byte range of the first match in `text`.
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        let text = Text::new(text);
+        (0..=text.chars.len())
+            .find_map(|start| self.match_at(&text, start))
+            .and_then(|caps| caps[0])
+    }
+
+    /// Replaces the first match (or every match, if `global`) in `text`.
+    ///
+    /// In the replacement, `&` or `\0` inserts the whole match and `\1`–`\9`
+    /// the capture groups; `\&` and `\\` insert a literal `&` and `\`.
+    /// Returns the new text and the number of replacements made.
+    pub fn replace(&self, text: &str, replacement: &str, global: bool) -> (String, usize) {
+        let source = text;
+        let text = Text::new(text);
+        let len = text.chars.len();
+        let mut out = String::new();
+        let mut last = 0;
+        let mut count = 0;
+        let mut pos = 0;
+        while pos <= len {
+            let Some(caps) = self.match_at(&text, pos) else {
+                pos += 1;
+                continue;
+            };
+            let (start, end) = caps[0].unwrap_or((text.offset(pos), text.offset(pos)));
+            out.push_str(&source[last..start]);
+            expand_replacement(&mut out, source, replacement, &caps);
+            last = end;
+            count += 1;
+            if !global {
+                break;
+            }
+            let end_char = text.index_of(end);
+            pos = if end_char > pos { end_char } else { pos + 1 };
+        }
+        out.push_str(&source[last..]);
+        (out, count)
+    }
+
+    /// Tries to match starting exactly at character index `start`.
+    fn match_at(&self, text: &Text, start: usize) -> Option<Captures> {
+        let mut caps: Vec<Option<(usize, usize)>> = vec![None; self.groups + 1];
+        let mut end = None;
+        let matched = self.m(&self.root, text, start, &mut caps, &mut |pos, _| {
+            end = Some(pos);
+            true
+        });
+        if !matched {
+            return None;
+        }
+        caps[0] = end.map(|end| (text.offset(start), text.offset(end)));
+        Some(caps)
+    }
+
+    /// Matches `node` at `pos`, then calls the continuation `k` with the end position.
+    fn m(
+        &self,
+        node: &Node,
+        text: &Text,
+        pos: usize,
+        caps: &mut Captures,
+        k: &mut dyn FnMut(usize, &mut Captures) -> bool,
+    ) -> bool {
+        let current = text.chars.get(pos).copied();
+        match node {
+            Node::Char(c) => current.is_some_and(|ch| self.chars_eq(ch, *c)) && k(pos + 1, caps),
+            Node::Any => current.is_some_and(|ch| ch != '\n') && k(pos + 1, caps),
+            Node::Class { ranges, negated } => {
+                current.is_some_and(|ch| self.class_matches(ranges, ch) != *negated)
+                    && k(pos + 1, caps)
+            }
+            Node::Start => pos == 0 && k(pos, caps),
+            Node::End => pos == text.chars.len() && k(pos, caps),
+            Node::Concat(nodes) => self.m_seq(nodes, text, pos, caps, k),
+            Node::Alt(alternatives) => {
+                for alternative in alternatives {
+                    if self.m(alternative, text, pos, caps, k) {
+                        return true;
+                    }
+                }
+                false
+            }
+            Node::Group { inner, index } => match index {
+                None => self.m(inner, text, pos, caps, k),
+                Some(index) => {
+                    let index = *index;
+                    self.m(inner, text, pos, caps, &mut |end, caps: &mut Captures| {
+                        let saved = caps[index];
+                        caps[index] = Some((text.offset(pos), text.offset(end)));
+                        if k(end, caps) {
+                            return true;
+                        }
+                        caps[index] = saved;
+                        false
+                    })
+                }
+            },
+            Node::Repeat {
+                inner,
+                min,
+                max,
+                greedy,
+            } => self.m_repeat(inner, (*min, *max, *greedy), 0, text, pos, caps, k),
+        }
+    }
+
+    fn m_seq(
+        &self,
+        nodes: &[Node],
+        text: &Text,
+        pos: usize,
+        caps: &mut Captures,
+        k: &mut dyn FnMut(usize, &mut Captures) -> bool,
+    ) -> bool {
+        match nodes.split_first() {
+            None => k(pos, caps),
+            Some((first, rest)) => self.m(first, text, pos, caps, &mut |next, caps| {
+                self.m_seq(rest, text, next, caps, k)
+            }),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn m_repeat(
+        &self,
+        inner: &Node,
+        (min, max, greedy): (usize, Option<usize>, bool),
+        count: usize,
+        text: &Text,
+        pos: usize,
+        caps: &mut Captures,
+        k: &mut dyn FnMut(usize, &mut Captures) -> bool,
+    ) -> bool {
+        let can_stop = count >= min;
+        let can_continue = max.is_none_or(|max| count < max);
+
+        if can_stop && !greedy && k(pos, caps) {
+            return true;
+        }
+        if can_continue {
+            let more = self.m(inner, text, pos, caps, &mut |next, caps| {
+                // An empty iteration can't make progress once the minimum is met
+                (next != pos || count < min)
+                    && self.m_repeat(inner, (min, max, greedy), count + 1, text, next, caps, k)
+            });
+            if more {
+                return true;
+            }
+        }
+        can_stop && greedy && k(pos, caps)
+    }
+
+    fn chars_eq(&self, a: char, b: char) -> bool {
+        a == b || (self.ignore_case && a.to_lowercase().eq(b.to_lowercase()))
+    }
+
+    fn class_matches(&self, ranges: &[(char, char)], ch: char) -> bool {
+        let in_ranges = |c: char| ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi);
+        in_ranges(ch)
+            || (self.ignore_case
+                && (ch.to_lowercase().any(in_ranges) || ch.to_uppercase().any(in_ranges)))
+    }
+}
+
+/// The text being matched, as characters with their byte offsets.
+struct Text {
+    chars: Vec<char>,
+    offsets: Vec<usize>,
+}
+
+impl Text {
+    fn new(text: &str) -> Self {
+        let (mut offsets, chars): (Vec<usize>, Vec<char>) = text.char_indices().unzip();
+        offsets.push(text.len());
+        Self { chars, offsets }
+    }
+
+    /// Returns the byte offset of character index `index`.
+    fn offset(&self, index: usize) -> usize {
+        self.offsets[index]
+    }
+
+    /// Returns the character index of byte offset `offset`.
+    fn index_of(&self, offset: usize) -> usize {
+        self.offsets.partition_point(|&o| o < offset)
+    }
+}
+
+/// Appends `replacement` to `out`, expanding `&` and `\N` from `caps`.
+fn expand_replacement(out: &mut String, source: &str, replacement: &str, caps: &Captures) {
+    let group = |out: &mut String, index: usize| {
+        if let Some(Some((start, end))) = caps.get(index) {
+            out.push_str(&source[*start..*end]);
+        }
+    };
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => group(out, 0),
+            '\\' => match chars.next() {
+                Some(d @ '0'..='9') => group(out, d as usize - '0' as usize),
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            },
+            c => out.push(c),
+        }
+    }
+}
+
+struct RegexParser {
+    chars: Vec<char>,
+    pos: usize,
+    groups: usize,
+}
+
+impl RegexParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    /// Steps past the current character, returning `value`.
+    fn advance<T>(&mut self, value: T) -> T {
+        self.pos += 1;
+        value
+    }
+
+    fn parse_alt(&mut self) -> Result<Node> {
+        let mut alternatives = vec![self.parse_concat()?];
+        while self.peek() == Some('|') {
+            self.pos += 1;
+            alternatives.push(self.parse_concat()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Node::Alt(alternatives)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            nodes.push(self.parse_quantifier(atom)?);
+        }
+        Ok(Node::Concat(nodes))
+    }
+
+    fn parse_atom(&mut self) -> Result<Node> {
+        let c = self.peek().unwrap_or_default();
+        self.pos += 1;
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '(' => {
+                let index = if self.chars[self.pos..].starts_with(&['?', ':']) {
+                    self.pos += 2;
+                    None
+                } else {
+                    self.groups += 1;
+                    Some(self.groups)
+                };
+                let inner = self.parse_alt()?;
+                if self.peek() != Some(')') {
+                    bail!("Invalid regex: unmatched (");
+                }
+                self.pos += 1;
+                Node::Group {
+                    inner: Box::new(inner),
+                    index,
+                }
+            }
+            '[' => self.parse_class()?,
+            '\\' => self.parse_escape()?,
+            '*' | '+' | '?' => bail!("Invalid regex: nothing to repeat before {}", c),
+            c => Node::Char(c),
+        })
+    }
+
+    fn parse_escape(&mut self) -> Result<Node> {
+        let Some(c) = self.peek() else {
+            bail!("Invalid regex: trailing \\");
+        };
+        self.pos += 1;
+        Ok(match shorthand_class(c) {
+            Some((ranges, negated)) => Node::Class { ranges, negated },
+            None => Node::Char(escaped_char(c)),
+        })
+    }
+
+    fn parse_class(&mut self) -> Result<Node> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.pos += 1;
+        }
+        let mut ranges = Vec::new();
+        let mut first = true;
+        loop {
+            let Some(c) = self.peek() else {
+                bail!("Invalid regex: unmatched [");
+            };
+            self.pos += 1;
+            if c == ']' && !first {
+                break;
+            }
+            first = false;
+            let lo = if c == '\\' {
+                let Some(e) = self.peek() else {
+                    bail!("Invalid regex: unmatched [");
+                };
+                self.pos += 1;
+                if let Some((class, false)) = shorthand_class(e) {
+                    ranges.extend(class);
+                    continue;
+                }
+                escaped_char(e)
+            } else {
+                c
+            };
+            let is_range =
+                self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|&n| n != ']');
+            if is_range {
+                let mut hi = self.chars[self.pos + 1];
+                self.pos += 2;
+                if hi == '\\' {
+                    hi = escaped_char(self.peek().unwrap_or('\\'));
+                    self.pos += 1;
+                }
+                if hi < lo {
+                    bail!("Invalid regex: bad range {}-{}", lo, hi);
+                }
+                ranges.push((lo, hi));
+            } else {
+                ranges.push((lo, lo));
+            }
+        }
+        Ok(Node::Class { ranges, negated })
+    }
+
+    fn parse_quantifier(&mut self, atom: Node) -> Result<Node> {
+        let (min, max) = match self.peek() {
+            Some('*') => self.advance((0, None)),
+            Some('+') => self.advance((1, None)),
+            Some('?') => self.advance((0, Some(1))),
+            Some('{') => match self.parse_braces() {
+                Some(bounds) => bounds,
+                None => return Ok(atom),
+            },
+            _ => return Ok(atom),
+        };
+        if matches!(atom, Node::Start | Node::End) {
+            bail!("Invalid regex: nothing to repeat");
+        }
+        let greedy = self.peek() != Some('?');
+        if !greedy {
+            self.pos += 1;
+        }
+        Ok(Node::Repeat {
+            inner: Box::new(atom),
+            min,
+            max,
+            greedy,
+        })
+    }
+
+    /// Parses `{n}`, `{n,}` or `{n,m}`, leaving the position after `}`.
+    ///
+    /// Returns `None` (consuming nothing) if the braces aren't a valid
+    /// quantifier, in which case `{` is matched literally.
+    fn parse_braces(&mut self) -> Option<(usize, Option<usize>)> {
+        let rest: String = self.chars[self.pos + 1..].iter().collect();
+        let close = rest.find('}')?;
+        let body = &rest[..close];
+        let bounds = match body.split_once(',') {
+            None => {
+                let n = body.parse().ok()?;
+                (n, Some(n))
+            }
+            Some((lo, "")) => (lo.parse().ok()?, None),
+            Some((lo, hi)) => (lo.parse().ok()?, Some(hi.parse().ok()?)),
+        };
+        if bounds.1.is_some_and(|max| max < bounds.0) {
+            return None;
+        }
+        self.pos += 1 + body.chars().count() + 1;
+        Some(bounds)
+    }
+}
+
+/// Returns the ranges of `\d`, `\w`, `\s` (and the negated `\D`, `\W`, `\S`).
+fn shorthand_class(c: char) -> Option<(Vec<(char, char)>, bool)> {
+    let digit = vec![('0', '9')];
+    let word = vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')];
+    let space = vec![(' ', ' '), ('\t', '\r')];
+    Some(match c {
+        'd' => (digit, false),
+        'D' => (digit, true),
+        'w' => (word, false),
+        'W' => (word, true),
+        's' => (space, false),
+        'S' => (space, true),
+        _ => return None,
+    })
+}
+
+fn escaped_char(c: char) -> char {
+    match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(pattern: &str, text: &'a str) -> Option<&'a str> {
+        Regex::new(pattern)
+            .unwrap()
+            .find(text)
+            .map(|(s, e)| &text[s..e])
+    }
+
+    #[test]
+    fn test_basic_matching() {
+        assert_eq!(find("b.d", "abcde"), Some("bcd"));
+        assert_eq!(find("^a", "ba"), None);
+        assert_eq!(find("a$", "ba"), Some("a"));
+        assert_eq!(find("colou?r", "the color"), Some("color"));
+        assert_eq!(find("[0-9]+", "port 8080/tcp"), Some("8080"));
+        assert_eq!(find("[^a-z ]+", "port 8080"), Some("8080"));
+        assert_eq!(find(r"\d{2,3}", "a1234"), Some("123"));
+        assert_eq!(
+            find(r"\w+@\w+\.com", "mail bob@ex.com now"),
+            Some("bob@ex.com")
+        );
+        assert_eq!(find("cat|dog", "hotdog"), Some("dog"));
+        assert_eq!(find("a{x}", "a{x}"), Some("a{x}"));
+    }
+
+    #[test]
+    fn test_greedy_and_lazy() {
+        assert_eq!(find("<.*>", "<a><b>"), Some("<a><b>"));
+        assert_eq!(find("<.*?>", "<a><b>"), Some("<a>"));
+        assert_eq!(find("(a|ab)c", "abc"), Some("abc"));
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        let re = Regex::with_case("always", true).unwrap();
+        assert!(re.is_match("Always"));
+        let re = Regex::with_case("[a-c]+", true).unwrap();
+        assert_eq!(re.find("xABCx"), Some((1, 4)));
+    }
+
+    #[test]
+    fn test_replace() {
+        let re = Regex::new("o").unwrap();
+        assert_eq!(re.replace("foo", "0", false), ("f0o".to_string(), 1));
+        assert_eq!(re.replace("foo", "0", true), ("f00".to_string(), 2));
+
+        let re = Regex::new(r"(\w+)=(\w+)").unwrap();
+        assert_eq!(
+            re.replace("a=1 b=2", r"\2=\1 [&]", true),
+            ("1=a [a=1] 2=b [b=2]".to_string(), 2)
+        );
+
+        // Empty matches advance one character at a time
+        let re = Regex::new("x*").unwrap();
+        assert_eq!(re.replace("ab", "-", true), ("-a-b-".to_string(), 3));
+    }
+
+    #[test]
+    fn test_unicode_offsets() {
+        let re = Regex::new("é+").unwrap();
+        assert_eq!(re.replace("cafééx", "e", true), ("cafex".to_string(), 1));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(Regex::new("(a").is_err());
+        assert!(Regex::new("a)").is_err());
+        assert!(Regex::new("[a").is_err());
+        assert!(Regex::new("*a").is_err());
+        assert!(Regex::new("a\\").is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::document::node::YamlNode;
+use crate::editor::memory::node_size;
 use std::collections::HashMap;
 
 /// Content stored in a register (nodes + optional keys for object members)
@@ -6,16 +7,39 @@ use std::collections::HashMap;
 pub struct RegisterContent {
     pub nodes: Vec<YamlNode>,
     pub keys: Vec<Option<String>>,
+    /// Yanked from a visual block: the values of one key across list
+    /// elements, pasted back element by element.
+    pub column: bool,
 }
 
 impl RegisterContent {
     pub fn new(nodes: Vec<YamlNode>, keys: Vec<Option<String>>) -> Self {
-        Self { nodes, keys }
+        Self {
+            nodes,
+            keys,
+            column: false,
+        }
+    }
+
+    /// Creates column content: the values of `key` from consecutive elements.
+    pub fn column(key: &str, nodes: Vec<YamlNode>) -> Self {
+        let keys = vec![Some(key.to_string()); nodes.len()];
+        Self {
+            nodes,
+            keys,
+            column: true,
+        }
     }
 
     pub fn is_empty(&self) -> bool {
         self.nodes.is_empty()
     }
+
+    /// Returns the estimated size of the content in bytes.
+    pub fn memory(&self) -> usize {
+        let keys: usize = self.keys.iter().flatten().map(String::capacity).sum();
+        keys + self.nodes.iter().map(node_size).sum::<usize>()
+    }
 }
 
 /// Manages all registers (unnamed, named a-z, numbered 0-9)
@@ -96,6 +120,7 @@ impl RegisterSet {
         if let Some(existing) = self.named.get_mut(&key) {
             existing.nodes.extend(content.nodes);
             existing.keys.extend(content.keys);
+            existing.column &= content.column;
         } else {
             self.named.insert(key, content);
         }
@@ -112,6 +137,33 @@ impl RegisterSet {
         self.numbered[1] = content;
     }
 
+    /// Returns the estimated size of all registers in bytes.
+    pub fn memory(&self) -> usize {
+        std::iter::once(&self.unnamed)
+            .chain(self.named.values())
+            .chain(self.numbered.iter())
+            .map(RegisterContent::memory)
+            .sum()
+    }
+
+    /// Returns the registers that hold content, in the order `:registers`
+    /// lists them: the unnamed register (`"`), then `0`-`9`, then `a`-`z`.
+    pub fn filled(&self) -> Vec<(char, &RegisterContent)> {
+        let mut named: Vec<(char, &RegisterContent)> =
+            self.named.iter().map(|(name, c)| (*name, c)).collect();
+        named.sort_by_key(|(name, _)| *name);
+        std::iter::once(('"', &self.unnamed))
+            .chain(
+                self.numbered
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| (char::from(b'0' + i as u8), c)),
+            )
+            .chain(named)
+            .filter(|(_, content)| !content.is_empty())
+            .collect()
+    }
+
     /// Updates the yank register "0 with the latest yank content.
     pub fn update_yank_register(&mut self, content: RegisterContent) {
         // Update "0 with latest yank
@@ -119,6 +171,66 @@ impl RegisterSet {
     }
 }
 
+/// Builds register content from system clipboard text (`"+p`).
+///
+/// The text is parsed as YAML, which also reads JSON. When the paste goes
+/// into a mapping (`into_mapping`), a pasted mapping's entries become keyed
+/// siblings; into a list, a pasted list's items become separate elements.
+/// Anything else is one node. JSON is pasted in block style, like the rest
+/// of a YAML document. Text that doesn't parse becomes a string, a literal
+/// block if it spans several lines.
+pub fn clipboard_content(text: &str, into_mapping: bool) -> RegisterContent {
+    use crate::document::node::{CollectionStyle, YamlString, YamlValue};
+    use crate::document::parser::parse_yaml_auto;
+
+    fn block_style(node: &mut YamlNode) {
+        node.set_style(CollectionStyle::Block);
+        match node.value_mut() {
+            YamlValue::Object(entries) => entries.values_mut().for_each(block_style),
+            YamlValue::Array(items) => items.iter_mut().for_each(block_style),
+            _ => {}
+        }
+    }
+
+    if text.trim().is_empty() {
+        return RegisterContent::new(vec![], vec![]);
+    }
+    let Ok(mut root) = parse_yaml_auto(text) else {
+        let text = text.trim_end_matches('\n');
+        let string = if text.contains('\n') {
+            YamlString::Literal(format!("{}\n", text))
+        } else {
+            YamlString::Plain(text.to_string())
+        };
+        return RegisterContent::new(vec![YamlNode::new(YamlValue::String(string))], vec![None]);
+    };
+    root.mark_all_modified();
+    if serde_json::from_str::<serde_json::Value>(text).is_ok() {
+        block_style(&mut root);
+    }
+
+    match root.value() {
+        YamlValue::Object(entries) if into_mapping => {
+            let (keys, nodes) = entries
+                .iter()
+                .filter(|(_, node)| !matches!(node.value(), YamlValue::Comment(_)))
+                .map(|(key, node)| (Some(key.clone()), node.clone()))
+                .unzip();
+            RegisterContent::new(nodes, keys)
+        }
+        YamlValue::Array(items) | YamlValue::MultiDoc(items) if !into_mapping => {
+            let nodes: Vec<YamlNode> = items
+                .iter()
+                .filter(|node| !matches!(node.value(), YamlValue::Comment(_)))
+                .cloned()
+                .collect();
+            let keys = vec![None; nodes.len()];
+            RegisterContent::new(nodes, keys)
+        }
+        _ => RegisterContent::new(vec![root], vec![None]),
+    }
+}
+
 impl Default for RegisterSet {
     fn default() -> Self {
         Self::new()
@@ -130,6 +242,33 @@ mod tests {
     use super::*;
     use crate::document::node::{YamlNode, YamlNumber, YamlString, YamlValue};
 
+    #[test]
+    fn test_filled_lists_registers_in_order() {
+        let node = |n: &str| YamlNode::new(YamlValue::String(YamlString::Plain(n.to_string())));
+        let mut registers = RegisterSet::new();
+        assert!(registers.filled().is_empty());
+
+        registers.set_named('b', RegisterContent::new(vec![node("b")], vec![None]));
+        registers.set_named('a', RegisterContent::new(vec![node("a")], vec![None]));
+        registers.push_delete_history(RegisterContent::new(vec![node("1")], vec![None]));
+        registers.set_unnamed(RegisterContent::new(vec![node("1")], vec![None]));
+        let names: String = registers.filled().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, "\"1ab");
+    }
+
+    #[test]
+    fn test_append_keeps_column_only_when_both_are_columns() {
+        let mut registers = RegisterSet::new();
+        let node = YamlNode::new(YamlValue::Boolean(true));
+        registers.set_named('a', RegisterContent::column("ok", vec![node.clone()]));
+        registers.append_named('a', RegisterContent::column("ok", vec![node.clone()]));
+        assert!(registers.get_named('a').unwrap().column);
+
+        registers.append_named('a', RegisterContent::new(vec![node], vec![None]));
+        assert!(!registers.get_named('a').unwrap().column);
+        assert_eq!(registers.get_named('a').unwrap().nodes.len(), 3);
+    }
+
     #[test]
     fn test_register_content_new() {
         let node = YamlNode::new(YamlValue::String(YamlString::Plain("test".to_string())));
@@ -262,4 +401,40 @@ mod tests {
         // Test get() for non-existent named register
         assert!(regs.get('z').is_none());
     }
+
+    #[test]
+    fn test_clipboard_content_splits_for_the_target() {
+        use crate::document::node::YamlValue;
+
+        let content = clipboard_content("image: nginx\nports: [80, 443]\n", true);
+        assert_eq!(
+            content.keys,
+            vec![Some("image".to_string()), Some("ports".to_string())]
+        );
+        assert!(matches!(content.nodes[1].value(), YamlValue::Array(items) if items.len() == 2));
+
+        // The same mapping pasted into a list is one element
+        let content = clipboard_content("image: nginx\nports: [80, 443]\n", false);
+        assert_eq!(content.keys, vec![None]);
+        assert!(matches!(content.nodes[0].value(), YamlValue::Object(_)));
+
+        // JSON arrays become separate list elements
+        let content = clipboard_content("[{\"a\": 1}, 2, \"x\"]", false);
+        assert_eq!(content.nodes.len(), 3);
+        assert!(content.nodes.iter().all(|n| n.is_modified()));
+    }
+
+    #[test]
+    fn test_clipboard_content_falls_back_to_text() {
+        use crate::document::node::{YamlString, YamlValue};
+
+        let content = clipboard_content("key: [unclosed\nsecond line\n", true);
+        assert_eq!(
+            content.nodes[0].value(),
+            &YamlValue::String(YamlString::Literal(
+                "key: [unclosed\nsecond line\n".to_string()
+            ))
+        );
+        assert!(clipboard_content("  \n", true).is_empty());
+    }
 }
@@ -0,0 +1,217 @@
+//! Query REPL for trying YAMLPath expressions against the buffer.
+//!
+//! The REPL pane (`:repl`) evaluates the expression as it is typed and lists
+//! the matching nodes without moving the main cursor. Submitted expressions
+//! are kept in a history that can be recalled with Up/Down, and the current
+//! result set can be promoted into the main view's search results.
+
+/// Maximum number of expressions kept in the REPL history.
+const MAX_HISTORY: usize = 100;
+
+/// Input line, history and latest results of the query REPL.
+#[derive(Debug, Clone, Default)]
+pub struct ReplState {
+    /// Current expression being typed
+    input: String,
+    /// Cursor position within `input`, in characters
+    cursor: usize,
+    /// Previously submitted expressions, oldest first
+    history: Vec<String>,
+    /// Position while browsing history (`None` when editing a fresh line)
+    history_pos: Option<usize>,
+    /// Paths matched by the current expression
+    results: Vec<Vec<usize>>,
+    /// Parse error for the current expression, if any
+    error: Option<String>,
+}
+
+impl ReplState {
+    /// Creates an empty REPL.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the expression being typed.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Returns the cursor position within the input, in characters.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Returns submitted expressions, oldest first.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Returns the paths matched by the current expression.
+    pub fn results(&self) -> &[Vec<usize>] {
+        &self.results
+    }
+
+    /// Returns the parse error for the current expression, if any.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Records the outcome of evaluating the current expression.
+    pub fn set_results(&mut self, results: Result<Vec<Vec<usize>>, String>) {
+        match results {
+            Ok(paths) => {
+                self.results = paths;
+                self.error = None;
+            }
+            Err(e) => {
+                self.results.clear();
+                self.error = Some(e);
+            }
+        }
+    }
+
+    /// Inserts a character at the cursor.
+    pub fn insert_char(&mut self, c: char) {
+        let byte = self.byte_index(self.cursor);
+        self.input.insert(byte, c);
+        self.cursor += 1;
+        self.history_pos = None;
+    }
+
+    /// Deletes the character before the cursor.
+    pub fn backspace(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.cursor -= 1;
+        let byte = self.byte_index(self.cursor);
+        self.input.remove(byte);
+        self.history_pos = None;
+    }
+
+    /// Moves the cursor one character left.
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// Moves the cursor one character right.
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.input.chars().count());
+    }
+
+    /// Adds the current expression to the history.
+    ///
+    /// Empty expressions and immediate repeats are not recorded.
+    pub fn submit(&mut self) {
+        let expr = self.input.trim().to_string();
+        self.history_pos = None;
+        if expr.is_empty() || self.history.last() == Some(&expr) {
+            return;
+        }
+        self.history.push(expr);
+        if self.history.len() > MAX_HISTORY {
+            self.history.remove(0);
+        }
+    }
+
+    /// Replaces the input with the previous history entry.
+    ///
+    /// Returns true if the input changed.
+    pub fn history_prev(&mut self) -> bool {
+        let pos = match self.history_pos {
+            None if self.history.is_empty() => return false,
+            None => self.history.len() - 1,
+            Some(0) => return false,
+            Some(pos) => pos - 1,
+        };
+        self.recall(Some(pos));
+        true
+    }
+
+    /// Replaces the input with the next history entry, or clears it past the newest.
+    ///
+    /// Returns true if the input changed.
+    pub fn history_next(&mut self) -> bool {
+        match self.history_pos {
+            None => false,
+            Some(pos) if pos + 1 < self.history.len() => {
+                self.recall(Some(pos + 1));
+                true
+            }
+            Some(_) => {
+                self.recall(None);
+                true
+            }
+        }
+    }
+
+    fn recall(&mut self, pos: Option<usize>) {
+        self.input = pos
+            .and_then(|p| self.history.get(p).cloned())
+            .unwrap_or_default();
+        self.cursor = self.input.chars().count();
+        self.history_pos = pos;
+    }
+
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.input
+            .char_indices()
+            .nth(char_index)
+            .map(|(i, _)| i)
+            .unwrap_or(self.input.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(text: &str) -> ReplState {
+        let mut repl = ReplState::new();
+        text.chars().for_each(|c| repl.insert_char(c));
+        repl
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut repl = typed("$.a");
+        repl.move_left();
+        repl.insert_char('é');
+        assert_eq!(repl.input(), "$.éa");
+        repl.backspace();
+        repl.backspace();
+        assert_eq!(repl.input(), "$a");
+        assert_eq!(repl.cursor(), 1);
+    }
+
+    #[test]
+    fn test_history_navigation() {
+        let mut repl = typed("$.a");
+        repl.submit();
+        repl.recall(None);
+        "$.b".chars().for_each(|c| repl.insert_char(c));
+        repl.submit();
+        repl.submit(); // repeat is not recorded
+        assert_eq!(repl.history(), ["$.a", "$.b"]);
+
+        assert!(repl.history_prev());
+        assert_eq!(repl.input(), "$.b");
+        assert!(repl.history_prev());
+        assert_eq!(repl.input(), "$.a");
+        assert!(!repl.history_prev());
+        assert!(repl.history_next());
+        assert_eq!(repl.input(), "$.b");
+        assert!(repl.history_next());
+        assert_eq!(repl.input(), "");
+        assert!(!repl.history_next());
+    }
+
+    #[test]
+    fn test_error_clears_results() {
+        let mut repl = ReplState::new();
+        repl.set_results(Ok(vec![vec![0]]));
+        repl.set_results(Err("bad".to_string()));
+        assert!(repl.results().is_empty());
+        assert_eq!(repl.error(), Some("bad"));
+    }
+}
@@ -0,0 +1,119 @@
+//! Idle-time re-validation (`:set autovalidate`).
+//!
+//! Validating a large document against its schema after every edit would
+//! make each keystroke wait for the whole document to be checked. Instead,
+//! an edit only queues the work: validation is split into units (the root's
+//! own keywords and each top-level entry, see [`Schema::units`]), and the
+//! event loop runs a few of them whenever it is idle, up to [`IDLE_BUDGET`]
+//! per turn. The section under the cursor is queued first, so the problems
+//! being worked on show up soonest.
+//!
+//! Each unit replaces only the diagnostics it covers; the rest keep their
+//! last results until their turn comes.
+
+use crate::schema::{Schema, ValidationUnit};
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Time spent validating per idle turn of the event loop.
+pub const IDLE_BUDGET: Duration = Duration::from_millis(10);
+
+/// A loaded schema, reloaded when its file changes.
+#[derive(Debug, Clone)]
+struct CachedSchema {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    schema: Schema,
+}
+
+/// Validation units waiting for idle time.
+#[derive(Debug, Clone, Default)]
+pub struct Revalidation {
+    pending: VecDeque<ValidationUnit>,
+    cached: Option<CachedSchema>,
+}
+
+impl Revalidation {
+    /// Creates an empty queue.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the queue with `units`, moving the top-level entry at
+    /// `first` (the cursor's section) to the front.
+    pub fn schedule(&mut self, units: Vec<ValidationUnit>, first: Option<usize>) {
+        self.pending = units.into();
+        if let Some(pos) = first.and_then(|i| {
+            self.pending
+                .iter()
+                .position(|&unit| unit == ValidationUnit::Entry(i))
+        }) {
+            if let Some(unit) = self.pending.remove(pos) {
+                self.pending.push_front(unit);
+            }
+        }
+    }
+
+    /// Takes the next unit to validate.
+    pub fn take_next(&mut self) -> Option<ValidationUnit> {
+        self.pending.pop_front()
+    }
+
+    /// Returns true while units are waiting.
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Drops all waiting units.
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Returns the schema at `path`, loading it the first time and again
+    /// whenever the file's modification time changes.
+    pub fn schema(&mut self, path: &Path) -> Result<&Schema> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let fresh = self
+            .cached
+            .as_ref()
+            .is_some_and(|c| c.path == path && c.modified == modified);
+        if !fresh {
+            self.cached = Some(CachedSchema {
+                path: path.to_path_buf(),
+                modified,
+                schema: Schema::load(path)?,
+            });
+        }
+        Ok(&self.cached.as_ref().expect("schema was just cached").schema)
+    }
+
+    /// Returns the schema loaded by the last call to [`Revalidation::schema`].
+    pub fn cached_schema(&self) -> Option<&Schema> {
+        self.cached.as_ref().map(|c| &c.schema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_puts_cursor_section_first() {
+        let mut queue = Revalidation::new();
+        queue.schedule(
+            vec![
+                ValidationUnit::Root,
+                ValidationUnit::Entry(0),
+                ValidationUnit::Entry(1),
+            ],
+            Some(1),
+        );
+        assert_eq!(queue.take_next(), Some(ValidationUnit::Entry(1)));
+        assert_eq!(queue.take_next(), Some(ValidationUnit::Root));
+        assert!(queue.is_pending());
+        queue.cancel();
+        assert_eq!(queue.take_next(), None);
+    }
+}
@@ -0,0 +1,648 @@
+//! Review mode: step through structural changes and keep or revert each.
+//!
+//! `:review` compares the buffer with the file on disk (`:review head`
+//! compares with the version committed at git `HEAD`) and walks the
+//! differences one at a time. Each change can be kept, reverted to the base
+//! version, or edited by hand before saving.
+//!
+//! Changes are found structurally: mappings are compared key by key and
+//! sequences are aligned on their longest common subsequence, so inserting
+//! an item shows up as one added item rather than every later item changing.
+//! Locations are stored as keys and indices instead of tree paths, and the
+//! diff is recomputed after every revert so later locations stay correct.
+
+use crate::document::node::{YamlNode, YamlValue};
+use anyhow::{anyhow, Result};
+
+/// Sequences longer than this are compared position by position instead of
+/// being aligned, to keep the alignment table small.
+const MAX_ALIGN_LEN: usize = 1000;
+
+/// One step in a change's location.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Step {
+    /// A mapping key
+    Key(String),
+    /// A sequence (or document) index
+    Index(usize),
+}
+
+/// What happened to a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    /// The node is new in the buffer
+    Added,
+    /// The node exists only in the base version
+    Removed,
+    /// The node exists in both but its value differs
+    Modified,
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeKind::Added => write!(f, "added"),
+            ChangeKind::Removed => write!(f, "removed"),
+            ChangeKind::Modified => write!(f, "modified"),
+        }
+    }
+}
+
+/// A difference between the base version and the buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// What happened
+    pub kind: ChangeKind,
+    /// Where, as keys and indices into the buffer.
+    ///
+    /// For a removed node the last step is where it would be reinserted:
+    /// its key, or the sequence index it would occupy.
+    pub location: Vec<Step>,
+    /// The node in the base version (`None` if added)
+    pub base: Option<YamlNode>,
+    /// The node in the buffer (`None` if removed)
+    pub current: Option<YamlNode>,
+    /// For a removed mapping entry, the index to reinsert it at
+    pub insert_at: usize,
+}
+
+impl Change {
+    /// Returns the location as a bracket-notation YAMLPath.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::document::parser::parse_yaml_auto;
+    /// use yamlquill::editor::review::diff;
+    ///
+    /// let base = parse_yaml_auto("spec:\n  replicas: 3\n").unwrap();
+    /// let current = parse_yaml_auto("spec:\n  replicas: 5\n").unwrap();
+    /// assert_eq!(diff(&base, &current)[0].location_string(), "$[\"spec\"][\"replicas\"]");
+    /// ```
+    pub fn location_string(&self) -> String {
+        let mut out = String::from("$");
+        for step in &self.location {
+            match step {
+                Step::Key(key) => out.push_str(&format!("[\"{}\"]", key.replace('"', "\\\""))),
+                Step::Index(index) => out.push_str(&format!("[{}]", index)),
+            }
+        }
+        out
+    }
+
+    /// Returns the location in dot notation (`.spec.ports[0]`), or `.` for
+    /// the node a diff started from.
+    pub fn relative_label(&self) -> String {
+        dot_label(&self.location)
+    }
+}
+
+/// Returns the changes that turn `base` into `current`, in document order.
+pub fn diff(base: &YamlNode, current: &YamlNode) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_into(base, current, &mut Vec::new(), &mut changes);
+    changes
+}
+
+fn diff_into(base: &YamlNode, current: &YamlNode, location: &mut Vec<Step>, out: &mut Vec<Change>) {
+    match (base.value(), current.value()) {
+        (YamlValue::Object(b), YamlValue::Object(c)) => {
+            for (key, current_child) in c {
+                location.push(Step::Key(key.clone()));
+                match b.get(key) {
+                    Some(base_child) => diff_into(base_child, current_child, location, out),
+                    None => out.push(added(location, current_child)),
+                }
+                location.pop();
+            }
+            // Reinsert removed entries after the surviving entries that preceded them
+            let mut survivors = 0;
+            for (key, base_child) in b {
+                if c.contains_key(key) {
+                    survivors += 1;
+                    continue;
+                }
+                location.push(Step::Key(key.clone()));
+                out.push(Change {
+                    kind: ChangeKind::Removed,
+                    location: location.clone(),
+                    base: Some(base_child.clone()),
+                    current: None,
+                    insert_at: survivors,
+                });
+                location.pop();
+            }
+        }
+        (YamlValue::Array(b), YamlValue::Array(c))
+        | (YamlValue::MultiDoc(b), YamlValue::MultiDoc(c)) => diff_sequences(b, c, location, out),
+        _ => {
+            if !same_value(base, current) {
+                out.push(Change {
+                    kind: ChangeKind::Modified,
+                    location: location.clone(),
+                    base: Some(base.clone()),
+                    current: Some(current.clone()),
+                    insert_at: 0,
+                });
+            }
+        }
+    }
+}
+
+fn added(location: &[Step], node: &YamlNode) -> Change {
+    Change {
+        kind: ChangeKind::Added,
+        location: location.to_vec(),
+        base: None,
+        current: Some(node.clone()),
+        insert_at: 0,
+    }
+}
+
+/// Diffs two sequences, pairing up unmatched items between common ones.
+fn diff_sequences(b: &[YamlNode], c: &[YamlNode], location: &mut Vec<Step>, out: &mut Vec<Change>) {
+    let matches = align(b, c);
+    let (mut i, mut j) = (0, 0);
+    for (mi, mj) in matches
+        .into_iter()
+        .chain(std::iter::once((b.len(), c.len())))
+    {
+        // Items between matches: pair them as modifications, then the rest
+        // are additions or removals
+        while i < mi && j < mj {
+            location.push(Step::Index(j));
+            diff_into(&b[i], &c[j], location, out);
+            location.pop();
+            i += 1;
+            j += 1;
+        }
+        while j < mj {
+            location.push(Step::Index(j));
+            out.push(added(location, &c[j]));
+            location.pop();
+            j += 1;
+        }
+        while i < mi {
+            location.push(Step::Index(j));
+            out.push(Change {
+                kind: ChangeKind::Removed,
+                location: location.clone(),
+                base: Some(b[i].clone()),
+                current: None,
+                insert_at: j,
+            });
+            location.pop();
+            i += 1;
+        }
+        i += 1;
+        j += 1;
+    }
+}
+
+/// Returns the index pairs of a longest common subsequence of equal items.
+fn align(b: &[YamlNode], c: &[YamlNode]) -> Vec<(usize, usize)> {
+    if b.len() > MAX_ALIGN_LEN || c.len() > MAX_ALIGN_LEN {
+        return Vec::new();
+    }
+    // lengths[i][j] = LCS length of b[i..] and c[j..]
+    let mut lengths = vec![vec![0u32; c.len() + 1]; b.len() + 1];
+    for i in (0..b.len()).rev() {
+        for j in (0..c.len()).rev() {
+            lengths[i][j] = if same_value(&b[i], &c[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < b.len() && j < c.len() {
+        if same_value(&b[i], &c[j]) {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Resolves a location of keys and indices to a tree path under `root`.
+pub fn resolve(root: &YamlNode, location: &[Step]) -> Option<Vec<usize>> {
+    let mut node = root;
+    let mut path = Vec::with_capacity(location.len());
+    for step in location {
+        let (index, child) = match (step, node.value()) {
+            (Step::Key(key), YamlValue::Object(entries)) => {
+                let (index, _, child) = entries.get_full(key)?;
+                (index, child)
+            }
+            (Step::Index(i), YamlValue::Array(items) | YamlValue::MultiDoc(items)) => {
+                (*i, items.get(*i)?)
+            }
+            _ => return None,
+        };
+        path.push(index);
+        node = child;
+    }
+    Some(path)
+}
+
+/// Returns the location of keys and indices of the node at `path`, the
+/// inverse of [`resolve`].
+pub fn locate(root: &YamlNode, path: &[usize]) -> Option<Vec<Step>> {
+    let mut node = root;
+    let mut location = Vec::with_capacity(path.len());
+    for &index in path {
+        let (step, child) = match node.value() {
+            YamlValue::Object(entries) => {
+                let (key, child) = entries.get_index(index)?;
+                (Step::Key(key.clone()), child)
+            }
+            YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+                (Step::Index(index), items.get(index)?)
+            }
+            _ => return None,
+        };
+        location.push(step);
+        node = child;
+    }
+    Some(location)
+}
+
+/// The `gD` popup: how one node differs from the base version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeDiff {
+    /// Where the node is, in dot notation (`.spec.replicas`)
+    pub path: String,
+    /// What the base version is (`"disk"`)
+    pub label: String,
+    /// The changes under the node, located relative to it. A node that is
+    /// new since the base version is one addition with an empty location.
+    pub changes: Vec<Change>,
+}
+
+/// Compares the node at `path` in `current` with the node at the same keys
+/// and indices in `base`.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::editor::review::{node_diff, ChangeKind};
+///
+/// let base = parse_yaml_auto("spec:\n  replicas: 3\n  image: a\n").unwrap();
+/// let current = parse_yaml_auto("spec:\n  replicas: 5\n  image: a\n").unwrap();
+/// let diff = node_diff(&base, &current, &[0], "disk");
+/// assert_eq!(diff.path, ".spec");
+/// assert_eq!(diff.changes.len(), 1);
+/// assert_eq!(diff.changes[0].kind, ChangeKind::Modified);
+/// assert_eq!(diff.changes[0].relative_label(), ".replicas");
+/// ```
+pub fn node_diff(base: &YamlNode, current: &YamlNode, path: &[usize], label: &str) -> NodeDiff {
+    let location = locate(current, path).unwrap_or_default();
+    let node = node_at(current, path);
+    let base_node = resolve(base, &location).and_then(|base_path| node_at(base, &base_path));
+    let changes = match (base_node, node) {
+        (Some(base_node), Some(node)) => diff(base_node, node),
+        (None, Some(node)) => vec![added(&[], node)],
+        _ => Vec::new(),
+    };
+    NodeDiff {
+        path: dot_label(&location),
+        label: label.to_string(),
+        changes,
+    }
+}
+
+fn dot_label(location: &[Step]) -> String {
+    if location.is_empty() {
+        return ".".to_string();
+    }
+    let mut out = String::new();
+    for step in location {
+        match step {
+            Step::Key(key) => {
+                out.push('.');
+                out.push_str(key);
+            }
+            Step::Index(index) => out.push_str(&format!("[{}]", index)),
+        }
+    }
+    out
+}
+
+fn node_at<'a>(root: &'a YamlNode, path: &[usize]) -> Option<&'a YamlNode> {
+    let mut node = root;
+    for &index in path {
+        node = match node.value() {
+            YamlValue::Object(entries) => entries.get_index(index)?.1,
+            YamlValue::Array(items) | YamlValue::MultiDoc(items) => items.get(index)?,
+            _ => return None,
+        };
+    }
+    Some(node)
+}
+
+fn node_at_mut<'a>(root: &'a mut YamlNode, path: &[usize]) -> Option<&'a mut YamlNode> {
+    let mut node = root;
+    for &index in path {
+        node = match node.value_mut_traverse() {
+            YamlValue::Object(entries) => entries.get_index_mut(index)?.1,
+            YamlValue::Array(items) | YamlValue::MultiDoc(items) => items.get_mut(index)?,
+            _ => return None,
+        };
+    }
+    Some(node)
+}
+
+/// Undoes one change under `root`, restoring the base version of the node.
+///
+/// Returns the tree path of the restored node, or for an added node the
+/// path it occupied.
+pub fn revert(root: &mut YamlNode, change: &Change) -> Result<Vec<usize>> {
+    let missing = || anyhow!("Change no longer applies");
+    // Nodes from the base version must not reuse its source spans
+    let base = change.base.clone().map(|mut node| {
+        node.mark_all_modified();
+        node
+    });
+    let Some((last, parent_location)) = change.location.split_last() else {
+        // The whole document changed type; restore it wholesale
+        *root = base.ok_or_else(missing)?;
+        return Ok(Vec::new());
+    };
+    let mut path = resolve(root, parent_location).ok_or_else(missing)?;
+    let parent = node_at_mut(root, &path).ok_or_else(missing)?;
+
+    match (change.kind, parent.value_mut(), last) {
+        (ChangeKind::Modified, YamlValue::Object(entries), Step::Key(key)) => {
+            let (index, _, node) = entries.get_full_mut(key).ok_or_else(missing)?;
+            *node = base.ok_or_else(missing)?;
+            path.push(index);
+        }
+        (ChangeKind::Added, YamlValue::Object(entries), Step::Key(key)) => {
+            let (index, _, _) = entries.shift_remove_full(key).ok_or_else(missing)?;
+            path.push(index);
+        }
+        (ChangeKind::Removed, YamlValue::Object(entries), Step::Key(key)) => {
+            let index = change.insert_at.min(entries.len());
+            entries.shift_insert(index, key.clone(), base.ok_or_else(missing)?);
+            path.push(index);
+        }
+        (kind, YamlValue::Array(items) | YamlValue::MultiDoc(items), Step::Index(index)) => {
+            let index = *index;
+            match kind {
+                ChangeKind::Modified => {
+                    *items.get_mut(index).ok_or_else(missing)? = base.ok_or_else(missing)?
+                }
+                ChangeKind::Added if index < items.len() => {
+                    items.remove(index);
+                }
+                ChangeKind::Removed => {
+                    items.insert(index.min(items.len()), base.ok_or_else(missing)?)
+                }
+                ChangeKind::Added => return Err(missing()),
+            }
+            path.push(index.min(items.len()));
+        }
+        _ => return Err(missing()),
+    }
+    Ok(path)
+}
+
+/// Returns `current` with every change not under one of the `staged`
+/// locations reverted to `base`, along with the number of changes kept.
+///
+/// This is what `:w --staged` writes: the file on disk plus only the staged
+/// changes.
+pub fn apply_staged(
+    base: &YamlNode,
+    current: &YamlNode,
+    staged: &[Vec<Step>],
+) -> (YamlNode, usize) {
+    let mut result = current.clone();
+    let mut kept = 0;
+    // Last first, so reverting a change doesn't move the ones before it
+    for change in diff(base, current).iter().rev() {
+        if staged
+            .iter()
+            .any(|location| change.location.starts_with(location))
+        {
+            kept += 1;
+        } else {
+            let _ = revert(&mut result, change);
+        }
+    }
+    (result, kept)
+}
+
+/// Returns true if two nodes have the same value, ignoring source positions
+/// and modification flags.
+pub fn same_value(a: &YamlNode, b: &YamlNode) -> bool {
+    match (a.value(), b.value()) {
+        (YamlValue::Object(x), YamlValue::Object(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .zip(y)
+                    .all(|((kx, vx), (ky, vy))| kx == ky && same_value(vx, vy))
+        }
+        (YamlValue::Array(x), YamlValue::Array(y))
+        | (YamlValue::MultiDoc(x), YamlValue::MultiDoc(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(vx, vy)| same_value(vx, vy))
+        }
+        (YamlValue::Comment(x), YamlValue::Comment(y)) => {
+            x.content() == y.content() && x.position() == y.position()
+        }
+        (x, y) => x == y,
+    }
+}
+
+/// Progress through a review.
+#[derive(Debug, Clone)]
+pub struct ReviewState {
+    base: YamlNode,
+    label: String,
+    changes: Vec<Change>,
+    index: usize,
+    kept: Vec<(ChangeKind, Vec<Step>)>,
+    reverted: usize,
+}
+
+impl ReviewState {
+    /// Starts reviewing `current` against `base`, described by `label`
+    /// (`"disk"` or `"HEAD"`).
+    pub fn new(base: YamlNode, label: &str, current: &YamlNode) -> Self {
+        let mut review = Self {
+            changes: Vec::new(),
+            base,
+            label: label.to_string(),
+            index: 0,
+            kept: Vec::new(),
+            reverted: 0,
+        };
+        review.refresh(current);
+        review
+    }
+
+    /// Recomputes the changes after the buffer changed, moving to the first
+    /// change that hasn't been kept.
+    pub fn refresh(&mut self, current: &YamlNode) {
+        self.changes = diff(&self.base, current);
+        self.index = self
+            .changes
+            .iter()
+            .position(|c| !self.is_kept(c))
+            .unwrap_or(0);
+    }
+
+    /// Returns what the buffer is compared with.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the base version's root node.
+    pub fn base(&self) -> &YamlNode {
+        &self.base
+    }
+
+    /// Returns every change, kept or not.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Returns the index of the change being reviewed.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the change being reviewed.
+    pub fn current(&self) -> Option<&Change> {
+        self.changes.get(self.index)
+    }
+
+    /// Returns true if `change` has been kept.
+    pub fn is_kept(&self, change: &Change) -> bool {
+        self.kept
+            .iter()
+            .any(|(kind, location)| *kind == change.kind && *location == change.location)
+    }
+
+    /// Returns the number of changes not yet kept.
+    pub fn remaining(&self) -> usize {
+        self.changes.iter().filter(|c| !self.is_kept(c)).count()
+    }
+
+    /// Returns the number of changes kept and reverted so far.
+    pub fn counts(&self) -> (usize, usize) {
+        (self.kept.len(), self.reverted)
+    }
+
+    /// Keeps the current change and moves to the next undecided one.
+    pub fn keep(&mut self) {
+        let Some(change) = self.current() else {
+            return;
+        };
+        if !self.is_kept(change) {
+            self.kept.push((change.kind, change.location.clone()));
+        }
+        let count = self.changes.len();
+        if let Some(next) = (1..=count)
+            .map(|offset| (self.index + offset) % count)
+            .find(|&i| !self.is_kept(&self.changes[i]))
+        {
+            self.index = next;
+        }
+    }
+
+    /// Records that the current change was reverted.
+    pub fn note_reverted(&mut self) {
+        self.reverted += 1;
+    }
+
+    /// Moves to the next change, wrapping around.
+    pub fn next(&mut self) {
+        if !self.changes.is_empty() {
+            self.index = (self.index + 1) % self.changes.len();
+        }
+    }
+
+    /// Moves to the previous change, wrapping around.
+    pub fn previous(&mut self) {
+        if !self.changes.is_empty() {
+            self.index = (self.index + self.changes.len() - 1) % self.changes.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    fn changes(base: &str, current: &str) -> Vec<(ChangeKind, String)> {
+        let base = parse_yaml_auto(base).unwrap();
+        let current = parse_yaml_auto(current).unwrap();
+        diff(&base, &current)
+            .iter()
+            .map(|c| (c.kind, c.location_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_mapping_changes() {
+        assert_eq!(
+            changes("a: 1\nb: 2\nc: 3\n", "a: 1\nb: 20\nd: 4\n"),
+            [
+                (ChangeKind::Modified, "$[\"b\"]".to_string()),
+                (ChangeKind::Added, "$[\"d\"]".to_string()),
+                (ChangeKind::Removed, "$[\"c\"]".to_string()),
+            ]
+        );
+        assert!(changes("a: 1\n", "a: 1\n").is_empty());
+    }
+
+    #[test]
+    fn test_sequence_alignment() {
+        // Inserting at the front is one addition, not three modifications
+        assert_eq!(
+            changes("- a\n- b\n- c\n", "- z\n- a\n- b\n- c\n"),
+            [(ChangeKind::Added, "$[0]".to_string())]
+        );
+        assert_eq!(
+            changes("- a\n- b\n- c\n", "- a\n- c\n"),
+            [(ChangeKind::Removed, "$[1]".to_string())]
+        );
+        assert_eq!(
+            changes("- a\n- b\n- c\n", "- a\n- x\n- c\n"),
+            [(ChangeKind::Modified, "$[1]".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_removed_entry_position() {
+        let base = parse_yaml_auto("a: 1\nb: 2\nc: 3\n").unwrap();
+        let current = parse_yaml_auto("a: 1\nc: 3\n").unwrap();
+        assert_eq!(diff(&base, &current)[0].insert_at, 1);
+    }
+
+    #[test]
+    fn test_keep_advances_past_kept() {
+        let base = parse_yaml_auto("a: 1\nb: 2\n").unwrap();
+        let current = parse_yaml_auto("a: 10\nb: 20\n").unwrap();
+        let mut review = ReviewState::new(base, "disk", &current);
+        assert_eq!(review.remaining(), 2);
+
+        review.keep();
+        assert_eq!(review.index(), 1);
+        review.keep();
+        assert_eq!(review.remaining(), 0);
+
+        review.refresh(&current);
+        assert_eq!(review.counts(), (2, 0));
+    }
+}
@@ -0,0 +1,172 @@
+//! Record samples of huge multi-document and JSONL files (`--sample`,
+//! `:sample`).
+//!
+//! Scrolling through millions of log records is slow and rarely useful; a
+//! few thousand picked at random (or the first or last few thousand) show
+//! the shape of the data. A sampled buffer keeps the records in their file
+//! order and is read-only, so the sample can't be written over the full file
+//! by accident; `:sample off` brings every record back.
+
+use anyhow::{bail, Result};
+
+/// Which records a sample takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleMode {
+    /// The first records
+    Head,
+    /// The last records
+    Tail,
+    /// Records picked uniformly at random
+    Random,
+}
+
+impl SampleMode {
+    /// Returns the mode's name as typed in `:sample`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Head => "head",
+            Self::Tail => "tail",
+            Self::Random => "random",
+        }
+    }
+}
+
+/// How many records to keep, and which.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub mode: SampleMode,
+    pub count: usize,
+}
+
+impl Sample {
+    /// Parses `:sample` arguments: a count, optionally preceded by `head`,
+    /// `tail` or `random` (the default).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::editor::sample::{Sample, SampleMode};
+    ///
+    /// let sample = Sample::parse("tail 500").unwrap();
+    /// assert_eq!(sample, Sample { mode: SampleMode::Tail, count: 500 });
+    /// assert_eq!(Sample::parse("1000").unwrap().mode, SampleMode::Random);
+    /// assert!(Sample::parse("some").is_err());
+    /// ```
+    pub fn parse(args: &str) -> Result<Self> {
+        let words: Vec<&str> = args.split_whitespace().collect();
+        let (mode, count) = match words.as_slice() {
+            [count] => (SampleMode::Random, *count),
+            ["head", count] => (SampleMode::Head, *count),
+            ["tail", count] => (SampleMode::Tail, *count),
+            ["random", count] => (SampleMode::Random, *count),
+            _ => bail!("Usage: :sample [head|tail|random] <count> | off"),
+        };
+        match count.parse::<usize>() {
+            Ok(count) if count > 0 => Ok(Self { mode, count }),
+            _ => bail!(
+                "Invalid sample size: {} (expected a positive number)",
+                count
+            ),
+        }
+    }
+
+    /// Returns the indexes of the records to keep out of `total`, in
+    /// ascending order. `seed` drives random samples.
+    pub fn indices(&self, total: usize, seed: u64) -> Vec<usize> {
+        let count = self.count.min(total);
+        match self.mode {
+            SampleMode::Head => (0..count).collect(),
+            SampleMode::Tail => (total - count..total).collect(),
+            SampleMode::Random => {
+                // Selection sampling (Knuth's algorithm S): one pass, already sorted
+                let mut rng = XorShift(seed | 1);
+                let mut picked = Vec::with_capacity(count);
+                for index in 0..total {
+                    let needed = (count - picked.len()) as u64;
+                    let left = (total - index) as u64;
+                    if rng.next_u64() % left < needed {
+                        picked.push(index);
+                    }
+                }
+                picked
+            }
+        }
+    }
+}
+
+/// What a sampled buffer shows, for the status line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleInfo {
+    /// The sample taken
+    pub sample: Sample,
+    /// Number of records shown
+    pub shown: usize,
+    /// Number of records in the full file
+    pub total: usize,
+}
+
+/// A small xorshift generator; samples don't need a strong one.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// Returns a seed for random samples that differs from run to run.
+pub fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0x9e37_79b9_7f4a_7c15)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_and_tail() {
+        let head = Sample {
+            mode: SampleMode::Head,
+            count: 3,
+        };
+        assert_eq!(head.indices(10, 0), [0, 1, 2]);
+        let tail = Sample {
+            mode: SampleMode::Tail,
+            count: 3,
+        };
+        assert_eq!(tail.indices(10, 0), [7, 8, 9]);
+        assert_eq!(tail.indices(2, 0), [0, 1]);
+    }
+
+    #[test]
+    fn test_random_sample_is_sorted_distinct_and_exact() {
+        let sample = Sample {
+            mode: SampleMode::Random,
+            count: 100,
+        };
+        for seed in [1, 42, random_seed()] {
+            let picked = sample.indices(1000, seed);
+            assert_eq!(picked.len(), 100);
+            assert!(picked.windows(2).all(|w| w[0] < w[1]));
+            assert!(picked.iter().all(|&i| i < 1000));
+        }
+        assert_ne!(sample.indices(1000, 1), sample.indices(1000, 2));
+        assert_eq!(sample.indices(50, 7), (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Sample::parse("").is_err());
+        assert!(Sample::parse("head").is_err());
+        assert_eq!(
+            Sample::parse("head 0").unwrap_err().to_string(),
+            "Invalid sample size: 0 (expected a positive number)"
+        );
+    }
+}
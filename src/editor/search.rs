@@ -0,0 +1,270 @@
+//! Patterns for `/` and `?` search.
+//!
+//! The search buffer is a plain substring by default. A leading escape picks
+//! another mode:
+//!
+//! - `\v` — a regular expression (see [`crate::editor::regex`]), also the
+//!   default when `:set regexsearch` is on
+//! - `\V` — a literal substring, even with `regexsearch` on
+//! - `\g` — a glob over dotted key paths, like `spec.*.image`
+//!
+//! All modes use smart case: matching ignores case unless the pattern has an
+//! uppercase letter.
+
+use crate::editor::regex::Regex;
+use anyhow::{bail, Result};
+
+/// A compiled search pattern.
+#[derive(Debug, Clone)]
+pub enum SearchPattern {
+    /// Substring match against keys and string values.
+    Text { query: String, ignore_case: bool },
+    /// Regex match against keys and string values.
+    Regex(Regex),
+    /// Glob match against a node's dotted key path.
+    KeyGlob(KeyGlob),
+}
+
+impl SearchPattern {
+    /// Compiles the search buffer. `regex_default` makes unprefixed patterns
+    /// regexes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::editor::search::SearchPattern;
+    ///
+    /// let pattern = SearchPattern::parse(r"\vimage:\d+", false).unwrap();
+    /// assert!(pattern.matches_text("image:42"));
+    /// assert!(SearchPattern::parse(r"\v(", false).is_err());
+    /// ```
+    pub fn parse(input: &str, regex_default: bool) -> Result<Self> {
+        let (mode, body) = match input.get(..2) {
+            Some(r"\v") => ('v', &input[2..]),
+            Some(r"\V") => ('V', &input[2..]),
+            Some(r"\g") => ('g', &input[2..]),
+            _ if regex_default => ('v', input),
+            _ => ('V', input),
+        };
+        let ignore_case = !body.chars().any(|c| c.is_uppercase());
+        Ok(match mode {
+            'v' => Self::Regex(Regex::with_case(body, ignore_case)?),
+            'g' => Self::KeyGlob(KeyGlob::new(body, ignore_case)?),
+            _ => Self::Text {
+                query: if ignore_case {
+                    body.to_lowercase()
+                } else {
+                    body.to_string()
+                },
+                ignore_case,
+            },
+        })
+    }
+
+    /// Returns true if the pattern is empty, e.g. just a mode prefix.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Text { query, .. } => query.is_empty(),
+            Self::Regex(_) => false,
+            Self::KeyGlob(glob) => glob.segments.is_empty(),
+        }
+    }
+
+    /// Returns true if a key or string value matches. Key globs never match
+    /// plain text; use [`SearchPattern::matches_path`] for them.
+    pub fn matches_text(&self, text: &str) -> bool {
+        match self {
+            Self::Text { query, ignore_case } => {
+                if *ignore_case {
+                    text.to_lowercase().contains(query.as_str())
+                } else {
+                    text.contains(query.as_str())
+                }
+            }
+            Self::Regex(regex) => regex.is_match(text),
+            Self::KeyGlob(_) => false,
+        }
+    }
+
+    /// Returns true if a key glob matches the node's key path (keys and list
+    /// indexes from the root). Other patterns never match paths.
+    pub fn matches_path(&self, segments: &[String]) -> bool {
+        match self {
+            Self::KeyGlob(glob) => glob.is_match(segments),
+            _ => false,
+        }
+    }
+}
+
+/// A glob over dotted key paths.
+///
+/// Segments are separated by `.`. Within a segment `*` matches any run of
+/// characters and `?` matches one; a `**` segment matches any number of
+/// segments. The pattern matches the end of a path, so `spec.*.image` finds
+/// `image` two levels under any `spec`; a leading `.` anchors it at the root.
+/// List indexes are plain numbers (`containers.0.image`).
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::search::KeyGlob;
+///
+/// let glob = KeyGlob::new("spec.*.image", false).unwrap();
+/// let path = |p: &str| p.split('.').map(String::from).collect::<Vec<_>>();
+/// assert!(glob.is_match(&path("template.spec.web.image")));
+/// assert!(!glob.is_match(&path("spec.web.ports.image")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeyGlob {
+    segments: Vec<String>,
+    anchored: bool,
+    ignore_case: bool,
+}
+
+impl KeyGlob {
+    /// Compiles a key glob.
+    pub fn new(pattern: &str, ignore_case: bool) -> Result<Self> {
+        let (anchored, body) = match pattern.strip_prefix('.') {
+            Some(rest) => (true, rest),
+            None => (false, pattern),
+        };
+        if body.is_empty() {
+            return Ok(Self {
+                segments: Vec::new(),
+                anchored,
+                ignore_case,
+            });
+        }
+        let segments: Vec<String> = body
+            .split('.')
+            .map(|s| {
+                if ignore_case {
+                    s.to_lowercase()
+                } else {
+                    s.to_string()
+                }
+            })
+            .collect();
+        if segments.iter().any(|s| s.is_empty()) {
+            bail!("Invalid key glob: empty segment in '{}'", pattern);
+        }
+        if let Some(bad) = segments.iter().find(|s| *s != "**" && s.contains("**")) {
+            bail!(
+                "Invalid key glob: '**' must be a whole segment, not '{}'",
+                bad
+            );
+        }
+        Ok(Self {
+            segments,
+            anchored,
+            ignore_case,
+        })
+    }
+
+    /// Returns true if the glob matches the path's segments.
+    pub fn is_match(&self, path: &[String]) -> bool {
+        if self.segments.is_empty() {
+            return false;
+        }
+        let path: Vec<String> = if self.ignore_case {
+            path.iter().map(|s| s.to_lowercase()).collect()
+        } else {
+            path.to_vec()
+        };
+        if self.anchored {
+            return match_segments(&self.segments, &path);
+        }
+        (0..path.len()).any(|start| match_segments(&self.segments, &path[start..]))
+    }
+}
+
+fn match_segments(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| match_segments(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, tail)) => wildcard_match(first, segment) && match_segments(rest, tail),
+            None => false,
+        },
+    }
+}
+
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    fn go(p: &[char], t: &[char]) -> bool {
+        match p.split_first() {
+            None => t.is_empty(),
+            Some(('*', rest)) => (0..=t.len()).any(|skip| go(rest, &t[skip..])),
+            Some(('?', rest)) => !t.is_empty() && go(rest, &t[1..]),
+            Some((c, rest)) => t.first() == Some(c) && go(rest, &t[1..]),
+        }
+    }
+    go(&pattern, &text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(p: &str) -> Vec<String> {
+        p.split('.').map(String::from).collect()
+    }
+
+    #[test]
+    fn test_prefixes_pick_the_mode() {
+        assert!(matches!(
+            SearchPattern::parse("a.b", false).unwrap(),
+            SearchPattern::Text { .. }
+        ));
+        assert!(matches!(
+            SearchPattern::parse("a.b", true).unwrap(),
+            SearchPattern::Regex(_)
+        ));
+        assert!(matches!(
+            SearchPattern::parse(r"\Va.b", true).unwrap(),
+            SearchPattern::Text { .. }
+        ));
+        assert!(matches!(
+            SearchPattern::parse(r"\ga.b", false).unwrap(),
+            SearchPattern::KeyGlob(_)
+        ));
+    }
+
+    #[test]
+    fn test_smart_case() {
+        let lower = SearchPattern::parse(r"\vna.e", false).unwrap();
+        assert!(lower.matches_text("NAME"));
+        let upper = SearchPattern::parse(r"\vNa.e", false).unwrap();
+        assert!(!upper.matches_text("name"));
+    }
+
+    #[test]
+    fn test_glob_wildcards() {
+        let glob = KeyGlob::new("containers.*.im?ge", false).unwrap();
+        assert!(glob.is_match(&path("spec.containers.0.image")));
+        assert!(!glob.is_match(&path("spec.containers.image")));
+
+        let deep = KeyGlob::new("spec.**.image", false).unwrap();
+        assert!(deep.is_match(&path("spec.image")));
+        assert!(deep.is_match(&path("spec.a.b.c.image")));
+
+        let partial = KeyGlob::new("*name", false).unwrap();
+        assert!(partial.is_match(&path("metadata.fullname")));
+    }
+
+    #[test]
+    fn test_leading_dot_anchors_at_root() {
+        let glob = KeyGlob::new(".spec.*", false).unwrap();
+        assert!(glob.is_match(&path("spec.replicas")));
+        assert!(!glob.is_match(&path("template.spec.replicas")));
+    }
+
+    #[test]
+    fn test_invalid_globs() {
+        assert!(KeyGlob::new("a..b", false).is_err());
+        assert!(KeyGlob::new("a.b**", false).is_err());
+    }
+}
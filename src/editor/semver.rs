@@ -0,0 +1,379 @@
+//! Semantic versions (`1.4.2`, `v2.0.0-rc.1+build.5`): `:bump`, the check
+//! of version fields, and `K` explanations.
+//!
+//! Versions follow [semver.org](https://semver.org): three numbers without
+//! leading zeros, then an optional pre-release (`-rc.1`) and build metadata
+//! (`+build.5`). A leading `v` is accepted and kept.
+
+use crate::document::node::{YamlNode, YamlValue};
+use crate::editor::diagnostics::{Severity, ValueProblem};
+
+/// Source name for the diagnostics the version check reports.
+pub const SEMVER_SOURCE: &str = "semver";
+
+/// Which number `:bump` increments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    Major,
+    Minor,
+    Patch,
+}
+
+impl Part {
+    /// Parses `major`, `minor` or `patch`.
+    pub fn parse(name: &str) -> Option<Part> {
+        match name {
+            "major" => Some(Part::Major),
+            "minor" => Some(Part::Minor),
+            "patch" => Some(Part::Patch),
+            _ => None,
+        }
+    }
+}
+
+/// A semantic version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    /// Whether the text starts with `v`
+    pub v_prefix: bool,
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Pre-release identifiers (`rc`, `1` for `-rc.1`)
+    pub pre: Vec<String>,
+    /// Build metadata identifiers (`build`, `5` for `+build.5`)
+    pub build: Vec<String>,
+}
+
+impl Version {
+    /// Parses a version, explaining what's wrong with one that isn't valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::editor::semver::{Part, Version};
+    ///
+    /// let version = Version::parse("v1.4.2-rc.1").unwrap();
+    /// assert_eq!(version.bump(Part::Minor).to_string(), "v1.5.0");
+    /// assert_eq!(
+    ///     Version::parse("1.4").unwrap_err(),
+    ///     "expected MAJOR.MINOR.PATCH, found 2 numbers"
+    /// );
+    /// ```
+    pub fn parse(text: &str) -> Result<Version, String> {
+        let (v_prefix, rest) = match text.strip_prefix('v') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (rest, build) = match rest.split_once('+') {
+            Some((rest, build)) => (rest, identifiers(build, "build metadata")?),
+            None => (rest, Vec::new()),
+        };
+        let (core, pre) = match rest.split_once('-') {
+            Some((core, pre)) => (core, identifiers(pre, "pre-release")?),
+            None => (rest, Vec::new()),
+        };
+        for identifier in &pre {
+            if is_number(identifier) && identifier.len() > 1 && identifier.starts_with('0') {
+                return Err(format!("pre-release {} has a leading zero", identifier));
+            }
+        }
+
+        let numbers: Vec<&str> = core.split('.').collect();
+        if numbers.len() != 3 {
+            return Err(format!(
+                "expected MAJOR.MINOR.PATCH, found {} number{}",
+                numbers.len(),
+                if numbers.len() == 1 { "" } else { "s" }
+            ));
+        }
+        let mut parsed = [0u64; 3];
+        for (value, number) in parsed.iter_mut().zip(&numbers) {
+            if !is_number(number) {
+                return Err(format!("'{}' is not a number", number));
+            }
+            if number.len() > 1 && number.starts_with('0') {
+                return Err(format!("{} has a leading zero", number));
+            }
+            *value = number
+                .parse()
+                .map_err(|_| format!("{} is too large", number))?;
+        }
+        let [major, minor, patch] = parsed;
+        Ok(Version {
+            v_prefix,
+            major,
+            minor,
+            patch,
+            pre,
+            build,
+        })
+    }
+
+    /// Returns the next version, dropping pre-release and build metadata.
+    ///
+    /// A pre-release is already ahead of its release, so bumping
+    /// `2.0.0-rc.1` by major, or `1.3.0-rc.1` by minor, releases it rather
+    /// than skipping a version (as `npm version` does).
+    pub fn bump(&self, part: Part) -> Version {
+        let release = !self.pre.is_empty();
+        let (major, minor, patch) = match part {
+            Part::Major if release && self.minor == 0 && self.patch == 0 => (self.major, 0, 0),
+            Part::Major => (self.major + 1, 0, 0),
+            Part::Minor if release && self.patch == 0 => (self.major, self.minor, 0),
+            Part::Minor => (self.major, self.minor + 1, 0),
+            Part::Patch if release => (self.major, self.minor, self.patch),
+            Part::Patch => (self.major, self.minor, self.patch + 1),
+        };
+        Version {
+            v_prefix: self.v_prefix,
+            major,
+            minor,
+            patch,
+            pre: Vec::new(),
+            build: Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.v_prefix {
+            write!(f, "v")?;
+        }
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        if !self.build.is_empty() {
+            write!(f, "+{}", self.build.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+fn is_number(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Splits dot-separated pre-release or build identifiers.
+fn identifiers(text: &str, what: &str) -> Result<Vec<String>, String> {
+    text.split('.')
+        .map(|identifier| {
+            if identifier.is_empty() {
+                Err(format!("empty {} identifier", what))
+            } else if !identifier
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+            {
+                Err(format!(
+                    "{} '{}' has characters other than letters, digits and -",
+                    what, identifier
+                ))
+            } else {
+                Ok(identifier.to_string())
+            }
+        })
+        .collect()
+}
+
+/// Returns true if `key` names a version (`version`, `appVersion`,
+/// `chart_version`), other than Kubernetes' `apiVersion` and `kubeVersion`.
+pub fn is_version_key(key: &str) -> bool {
+    key.eq_ignore_ascii_case("version")
+        || ((key.ends_with("Version") || key.to_ascii_lowercase().ends_with("_version"))
+            && !matches!(key, "apiVersion" | "kubeVersion"))
+}
+
+/// Returns true for text that reads like a dotted version (`1.2`, `v3.0.1`)
+/// rather than a range (`1.2.x`, `>=1.2.0`, `1.2 - 1.4`).
+pub fn looks_like_version(text: &str) -> bool {
+    let rest = text.strip_prefix('v').unwrap_or(text);
+    rest.starts_with(|c: char| c.is_ascii_digit())
+        && rest.contains('.')
+        && !rest.contains(char::is_whitespace)
+        && !rest.split('.').any(|part| matches!(part, "x" | "X" | "*"))
+}
+
+/// Checks string versions in version fields under `root`.
+///
+/// A dotted version that isn't semantic is a warning. Numbers are left
+/// alone, since a YAML number can't be a semantic version and is usually a
+/// file format's version (`version: 2`), as is the `version` of a Docker
+/// Compose file.
+pub fn check_values(root: &YamlNode) -> Vec<ValueProblem> {
+    let mut problems = Vec::new();
+    check_node(root, &mut Vec::new(), &mut problems);
+    problems
+}
+
+fn check_node(node: &YamlNode, path: &mut Vec<usize>, problems: &mut Vec<ValueProblem>) {
+    match node.value() {
+        YamlValue::Object(fields) => {
+            let compose = fields.contains_key("services");
+            for (index, (key, child)) in fields.iter().enumerate() {
+                path.push(index);
+                match child.value() {
+                    YamlValue::String(text) if is_version_key(key) && !compose => {
+                        let text = text.as_str().trim();
+                        if looks_like_version(text) {
+                            if let Err(e) = Version::parse(text) {
+                                problems.push(ValueProblem {
+                                    path: path.clone(),
+                                    severity: Severity::Warning,
+                                    message: format!("{} is not a semantic version: {}", text, e),
+                                });
+                            }
+                        }
+                    }
+                    _ => check_node(child, path, problems),
+                }
+                path.pop();
+            }
+        }
+        YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+            for (index, item) in items.iter().enumerate() {
+                path.push(index);
+                check_node(item, path, problems);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Explains a semantic version: its parts and the versions `:bump` gives.
+///
+/// Under a version key, a dotted version that isn't valid is an error;
+/// elsewhere only valid versions are recognised.
+pub fn explain(
+    key: Option<&str>,
+    value: &YamlValue,
+) -> Option<Result<(String, Vec<String>), String>> {
+    let YamlValue::String(text) = value else {
+        return None;
+    };
+    let text = text.as_str().trim();
+    let version = match Version::parse(text) {
+        Ok(version) => version,
+        Err(e) if key.is_some_and(is_version_key) && looks_like_version(text) => {
+            return Some(Err(format!("Not a semantic version: {}", e)))
+        }
+        Err(_) => return None,
+    };
+
+    let mut lines = vec![format!(
+        "Major {}, minor {}, patch {}",
+        version.major, version.minor, version.patch
+    )];
+    if !version.pre.is_empty() {
+        lines.push(format!(
+            "Pre-release {}: comes before {}.{}.{}",
+            version.pre.join("."),
+            version.major,
+            version.minor,
+            version.patch
+        ));
+    }
+    if !version.build.is_empty() {
+        lines.push(format!(
+            "Build {}: ignored when comparing versions",
+            version.build.join(".")
+        ));
+    }
+    if version.major == 0 {
+        lines.push("Major version 0: anything may change at any time".to_string());
+    }
+    lines.push(String::new());
+    lines.push("Next versions (:bump):".to_string());
+    for (name, part) in [
+        ("patch", Part::Patch),
+        ("minor", Part::Minor),
+        ("major", Part::Major),
+    ] {
+        lines.push(format!("  {:<6} {}", name, version.bump(part)));
+    }
+    Some(Ok((format!("Semantic version {}", text), lines)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    #[test]
+    fn test_parse_and_display() {
+        let version = Version::parse("v2.0.0-rc.1+build.5").unwrap();
+        assert!(version.v_prefix);
+        assert_eq!(version.pre, ["rc", "1"]);
+        assert_eq!(version.build, ["build", "5"]);
+        assert_eq!(version.to_string(), "v2.0.0-rc.1+build.5");
+
+        assert_eq!(
+            Version::parse("1.02.3").unwrap_err(),
+            "02 has a leading zero"
+        );
+        assert_eq!(Version::parse("1.2.x").unwrap_err(), "'x' is not a number");
+        assert_eq!(
+            Version::parse("1.2.3-rc..1").unwrap_err(),
+            "empty pre-release identifier"
+        );
+        assert_eq!(
+            Version::parse("1.2.3-01").unwrap_err(),
+            "pre-release 01 has a leading zero"
+        );
+    }
+
+    #[test]
+    fn test_bump() {
+        let bump = |text: &str, part| Version::parse(text).unwrap().bump(part).to_string();
+        assert_eq!(bump("1.4.2", Part::Patch), "1.4.3");
+        assert_eq!(bump("1.4.2", Part::Minor), "1.5.0");
+        assert_eq!(bump("1.4.2+build.5", Part::Major), "2.0.0");
+        // A pre-release is released rather than skipped
+        assert_eq!(bump("1.4.2-rc.1", Part::Patch), "1.4.2");
+        assert_eq!(bump("1.5.0-rc.1", Part::Minor), "1.5.0");
+        assert_eq!(bump("1.5.1-rc.1", Part::Minor), "1.6.0");
+        assert_eq!(bump("2.0.0-rc.1", Part::Major), "2.0.0");
+    }
+
+    #[test]
+    fn test_check_version_fields() {
+        let yaml = "\
+apiVersion: v2
+name: app
+version: \"1.4\"
+appVersion: 1.16.0
+dependencies:
+  - name: redis
+    version: 17.3.x
+  - name: pg
+    version: 12.01.0
+---
+version: 1.4
+---
+version: \"3.8\"
+services: {}
+";
+        let root = parse_yaml_auto(yaml).unwrap();
+        let problems: Vec<_> = check_values(&root)
+            .into_iter()
+            .map(|p| (p.path, p.message))
+            .collect();
+        // Ranges, numbers and Compose files are skipped
+        assert_eq!(
+            problems,
+            [
+                (
+                    vec![0, 2],
+                    "1.4 is not a semantic version: expected MAJOR.MINOR.PATCH, found 2 numbers"
+                        .to_string()
+                ),
+                (
+                    vec![0, 4, 1, 1],
+                    "12.01.0 is not a semantic version: 01 has a leading zero".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -41,15 +41,25 @@
 //! assert_eq!(state.mode(), &EditorMode::Insert);
 //! ```
 
+use super::coerce::{coerce, ScalarType};
 use super::cursor::Cursor;
+use super::diagnostics::{Diagnostic, DiagnosticList};
 use super::jumplist::JumpList;
 use super::marks::MarkSet;
 use super::mode::EditorMode;
+use super::multiline::MultilineBuffer;
 use super::registers::RegisterSet;
 use super::repeat::RepeatableCommand;
-use crate::document::node::{YamlNode, YamlNumber, YamlString, YamlValue};
+use super::watch::{Watch, MAX_WATCHES};
+use crate::config::modeline::{parse_modeline, Modeline, ModelineOption};
+use crate::config::project::{ProjectConfig, PROJECT_CONFIG_FILE};
+use crate::config::trust::TrustStore;
+use crate::config::Config;
+use crate::document::node::{NodeId, YamlNode, YamlNumber, YamlString, YamlValue};
 use crate::document::tree::YamlTree;
+use crate::ui::breadcrumbs::ClickTarget;
 use crate::ui::tree_view::TreeViewState;
+use std::collections::{HashMap, HashSet};
 
 #[cfg(test)]
 use indexmap::IndexMap;
@@ -82,7 +92,7 @@ impl ThemePickerState {
     /// Initializes with the list of available themes and sets the selected
     /// index to the current theme if found, otherwise defaults to 0.
     pub fn new(current_theme: String) -> Self {
-        let themes = crate::theme::list_builtin_themes();
+        let themes = crate::theme::list_themes();
 
         // Find index of current theme
         let selected_index = themes.iter().position(|t| t == &current_theme).unwrap_or(0);
@@ -96,6 +106,23 @@ impl ThemePickerState {
     }
 }
 
+/// Shown when an edit is attempted on a read-only buffer.
+const READ_ONLY_MESSAGE: &str = "Buffer is read-only (:view to allow edits)";
+
+/// Shown when an edit is attempted on a sampled buffer.
+const SAMPLED_MESSAGE: &str = "Sampled buffer is read-only (:sample off loads every record)";
+
+/// Most paths the pin panel holds, one for each of `g1` to `g9`.
+pub const MAX_PINS: usize = 9;
+
+/// The full document behind a sampled buffer (`:sample`).
+#[derive(Debug, Clone)]
+struct SampledRecords {
+    info: super::sample::SampleInfo,
+    full: YamlTree,
+    was_read_only: bool,
+}
+
 /// Parses a string into a YamlValue, detecting type automatically.
 ///
 /// - "true"/"false" → Boolean
@@ -183,6 +210,26 @@ pub enum MessageLevel {
     Error,
 }
 
+/// What `--pick` mode prints for the selected node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickOutput {
+    /// The node's path in dot notation (`.spec.replicas`)
+    Path,
+    /// The node's value; containers are serialized as YAML
+    Value,
+}
+
+/// The file's size and modification time as last seen on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileInfo {
+    /// Size in bytes
+    pub size: u64,
+    /// Last modified time in Unix seconds
+    pub modified: i64,
+    /// The file was modified after the buffer was loaded or saved
+    pub newer_on_disk: bool,
+}
+
 /// Stage of the add operation state machine.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AddModeStage {
@@ -207,9 +254,59 @@ pub struct EditorState {
     command_buffer: String,
     show_help: bool,
     help_scroll: usize,
+    // The `:registers` overlay's first shown row, while open
+    registers_scroll: Option<usize>,
     pending_theme: Option<String>,
+    pending_window_command: Option<super::window::WindowCommand>,
+    pending_buffer_command: Option<crate::app::BufferCommand>,
+    // Set by `:q!` so the app quits despite unsaved changes in other buffers
+    force_quit: bool,
+    // This buffer's 1-based position in the buffer list, and the list's length
+    buffer_position: Option<(usize, usize)>,
     current_theme: String,
     show_theme_picker: bool,
+    bookmark_picker: Option<super::bookmarks::BookmarkPickerState>,
+    // The `:marks` picker, while open
+    mark_picker: Option<super::marks::MarkPickerState>,
+    // Backups of the current file (`:backups`)
+    backup_picker: Option<super::backups::BackupPickerState>,
+    // Whether the Kubernetes commands are enabled (`:set k8s`)
+    k8s: bool,
+    // The `.env` file placeholders are previewed with (`:set resolve-env=FILE`)
+    resolve_env_file: Option<String>,
+    // The `:containers` picker, while open
+    container_picker: Option<super::k8s::ContainerPickerState>,
+    // The `:graph` overlay, while open
+    pipeline_graph: Option<super::pipeline::GraphState>,
+    // The `:usages` results panel, while open
+    usages_panel: Option<super::usages::UsagesPanelState>,
+    // Where project bookmark files live (`None` keeps bookmarks in memory)
+    bookmark_dir: Option<std::path::PathBuf>,
+    // Notes on nodes, stored in a `file.yaml.notes.toml` sidecar
+    notes: super::notes::NoteStore,
+    // Notes shown in the popup (`:note`, `:notes`)
+    note_popup: Option<Vec<super::notes::Note>>,
+    // The `K` popup, while open
+    explanation: Option<super::explain::Explanation>,
+    // The `gD` popup, while open
+    node_diff: Option<super::review::NodeDiff>,
+    task_list: Option<super::tasks::TaskListState>,
+    // The quickfix list (`:lint`, `:copen`), and whether its panel is open
+    quickfix: Option<super::quickfix::QuickfixList>,
+    quickfix_open: bool,
+    // Progress through repeated keys while the `:dedupe` prompt is open
+    dedupe: Option<super::dedupe::DedupeSession>,
+    // The key jump in progress (`f`)
+    key_jump: Option<super::keyjump::KeyJump>,
+    // Hint mode in progress (`s`)
+    hints: Option<super::hints::Hints>,
+    facet_panel: Option<super::facet::FacetPanelState>,
+    // Describes the active view filter for the status line (`:facet`)
+    view_filter_label: Option<String>,
+    // Changes being stepped through in review mode (`:review`)
+    review: Option<super::review::ReviewState>,
+    // Locations whose changes `:w --staged` writes (`:stage`)
+    staged: Vec<Vec<super::review::Step>>,
     theme_picker_state: Option<ThemePickerState>,
     // Old clipboard fields - TODO: remove after register migration
     // clipboard: Option<YamlNode>,
@@ -226,13 +323,101 @@ pub struct EditorState {
     relative_line_numbers: bool,
     enable_mouse: bool,
     create_backup: bool,
+    persistent_undo: bool,
+    reload_on_change: bool,
+    /// Whether unprefixed `/` patterns are regexes
+    regex_search: bool,
+    /// Whether `/` also searches inside collapsed nodes
+    search_collapsed: bool,
+    /// Whether the breadcrumb bar is shown above the tree
+    show_breadcrumbs: bool,
+    /// Where the breadcrumb segments were last drawn, for mouse clicks
+    breadcrumb_targets: Vec<ClickTarget>,
+    /// Paths pinned to the pin panel (`:pin`), in pin order
+    pins: Vec<Vec<usize>>,
+    /// Where the pinned paths were last drawn, for mouse clicks
+    pin_targets: Vec<ClickTarget>,
+    /// YAMLPath expressions shown in the watch panel (`:watch`)
+    watches: Vec<Watch>,
+    // Lines of context kept above and below the cursor (`:set scrolloff`)
+    scrolloff: usize,
+    // Lines (or columns) scrolled per mouse wheel tick
+    mouse_scroll_lines: usize,
+    // Memory guardrails: the budget in MB, and whether it has been crossed
+    memory_budget_mb: usize,
+    memory_degraded: bool,
+    // File size in bytes from which files are loaded lazily
+    lazy_load_threshold: usize,
+    // Limits files are checked against when they are opened
+    load_limits: crate::document::limits::LoadLimits,
+    // Largest compressed undo file in MB, 0 for no limit
+    undo_file_limit_mb: usize,
+    // Serializer styles for YAML and JSON saves
+    yaml_style: String,
+    json_style: String,
+    // Modification time and size of the file when last loaded or saved
+    disk_state: Option<(std::time::SystemTime, u64)>,
+    // The same, as last read from disk by the once-a-second check
+    seen_disk_state: Option<(std::time::SystemTime, u64)>,
+    // Hash of the file's contents when last loaded or saved
+    disk_hash: Option<u64>,
+    // Write command blocked because the file changed on disk, waiting for
+    // overwrite/reload/diff/cancel
+    save_conflict: Option<String>,
+    last_disk_check: Option<std::time::Instant>,
+    // Status line format for file details (`%s %t %n`)
+    statusline: String,
+    // `:lint` rules switched off, and the longest line it accepts
+    lint_disabled: Vec<String>,
+    lint_line_length: usize,
+    // Read-only live view served by `:share`, and when it was last updated
+    share: Option<crate::export::share::ShareServer>,
+    last_share_publish: Option<std::time::Instant>,
+    // Subtree text waiting for the event loop to open it in `$EDITOR`
+    pending_external_edit: Option<String>,
+    // `:!cmd` waiting for the event loop to run it
+    pending_filter: Option<super::external::FilterRequest>,
+    // `--read-only` / `:view`: every edit is refused
+    read_only: bool,
+    // `--clean`: nothing is read from or written to the user's setup
+    safe_mode: bool,
+    // Set while only a sample of the records is shown (`:sample`)
+    sampled: Option<SampledRecords>,
+    // The node each path in marks, jumps, search results, diagnostics and
+    // folds pointed at when last checked, so they can follow it after edits
+    tracked_nodes: HashMap<Vec<usize>, NodeId>,
+    // Nodes bookmarked or jumped to by bookmark in this session, by name
+    bookmarked_nodes: HashMap<String, NodeId>,
+    // The file changed on disk and the reload/ignore prompt is showing
+    external_change: bool,
+    indent_size: usize,
+    // Buffer-local settings from a `# yamlquill:` modeline
+    modeline: Option<Modeline>,
+    modeline_defaults: Option<Config>,
+    schema_path: Option<String>,
+    // Trusted locations and the project config applied to this buffer
+    trust_store: TrustStore,
+    project_config: Option<ProjectConfig>,
+    project_defaults: Option<Config>,
+    diagnostics: DiagnosticList,
+    // Schema validation queued for idle time (`:set autovalidate`)
+    auto_validate: bool,
+    revalidation: super::revalidate::Revalidation,
+    // `--pick` mode: what to print, and the result once Enter is pressed
+    pick_mode: Option<PickOutput>,
+    picked: Option<String>,
+    repl: super::repl::ReplState,
     edit_buffer: Option<String>,
     edit_cursor: usize,
+    // Multi-line editor, open instead of `edit_buffer` for strings spanning lines
+    multiline_edit: Option<MultilineBuffer>,
     cursor_visible: bool,
     cursor_blink_ticks: u8,
     pending_command: Option<char>,
     pending_count: Option<u32>,
     scroll_offset: usize,
+    // Columns hidden off the left edge of the tree view
+    horizontal_scroll: usize,
     viewport_height: usize,
     undo_tree: super::undo::UndoTree,
     add_mode_stage: AddModeStage,
@@ -253,6 +438,8 @@ pub struct EditorState {
     pending_mark_jump: bool,
     visual_anchor: Option<Vec<usize>>,
     visual_selection: Vec<Vec<usize>>,
+    // Visual block mode: the selection is one key across list elements
+    visual_block: bool,
     last_command: Option<RepeatableCommand>,
 }
 
@@ -282,6 +469,7 @@ impl EditorState {
     /// ```
     pub fn new(tree: YamlTree, initial_theme_name: String) -> Self {
         let mut tree_view = TreeViewState::new();
+        tree_view.set_max_depth(Config::default().max_render_depth);
         // Expand all nodes by default for regular JSON files
         // JSONL files start collapsed to show previews
         if !matches!(tree.root().value(), YamlValue::MultiDoc(_)) {
@@ -304,7 +492,7 @@ impl EditorState {
         };
         let undo_tree = super::undo::UndoTree::new(initial_snapshot, undo_limit);
 
-        Self {
+        let mut state = Self {
             tree,
             mode: EditorMode::Normal,
             cursor,
@@ -315,9 +503,37 @@ impl EditorState {
             command_buffer: String::new(),
             show_help: false,
             help_scroll: 0,
+            registers_scroll: None,
             pending_theme: None,
+            pending_window_command: None,
+            pending_buffer_command: None,
+            force_quit: false,
+            buffer_position: None,
             current_theme: initial_theme_name,
             show_theme_picker: false,
+            bookmark_picker: None,
+            mark_picker: None,
+            backup_picker: None,
+            k8s: false,
+            resolve_env_file: None,
+            container_picker: None,
+            pipeline_graph: None,
+            usages_panel: None,
+            bookmark_dir: super::bookmarks::BookmarkStore::default_dir(),
+            notes: super::notes::NoteStore::default(),
+            note_popup: None,
+            explanation: None,
+            node_diff: None,
+            task_list: None,
+            quickfix: None,
+            quickfix_open: false,
+            dedupe: None,
+            key_jump: None,
+            hints: None,
+            facet_panel: None,
+            view_filter_label: None,
+            review: None,
+            staged: Vec::new(),
             theme_picker_state: None,
             // Old clipboard init - TODO: remove after register migration
             // clipboard: None,
@@ -334,13 +550,62 @@ impl EditorState {
             relative_line_numbers: false,
             enable_mouse: true,
             create_backup: false,
+            persistent_undo: false,
+            reload_on_change: false,
+            regex_search: false,
+            search_collapsed: true,
+            show_breadcrumbs: false,
+            breadcrumb_targets: Vec::new(),
+            pins: Vec::new(),
+            pin_targets: Vec::new(),
+            watches: Vec::new(),
+            scrolloff: 0,
+            mouse_scroll_lines: 3,
+            memory_budget_mb: Config::default().memory_budget_mb,
+            memory_degraded: false,
+            lazy_load_threshold: Config::default().lazy_load_threshold,
+            load_limits: Config::default().load_limits(),
+            undo_file_limit_mb: Config::default().undo_file_limit_mb,
+            yaml_style: Config::default().yaml_style,
+            json_style: Config::default().json_style,
+            disk_state: None,
+            seen_disk_state: None,
+            disk_hash: None,
+            save_conflict: None,
+            last_disk_check: None,
+            statusline: Config::default().statusline,
+            lint_disabled: Vec::new(),
+            lint_line_length: Config::default().lint_line_length,
+            share: None,
+            last_share_publish: None,
+            pending_external_edit: None,
+            pending_filter: None,
+            read_only: false,
+            safe_mode: false,
+            sampled: None,
+            external_change: false,
+            indent_size: 2,
+            modeline: None,
+            modeline_defaults: None,
+            schema_path: None,
+            trust_store: TrustStore::new(),
+            project_config: None,
+            project_defaults: None,
+            diagnostics: DiagnosticList::new(),
+            auto_validate: false,
+            revalidation: super::revalidate::Revalidation::new(),
+            pick_mode: None,
+            picked: None,
+            repl: super::repl::ReplState::new(),
             edit_buffer: None,
             edit_cursor: 0,
+            multiline_edit: None,
             cursor_visible: true,
             cursor_blink_ticks: 0,
             pending_command: None,
             pending_count: None,
             scroll_offset: 0,
+            horizontal_scroll: 0,
             viewport_height: 20,
             undo_tree,
             add_mode_stage: AddModeStage::None,
@@ -358,9 +623,15 @@ impl EditorState {
             pending_mark_set: false,
             pending_mark_jump: false,
             visual_anchor: None,
+            visual_block: false,
             visual_selection: Vec::new(),
             last_command: None,
-        }
+            tracked_nodes: HashMap::new(),
+            bookmarked_nodes: HashMap::new(),
+        };
+        state.track_nodes();
+        state.run_value_checks();
+        state
     }
 
     /// Creates a new editor state with a default theme (for tests).
@@ -426,6 +697,7 @@ impl EditorState {
     ///
     /// Marks the document as dirty so the user can save the formatted result.
     pub fn format_document(&mut self) -> anyhow::Result<()> {
+        self.ensure_writable()?;
         use crate::document::parser::parse_yaml;
         use crate::file::loader::parse_yamll_content;
         use crate::file::saver::{serialize_node_compact, serialize_node_jq_style};
@@ -444,8 +716,7 @@ impl EditorState {
             }
         } else {
             // Regular JSON: use jq-style multi-line formatting
-            let indent_size = 2;
-            let mut yaml_str = serialize_node_jq_style(self.tree.root(), indent_size, 0);
+            let mut yaml_str = serialize_node_jq_style(self.tree.root(), self.indent_size, 0);
 
             // jq always ensures a trailing newline
             if !yaml_str.ends_with('\n') {
@@ -481,11 +752,22 @@ impl EditorState {
         // Reset to default expansion state:
         // - Regular JSON files: fully expanded
         // - JSONL files: fully collapsed
+        let resolve_merge = self.tree_view.resolve_merge();
+        let max_depth = self.tree_view.max_depth();
         self.tree_view = TreeViewState::new();
+        self.tree_view.set_resolve_merge(resolve_merge);
+        self.tree_view.set_max_depth(max_depth);
+        self.diagnostics.clear();
         if !matches!(self.tree.root().value(), YamlValue::MultiDoc(_)) {
             self.tree_view.expand_all(&self.tree);
         }
+        if self.prometheus() {
+            self.fold_rules();
+        }
+        self.track_nodes();
         self.tree_view.rebuild(&self.tree);
+        self.schedule_revalidation();
+        self.run_value_checks();
 
         // Reset cursor to first visible line
         if let Some(first_line) = self.tree_view.lines().first() {
@@ -503,6 +785,7 @@ impl EditorState {
         self.undo_tree = super::undo::UndoTree::new(initial_snapshot, 50);
 
         self.clear_message();
+        self.check_memory();
     }
 
     /// Returns a reference to the current editing mode.
@@ -638,7 +921,8 @@ impl EditorState {
 
     /// Clears the dirty flag, indicating all changes have been saved.
     ///
-    /// This should be called after successfully saving the document.
+    /// This should be called after successfully saving the document. Every
+    /// change is on disk then, so the `:stage` queue is emptied too.
     ///
     /// # Examples
     ///
@@ -659,6 +943,7 @@ impl EditorState {
     /// ```
     pub fn clear_dirty(&mut self) {
         self.dirty = false;
+        self.staged.clear();
     }
 
     /// Returns the filename of the document being edited, if any.
@@ -702,6 +987,7 @@ impl EditorState {
     /// ```
     pub fn set_filename(&mut self, filename: String) {
         self.filename = Some(filename);
+        self.record_disk_state();
     }
 
     /// Returns a reference to the tree view state.
@@ -771,24 +1057,431 @@ impl EditorState {
     /// state.rebuild_tree_view();
     /// ```
     pub fn rebuild_tree_view(&mut self) {
+        self.follow_nodes();
         self.tree_view.rebuild(&self.tree);
+        for watch in &mut self.watches {
+            watch.evaluate(self.tree.root());
+        }
+    }
+
+    /// Moves marks, jumps, search results, diagnostics and folds to where
+    /// their nodes are now, after edits shifted sibling indices.
+    ///
+    /// Paths whose node was deleted are dropped. Cheap to call when nothing
+    /// moved, so it runs on every rebuild.
+    fn follow_nodes(&mut self) {
+        let tracked = std::mem::take(&mut self.tracked_nodes);
+        let ids = tracked.values().copied().collect();
+        let moved = self.tree.paths_of(&ids);
+        self.remap_node_paths(|path| match tracked.get(path) {
+            Some(id) => moved.get(id).cloned(),
+            None => Some(path.to_vec()),
+        });
+        self.track_nodes();
+    }
+
+    /// Records which node every tracked path points at now, without moving any.
+    ///
+    /// Called after a node is replaced in place or the whole tree is swapped,
+    /// where paths stay valid but the nodes are new.
+    fn track_nodes(&mut self) {
+        let mut paths = Vec::new();
+        self.remap_node_paths(|path| {
+            paths.push(path.to_vec());
+            Some(path.to_vec())
+        });
+        self.tracked_nodes = paths
+            .into_iter()
+            .filter_map(|path| {
+                let id = self.tree.get_node(&path)?.id();
+                Some((path, id))
+            })
+            .collect();
+    }
+
+    /// Applies `f` to every path the editor keeps into the document.
+    fn remap_node_paths(&mut self, mut f: impl FnMut(&[usize]) -> Option<Vec<usize>>) {
+        self.marks.remap_paths(&mut f);
+        self.jumplist.remap_paths(&mut f);
+        self.tree_view.remap_expanded_paths(&mut f);
+        self.tree_view.remap_filter(&mut f);
+        self.diagnostics.remap_paths(&mut f);
+        self.tree_view
+            .set_diagnostic_markers(self.diagnostics.markers());
+        self.search_results = self
+            .search_results
+            .iter()
+            .filter_map(|path| f(path))
+            .collect();
+        self.pins = self.pins.iter().filter_map(|path| f(path)).collect();
+        self.search_index = self
+            .search_index
+            .min(self.search_results.len().saturating_sub(1));
+    }
+
+    /// Moves the node under the cursor to the end of the container above it
+    /// (`>`).
+    ///
+    /// A mapping entry keeps its key when it moves into a mapping and
+    /// becomes a one-key mapping in a list; a one-key mapping in a list
+    /// becomes that entry in a mapping. Comments in between are skipped.
+    /// The move is one undo step.
+    pub fn indent_node(&mut self) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        use crate::document::ops::{apply_all, Op};
+        use anyhow::bail;
+
+        self.ensure_not_merged()?;
+        let path = self.cursor.path().to_vec();
+        let Some((&index, parent)) = path.split_last() else {
+            bail!("The root can't be indented");
+        };
+        let siblings: Vec<&YamlNode> = match self.tree.get_node(parent).map(|n| n.value()) {
+            Some(YamlValue::Object(entries)) => entries.values().collect(),
+            Some(YamlValue::Array(items)) => items.iter().collect(),
+            _ => bail!("Documents can't be indented"),
+        };
+        let Some(target) = (0..index)
+            .rev()
+            .find(|&i| !siblings[i].value().is_comment())
+        else {
+            bail!("Nothing above to indent into");
+        };
+
+        let delete = Op::Delete { path: path.clone() };
+        let Op::Insert { node, key, .. } = delete.invert(&self.tree)? else {
+            unreachable!("a delete is undone by an insert");
+        };
+        let (key, node, len) = match siblings[target].value() {
+            YamlValue::Object(entries) => {
+                let (key, node) = Self::as_entry(key, node)?;
+                if entries.contains_key(&key) {
+                    bail!("Key '{}' already exists there", key);
+                }
+                (Some(key), node, entries.len())
+            }
+            YamlValue::Array(items) => (None, Self::as_item(key, node), items.len()),
+            _ => bail!("The node above is not a mapping or list"),
+        };
+
+        let mut to = parent.to_vec();
+        to.extend([target, len]);
+        let insert = Op::Insert {
+            path: to.clone(),
+            key,
+            node,
+        };
+        apply_all(&mut self.tree, &[delete, insert])?;
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.reveal_path(to);
+        self.checkpoint();
+        Ok(())
+    }
+
+    /// Nests the node under the cursor in a new list, or with `key`, in a
+    /// new mapping under that key (`g[`/`:wrap-array`, `g{`/`:wrap-object`).
+    ///
+    /// `port: 80` wrapped in a list becomes a `port` list holding `80`, and
+    /// `- nginx` wrapped under `image` becomes `- image: nginx`. The cursor
+    /// moves onto the wrapped node. The edit is one undo step.
+    pub fn wrap_node(&mut self, key: Option<&str>) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        use crate::document::ops::{apply_all, Op};
+        use anyhow::bail;
+
+        self.ensure_not_merged()?;
+        let path = self.cursor.path().to_vec();
+        if path.is_empty() {
+            bail!("Can't wrap the root");
+        }
+        let Some(node) = self.tree.get_node(&path) else {
+            bail!("Nothing to wrap");
+        };
+        if node.value().is_comment() {
+            bail!("Can't wrap a comment");
+        }
+
+        let inner = node.clone();
+        let wrapper = match key {
+            Some(key) => {
+                let mut entries = indexmap::IndexMap::new();
+                entries.insert(key.to_string(), inner);
+                YamlNode::new(YamlValue::Object(entries))
+            }
+            None => YamlNode::new(YamlValue::Array(vec![inner])),
+        };
+        apply_all(
+            &mut self.tree,
+            &[Op::Replace {
+                path: path.clone(),
+                node: wrapper,
+            }],
+        )?;
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        let mut inner_path = path;
+        inner_path.push(0);
+        self.reveal_path(inner_path);
+        self.checkpoint();
+        Ok(())
+    }
+
+    /// Replaces a container holding a single node with that node (`g]`,
+    /// `:unwrap`), undoing `g[` and `g{`.
+    ///
+    /// Acts on the container at the cursor, or on the one holding the cursor
+    /// when it is on a scalar. Comments in the container are dropped. The
+    /// edit is one undo step.
+    pub fn unwrap_node(&mut self) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        use crate::document::ops::{apply_all, Op};
+        use anyhow::bail;
+
+        self.ensure_not_merged()?;
+        let mut path = self.cursor.path().to_vec();
+        if !self
+            .tree
+            .get_node(&path)
+            .is_some_and(|node| node.value().is_container())
+        {
+            path.pop();
+        }
+        let children: Vec<&YamlNode> = match self.tree.get_node(&path).map(|n| n.value()) {
+            Some(YamlValue::Object(entries)) => entries.values().collect(),
+            Some(YamlValue::Array(items)) => items.iter().collect(),
+            _ => bail!("Not on a mapping or list"),
+        };
+        let children: Vec<&YamlNode> = children
+            .into_iter()
+            .filter(|child| !child.value().is_comment())
+            .collect();
+        let [child] = children.as_slice() else {
+            bail!(
+                "Only a container with one child can be unwrapped (:splice moves {} children)",
+                children.len()
+            );
+        };
+
+        // The node's text moves, so it's all written anew
+        let mut node = (*child).clone();
+        node.mark_all_modified();
+        apply_all(
+            &mut self.tree,
+            &[Op::Replace {
+                path: path.clone(),
+                node,
+            }],
+        )?;
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.reveal_path(path);
+        self.checkpoint();
+        Ok(())
+    }
+
+    /// Moves the children of the container at the cursor into its parent,
+    /// in its place (`:splice`).
+    ///
+    /// Mapping entries keep their keys in a mapping and become one-key
+    /// mappings in a list; list items spliced into a mapping must be one-key
+    /// mappings. Nothing moves if a key would clash with one already in the
+    /// parent. The edit is one undo step. Returns the number of children
+    /// moved.
+    pub fn splice_node(&mut self) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        use crate::document::ops::{apply_all, Op};
+        use anyhow::bail;
+
+        self.ensure_not_merged()?;
+        let path = self.cursor.path().to_vec();
+        let Some((&index, parent_path)) = path.split_last() else {
+            bail!("The root has no parent to splice into");
+        };
+        // The children's text moves, so it's all written anew
+        let moved = |child: &YamlNode| {
+            let mut child = child.clone();
+            child.mark_all_modified();
+            child
+        };
+        let children: Vec<(Option<String>, YamlNode)> =
+            match self.tree.get_node(&path).map(|n| n.value()) {
+                Some(YamlValue::Object(entries)) => entries
+                    .iter()
+                    .map(|(key, child)| (Some(key.clone()), moved(child)))
+                    .collect(),
+                Some(YamlValue::Array(items)) => {
+                    items.iter().map(|child| (None, moved(child))).collect()
+                }
+                _ => bail!("Not on a mapping or list"),
+            };
+        let Some(parent) = self.tree.get_node(parent_path) else {
+            bail!("Not on a mapping or list");
+        };
+
+        let spliced = match parent.value() {
+            YamlValue::Object(entries) => {
+                let mut merged = indexmap::IndexMap::new();
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i != index {
+                        merged.insert(key.clone(), value.clone());
+                        continue;
+                    }
+                    for (child_key, child) in &children {
+                        let (child_key, child) = if child.value().is_comment() {
+                            let mut n = 0;
+                            let taken = |key: &String| {
+                                entries.contains_key(key) || merged.contains_key(key)
+                            };
+                            while taken(&format!("__comment_{}__", n)) {
+                                n += 1;
+                            }
+                            (format!("__comment_{}__", n), child.clone())
+                        } else {
+                            Self::as_entry(child_key.clone(), child.clone()).map_err(|_| {
+                                anyhow::anyhow!(
+                                    "Only list items with one key can be spliced into a mapping"
+                                )
+                            })?
+                        };
+                        let clash = entries
+                            .get_index_of(&child_key)
+                            .is_some_and(|other| other != index)
+                            || merged.contains_key(&child_key);
+                        if clash {
+                            bail!("Key '{}' already exists there", child_key);
+                        }
+                        merged.insert(child_key, child);
+                    }
+                }
+                YamlValue::Object(merged)
+            }
+            YamlValue::Array(items) => {
+                let mut merged = items.clone();
+                merged.splice(
+                    index..=index,
+                    children.iter().map(|(key, child)| {
+                        if child.value().is_comment() {
+                            child.clone()
+                        } else {
+                            Self::as_item(key.clone(), child.clone())
+                        }
+                    }),
+                );
+                YamlValue::Array(merged)
+            }
+            _ => bail!("Documents can't be spliced"),
+        };
+
+        let mut node = parent.clone();
+        *node.value_mut() = spliced;
+        apply_all(
+            &mut self.tree,
+            &[Op::Replace {
+                path: parent_path.to_vec(),
+                node,
+            }],
+        )?;
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        if children.is_empty() {
+            self.cursor.set_path(parent_path.to_vec());
+        } else {
+            self.reveal_path(path);
+        }
+        self.checkpoint();
+        Ok(children.len())
+    }
+
+    /// Moves the node under the cursor out of its container, to just after
+    /// it (`<`).
+    ///
+    /// Entries and one-key mappings convert as for
+    /// [`EditorState::indent_node`], so `<` undoes `>`. The move is one
+    /// undo step.
+    pub fn outdent_node(&mut self) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        use crate::document::ops::{apply_all, Op};
+        use anyhow::bail;
+
+        self.ensure_not_merged()?;
+        let path = self.cursor.path().to_vec();
+        let Some((&parent_index, grandparent)) =
+            path.split_last().and_then(|(_, p)| p.split_last())
+        else {
+            bail!("Already at the top level");
+        };
+
+        let delete = Op::Delete { path: path.clone() };
+        let Op::Insert { node, key, .. } = delete.invert(&self.tree)? else {
+            unreachable!("a delete is undone by an insert");
+        };
+        let (key, node) = match self.tree.get_node(grandparent).map(|n| n.value()) {
+            Some(YamlValue::Object(entries)) => {
+                let (key, node) = Self::as_entry(key, node)?;
+                if entries.contains_key(&key) {
+                    bail!("Key '{}' already exists there", key);
+                }
+                (Some(key), node)
+            }
+            Some(YamlValue::Array(_)) => (None, Self::as_item(key, node)),
+            _ => bail!("Already at the top level of the document"),
+        };
+
+        let mut to = grandparent.to_vec();
+        to.push(parent_index + 1);
+        let insert = Op::Insert {
+            path: to.clone(),
+            key,
+            node,
+        };
+        apply_all(&mut self.tree, &[delete, insert])?;
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.reveal_path(to);
+        self.checkpoint();
+        Ok(())
+    }
+
+    /// Returns a node moving into a mapping as a key and value: a mapping
+    /// entry as it is, or a list item that is a one-key mapping unwrapped.
+    fn as_entry(key: Option<String>, node: YamlNode) -> anyhow::Result<(String, YamlNode)> {
+        match (key, node.value()) {
+            (Some(key), _) => Ok((key, node)),
+            (None, YamlValue::Object(fields)) if fields.len() == 1 => {
+                let (key, value) = fields.first().expect("one entry");
+                Ok((key.clone(), value.clone()))
+            }
+            _ => anyhow::bail!("Only a list item with one key can go into a mapping"),
+        }
+    }
+
+    /// Returns a node moving into a list as an item: a mapping entry
+    /// becomes a one-key mapping.
+    fn as_item(key: Option<String>, node: YamlNode) -> YamlNode {
+        match key {
+            Some(key) => YamlNode::new(YamlValue::Object(indexmap::IndexMap::from([(key, node)]))),
+            None => node,
+        }
     }
 
     /// Deletes the node at the current cursor position.
     /// Stores the deleted node in register history before deletion.
     /// Adjusts the cursor position after deletion and rebuilds the tree view.
     pub fn delete_node_at_cursor(&mut self) -> anyhow::Result<()> {
-        use crate::document::node::YamlValue;
+        self.ensure_writable()?;
+        use crate::document::ops::Op;
         use crate::editor::registers::RegisterContent;
 
+        self.ensure_not_merged()?;
+
         let path = self.cursor.path().to_vec();
 
-        // Collect the node to delete
-        let node = self
-            .tree
-            .get_node(&path)
-            .ok_or_else(|| anyhow::anyhow!("No node at cursor"))?
-            .clone();
+        // Collect the node to delete, with its key if it's a mapping entry
+        let delete = Op::Delete { path: path.clone() };
+        let Op::Insert { node, key, .. } = delete.invert(&self.tree)? else {
+            unreachable!("a delete is undone by an insert");
+        };
 
         // Check if node has an anchor that's being referenced
         if let Some(anchor_name) = node.anchor() {
@@ -806,23 +1499,6 @@ impl EditorState {
             }
         }
 
-        // Store key if deleting from object
-        let key = if !path.is_empty() {
-            let parent_path = &path[..path.len() - 1];
-            let index = path[path.len() - 1];
-            if let Some(parent) = self.tree.get_node(parent_path) {
-                if let YamlValue::Object(fields) = parent.value() {
-                    fields.get_index(index).map(|(k, _)| k.clone())
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
         let content = RegisterContent::new(vec![node], vec![key]);
 
         // Update target register if specified
@@ -832,38 +1508,34 @@ impl EditorState {
             } else {
                 self.registers.set_named(reg, content.clone());
             }
+            // The + and * registers are the system clipboard
+            if matches!(reg, '+' | '*') {
+                self.check_memory();
+                self.mirror_to_clipboard(&content);
+            }
         } else {
             // Update unnamed register
             self.registers.set_unnamed(content.clone());
 
             // Sync to system clipboard
-            let yaml_value = self.node_to_serde_value(content.nodes[0].value());
-            if let Ok(yaml_str) = serde_yaml::to_string(&yaml_value) {
-                use arboard::Clipboard;
-                if let Ok(mut clipboard) = Clipboard::new() {
-                    let _ = clipboard.set_text(yaml_str);
-                }
-            }
+            self.check_memory();
+            self.mirror_to_clipboard(&content);
         }
 
         // Push to delete history ("1-"9)
         self.registers.push_delete_history(content);
 
         // Find current line index before deletion
-        let lines = self.tree_view.lines();
-        let current_idx = lines.iter().position(|l| l.path == path);
+        let current_idx = self.tree_view.line_of(&path);
 
         // Delete the node
-        self.tree.delete_node(&path)?;
+        delete.apply(&mut self.tree)?;
 
         // Update anchor registry to remove registrations for the deleted node
         self.tree.anchor_registry_mut().remove_node(&path);
 
         self.mark_dirty();
 
-        // Update expanded paths to account for shifted indices after deletion
-        self.tree_view_mut().update_paths_after_deletion(&path);
-
         self.rebuild_tree_view();
 
         // Adjust cursor position
@@ -923,7 +1595,7 @@ impl EditorState {
         let current_path = self.cursor.path();
 
         // Find current line index
-        if let Some(current_idx) = lines.iter().position(|l| l.path == current_path) {
+        if let Some(current_idx) = self.tree_view.line_of(current_path) {
             if current_idx + 1 < lines.len() {
                 let next_path = lines[current_idx + 1].path.clone();
                 self.cursor.set_path(next_path);
@@ -973,7 +1645,7 @@ impl EditorState {
 
         let current_path = self.cursor.path();
 
-        if let Some(current_idx) = lines.iter().position(|l| l.path == current_path) {
+        if let Some(current_idx) = self.tree_view.line_of(current_path) {
             if current_idx > 0 {
                 let prev_path = lines[current_idx - 1].path.clone();
                 self.cursor.set_path(prev_path);
@@ -1016,6 +1688,9 @@ impl EditorState {
     /// ```
     pub fn toggle_expand_at_cursor(&mut self) {
         let current_path = self.cursor.path().to_vec();
+        if !self.load_deferred_at(&current_path) {
+            return;
+        }
 
         // Check if we're expanding a JSONL line (direct child of MultiDoc)
         let is_jsonl_line =
@@ -1023,7 +1698,13 @@ impl EditorState {
 
         let was_expanded = self.tree_view.is_expanded(&current_path);
 
-        if is_jsonl_line && !was_expanded {
+        // A node cut off at the depth limit shows collapsed, so expanding it
+        // goes deeper rather than folding it
+        if was_expanded && self.expand_deeper_at_cursor().is_ok() {
+            return;
+        }
+
+        if is_jsonl_line && !was_expanded && !self.memory_degraded {
             // Expanding a JSONL line - expand entire tree within it
             self.tree_view
                 .expand_node_and_descendants(&self.tree, &current_path);
@@ -1032,15 +1713,84 @@ impl EditorState {
             self.tree_view.toggle_expand(&current_path);
         }
 
-        self.tree_view.rebuild(&self.tree);
+        self.rebuild_tree_view();
     }
 
     /// Fully expands the node at the cursor and all its descendants.
     pub fn expand_all_at_cursor(&mut self) {
+        if self.memory_degraded {
+            self.set_message(
+                "Expand-all is off while memory is over budget (:memory)".to_string(),
+                MessageLevel::Warning,
+            );
+            return;
+        }
         let current_path = self.cursor.path().to_vec();
+        if !self.load_deferred_at(&current_path) {
+            return;
+        }
         self.tree_view
             .expand_node_and_descendants(&self.tree, &current_path);
-        self.tree_view.rebuild(&self.tree);
+        self.rebuild_tree_view();
+    }
+
+    /// Parses the node at `path` if it is a section of a lazily loaded file
+    /// that hasn't been loaded yet, leaving it collapsed so the caller's
+    /// expand opens it.
+    ///
+    /// Returns false, with an error message, if the section doesn't parse.
+    fn load_deferred_at(&mut self, path: &[usize]) -> bool {
+        match self.tree.load_deferred(path) {
+            Ok(loaded) => {
+                if loaded && self.tree_view.is_expanded(path) {
+                    self.tree_view.toggle_expand(path);
+                }
+                if loaded {
+                    self.schedule_revalidation();
+                }
+                true
+            }
+            Err(e) => {
+                self.set_message(format!("{:#}", e), MessageLevel::Error);
+                false
+            }
+        }
+    }
+
+    /// Parses every section of a lazily loaded file that hasn't been loaded
+    /// yet, so edits of the whole document (`:%s`, `:path-set`,
+    /// `:path-delete`, `:images`) don't pass over them.
+    ///
+    /// Fails, naming the sections, if any of them doesn't parse.
+    fn load_all_deferred(&mut self) -> anyhow::Result<()> {
+        let count = match self.tree.root().value() {
+            YamlValue::Object(entries) => entries.len(),
+            YamlValue::Array(items) | YamlValue::MultiDoc(items) => items.len(),
+            _ => 0,
+        };
+        let mut loaded = false;
+        let mut failed = Vec::new();
+        for i in 0..count {
+            match self.tree.load_deferred(&[i]) {
+                Ok(true) => loaded = true,
+                Ok(false) => {}
+                Err(_) => failed.push(
+                    self.path_to_string(&[i], "dot")
+                        .unwrap_or_else(|| format!("[{}]", i)),
+                ),
+            }
+        }
+        if loaded {
+            self.schedule_revalidation();
+            self.rebuild_tree_view();
+        }
+        if !failed.is_empty() {
+            anyhow::bail!(
+                "Cannot edit the whole document: {} failed to parse",
+                failed.join(", ")
+            );
+        }
+        Ok(())
     }
 
     /// Fully collapses the node at the cursor and all its descendants.
@@ -1048,7 +1798,7 @@ impl EditorState {
         let current_path = self.cursor.path().to_vec();
         self.tree_view
             .collapse_node_and_descendants(&self.tree, &current_path);
-        self.tree_view.rebuild(&self.tree);
+        self.rebuild_tree_view();
     }
 
     /// Returns the current scroll offset (top line of viewport).
@@ -1076,21 +1826,83 @@ impl EditorState {
         }
 
         // Find current cursor line index
-        let cursor_idx = lines
-            .iter()
-            .position(|l| l.path == self.cursor.path())
-            .unwrap_or(0);
+        let cursor_idx = self.tree_view.line_of(self.cursor.path()).unwrap_or(0);
+
+        // Keep `scrolloff` lines around the cursor, as far as the viewport allows
+        let margin = self.scrolloff.min(viewport_height.saturating_sub(1) / 2);
 
         // Ensure cursor is visible in viewport
-        if cursor_idx < self.scroll_offset {
-            // Cursor is above viewport, scroll up
-            self.scroll_offset = cursor_idx;
-        } else if cursor_idx >= self.scroll_offset + viewport_height {
-            // Cursor is below viewport, scroll down
-            self.scroll_offset = cursor_idx - viewport_height + 1;
+        if cursor_idx < self.scroll_offset + margin {
+            // Cursor is above viewport (or its margin), scroll up
+            self.scroll_offset = cursor_idx.saturating_sub(margin);
+        } else if cursor_idx + margin >= self.scroll_offset + viewport_height {
+            // Cursor is below viewport (or its margin), scroll down, but not past the end
+            let last_offset = lines.len().saturating_sub(viewport_height);
+            self.scroll_offset = (cursor_idx + margin + 1 - viewport_height).min(last_offset);
+        }
+    }
+
+    /// Returns how many columns of the tree view are scrolled off the left edge.
+    pub fn horizontal_scroll(&self) -> usize {
+        self.horizontal_scroll
+    }
+
+    /// Scrolls the viewport by `delta` lines without moving it to the cursor
+    /// first (mouse wheel behavior).
+    ///
+    /// Like vim, the cursor stays put unless it would leave the viewport (or
+    /// its `scrolloff` margin), in which case it is dragged along to the
+    /// nearest line that is still in view.
+    pub fn scroll_viewport(&mut self, delta: isize) {
+        let lines = self.tree_view.lines();
+        if lines.is_empty() || self.viewport_height == 0 {
+            return;
+        }
+
+        let last_offset = lines.len().saturating_sub(self.viewport_height);
+        self.scroll_offset = self
+            .scroll_offset
+            .saturating_add_signed(delta)
+            .min(last_offset);
+
+        // Keep the cursor inside the new viewport, honoring scrolloff except
+        // where the view is already pinned to the start or end of the document
+        let margin = self
+            .scrolloff
+            .min(self.viewport_height.saturating_sub(1) / 2);
+        let top = if self.scroll_offset == 0 {
+            0
+        } else {
+            self.scroll_offset + margin
+        };
+        let bottom = if self.scroll_offset == last_offset {
+            lines.len() - 1
+        } else {
+            self.scroll_offset + self.viewport_height - 1 - margin
+        };
+        let cursor_idx = self.tree_view.line_of(self.cursor.path()).unwrap_or(0);
+        let clamped = cursor_idx.clamp(top, bottom.min(lines.len() - 1));
+        if clamped != cursor_idx {
+            self.cursor.set_path(lines[clamped].path.clone());
         }
     }
 
+    /// Scrolls the tree view sideways by `delta` columns, stopping once the
+    /// widest line's last column reaches the left edge.
+    pub fn scroll_horizontal(&mut self, delta: isize) {
+        let widest = self
+            .tree_view
+            .lines()
+            .iter()
+            .map(|line| line.display_width())
+            .max()
+            .unwrap_or(0);
+        self.horizontal_scroll = self
+            .horizontal_scroll
+            .saturating_add_signed(delta)
+            .min(widest.saturating_sub(1));
+    }
+
     /// Jumps to the first line in the tree.
     pub fn jump_to_top(&mut self) {
         let lines = self.tree_view.lines();
@@ -1137,10 +1949,7 @@ impl EditorState {
             return;
         }
 
-        let current_idx = lines
-            .iter()
-            .position(|l| l.path == self.cursor.path())
-            .unwrap_or(0);
+        let current_idx = self.tree_view.line_of(self.cursor.path()).unwrap_or(0);
 
         // Calculate scroll amount (half viewport height)
         let scroll_amount = self.viewport_height / 2;
@@ -1169,10 +1978,7 @@ impl EditorState {
             return;
         }
 
-        let current_idx = lines
-            .iter()
-            .position(|l| l.path == self.cursor.path())
-            .unwrap_or(0);
+        let current_idx = self.tree_view.line_of(self.cursor.path()).unwrap_or(0);
 
         // Calculate scroll amount (half viewport height)
         let scroll_amount = self.viewport_height / 2;
@@ -1199,10 +2005,7 @@ impl EditorState {
             return;
         }
 
-        let current_idx = lines
-            .iter()
-            .position(|l| l.path == self.cursor.path())
-            .unwrap_or(0);
+        let current_idx = self.tree_view.line_of(self.cursor.path()).unwrap_or(0);
 
         // Calculate scroll amount (full viewport height)
         let scroll_amount = self.viewport_height;
@@ -1231,10 +2034,7 @@ impl EditorState {
             return;
         }
 
-        let current_idx = lines
-            .iter()
-            .position(|l| l.path == self.cursor.path())
-            .unwrap_or(0);
+        let current_idx = self.tree_view.line_of(self.cursor.path()).unwrap_or(0);
 
         // Calculate scroll amount (full viewport height)
         let scroll_amount = self.viewport_height;
@@ -1261,10 +2061,7 @@ impl EditorState {
             return;
         }
 
-        let cursor_idx = lines
-            .iter()
-            .position(|l| l.path == self.cursor.path())
-            .unwrap_or(0);
+        let cursor_idx = self.tree_view.line_of(self.cursor.path()).unwrap_or(0);
 
         // Calculate scroll offset to center cursor
         let half_height = self.viewport_height / 2;
@@ -1285,10 +2082,7 @@ impl EditorState {
             return;
         }
 
-        let cursor_idx = lines
-            .iter()
-            .position(|l| l.path == self.cursor.path())
-            .unwrap_or(0);
+        let cursor_idx = self.tree_view.line_of(self.cursor.path()).unwrap_or(0);
 
         // Set scroll offset so cursor is at top
         self.scroll_offset = cursor_idx;
@@ -1308,10 +2102,7 @@ impl EditorState {
             return;
         }
 
-        let cursor_idx = lines
-            .iter()
-            .position(|l| l.path == self.cursor.path())
-            .unwrap_or(0);
+        let cursor_idx = self.tree_view.line_of(self.cursor.path()).unwrap_or(0);
 
         // Set scroll offset so cursor is at bottom
         self.scroll_offset = cursor_idx.saturating_sub(self.viewport_height - 1);
@@ -1574,7 +2365,7 @@ impl EditorState {
 
         // Find the current line to get its depth
         let lines = self.tree_view.lines();
-        let current_line_idx = lines.iter().position(|line| line.path == *current_path);
+        let current_line_idx = self.tree_view.line_of(current_path);
 
         if let Some(idx) = current_line_idx {
             let current_depth = lines[idx].depth;
@@ -1644,7 +2435,7 @@ impl EditorState {
 
         // Find the current line to get its depth
         let lines = self.tree_view.lines();
-        let current_line_idx = lines.iter().position(|line| line.path == *current_path);
+        let current_line_idx = self.tree_view.line_of(current_path);
 
         if let Some(idx) = current_line_idx {
             let current_depth = lines[idx].depth;
@@ -1730,7 +2521,7 @@ impl EditorState {
     fn generate_completions(&self, prefix: &str) -> Vec<String> {
         // Handle `:theme ` completion
         if let Some(partial) = prefix.strip_prefix("theme ") {
-            let themes = crate::theme::list_builtin_themes();
+            let themes = crate::theme::list_themes();
             return themes
                 .into_iter()
                 .filter(|t| t.starts_with(partial))
@@ -1751,6 +2542,38 @@ impl EditorState {
                 "nomouse",
                 "create_backup",
                 "nocreate_backup",
+                "resolvemerge",
+                "noresolvemerge",
+                "autoread",
+                "noautoread",
+                "regexsearch",
+                "noregexsearch",
+                "searchcollapsed",
+                "nosearchcollapsed",
+                "breadcrumbs",
+                "nobreadcrumbs",
+                "typebadges",
+                "notypebadges",
+                "cloudformation",
+                "nocloudformation",
+                "prometheus",
+                "noprometheus",
+                "k8s",
+                "nok8s",
+                "decodesecrets",
+                "nodecodesecrets",
+                "resolve-env",
+                "noresolve-env",
+                "autovalidate",
+                "noautovalidate",
+                "keylog",
+                "nokeylog",
+                "keylogredact",
+                "nokeylogredact",
+                "scrolloff=",
+                "maxdepth=",
+                "mousescroll=",
+                "memorybudget=",
                 "save",
             ];
             return settings
@@ -1799,6 +2622,40 @@ impl EditorState {
         self.help_scroll = self.help_scroll.saturating_sub(1);
     }
 
+    /// Opens the `:registers` overlay, listing what each register holds.
+    pub fn open_registers(&mut self) -> anyhow::Result<()> {
+        if self.registers.filled().is_empty() {
+            anyhow::bail!("All registers are empty");
+        }
+        self.registers_scroll = Some(0);
+        Ok(())
+    }
+
+    /// Returns the first row the `:registers` overlay shows, while it is open.
+    pub fn registers_scroll(&self) -> Option<usize> {
+        self.registers_scroll
+    }
+
+    /// Scrolls the `:registers` overlay down, stopping at its last row.
+    pub fn scroll_registers_down(&mut self) {
+        let last = self.registers.filled().len().saturating_sub(1);
+        if let Some(scroll) = &mut self.registers_scroll {
+            *scroll = (*scroll + 1).min(last);
+        }
+    }
+
+    /// Scrolls the `:registers` overlay up.
+    pub fn scroll_registers_up(&mut self) {
+        if let Some(scroll) = &mut self.registers_scroll {
+            *scroll = scroll.saturating_sub(1);
+        }
+    }
+
+    /// Closes the `:registers` overlay.
+    pub fn close_registers(&mut self) {
+        self.registers_scroll = None;
+    }
+
     /// Returns whether the theme picker is currently visible.
     pub fn show_theme_picker(&self) -> bool {
         self.show_theme_picker
@@ -1888,23 +2745,483 @@ impl EditorState {
         self.show_theme_picker = false;
     }
 
-    /// Returns the pending theme name if there is one, consuming it.
-    pub fn take_pending_theme(&mut self) -> Option<String> {
-        self.pending_theme.take()
+    /// Remembers the file's current modification time as the version in the buffer.
+    ///
+    /// Called after the file is loaded or saved, so only changes made by other
+    /// programs are reported by [`check_external_change`](Self::check_external_change).
+    pub fn record_disk_state(&mut self) {
+        self.disk_state = self.read_disk_state();
+        self.seen_disk_state = self.disk_state;
+        self.disk_hash = self.read_disk_hash();
+        self.external_change = false;
     }
 
-    /// Requests a theme change.
-    pub fn request_theme_change(&mut self, theme_name: String) {
-        self.current_theme = theme_name.clone();
-        self.pending_theme = Some(theme_name);
+    fn read_disk_state(&self) -> Option<(std::time::SystemTime, u64)> {
+        let metadata = std::fs::metadata(self.filename.as_deref()?).ok()?;
+        Some((metadata.modified().ok()?, metadata.len()))
     }
 
-    /// Sets the current theme name (called when theme is applied).
-    pub fn set_current_theme(&mut self, theme_name: String) {
-        self.current_theme = theme_name;
+    /// Hashes the file's contents as they are on disk.
+    ///
+    /// Returns `None` for files at or over `lazy_load_threshold`, which
+    /// aren't read whole just to hash them, and for files that can't be read.
+    fn read_disk_hash(&self) -> Option<u64> {
+        use std::hash::{Hash, Hasher};
+
+        let filename = self.filename.as_deref()?;
+        if std::fs::metadata(filename).ok()?.len() >= self.lazy_load_threshold as u64 {
+            return None;
+        }
+        let bytes = std::fs::read(filename).ok()?;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        Some(hasher.finish())
     }
 
-    /// Yank nodes starting at cursor for count iterations.
+    /// Returns true if another program changed the file since it was loaded
+    /// or saved.
+    ///
+    /// When the contents were hashed on load or save they are hashed again,
+    /// so a file that was only touched (or rewritten unchanged) doesn't count
+    /// and one rewritten within the same second at the same size does. Files
+    /// too big to hash are compared by modification time and size. A file
+    /// that was deleted doesn't count either.
+    pub fn changed_on_disk(&self) -> bool {
+        let current = self.read_disk_state();
+        if current.is_none() || self.disk_state.is_none() {
+            return false;
+        }
+        match self.disk_hash {
+            Some(hash) => self.read_disk_hash() != Some(hash),
+            None => current != self.disk_state,
+        }
+    }
+
+    /// Blocks a write of the current file that would overwrite changes made
+    /// on disk since it was loaded or saved.
+    ///
+    /// Returns true if the write was blocked; `command` is kept so the
+    /// overwrite/reload/diff/cancel prompt can run it once overwriting is
+    /// chosen.
+    pub fn block_conflicting_save(&mut self, command: &str) -> bool {
+        if !self.changed_on_disk() {
+            return false;
+        }
+        let filename = self.filename.clone().unwrap_or_default();
+        self.save_conflict = Some(command.to_string());
+        self.set_message(
+            format!(
+                "\"{}\" changed on disk since it was loaded. (o)verwrite, (r)eload, (d)iff, (c)ancel",
+                filename
+            ),
+            MessageLevel::Error,
+        );
+        true
+    }
+
+    /// Returns the write command waiting on the save conflict prompt, if any.
+    pub fn save_conflict(&self) -> Option<&str> {
+        self.save_conflict.as_deref()
+    }
+
+    /// Closes the save conflict prompt, returning the blocked write command.
+    pub fn take_save_conflict(&mut self) -> Option<String> {
+        self.save_conflict.take()
+    }
+
+    /// Checks whether the file was changed on disk by another program.
+    ///
+    /// With `reload_on_change` set, an unmodified buffer is reloaded right
+    /// away. Otherwise the reload/ignore prompt is shown, as a conflict
+    /// warning if the buffer has unsaved changes. Returns true if a change
+    /// was detected.
+    pub fn check_external_change(&mut self) -> bool {
+        if self.external_change || self.save_conflict.is_some() {
+            return false;
+        }
+        let current = self.read_disk_state();
+        if current.is_none() || current == self.disk_state {
+            return false;
+        }
+
+        let filename = self.filename.clone().unwrap_or_default();
+        if self.reload_on_change && !self.dirty {
+            match self.reload_from_disk() {
+                Ok(()) => self.set_message(
+                    format!("\"{}\" changed on disk and was reloaded", filename),
+                    MessageLevel::Info,
+                ),
+                Err(e) => {
+                    self.set_message(format!("Error reloading file: {}", e), MessageLevel::Error)
+                }
+            }
+            return true;
+        }
+
+        self.external_change = true;
+        if self.dirty {
+            self.set_message(
+                format!(
+                    "Conflict: \"{}\" changed on disk and you have unsaved changes. (r)eload and lose them, (i)gnore",
+                    filename
+                ),
+                MessageLevel::Error,
+            );
+        } else {
+            self.set_message(
+                format!("\"{}\" changed on disk. (r)eload, (i)gnore", filename),
+                MessageLevel::Warning,
+            );
+        }
+        true
+    }
+
+    /// Calls [`check_external_change`](Self::check_external_change) at most once a second.
+    ///
+    /// Meant to be called on every turn of the event loop.
+    pub fn poll_external_change(&mut self) {
+        let now = std::time::Instant::now();
+        if self
+            .last_disk_check
+            .is_some_and(|last| now.duration_since(last) < std::time::Duration::from_secs(1))
+        {
+            return;
+        }
+        self.last_disk_check = Some(now);
+        self.seen_disk_state = self.read_disk_state();
+        self.check_external_change();
+    }
+
+    /// Returns the file's size and modification time as last seen on disk.
+    ///
+    /// The file is only looked at when loaded, saved, or by
+    /// [`poll_external_change`](Self::poll_external_change), so drawing the
+    /// status line doesn't touch the disk. `None` for a buffer without a file.
+    pub fn file_info(&self) -> Option<FileInfo> {
+        let (modified, size) = self.seen_disk_state?;
+        let newer_on_disk = self.disk_state.is_some_and(|(loaded, _)| modified > loaded);
+        let modified = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        Some(FileInfo {
+            size,
+            modified,
+            newer_on_disk,
+        })
+    }
+
+    /// Queues the subtree under the cursor to be edited in `$EDITOR`.
+    ///
+    /// The event loop picks the request up with
+    /// [`take_pending_external_edit`](Self::take_pending_external_edit), since
+    /// it has to suspend the terminal first.
+    pub fn request_external_edit(&mut self) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        let text = self.cursor_subtree_text()?;
+        self.pending_external_edit = Some(text);
+        Ok(())
+    }
+
+    /// Returns the subtree under the cursor as YAML, for replacing as a whole.
+    fn cursor_subtree_text(&self) -> anyhow::Result<String> {
+        self.ensure_not_merged()?;
+        let node = self
+            .tree
+            .get_node(self.cursor.path())
+            .ok_or_else(|| anyhow::anyhow!("Nothing to edit"))?;
+        match node.value() {
+            YamlValue::Alias(_) => anyhow::bail!("Cannot edit alias - edit its anchor instead"),
+            YamlValue::Comment(_) => anyhow::bail!("Not a value"),
+            _ => {}
+        }
+        crate::file::saver::serialize_subtree(node, &self.tree)
+    }
+
+    /// Takes the subtree text queued by [`request_external_edit`](Self::request_external_edit).
+    pub fn take_pending_external_edit(&mut self) -> Option<String> {
+        self.pending_external_edit.take()
+    }
+
+    /// Replaces the subtree under the cursor with the external editor's result.
+    ///
+    /// `original` is the text the editor was given. The temporary file is
+    /// removed unless its contents are not valid YAML, so nothing typed is
+    /// lost. The outcome is reported in the message area.
+    pub fn finish_external_edit(
+        &mut self,
+        original: &str,
+        edit: anyhow::Result<super::external::ExternalEdit>,
+    ) {
+        let edit = match edit {
+            Ok(edit) => edit,
+            Err(e) => {
+                self.set_message(format!("{:#}", e), MessageLevel::Error);
+                return;
+            }
+        };
+        let path = self.cursor.path().to_vec();
+        match self.apply_external_text(&path, original, &edit.text) {
+            Ok(changed) => {
+                let _ = std::fs::remove_file(&edit.path);
+                let message = if changed {
+                    "Subtree updated"
+                } else {
+                    "No changes"
+                };
+                self.set_message(message.to_string(), MessageLevel::Info);
+            }
+            Err(e) => self.set_message(
+                format!(
+                    "Invalid YAML, edit not applied (kept in {}): {}",
+                    edit.path.display(),
+                    e
+                ),
+                MessageLevel::Error,
+            ),
+        }
+    }
+
+    /// Parses `edited` and puts it in place of the node at `path`.
+    ///
+    /// Returns false if the text is unchanged. The edit is one undo step.
+    fn apply_external_text(
+        &mut self,
+        path: &[usize],
+        original: &str,
+        edited: &str,
+    ) -> anyhow::Result<bool> {
+        if edited.trim_end() == original.trim_end() {
+            return Ok(false);
+        }
+        let mut node = crate::document::parser::parse_yaml_auto(edited)?;
+        if matches!(node.value(), YamlValue::MultiDoc(_)) {
+            anyhow::bail!("expected a single document");
+        }
+        node.mark_all_modified();
+        let target = self
+            .tree
+            .get_node(path)
+            .ok_or_else(|| anyhow::anyhow!("Node not found at cursor"))?;
+        node.anchor = target.anchor.clone();
+        crate::document::ops::Op::Replace {
+            path: path.to_vec(),
+            node,
+        }
+        .apply(&mut self.tree)?;
+        self.track_nodes();
+
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        // Replacing the whole document can take the cursor's node with it
+        if self.tree.get_node(self.cursor.path()).is_none() {
+            let first = self.tree_view.lines().first().map(|l| l.path.clone());
+            self.cursor.set_path(first.unwrap_or_default());
+        }
+        self.checkpoint();
+        Ok(true)
+    }
+
+    /// Queues the subtree under the cursor, or the whole document, to be
+    /// piped through a shell command (`:!cmd`, `:%!cmd`).
+    ///
+    /// The event loop runs it and hands the output to
+    /// [`finish_filter`](Self::finish_filter).
+    pub fn request_filter(&mut self, command: &str, whole_document: bool) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        let command = command.trim();
+        if command.is_empty() {
+            anyhow::bail!("No command given");
+        }
+        let (input, path) = if whole_document {
+            if matches!(self.tree.root().value(), YamlValue::MultiDoc(_)) {
+                anyhow::bail!("Cannot filter a multi-document file as a whole");
+            }
+            (crate::file::saver::yaml_text(&self.tree)?, Vec::new())
+        } else {
+            (self.cursor_subtree_text()?, self.cursor.path().to_vec())
+        };
+        self.pending_filter = Some(super::external::FilterRequest {
+            command: command.to_string(),
+            input,
+            path,
+        });
+        Ok(())
+    }
+
+    /// Takes the filter queued by [`request_filter`](Self::request_filter).
+    pub fn take_pending_filter(&mut self) -> Option<super::external::FilterRequest> {
+        self.pending_filter.take()
+    }
+
+    /// Replaces the filtered node with the command's output.
+    ///
+    /// Output that is empty or isn't valid YAML leaves the tree alone. The
+    /// outcome is reported in the message area.
+    pub fn finish_filter(
+        &mut self,
+        request: &super::external::FilterRequest,
+        output: anyhow::Result<String>,
+    ) {
+        let result = output.and_then(|output| {
+            if output.trim().is_empty() {
+                anyhow::bail!("{} printed nothing; nothing changed", request.command);
+            }
+            self.apply_external_text(&request.path, &request.input, &output)
+                .map_err(|e| anyhow::anyhow!("Output is not valid YAML, nothing changed: {}", e))
+        });
+        match result {
+            Ok(true) => self.set_message(
+                format!("Filtered through {}", request.command),
+                MessageLevel::Info,
+            ),
+            Ok(false) => self.set_message("No changes".to_string(), MessageLevel::Info),
+            Err(e) => self.set_message(format!("{:#}", e), MessageLevel::Error),
+        }
+    }
+
+    /// Starts serving a read-only live view of this buffer and returns its URL.
+    ///
+    /// `addr` is a port or `host:port`; the default listens on a free local
+    /// port. A running share is replaced.
+    pub fn start_share(&mut self, addr: Option<&str>) -> anyhow::Result<String> {
+        let addr = match addr {
+            None => "127.0.0.1:0".to_string(),
+            Some(port) if port.parse::<u16>().is_ok() => format!("127.0.0.1:{}", port),
+            Some(addr) => addr.to_string(),
+        };
+        self.share = None;
+        let server = crate::export::share::ShareServer::start(&addr)?;
+        let url = server.url();
+        self.share = Some(server);
+        self.last_share_publish = None;
+        self.poll_share();
+        Ok(url)
+    }
+
+    /// Stops the `:share` server; returns false if none was running.
+    pub fn stop_share(&mut self) -> bool {
+        self.share.take().is_some()
+    }
+
+    /// Returns the URL of the running `:share` view, if any.
+    pub fn share_url(&self) -> Option<String> {
+        self.share.as_ref().map(|s| s.url())
+    }
+
+    /// Publishes the current view to the `:share` server at most once a second.
+    ///
+    /// Meant to be called on every turn of the event loop.
+    pub fn poll_share(&mut self) {
+        let Some(server) = &self.share else {
+            return;
+        };
+        let now = std::time::Instant::now();
+        if self
+            .last_share_publish
+            .is_some_and(|last| now.duration_since(last) < std::time::Duration::from_secs(1))
+        {
+            return;
+        }
+        self.last_share_publish = Some(now);
+        let title = format!("{} (read-only)", self.filename().unwrap_or("[stdin]"));
+        server.publish(&crate::export::render_html(
+            &self.tree_view,
+            &self.theme_colors(),
+            &title,
+        ));
+    }
+
+    /// Returns true while the reload/ignore prompt for an external change is showing.
+    pub fn external_change_pending(&self) -> bool {
+        self.external_change
+    }
+
+    /// Dismisses the external change prompt, keeping the buffer as it is.
+    ///
+    /// The new version on disk becomes the baseline, so the same change is
+    /// not reported again.
+    pub fn ignore_external_change(&mut self) {
+        self.record_disk_state();
+        self.set_message(
+            "Kept buffer; :e! reloads from disk".to_string(),
+            MessageLevel::Info,
+        );
+    }
+
+    /// Reloads the file from disk, discarding unsaved changes.
+    pub fn reload_from_disk(&mut self) -> anyhow::Result<()> {
+        use crate::file::loader::load_yaml_file_lazy;
+
+        let filename = self
+            .filename
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No file name"))?;
+        let tree = load_yaml_file_lazy(&filename, self.lazy_load_threshold, &self.load_limits)?;
+        self.reload_tree(tree);
+        self.load_buffer_settings();
+        self.load_undo_history();
+        self.record_disk_state();
+        Ok(())
+    }
+
+    /// Returns the pending theme name if there is one, consuming it.
+    pub fn take_pending_theme(&mut self) -> Option<String> {
+        self.pending_theme.take()
+    }
+
+    /// Requests a theme change.
+    pub fn request_theme_change(&mut self, theme_name: String) {
+        self.current_theme = theme_name.clone();
+        self.pending_theme = Some(theme_name);
+    }
+
+    /// Returns the pending window command if there is one, consuming it.
+    pub fn take_pending_window_command(&mut self) -> Option<super::window::WindowCommand> {
+        self.pending_window_command.take()
+    }
+
+    /// Requests a change to the window layout (split, focus, close).
+    pub fn request_window_command(&mut self, command: super::window::WindowCommand) {
+        self.pending_window_command = Some(command);
+    }
+
+    /// Returns the pending buffer command if there is one, consuming it.
+    pub fn take_pending_buffer_command(&mut self) -> Option<crate::app::BufferCommand> {
+        self.pending_buffer_command.take()
+    }
+
+    /// Requests a buffer change (`:e`, `:bn`, `:bp`, `:ls`).
+    pub fn request_buffer_command(&mut self, command: crate::app::BufferCommand) {
+        self.pending_buffer_command = Some(command);
+    }
+
+    /// Marks the next quit as forced (`:q!`).
+    pub fn request_force_quit(&mut self) {
+        self.force_quit = true;
+    }
+
+    /// Returns whether the quit was forced, clearing the flag.
+    pub fn take_force_quit(&mut self) -> bool {
+        std::mem::take(&mut self.force_quit)
+    }
+
+    /// Returns this buffer's 1-based position in the buffer list and the list's length.
+    pub fn buffer_position(&self) -> Option<(usize, usize)> {
+        self.buffer_position
+    }
+
+    /// Sets this buffer's position in the buffer list.
+    pub fn set_buffer_position(&mut self, number: usize, count: usize) {
+        self.buffer_position = Some((number, count));
+    }
+
+    /// Sets the current theme name (called when theme is applied).
+    pub fn set_current_theme(&mut self, theme_name: String) {
+        self.current_theme = theme_name;
+    }
+
+    /// Yank nodes starting at cursor for count iterations.
     /// Updates target register (unnamed if not specified), register "0, and system clipboard (unnamed only).
     pub fn yank_nodes(&mut self, count: u32) -> bool {
         use crate::document::node::YamlValue;
@@ -1966,35 +3283,23 @@ impl EditorState {
             } else {
                 self.registers.set_named(reg, content.clone());
             }
+            // The + and * registers are the system clipboard
+            if matches!(reg, '+' | '*') {
+                self.check_memory();
+                self.mirror_to_clipboard(&content);
+            }
         } else {
             // Unnamed register
             self.registers.set_unnamed(content.clone());
 
             // Sync to system clipboard
-            let clipboard_text = if content.nodes.len() == 1 {
-                // Single node: serialize as-is
-                let yaml_value = self.node_to_serde_value(content.nodes[0].value());
-                serde_yaml::to_string(&yaml_value).ok()
-            } else {
-                // Multiple nodes: serialize as JSON array
-                let array: Vec<serde_yaml::Value> = content
-                    .nodes
-                    .iter()
-                    .map(|node| self.node_to_serde_value(node.value()))
-                    .collect();
-                serde_yaml::to_string(&array).ok()
-            };
-
-            if let Some(yaml_str) = clipboard_text {
-                use arboard::Clipboard;
-                if let Ok(mut clipboard) = Clipboard::new() {
-                    let _ = clipboard.set_text(yaml_str);
-                }
-            }
+            self.check_memory();
+            self.mirror_to_clipboard(&content);
         }
 
         // Update "0 (last yank)
         self.registers.update_yank_register(content);
+        self.check_memory();
 
         true
     }
@@ -2038,6 +3343,58 @@ impl EditorState {
                 // Use a string representation
                 serde_yaml::Value::String(format!("# {}", c.content))
             }
+            YamlValue::Deferred(source) => {
+                serde_yaml::from_str(&source.text).unwrap_or(serde_yaml::Value::Null)
+            }
+        }
+    }
+
+    /// Returns the `--pick` output mode, if the editor was started as a picker.
+    pub fn pick_mode(&self) -> Option<PickOutput> {
+        self.pick_mode
+    }
+
+    /// Turns `--pick` mode on or off.
+    pub fn set_pick_mode(&mut self, mode: Option<PickOutput>) {
+        self.pick_mode = mode;
+    }
+
+    /// Returns the picked path or value, once a node has been picked.
+    pub fn picked(&self) -> Option<&str> {
+        self.picked.as_deref()
+    }
+
+    /// Picks the node under the cursor, returning true if a result was recorded.
+    ///
+    /// Strings are output raw (unquoted) so the result can be used directly
+    /// in shell scripts; containers are serialized as YAML.
+    pub fn pick_at_cursor(&mut self) -> bool {
+        use crate::document::node::YamlValue;
+
+        let picked = match self.pick_mode {
+            None => None,
+            Some(PickOutput::Path) => self.compute_path_string("dot"),
+            Some(PickOutput::Value) => {
+                self.tree
+                    .get_node(self.cursor.path())
+                    .map(|node| match node.value() {
+                        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
+                            let value = self.node_to_serde_value(node.value());
+                            serde_yaml::to_string(&value)
+                                .map(|yaml| yaml.trim_end().to_string())
+                                .unwrap_or_default()
+                        }
+                        scalar => scalar.to_string(),
+                    })
+            }
+        };
+
+        match picked {
+            Some(picked) => {
+                self.picked = Some(picked);
+                true
+            }
+            None => false,
         }
     }
 
@@ -2047,7 +3404,11 @@ impl EditorState {
     /// # Arguments
     /// * `format` - "dot" for `.foo[3].bar`, "bracket" for `["foo"][3]["bar"]`, "jq" for jq-style
     pub fn compute_path_string(&self, format: &str) -> Option<String> {
-        let path = self.cursor.path();
+        self.path_to_string(self.cursor.path(), format)
+    }
+
+    /// Formats a node path as a string; see [`EditorState::compute_path_string`] for formats.
+    pub fn path_to_string(&self, path: &[usize], format: &str) -> Option<String> {
         if path.is_empty() {
             // At root - different formats handle this differently
             return match format {
@@ -2219,32 +3580,11 @@ impl EditorState {
     }
 
     /// Pastes nodes at cursor from register (after current position).
+    ///
+    /// The `+` and `*` registers paste the system clipboard, parsed as YAML
+    /// or JSON (see [`Self::paste_text`]).
     pub fn paste_nodes_at_cursor(&mut self) -> anyhow::Result<()> {
-        use anyhow::anyhow;
-
-        // Get content from appropriate register
-        let content = if let Some(reg) = self.pending_register {
-            self.registers
-                .get(reg)
-                .ok_or_else(|| anyhow!("Nothing in register '{}'", reg))?
-                .clone()
-        } else {
-            self.registers.get_unnamed().clone()
-        };
-
-        if content.is_empty() {
-            return Err(anyhow!("Nothing to paste"));
-        }
-
-        // Paste each node
-        for (node, key) in content.nodes.iter().zip(content.keys.iter()) {
-            self.paste_single_node(node.clone(), key.clone(), true)?;
-        }
-
-        self.mark_dirty();
-        // Create undo checkpoint AFTER mutation so undo restores pre-paste state
-        self.checkpoint();
-        Ok(())
+        self.paste_from_register(true)
     }
 
     /// Deprecated alias for paste_nodes_at_cursor (for backwards compatibility).
@@ -2254,23 +3594,62 @@ impl EditorState {
 
     /// Pastes nodes before cursor from register.
     pub fn paste_nodes_before_cursor(&mut self) -> anyhow::Result<()> {
+        self.paste_from_register(false)
+    }
+
+    /// Pastes text as a subtree, the way `"+p` pastes the system clipboard.
+    ///
+    /// The text is parsed as YAML, which covers JSON; text that doesn't
+    /// parse is pasted as a string. See [`registers::clipboard_content`]
+    /// for how mappings and lists are split.
+    ///
+    /// [`registers::clipboard_content`]: super::registers::clipboard_content
+    pub fn paste_text(&mut self, text: &str, after: bool) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        let into_mapping = self.paste_target_is_mapping(after);
+        self.paste_content(
+            super::registers::clipboard_content(text, into_mapping),
+            after,
+        )
+    }
+
+    fn paste_from_register(&mut self, after: bool) -> anyhow::Result<()> {
+        self.ensure_writable()?;
         use anyhow::anyhow;
 
-        let content = if let Some(reg) = self.pending_register {
-            self.registers
+        // Get content from appropriate register
+        let content = match self.pending_register {
+            Some('+' | '*') => {
+                let text = arboard::Clipboard::new()
+                    .and_then(|mut clipboard| clipboard.get_text())
+                    .map_err(|e| anyhow!("Can't read the clipboard: {}", e))?;
+                return self.paste_text(&text, after);
+            }
+            Some(reg) => self
+                .registers
                 .get(reg)
                 .ok_or_else(|| anyhow!("Nothing in register '{}'", reg))?
-                .clone()
-        } else {
-            self.registers.get_unnamed().clone()
+                .clone(),
+            None => self.registers.get_unnamed().clone(),
         };
+        self.paste_content(content, after)
+    }
 
+    fn paste_content(
+        &mut self,
+        content: super::registers::RegisterContent,
+        after: bool,
+    ) -> anyhow::Result<()> {
         if content.is_empty() {
-            return Err(anyhow!("Nothing to paste"));
+            anyhow::bail!("Nothing to paste");
+        }
+        if content.column {
+            return self.paste_column().map(|_| ());
         }
 
+        // Paste each node
         for (node, key) in content.nodes.iter().zip(content.keys.iter()) {
-            self.paste_single_node(node.clone(), key.clone(), false)?;
+            self.paste_single_node(node.duplicate(), key.clone(), after)?;
         }
 
         self.mark_dirty();
@@ -2279,20 +3658,373 @@ impl EditorState {
         Ok(())
     }
 
+    /// Returns whether a paste would insert into a mapping (rather than a
+    /// list), following the same rules as `paste_single_node`.
+    fn paste_target_is_mapping(&self, after: bool) -> bool {
+        use crate::document::node::YamlValue;
+
+        let path = self.cursor.path();
+        let container = match path.split_last() {
+            None => path,
+            Some(_)
+                if after
+                    && self.tree_view.is_expanded(path)
+                    && self
+                        .tree
+                        .get_node(path)
+                        .is_some_and(|n| n.value().is_container()) =>
+            {
+                path
+            }
+            Some((_, parent)) => parent,
+        };
+        matches!(
+            self.tree.get_node(container).map(|n| n.value()),
+            Some(YamlValue::Object(_))
+        )
+    }
+
     /// Deprecated alias for paste_nodes_before_cursor (for backwards compatibility).
     pub fn paste_node_before_cursor(&mut self) -> anyhow::Result<()> {
         self.paste_nodes_before_cursor()
     }
 
-    /// Helper to paste a single node.
-    fn paste_single_node(
-        &mut self,
-        node: YamlNode,
-        key: Option<String>,
-        after: bool,
-    ) -> anyhow::Result<()> {
-        use crate::document::node::YamlValue;
-        use anyhow::anyhow;
+    /// Returns whether the register `p` would paste from holds a column.
+    pub fn paste_register_is_column(&self) -> bool {
+        match self.pending_register {
+            Some(reg) => self.registers.get(reg).is_some_and(|c| c.column),
+            None => self.registers.get_unnamed().column,
+        }
+    }
+
+    /// Pastes a column register element by element, in one undoable edit.
+    ///
+    /// In visual block mode the values fill every selected element, repeating
+    /// from the start when there are fewer values than elements (so yanking
+    /// one value copies it down the block). Otherwise value N goes to the Nth
+    /// element from the one under the cursor, stopping at the end of the list.
+    /// The key is replaced where it exists and appended where it doesn't.
+    /// Returns the name of the key and the number of elements changed.
+    pub fn paste_column(&mut self) -> anyhow::Result<(String, usize)> {
+        self.ensure_writable()?;
+        use crate::document::node::YamlValue;
+        use anyhow::anyhow;
+
+        let content = match self.pending_register {
+            Some(reg) => self
+                .registers
+                .get(reg)
+                .ok_or_else(|| anyhow!("Nothing in register '{}'", reg))?
+                .clone(),
+            None => self.registers.get_unnamed().clone(),
+        };
+        let Some(Some(key)) = content.keys.first().cloned() else {
+            return Err(anyhow!("Nothing to paste"));
+        };
+
+        let (list_path, targets): (Vec<usize>, Vec<(usize, &YamlNode)>) = if self.visual_block {
+            let (list_path, _, first, last) = self
+                .visual_block_range()
+                .ok_or_else(|| anyhow!("Cursor left the visual block"))?;
+            let values = content.nodes.iter().cycle();
+            (list_path, (first..=last).zip(values).collect())
+        } else {
+            let element = self
+                .enclosing_list_element(self.cursor.path())
+                .ok_or_else(|| {
+                    anyhow!("Column paste needs the cursor in an element of a list of mappings")
+                })?;
+            let list_path = element[..element.len() - 1].to_vec();
+            let first = element[element.len() - 1];
+            let len = match self.tree.get_node(&list_path).map(|n| n.value()) {
+                Some(YamlValue::Array(items)) => items.len(),
+                _ => 0,
+            };
+            let targets = (first..len).zip(content.nodes.iter()).collect();
+            (list_path, targets)
+        };
+
+        let mut changed = 0;
+        for (index, value) in targets {
+            let mut element_path = list_path.clone();
+            element_path.push(index);
+            if self.set_element_key(&element_path, &key, value.duplicate()) {
+                changed += 1;
+            }
+        }
+
+        if self.mode == EditorMode::Visual {
+            self.exit_visual_mode();
+        }
+        if changed == 0 {
+            return Err(anyhow!("No mappings to paste '{}' into", key));
+        }
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        Ok((key, changed))
+    }
+
+    /// Sets `key` in the mapping at `element_path`, replacing its value or
+    /// appending it. Returns false when the node isn't a mapping.
+    fn set_element_key(&mut self, element_path: &[usize], key: &str, value: YamlNode) -> bool {
+        use crate::document::node::YamlValue;
+
+        match self.tree.get_node_mut(element_path).map(|n| n.value_mut()) {
+            Some(YamlValue::Object(fields)) => {
+                match fields.get_mut(key) {
+                    Some(existing) => *existing = value,
+                    None => {
+                        fields.insert(key.to_string(), value);
+                    }
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Copies `key` from the first selected list element into every other
+    /// selected element, adding it where it's missing (`:filldown`).
+    ///
+    /// Elements are the mappings in a list that contain the selected nodes,
+    /// so selecting a field or the element itself both work. The edit is one
+    /// undo step. Returns the number of elements filled.
+    pub fn fill_down(&mut self, key: &str) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        use crate::document::node::YamlValue;
+        use anyhow::{anyhow, bail};
+
+        let mut elements: Vec<Vec<usize>> = Vec::new();
+        for path in &self.visual_selection {
+            if let Some(element) = self.enclosing_list_element(path) {
+                if !elements.contains(&element) {
+                    elements.push(element);
+                }
+            }
+        }
+        elements.sort();
+        let Some((first, rest)) = elements.split_first() else {
+            bail!("Select list elements in visual mode first");
+        };
+
+        let value = match self.tree.get_node(first).map(|n| n.value()) {
+            Some(YamlValue::Object(fields)) => fields.get(key).cloned(),
+            _ => None,
+        }
+        .ok_or_else(|| anyhow!("First selected element has no '{}'", key))?;
+
+        let mut filled = 0;
+        for element in rest {
+            if self.set_element_key(element, key, value.duplicate()) {
+                filled += 1;
+            }
+        }
+
+        self.clear_visual_selection();
+        if filled > 0 {
+            self.mark_dirty();
+            self.rebuild_tree_view();
+            self.checkpoint();
+        }
+        Ok(filled)
+    }
+
+    /// Sets every selected scalar to `input`, parsed like a typed value
+    /// (visual `c`, `:change`).
+    ///
+    /// Scalars inside selected mappings and lists that are shown in the
+    /// tree count too; containers, comments and aliases are skipped. The
+    /// edit is one undo step. Returns the number of values changed.
+    pub fn change_selection(&mut self, input: &str) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+
+        let mut targets: Vec<Vec<usize>> = Vec::new();
+        for selected in &self.visual_selection {
+            let shown = self
+                .tree_view
+                .lines()
+                .iter()
+                .map(|line| &line.path)
+                .filter(|path| path.len() > selected.len() && path.starts_with(selected));
+            for path in std::iter::once(selected).chain(shown) {
+                let scalar = self.tree.get_node(path).is_some_and(|node| {
+                    let value = node.value();
+                    !value.is_container()
+                        && !value.is_comment()
+                        && !value.is_deferred()
+                        && node.alias_target().is_none()
+                });
+                if scalar && !targets.contains(path) {
+                    targets.push(path.clone());
+                }
+            }
+        }
+        if targets.is_empty() {
+            anyhow::bail!("Select scalar values in visual mode first");
+        }
+
+        let value = parse_scalar_value(input);
+        for path in &targets {
+            if let Some(node) = self.tree.get_node_mut(path) {
+                *node.value_mut() = value.clone();
+            }
+        }
+
+        self.clear_visual_selection();
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        Ok(targets.len())
+    }
+
+    /// Renames every selected mapping key using `template`, where `{}`
+    /// stands for the old key (visual `r`, `:rename`).
+    ///
+    /// `:rename app_{}` prefixes each key and `:rename {}_old` suffixes it.
+    /// Nothing is renamed if a new key would clash with a key already in
+    /// its mapping. The edit is one undo step. Returns the number of keys
+    /// renamed.
+    pub fn rename_selection(&mut self, template: &str) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        use crate::document::node::YamlValue;
+        use anyhow::bail;
+
+        if !template.contains("{}") {
+            bail!("Use {{}} for the old key, e.g. :rename new_{{}}");
+        }
+
+        let mut renames: Vec<(Vec<usize>, String)> = Vec::new();
+        for path in &self.visual_selection {
+            let Some((&index, parent)) = path.split_last() else {
+                continue;
+            };
+            let Some(YamlValue::Object(fields)) = self.tree.get_node(parent).map(|n| n.value())
+            else {
+                continue;
+            };
+            let Some((old, _)) = fields.get_index(index) else {
+                continue;
+            };
+            let new = template.replace("{}", old);
+            if new == *old {
+                continue;
+            }
+            let clash = fields.contains_key(&new)
+                || renames
+                    .iter()
+                    .any(|(other, key)| other[..other.len() - 1] == *parent && *key == new);
+            if clash {
+                bail!("Key '{}' already exists", new);
+            }
+            renames.push((path.clone(), new));
+        }
+        if renames.is_empty() {
+            bail!("Select mapping keys in visual mode first");
+        }
+
+        for (path, key) in &renames {
+            self.rename_key_at(path, key);
+        }
+
+        self.clear_visual_selection();
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        Ok(renames.len())
+    }
+
+    /// Imports key/value pairs into the mapping under the cursor (`:import env`).
+    ///
+    /// Pairs come from the `.env` file at `file`, or from the environment
+    /// when there is none, keeping only keys matching the `filter` regex.
+    /// Existing keys are updated in place and new ones appended; a null
+    /// under the cursor becomes a new mapping. Values are imported as
+    /// strings. The import is one undo step. Returns the number of keys
+    /// added and updated.
+    pub fn import_env(
+        &mut self,
+        file: Option<&str>,
+        filter: Option<&str>,
+    ) -> anyhow::Result<(usize, usize)> {
+        self.ensure_writable()?;
+        use crate::document::node::{YamlString, YamlValue};
+        use crate::editor::dotenv;
+        use anyhow::{bail, Context};
+
+        let pairs = match file {
+            Some(file) => {
+                let text = std::fs::read_to_string(file)
+                    .with_context(|| format!("Can't read {}", file))?;
+                dotenv::parse(&text)?
+            }
+            None => dotenv::environment(),
+        };
+        let pairs = dotenv::filter(pairs, filter)?;
+        if pairs.is_empty() {
+            bail!("No variables to import");
+        }
+
+        let path = self.cursor.path().to_vec();
+        let Some(node) = self.tree.get_node_mut(&path) else {
+            bail!("Nothing under the cursor");
+        };
+        if matches!(node.value(), YamlValue::Null) {
+            *node = YamlNode::new(YamlValue::Object(Default::default()));
+        }
+        let YamlValue::Object(fields) = node.value_mut() else {
+            bail!("Put the cursor on a mapping to import into");
+        };
+
+        let (mut added, mut updated) = (0, 0);
+        for (key, value) in pairs {
+            let value = YamlValue::String(YamlString::Plain(value));
+            match fields.get_mut(&key) {
+                Some(existing) if existing.value() == &value => {}
+                Some(existing) => {
+                    *existing = YamlNode::new(value);
+                    updated += 1;
+                }
+                None => {
+                    fields.insert(key, YamlNode::new(value));
+                    added += 1;
+                }
+            }
+        }
+
+        if added + updated > 0 {
+            self.mark_dirty();
+            self.rebuild_tree_view();
+            self.checkpoint();
+        }
+        Ok((added, updated))
+    }
+
+    /// Returns the path of the innermost mapping that contains `path` (or is
+    /// `path`) and is itself an element of a list.
+    fn enclosing_list_element(&self, path: &[usize]) -> Option<Vec<usize>> {
+        use crate::document::node::YamlValue;
+
+        (1..=path.len()).rev().find_map(|len| {
+            let element = &path[..len];
+            let in_list = matches!(
+                self.tree.get_node(&element[..len - 1])?.value(),
+                YamlValue::Array(_)
+            );
+            let is_mapping = matches!(self.tree.get_node(element)?.value(), YamlValue::Object(_));
+            (in_list && is_mapping).then(|| element.to_vec())
+        })
+    }
+
+    /// Helper to paste a single node.
+    fn paste_single_node(
+        &mut self,
+        node: YamlNode,
+        key: Option<String>,
+        after: bool,
+    ) -> anyhow::Result<()> {
+        use crate::document::node::YamlValue;
+        use anyhow::anyhow;
 
         let current_path = self.cursor.path().to_vec();
 
@@ -2343,8 +4075,6 @@ impl EditorState {
                                 node.clone(),
                             )?;
 
-                            self.tree_view_mut()
-                                .update_paths_after_insertion(&insert_path);
                             self.rebuild_tree_view();
                             self.cursor.set_path(insert_path);
                             return Ok(());
@@ -2356,8 +4086,6 @@ impl EditorState {
 
                             self.tree.insert_node_in_array(&insert_path, node.clone())?;
 
-                            self.tree_view_mut()
-                                .update_paths_after_insertion(&insert_path);
                             self.rebuild_tree_view();
                             self.cursor.set_path(insert_path);
                             return Ok(());
@@ -2413,8 +4141,6 @@ impl EditorState {
                         self.tree_view_mut().toggle_expand(&[]);
                     }
 
-                    self.tree_view_mut()
-                        .update_paths_after_insertion(&insert_path);
                     self.rebuild_tree_view();
                     self.cursor.set_path(insert_path);
                     return Ok(());
@@ -2433,8 +4159,6 @@ impl EditorState {
                         self.tree_view_mut().toggle_expand(&[]);
                     }
 
-                    self.tree_view_mut()
-                        .update_paths_after_insertion(&insert_path);
                     self.rebuild_tree_view();
                     self.cursor.set_path(insert_path);
                     return Ok(());
@@ -2506,8 +4230,6 @@ impl EditorState {
                     .insert_node_in_object(&insert_path, key_name, node.clone())?;
 
                 // Update tree view and cursor
-                self.tree_view_mut()
-                    .update_paths_after_insertion(&insert_path);
                 self.rebuild_tree_view();
                 self.cursor.set_path(insert_path);
             }
@@ -2518,8 +4240,6 @@ impl EditorState {
                 self.tree.insert_node_in_array(&insert_path, node.clone())?;
 
                 // Update tree view and cursor
-                self.tree_view_mut()
-                    .update_paths_after_insertion(&insert_path);
                 self.rebuild_tree_view();
                 self.cursor.set_path(insert_path);
             }
@@ -2564,7 +4284,7 @@ impl EditorState {
         let lines = self.tree_view.lines();
 
         // Find the line at the current cursor position
-        let line = lines.iter().find(|l| l.path == current_path)?;
+        let line = &lines[self.tree_view.line_of(current_path)?];
 
         // Return the key if it exists
         line.key.clone()
@@ -2589,76 +4309,196 @@ impl EditorState {
         };
 
         // Set up the search
-        self.search_buffer = key_name.clone();
+        // Match the key literally even when unprefixed patterns are regexes
+        self.search_buffer = if self.regex_search {
+            format!("\\V{}", key_name)
+        } else {
+            key_name
+        };
         self.search_forward = forward;
-        self.execute_search();
+        // A literal pattern always compiles
+        let _ = self.execute_search();
 
         true
     }
 
     /// Executes a search for the current search buffer text.
     /// Uses smart case: case-insensitive search unless the pattern contains uppercase letters.
-    pub fn execute_search(&mut self) {
+    ///
+    /// The pattern is a substring, a regex (`\v` prefix, or any unprefixed
+    /// pattern with `regexsearch` set) or a key-path glob (`\g` prefix); see
+    /// [`crate::editor::search`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving no results, if the pattern does not compile.
+    pub fn execute_search(&mut self) -> anyhow::Result<()> {
+        use crate::editor::search::SearchPattern;
+
         if self.search_buffer.is_empty() {
-            return;
+            return Ok(());
         }
 
-        // Smart case: case-insensitive unless pattern has uppercase
-        let case_sensitive = self.search_buffer.chars().any(|c| c.is_uppercase());
-        let query = if case_sensitive {
-            self.search_buffer.clone()
-        } else {
-            self.search_buffer.to_lowercase()
-        };
-
         self.search_results.clear();
         self.search_index = 0;
         self.search_type = Some(SearchType::Text);
 
-        // Search through all visible lines
+        let pattern = SearchPattern::parse(&self.search_buffer, self.regex_search)?;
+        if pattern.is_empty() {
+            return Ok(());
+        }
+
+        if self.search_collapsed {
+            self.search_results = self.search_tree(&pattern);
+            let tree_view = &self.tree_view;
+            self.search_results
+                .retain(|path| tree_view.passes_filter(path));
+        } else {
+            self.search_results = self.search_visible_lines(&pattern);
+        }
+        self.track_nodes();
+
+        // Jump to first or last result based on search direction
+        if !self.search_results.is_empty() {
+            self.search_index = if self.search_forward {
+                0
+            } else {
+                self.search_results.len() - 1
+            };
+            self.reveal_path(self.search_results[self.search_index].clone());
+        }
+        Ok(())
+    }
+
+    /// Returns the paths of every node in the document matching `pattern`,
+    /// in document order, including those inside collapsed nodes and those
+    /// hidden by a view filter.
+    fn search_tree(&self, pattern: &crate::editor::search::SearchPattern) -> Vec<Vec<usize>> {
+        use crate::document::node::{YamlNode, YamlValue};
+        use crate::editor::search::SearchPattern;
+
+        fn walk(
+            node: &YamlNode,
+            key: Option<&str>,
+            path: &mut Vec<usize>,
+            segments: &mut Vec<String>,
+            pattern: &SearchPattern,
+            results: &mut Vec<Vec<usize>>,
+        ) {
+            if !path.is_empty() {
+                let matches = key.is_some_and(|k| pattern.matches_text(k))
+                    || matches!(node.value(), YamlValue::String(s) if pattern.matches_text(s.as_str()))
+                    || (matches!(pattern, SearchPattern::KeyGlob(_))
+                        && pattern.matches_path(segments));
+                if matches {
+                    results.push(path.clone());
+                }
+            }
+            let children: Vec<(Option<&str>, &YamlNode)> = match node.value() {
+                YamlValue::Object(entries) => {
+                    entries.iter().map(|(k, v)| (Some(k.as_str()), v)).collect()
+                }
+                YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+                    items.iter().map(|v| (None, v)).collect()
+                }
+                _ => return,
+            };
+            for (i, (key, child)) in children.into_iter().enumerate() {
+                path.push(i);
+                segments.push(key.map_or_else(|| i.to_string(), str::to_string));
+                walk(child, key, path, segments, pattern, results);
+                segments.pop();
+                path.pop();
+            }
+        }
+
+        let mut results = Vec::new();
+        walk(
+            self.tree.root(),
+            None,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            pattern,
+            &mut results,
+        );
+        results
+    }
+
+    /// Returns the paths of the shown lines matching `pattern`.
+    fn search_visible_lines(
+        &self,
+        pattern: &crate::editor::search::SearchPattern,
+    ) -> Vec<Vec<usize>> {
+        use crate::editor::search::SearchPattern;
+
+        let mut results = Vec::new();
         for line in self.tree_view.lines() {
             let mut matches = false;
 
             // Check key name
             if let Some(key) = &line.key {
-                let key_text = if case_sensitive {
-                    key.clone()
-                } else {
-                    key.to_lowercase()
-                };
-                if key_text.contains(&query) {
+                if pattern.matches_text(key) {
                     matches = true;
                 }
             }
 
             // Check string values
             if let crate::ui::tree_view::ValueType::String = line.value_type {
-                let value_text = if case_sensitive {
-                    line.value_preview.clone()
-                } else {
-                    line.value_preview.to_lowercase()
-                };
-                if value_text.contains(&query) {
+                if pattern.matches_text(&line.value_preview) {
                     matches = true;
                 }
             }
 
+            // Check the key path
+            if matches!(pattern, SearchPattern::KeyGlob(_))
+                && pattern.matches_path(&self.key_path_segments(&line.path))
+            {
+                matches = true;
+            }
+
             if matches {
-                self.search_results.push(line.path.clone());
+                results.push(line.path.clone());
             }
         }
+        results
+    }
 
-        // Jump to first or last result based on search direction
-        if !self.search_results.is_empty() {
-            if self.search_forward {
-                self.search_index = 0;
-                self.cursor.set_path(self.search_results[0].clone());
-            } else {
-                self.search_index = self.search_results.len() - 1;
-                self.cursor
-                    .set_path(self.search_results[self.search_index].clone());
+    /// Moves the cursor to `path`, expanding collapsed ancestors so the node
+    /// is shown.
+    fn reveal_path(&mut self, path: Vec<usize>) {
+        if (1..path.len()).any(|depth| !self.tree_view.is_expanded(&path[..depth])) {
+            let mut expanded = self.tree_view.expanded_paths().clone();
+            expanded.extend((1..path.len()).map(|depth| path[..depth].to_vec()));
+            self.tree_view.set_expanded_paths(expanded);
+            self.rebuild_tree_view();
+        }
+        self.cursor.set_path(path);
+    }
+
+    /// Returns the keys (and list indexes, as numbers) leading to `path`.
+    fn key_path_segments(&self, path: &[usize]) -> Vec<String> {
+        use crate::document::node::YamlValue;
+
+        let mut segments = Vec::with_capacity(path.len());
+        let mut current = self.tree.root();
+        for &index in path {
+            let next = match current.value() {
+                YamlValue::Object(entries) => entries.get_index(index).map(|(key, node)| {
+                    segments.push(key.clone());
+                    node
+                }),
+                YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+                    segments.push(index.to_string());
+                    items.get(index)
+                }
+                _ => None,
+            };
+            match next {
+                Some(node) => current = node,
+                None => break,
             }
         }
+        segments
     }
 
     /// Executes a JSONPath query and populates search results.
@@ -2680,13 +4520,14 @@ impl EditorState {
         // Evaluate against the tree root
         let evaluator = Evaluator::new(self.tree.root());
         self.search_results = evaluator.evaluate_paths(&path.segments);
+        self.track_nodes();
 
         // Set search type
         self.search_type = Some(SearchType::YamlPath(query.to_string()));
 
         // Jump to first result or show message
         if !self.search_results.is_empty() {
-            self.cursor.set_path(self.search_results[0].clone());
+            self.reveal_path(self.search_results[0].clone());
             self.set_message(
                 format!("Found {} matches for {}", self.search_results.len(), query),
                 MessageLevel::Info,
@@ -2696,1608 +4537,5877 @@ impl EditorState {
         }
     }
 
-    /// Jumps to the next search result (respects search direction).
-    /// Returns (success, wrapped) where wrapped indicates if the search wrapped around.
-    pub fn next_search_result(&mut self) -> (bool, bool) {
-        if self.search_results.is_empty() {
-            return (false, false);
-        }
+    /// Returns the paths of every node matching a YAMLPath query.
+    fn query_paths(&self, query: &str) -> anyhow::Result<Vec<Vec<usize>>> {
+        use crate::yamlpath::{Evaluator, Parser};
 
-        let wrapped;
-        if self.search_forward {
-            let old_index = self.search_index;
-            self.search_index = (self.search_index + 1) % self.search_results.len();
-            wrapped = self.search_index < old_index; // Wrapped if new index is less than old
-        } else {
-            wrapped = self.search_index == 0; // Wrapped if we're going back from 0
-            self.search_index = if self.search_index == 0 {
-                self.search_results.len() - 1
-            } else {
-                self.search_index - 1
-            };
-        }
-        self.cursor
-            .set_path(self.search_results[self.search_index].clone());
-        (true, wrapped)
+        let path = Parser::parse(query).map_err(|e| anyhow::anyhow!("Invalid YAMLPath: {}", e))?;
+        Ok(Evaluator::new(self.tree.root()).evaluate_paths(&path.segments))
     }
 
-    /// Returns the current search results info.
-    pub fn search_results_info(&self) -> Option<(usize, usize)> {
-        if self.search_results.is_empty() {
-            None
-        } else {
-            Some((self.search_index + 1, self.search_results.len()))
+    /// Sets every scalar matching a YAMLPath to `value` in one undoable edit (`:path-set`).
+    ///
+    /// The value is typed the same way as `:set`-style input (`true`, `3`,
+    /// `null`); wrap it in quotes to force a string. Strings keep their block
+    /// style. Mappings and sequences that match are left alone. Returns the
+    /// number of nodes changed.
+    pub fn path_set(&mut self, query: &str, value: &str) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        self.load_all_deferred()?;
+        use crate::document::node::YamlValue;
+
+        let paths = self.query_paths(query)?;
+        let unquoted = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')));
+        let new_value = match unquoted {
+            Some(text) => YamlValue::String(YamlString::Plain(text.to_string())),
+            None => parse_scalar_value(value),
+        };
+
+        let mut changed = 0;
+        for path in &paths {
+            let Some(node) = self.tree.get_node_mut(path) else {
+                continue;
+            };
+            let value = match (node.value(), &new_value) {
+                (
+                    YamlValue::Object(_)
+                    | YamlValue::Array(_)
+                    | YamlValue::MultiDoc(_)
+                    | YamlValue::Comment(_),
+                    _,
+                ) => continue,
+                (YamlValue::String(YamlString::Literal(_)), YamlValue::String(s)) => {
+                    YamlValue::String(YamlString::Literal(s.as_str().to_string()))
+                }
+                (YamlValue::String(YamlString::Folded(_)), YamlValue::String(s)) => {
+                    YamlValue::String(YamlString::Folded(s.as_str().to_string()))
+                }
+                _ => new_value.clone(),
+            };
+            *node.value_mut() = value;
+            changed += 1;
         }
-    }
 
-    /// Returns the current search type, if any.
-    pub fn search_type(&self) -> Option<&SearchType> {
-        self.search_type.as_ref()
+        if changed > 0 {
+            self.mark_dirty();
+            self.rebuild_tree_view();
+            self.checkpoint();
+        }
+        Ok(changed)
     }
 
-    /// Clears search results but preserves search buffer and type.
-    /// This removes search info from the status bar while keeping
-    /// the search query available for potential "repeat search" features.
-    pub fn clear_search_results(&mut self) {
-        self.search_results.clear();
-        self.search_index = 0;
-    }
+    /// Computes `aggregate` over the numbers in `query`'s matches, or else the
+    /// visual selection, or else the matches of the last `:path` search.
+    ///
+    /// The result is yanked (unnamed register and `"0`) so `p` can paste it.
+    /// Returns it with how many numbers went into it.
+    pub fn aggregate(
+        &mut self,
+        aggregate: super::aggregate::Aggregate,
+        query: Option<&str>,
+    ) -> anyhow::Result<(YamlNumber, usize)> {
+        use super::aggregate::Aggregate;
+        use crate::editor::registers::RegisterContent;
 
-    /// Returns whether line numbers should be shown.
-    pub fn show_line_numbers(&self) -> bool {
-        self.show_line_numbers
-    }
+        let paths = if let Some(query) = query {
+            self.query_paths(query)?
+        } else if !self.visual_selection.is_empty() {
+            self.visual_selection.clone()
+        } else if let Some(SearchType::YamlPath(last)) = &self.search_type {
+            // Matches are cleared by the next key, so run the query again
+            self.query_paths(&last.clone())?
+        } else {
+            anyhow::bail!(
+                "Nothing to {}: select nodes or give a YAMLPath",
+                aggregate.name()
+            );
+        };
 
-    /// Sets whether line numbers should be shown.
-    pub fn set_show_line_numbers(&mut self, show: bool) {
-        self.show_line_numbers = show;
-    }
+        let numbers = Aggregate::numbers(paths.iter().filter_map(|p| self.tree.get_node(p)));
+        let result = aggregate.apply(&numbers)?;
 
-    /// Returns whether relative line numbers should be shown.
-    pub fn relative_line_numbers(&self) -> bool {
-        self.relative_line_numbers
-    }
+        let content = RegisterContent::new(
+            vec![YamlNode::new(YamlValue::Number(result.clone()))],
+            vec![None],
+        );
+        self.registers.set_unnamed(content.clone());
+        self.registers.update_yank_register(content);
+        Ok((result, numbers.len()))
+    }
+
+    /// Returns every node matching a YAMLPath as its bracket path and YAML text.
+    pub fn query(&self, query: &str) -> anyhow::Result<Vec<(String, String)>> {
+        self.query_paths(query)?
+            .into_iter()
+            .filter_map(|path| {
+                let node = self.tree.get_node(&path)?;
+                let text = crate::file::saver::serialize_subtree(node, &self.tree);
+                Some(text.map(|text| {
+                    (
+                        self.path_to_string(&path, "bracket").unwrap_or_default(),
+                        text,
+                    )
+                }))
+            })
+            .collect()
+    }
+
+    /// Toggles the mapping or sequence at the cursor between block and flow
+    /// style (`gf`), returning the new style.
+    ///
+    /// Flow style can't hold comments, so a node containing comments stays
+    /// in block style.
+    pub fn toggle_flow_style_at_cursor(
+        &mut self,
+    ) -> anyhow::Result<crate::document::node::CollectionStyle> {
+        self.ensure_writable()?;
+        use crate::document::node::CollectionStyle;
 
-    /// Sets whether relative line numbers should be shown.
-    pub fn set_relative_line_numbers(&mut self, show: bool) {
-        self.relative_line_numbers = show;
+        fn has_comments(node: &YamlNode) -> bool {
+            match node.value() {
+                YamlValue::Comment(_) => true,
+                YamlValue::Object(entries) => entries.values().any(has_comments),
+                YamlValue::Array(items) => items.iter().any(has_comments),
+                _ => false,
+            }
+        }
+
+        let path = self.cursor.path().to_vec();
+        let node = self
+            .tree
+            .get_node(&path)
+            .ok_or_else(|| anyhow::anyhow!("No node at cursor"))?;
+        if !matches!(node.value(), YamlValue::Object(_) | YamlValue::Array(_)) {
+            anyhow::bail!("Not a mapping or sequence");
+        }
+        let style = match node.style() {
+            CollectionStyle::Block => {
+                if has_comments(node) {
+                    anyhow::bail!("Flow style can't hold comments; remove them first");
+                }
+                CollectionStyle::Flow
+            }
+            CollectionStyle::Flow => CollectionStyle::Block,
+        };
+
+        if let Some(node) = self.tree.get_node_mut(&path) {
+            node.set_style(style);
+        }
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        Ok(style)
     }
 
-    /// Returns whether mouse support is enabled.
-    pub fn enable_mouse(&self) -> bool {
-        self.enable_mouse
+    /// Coerces the scalar at the cursor to `target` (`gt`, `:type`), e.g. the
+    /// string `"80"` to the int `80`. Fails if the value doesn't fit the type.
+    pub fn change_type_at_cursor(&mut self, target: ScalarType) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        self.ensure_not_merged()?;
+        let path = self.cursor.path().to_vec();
+        let node = self
+            .tree
+            .get_node(&path)
+            .ok_or_else(|| anyhow::anyhow!("No node at cursor"))?;
+        if ScalarType::of(node.value()) == Some(target) {
+            anyhow::bail!("Already {}", target.name());
+        }
+        let value = coerce(node.value(), target)?;
+        if let Some(node) = self.tree.get_node_mut(&path) {
+            *node.value_mut() = value;
+        }
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        Ok(())
     }
 
-    /// Sets whether mouse support is enabled.
-    pub fn set_enable_mouse(&mut self, enable: bool) {
-        self.enable_mouse = enable;
+    /// Joins the items of the list at the cursor, or holding the cursor,
+    /// into one string separated by `separator` (`gJ`, `:join`).
+    ///
+    /// Only lists of scalars can be joined. The edit is one undo step.
+    /// Returns the number of items joined.
+    pub fn join_list_at_cursor(&mut self, separator: &str) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        use anyhow::bail;
+
+        self.ensure_not_merged()?;
+        let cursor = self.cursor.path().to_vec();
+        let path = match self.tree.get_node(&cursor).map(|n| n.value()) {
+            Some(YamlValue::Array(_)) => cursor,
+            _ => match cursor.split_last() {
+                Some((_, parent))
+                    if matches!(
+                        self.tree.get_node(parent).map(|n| n.value()),
+                        Some(YamlValue::Array(_))
+                    ) =>
+                {
+                    parent.to_vec()
+                }
+                _ => bail!("Not on a list"),
+            },
+        };
+        let Some(YamlValue::Array(items)) = self.tree.get_node(&path).map(|n| n.value()) else {
+            bail!("Not on a list");
+        };
+        let mut parts = Vec::with_capacity(items.len());
+        for item in items {
+            parts.push(match item.value() {
+                YamlValue::String(text) => text.as_str().to_string(),
+                YamlValue::Number(number) => number.to_string(),
+                YamlValue::Boolean(b) => b.to_string(),
+                YamlValue::Null => "null".to_string(),
+                _ => bail!("Only lists of scalars can be joined"),
+            });
+        }
+
+        let joined = parts.join(separator);
+        if let Some(node) = self.tree.get_node_mut(&path) {
+            *node.value_mut() = YamlValue::String(YamlString::Plain(joined));
+        }
+        self.cursor.set_path(path);
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        Ok(parts.len())
     }
 
-    /// Returns whether backup files should be created before saving.
-    pub fn create_backup(&self) -> bool {
-        self.create_backup
+    /// Splits the string at the cursor into a list of strings at each
+    /// `delimiter`, or at whitespace if it is empty (`gS`, `:split-string`).
+    ///
+    /// Pieces are trimmed and empty ones dropped, so `a, b,c` split at `,`
+    /// gives three items. The edit is one undo step. Returns the number of
+    /// items.
+    pub fn split_string_at_cursor(&mut self, delimiter: &str) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+
+        self.ensure_not_merged()?;
+        let path = self.cursor.path().to_vec();
+        let Some(YamlValue::String(text)) = self.tree.get_node(&path).map(|n| n.value()) else {
+            anyhow::bail!("Not on a string");
+        };
+        let pieces: Vec<&str> = if delimiter.is_empty() {
+            text.as_str().split_whitespace().collect()
+        } else {
+            text.as_str().split(delimiter).map(str::trim).collect()
+        };
+        let items: Vec<YamlNode> = pieces
+            .into_iter()
+            .filter(|piece| !piece.is_empty())
+            .map(|piece| YamlNode::new(YamlValue::String(YamlString::Plain(piece.to_string()))))
+            .collect();
+        let count = items.len();
+
+        if let Some(node) = self.tree.get_node_mut(&path) {
+            *node.value_mut() = YamlValue::Array(items);
+        }
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        if count > 0 {
+            self.reveal_path([path.as_slice(), &[0]].concat());
+            self.cursor.set_path(path);
+        }
+        self.checkpoint();
+        Ok(count)
     }
 
-    /// Sets whether backup files should be created before saving.
-    pub fn set_create_backup(&mut self, enable: bool) {
-        self.create_backup = enable;
+    /// Increments the semantic version at the cursor (`:bump`) and returns
+    /// the new version.
+    ///
+    /// See [`super::semver::Version::bump`]; a `v` prefix is kept.
+    pub fn bump_version_at_cursor(&mut self, part: super::semver::Part) -> anyhow::Result<String> {
+        self.ensure_writable()?;
+        use super::semver::{looks_like_version, Version};
+
+        self.ensure_not_merged()?;
+        let path = self.cursor.path().to_vec();
+        let text = match self.tree.get_node(&path).map(|n| n.value()) {
+            Some(YamlValue::String(text)) if looks_like_version(text.as_str().trim()) => {
+                text.as_str().trim()
+            }
+            _ => anyhow::bail!("Not a semantic version"),
+        };
+        let version = Version::parse(text)
+            .map_err(|e| anyhow::anyhow!("Not a semantic version: {}", e))?
+            .bump(part)
+            .to_string();
+        if let Some(node) = self.tree.get_node_mut(&path) {
+            *node.value_mut() = YamlValue::String(YamlString::Plain(version.clone()));
+        }
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        Ok(version)
+    }
+
+    /// Shifts (`+1d`, `-2h30m`) or re-formats (`iso`, `utc`, `date`,
+    /// `epoch`) the timestamp at the cursor (`:time`) and returns the new
+    /// value.
+    ///
+    /// An integer counts as Unix seconds, and stays one when shifted.
+    pub fn change_time_at_cursor(&mut self, change: &str) -> anyhow::Result<String> {
+        self.ensure_writable()?;
+        use super::datetime::{looks_like_timestamp, DateTime, Format};
+
+        self.ensure_not_merged()?;
+        let (time, epoch) = match self.tree.get_node(self.cursor.path()).map(|n| n.value()) {
+            Some(YamlValue::String(text)) if looks_like_timestamp(text.as_str()) => (
+                DateTime::parse(text.as_str())
+                    .map_err(|e| anyhow::anyhow!("Not a timestamp: {}", e))?,
+                false,
+            ),
+            Some(YamlValue::Number(YamlNumber::Integer(seconds))) => {
+                (DateTime::from_unix(*seconds), true)
+            }
+            _ => anyhow::bail!("Not a timestamp"),
+        };
+        let change = change.trim();
+        let (time, format) = if change.starts_with(['+', '-']) {
+            let time = time
+                .shift(change)
+                .map_err(|e| anyhow::anyhow!("Invalid shift: {}", e))?;
+            (time, epoch.then_some(Format::Epoch))
+        } else {
+            let format = Format::parse(change).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown format '{}' (use iso, utc, date or epoch, or a shift like +1d)",
+                    change
+                )
+            })?;
+            (time, Some(format))
+        };
+        self.set_time_at_cursor(time, format)
     }
 
-    /// Returns a Config object with the current editor settings.
-    pub fn to_config(&self) -> crate::config::Config {
-        use crate::config::Config;
+    /// Sets the scalar at the cursor to the current time (`:now`), in UTC
+    /// unless another format is given, and returns the new value.
+    pub fn set_now_at_cursor(&mut self, format: super::datetime::Format) -> anyhow::Result<String> {
+        self.ensure_writable()?;
+        use super::datetime::DateTime;
+        use std::time::{SystemTime, UNIX_EPOCH};
 
-        Config {
-            theme: self.current_theme.clone(),
-            show_line_numbers: self.show_line_numbers,
-            relative_line_numbers: self.relative_line_numbers,
-            enable_mouse: self.enable_mouse,
-            create_backup: self.create_backup,
-            ..Config::default()
+        self.ensure_not_merged()?;
+        match self.tree.get_node(self.cursor.path()).map(|n| n.value()) {
+            Some(YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::MultiDoc(_)) | None => {
+                anyhow::bail!("Not a scalar (move to a value to set the time)")
+            }
+            _ => {}
         }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        self.set_time_at_cursor(DateTime::from_unix(now), Some(format))
     }
 
-    /// Saves current settings to the config file.
-    pub fn save_config(&self) -> anyhow::Result<()> {
-        self.to_config().save()
+    /// Writes a timestamp to the node at the cursor, re-formatted if asked.
+    fn set_time_at_cursor(
+        &mut self,
+        time: super::datetime::DateTime,
+        format: Option<super::datetime::Format>,
+    ) -> anyhow::Result<String> {
+        use super::datetime::{DateTime, Format};
+
+        let value = match format {
+            None => YamlValue::String(YamlString::Plain(time.to_string())),
+            Some(Format::Iso) => YamlValue::String(YamlString::Plain(
+                DateTime {
+                    date_only: false,
+                    ..time
+                }
+                .to_string(),
+            )),
+            Some(Format::Utc) => YamlValue::String(YamlString::Plain(time.to_utc().to_string())),
+            Some(Format::Date) => YamlValue::String(YamlString::Plain(time.to_date().to_string())),
+            Some(Format::Epoch) => YamlValue::Number(YamlNumber::Integer(time.unix())),
+        };
+        let text = value.to_string();
+        let path = self.cursor.path().to_vec();
+        if let Some(node) = self.tree.get_node_mut(&path) {
+            *node.value_mut() = value;
+        }
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        Ok(text)
     }
 
-    /// Returns the current edit buffer content, if editing.
-    pub fn edit_buffer(&self) -> Option<&str> {
-        self.edit_buffer.as_deref()
+    /// Replaces the string at the cursor with its base64 encoding
+    /// (`:b64 encode`).
+    pub fn encode_base64_at_cursor(&mut self) -> anyhow::Result<()> {
+        let text = self.string_at_cursor()?;
+        let encoded = super::base64::encode(text.as_bytes());
+        self.set_string_at_cursor(YamlString::Plain(encoded));
+        Ok(())
     }
 
-    /// Starts editing the node at the current cursor position.
-    /// Starts with an empty buffer for typing a new value.
-    pub fn start_editing(&mut self) {
-        let path = self.cursor.path();
-        if let Some(node) = self.tree.get_node(path) {
-            // Check if node is an alias - aliases are read-only
-            if let crate::document::node::YamlValue::Alias(name) = node.value() {
-                let anchor_name = node.alias_target().unwrap_or(name);
-                self.set_message(
-                    format!(
-                        "Cannot edit alias - navigate to anchor definition (&{}) to edit",
-                        anchor_name
-                    ),
-                    MessageLevel::Error,
-                );
-                return;
-            }
+    /// Replaces the base64 string at the cursor with the text it encodes
+    /// (`:b64 decode`). Text of several lines becomes a literal block.
+    ///
+    /// Returns an error if the string isn't base64 or decodes to binary data,
+    /// which a YAML string can't hold.
+    pub fn decode_base64_at_cursor(&mut self) -> anyhow::Result<()> {
+        let text = self.string_at_cursor()?;
+        super::base64::decode(&text).map_err(|e| anyhow::anyhow!("Not base64: {}", e))?;
+        let decoded = super::base64::decode_text(&text)
+            .ok_or_else(|| anyhow::anyhow!("Decodes to binary data, not text"))?;
+        let value = if decoded.contains('\n') {
+            YamlString::Literal(decoded)
+        } else {
+            YamlString::Plain(decoded)
+        };
+        self.set_string_at_cursor(value);
+        Ok(())
+    }
 
-            // Check if node is editable (not a container)
-            match node.value() {
-                crate::document::node::YamlValue::Object(_)
-                | crate::document::node::YamlValue::Array(_)
-                | crate::document::node::YamlValue::MultiDoc(_) => {
-                    // Can't edit containers
-                }
-                crate::document::node::YamlValue::String(s) => {
-                    // Pre-populate with current string value (without JSON quotes)
-                    let content = s.as_str().to_string();
-                    self.edit_cursor = content.len();
-                    self.edit_buffer = Some(content);
-                    self.reset_cursor_blink();
-                }
-                crate::document::node::YamlValue::Number(n) => {
-                    // Pre-populate with current number value
-                    let num_str = match n {
-                        YamlNumber::Integer(i) => i.to_string(),
-                        YamlNumber::Float(f) => {
-                            if f.fract() == 0.0 && f.is_finite() {
-                                format!("{:.0}", f)
-                            } else {
-                                f.to_string()
-                            }
-                        }
-                    };
-                    self.edit_cursor = num_str.len();
-                    self.edit_buffer = Some(num_str);
-                    self.reset_cursor_blink();
-                }
-                crate::document::node::YamlValue::Boolean(b) => {
-                    // Pre-populate with current boolean value
-                    let content = b.to_string();
-                    self.edit_cursor = content.len();
-                    self.edit_buffer = Some(content);
-                    self.reset_cursor_blink();
-                }
-                crate::document::node::YamlValue::Null => {
-                    // Pre-populate with "null"
-                    self.edit_cursor = 4; // "null".len()
-                    self.edit_buffer = Some("null".to_string());
-                    self.reset_cursor_blink();
-                }
-                crate::document::node::YamlValue::Alias(_) => {
-                    // Unreachable - handled above with error message
-                    unreachable!("Alias editing should be blocked earlier");
-                }
-                crate::document::node::YamlValue::Comment(c) => {
-                    // Pre-populate with current comment text
-                    let content = c.content().to_string();
-                    self.edit_cursor = content.len();
-                    self.edit_buffer = Some(content);
-                    self.reset_cursor_blink();
-                }
-            }
+    /// Decodes the string at the cursor if it is base64 of some text,
+    /// otherwise encodes it (`gb`). Returns true if it was decoded.
+    pub fn toggle_base64_at_cursor(&mut self) -> anyhow::Result<bool> {
+        let text = self.string_at_cursor()?;
+        if super::base64::decode_text(&text).is_some() {
+            self.decode_base64_at_cursor()?;
+            Ok(true)
+        } else {
+            self.encode_base64_at_cursor()?;
+            Ok(false)
         }
     }
 
-    /// Validate that the edit buffer content is valid for the given value type.
-    /// Returns Ok(()) if valid, Err with message if invalid.
-    fn validate_edit_input(buffer: &str, original_value: &YamlValue) -> anyhow::Result<()> {
-        use anyhow::anyhow;
+    /// Returns the string at the cursor, if it can be changed.
+    fn string_at_cursor(&self) -> anyhow::Result<String> {
+        self.ensure_writable()?;
+        self.ensure_not_merged()?;
+        match self.tree.get_node(self.cursor.path()).map(|n| n.value()) {
+            Some(YamlValue::String(s)) => Ok(s.as_str().to_string()),
+            _ => anyhow::bail!("Not a string (move to a string value)"),
+        }
+    }
 
-        // Special case: "null" is always valid (converts any type to Null)
-        if buffer == "null" {
-            return Ok(());
+    /// Writes a string to the node at the cursor as one undo step.
+    fn set_string_at_cursor(&mut self, value: YamlString) {
+        let path = self.cursor.path().to_vec();
+        if let Some(node) = self.tree.get_node_mut(&path) {
+            *node.value_mut() = YamlValue::String(value);
         }
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+    }
 
-        match original_value {
-            YamlValue::Number(_) => {
-                // Try parsing as integer or float
-                if buffer.parse::<i64>().is_err() && buffer.parse::<f64>().is_err() {
-                    return Err(anyhow!("Invalid number format: '{}'", buffer));
-                }
-            }
-            YamlValue::Boolean(_) => {
-                // Must be exactly "true" or "false"
-                if !matches!(buffer, "true" | "false") {
-                    return Err(anyhow!(
-                        "Invalid boolean: '{}' (must be 'true' or 'false')",
-                        buffer
-                    ));
-                }
-            }
-            YamlValue::Alias(_) => {
-                // Must start with * and have at least one character after
-                if !buffer.starts_with('*') || buffer.len() < 2 {
-                    return Err(anyhow!("Alias must be in format '*name'"));
-                }
+    /// Sets or removes (`None`) the explicit YAML tag on the node at the
+    /// cursor (`:tag`).
+    ///
+    /// Tags are written as in the file: `!Ref`, `!!timestamp`,
+    /// `!<tag:example.com,2024:thing>`. Returns an error if the tag doesn't
+    /// start with `!` or contains whitespace.
+    pub fn set_tag_at_cursor(&mut self, tag: Option<String>) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        self.ensure_not_merged()?;
+        if let Some(tag) = &tag {
+            if !tag.starts_with('!') || tag.len() < 2 || tag.contains(char::is_whitespace) {
+                anyhow::bail!("Invalid tag '{}' (tags look like !Ref or !!str)", tag);
             }
-            // Strings, Null, and Comments accept any input
-            YamlValue::String(_) | YamlValue::Null | YamlValue::Comment(_) => {}
-            // Containers shouldn't be editable
-            YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
-                return Err(anyhow!("Cannot edit container types"));
+        }
+        let path = self.cursor.path().to_vec();
+        let node = self
+            .tree
+            .get_node_mut(&path)
+            .ok_or_else(|| anyhow::anyhow!("No node at cursor"))?;
+        if node.tag() == tag.as_deref() {
+            match tag {
+                Some(tag) => anyhow::bail!("Already tagged {}", tag),
+                None => anyhow::bail!("No tag to remove"),
             }
         }
-
+        node.set_tag(tag);
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
         Ok(())
     }
 
-    /// Cancels editing and clears the edit buffer without saving changes.
-    pub fn cancel_editing(&mut self) {
-        self.edit_buffer = None;
-        self.edit_cursor = 0;
-    }
-
-    /// Commits the edited value from the buffer to the tree.
-    /// Parses the buffer according to the original node's type and updates the tree.
-    /// Returns an error if the buffer content is invalid for the node's type.
-    pub fn commit_editing(&mut self) -> anyhow::Result<()> {
-        use crate::document::node::YamlValue;
-        use anyhow::{anyhow, Context};
+    /// Sorts the keys of the mapping at the cursor if it is expanded,
+    /// otherwise of the mapping containing the cursor (`:sort`, `gs`).
+    ///
+    /// `reverse` sorts Z to A and `recursive` also sorts every mapping below
+    /// it. The cursor and expanded nodes follow their keys. Returns the number
+    /// of mappings whose order changed; nothing is recorded for undo if none did.
+    ///
+    /// Returns an error if the cursor is not on or in a mapping.
+    pub fn sort_keys_at_cursor(&mut self, reverse: bool, recursive: bool) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        let cursor = self.cursor.path().to_vec();
+        let is_mapping = |path: &[usize]| {
+            matches!(
+                self.tree.get_node(path).map(|node| node.value()),
+                Some(YamlValue::Object(_))
+            )
+        };
+        // Like paste, an expanded mapping means "in here", a collapsed one a sibling
+        let target = if is_mapping(&cursor) && self.tree_view.is_expanded(&cursor) {
+            cursor.clone()
+        } else if !cursor.is_empty() && is_mapping(&cursor[..cursor.len() - 1]) {
+            cursor[..cursor.len() - 1].to_vec()
+        } else {
+            anyhow::bail!("Not in a mapping");
+        };
 
-        let buffer_content = self
-            .edit_buffer
-            .as_ref()
-            .ok_or_else(|| anyhow!("No active edit buffer"))?
-            .clone();
+        let cursor_node = self.tree.get_node(&cursor).map(YamlNode::id);
 
-        let path = self.cursor.path();
-        let node = self
+        let sorted = self
             .tree
-            .get_node(path)
-            .ok_or_else(|| anyhow!("Node not found at cursor"))?;
-
-        // Validate input before attempting to parse
-        Self::validate_edit_input(&buffer_content, node.value())?;
+            .get_node_mut(&target)
+            .map(|node| node.sort_keys(reverse, recursive))
+            .unwrap_or(0);
+        if sorted == 0 {
+            self.set_message("Already sorted".to_string(), MessageLevel::Info);
+            return Ok(0);
+        }
 
-        // Special case: "null" always converts to Null regardless of original type
-        let new_value = if buffer_content == "null" {
-            YamlValue::Null
+        // Folds and marks follow their nodes; the cursor does too
+        if let Some(path) = cursor_node.and_then(|id| self.tree.path_of(id)) {
+            self.cursor.set_path(path);
+        }
+        self.clear_search_results();
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        let message = if sorted == 1 {
+            "Sorted keys".to_string()
         } else {
-            // Otherwise, determine the new value based on the original node's type
-            match node.value() {
-                YamlValue::String(original_style) => {
-                    // Preserve the original string style (Plain, Literal, or Folded)
-                    let new_string = match original_style {
-                        YamlString::Plain(_) => YamlString::Plain(buffer_content),
-                        YamlString::Literal(_) => YamlString::Literal(buffer_content),
-                        YamlString::Folded(_) => YamlString::Folded(buffer_content),
-                    };
-                    YamlValue::String(new_string)
-                }
-                YamlValue::Number(_) => {
-                    // Try integer first, then float
-                    if let Ok(i) = buffer_content.parse::<i64>() {
-                        YamlValue::Number(YamlNumber::Integer(i))
-                    } else {
-                        let num = buffer_content
-                            .parse::<f64>()
-                            .context("Invalid number format")?;
-                        YamlValue::Number(YamlNumber::Float(num))
-                    }
-                }
-                YamlValue::Boolean(_) => {
-                    let bool_val = match buffer_content.as_str() {
-                        "true" => true,
-                        "false" => false,
-                        _ => return Err(anyhow!("Boolean value must be true or false")),
-                    };
-                    YamlValue::Boolean(bool_val)
-                }
-                YamlValue::Null => {
-                    // This shouldn't happen since we checked for "null" above
-                    YamlValue::Null
-                }
-                YamlValue::Alias(_) => {
-                    // Parse alias reference (should start with *)
-                    if let Some(stripped) = buffer_content.strip_prefix('*') {
-                        YamlValue::Alias(stripped.to_string())
-                    } else {
-                        return Err(anyhow!("Alias must start with *"));
+            format!("Sorted {} mappings", sorted)
+        };
+        self.set_message(message, MessageLevel::Info);
+        Ok(sorted)
+    }
+
+    /// Deletes every node matching a YAMLPath in one undoable edit (`:path-delete`).
+    ///
+    /// Returns the number of nodes deleted. The root is never deleted.
+    pub fn path_delete(&mut self, query: &str) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        self.load_all_deferred()?;
+        use crate::document::ops::{apply_all, Op};
+
+        let mut paths = self.query_paths(query)?;
+        paths.retain(|path| !path.is_empty());
+        // Deepest and last first, so earlier paths stay valid
+        paths.sort_by(|a, b| b.cmp(a));
+        paths.dedup();
+
+        let ops: Vec<Op> = paths.into_iter().map(|path| Op::Delete { path }).collect();
+        apply_all(&mut self.tree, &ops)?;
+        let deleted = ops.len();
+
+        if deleted > 0 {
+            self.clear_search_results();
+            // Move the cursor up to the nearest node that still exists
+            let mut cursor = self.cursor.path().to_vec();
+            while !cursor.is_empty() && self.tree.get_node(&cursor).is_none() {
+                match cursor.last_mut() {
+                    Some(last) if *last > 0 => *last -= 1,
+                    _ => {
+                        cursor.pop();
                     }
                 }
-                YamlValue::Comment(c) => {
-                    // Update comment content while preserving position and source line
-                    let new_comment = if let Some(line) = c.source_line() {
-                        crate::document::node::CommentNode::from_source(
-                            buffer_content,
-                            c.position().clone(),
-                            line,
-                        )
-                    } else {
-                        crate::document::node::CommentNode::new(
-                            buffer_content,
-                            c.position().clone(),
-                        )
-                    };
-                    YamlValue::Comment(new_comment)
-                }
-                YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
-                    return Err(anyhow!("Cannot edit container types"));
-                }
             }
-        };
+            self.cursor.set_path(cursor);
+            self.mark_dirty();
+            self.rebuild_tree_view();
+            self.checkpoint();
+        }
+        Ok(deleted)
+    }
 
-        // Update the node in the tree
-        let node_mut = self
-            .tree
-            .get_node_mut(path)
-            .ok_or_else(|| anyhow!("Node not found for update"))?;
-        *node_mut.value_mut() = new_value;
+    /// Runs a `:%s/pattern/replacement/flags` command over the whole document.
+    ///
+    /// All replacements are one undo step, and the cursor moves to the first
+    /// changed node. A key rename that would clash with an existing key is
+    /// skipped. With the `n` flag only the number of matches is reported.
+    pub fn substitute(&mut self, command: &str) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        use super::substitute::{Change, Substitution};
+        use crate::document::node::YamlValue;
 
-        // Clear edit buffer and mark dirty
-        self.edit_buffer = None;
+        let sub = Substitution::parse(command)?;
+        self.load_all_deferred()?;
+        let (changes, count) = sub.plan(self.tree.root())?;
+        if changes.is_empty() {
+            anyhow::bail!("Pattern not found: {}", sub.pattern);
+        }
+        let counted =
+            |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+        let nodes = |n: usize| counted(n, "node", "nodes");
+        if sub.count_only {
+            self.set_message(
+                format!(
+                    "{} on {}",
+                    counted(count, "match", "matches"),
+                    nodes(changes.len())
+                ),
+                MessageLevel::Info,
+            );
+            return Ok(());
+        }
+
+        let mut skipped = 0;
+        let mut first = None;
+        for change in &changes {
+            let applied = match change {
+                Change::Value { path, text } => match self.tree.get_node_mut(path) {
+                    Some(node) => match node.value_mut() {
+                        YamlValue::String(s) => {
+                            *s = match s {
+                                YamlString::Plain(_) => YamlString::Plain(text.clone()),
+                                YamlString::Literal(_) => YamlString::Literal(text.clone()),
+                                YamlString::Folded(_) => YamlString::Folded(text.clone()),
+                            };
+                            true
+                        }
+                        _ => false,
+                    },
+                    None => false,
+                },
+                Change::Key { path, key } => self.rename_key_at(path, key),
+            };
+            if applied {
+                first.get_or_insert_with(|| change.path().to_vec());
+            } else {
+                skipped += 1;
+            }
+        }
+
+        let Some(first) = first else {
+            anyhow::bail!("No substitutions made: the new keys already exist");
+        };
         self.mark_dirty();
+        self.record_jump();
+        self.cursor.set_path(first);
         self.rebuild_tree_view();
-
         self.checkpoint();
-        Ok(())
-    }
 
-    /// Inserts a character at the current cursor position in the edit buffer.
-    pub fn push_to_edit_buffer(&mut self, ch: char) {
-        if let Some(ref mut buffer) = self.edit_buffer {
-            buffer.insert(self.edit_cursor, ch);
-            self.edit_cursor += ch.len_utf8(); // Advance by byte length, not 1
-            self.reset_cursor_blink();
+        let mut message = format!(
+            "{} on {}",
+            counted(count, "substitution", "substitutions"),
+            nodes(changes.len() - skipped)
+        );
+        if skipped > 0 {
+            message.push_str(&format!(" ({} skipped: key already exists)", skipped));
         }
+        self.set_message(message, MessageLevel::Info);
+        Ok(())
     }
 
-    /// Removes the character before the cursor (backspace).
-    pub fn pop_from_edit_buffer(&mut self) {
-        if let Some(ref mut buffer) = self.edit_buffer {
-            if self.edit_cursor > 0 {
-                // Find the start of the character before cursor (handles multi-byte UTF-8)
-                let char_start = buffer[..self.edit_cursor]
-                    .char_indices()
-                    .next_back()
-                    .map(|(i, _)| i)
-                    .unwrap_or(0);
-                buffer.remove(char_start);
-                self.edit_cursor = char_start;
-                self.reset_cursor_blink();
-            }
-        }
+    /// Renames the key of the mapping entry at `path` without a checkpoint.
+    ///
+    /// Returns false if the parent is not a mapping or `key` is already used.
+    fn rename_key_at(&mut self, path: &[usize], key: &str) -> bool {
+        use crate::document::ops::Op;
+
+        let rename = Op::RenameKey {
+            path: path.to_vec(),
+            key: key.to_string(),
+        };
+        rename.apply(&mut self.tree).is_ok()
     }
 
-    /// Clears the edit buffer entirely and resets cursor.
-    pub fn clear_edit_buffer(&mut self) {
-        if let Some(ref mut buffer) = self.edit_buffer {
-            buffer.clear();
-            self.edit_cursor = 0;
-            self.reset_cursor_blink();
-        }
+    /// Returns the query REPL.
+    pub fn repl(&self) -> &super::repl::ReplState {
+        &self.repl
     }
 
-    /// Sets the edit buffer directly (test-only).
+    /// Returns the query REPL for editing its input.
     ///
-    /// **Warning:** This is only for testing purposes. Do not use in production code.
-    #[doc(hidden)]
-    pub fn set_edit_buffer_for_test(&mut self, content: String) {
-        self.edit_cursor = content.len();
-        self.edit_buffer = Some(content);
+    /// Call [`EditorState::evaluate_repl`] after changing the input.
+    pub fn repl_mut(&mut self) -> &mut super::repl::ReplState {
+        &mut self.repl
     }
 
-    /// Moves the edit cursor left by one character.
-    pub fn edit_cursor_left(&mut self) {
-        if let Some(ref buffer) = self.edit_buffer {
-            if self.edit_cursor > 0 {
-                // Find the start of the previous character (handles multi-byte UTF-8)
-                let char_start = buffer[..self.edit_cursor]
-                    .char_indices()
-                    .next_back()
-                    .map(|(i, _)| i)
-                    .unwrap_or(0);
-                self.edit_cursor = char_start;
-                self.reset_cursor_blink();
-            }
-        }
+    /// Opens the query REPL pane and gives it focus.
+    pub fn open_repl(&mut self) {
+        self.mode = EditorMode::Repl;
+        self.evaluate_repl();
     }
 
-    /// Moves the edit cursor right by one character.
-    pub fn edit_cursor_right(&mut self) {
-        if let Some(ref buffer) = self.edit_buffer {
-            if self.edit_cursor < buffer.len() {
-                // Find the start of the next character (handles multi-byte UTF-8)
-                if let Some((next_pos, _)) = buffer[self.edit_cursor..].char_indices().nth(1) {
-                    self.edit_cursor += next_pos;
-                } else {
-                    // No next character, move to end
-                    self.edit_cursor = buffer.len();
-                }
-                self.reset_cursor_blink();
-            }
-        }
+    /// Closes the query REPL pane, keeping its history.
+    pub fn close_repl(&mut self) {
+        self.mode = EditorMode::Normal;
     }
 
-    /// Moves the edit cursor to the beginning of the buffer (Ctrl-a).
-    pub fn edit_cursor_home(&mut self) {
-        self.edit_cursor = 0;
-        self.reset_cursor_blink();
+    /// Evaluates the REPL expression against the current buffer.
+    ///
+    /// Results are listed in the REPL pane only; the cursor does not move.
+    pub fn evaluate_repl(&mut self) {
+        use crate::yamlpath::{Evaluator, Parser};
+
+        let query = self.repl.input().trim();
+        let results = if query.is_empty() {
+            Ok(Vec::new())
+        } else {
+            Parser::parse(query)
+                .map(|path| Evaluator::new(self.tree.root()).evaluate_paths(&path.segments))
+                .map_err(|e| e.to_string())
+        };
+        self.repl.set_results(results);
     }
 
-    /// Moves the edit cursor to the end of the buffer (Ctrl-e).
-    pub fn edit_cursor_end(&mut self) {
-        if let Some(ref buffer) = self.edit_buffer {
-            self.edit_cursor = buffer.len();
-            self.reset_cursor_blink();
+    /// Makes the REPL results the main view's search results and closes the pane.
+    ///
+    /// The cursor jumps to the first result; `n`/`N` step through the rest.
+    /// Returns the number of results promoted.
+    pub fn promote_repl_results(&mut self) -> usize {
+        let query = self.repl.input().trim().to_string();
+        let results = self.repl.results().to_vec();
+        self.repl.submit();
+        self.close_repl();
+
+        let count = results.len();
+        self.search_results = results;
+        self.search_index = 0;
+        self.track_nodes();
+        self.search_type = Some(SearchType::YamlPath(query.clone()));
+
+        if let Some(first) = self.search_results.first().cloned() {
+            self.record_jump();
+            self.cursor.set_path(first);
+            self.set_message(
+                format!("{} matches for {} (n/N to step)", count, query),
+                MessageLevel::Info,
+            );
+        } else {
+            self.set_message(format!("No matches for {}", query), MessageLevel::Info);
         }
+        count
     }
 
-    /// Deletes the character at the cursor position (Ctrl-d).
-    pub fn edit_delete_at_cursor(&mut self) {
-        if let Some(ref mut buffer) = self.edit_buffer {
-            if self.edit_cursor < buffer.len() {
-                buffer.remove(self.edit_cursor);
-                self.reset_cursor_blink();
-            }
+    /// Jumps to the next search result (respects search direction).
+    /// Returns (success, wrapped) where wrapped indicates if the search wrapped around.
+    pub fn next_search_result(&mut self) -> (bool, bool) {
+        if self.search_results.is_empty() {
+            return (false, false);
         }
-    }
 
-    /// Deletes from cursor to end of buffer (Ctrl-k).
-    pub fn edit_kill_to_end(&mut self) {
-        if let Some(ref mut buffer) = self.edit_buffer {
-            buffer.truncate(self.edit_cursor);
-            self.reset_cursor_blink();
+        let wrapped;
+        if self.search_forward {
+            let old_index = self.search_index;
+            self.search_index = (self.search_index + 1) % self.search_results.len();
+            wrapped = self.search_index < old_index; // Wrapped if new index is less than old
+        } else {
+            wrapped = self.search_index == 0; // Wrapped if we're going back from 0
+            self.search_index = if self.search_index == 0 {
+                self.search_results.len() - 1
+            } else {
+                self.search_index - 1
+            };
         }
+        self.reveal_path(self.search_results[self.search_index].clone());
+        (true, wrapped)
     }
 
-    /// Returns the current edit cursor position.
-    pub fn edit_cursor_position(&self) -> usize {
-        self.edit_cursor
+    /// Returns the current search results info.
+    pub fn search_results_info(&self) -> Option<(usize, usize)> {
+        if self.search_results.is_empty() {
+            None
+        } else {
+            Some((self.search_index + 1, self.search_results.len()))
+        }
     }
 
-    /// Returns whether the cursor is currently visible (for blinking).
-    pub fn cursor_visible(&self) -> bool {
-        self.cursor_visible
+    /// Returns the current search type, if any.
+    pub fn search_type(&self) -> Option<&SearchType> {
+        self.search_type.as_ref()
     }
 
-    /// Updates the cursor blink state. Call this periodically to make cursor blink.
-    /// Toggles visibility every ~5 ticks (adjust based on render frequency).
-    pub fn update_cursor_blink(&mut self) {
-        self.cursor_blink_ticks = self.cursor_blink_ticks.wrapping_add(1);
-        if self.cursor_blink_ticks >= 5 {
-            self.cursor_visible = !self.cursor_visible;
-            self.cursor_blink_ticks = 0;
-        }
+    /// Clears search results but preserves search buffer and type.
+    /// This removes search info from the status bar while keeping
+    /// the search query available for potential "repeat search" features.
+    pub fn clear_search_results(&mut self) {
+        self.search_results.clear();
+        self.search_index = 0;
     }
 
-    /// Resets cursor to visible (called on any edit action to show immediate feedback).
-    pub fn reset_cursor_blink(&mut self) {
-        self.cursor_visible = true;
-        self.cursor_blink_ticks = 0;
+    /// Returns whether line numbers should be shown.
+    pub fn show_line_numbers(&self) -> bool {
+        self.show_line_numbers
     }
 
-    /// Returns the current pending command character, if any.
-    pub fn pending_command(&self) -> Option<char> {
-        self.pending_command
+    /// Sets whether line numbers should be shown.
+    pub fn set_show_line_numbers(&mut self, show: bool) {
+        self.show_line_numbers = show;
     }
 
-    /// Sets the pending command character.
-    pub fn set_pending_command(&mut self, ch: char) {
-        self.pending_command = Some(ch);
+    /// Returns whether relative line numbers should be shown.
+    pub fn relative_line_numbers(&self) -> bool {
+        self.relative_line_numbers
     }
 
-    /// Clears the pending command.
-    pub fn clear_pending_command(&mut self) {
-        self.pending_command = None;
+    /// Sets whether relative line numbers should be shown.
+    pub fn set_relative_line_numbers(&mut self, show: bool) {
+        self.relative_line_numbers = show;
     }
 
-    /// Returns the current pending count, defaulting to 1 if none.
-    pub fn get_count(&self) -> u32 {
-        self.pending_count.unwrap_or(1)
+    /// Returns whether mouse support is enabled.
+    pub fn enable_mouse(&self) -> bool {
+        self.enable_mouse
     }
 
-    /// Returns the raw pending count (None if no count entered).
-    pub fn pending_count(&self) -> Option<u32> {
-        self.pending_count
+    /// Sets whether mouse support is enabled.
+    pub fn set_enable_mouse(&mut self, enable: bool) {
+        self.enable_mouse = enable;
     }
 
-    /// Adds a digit to the pending count.
-    /// First digit starts the count, subsequent digits multiply by 10 and add.
-    pub fn push_count_digit(&mut self, digit: u32) {
-        if let Some(count) = self.pending_count {
-            self.pending_count = Some(count.saturating_mul(10).saturating_add(digit));
-        } else {
-            self.pending_count = Some(digit);
-        }
+    /// Returns whether backup files should be created before saving.
+    pub fn create_backup(&self) -> bool {
+        self.create_backup
     }
 
-    /// Clears the pending count.
-    pub fn clear_pending_count(&mut self) {
-        self.pending_count = None;
+    /// Sets whether backup files should be created before saving.
+    pub fn set_create_backup(&mut self, enable: bool) {
+        self.create_backup = enable;
     }
 
-    /// Clears both pending command and count (used together often).
-    pub fn clear_pending(&mut self) {
-        self.pending_command = None;
-        self.pending_count = None;
-        self.pending_register = None;
-        self.append_mode = false;
+    /// Returns whether undo history is persisted across sessions.
+    pub fn persistent_undo(&self) -> bool {
+        self.persistent_undo
     }
 
-    /// Returns a reference to the unnamed register content.
-    pub fn get_unnamed_register(&self) -> &crate::editor::registers::RegisterContent {
-        self.registers.get_unnamed()
+    /// Sets whether undo history is persisted across sessions.
+    pub fn set_persistent_undo(&mut self, enable: bool) {
+        self.persistent_undo = enable;
     }
 
-    /// Returns the pending register character if one is waiting.
-    pub fn get_pending_register(&self) -> Option<char> {
-        self.pending_register
+    /// Returns whether unmodified buffers reload automatically when their file changes.
+    pub fn reload_on_change(&self) -> bool {
+        self.reload_on_change
     }
 
-    /// Sets the pending register and append mode for the next yank/delete operation.
-    pub fn set_pending_register(&mut self, register: char, append: bool) {
-        self.pending_register = Some(register);
-        self.append_mode = append;
+    /// Sets whether unmodified buffers reload automatically when their file changes.
+    pub fn set_reload_on_change(&mut self, enable: bool) {
+        self.reload_on_change = enable;
     }
 
-    /// Returns whether append mode is active for the pending register.
-    pub fn get_append_mode(&self) -> bool {
-        self.append_mode
+    /// Returns whether `/` and `?` treat unprefixed patterns as regexes.
+    pub fn regex_search(&self) -> bool {
+        self.regex_search
     }
 
-    /// Returns a reference to the register set.
-    pub fn registers(&self) -> &RegisterSet {
-        &self.registers
+    /// Sets whether `/` and `?` treat unprefixed patterns as regexes.
+    pub fn set_regex_search(&mut self, enable: bool) {
+        self.regex_search = enable;
     }
 
-    /// Clears the pending register and append mode.
-    pub fn clear_register_pending(&mut self) {
-        self.pending_register = None;
-        self.append_mode = false;
+    /// Returns whether `/` and `?` also search inside collapsed nodes.
+    pub fn search_collapsed(&self) -> bool {
+        self.search_collapsed
     }
 
-    /// Returns the current cursor position as (row, col) where row is 1-based line number.
-    ///
-    /// Returns (0, 0) if the cursor is not found in the tree view.
-    pub fn cursor_position(&self) -> (usize, usize) {
-        let lines = self.tree_view.lines();
-        let current_path = self.cursor.path();
+    /// Sets whether `/` and `?` also search inside collapsed nodes. When off,
+    /// only the lines currently shown are searched.
+    pub fn set_search_collapsed(&mut self, enable: bool) {
+        self.search_collapsed = enable;
+    }
 
-        if let Some(idx) = lines.iter().position(|l| l.path == current_path) {
-            let row = idx + 1; // 1-based line number
-            let col = 1; // Tree view doesn't have horizontal position
-            (row, col)
-        } else {
-            (0, 0)
-        }
+    /// Returns whether the breadcrumb bar is shown above the tree.
+    pub fn show_breadcrumbs(&self) -> bool {
+        self.show_breadcrumbs
     }
 
-    /// Returns the total number of lines in the tree view.
-    pub fn total_lines(&self) -> usize {
-        self.tree_view.lines().len()
+    /// Shows or hides the breadcrumb bar.
+    pub fn set_show_breadcrumbs(&mut self, enable: bool) {
+        self.show_breadcrumbs = enable;
+        if !enable {
+            self.breadcrumb_targets.clear();
+        }
     }
 
-    /// Captures the current editor state as an undo checkpoint.
-    ///
-    /// This is called automatically before mutation operations to enable undo/redo.
-    /// Checkpoints capture both the tree structure and cursor position.
-    fn checkpoint(&mut self) {
-        let snapshot = super::undo::EditorSnapshot {
-            tree: self.tree.clone(),
-            cursor_path: self.cursor.path().to_vec(),
-            expanded_paths: self.tree_view.expanded_paths().clone(),
-        };
-        self.undo_tree.add_checkpoint(snapshot);
+    /// Returns whether each tree line shows its value's type.
+    pub fn show_type_badges(&self) -> bool {
+        self.tree_view.type_badges()
     }
 
-    /// Undoes the last operation.
-    ///
-    /// Restores the editor to the previous checkpoint state, including both
-    /// the tree structure and cursor position. Returns true if undo succeeded,
-    /// false if already at the root state.
-    pub fn undo(&mut self) -> bool {
-        if let Some(snapshot) = self.undo_tree.undo() {
-            let current_cursor = self.cursor.path().to_vec();
-            self.tree = snapshot.tree;
-            self.tree_view.set_expanded_paths(snapshot.expanded_paths);
+    /// Shows or hides the type badge after each tree line.
+    pub fn set_show_type_badges(&mut self, enable: bool) {
+        self.tree_view.set_type_badges(enable);
+    }
 
-            // Only restore cursor if current position is now invalid
-            if self.tree.get_node(&current_cursor).is_none() {
-                self.cursor.set_path(snapshot.cursor_path);
-            }
+    /// Returns whether the values of a Secret's `data` show decoded.
+    pub fn decode_secrets(&self) -> bool {
+        self.tree_view.decode_secrets()
+    }
 
+    /// Shows the base64 values under a Kubernetes Secret's `data` decoded
+    /// in the tree, or as written.
+    pub fn set_decode_secrets(&mut self, enable: bool) {
+        if self.tree_view.decode_secrets() != enable {
+            self.tree_view.set_decode_secrets(enable);
             self.rebuild_tree_view();
-            true
-        } else {
-            false
         }
     }
 
-    /// Redoes the last undone operation.
-    ///
-    /// Restores the editor to the next checkpoint state (newest branch if multiple
-    /// exist), including both the tree structure and cursor position. Returns true
-    /// if redo succeeded, false if no redo history exists.
-    pub fn redo(&mut self) -> bool {
-        if let Some(snapshot) = self.undo_tree.redo() {
-            let current_cursor = self.cursor.path().to_vec();
-            self.tree = snapshot.tree;
-            self.tree_view.set_expanded_paths(snapshot.expanded_paths);
-
-            // Only restore cursor if current position is now invalid
-            if self.tree.get_node(&current_cursor).is_none() {
-                self.cursor.set_path(snapshot.cursor_path);
-            }
+    /// Returns whether `${VAR}` placeholders are previewed with their values,
+    /// and the `.env` file the values come from besides the environment.
+    pub fn resolve_env(&self) -> Option<Option<&str>> {
+        self.tree_view
+            .env()
+            .map(|_| self.resolve_env_file.as_deref())
+    }
+
+    /// Previews `${VAR}` and `$VAR` placeholders in string values with their
+    /// interpolated values (`:set resolve-env`), taken from the environment
+    /// or, when `file` is given, from that `.env` file and then the
+    /// environment. The document isn't changed. Returns the number of
+    /// variables available.
+    pub fn set_resolve_env(&mut self, file: Option<&str>) -> anyhow::Result<usize> {
+        use crate::editor::dotenv;
+        use anyhow::Context;
+
+        let mut vars: std::collections::HashMap<String, String> =
+            dotenv::environment().into_iter().collect();
+        if let Some(file) = file {
+            let text =
+                std::fs::read_to_string(file).with_context(|| format!("Can't read {}", file))?;
+            vars.extend(dotenv::parse(&text)?);
+        }
+        let count = vars.len();
+        self.resolve_env_file = file.map(str::to_string);
+        self.tree_view.set_env(Some(vars));
+        self.rebuild_tree_view();
+        Ok(count)
+    }
 
+    /// Turns the placeholder preview off (`:set noresolve-env`).
+    pub fn clear_resolve_env(&mut self) {
+        self.resolve_env_file = None;
+        if self.tree_view.env().is_some() {
+            self.tree_view.set_env(None);
             self.rebuild_tree_view();
-            true
-        } else {
-            false
         }
     }
 
-    /// Returns the current add mode stage.
-    pub fn add_mode_stage(&self) -> &AddModeStage {
-        &self.add_mode_stage
+    /// Returns whether CloudFormation mode is on.
+    pub fn cloudformation(&self) -> bool {
+        self.tree_view.cloudformation()
     }
 
-    /// Returns the current add key buffer.
-    pub fn add_key_buffer(&self) -> &str {
-        &self.add_key_buffer
+    /// Turns CloudFormation mode on or off: intrinsics are highlighted and
+    /// `Tab` completes logical IDs while editing a `!Ref` or `!GetAtt` value.
+    pub fn set_cloudformation(&mut self, enable: bool) {
+        if self.tree_view.cloudformation() != enable {
+            self.tree_view.set_cloudformation(enable);
+            self.rebuild_tree_view();
+        }
     }
 
-    /// Returns the current cursor position in the add key buffer.
-    pub fn add_key_cursor_position(&self) -> usize {
-        self.add_key_cursor
+    /// Returns whether Prometheus mode is on.
+    pub fn prometheus(&self) -> bool {
+        self.tree_view.prometheus()
     }
 
-    /// Pushes a character to the add key buffer at cursor position.
-    pub fn push_to_add_key_buffer(&mut self, ch: char) {
-        self.add_key_buffer.insert(self.add_key_cursor, ch);
-        self.add_key_cursor += ch.len_utf8(); // Advance by byte length, not 1
-        self.reset_cursor_blink();
+    /// Turns Prometheus mode on or off: `expr` fields are checked as PromQL
+    /// after every change, and rules fold to their `alert` or `record` name.
+    ///
+    /// Turning it on folds every rule, so a large rule file reads as a list
+    /// of names.
+    pub fn set_prometheus(&mut self, enable: bool) {
+        use crate::document::prometheus::PROMQL_SOURCE;
+
+        if self.tree_view.prometheus() == enable {
+            return;
+        }
+        self.tree_view.set_prometheus(enable);
+        if enable {
+            self.fold_rules();
+            self.check_promql();
+        } else {
+            self.set_diagnostics(PROMQL_SOURCE, Vec::new());
+        }
+        self.rebuild_tree_view();
     }
 
-    /// Removes the character before cursor in the add key buffer (backspace).
-    pub fn pop_from_add_key_buffer(&mut self) {
-        if self.add_key_cursor > 0 {
-            // Find the start of the character before cursor (handles multi-byte UTF-8)
-            let char_start = self.add_key_buffer[..self.add_key_cursor]
-                .char_indices()
-                .next_back()
-                .map(|(i, _)| i)
-                .unwrap_or(0);
-            self.add_key_buffer.remove(char_start);
-            self.add_key_cursor = char_start;
-            self.reset_cursor_blink();
+    /// Collapses every Prometheus rule and expands the lists holding them.
+    fn fold_rules(&mut self) {
+        use crate::document::prometheus::rule_paths;
+
+        let rules = rule_paths(self.tree.root());
+        let mut expanded = self.tree_view.expanded_paths().clone();
+        for rule in &rules {
+            expanded.remove(rule);
+            for depth in 1..rule.len() {
+                expanded.insert(rule[..depth].to_vec());
+            }
+        }
+        self.tree_view.set_expanded_paths(expanded);
+
+        // Keep the cursor visible
+        let cursor = self.cursor.path().to_vec();
+        if let Some(rule) = rules
+            .iter()
+            .find(|rule| cursor.len() > rule.len() && cursor.starts_with(rule))
+        {
+            self.cursor.set_path(rule.clone());
         }
     }
 
-    /// Clears the add key buffer and resets cursor.
-    pub fn clear_add_key_buffer(&mut self) {
-        self.add_key_buffer.clear();
-        self.add_key_cursor = 0;
+    /// Checks every `expr` field as PromQL in Prometheus mode, replacing the
+    /// `promql` diagnostics.
+    fn check_promql(&mut self) {
+        use crate::document::prometheus::{check, expr_paths, PROMQL_SOURCE};
+
+        if !self.prometheus() {
+            return;
+        }
+        let mut diagnostics = Vec::new();
+        for path in expr_paths(self.tree.root()) {
+            let Some(YamlValue::String(expr)) = self.tree.get_node(&path).map(|n| n.value()) else {
+                continue;
+            };
+            if let Err(problem) = check(expr.as_str()) {
+                let location = self
+                    .path_to_string(&path, "bracket")
+                    .unwrap_or_else(|| "$".to_string());
+                diagnostics.push(Diagnostic::error(
+                    PROMQL_SOURCE,
+                    path,
+                    location,
+                    format!("PromQL: {}", problem),
+                ));
+            }
+        }
+        self.set_diagnostics(PROMQL_SOURCE, diagnostics);
     }
 
-    /// Starts an add operation at the current cursor position.
-    ///
-    /// Determines whether we're adding to an array or object, and sets the
-    /// appropriate add_mode_stage. For arrays, immediately enters Insert mode.
-    /// For objects, stays in Normal mode and waits for key input.
-    pub fn start_add_operation(&mut self) {
-        use crate::document::node::YamlValue;
+    /// Runs the value checks: repeated keys, network values and versions
+    /// always, and those of the enabled profiles (Prometheus and Kubernetes).
+    fn run_value_checks(&mut self) {
+        self.check_duplicate_keys();
+        self.check_network();
+        self.check_versions();
+        self.check_promql();
+        self.check_k8s_values();
+    }
 
-        // Clear any previous messages so the edit area is visible
-        self.clear_message();
+    /// Returns whether the Kubernetes commands are enabled.
+    pub fn k8s(&self) -> bool {
+        self.k8s
+    }
 
-        let current_path = self.cursor.path().to_vec();
+    /// Enables or disables the Kubernetes profile: the commands
+    /// (`:containers`, `:images` and `:env`) and the checks of resource
+    /// quantities and durations.
+    pub fn set_k8s(&mut self, enable: bool) {
+        use super::k8s::K8S_SOURCE;
 
-        // Special case: if cursor is at root (empty path)
-        if current_path.is_empty() {
-            // Check if root is a container
-            match self.tree.root().value() {
-                YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
-                    // Root is container, we can add to it
-                    // Determine which type
-                    match self.tree.root().value() {
-                        YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
-                            // Array or JSONL: go straight to value input
-                            self.add_mode_stage = AddModeStage::AwaitingValue;
-                            self.add_insertion_point = Some(vec![0]); // Insert at position 0
+        self.k8s = enable;
+        if enable {
+            self.check_k8s_values();
+        } else {
+            self.set_diagnostics(K8S_SOURCE, Vec::new());
+        }
+    }
 
-                            // Enter Insert mode with empty edit buffer
-                            self.edit_buffer = Some(String::new());
-                            self.edit_cursor = 0;
-                            self.set_mode(EditorMode::Insert);
-                            self.reset_cursor_blink();
-                            // Set mode indicator message
-                            self.set_message("-- INSERT --".to_string(), MessageLevel::Info);
-                        }
-                        YamlValue::Object(_) => {
-                            // Object: need key first
-                            self.add_mode_stage = AddModeStage::AwaitingKey;
-                            self.clear_add_key_buffer(); // Reset buffer and cursor
-                            self.add_insertion_point = Some(vec![0]); // Insert at position 0
-                            self.reset_cursor_blink();
-                        }
-                        _ => unreachable!(),
-                    }
-                }
-                _ => {
-                    // Root is scalar, can't add sibling
-                    self.set_message(
-                        "Cannot add sibling to root node".to_string(),
-                        MessageLevel::Error,
-                    );
-                }
-            }
+    /// Checks quantities and durations in known fields when the Kubernetes
+    /// profile is on, replacing the `k8s` diagnostics.
+    fn check_k8s_values(&mut self) {
+        use super::k8s::{check_values, K8S_SOURCE};
+
+        if !self.k8s {
             return;
         }
+        let problems = check_values(self.tree.root());
+        self.set_problems(K8S_SOURCE, problems);
+    }
 
-        // Check if the current node (not parent) is a container
-        // If it's an EMPTY container, add inside it
-        // If it's a NON-EMPTY container and NOT at root, add as sibling after it (to preserve expansion state)
-        // If it's a NON-EMPTY container AT root, add inside it (can't add sibling to root)
-        if let Some(current_node) = self.tree.get_node(&current_path) {
-            match current_node.value() {
-                YamlValue::Array(elements) | YamlValue::MultiDoc(elements) => {
-                    // If array/JSONL is empty OR we're at root level, add inside it
-                    if elements.is_empty() || current_path.is_empty() {
-                        let insert_index = elements.len(); // Get length before mutable borrow
+    /// Flags keys written more than once in a mapping, replacing the
+    /// `duplicates` diagnostics.
+    fn check_duplicate_keys(&mut self) {
+        use super::dedupe::{check_keys, DUPLICATES_SOURCE};
 
-                        // Ensure the container is expanded so the new child will be visible
-                        if !self.tree_view().is_expanded(&current_path) {
-                            self.tree_view_mut().toggle_expand(&current_path);
-                        }
+        let problems = check_keys(self.tree.root());
+        self.set_problems(DUPLICATES_SOURCE, problems);
+    }
 
-                        self.add_mode_stage = AddModeStage::AwaitingValue;
-                        let mut insertion_path = current_path.clone();
-                        insertion_path.push(insert_index); // Insert at end
-                        self.add_insertion_point = Some(insertion_path);
-
-                        // Enter Insert mode with empty edit buffer
-                        self.edit_buffer = Some(String::new());
-                        self.edit_cursor = 0;
-                        self.set_mode(EditorMode::Insert);
-                        self.reset_cursor_blink();
-                        // Set mode indicator message
-                        self.set_message("-- INSERT --".to_string(), MessageLevel::Info);
-                        return;
-                    }
-                    // Non-empty array/JSONL at non-root: fall through to add sibling after
-                }
-                YamlValue::Object(entries) => {
-                    // If object is empty OR we're at root level, add inside it
-                    if entries.is_empty() || current_path.is_empty() {
-                        let insert_index = entries.len(); // Get length before mutable borrow
+    /// Checks IP addresses, CIDR ranges and ports, replacing the `network`
+    /// diagnostics.
+    fn check_network(&mut self) {
+        use super::network::{check_values, NETWORK_SOURCE};
 
-                        // Ensure the container is expanded so the new child will be visible
-                        if !self.tree_view().is_expanded(&current_path) {
-                            self.tree_view_mut().toggle_expand(&current_path);
-                        }
+        let problems = check_values(self.tree.root());
+        self.set_problems(NETWORK_SOURCE, problems);
+    }
 
-                        self.add_mode_stage = AddModeStage::AwaitingKey;
-                        self.clear_add_key_buffer(); // Reset buffer and cursor
-                        let mut insertion_path = current_path.clone();
-                        insertion_path.push(insert_index); // Insert at end
-                        self.add_insertion_point = Some(insertion_path);
-                        self.reset_cursor_blink();
-                        // Stay in Normal mode, wait for key input
-                        return;
-                    }
-                    // Non-empty object at non-root: fall through to add sibling after
-                }
-                _ => {
-                    // Current node is a scalar, fall through to add sibling
+    /// Checks that version fields hold semantic versions, replacing the
+    /// `semver` diagnostics.
+    fn check_versions(&mut self) {
+        use super::semver::{check_values, SEMVER_SOURCE};
+
+        let problems = check_values(self.tree.root());
+        self.set_problems(SEMVER_SOURCE, problems);
+    }
+
+    /// Replaces the diagnostics from `source` with `problems`.
+    fn set_problems(&mut self, source: &str, problems: Vec<super::diagnostics::ValueProblem>) {
+        use super::diagnostics::Severity;
+
+        let diagnostics = problems
+            .into_iter()
+            .map(|problem| {
+                let location = self
+                    .path_to_string(&problem.path, "bracket")
+                    .unwrap_or_else(|| "$".to_string());
+                match problem.severity {
+                    Severity::Error => {
+                        Diagnostic::error(source, problem.path, location, problem.message)
+                    }
+                    _ => Diagnostic::warning(source, problem.path, location, problem.message),
                 }
-            }
+            })
+            .collect();
+        self.set_diagnostics(source, diagnostics);
+    }
+
+    fn ensure_k8s(&self) -> anyhow::Result<()> {
+        if !self.k8s {
+            anyhow::bail!("Kubernetes commands are off (:set k8s)");
         }
+        Ok(())
+    }
 
-        // Current node is a scalar - add sibling after it in parent container
-        let parent_path = &current_path[..current_path.len() - 1];
-        let current_index = current_path[current_path.len() - 1];
+    /// Stores where the breadcrumb segments were drawn. Called by the renderer.
+    pub fn set_breadcrumb_targets(&mut self, targets: Vec<ClickTarget>) {
+        self.breadcrumb_targets = targets;
+    }
 
-        // Get parent node
-        let parent = if parent_path.is_empty() {
-            self.tree.root()
-        } else {
-            match self.tree.get_node(parent_path) {
-                Some(node) => node,
-                None => {
-                    self.set_message("Invalid cursor position".to_string(), MessageLevel::Error);
-                    return;
-                }
-            }
-        };
+    /// Stores where the pinned paths were drawn. Called by the renderer.
+    pub fn set_pin_targets(&mut self, targets: Vec<ClickTarget>) {
+        self.pin_targets = targets;
+    }
 
-        // Determine parent type and set up add operation
-        match parent.value() {
-            YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
-                // Adding to array/JSONL: insert after current element
-                self.add_mode_stage = AddModeStage::AwaitingValue;
-                let mut insertion_path = parent_path.to_vec();
-                insertion_path.push(current_index + 1);
-                self.add_insertion_point = Some(insertion_path);
+    /// Returns the path of the breadcrumb or pin drawn at the 0-based screen
+    /// cell, if any.
+    pub fn click_target_at(&self, column: u16, row: u16) -> Option<Vec<usize>> {
+        self.breadcrumb_targets
+            .iter()
+            .chain(&self.pin_targets)
+            .find(|t| t.row == row && (t.start..t.end).contains(&column))
+            .map(|t| t.path.clone())
+    }
 
-                // Enter Insert mode with empty edit buffer
-                self.edit_buffer = Some(String::new());
-                self.edit_cursor = 0;
-                self.set_mode(EditorMode::Insert);
-                self.reset_cursor_blink();
-                // Set mode indicator message
-                self.set_message("-- INSERT --".to_string(), MessageLevel::Info);
-            }
-            YamlValue::Object(_) => {
-                // Adding to object: need key first
-                self.add_mode_stage = AddModeStage::AwaitingKey;
-                self.clear_add_key_buffer(); // Reset buffer and cursor
-                let mut insertion_path = parent_path.to_vec();
-                insertion_path.push(current_index + 1);
-                self.add_insertion_point = Some(insertion_path);
-                self.reset_cursor_blink();
-                // Stay in Normal mode, wait for key input
-            }
-            _ => {
-                self.set_message("Parent is not a container".to_string(), MessageLevel::Error);
-            }
+    /// Moves the cursor to the node named by a clicked breadcrumb or pin,
+    /// recording a jump. Returns false if nothing clickable was drawn there.
+    pub fn click_at(&mut self, column: u16, row: u16) -> bool {
+        let Some(path) = self.click_target_at(column, row) else {
+            return false;
+        };
+        if path != self.cursor.path() {
+            self.record_jump();
+            self.reveal_path(path);
         }
+        true
     }
 
-    /// Commits the add operation by creating and inserting the new node.
-    ///
-    /// Parses the edit buffer value, creates a YamlNode, inserts it at the
-    /// add_insertion_point, creates an undo checkpoint, and moves cursor to
-    /// the new node.
-    pub fn commit_add_operation(&mut self) -> anyhow::Result<()> {
-        use anyhow::anyhow;
+    /// Returns how many lines of context are kept above and below the cursor.
+    pub fn scrolloff(&self) -> usize {
+        self.scrolloff
+    }
 
-        // Verify we're in AwaitingValue stage
-        if !matches!(self.add_mode_stage, AddModeStage::AwaitingValue) {
-            return Err(anyhow!("Not in AwaitingValue stage"));
+    /// Sets how many lines of context are kept above and below the cursor.
+    pub fn set_scrolloff(&mut self, lines: usize) {
+        self.scrolloff = lines;
+    }
+
+    /// Returns how many levels of nesting the tree shows, 0 for no limit.
+    pub fn max_render_depth(&self) -> usize {
+        self.tree_view.max_depth()
+    }
+
+    /// Sets how many levels of nesting the tree shows before summarizing
+    /// deeper subtrees (`:set maxdepth`), 0 for no limit.
+    pub fn set_max_render_depth(&mut self, depth: usize) {
+        if self.tree_view.max_depth() != depth {
+            self.tree_view.set_max_depth(depth);
+            self.rebuild_tree_view();
         }
+    }
 
-        // Get the value from edit buffer
-        let value_str = self
-            .edit_buffer
-            .as_ref()
-            .ok_or_else(|| anyhow!("No edit buffer"))?;
+    /// Shows the children of the node at the cursor that were cut off at
+    /// the depth limit (`zd`); the limit counts again from the node.
+    pub fn expand_deeper_at_cursor(&mut self) -> anyhow::Result<()> {
+        let path = self.cursor.path().to_vec();
+        let cut_off = self
+            .tree_view
+            .line_of(&path)
+            .and_then(|i| self.tree_view.lines().get(i))
+            .is_some_and(|line| line.too_deep);
+        if !cut_off {
+            anyhow::bail!(
+                "Not at the depth limit (:set maxdepth={})",
+                self.tree_view.max_depth()
+            );
+        }
+        self.tree_view.expand_deeper(&path);
+        self.rebuild_tree_view();
+        Ok(())
+    }
 
-        // Parse the value
-        let value = parse_scalar_value(value_str);
-        let node = YamlNode::new(value);
+    /// Returns the file size in bytes from which files are loaded lazily.
+    pub fn lazy_load_threshold(&self) -> usize {
+        self.lazy_load_threshold
+    }
 
-        // Get insertion point
-        let insertion_path = self
-            .add_insertion_point
-            .as_ref()
-            .ok_or_else(|| anyhow!("No insertion point set"))?
-            .clone();
+    /// Returns the limits files are checked against when they are opened.
+    pub fn load_limits(&self) -> &crate::document::limits::LoadLimits {
+        &self.load_limits
+    }
 
-        // Determine parent type and insert
-        let parent_path = if insertion_path.is_empty() {
-            &[]
+    /// Returns the largest size of an undo file in MB (0 for no limit).
+    pub fn undo_file_limit_mb(&self) -> usize {
+        self.undo_file_limit_mb
+    }
+
+    /// Returns the status line format for file details.
+    pub fn statusline(&self) -> &str {
+        &self.statusline
+    }
+
+    /// Sets the status line format for file details (`%s`, `%t`, `%n`).
+    pub fn set_statusline(&mut self, format: String) {
+        self.statusline = format;
+    }
+
+    /// Returns how many lines a mouse wheel tick scrolls.
+    pub fn mouse_scroll_lines(&self) -> usize {
+        self.mouse_scroll_lines
+    }
+
+    /// Sets how many lines a mouse wheel tick scrolls.
+    pub fn set_mouse_scroll_lines(&mut self, lines: usize) {
+        self.mouse_scroll_lines = lines;
+    }
+
+    /// Returns the memory budget in megabytes (0 for no limit).
+    pub fn memory_budget(&self) -> usize {
+        self.memory_budget_mb
+    }
+
+    /// Sets the memory budget in megabytes (0 for no limit) and checks the
+    /// buffer against it.
+    pub fn set_memory_budget(&mut self, megabytes: usize) {
+        self.memory_budget_mb = megabytes;
+        self.check_memory();
+    }
+
+    /// Returns true once the buffer has crossed its memory budget.
+    ///
+    /// Stays set until usage falls below half the budget, so that a buffer
+    /// hovering at the limit doesn't keep switching modes.
+    pub fn memory_degraded(&self) -> bool {
+        self.memory_degraded
+    }
+
+    /// Returns the estimated memory held by the document, its undo history
+    /// and the registers.
+    pub fn memory_usage(&self) -> super::memory::MemoryUsage {
+        super::memory::MemoryUsage {
+            tree: self.undo_tree.current_size() + self.tree.original_source().map_or(0, str::len),
+            undo: self.undo_tree.memory(),
+            undo_states: self.undo_tree.len(),
+            registers: self.registers.memory(),
+        }
+    }
+
+    /// Shows the memory estimate and budget (`:memory`).
+    pub fn show_memory(&mut self) {
+        use super::memory::format_size;
+
+        let usage = self.memory_usage();
+        let budget = match self.memory_budget_mb {
+            0 => "no budget".to_string(),
+            mb => format!("budget {}", format_size(mb << 20)),
+        };
+        let (level, over) = if self.memory_degraded {
+            (MessageLevel::Warning, " - over budget")
         } else {
-            &insertion_path[..insertion_path.len() - 1]
+            (MessageLevel::Info, "")
         };
+        self.set_message(
+            format!(
+                "Memory: {} ({}; {}){}",
+                format_size(usage.total()),
+                usage.summary(),
+                budget,
+                over
+            ),
+            level,
+        );
+    }
 
-        let parent = if parent_path.is_empty() {
-            self.tree.root()
+    /// Checks the memory estimate against `memory_budget_mb`, dropping the
+    /// oldest undo states while it is over.
+    ///
+    /// Crossing the budget shows a warning once and turns on the degraded
+    /// mode described in [`super::memory`].
+    fn check_memory(&mut self) {
+        use super::memory::format_size;
+
+        let budget = self.memory_budget_mb.saturating_mul(1 << 20);
+        if budget == 0 {
+            self.memory_degraded = false;
+            return;
+        }
+        let usage = self.memory_usage();
+        let mut total = usage.total();
+        if total <= budget {
+            if total < budget / 2 {
+                self.memory_degraded = false;
+            }
+            return;
+        }
+
+        let mut dropped = 0;
+        while total > budget && self.undo_tree.drop_oldest() {
+            dropped += 1;
+            total = usage.total() - usage.undo + self.undo_tree.memory();
+        }
+        if !self.memory_degraded {
+            self.memory_degraded = true;
+            self.set_message(
+                format!(
+                    "Memory over the {} budget: dropped {} oldest undo state{}; large yanks skip the clipboard and E is off (:memory)",
+                    format_size(budget),
+                    dropped,
+                    if dropped == 1 { "" } else { "s" }
+                ),
+                MessageLevel::Warning,
+            );
+        }
+    }
+
+    /// Copies register content to the system clipboard: a single node as
+    /// YAML, several as a YAML sequence.
+    ///
+    /// While over the memory budget, content over
+    /// [`super::memory::CLIPBOARD_MIRROR_LIMIT`] stays in the register only.
+    fn mirror_to_clipboard(&mut self, content: &super::registers::RegisterContent) {
+        if self.memory_degraded && content.memory() > super::memory::CLIPBOARD_MIRROR_LIMIT {
+            self.set_message(
+                "Yank kept in the register only: too large for the clipboard while over the memory budget".to_string(),
+                MessageLevel::Warning,
+            );
+            return;
+        }
+        let clipboard_text = if content.nodes.len() == 1 {
+            // Single node: serialize as-is
+            let yaml_value = self.node_to_serde_value(content.nodes[0].value());
+            serde_yaml::to_string(&yaml_value).ok()
         } else {
-            self.tree
-                .get_node(parent_path)
-                .ok_or_else(|| anyhow!("Parent node not found"))?
+            // Multiple nodes: serialize as JSON array
+            let array: Vec<serde_yaml::Value> = content
+                .nodes
+                .iter()
+                .map(|node| self.node_to_serde_value(node.value()))
+                .collect();
+            serde_yaml::to_string(&array).ok()
         };
 
-        match parent.value() {
-            YamlValue::Array(_) => {
-                self.tree.insert_node_in_array(&insertion_path, node)?;
-                self.set_message("Added element".to_string(), MessageLevel::Info);
-            }
-            YamlValue::Object(_) => {
-                let key = self.add_key_buffer.clone();
-                self.tree
-                    .insert_node_in_object(&insertion_path, key.clone(), node)?;
-                self.set_message(format!("Added field '{}'", key), MessageLevel::Info);
-            }
-            _ => {
-                return Err(anyhow!("Parent is not a container"));
+        if let Some(yaml_str) = clipboard_text {
+            use arboard::Clipboard;
+            if let Ok(mut clipboard) = Clipboard::new() {
+                let _ = clipboard.set_text(yaml_str);
             }
         }
+    }
 
-        // Update expanded paths to account for shifted indices after insertion
-        self.tree_view_mut()
-            .update_paths_after_insertion(&insertion_path);
-
-        // Rebuild tree view to show new node
-        self.rebuild_tree_view();
+    /// Returns the fields pinned to multi-document record previews.
+    pub fn preview_fields(&self) -> &[String] {
+        self.tree_view.preview_fields()
+    }
 
-        // Move cursor to newly created node
-        self.cursor.set_path(insertion_path.clone());
+    /// Pins a dotted field (`status.phase`) to the front of every record's
+    /// collapsed preview (`:preview-field add`). Returns false if it already
+    /// was pinned.
+    pub fn add_preview_field(&mut self, field: &str) -> bool {
+        let mut fields = self.tree_view.preview_fields().to_vec();
+        if fields.iter().any(|f| f == field) {
+            return false;
+        }
+        fields.push(field.to_string());
+        self.set_preview_fields(fields);
+        true
+    }
 
-        // Mark dirty and create undo checkpoint
-        self.mark_dirty();
-        self.checkpoint();
+    /// Unpins a field from record previews. Returns false if it wasn't pinned.
+    pub fn remove_preview_field(&mut self, field: &str) -> bool {
+        let mut fields = self.tree_view.preview_fields().to_vec();
+        let before = fields.len();
+        fields.retain(|f| f != field);
+        if fields.len() == before {
+            return false;
+        }
+        self.set_preview_fields(fields);
+        true
+    }
 
-        // Clear add operation state and edit buffer
-        self.cancel_add_operation();
-        self.cancel_editing();
+    /// Replaces the fields pinned to record previews.
+    pub fn set_preview_fields(&mut self, fields: Vec<String>) {
+        self.tree_view.set_preview_fields(fields);
+        self.rebuild_tree_view();
+    }
 
-        Ok(())
+    /// Returns whether merge keys (`<<`) are resolved in the tree view.
+    pub fn resolve_merge(&self) -> bool {
+        self.tree_view.resolve_merge()
     }
 
-    /// Transitions from AwaitingKey to AwaitingValue stage.
+    /// Sets whether merge keys (`<<`) are resolved in the tree view.
     ///
-    /// Called when user presses Enter after typing object key.
-    pub fn transition_add_to_value(&mut self) {
-        if matches!(self.add_mode_stage, AddModeStage::AwaitingKey) {
-            // Check for empty key
-            if self.add_key_buffer.is_empty() {
-                self.set_message("Key cannot be empty".to_string(), MessageLevel::Error);
-                return;
+    /// When enabled, mappings that merge an anchor also list the inherited
+    /// entries (read-only). The document itself is unchanged, so the original
+    /// merge key is what gets written on save.
+    pub fn set_resolve_merge(&mut self, resolve: bool) {
+        self.tree_view.set_resolve_merge(resolve);
+        self.rebuild_tree_view();
+
+        // Inherited lines disappear when resolution is turned off
+        if !self
+            .tree_view
+            .lines()
+            .iter()
+            .any(|line| line.path == self.cursor.path())
+        {
+            let mut path = self.cursor.path().to_vec();
+            while !path.is_empty() && self.tree.get_node(&path).is_none() {
+                path.pop();
             }
+            if path.is_empty() {
+                if let Some(first_line) = self.tree_view.lines().first() {
+                    path = first_line.path.clone();
+                }
+            }
+            self.cursor.set_path(path);
+        }
+    }
 
-            // Transition to value stage
-            self.add_mode_stage = AddModeStage::AwaitingValue;
+    /// Returns true if the cursor is on an entry inherited through a merge key.
+    fn cursor_on_merged_line(&self) -> bool {
+        self.tree_view
+            .lines()
+            .iter()
+            .any(|line| line.merged && line.path == self.cursor.path())
+    }
 
-            // Enter Insert mode
-            self.edit_buffer = Some(String::new());
-            self.edit_cursor = 0;
-            self.set_mode(EditorMode::Insert);
-            self.reset_cursor_blink();
-            // Set mode indicator message
-            self.set_message("-- INSERT --".to_string(), MessageLevel::Info);
-        }
+    /// Returns whether edits to this buffer are refused (`--read-only`, `:view`).
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
     }
 
-    /// Cancels the add operation and clears all related state.
-    pub fn cancel_add_operation(&mut self) {
-        self.add_mode_stage = AddModeStage::None;
-        self.add_key_buffer.clear();
-        self.add_insertion_point = None;
+    /// Sets whether edits to this buffer are refused.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
     }
 
-    /// Starts an add container operation (ao for object, aa for array).
-    ///
-    /// Immediately adds an empty container {} or [] without going through
-    /// the value input stage. For objects, prompts for key name first.
-    ///
-    /// # Arguments
+    /// Returns whether the editor runs in safe mode (`--clean`).
+    pub fn is_safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    /// Turns safe mode on or off.
     ///
-    /// * `is_object` - true for object {}, false for array []
-    pub fn start_add_container_operation(&mut self, is_object: bool) {
-        use crate::document::node::YamlValue;
+    /// In safe mode project configs and modelines are ignored, undo history
+    /// and notes are neither loaded nor written, and `:set save` is refused,
+    /// so odd behavior can be checked against a stock setup.
+    pub fn set_safe_mode(&mut self, safe_mode: bool) {
+        self.safe_mode = safe_mode;
+    }
 
-        // Clear any previous messages so the edit area is visible
-        self.clear_message();
+    /// Fails if the buffer is read-only.
+    pub fn ensure_writable(&self) -> anyhow::Result<()> {
+        if self.read_only {
+            anyhow::bail!(self.read_only_message());
+        }
+        Ok(())
+    }
 
-        let current_path = self.cursor.path().to_vec();
+    /// Reports that the buffer is read-only if it is.
+    ///
+    /// Returns true when the caller should abort the operation.
+    pub fn reject_read_only(&mut self) -> bool {
+        if self.read_only {
+            self.set_message(self.read_only_message().to_string(), MessageLevel::Error);
+        }
+        self.read_only
+    }
 
-        // Create the container node
-        let container_node = if is_object {
-            YamlNode::new(YamlValue::Object(indexmap::IndexMap::new()))
+    fn read_only_message(&self) -> &'static str {
+        if self.sampled.is_some() {
+            SAMPLED_MESSAGE
         } else {
-            YamlNode::new(YamlValue::Array(vec![]))
-        };
+            READ_ONLY_MESSAGE
+        }
+    }
 
-        // Special case: if cursor is at root (empty path)
-        if current_path.is_empty() {
-            // At root - check if root is a container
-            match self.tree.root().value() {
-                YamlValue::Object(_) => {
-                    self.add_mode_stage = AddModeStage::AwaitingKey;
-                    self.clear_add_key_buffer(); // Reset buffer and cursor
-                    self.add_insertion_point = Some(vec![0]);
-                    // For object containers in object root, we need a key
-                    // Store the container temporarily and wait for key
-                    self.temp_container = Some(container_node);
-                    self.reset_cursor_blink();
-                    return;
-                }
-                YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
-                    // Insert directly into array/JSONL at position 0
-                    let insertion_path = vec![0];
-                    match self
-                        .tree
-                        .insert_node_in_array(&insertion_path, container_node)
-                    {
-                        Ok(_) => {
-                            self.tree_view_mut()
-                                .update_paths_after_insertion(&insertion_path);
-                            self.rebuild_tree_view();
-                            self.cursor.set_path(insertion_path.clone());
-                            self.mark_dirty();
-                            self.checkpoint();
+    /// Replaces a multi-document buffer's records with a sample of them
+    /// (`--sample`, `:sample`). The buffer becomes read-only until
+    /// [`EditorState::load_all_records`]; sampling again resamples the full set.
+    pub fn apply_sample(
+        &mut self,
+        sample: super::sample::Sample,
+        seed: u64,
+    ) -> anyhow::Result<super::sample::SampleInfo> {
+        use super::sample::SampleInfo;
 
-                            let msg = if is_object {
-                                "Added empty object"
-                            } else {
-                                "Added empty array"
-                            };
-                            self.set_message(msg.to_string(), MessageLevel::Info);
-                        }
-                        Err(e) => {
-                            self.set_message(format!("Add failed: {}", e), MessageLevel::Error);
-                        }
-                    }
-                    return;
-                }
-                _ => {
-                    self.set_message(
-                        "Cannot add sibling to root node".to_string(),
-                        MessageLevel::Error,
-                    );
-                    return;
-                }
-            }
+        if self.sampled.is_none() && self.dirty {
+            anyhow::bail!("Save or undo your changes before sampling");
         }
+        let full = match &self.sampled {
+            Some(sampled) => &sampled.full,
+            None => &self.tree,
+        };
+        let YamlValue::MultiDoc(records) = full.root().value() else {
+            anyhow::bail!("Sampling needs a multi-document or JSONL file");
+        };
+        let kept: Vec<YamlNode> = sample
+            .indices(records.len(), seed)
+            .into_iter()
+            .map(|i| records[i].clone())
+            .collect();
+        let info = SampleInfo {
+            sample,
+            shown: kept.len(),
+            total: records.len(),
+        };
 
-        // Check if the current node (not parent) is a container
-        // If it's empty OR at root level, add inside it
-        // If it's non-empty and not at root, add as sibling (to preserve expansion state)
-        if let Some(current_node) = self.tree.get_node(&current_path) {
-            match current_node.value() {
-                YamlValue::Array(elements) | YamlValue::MultiDoc(elements)
-                    if elements.is_empty() || current_path.is_empty() =>
-                {
-                    let insert_index = elements.len(); // Get length before mutable borrow
+        let sampled_tree = YamlTree::new(YamlNode::new(YamlValue::MultiDoc(kept)));
+        let previous = self.sampled.take();
+        let (full, was_read_only) = match previous {
+            Some(sampled) => (sampled.full, sampled.was_read_only),
+            None => (
+                std::mem::replace(
+                    &mut self.tree,
+                    YamlTree::new(YamlNode::new(YamlValue::Null)),
+                ),
+                self.read_only,
+            ),
+        };
+        self.reload_tree(sampled_tree);
+        self.tree_view.set_filter(None);
+        self.view_filter_label = None;
+        self.read_only = true;
+        self.sampled = Some(SampledRecords {
+            info,
+            full,
+            was_read_only,
+        });
+        Ok(info)
+    }
+
+    /// Shows every record again after [`EditorState::apply_sample`]
+    /// (`:sample off`). Returns the number of records, or `None` if the
+    /// buffer was not sampled.
+    pub fn load_all_records(&mut self) -> Option<usize> {
+        let sampled = self.sampled.take()?;
+        self.reload_tree(sampled.full);
+        self.tree_view.set_filter(None);
+        self.view_filter_label = None;
+        self.read_only = sampled.was_read_only;
+        Some(sampled.info.total)
+    }
+
+    /// Returns what the buffer shows if only a sample of its records is loaded.
+    pub fn sample_info(&self) -> Option<super::sample::SampleInfo> {
+        self.sampled.as_ref().map(|sampled| sampled.info)
+    }
+
+    /// Fails if the cursor is on a merged entry, which is read-only.
+    fn ensure_not_merged(&self) -> anyhow::Result<()> {
+        if self.cursor_on_merged_line() {
+            anyhow::bail!("Cannot modify merged key - edit the anchor it was merged from");
+        }
+        Ok(())
+    }
 
-                    // Empty array/JSONL or root-level array/JSONL: add inside it
-                    // Ensure the container is expanded so the new child will be visible
-                    if !self.tree_view().is_expanded(&current_path) {
-                        self.tree_view_mut().toggle_expand(&current_path);
-                    }
+    /// Returns the number of spaces per indentation level.
+    pub fn indent_size(&self) -> usize {
+        self.indent_size
+    }
 
-                    let mut insertion_path = current_path.clone();
-                    insertion_path.push(insert_index); // Insert at end
+    /// Sets the number of spaces per indentation level.
+    pub fn set_indent_size(&mut self, size: usize) {
+        self.indent_size = size;
+    }
 
-                    match self
-                        .tree
-                        .insert_node_in_array(&insertion_path, container_node)
-                    {
-                        Ok(_) => {
-                            self.tree_view_mut()
-                                .update_paths_after_insertion(&insertion_path);
-                            self.rebuild_tree_view();
-                            self.cursor.set_path(insertion_path.clone());
-                            self.mark_dirty();
-                            self.checkpoint();
+    /// Returns the JSON Schema path associated with this buffer, if any.
+    pub fn schema_path(&self) -> Option<&str> {
+        self.schema_path.as_deref()
+    }
 
-                            let msg = if is_object {
-                                "Added empty object"
-                            } else {
-                                "Added empty array"
-                            };
-                            self.set_message(msg.to_string(), MessageLevel::Info);
-                        }
-                        Err(e) => {
-                            self.set_message(format!("Add failed: {}", e), MessageLevel::Error);
-                        }
-                    }
-                    return;
-                }
-                YamlValue::Object(entries) if entries.is_empty() || current_path.is_empty() => {
-                    let insert_index = entries.len(); // Get length before mutable borrow
+    /// Sets the JSON Schema path associated with this buffer.
+    pub fn set_schema_path(&mut self, path: Option<String>) {
+        self.schema_path = path;
+        self.schedule_revalidation();
+    }
 
-                    // Empty object or root-level object: add inside it
-                    // Ensure the container is expanded so the new child will be visible
-                    if !self.tree_view().is_expanded(&current_path) {
-                        self.tree_view_mut().toggle_expand(&current_path);
-                    }
+    /// Returns whether the document is re-validated in idle time after edits.
+    pub fn auto_validate(&self) -> bool {
+        self.auto_validate
+    }
 
-                    self.add_mode_stage = AddModeStage::AwaitingKey;
-                    self.clear_add_key_buffer(); // Reset buffer and cursor
-                    let mut insertion_path = current_path.clone();
-                    insertion_path.push(insert_index); // Insert at end
-                    self.add_insertion_point = Some(insertion_path);
-                    // For containers in objects, we need a key
-                    // Store the container temporarily and wait for key
-                    self.temp_container = Some(container_node);
-                    self.reset_cursor_blink();
-                    return;
-                }
-                _ => {
-                    // Non-empty container at non-root or scalar: fall through to add sibling
-                }
+    /// Turns idle-time schema re-validation on or off.
+    ///
+    /// Turning it on queues a full validation of the document.
+    pub fn set_auto_validate(&mut self, enable: bool) {
+        if self.auto_validate != enable {
+            self.auto_validate = enable;
+            self.schedule_revalidation();
+        }
+    }
+
+    /// Queues validation of the document against the buffer's schema, to be
+    /// run by [`EditorState::run_idle_validation`].
+    ///
+    /// Does nothing unless `autovalidate` is on and a schema is set.
+    fn schedule_revalidation(&mut self) {
+        let schema_path = match (&self.schema_path, self.auto_validate) {
+            (Some(path), true) => self.resolve_relative_path(path),
+            _ => {
+                self.revalidation.cancel();
+                return;
+            }
+        };
+        match self.revalidation.schema(&schema_path) {
+            Ok(schema) => {
+                let units = schema.units(&self.tree);
+                let first = self.cursor.path().first().copied();
+                self.revalidation.schedule(units, first);
+            }
+            Err(e) => {
+                self.revalidation.cancel();
+                self.set_message(format!("Schema: {:#}", e), MessageLevel::Error);
             }
         }
+    }
 
-        // Current node is either a scalar or non-empty container
-        // Add sibling after it in parent container
-        let parent_path = &current_path[..current_path.len() - 1];
-        let current_index = current_path[current_path.len() - 1];
+    /// Returns true while queued validation is waiting for idle time.
+    pub fn revalidation_pending(&self) -> bool {
+        self.revalidation.is_pending()
+    }
 
-        let parent = if parent_path.is_empty() {
-            self.tree.root()
-        } else {
-            match self.tree.get_node(parent_path) {
-                Some(node) => node,
-                None => {
-                    self.set_message("Invalid cursor position".to_string(), MessageLevel::Error);
-                    return;
-                }
+    /// Runs queued schema validation until `budget` has elapsed.
+    ///
+    /// Called by the event loop when no input arrived. At least one unit
+    /// runs per call, so validation always makes progress. Returns true if
+    /// any work was done.
+    pub fn run_idle_validation(&mut self, budget: std::time::Duration) -> bool {
+        use crate::schema::SCHEMA_SOURCE;
+
+        if !self.revalidation.is_pending() {
+            return false;
+        }
+        let start = std::time::Instant::now();
+        while let Some(unit) = self.revalidation.take_next() {
+            let Some(schema) = self.revalidation.cached_schema() else {
+                break;
+            };
+            let diagnostics = schema.validate_unit(&self.tree, unit);
+            let (path, subtree) = unit.scope();
+            self.diagnostics
+                .replace_at(SCHEMA_SOURCE, &path, subtree, diagnostics);
+            if start.elapsed() >= budget {
+                break;
             }
+        }
+        self.tree_view
+            .set_diagnostic_markers(self.diagnostics.markers());
+        self.track_nodes();
+        true
+    }
+
+    /// Indexes the `.Values` references of a Helm chart's templates.
+    ///
+    /// The chart is `chart_dir`, or else the directory of this values file.
+    fn helm_index(&self, chart_dir: Option<&str>) -> anyhow::Result<super::helm::HelmIndex> {
+        let dir = match chart_dir {
+            Some(dir) => std::path::PathBuf::from(dir),
+            None => self
+                .file_dir()
+                .ok_or_else(|| anyhow::anyhow!("No file name (use :usages <chart-dir>)"))?,
         };
+        super::helm::HelmIndex::build(&dir)
+    }
 
-        let mut path = parent_path.to_vec();
-        path.push(current_index + 1);
+    /// Lists the chart templates that read the values key under the cursor
+    /// (`:usages`).
+    pub fn helm_usages(
+        &self,
+        chart_dir: Option<&str>,
+    ) -> anyhow::Result<Vec<super::helm::Reference>> {
+        let index = self.helm_index(chart_dir)?;
+        let keys = self.key_path_segments(self.cursor.path());
+        Ok(index.usages(&keys).into_iter().cloned().collect())
+    }
 
-        match parent.value() {
-            YamlValue::Object(_) => {
-                self.add_mode_stage = AddModeStage::AwaitingKey;
-                self.clear_add_key_buffer(); // Reset buffer and cursor
-                self.add_insertion_point = Some(path);
-                // For containers in objects, we need a key
-                // Store the container temporarily and wait for key
-                self.temp_container = Some(container_node);
-                self.reset_cursor_blink();
-            }
-            YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
-                // Insert directly into array/JSONL (no key needed)
-                match self.tree.insert_node_in_array(&path, container_node) {
-                    Ok(_) => {
-                        self.tree_view_mut().update_paths_after_insertion(&path);
-                        self.rebuild_tree_view();
-                        self.cursor.set_path(path.clone());
-                        self.mark_dirty();
-                        self.checkpoint();
+    /// Whether a bare `:usages` searches for the value under the cursor
+    /// rather than the Helm templates reading its key: it does on a scalar,
+    /// unless the file is in a chart with a `templates` directory.
+    pub fn usages_searches_value(&self) -> bool {
+        let on_scalar = self
+            .tree
+            .get_node(self.cursor.path())
+            .is_some_and(|node| super::usages::scalar_text(node.value()).is_some());
+        let in_chart = self
+            .file_dir()
+            .is_some_and(|dir| dir.join("templates").is_dir());
+        on_scalar && !in_chart
+    }
+
+    /// Finds the other uses of the scalar value under the cursor in this
+    /// document and, with `project`, in the YAML files of the project, and
+    /// shows them in the `:usages` panel. Returns the value and the number
+    /// of other uses; the panel is only opened if there are some.
+    pub fn find_value_usages(&mut self, project: bool) -> anyhow::Result<(String, usize)> {
+        use super::usages::{find, find_in_files, scalar_text, UsagesPanelState};
+
+        let text = self
+            .tree
+            .get_node(self.cursor.path())
+            .and_then(|node| scalar_text(node.value()))
+            .ok_or_else(|| anyhow::anyhow!("Not on a scalar value"))?;
+        let mut usages = find(self.tree.root(), &text);
+        usages.retain(|usage| usage.path != self.cursor.path());
+        if project {
+            let dir = self.file_dir().ok_or_else(|| {
+                anyhow::anyhow!("No file name (project search needs a saved file)")
+            })?;
+            let root = super::bookmarks::project_root(&dir);
+            let this = self.filename.as_deref().map(std::path::Path::new);
+            usages.extend(find_in_files(&root, this, &text));
+        }
+        let count = usages.len();
+        if count > 0 {
+            self.usages_panel = Some(UsagesPanelState::new(text.clone(), usages));
+        }
+        Ok((text, count))
+    }
+
+    /// Previews extracting the repeated value under the cursor as the
+    /// anchor `name` (`:extract`), defined in the top-level mapping `key`
+    /// or else at its first use. The affected paths are listed in the
+    /// `:usages` panel, where `Enter` applies the extraction. Returns the
+    /// value and the number of uses that become aliases.
+    pub fn preview_extract(
+        &mut self,
+        name: &str,
+        key: Option<&str>,
+    ) -> anyhow::Result<(String, usize)> {
+        use super::usages::{plan_extract, scalar_text, UsagesPanelState};
+
+        self.ensure_writable()?;
+        let (extraction, rows) = plan_extract(&self.tree, self.cursor.path(), name, key)?;
+        let text = self
+            .tree
+            .get_node(&extraction.source)
+            .and_then(|node| scalar_text(node.value()))
+            .unwrap_or_default();
+        let count = extraction.aliases.len();
+        self.usages_panel = Some(UsagesPanelState::preview(text.clone(), extraction, rows));
+        Ok((text, count))
+    }
 
-                        let msg = if is_object {
-                            "Added empty object"
-                        } else {
-                            "Added empty array"
-                        };
-                        self.set_message(msg.to_string(), MessageLevel::Info);
-                    }
-                    Err(e) => {
-                        self.set_message(format!("Add failed: {}", e), MessageLevel::Error);
-                    }
+    /// Applies a previewed `:extract` as one undo step.
+    fn apply_extraction(&mut self, extraction: super::usages::Extraction) -> anyhow::Result<()> {
+        use crate::document::node::{YamlNode, YamlValue};
+
+        self.ensure_writable()?;
+        let name = extraction.name;
+        let mut definition = self
+            .tree
+            .get_node(&extraction.source)
+            .ok_or_else(|| anyhow::anyhow!("The value has moved; run :extract again"))?
+            .clone();
+        for path in &extraction.aliases {
+            if let Some(node) = self.tree.get_node_mut(path) {
+                let mut alias = YamlNode::new(YamlValue::Alias(name.clone()));
+                alias.set_alias_target(Some(name.clone()));
+                *node = alias;
+            }
+        }
+        match extraction.key {
+            Some(key) => {
+                definition.set_anchor(Some(name.clone()));
+                let YamlValue::Object(entries) = self.tree.root_mut().value_mut() else {
+                    anyhow::bail!("Variables need a mapping at the top level");
+                };
+                if !entries.contains_key(&key) {
+                    // A new variables mapping goes first, ahead of every alias
+                    let vars = YamlNode::new(YamlValue::Object(indexmap::IndexMap::new()));
+                    entries.shift_insert(0, key.clone(), vars);
+                }
+                if let Some(YamlValue::Object(vars)) =
+                    entries.get_mut(&key).map(|vars| vars.value_mut())
+                {
+                    vars.insert(name, definition);
                 }
             }
-            _ => {
-                self.set_message("Parent is not a container".to_string(), MessageLevel::Error);
+            None => {
+                if let Some(node) = self.tree.get_node_mut(&extraction.source) {
+                    node.set_anchor(Some(name));
+                }
             }
         }
+        self.tree.build_anchor_registry();
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        Ok(())
     }
 
-    /// Starts a rename operation on the current object key.
-    ///
-    /// Checks if the cursor is on an object key (not array element, not root),
-    /// then enters Insert mode with the current key name pre-populated in the
-    /// edit buffer.
-    pub fn start_rename_operation(&mut self) {
-        use crate::document::node::YamlValue;
+    /// Returns the `:usages` panel, if open.
+    pub fn usages_panel(&self) -> Option<&super::usages::UsagesPanelState> {
+        self.usages_panel.as_ref()
+    }
 
-        // Clear any previous messages so the edit area is visible
-        self.clear_message();
+    /// Returns the `:usages` panel mutably, if open.
+    pub fn usages_panel_mut(&mut self) -> Option<&mut super::usages::UsagesPanelState> {
+        self.usages_panel.as_mut()
+    }
 
-        let current_path = self.cursor.path().to_vec();
+    /// Closes the `:usages` panel without jumping.
+    pub fn close_usages_panel(&mut self) {
+        self.usages_panel = None;
+    }
 
-        // Can't rename root
-        if current_path.is_empty() {
-            self.set_message("Cannot rename root node".to_string(), MessageLevel::Error);
+    /// Closes the `:usages` panel and jumps to the selected use, opening its
+    /// file if it's in another one. A previewed `:extract` is applied instead.
+    pub fn usages_panel_select(&mut self) {
+        let Some(panel) = self.usages_panel.take() else {
+            return;
+        };
+        if let Some(extraction) = panel.extraction {
+            let (name, count) = (extraction.name.clone(), extraction.aliases.len());
+            match self.apply_extraction(extraction) {
+                Ok(()) => self.set_message(
+                    format!(
+                        "Extracted {} as &{} ({} alias{})",
+                        panel.value,
+                        name,
+                        count,
+                        if count == 1 { "" } else { "es" }
+                    ),
+                    MessageLevel::Info,
+                ),
+                Err(e) => self.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return;
+        }
+        let Some(usage) = panel.selected().cloned() else {
             return;
+        };
+        match usage.file {
+            Some(file) => self.request_buffer_command(crate::app::BufferCommand::JumpPath {
+                file: file.to_string_lossy().to_string(),
+                path: usage.yamlpath,
+            }),
+            None => {
+                self.record_jump();
+                self.reveal_path(usage.path);
+            }
         }
+    }
 
-        // Get parent to check if it's an object
-        let parent_path = &current_path[..current_path.len() - 1];
-        let current_index = current_path[current_path.len() - 1];
+    /// Flags the values keys no chart template reads with a warning in the
+    /// gutter (`:usages unused`). Returns the dotted names of those keys.
+    pub fn flag_unused_values(&mut self, chart_dir: Option<&str>) -> anyhow::Result<Vec<String>> {
+        use super::helm::HELM_SOURCE;
 
-        let parent = if parent_path.is_empty() {
-            self.tree.root()
-        } else {
-            match self.tree.get_node(parent_path) {
-                Some(node) => node,
-                None => {
-                    self.set_message("Invalid cursor position".to_string(), MessageLevel::Error);
-                    return;
-                }
-            }
+        let index = self.helm_index(chart_dir)?;
+        let unused = index.unused(self.tree.root());
+        let diagnostics = unused
+            .iter()
+            .map(|(path, name)| {
+                Diagnostic::warning(
+                    HELM_SOURCE,
+                    path.clone(),
+                    format!("$.{}", name),
+                    "Not used by any template".to_string(),
+                )
+            })
+            .collect();
+        self.set_diagnostics(HELM_SOURCE, diagnostics);
+        Ok(unused.into_iter().map(|(_, name)| name).collect())
+    }
+
+    /// Returns the diagnostics currently reported for the document.
+    pub fn diagnostics(&self) -> &DiagnosticList {
+        &self.diagnostics
+    }
+
+    /// Replaces the diagnostics from one source and refreshes the gutter markers.
+    pub fn set_diagnostics(&mut self, source: &str, diagnostics: Vec<Diagnostic>) {
+        self.diagnostics.replace_source(source, diagnostics);
+        self.tree_view
+            .set_diagnostic_markers(self.diagnostics.markers());
+        self.track_nodes();
+    }
+
+    /// Removes all diagnostics and gutter markers.
+    pub fn clear_diagnostics(&mut self) {
+        self.diagnostics.clear();
+        self.tree_view.set_diagnostic_markers(Default::default());
+    }
+
+    /// Validates the document against a JSON Schema file.
+    ///
+    /// Relative schema paths are resolved against the directory of the file
+    /// being edited. On success the schema diagnostics are replaced and a
+    /// summary is shown in the message area; the first violation is included
+    /// so that a single problem can be fixed without opening a list. The
+    /// schema becomes the buffer's schema, used by `:set autovalidate`.
+    pub fn validate_schema(&mut self, schema_path: &str) -> anyhow::Result<usize> {
+        use crate::schema::{Schema, SCHEMA_SOURCE};
+
+        let resolved = self.resolve_relative_path(schema_path);
+        let schema = Schema::load(&resolved)?;
+        let diagnostics = schema.validate(&self.tree);
+        let count = diagnostics.len();
+        let first = diagnostics.first().map(|d| d.to_string());
+        // `]q` steps through the violations
+        self.quickfix = Some(super::quickfix::QuickfixList::new(
+            "Schema",
+            diagnostics.iter().cloned().map(Into::into).collect(),
+        ));
+        self.set_diagnostics(SCHEMA_SOURCE, diagnostics);
+        // Later edits are checked against this schema with `autovalidate`;
+        // the document as it stands is already done.
+        self.schema_path = Some(schema_path.to_string());
+        self.revalidation.cancel();
+
+        match first {
+            None => self.set_message(
+                format!("Schema: {} is valid", self.filename().unwrap_or("document")),
+                MessageLevel::Info,
+            ),
+            Some(first) => self.set_message(
+                format!("Schema: {} (first: {})", self.diagnostics.summary(), first),
+                MessageLevel::Error,
+            ),
+        }
+        Ok(count)
+    }
+
+    /// Resolves a path relative to the directory of the current file.
+    ///
+    /// Absolute paths, and all paths when no file name is set, are returned unchanged.
+    pub fn resolve_relative_path(&self, path: &str) -> std::path::PathBuf {
+        let candidate = std::path::Path::new(path);
+        if candidate.is_absolute() {
+            return candidate.to_path_buf();
+        }
+        match self
+            .filename
+            .as_deref()
+            .and_then(|f| std::path::Path::new(f).parent())
+        {
+            Some(dir) => dir.join(candidate),
+            None => candidate.to_path_buf(),
+        }
+    }
+
+    /// Returns the name of the active theme.
+    pub fn current_theme(&self) -> &str {
+        &self.current_theme
+    }
+
+    /// Returns the colors of the active theme.
+    pub fn theme_colors(&self) -> crate::theme::colors::ThemeColors {
+        crate::theme::get_theme(&self.current_theme)
+            .map(|theme| theme.colors)
+            .unwrap_or_else(crate::theme::colors::ThemeColors::default_dark)
+    }
+
+    /// Writes an HTML rendering of the tree, as currently folded, to `path`.
+    pub fn export_html(&self, path: &str) -> anyhow::Result<()> {
+        let title = self.filename().unwrap_or("[stdin]");
+        let html = crate::export::render_html(&self.tree_view, &self.theme_colors(), title);
+        std::fs::write(path, html).map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path, e))
+    }
+
+    /// Writes a Markdown outline of the document's keys to `path`.
+    pub fn export_outline(
+        &self,
+        path: &str,
+        options: &crate::export::outline::OutlineOptions,
+    ) -> anyhow::Result<()> {
+        let outline = crate::export::outline::render_outline(&self.tree, options);
+        std::fs::write(path, outline)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path, e))
+    }
+
+    /// Applies display and editing settings from a config.
+    ///
+    /// The theme is not changed here; the CLI `--theme` flag takes precedence
+    /// over the config file at startup.
+    pub fn apply_config(&mut self, config: &Config) {
+        self.show_line_numbers = config.show_line_numbers;
+        self.relative_line_numbers = config.relative_line_numbers;
+        self.enable_mouse = config.enable_mouse;
+        self.create_backup = config.create_backup;
+        self.persistent_undo = config.persistent_undo;
+        self.reload_on_change = config.reload_on_change;
+        self.regex_search = config.regex_search;
+        self.search_collapsed = config.search_collapsed;
+        self.set_show_breadcrumbs(config.show_breadcrumbs);
+        self.set_show_type_badges(config.show_type_badges);
+        self.set_cloudformation(config.cloudformation);
+        self.set_prometheus(config.prometheus);
+        self.set_decode_secrets(config.decode_secrets);
+        self.k8s = config.k8s;
+        self.set_auto_validate(config.auto_validate);
+        self.scrolloff = config.scrolloff;
+        self.set_max_render_depth(config.max_render_depth);
+        self.mouse_scroll_lines = config.mouse_scroll_lines;
+        self.set_memory_budget(config.memory_budget_mb);
+        self.lazy_load_threshold = config.lazy_load_threshold;
+        self.load_limits = config.load_limits();
+        self.undo_file_limit_mb = config.undo_file_limit_mb;
+        self.yaml_style = config.yaml_style.clone();
+        self.json_style = config.json_style.clone();
+        self.statusline = config.statusline.clone();
+        self.lint_disabled = config.lint_disabled.clone();
+        self.lint_line_length = config.lint_line_length;
+        if self.tree_view.preview_fields() != config.preview_fields.as_slice() {
+            self.tree_view
+                .set_preview_fields(config.preview_fields.clone());
+            self.rebuild_tree_view();
+        }
+        self.indent_size = config.indent_size;
+        if self.resolve_merge() != config.resolve_merge_keys {
+            self.set_resolve_merge(config.resolve_merge_keys);
+        }
+    }
+
+    /// Returns the trust store used to gate project configs and modelines.
+    pub fn trust_store(&self) -> &TrustStore {
+        &self.trust_store
+    }
+
+    /// Sets the trust store used to gate project configs and modelines.
+    pub fn set_trust_store(&mut self, store: TrustStore) {
+        self.trust_store = store;
+    }
+
+    /// Returns the project config applied to the current buffer, if any.
+    pub fn project_config(&self) -> Option<&ProjectConfig> {
+        self.project_config.as_ref()
+    }
+
+    /// Returns the absolute directory of the file being edited.
+    fn file_dir(&self) -> Option<std::path::PathBuf> {
+        let filename = self.filename.as_deref()?;
+        let path = std::path::Path::new(filename);
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir().ok()?.join(path)
         };
+        path.parent().map(|dir| dir.to_path_buf())
+    }
 
-        // Check if parent is an object
-        if let YamlValue::Object(entries) = parent.value() {
-            // Get the current key name
-            if let Some((key, _)) = entries.get_index(current_index) {
-                let key_name = key.clone();
+    /// Applies the project config and modeline for the current file, if trusted.
+    ///
+    /// Settings from the previous buffer's project config and modeline are
+    /// reverted first. A `.yamlquill.toml` is applied only if its directory was
+    /// trusted with its current contents; a modeline only if the file's
+    /// directory is trusted. Anything skipped is reported with a hint to `:trust`.
+    pub fn load_buffer_settings(&mut self) {
+        self.clear_modeline();
+        self.clear_project_config();
+        if self.safe_mode {
+            return;
+        }
 
-                // Enter rename mode with key name in edit buffer
-                self.is_renaming_key = true;
-                self.rename_original_key = Some(key_name.clone());
-                self.edit_buffer = Some(key_name.clone());
-                self.edit_cursor = key_name.len();
-                self.set_mode(EditorMode::Insert);
-                self.reset_cursor_blink();
-                self.set_message("-- RENAME --".to_string(), MessageLevel::Info);
+        let Some(dir) = self.file_dir() else {
+            return;
+        };
+        let mut ignored = Vec::new();
+
+        match ProjectConfig::discover(&dir) {
+            Some(Ok(project)) => {
+                if self
+                    .trust_store
+                    .is_config_trusted(project.dir(), &project.contents)
+                {
+                    if let Err(e) = self.apply_project_config(project) {
+                        self.set_message(format!("{}", e), MessageLevel::Error);
+                        return;
+                    }
+                } else {
+                    ignored.push(PROJECT_CONFIG_FILE);
+                }
+            }
+            Some(Err(e)) => {
+                self.set_message(format!("{}", e), MessageLevel::Error);
+                return;
+            }
+            None => {}
+        }
+
+        let has_modeline = self
+            .tree
+            .original_source()
+            .and_then(parse_modeline)
+            .is_some();
+        if has_modeline {
+            if self.trust_store.is_trusted(&dir) {
+                self.apply_modeline();
             } else {
-                self.set_message("Invalid object index".to_string(), MessageLevel::Error);
+                ignored.push("modeline");
+            }
+        }
+
+        if !ignored.is_empty() {
+            self.set_message(
+                format!(
+                    "Ignored untrusted {} in {} (:trust to allow)",
+                    ignored.join(" and "),
+                    dir.display()
+                ),
+                MessageLevel::Warning,
+            );
+        }
+    }
+
+    fn apply_project_config(&mut self, project: ProjectConfig) -> anyhow::Result<()> {
+        let defaults = self.to_config();
+        let merged = project.apply_to(&defaults)?;
+        self.apply_config(&merged);
+        if merged.theme != self.current_theme && crate::theme::get_theme(&merged.theme).is_some() {
+            self.request_theme_change(merged.theme.clone());
+        }
+        self.project_defaults = Some(defaults);
+        self.project_config = Some(project);
+        Ok(())
+    }
+
+    /// Reverts any settings applied by the current buffer's project config.
+    pub fn clear_project_config(&mut self) {
+        let (Some(project), Some(defaults)) =
+            (self.project_config.take(), self.project_defaults.take())
+        else {
+            return;
+        };
+        let mut config = self.to_config();
+        if project.revert(&mut config, &defaults).is_ok() {
+            self.apply_config(&config);
+            if config.theme != self.current_theme {
+                self.request_theme_change(config.theme);
+            }
+        }
+    }
+
+    /// Trusts the current project (or the file's directory) and applies its settings.
+    ///
+    /// The project root is the directory holding the nearest `.yamlquill.toml`,
+    /// or the file's own directory if there is none. Returns the trusted directory.
+    pub fn trust_current_location(&mut self) -> anyhow::Result<std::path::PathBuf> {
+        let dir = self
+            .file_dir()
+            .ok_or_else(|| anyhow::anyhow!("No file name"))?;
+        let project = ProjectConfig::discover(&dir).transpose()?;
+        let root = project
+            .as_ref()
+            .map(|p| p.dir().to_path_buf())
+            .unwrap_or(dir);
+
+        self.trust_store
+            .trust(&root, project.as_ref().map(|p| p.contents.as_str()));
+        self.trust_store.save()?;
+        self.load_buffer_settings();
+        // History only fits the file as opened, before any edit
+        if self.undo_tree.len() == 1 {
+            self.load_undo_history();
+        }
+        Ok(root)
+    }
+
+    /// Revokes trust for the current project and reverts its settings.
+    ///
+    /// Returns the directory whose trust was revoked, or `None` if it wasn't trusted.
+    pub fn untrust_current_location(&mut self) -> anyhow::Result<Option<std::path::PathBuf>> {
+        let dir = self
+            .file_dir()
+            .ok_or_else(|| anyhow::anyhow!("No file name"))?;
+        let root = match ProjectConfig::discover(&dir) {
+            Some(Ok(project)) => project.dir().to_path_buf(),
+            _ => dir,
+        };
+
+        if !self.trust_store.revoke(&root) {
+            return Ok(None);
+        }
+        self.trust_store.save()?;
+        self.clear_modeline();
+        self.clear_project_config();
+        Ok(Some(root))
+    }
+
+    /// Returns the modeline applied to the current buffer, if any.
+    pub fn modeline(&self) -> Option<&Modeline> {
+        self.modeline.as_ref()
+    }
+
+    /// Applies the `# yamlquill:` modeline from the document source, if present.
+    ///
+    /// Settings from a previously applied modeline are reverted first, so
+    /// modeline options only ever affect the buffer they came from. Options
+    /// outside the allowlist are ignored and reported as a warning.
+    pub fn apply_modeline(&mut self) {
+        self.clear_modeline();
+
+        let Some(modeline) = self.tree.original_source().and_then(parse_modeline) else {
+            return;
+        };
+
+        self.modeline_defaults = Some(self.to_config());
+        for option in &modeline.options {
+            match option {
+                ModelineOption::Indent(size) => self.indent_size = *size,
+                ModelineOption::FoldLevel(level) => {
+                    self.tree_view.set_fold_level(&self.tree, *level);
+                    self.rebuild_tree_view();
+                    if let Some(first_line) = self.tree_view.lines().first() {
+                        self.cursor.set_path(first_line.path.clone());
+                    }
+                }
+                ModelineOption::Schema(path) => self.set_schema_path(Some(path.clone())),
+                ModelineOption::Number(on) => self.show_line_numbers = *on,
+                ModelineOption::RelativeNumber(on) => self.relative_line_numbers = *on,
+                ModelineOption::ResolveMerge(on) => self.set_resolve_merge(*on),
+            }
+        }
+
+        if !modeline.rejected.is_empty() {
+            self.set_message(
+                format!(
+                    "Modeline: ignored unsupported option(s): {}",
+                    modeline.rejected.join(" ")
+                ),
+                MessageLevel::Warning,
+            );
+        }
+        self.modeline = Some(modeline);
+    }
+
+    /// Reverts any settings applied by the current buffer's modeline.
+    pub fn clear_modeline(&mut self) {
+        let (Some(modeline), Some(defaults)) =
+            (self.modeline.take(), self.modeline_defaults.take())
+        else {
+            return;
+        };
+
+        for option in &modeline.options {
+            match option {
+                ModelineOption::Indent(_) => self.indent_size = defaults.indent_size,
+                ModelineOption::Schema(_) => self.set_schema_path(None),
+                ModelineOption::Number(_) => self.show_line_numbers = defaults.show_line_numbers,
+                ModelineOption::RelativeNumber(_) => {
+                    self.relative_line_numbers = defaults.relative_line_numbers
+                }
+                ModelineOption::ResolveMerge(_) => {
+                    self.set_resolve_merge(defaults.resolve_merge_keys)
+                }
+                ModelineOption::FoldLevel(_) => {}
+            }
+        }
+    }
+
+    /// Returns a Config object with the current editor settings.
+    pub fn to_config(&self) -> crate::config::Config {
+        Config {
+            theme: self.current_theme.clone(),
+            indent_size: self.indent_size,
+            show_line_numbers: self.show_line_numbers,
+            relative_line_numbers: self.relative_line_numbers,
+            enable_mouse: self.enable_mouse,
+            create_backup: self.create_backup,
+            persistent_undo: self.persistent_undo,
+            reload_on_change: self.reload_on_change,
+            regex_search: self.regex_search,
+            search_collapsed: self.search_collapsed,
+            show_breadcrumbs: self.show_breadcrumbs,
+            show_type_badges: self.show_type_badges(),
+            cloudformation: self.cloudformation(),
+            prometheus: self.prometheus(),
+            decode_secrets: self.decode_secrets(),
+            k8s: self.k8s,
+            auto_validate: self.auto_validate,
+            scrolloff: self.scrolloff,
+            max_render_depth: self.max_render_depth(),
+            mouse_scroll_lines: self.mouse_scroll_lines,
+            memory_budget_mb: self.memory_budget_mb,
+            lazy_load_threshold: self.lazy_load_threshold,
+            max_file_size_mb: self.load_limits.max_file_size_mb,
+            max_parse_depth: self.load_limits.max_depth,
+            max_node_count: self.load_limits.max_nodes,
+            max_alias_expansion: self.load_limits.max_alias_expansion,
+            undo_file_limit_mb: self.undo_file_limit_mb,
+            yaml_style: self.yaml_style.clone(),
+            json_style: self.json_style.clone(),
+            preview_fields: self.tree_view.preview_fields().to_vec(),
+            statusline: self.statusline.clone(),
+            lint_disabled: self.lint_disabled.clone(),
+            lint_line_length: self.lint_line_length,
+            resolve_merge_keys: self.resolve_merge(),
+            ..Config::default()
+        }
+    }
+
+    /// Saves current settings to the config file.
+    ///
+    /// Settings that came from a modeline or project config are buffer-local,
+    /// so the user's own values are written instead.
+    pub fn save_config(&self) -> anyhow::Result<()> {
+        if self.safe_mode {
+            anyhow::bail!("Settings aren't saved in safe mode (--clean)");
+        }
+        self.user_config()?.save()
+    }
+
+    /// Returns the current settings without those that came from a modeline
+    /// or project config.
+    fn user_config(&self) -> anyhow::Result<Config> {
+        let mut config = self.to_config();
+        if let (Some(modeline), Some(defaults)) = (&self.modeline, &self.modeline_defaults) {
+            modeline.revert(&mut config, defaults);
+        }
+        if let (Some(project), Some(defaults)) = (&self.project_config, &self.project_defaults) {
+            project.revert(&mut config, defaults)?;
+        }
+        Ok(config)
+    }
+
+    /// Writes the current settings, theme and pinned preview fields to a
+    /// profile file (`:profile export`).
+    ///
+    /// Like `:set save`, settings from a modeline or project config are left
+    /// out in favor of the user's own.
+    pub fn export_profile(&self, path: &str) -> anyhow::Result<()> {
+        let text = crate::config::profile::export(&self.user_config()?)?;
+        std::fs::write(path, text).map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path, e))
+    }
+
+    /// Applies the settings in a profile file (`:profile import`) and
+    /// returns what changed. Nothing is applied if a setting is invalid.
+    pub fn import_profile(&mut self, path: &str) -> anyhow::Result<crate::config::profile::Import> {
+        use crate::config::profile::Profile;
+
+        let import = Profile::load(std::path::Path::new(path))?.import(&self.to_config())?;
+        let theme = &import.config.theme;
+        if crate::theme::get_theme(theme).is_none() {
+            anyhow::bail!("Unknown theme '{}' in {}", theme, path);
+        }
+        self.apply_config(&import.config);
+        if *theme != self.current_theme {
+            self.request_theme_change(theme.clone());
+        }
+        Ok(import)
+    }
+
+    /// Returns the current edit buffer content, if editing.
+    pub fn edit_buffer(&self) -> Option<&str> {
+        self.edit_buffer.as_deref()
+    }
+
+    /// Returns the multi-line editor, if a string spanning lines is being edited.
+    pub fn multiline_edit(&self) -> Option<&MultilineBuffer> {
+        self.multiline_edit.as_ref()
+    }
+
+    /// Returns the multi-line editor for typing into, if open.
+    pub fn multiline_edit_mut(&mut self) -> Option<&mut MultilineBuffer> {
+        self.reset_cursor_blink();
+        self.multiline_edit.as_mut()
+    }
+
+    /// Commits the multi-line editor's text to the node at the cursor.
+    ///
+    /// The string keeps its style (literal, folded or plain).
+    pub fn commit_multiline_edit(&mut self) -> anyhow::Result<()> {
+        let buffer = self
+            .multiline_edit
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("No active edit buffer"))?;
+        self.edit_buffer = Some(buffer.text());
+        let result = self.commit_editing();
+        self.edit_buffer = None;
+        result
+    }
+
+    /// Starts editing the node at the current cursor position.
+    /// Starts with an empty buffer for typing a new value.
+    ///
+    /// Strings that span lines (block scalars) open the multi-line editor
+    /// instead of the edit prompt.
+    pub fn start_editing(&mut self) {
+        if self.reject_read_only() {
+            return;
+        }
+        self.reset_completion();
+        if let Err(e) = self.ensure_not_merged() {
+            self.set_message(e.to_string(), MessageLevel::Error);
+            return;
+        }
+        let path = self.cursor.path();
+        if let Some(node) = self.tree.get_node(path) {
+            // Check if node is an alias - aliases are read-only
+            if let crate::document::node::YamlValue::Alias(name) = node.value() {
+                let anchor_name = node.alias_target().unwrap_or(name);
+                self.set_message(
+                    format!(
+                        "Cannot edit alias - navigate to anchor definition (&{}) to edit",
+                        anchor_name
+                    ),
+                    MessageLevel::Error,
+                );
+                return;
+            }
+
+            // Check if node is editable (not a container)
+            match node.value() {
+                crate::document::node::YamlValue::Object(_)
+                | crate::document::node::YamlValue::Array(_)
+                | crate::document::node::YamlValue::MultiDoc(_)
+                | crate::document::node::YamlValue::Deferred(_) => {
+                    // Can't edit containers
+                }
+                crate::document::node::YamlValue::String(s) if s.is_multiline() => {
+                    self.multiline_edit = Some(MultilineBuffer::new(s.as_str()));
+                    self.reset_cursor_blink();
+                }
+                crate::document::node::YamlValue::String(s) => {
+                    // Pre-populate with current string value (without JSON quotes)
+                    let content = s.as_str().to_string();
+                    self.edit_cursor = content.len();
+                    self.edit_buffer = Some(content);
+                    self.reset_cursor_blink();
+                }
+                crate::document::node::YamlValue::Number(n) => {
+                    // Pre-populate with current number value
+                    let num_str = match n {
+                        YamlNumber::Integer(i) => i.to_string(),
+                        YamlNumber::Float(f) => {
+                            if f.fract() == 0.0 && f.is_finite() {
+                                format!("{:.0}", f)
+                            } else {
+                                f.to_string()
+                            }
+                        }
+                    };
+                    self.edit_cursor = num_str.len();
+                    self.edit_buffer = Some(num_str);
+                    self.reset_cursor_blink();
+                }
+                crate::document::node::YamlValue::Boolean(b) => {
+                    // Pre-populate with current boolean value
+                    let content = b.to_string();
+                    self.edit_cursor = content.len();
+                    self.edit_buffer = Some(content);
+                    self.reset_cursor_blink();
+                }
+                crate::document::node::YamlValue::Null => {
+                    // Pre-populate with "null"
+                    self.edit_cursor = 4; // "null".len()
+                    self.edit_buffer = Some("null".to_string());
+                    self.reset_cursor_blink();
+                }
+                crate::document::node::YamlValue::Alias(_) => {
+                    // Unreachable - handled above with error message
+                    unreachable!("Alias editing should be blocked earlier");
+                }
+                crate::document::node::YamlValue::Comment(c) => {
+                    // Pre-populate with current comment text
+                    let content = c.content().to_string();
+                    self.edit_cursor = content.len();
+                    self.edit_buffer = Some(content);
+                    self.reset_cursor_blink();
+                }
+            }
+        }
+    }
+
+    /// Validate that the edit buffer content is valid for the given value type.
+    /// Returns Ok(()) if valid, Err with message if invalid.
+    fn validate_edit_input(buffer: &str, original_value: &YamlValue) -> anyhow::Result<()> {
+        use anyhow::anyhow;
+
+        // Special case: "null" is always valid (converts any type to Null)
+        if buffer == "null" {
+            return Ok(());
+        }
+
+        match original_value {
+            YamlValue::Number(_) => {
+                // Try parsing as integer or float
+                if buffer.parse::<i64>().is_err() && buffer.parse::<f64>().is_err() {
+                    return Err(anyhow!("Invalid number format: '{}'", buffer));
+                }
+            }
+            YamlValue::Boolean(_) => {
+                // Must be exactly "true" or "false"
+                if !matches!(buffer, "true" | "false") {
+                    return Err(anyhow!(
+                        "Invalid boolean: '{}' (must be 'true' or 'false')",
+                        buffer
+                    ));
+                }
+            }
+            YamlValue::Alias(_) => {
+                // Must start with * and have at least one character after
+                if !buffer.starts_with('*') || buffer.len() < 2 {
+                    return Err(anyhow!("Alias must be in format '*name'"));
+                }
+            }
+            // Strings, Null, and Comments accept any input
+            YamlValue::String(_) | YamlValue::Null | YamlValue::Comment(_) => {}
+            // Containers shouldn't be editable
+            YamlValue::Object(_)
+            | YamlValue::Array(_)
+            | YamlValue::MultiDoc(_)
+            | YamlValue::Deferred(_) => {
+                return Err(anyhow!("Cannot edit container types"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cancels editing and clears the edit buffer without saving changes.
+    pub fn cancel_editing(&mut self) {
+        self.edit_buffer = None;
+        self.multiline_edit = None;
+        self.edit_cursor = 0;
+    }
+
+    /// Commits the edited value from the buffer to the tree.
+    /// Parses the buffer according to the original node's type and updates the tree.
+    /// Returns an error if the buffer content is invalid for the node's type.
+    pub fn commit_editing(&mut self) -> anyhow::Result<()> {
+        use crate::document::node::YamlValue;
+        use anyhow::{anyhow, Context};
+
+        let buffer_content = self
+            .edit_buffer
+            .as_ref()
+            .ok_or_else(|| anyhow!("No active edit buffer"))?
+            .clone();
+
+        let path = self.cursor.path();
+        let node = self
+            .tree
+            .get_node(path)
+            .ok_or_else(|| anyhow!("Node not found at cursor"))?;
+
+        // Work out `*2`, `+10` or `=1024*1024` on a number
+        let evaluated = match node.value() {
+            YamlValue::Number(current) => super::calc::evaluate(&buffer_content, current)?,
+            _ => None,
+        };
+
+        // Validate input before attempting to parse
+        if evaluated.is_none() {
+            Self::validate_edit_input(&buffer_content, node.value())?;
+        }
+
+        // Special case: "null" always converts to Null regardless of original type
+        let new_value = if let Some(number) = evaluated {
+            YamlValue::Number(number)
+        } else if buffer_content == "null" {
+            YamlValue::Null
+        } else {
+            // Otherwise, determine the new value based on the original node's type
+            match node.value() {
+                YamlValue::String(original_style) => {
+                    // Preserve the original string style (Plain, Literal, or Folded)
+                    let new_string = match original_style {
+                        YamlString::Plain(_) => YamlString::Plain(buffer_content),
+                        YamlString::Literal(_) => YamlString::Literal(buffer_content),
+                        YamlString::Folded(_) => YamlString::Folded(buffer_content),
+                    };
+                    YamlValue::String(new_string)
+                }
+                YamlValue::Number(_) => {
+                    // Try integer first, then float
+                    if let Ok(i) = buffer_content.parse::<i64>() {
+                        YamlValue::Number(YamlNumber::Integer(i))
+                    } else {
+                        let num = buffer_content
+                            .parse::<f64>()
+                            .context("Invalid number format")?;
+                        YamlValue::Number(YamlNumber::Float(num))
+                    }
+                }
+                YamlValue::Boolean(_) => {
+                    let bool_val = match buffer_content.as_str() {
+                        "true" => true,
+                        "false" => false,
+                        _ => return Err(anyhow!("Boolean value must be true or false")),
+                    };
+                    YamlValue::Boolean(bool_val)
+                }
+                YamlValue::Null => {
+                    // This shouldn't happen since we checked for "null" above
+                    YamlValue::Null
+                }
+                YamlValue::Alias(_) => {
+                    // Parse alias reference (should start with *)
+                    if let Some(stripped) = buffer_content.strip_prefix('*') {
+                        YamlValue::Alias(stripped.to_string())
+                    } else {
+                        return Err(anyhow!("Alias must start with *"));
+                    }
+                }
+                YamlValue::Comment(c) => {
+                    // Update comment content while preserving position and source line
+                    let new_comment = if let Some(line) = c.source_line() {
+                        crate::document::node::CommentNode::from_source(
+                            buffer_content,
+                            c.position().clone(),
+                            line,
+                        )
+                    } else {
+                        crate::document::node::CommentNode::new(
+                            buffer_content,
+                            c.position().clone(),
+                        )
+                    };
+                    YamlValue::Comment(new_comment)
+                }
+                YamlValue::Object(_)
+                | YamlValue::Array(_)
+                | YamlValue::MultiDoc(_)
+                | YamlValue::Deferred(_) => {
+                    return Err(anyhow!("Cannot edit container types"));
+                }
+            }
+        };
+
+        // Update the node in the tree
+        let node_mut = self
+            .tree
+            .get_node_mut(path)
+            .ok_or_else(|| anyhow!("Node not found for update"))?;
+        *node_mut.value_mut() = new_value;
+
+        // Clear edit buffer and mark dirty
+        self.edit_buffer = None;
+        self.mark_dirty();
+        self.rebuild_tree_view();
+
+        self.checkpoint();
+        Ok(())
+    }
+
+    /// Inserts a character at the current cursor position in the edit buffer.
+    pub fn push_to_edit_buffer(&mut self, ch: char) {
+        if let Some(ref mut buffer) = self.edit_buffer {
+            buffer.insert(self.edit_cursor, ch);
+            self.edit_cursor += ch.len_utf8(); // Advance by byte length, not 1
+            self.reset_cursor_blink();
+        }
+        self.reset_completion();
+    }
+
+    /// Removes the character before the cursor (backspace).
+    pub fn pop_from_edit_buffer(&mut self) {
+        if let Some(ref mut buffer) = self.edit_buffer {
+            if self.edit_cursor > 0 {
+                // Find the start of the character before cursor (handles multi-byte UTF-8)
+                let char_start = buffer[..self.edit_cursor]
+                    .char_indices()
+                    .next_back()
+                    .map(|(i, _)| i)
+                    .unwrap_or(0);
+                buffer.remove(char_start);
+                self.edit_cursor = char_start;
+                self.reset_cursor_blink();
+            }
+        }
+        self.reset_completion();
+    }
+
+    /// Completes the logical ID being typed into a `!Ref` or `!GetAtt` value
+    /// in CloudFormation mode (`Tab` while editing).
+    ///
+    /// The first press completes what was typed to the first matching ID;
+    /// further presses cycle through the rest. For `!GetAtt` the attribute
+    /// after the dot is kept. Returns false if completion doesn't apply, so
+    /// the key can be handled as usual.
+    pub fn complete_edit_reference(&mut self) -> bool {
+        use crate::document::cloudformation::reference_candidates;
+
+        if !self.cloudformation()
+            || self.is_renaming_key()
+            || self.add_mode_stage != AddModeStage::None
+        {
+            return false;
+        }
+        let Some(buffer) = self.edit_buffer.clone() else {
+            return false;
+        };
+        let Some(tag) = self
+            .tree
+            .get_node(self.cursor.path())
+            .and_then(|node| node.tag())
+            .filter(|tag| matches!(*tag, "!Ref" | "!GetAtt"))
+            .map(str::to_string)
+        else {
+            return false;
+        };
+
+        if self.completion_candidates.is_empty() {
+            let id = buffer.split('.').next().unwrap_or_default().to_string();
+            self.completion_candidates = reference_candidates(self.tree.root(), &tag, &id);
+            self.completion_prefix = buffer;
+            self.completion_index = 0;
+            if self.completion_candidates.is_empty() {
+                self.set_message(
+                    format!("No logical IDs for {} start with '{}'", tag, id),
+                    MessageLevel::Warning,
+                );
+                return true;
+            }
+        } else {
+            self.completion_index = (self.completion_index + 1) % self.completion_candidates.len();
+        }
+
+        let mut completed = self.completion_candidates[self.completion_index].clone();
+        if let Some((_, attribute)) = self.completion_prefix.split_once('.') {
+            completed = format!("{}.{}", completed, attribute);
+        }
+        self.edit_cursor = completed.len();
+        self.edit_buffer = Some(completed);
+        self.reset_cursor_blink();
+        true
+    }
+
+    /// Clears the edit buffer entirely and resets cursor.
+    pub fn clear_edit_buffer(&mut self) {
+        if let Some(ref mut buffer) = self.edit_buffer {
+            buffer.clear();
+            self.edit_cursor = 0;
+            self.reset_cursor_blink();
+        }
+    }
+
+    /// Sets the edit buffer directly (test-only).
+    ///
+    /// **Warning:** This is only for testing purposes. Do not use in production code.
+    #[doc(hidden)]
+    pub fn set_edit_buffer_for_test(&mut self, content: String) {
+        self.edit_cursor = content.len();
+        self.edit_buffer = Some(content);
+    }
+
+    /// Moves the edit cursor left by one character.
+    pub fn edit_cursor_left(&mut self) {
+        if let Some(ref buffer) = self.edit_buffer {
+            if self.edit_cursor > 0 {
+                // Find the start of the previous character (handles multi-byte UTF-8)
+                let char_start = buffer[..self.edit_cursor]
+                    .char_indices()
+                    .next_back()
+                    .map(|(i, _)| i)
+                    .unwrap_or(0);
+                self.edit_cursor = char_start;
+                self.reset_cursor_blink();
+            }
+        }
+    }
+
+    /// Moves the edit cursor right by one character.
+    pub fn edit_cursor_right(&mut self) {
+        if let Some(ref buffer) = self.edit_buffer {
+            if self.edit_cursor < buffer.len() {
+                // Find the start of the next character (handles multi-byte UTF-8)
+                if let Some((next_pos, _)) = buffer[self.edit_cursor..].char_indices().nth(1) {
+                    self.edit_cursor += next_pos;
+                } else {
+                    // No next character, move to end
+                    self.edit_cursor = buffer.len();
+                }
+                self.reset_cursor_blink();
+            }
+        }
+    }
+
+    /// Moves the edit cursor to the beginning of the buffer (Ctrl-a).
+    pub fn edit_cursor_home(&mut self) {
+        self.edit_cursor = 0;
+        self.reset_cursor_blink();
+    }
+
+    /// Moves the edit cursor to the end of the buffer (Ctrl-e).
+    pub fn edit_cursor_end(&mut self) {
+        if let Some(ref buffer) = self.edit_buffer {
+            self.edit_cursor = buffer.len();
+            self.reset_cursor_blink();
+        }
+    }
+
+    /// Deletes the character at the cursor position (Ctrl-d).
+    pub fn edit_delete_at_cursor(&mut self) {
+        if let Some(ref mut buffer) = self.edit_buffer {
+            if self.edit_cursor < buffer.len() {
+                buffer.remove(self.edit_cursor);
+                self.reset_cursor_blink();
+            }
+        }
+    }
+
+    /// Deletes from cursor to end of buffer (Ctrl-k).
+    pub fn edit_kill_to_end(&mut self) {
+        if let Some(ref mut buffer) = self.edit_buffer {
+            buffer.truncate(self.edit_cursor);
+            self.reset_cursor_blink();
+        }
+    }
+
+    /// Returns the current edit cursor position.
+    pub fn edit_cursor_position(&self) -> usize {
+        self.edit_cursor
+    }
+
+    /// Returns whether the cursor is currently visible (for blinking).
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Updates the cursor blink state. Call this periodically to make cursor blink.
+    /// Toggles visibility every ~5 ticks (adjust based on render frequency).
+    pub fn update_cursor_blink(&mut self) {
+        self.cursor_blink_ticks = self.cursor_blink_ticks.wrapping_add(1);
+        if self.cursor_blink_ticks >= 5 {
+            self.cursor_visible = !self.cursor_visible;
+            self.cursor_blink_ticks = 0;
+        }
+    }
+
+    /// Resets cursor to visible (called on any edit action to show immediate feedback).
+    pub fn reset_cursor_blink(&mut self) {
+        self.cursor_visible = true;
+        self.cursor_blink_ticks = 0;
+    }
+
+    /// Returns the current pending command character, if any.
+    pub fn pending_command(&self) -> Option<char> {
+        self.pending_command
+    }
+
+    /// Sets the pending command character.
+    pub fn set_pending_command(&mut self, ch: char) {
+        self.pending_command = Some(ch);
+    }
+
+    /// Clears the pending command.
+    pub fn clear_pending_command(&mut self) {
+        self.pending_command = None;
+    }
+
+    /// Returns the current pending count, defaulting to 1 if none.
+    pub fn get_count(&self) -> u32 {
+        self.pending_count.unwrap_or(1)
+    }
+
+    /// Returns the raw pending count (None if no count entered).
+    pub fn pending_count(&self) -> Option<u32> {
+        self.pending_count
+    }
+
+    /// Adds a digit to the pending count.
+    /// First digit starts the count, subsequent digits multiply by 10 and add.
+    pub fn push_count_digit(&mut self, digit: u32) {
+        if let Some(count) = self.pending_count {
+            self.pending_count = Some(count.saturating_mul(10).saturating_add(digit));
+        } else {
+            self.pending_count = Some(digit);
+        }
+    }
+
+    /// Clears the pending count.
+    pub fn clear_pending_count(&mut self) {
+        self.pending_count = None;
+    }
+
+    /// Clears both pending command and count (used together often).
+    pub fn clear_pending(&mut self) {
+        self.pending_command = None;
+        self.pending_count = None;
+        self.pending_register = None;
+        self.append_mode = false;
+    }
+
+    /// Returns a reference to the unnamed register content.
+    pub fn get_unnamed_register(&self) -> &crate::editor::registers::RegisterContent {
+        self.registers.get_unnamed()
+    }
+
+    /// Returns the pending register character if one is waiting.
+    pub fn get_pending_register(&self) -> Option<char> {
+        self.pending_register
+    }
+
+    /// Sets the pending register and append mode for the next yank/delete operation.
+    pub fn set_pending_register(&mut self, register: char, append: bool) {
+        self.pending_register = Some(register);
+        self.append_mode = append;
+    }
+
+    /// Returns whether append mode is active for the pending register.
+    pub fn get_append_mode(&self) -> bool {
+        self.append_mode
+    }
+
+    /// Returns a reference to the register set.
+    pub fn registers(&self) -> &RegisterSet {
+        &self.registers
+    }
+
+    /// Clears the pending register and append mode.
+    pub fn clear_register_pending(&mut self) {
+        self.pending_register = None;
+        self.append_mode = false;
+    }
+
+    /// Returns the current cursor position as (row, col) where row is 1-based line number.
+    ///
+    /// Returns (0, 0) if the cursor is not found in the tree view.
+    pub fn cursor_position(&self) -> (usize, usize) {
+        let current_path = self.cursor.path();
+
+        if let Some(idx) = self.tree_view.line_of(current_path) {
+            let row = idx + 1; // 1-based line number
+            let col = 1; // Tree view doesn't have horizontal position
+            (row, col)
+        } else {
+            (0, 0)
+        }
+    }
+
+    /// Returns the total number of lines in the tree view.
+    pub fn total_lines(&self) -> usize {
+        self.tree_view.lines().len()
+    }
+
+    /// Captures the current editor state as an undo checkpoint.
+    ///
+    /// This is called automatically before mutation operations to enable undo/redo.
+    /// Checkpoints capture both the tree structure and cursor position.
+    fn checkpoint(&mut self) {
+        let snapshot = super::undo::EditorSnapshot {
+            tree: self.tree.clone(),
+            cursor_path: self.cursor.path().to_vec(),
+            expanded_paths: self.tree_view.expanded_paths().clone(),
+        };
+        self.undo_tree.add_checkpoint(snapshot);
+        self.check_memory();
+        self.schedule_revalidation();
+        self.run_value_checks();
+    }
+
+    /// Undoes the last operation.
+    ///
+    /// Restores the editor to the previous checkpoint state, including both
+    /// the tree structure and cursor position. Returns true if undo succeeded,
+    /// false if already at the root state or if the history doesn't apply,
+    /// which is reported as an error message.
+    pub fn undo(&mut self) -> bool {
+        let result = self.try_undo();
+        self.report_history_error(result)
+    }
+
+    /// Undoes the last operation, like [`EditorState::undo`], returning an
+    /// error if the history doesn't apply.
+    pub fn try_undo(&mut self) -> anyhow::Result<bool> {
+        let snapshot = self.undo_tree.undo()?;
+        Ok(self.restore_snapshot(snapshot))
+    }
+
+    /// Restores a snapshot undo or redo moved to; returns false if there was none.
+    fn restore_snapshot(&mut self, snapshot: Option<super::undo::EditorSnapshot>) -> bool {
+        let Some(snapshot) = snapshot else {
+            return false;
+        };
+        let current_cursor = self.cursor.path().to_vec();
+        self.tree = snapshot.tree;
+        self.follow_nodes();
+        self.tree_view.set_expanded_paths(snapshot.expanded_paths);
+
+        // Only restore cursor if current position is now invalid
+        if self.tree.get_node(&current_cursor).is_none() {
+            self.cursor.set_path(snapshot.cursor_path);
+        }
+
+        self.rebuild_tree_view();
+        self.schedule_revalidation();
+        self.run_value_checks();
+        true
+    }
+
+    /// Shows the error of an undo or redo that failed; returns whether it moved.
+    fn report_history_error(&mut self, result: anyhow::Result<bool>) -> bool {
+        result.unwrap_or_else(|e| {
+            self.set_message(format!("{:#}", e), MessageLevel::Error);
+            false
+        })
+    }
+
+    /// Restores undo history saved by a previous session, if enabled.
+    ///
+    /// History is only restored if the file's directory is trusted (an undo
+    /// file can come with a checked-out repository, like a modeline) and the
+    /// file still matches what was saved; otherwise the undo file is left
+    /// alone and ignored.
+    pub fn load_undo_history(&mut self) {
+        use super::undo::{undo_file_path, UndoTree};
+
+        if !self.persistent_undo || self.safe_mode {
+            return;
+        }
+        let Some(filename) = self.filename.clone() else {
+            return;
+        };
+        let path = std::path::Path::new(&filename);
+        if !undo_file_path(path).exists() {
+            return;
+        }
+        let Some(dir) = self.file_dir() else {
+            return;
+        };
+        if !self.trust_store.is_trusted(&dir) {
+            self.set_message(
+                format!(
+                    "Ignored untrusted undo file in {} (:trust to allow)",
+                    dir.display()
+                ),
+                MessageLevel::Warning,
+            );
+            return;
+        }
+        let Ok(contents) = std::fs::read(path) else {
+            return;
+        };
+        match UndoTree::load_from(
+            &undo_file_path(path),
+            &contents,
+            self.undo_tree.limit(),
+            self.undo_file_limit_mb << 20,
+        ) {
+            Ok(Some(mut undo_tree)) => {
+                undo_tree.set_current_tree(self.tree.clone());
+                self.undo_tree = undo_tree;
+            }
+            Ok(None) => {}
+            Err(e) => self.set_message(format!("{}", e), MessageLevel::Warning),
+        }
+    }
+
+    /// Writes the undo history next to the file, if enabled.
+    ///
+    /// Call after the file has been saved so the history is tied to its new contents.
+    pub fn write_undo_history(&mut self) {
+        use super::undo::undo_file_path;
+
+        if !self.persistent_undo || self.safe_mode {
+            return;
+        }
+        let Some(filename) = self.filename.clone() else {
+            return;
+        };
+        let path = std::path::Path::new(&filename);
+        let limit = self.undo_file_limit_mb << 20;
+        let result = std::fs::read(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| {
+                self.undo_tree
+                    .save_to(&undo_file_path(path), &contents, limit)
+            });
+        match result {
+            Ok(0) => {}
+            Ok(dropped) => self.set_message(
+                format!(
+                    "Undo file over undo_file_limit_mb: left out the {} oldest state{}",
+                    dropped,
+                    if dropped == 1 { "" } else { "s" }
+                ),
+                MessageLevel::Warning,
+            ),
+            Err(e) => self.set_message(
+                format!("Undo history not saved: {}", e),
+                MessageLevel::Warning,
+            ),
+        }
+    }
+
+    /// Redoes the last undone operation.
+    ///
+    /// Restores the editor to the next checkpoint state (newest branch if multiple
+    /// exist), including both the tree structure and cursor position. Returns true
+    /// if redo succeeded, false if no redo history exists or if it doesn't apply,
+    /// which is reported as an error message.
+    pub fn redo(&mut self) -> bool {
+        let result = self.try_redo();
+        self.report_history_error(result)
+    }
+
+    /// Redoes the last undone operation, like [`EditorState::redo`],
+    /// returning an error if the history doesn't apply.
+    pub fn try_redo(&mut self) -> anyhow::Result<bool> {
+        let snapshot = self.undo_tree.redo()?;
+        Ok(self.restore_snapshot(snapshot))
+    }
+
+    /// Returns the current add mode stage.
+    pub fn add_mode_stage(&self) -> &AddModeStage {
+        &self.add_mode_stage
+    }
+
+    /// Returns the current add key buffer.
+    pub fn add_key_buffer(&self) -> &str {
+        &self.add_key_buffer
+    }
+
+    /// Returns the current cursor position in the add key buffer.
+    pub fn add_key_cursor_position(&self) -> usize {
+        self.add_key_cursor
+    }
+
+    /// Pushes a character to the add key buffer at cursor position.
+    pub fn push_to_add_key_buffer(&mut self, ch: char) {
+        self.add_key_buffer.insert(self.add_key_cursor, ch);
+        self.add_key_cursor += ch.len_utf8(); // Advance by byte length, not 1
+        self.reset_cursor_blink();
+    }
+
+    /// Removes the character before cursor in the add key buffer (backspace).
+    pub fn pop_from_add_key_buffer(&mut self) {
+        if self.add_key_cursor > 0 {
+            // Find the start of the character before cursor (handles multi-byte UTF-8)
+            let char_start = self.add_key_buffer[..self.add_key_cursor]
+                .char_indices()
+                .next_back()
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+            self.add_key_buffer.remove(char_start);
+            self.add_key_cursor = char_start;
+            self.reset_cursor_blink();
+        }
+    }
+
+    /// Clears the add key buffer and resets cursor.
+    pub fn clear_add_key_buffer(&mut self) {
+        self.add_key_buffer.clear();
+        self.add_key_cursor = 0;
+    }
+
+    /// Starts an add operation at the current cursor position.
+    ///
+    /// Determines whether we're adding to an array or object, and sets the
+    /// appropriate add_mode_stage. For arrays, immediately enters Insert mode.
+    /// For objects, stays in Normal mode and waits for key input.
+    pub fn start_add_operation(&mut self) {
+        if self.reject_read_only() {
+            return;
+        }
+        use crate::document::node::YamlValue;
+
+        // Clear any previous messages so the edit area is visible
+        self.clear_message();
+
+        let current_path = self.cursor.path().to_vec();
+
+        // Special case: if cursor is at root (empty path)
+        if current_path.is_empty() {
+            // Check if root is a container
+            match self.tree.root().value() {
+                YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
+                    // Root is container, we can add to it
+                    // Determine which type
+                    match self.tree.root().value() {
+                        YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
+                            // Array or JSONL: go straight to value input
+                            self.add_mode_stage = AddModeStage::AwaitingValue;
+                            self.add_insertion_point = Some(vec![0]); // Insert at position 0
+
+                            // Enter Insert mode with empty edit buffer
+                            self.edit_buffer = Some(String::new());
+                            self.edit_cursor = 0;
+                            self.set_mode(EditorMode::Insert);
+                            self.reset_cursor_blink();
+                            // Set mode indicator message
+                            self.set_message("-- INSERT --".to_string(), MessageLevel::Info);
+                        }
+                        YamlValue::Object(_) => {
+                            // Object: need key first
+                            self.add_mode_stage = AddModeStage::AwaitingKey;
+                            self.clear_add_key_buffer(); // Reset buffer and cursor
+                            self.add_insertion_point = Some(vec![0]); // Insert at position 0
+                            self.reset_cursor_blink();
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                _ => {
+                    // Root is scalar, can't add sibling
+                    self.set_message(
+                        "Cannot add sibling to root node".to_string(),
+                        MessageLevel::Error,
+                    );
+                }
+            }
+            return;
+        }
+
+        // Check if the current node (not parent) is a container
+        // If it's an EMPTY container, add inside it
+        // If it's a NON-EMPTY container and NOT at root, add as sibling after it (to preserve expansion state)
+        // If it's a NON-EMPTY container AT root, add inside it (can't add sibling to root)
+        if let Some(current_node) = self.tree.get_node(&current_path) {
+            match current_node.value() {
+                // If array/JSONL is empty OR we're at root level, add inside it
+                YamlValue::Array(elements) | YamlValue::MultiDoc(elements)
+                    if elements.is_empty() || current_path.is_empty() =>
+                {
+                    let insert_index = elements.len(); // Get length before mutable borrow
+
+                    // Ensure the container is expanded so the new child will be visible
+                    if !self.tree_view().is_expanded(&current_path) {
+                        self.tree_view_mut().toggle_expand(&current_path);
+                    }
+
+                    self.add_mode_stage = AddModeStage::AwaitingValue;
+                    let mut insertion_path = current_path.clone();
+                    insertion_path.push(insert_index); // Insert at end
+                    self.add_insertion_point = Some(insertion_path);
+
+                    // Enter Insert mode with empty edit buffer
+                    self.edit_buffer = Some(String::new());
+                    self.edit_cursor = 0;
+                    self.set_mode(EditorMode::Insert);
+                    self.reset_cursor_blink();
+                    // Set mode indicator message
+                    self.set_message("-- INSERT --".to_string(), MessageLevel::Info);
+                    return;
+                }
+                // If object is empty OR we're at root level, add inside it
+                YamlValue::Object(entries) if entries.is_empty() || current_path.is_empty() => {
+                    let insert_index = entries.len(); // Get length before mutable borrow
+
+                    // Ensure the container is expanded so the new child will be visible
+                    if !self.tree_view().is_expanded(&current_path) {
+                        self.tree_view_mut().toggle_expand(&current_path);
+                    }
+
+                    self.add_mode_stage = AddModeStage::AwaitingKey;
+                    self.clear_add_key_buffer(); // Reset buffer and cursor
+                    let mut insertion_path = current_path.clone();
+                    insertion_path.push(insert_index); // Insert at end
+                    self.add_insertion_point = Some(insertion_path);
+                    self.reset_cursor_blink();
+                    // Stay in Normal mode, wait for key input
+                    return;
+                }
+                _ => {
+                    // Scalar, or non-empty container at non-root: fall through to add sibling after
+                }
+            }
+        }
+
+        // Current node is a scalar - add sibling after it in parent container
+        let parent_path = &current_path[..current_path.len() - 1];
+        let current_index = current_path[current_path.len() - 1];
+
+        // Get parent node
+        let parent = if parent_path.is_empty() {
+            self.tree.root()
+        } else {
+            match self.tree.get_node(parent_path) {
+                Some(node) => node,
+                None => {
+                    self.set_message("Invalid cursor position".to_string(), MessageLevel::Error);
+                    return;
+                }
+            }
+        };
+
+        // Determine parent type and set up add operation
+        match parent.value() {
+            YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
+                // Adding to array/JSONL: insert after current element
+                self.add_mode_stage = AddModeStage::AwaitingValue;
+                let mut insertion_path = parent_path.to_vec();
+                insertion_path.push(current_index + 1);
+                self.add_insertion_point = Some(insertion_path);
+
+                // Enter Insert mode with empty edit buffer
+                self.edit_buffer = Some(String::new());
+                self.edit_cursor = 0;
+                self.set_mode(EditorMode::Insert);
+                self.reset_cursor_blink();
+                // Set mode indicator message
+                self.set_message("-- INSERT --".to_string(), MessageLevel::Info);
+            }
+            YamlValue::Object(_) => {
+                // Adding to object: need key first
+                self.add_mode_stage = AddModeStage::AwaitingKey;
+                self.clear_add_key_buffer(); // Reset buffer and cursor
+                let mut insertion_path = parent_path.to_vec();
+                insertion_path.push(current_index + 1);
+                self.add_insertion_point = Some(insertion_path);
+                self.reset_cursor_blink();
+                // Stay in Normal mode, wait for key input
+            }
+            _ => {
+                self.set_message("Parent is not a container".to_string(), MessageLevel::Error);
+            }
+        }
+    }
+
+    /// Commits the add operation by creating and inserting the new node.
+    ///
+    /// Parses the edit buffer value, creates a YamlNode, inserts it at the
+    /// add_insertion_point, creates an undo checkpoint, and moves cursor to
+    /// the new node.
+    pub fn commit_add_operation(&mut self) -> anyhow::Result<()> {
+        use anyhow::anyhow;
+
+        // Verify we're in AwaitingValue stage
+        if !matches!(self.add_mode_stage, AddModeStage::AwaitingValue) {
+            return Err(anyhow!("Not in AwaitingValue stage"));
+        }
+
+        // Get the value from edit buffer
+        let value_str = self
+            .edit_buffer
+            .as_ref()
+            .ok_or_else(|| anyhow!("No edit buffer"))?;
+
+        // Parse the value
+        let value = parse_scalar_value(value_str);
+        let node = YamlNode::new(value);
+
+        // Get insertion point
+        let insertion_path = self
+            .add_insertion_point
+            .as_ref()
+            .ok_or_else(|| anyhow!("No insertion point set"))?
+            .clone();
+
+        // Determine parent type and insert
+        let parent_path = if insertion_path.is_empty() {
+            &[]
+        } else {
+            &insertion_path[..insertion_path.len() - 1]
+        };
+
+        let parent = if parent_path.is_empty() {
+            self.tree.root()
+        } else {
+            self.tree
+                .get_node(parent_path)
+                .ok_or_else(|| anyhow!("Parent node not found"))?
+        };
+
+        match parent.value() {
+            YamlValue::Array(_) => {
+                self.tree.insert_node_in_array(&insertion_path, node)?;
+                self.set_message("Added element".to_string(), MessageLevel::Info);
+            }
+            YamlValue::Object(_) => {
+                let key = self.add_key_buffer.clone();
+                self.tree
+                    .insert_node_in_object(&insertion_path, key.clone(), node)?;
+                self.set_message(format!("Added field '{}'", key), MessageLevel::Info);
+            }
+            _ => {
+                return Err(anyhow!("Parent is not a container"));
+            }
+        }
+
+        // Rebuild tree view to show new node
+        self.rebuild_tree_view();
+
+        // Move cursor to newly created node
+        self.cursor.set_path(insertion_path.clone());
+
+        // Mark dirty and create undo checkpoint
+        self.mark_dirty();
+        self.checkpoint();
+
+        // Clear add operation state and edit buffer
+        self.cancel_add_operation();
+        self.cancel_editing();
+
+        Ok(())
+    }
+
+    /// Transitions from AwaitingKey to AwaitingValue stage.
+    ///
+    /// Called when user presses Enter after typing object key.
+    pub fn transition_add_to_value(&mut self) {
+        if matches!(self.add_mode_stage, AddModeStage::AwaitingKey) {
+            // Check for empty key
+            if self.add_key_buffer.is_empty() {
+                self.set_message("Key cannot be empty".to_string(), MessageLevel::Error);
+                return;
+            }
+
+            // Transition to value stage
+            self.add_mode_stage = AddModeStage::AwaitingValue;
+
+            // Enter Insert mode
+            self.edit_buffer = Some(String::new());
+            self.edit_cursor = 0;
+            self.set_mode(EditorMode::Insert);
+            self.reset_cursor_blink();
+            // Set mode indicator message
+            self.set_message("-- INSERT --".to_string(), MessageLevel::Info);
+        }
+    }
+
+    /// Cancels the add operation and clears all related state.
+    pub fn cancel_add_operation(&mut self) {
+        self.add_mode_stage = AddModeStage::None;
+        self.add_key_buffer.clear();
+        self.add_insertion_point = None;
+    }
+
+    /// Starts an add container operation (ao for object, aa for array).
+    ///
+    /// Immediately adds an empty container {} or [] without going through
+    /// the value input stage. For objects, prompts for key name first.
+    ///
+    /// # Arguments
+    ///
+    /// * `is_object` - true for object {}, false for array []
+    pub fn start_add_container_operation(&mut self, is_object: bool) {
+        if self.reject_read_only() {
+            return;
+        }
+        use crate::document::node::YamlValue;
+
+        // Clear any previous messages so the edit area is visible
+        self.clear_message();
+
+        let current_path = self.cursor.path().to_vec();
+
+        // Create the container node
+        let container_node = if is_object {
+            YamlNode::new(YamlValue::Object(indexmap::IndexMap::new()))
+        } else {
+            YamlNode::new(YamlValue::Array(vec![]))
+        };
+
+        // Special case: if cursor is at root (empty path)
+        if current_path.is_empty() {
+            // At root - check if root is a container
+            match self.tree.root().value() {
+                YamlValue::Object(_) => {
+                    self.add_mode_stage = AddModeStage::AwaitingKey;
+                    self.clear_add_key_buffer(); // Reset buffer and cursor
+                    self.add_insertion_point = Some(vec![0]);
+                    // For object containers in object root, we need a key
+                    // Store the container temporarily and wait for key
+                    self.temp_container = Some(container_node);
+                    self.reset_cursor_blink();
+                    return;
+                }
+                YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
+                    // Insert directly into array/JSONL at position 0
+                    let insertion_path = vec![0];
+                    match self
+                        .tree
+                        .insert_node_in_array(&insertion_path, container_node)
+                    {
+                        Ok(_) => {
+                            self.rebuild_tree_view();
+                            self.cursor.set_path(insertion_path.clone());
+                            self.mark_dirty();
+                            self.checkpoint();
+
+                            let msg = if is_object {
+                                "Added empty object"
+                            } else {
+                                "Added empty array"
+                            };
+                            self.set_message(msg.to_string(), MessageLevel::Info);
+                        }
+                        Err(e) => {
+                            self.set_message(format!("Add failed: {}", e), MessageLevel::Error);
+                        }
+                    }
+                    return;
+                }
+                _ => {
+                    self.set_message(
+                        "Cannot add sibling to root node".to_string(),
+                        MessageLevel::Error,
+                    );
+                    return;
+                }
+            }
+        }
+
+        // Check if the current node (not parent) is a container
+        // If it's empty OR at root level, add inside it
+        // If it's non-empty and not at root, add as sibling (to preserve expansion state)
+        if let Some(current_node) = self.tree.get_node(&current_path) {
+            match current_node.value() {
+                YamlValue::Array(elements) | YamlValue::MultiDoc(elements)
+                    if elements.is_empty() || current_path.is_empty() =>
+                {
+                    let insert_index = elements.len(); // Get length before mutable borrow
+
+                    // Empty array/JSONL or root-level array/JSONL: add inside it
+                    // Ensure the container is expanded so the new child will be visible
+                    if !self.tree_view().is_expanded(&current_path) {
+                        self.tree_view_mut().toggle_expand(&current_path);
+                    }
+
+                    let mut insertion_path = current_path.clone();
+                    insertion_path.push(insert_index); // Insert at end
+
+                    match self
+                        .tree
+                        .insert_node_in_array(&insertion_path, container_node)
+                    {
+                        Ok(_) => {
+                            self.rebuild_tree_view();
+                            self.cursor.set_path(insertion_path.clone());
+                            self.mark_dirty();
+                            self.checkpoint();
+
+                            let msg = if is_object {
+                                "Added empty object"
+                            } else {
+                                "Added empty array"
+                            };
+                            self.set_message(msg.to_string(), MessageLevel::Info);
+                        }
+                        Err(e) => {
+                            self.set_message(format!("Add failed: {}", e), MessageLevel::Error);
+                        }
+                    }
+                    return;
+                }
+                YamlValue::Object(entries) if entries.is_empty() || current_path.is_empty() => {
+                    let insert_index = entries.len(); // Get length before mutable borrow
+
+                    // Empty object or root-level object: add inside it
+                    // Ensure the container is expanded so the new child will be visible
+                    if !self.tree_view().is_expanded(&current_path) {
+                        self.tree_view_mut().toggle_expand(&current_path);
+                    }
+
+                    self.add_mode_stage = AddModeStage::AwaitingKey;
+                    self.clear_add_key_buffer(); // Reset buffer and cursor
+                    let mut insertion_path = current_path.clone();
+                    insertion_path.push(insert_index); // Insert at end
+                    self.add_insertion_point = Some(insertion_path);
+                    // For containers in objects, we need a key
+                    // Store the container temporarily and wait for key
+                    self.temp_container = Some(container_node);
+                    self.reset_cursor_blink();
+                    return;
+                }
+                _ => {
+                    // Non-empty container at non-root or scalar: fall through to add sibling
+                }
+            }
+        }
+
+        // Current node is either a scalar or non-empty container
+        // Add sibling after it in parent container
+        let parent_path = &current_path[..current_path.len() - 1];
+        let current_index = current_path[current_path.len() - 1];
+
+        let parent = if parent_path.is_empty() {
+            self.tree.root()
+        } else {
+            match self.tree.get_node(parent_path) {
+                Some(node) => node,
+                None => {
+                    self.set_message("Invalid cursor position".to_string(), MessageLevel::Error);
+                    return;
+                }
+            }
+        };
+
+        let mut path = parent_path.to_vec();
+        path.push(current_index + 1);
+
+        match parent.value() {
+            YamlValue::Object(_) => {
+                self.add_mode_stage = AddModeStage::AwaitingKey;
+                self.clear_add_key_buffer(); // Reset buffer and cursor
+                self.add_insertion_point = Some(path);
+                // For containers in objects, we need a key
+                // Store the container temporarily and wait for key
+                self.temp_container = Some(container_node);
+                self.reset_cursor_blink();
+            }
+            YamlValue::Array(_) | YamlValue::MultiDoc(_) => {
+                // Insert directly into array/JSONL (no key needed)
+                match self.tree.insert_node_in_array(&path, container_node) {
+                    Ok(_) => {
+                        self.rebuild_tree_view();
+                        self.cursor.set_path(path.clone());
+                        self.mark_dirty();
+                        self.checkpoint();
+
+                        let msg = if is_object {
+                            "Added empty object"
+                        } else {
+                            "Added empty array"
+                        };
+                        self.set_message(msg.to_string(), MessageLevel::Info);
+                    }
+                    Err(e) => {
+                        self.set_message(format!("Add failed: {}", e), MessageLevel::Error);
+                    }
+                }
+            }
+            _ => {
+                self.set_message("Parent is not a container".to_string(), MessageLevel::Error);
+            }
+        }
+    }
+
+    /// Starts a rename operation on the current object key.
+    ///
+    /// Checks if the cursor is on an object key (not array element, not root),
+    /// then enters Insert mode with the current key name pre-populated in the
+    /// edit buffer.
+    pub fn start_rename_operation(&mut self) {
+        if self.reject_read_only() {
+            return;
+        }
+        use crate::document::node::YamlValue;
+
+        // Clear any previous messages so the edit area is visible
+        self.clear_message();
+
+        if let Err(e) = self.ensure_not_merged() {
+            self.set_message(e.to_string(), MessageLevel::Error);
+            return;
+        }
+
+        let current_path = self.cursor.path().to_vec();
+
+        // Can't rename root
+        if current_path.is_empty() {
+            self.set_message("Cannot rename root node".to_string(), MessageLevel::Error);
+            return;
+        }
+
+        // Get parent to check if it's an object
+        let parent_path = &current_path[..current_path.len() - 1];
+        let current_index = current_path[current_path.len() - 1];
+
+        let parent = if parent_path.is_empty() {
+            self.tree.root()
+        } else {
+            match self.tree.get_node(parent_path) {
+                Some(node) => node,
+                None => {
+                    self.set_message("Invalid cursor position".to_string(), MessageLevel::Error);
+                    return;
+                }
+            }
+        };
+
+        // Check if parent is an object
+        if let YamlValue::Object(entries) = parent.value() {
+            // Get the current key name
+            if let Some((key, _)) = entries.get_index(current_index) {
+                let key_name = key.clone();
+
+                // Enter rename mode with key name in edit buffer
+                self.is_renaming_key = true;
+                self.rename_original_key = Some(key_name.clone());
+                self.edit_buffer = Some(key_name.clone());
+                self.edit_cursor = key_name.len();
+                self.set_mode(EditorMode::Insert);
+                self.reset_cursor_blink();
+                self.set_message("-- RENAME --".to_string(), MessageLevel::Info);
+            } else {
+                self.set_message("Invalid object index".to_string(), MessageLevel::Error);
+            }
+        } else {
+            self.set_message(
+                "Can only rename object keys, not array elements".to_string(),
+                MessageLevel::Error,
+            );
+        }
+    }
+
+    /// Commits the rename operation, updating the key name in the object.
+    pub fn commit_rename(&mut self) -> anyhow::Result<()> {
+        use crate::document::ops::Op;
+        use anyhow::anyhow;
+
+        let new_key = self
+            .edit_buffer
+            .as_ref()
+            .ok_or_else(|| anyhow!("No edit buffer"))?
+            .clone();
+
+        if new_key.is_empty() {
+            return Err(anyhow!("Key cannot be empty"));
+        }
+
+        let original_key = self
+            .rename_original_key
+            .as_ref()
+            .ok_or_else(|| anyhow!("No original key stored"))?
+            .clone();
+
+        // If key didn't change, just exit
+        if new_key == original_key {
+            self.cancel_rename();
+            return Ok(());
+        }
+
+        let current_path = self.cursor.path().to_vec();
+        if current_path.is_empty() {
+            return Err(anyhow!("Cannot rename root"));
+        }
+
+        Op::RenameKey {
+            path: current_path,
+            key: new_key.clone(),
+        }
+        .apply(&mut self.tree)?;
+
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        self.set_message(
+            format!("Renamed '{}' to '{}'", original_key, new_key),
+            MessageLevel::Info,
+        );
+
+        self.cancel_rename();
+        Ok(())
+    }
+
+    /// Cancels the rename operation and clears related state.
+    pub fn cancel_rename(&mut self) {
+        self.is_renaming_key = false;
+        self.rename_original_key = None;
+        self.edit_buffer = None;
+        self.edit_cursor = 0;
+    }
+
+    /// Returns whether we're currently in rename mode.
+    pub fn is_renaming_key(&self) -> bool {
+        self.is_renaming_key
+    }
+
+    /// Starts a comment addition operation at the current cursor position.
+    ///
+    /// Opens an edit prompt for entering the comment text. The comment will be
+    /// added at the cursor position with a default position of Above.
+    ///
+    /// For now, this implementation adds comments with CommentPosition::Above.
+    /// A future enhancement could prompt the user to select the position type
+    /// (Above/Line/Below).
+    pub fn start_add_comment_operation(&mut self) {
+        if self.reject_read_only() {
+            return;
+        }
+        use crate::document::node::YamlValue;
+
+        // Clear any previous messages so the edit area is visible
+        self.clear_message();
+
+        // Verify cursor is on a valid node (not a comment)
+        let current_path = self.cursor.path().to_vec();
+        if let Some(current_node) = self.tree.get_node(&current_path) {
+            match current_node.value() {
+                YamlValue::Comment(_) => {
+                    self.set_message(
+                        "Cannot add comment to comment node".to_string(),
+                        MessageLevel::Error,
+                    );
+                }
+                _ => {
+                    // Valid node - start comment entry
+                    // Enter Insert mode with empty edit buffer
+                    self.edit_buffer = Some(String::new());
+                    self.edit_cursor = 0;
+                    self.set_mode(EditorMode::Insert);
+                    self.reset_cursor_blink();
+                    // Set mode indicator message with prompt
+                    self.set_message(
+                        "-- INSERT (Comment) -- Enter text and press Enter to save, Esc to cancel"
+                            .to_string(),
+                        MessageLevel::Info,
+                    );
+                    // Store that we're in comment-add mode
+                    // We'll use the add_mode_stage for this
+                    self.add_mode_stage = AddModeStage::AwaitingComment;
+                }
+            }
+        } else {
+            self.set_message("No node at cursor".to_string(), MessageLevel::Error);
+        }
+    }
+
+    /// Commits a comment add operation after receiving the comment text.
+    ///
+    /// Creates a new Comment node with the entered text and inserts it into
+    /// the tree near the current cursor position. Currently adds comments with
+    /// CommentPosition::Above.
+    pub fn commit_add_comment(&mut self) -> anyhow::Result<()> {
+        use crate::document::node::{CommentNode, CommentPosition, YamlNode, YamlValue};
+        use anyhow::anyhow;
+
+        // Get the comment text from edit buffer
+        let comment_text = self
+            .edit_buffer
+            .clone()
+            .ok_or_else(|| anyhow!("No comment text in buffer"))?;
+
+        // Empty comments are allowed
+        // Create a comment node with Above position (TODO: prompt for position)
+        let comment_node = YamlNode::new(YamlValue::Comment(CommentNode::new(
+            comment_text,
+            CommentPosition::Above,
+        )));
+
+        // Get current path
+        let current_path = self.cursor.path().to_vec();
+
+        // Insert comment as sibling before current node
+        // This requires getting the parent and inserting at the current index
+        if current_path.is_empty() {
+            // Can't add comment to root
+            return Err(anyhow!("Cannot add comment to root node"));
+        }
+
+        let parent_path = &current_path[..current_path.len() - 1];
+        let current_index = current_path[current_path.len() - 1];
+
+        let parent = if parent_path.is_empty() {
+            self.tree.root_mut()
+        } else {
+            self.tree
+                .get_node_mut(parent_path)
+                .ok_or_else(|| anyhow!("Parent node not found"))?
+        };
+
+        match parent.value_mut() {
+            YamlValue::Array(arr) => {
+                // Insert comment before current element in array
+                arr.insert(current_index, comment_node);
+                self.mark_dirty();
+                self.rebuild_tree_view();
+                self.checkpoint();
+            }
+            YamlValue::Object(entries) => {
+                // Find a unique __comment_N__ key
+                let mut n = 0;
+                loop {
+                    let candidate = format!("__comment_{}__", n);
+                    if !entries.contains_key(&candidate) {
+                        break;
+                    }
+                    n += 1;
+                }
+                let key = format!("__comment_{}__", n);
+                entries.shift_insert(current_index, key, comment_node);
+                self.mark_dirty();
+                self.rebuild_tree_view();
+                self.checkpoint();
+            }
+            YamlValue::MultiDoc(docs) => {
+                // Insert comment before current document
+                docs.insert(current_index, comment_node);
+                self.mark_dirty();
+                self.rebuild_tree_view();
+                self.checkpoint();
+            }
+            _ => {
+                return Err(anyhow!("Parent is not a container"));
+            }
+        }
+
+        // Clear add mode state
+        self.add_mode_stage = AddModeStage::None;
+        self.edit_buffer = None;
+        self.edit_cursor = 0;
+
+        Ok(())
+    }
+
+    /// Cancels a comment add operation.
+    pub fn cancel_add_comment(&mut self) {
+        self.add_mode_stage = AddModeStage::None;
+        self.edit_buffer = None;
+        self.edit_cursor = 0;
+    }
+
+    /// Commits a container add operation after receiving the key name.
+    ///
+    /// Called when user finishes entering a key name for adding a container
+    /// to an object. Retrieves the temporarily stored container from clipboard
+    /// and inserts it with the provided key.
+    pub fn commit_container_add(&mut self) -> anyhow::Result<()> {
+        use anyhow::anyhow;
+
+        // Get the container from temporary storage
+        let container_node = self
+            .temp_container
+            .take()
+            .ok_or_else(|| anyhow!("No container to add"))?;
+
+        // Get the key from add_key_buffer
+        let key = self.add_key_buffer.clone();
+        if key.is_empty() {
+            return Err(anyhow!("Key cannot be empty"));
+        }
+
+        // Get insertion point
+        let insertion_path = self
+            .add_insertion_point
+            .as_ref()
+            .ok_or_else(|| anyhow!("No insertion point set"))?
+            .clone();
+
+        // Insert the container with the key
+        self.tree
+            .insert_node_in_object(&insertion_path, key.clone(), container_node.clone())?;
+
+        self.rebuild_tree_view();
+        self.cursor.set_path(insertion_path.clone());
+        self.mark_dirty();
+        self.checkpoint();
+
+        let container_type = match container_node.value() {
+            YamlValue::Object(_) => "object",
+            YamlValue::Array(_) => "array",
+            _ => "container",
+        };
+        self.set_message(
+            format!("Added empty {} '{}'", container_type, key),
+            MessageLevel::Info,
+        );
+
+        // Clear add operation state
+        self.cancel_add_operation();
+
+        Ok(())
+    }
+
+    // Visual mode, marks, jump list, and repeat command accessors
+
+    /// Returns a reference to the jump list.
+    pub fn jumplist(&self) -> &JumpList {
+        &self.jumplist
+    }
+
+    /// Returns a mutable reference to the jump list.
+    pub fn jumplist_mut(&mut self) -> &mut JumpList {
+        &mut self.jumplist
+    }
+
+    /// Starts a key jump (`f`): the letters typed next pick a key on screen.
+    pub fn start_key_jump(&mut self) {
+        self.key_jump = Some(super::keyjump::KeyJump::default());
+        self.set_message("f: type the start of a key".to_string(), MessageLevel::Info);
+    }
+
+    /// Returns the key jump in progress, if any.
+    pub fn key_jump(&self) -> Option<&super::keyjump::KeyJump> {
+        self.key_jump.as_ref()
+    }
+
+    /// Handles a letter typed during a key jump: jumps to the key it
+    /// labels, or adds it to the letters typed and narrows the matches.
+    pub fn key_jump_input(&mut self, c: char) {
+        let Some(mut jump) = self.key_jump.take() else {
+            return;
+        };
+        if let Some(path) = jump.labelled(c) {
+            let path = path.to_vec();
+            self.finish_key_jump(Some(path));
+            return;
+        }
+        jump.query.push(c);
+        self.update_key_jump(jump);
+    }
+
+    /// Removes the last letter typed during a key jump.
+    pub fn key_jump_backspace(&mut self) {
+        if let Some(mut jump) = self.key_jump.take() {
+            jump.query.pop();
+            self.update_key_jump(jump);
+        }
+    }
+
+    /// Jumps to the first match after the cursor (`Enter` during a key jump).
+    pub fn key_jump_accept(&mut self) {
+        let first = self
+            .key_jump
+            .as_ref()
+            .and_then(|jump| jump.targets.first())
+            .map(|(path, _)| path.clone());
+        self.finish_key_jump(first);
+    }
+
+    /// Ends a key jump, moving the cursor to `target` if there is one.
+    pub fn finish_key_jump(&mut self, target: Option<Vec<usize>>) {
+        self.key_jump = None;
+        self.tree_view.set_jump_labels(HashMap::new());
+        self.clear_message();
+        if let Some(path) = target {
+            self.record_jump();
+            self.cursor.set_path(path);
+        }
+    }
+
+    /// Finds the keys on screen matching the letters typed so far, jumping
+    /// at once to a single match and labelling several.
+    fn update_key_jump(&mut self, mut jump: super::keyjump::KeyJump) {
+        use super::keyjump::{key_matches, labels, next_letters};
+        use crate::document::node::YamlValue;
+
+        if jump.query.is_empty() {
+            self.tree_view.set_jump_labels(HashMap::new());
+            self.start_key_jump();
+            return;
+        }
+
+        // Mapping keys on screen, from the cursor down and then from the top
+        let lines = self.tree_view.lines();
+        let start = self.scroll_offset.min(lines.len());
+        let end = (start + self.viewport_height).min(lines.len());
+        if start >= end {
+            // Nothing on screen, as in an empty document
+            let query = jump.query.clone();
+            self.finish_key_jump(None);
+            self.set_message(
+                format!("No key on screen starts with {}", query),
+                MessageLevel::Error,
+            );
+            return;
+        }
+        let cursor = self.tree_view.line_of(self.cursor.path()).unwrap_or(start);
+        let in_mapping = |path: &[usize]| {
+            path.split_last().is_some_and(|(_, parent)| {
+                self.tree
+                    .get_node(parent)
+                    .is_some_and(|node| matches!(node.value(), YamlValue::Object(_)))
+            })
+        };
+        let keys: Vec<(Vec<usize>, &str)> = (cursor + 1..end)
+            .chain(start..=cursor.min(end.saturating_sub(1)))
+            .filter_map(|i| {
+                let line = &lines[i];
+                let key = line.key.as_deref()?;
+                in_mapping(&line.path).then(|| (line.path.clone(), key))
+            })
+            .collect();
+        let matching: Vec<_> = keys
+            .iter()
+            .filter(|(_, key)| key_matches(key, &jump.query))
+            .collect();
+
+        match matching.as_slice() {
+            [] => {
+                let query = jump.query.clone();
+                self.finish_key_jump(None);
+                self.set_message(
+                    format!("No key on screen starts with {}", query),
+                    MessageLevel::Error,
+                );
+            }
+            [(path, _)] => {
+                let path = path.clone();
+                self.finish_key_jump(Some(path));
+            }
+            _ => {
+                let reserved = next_letters(matching.iter().map(|(_, key)| *key), &jump.query);
+                let mut labels = labels(matching.len(), &reserved).into_iter();
+                jump.targets = matching
+                    .iter()
+                    .map(|(path, _)| (path.clone(), labels.next()))
+                    .collect();
+                self.tree_view.set_jump_labels(
+                    jump.targets
+                        .iter()
+                        .filter_map(|(path, label)| Some((path.clone(), (*label)?.to_string())))
+                        .collect(),
+                );
+                self.set_message(
+                    format!(
+                        "f{}: {} keys; type more, a label, or Enter for the next",
+                        jump.query,
+                        jump.targets.len()
+                    ),
+                    MessageLevel::Info,
+                );
+                self.key_jump = Some(jump);
+            }
+        }
+    }
+
+    /// Starts hint mode (`s`): labels every line on screen but the cursor's,
+    /// nearest first.
+    pub fn start_hints(&mut self) {
+        let lines = self.tree_view.lines();
+        let start = self.scroll_offset.min(lines.len());
+        let end = (start + self.viewport_height).min(lines.len());
+        let cursor = self.tree_view.line_of(self.cursor.path()).unwrap_or(start);
+        let mut on_screen: Vec<usize> = (start..end).filter(|&i| i != cursor).collect();
+        // Nearest first; below the cursor before above at the same distance
+        on_screen.sort_by_key(|&i| (i.abs_diff(cursor), i < cursor));
+        let labels = super::hints::labels(on_screen.len());
+        let targets: Vec<_> = on_screen
+            .into_iter()
+            .zip(labels)
+            .map(|(i, label)| (lines[i].path.clone(), label))
+            .collect();
+        if targets.is_empty() {
+            self.set_message("No other line on screen".to_string(), MessageLevel::Error);
+            return;
+        }
+        self.update_hints(super::hints::Hints {
+            typed: String::new(),
+            targets,
+        });
+    }
+
+    /// Returns hint mode in progress, if any.
+    pub fn hints(&self) -> Option<&super::hints::Hints> {
+        self.hints.as_ref()
+    }
+
+    /// Handles a letter typed in hint mode: jumps to the line it completes
+    /// the label of, or narrows the hints to the labels it starts.
+    pub fn hints_input(&mut self, c: char) {
+        let Some(mut hints) = self.hints.take() else {
+            return;
+        };
+        hints.typed.push(c);
+        let remaining: Vec<_> = hints.remaining().collect();
+        match remaining.as_slice() {
+            [] => {
+                self.finish_hints(None);
+                self.set_message(
+                    format!("No line labelled {}", hints.typed),
+                    MessageLevel::Error,
+                );
+            }
+            [(path, label)] if *label == hints.typed => {
+                let path = path.clone();
+                self.finish_hints(Some(path));
+            }
+            _ => self.update_hints(hints),
+        }
+    }
+
+    /// Removes the last letter typed in hint mode.
+    pub fn hints_backspace(&mut self) {
+        if let Some(mut hints) = self.hints.take() {
+            hints.typed.pop();
+            self.update_hints(hints);
+        }
+    }
+
+    /// Ends hint mode, moving the cursor to `target` if there is one.
+    pub fn finish_hints(&mut self, target: Option<Vec<usize>>) {
+        self.hints = None;
+        self.tree_view.set_jump_labels(HashMap::new());
+        self.clear_message();
+        if let Some(path) = target {
+            self.record_jump();
+            self.cursor.set_path(path);
+        }
+    }
+
+    /// Shows what is left to type of the labels matching the letters typed.
+    fn update_hints(&mut self, hints: super::hints::Hints) {
+        self.tree_view.set_jump_labels(
+            hints
+                .remaining()
+                .map(|(path, label)| (path.clone(), label[hints.typed.len()..].to_string()))
+                .collect(),
+        );
+        self.set_message(
+            format!("s{}: type a label", hints.typed),
+            MessageLevel::Info,
+        );
+        self.hints = Some(hints);
+    }
+
+    /// Records the current cursor position in the jump list.
+    ///
+    /// This should be called before any "big jump" (gg, G, search, marks)
+    /// but not for regular movement commands (j, k, h, l).
+    pub fn record_jump(&mut self) {
+        let cursor = self.cursor.path().to_vec();
+        self.jumplist.record_jump(cursor);
+        self.track_nodes();
+    }
+
+    /// Jumps backward in the jump list.
+    ///
+    /// Returns true if successful, false if already at oldest jump.
+    pub fn jump_backward(&mut self) -> bool {
+        if let Some(path) = self.jumplist.jump_backward() {
+            self.cursor.set_path(path.clone());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Jumps forward in the jump list.
+    ///
+    /// Returns true if successful, false if already at newest jump.
+    pub fn jump_forward(&mut self) -> bool {
+        if let Some(path) = self.jumplist.jump_forward() {
+            self.cursor.set_path(path.clone());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns a reference to the mark set.
+    pub fn marks(&self) -> &MarkSet {
+        &self.marks
+    }
+
+    /// Returns a mutable reference to the mark set.
+    pub fn marks_mut(&mut self) -> &mut MarkSet {
+        &mut self.marks
+    }
+
+    /// Returns whether we're waiting for a mark name after 'm'.
+    pub fn pending_mark_set(&self) -> bool {
+        self.pending_mark_set
+    }
+
+    /// Sets the pending mark set state.
+    pub fn set_pending_mark_set(&mut self, pending: bool) {
+        self.pending_mark_set = pending;
+    }
+
+    /// Returns whether we're waiting for a mark name after '\''.
+    pub fn pending_mark_jump(&self) -> bool {
+        self.pending_mark_jump
+    }
+
+    /// Sets the pending mark jump state.
+    pub fn set_pending_mark_jump(&mut self, pending: bool) {
+        self.pending_mark_jump = pending;
+    }
+
+    /// Sets a mark at the current cursor position.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The mark name (a-z)
+    pub fn set_mark(&mut self, name: char) {
+        let cursor = self.cursor.path().to_vec();
+        self.marks.set_mark(name, cursor);
+        self.track_nodes();
+    }
+
+    /// Jumps to a previously set mark.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The mark name (a-z)
+    ///
+    /// # Returns
+    ///
+    /// Returns true if the mark exists and the jump was successful,
+    /// false if the mark doesn't exist.
+    pub fn jump_to_mark(&mut self, name: char) -> bool {
+        if let Some(path) = self.marks.get_mark(name) {
+            self.cursor.set_path(path.clone());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Explains the value under the cursor in a popup (`K`): for a cron
+    /// schedule, what it means and when it runs next.
+    pub fn explain_at_cursor(&mut self) -> anyhow::Result<()> {
+        use super::explain::{explain, Context};
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let path = self.cursor.path();
+        let node = self
+            .tree
+            .get_node(path)
+            .ok_or_else(|| anyhow::anyhow!("Nothing to explain here"))?;
+        let mut context = Context {
+            now: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0),
+            ..Context::default()
+        };
+        if let Some((&index, parent)) = path.split_last() {
+            match self.tree.get_node(parent).map(|n| n.value()) {
+                Some(YamlValue::Object(fields)) => {
+                    context.key = fields.get_index(index).map(|(key, _)| key.as_str());
+                    context.siblings = fields
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| *i != index)
+                        .map(|(_, (key, value))| (key.clone(), value.value()))
+                        .collect();
+                }
+                Some(YamlValue::Array(items)) => {
+                    // A list item takes the list's key
+                    if let Some((&list_index, grandparent)) = parent.split_last() {
+                        if let Some(YamlValue::Object(fields)) =
+                            self.tree.get_node(grandparent).map(|n| n.value())
+                        {
+                            context.key = fields.get_index(list_index).map(|(key, _)| key.as_str());
+                        }
+                    }
+                    context.siblings = items
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| *i != index)
+                        .map(|(i, item)| (format!("item {}", i), item.value()))
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+        match explain(node.value(), &context) {
+            Ok(Some(explanation)) => {
+                self.explanation = Some(explanation);
+                Ok(())
+            }
+            Ok(None) => anyhow::bail!("Nothing to explain here"),
+            Err(e) => anyhow::bail!(e),
+        }
+    }
+
+    /// Returns the `K` popup's explanation, while it is open.
+    pub fn explanation(&self) -> Option<&super::explain::Explanation> {
+        self.explanation.as_ref()
+    }
+
+    /// Closes the `K` popup.
+    pub fn close_explanation(&mut self) {
+        self.explanation = None;
+    }
+
+    /// Opens the `gD` popup, diffing the node at the cursor against the file
+    /// on disk.
+    pub fn show_node_diff(&mut self) -> anyhow::Result<()> {
+        let filename = self
+            .filename
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No file name"))?;
+        let disk = self.load_from_disk(&filename)?;
+        self.node_diff = Some(super::review::node_diff(
+            disk.root(),
+            self.tree.root(),
+            self.cursor.path(),
+            "disk",
+        ));
+        Ok(())
+    }
+
+    /// Returns the `gD` popup's diff, while it is open.
+    pub fn node_diff(&self) -> Option<&super::review::NodeDiff> {
+        self.node_diff.as_ref()
+    }
+
+    /// Closes the `gD` popup.
+    pub fn close_node_diff(&mut self) {
+        self.node_diff = None;
+    }
+
+    /// Opens the `:marks` picker over the set marks.
+    pub fn open_mark_picker(&mut self) -> anyhow::Result<()> {
+        use super::marks::{MarkEntry, MarkPickerState};
+        use crate::ui::tree_view::format_collapsed_preview;
+
+        if self.marks.is_empty() {
+            anyhow::bail!("No marks set (m{{a-z}} sets one)");
+        }
+        let entries = self
+            .marks
+            .iter()
+            .map(|(name, path)| MarkEntry {
+                name,
+                path: path.to_vec(),
+                location: self
+                    .path_to_string(path, "dot")
+                    .unwrap_or_else(|| ".".to_string()),
+                preview: self
+                    .tree
+                    .get_node(path)
+                    .map(|node| format_collapsed_preview(node, 40))
+                    .unwrap_or_default(),
+            })
+            .collect();
+        self.mark_picker = Some(MarkPickerState::new(entries));
+        Ok(())
+    }
+
+    /// Returns the mark picker, if open.
+    pub fn mark_picker(&self) -> Option<&super::marks::MarkPickerState> {
+        self.mark_picker.as_ref()
+    }
+
+    /// Returns the mark picker mutably, if open.
+    pub fn mark_picker_mut(&mut self) -> Option<&mut super::marks::MarkPickerState> {
+        self.mark_picker.as_mut()
+    }
+
+    /// Closes the mark picker without jumping.
+    pub fn close_mark_picker(&mut self) {
+        self.mark_picker = None;
+    }
+
+    /// Closes the mark picker and jumps to the selected mark.
+    pub fn mark_picker_select(&mut self) {
+        let path = self
+            .mark_picker
+            .take()
+            .and_then(|picker| picker.selected().map(|mark| mark.path.clone()));
+        if let Some(path) = path {
+            self.record_jump();
+            self.reveal_path(path);
+        }
+    }
+
+    /// Sets the directory where project bookmark files are kept.
+    ///
+    /// `None` keeps bookmarks in memory for this session only.
+    pub fn set_bookmark_dir(&mut self, dir: Option<std::path::PathBuf>) {
+        self.bookmark_dir = dir;
+    }
+
+    /// Returns the directory where project bookmark files are kept.
+    pub fn bookmark_dir(&self) -> Option<&std::path::Path> {
+        self.bookmark_dir.as_deref()
+    }
+
+    /// Loads the bookmarks of the current file's project.
+    ///
+    /// Bookmarks are read from disk every time, so changes made from other
+    /// buffers (or other yamlquill processes) are always seen.
+    pub fn bookmark_store(&self) -> anyhow::Result<super::bookmarks::BookmarkStore> {
+        use super::bookmarks::{project_root, BookmarkStore};
+
+        let dir = self
+            .file_dir()
+            .ok_or_else(|| anyhow::anyhow!("Bookmarks need a file name (use :w <filename>)"))?;
+        let root = project_root(&dir);
+        Ok(match &self.bookmark_dir {
+            Some(store_dir) => BookmarkStore::for_project(&root, store_dir),
+            None => BookmarkStore::in_memory(&root),
+        })
+    }
+
+    /// Bookmarks the node under the cursor as `name`, replacing any bookmark of that name.
+    pub fn add_bookmark(&mut self, name: &str) -> anyhow::Result<()> {
+        use super::bookmarks::Bookmark;
+
+        let mut store = self.bookmark_store()?;
+        let file = self
+            .file_path()
+            .and_then(|path| store.relative_file(&path))
+            .ok_or_else(|| anyhow::anyhow!("File is outside the project"))?;
+        let path = self
+            .path_to_string(self.cursor.path(), "bracket")
+            .ok_or_else(|| anyhow::anyhow!("Cannot bookmark this node"))?;
+        if let Some(node) = self.tree.get_node(self.cursor.path()) {
+            self.bookmarked_nodes.insert(name.to_string(), node.id());
+        }
+        store.add(Bookmark {
+            name: name.to_string(),
+            file,
+            path,
+        });
+        store.save()
+    }
+
+    /// Deletes the bookmark called `name`.
+    pub fn remove_bookmark(&mut self, name: &str) -> anyhow::Result<()> {
+        let mut store = self.bookmark_store()?;
+        if !store.remove(name) {
+            anyhow::bail!("No bookmark named {}", name);
+        }
+        store.save()
+    }
+
+    /// Jumps to the bookmark called `name`.
+    ///
+    /// A bookmark in another file is opened through the buffer list. If the
+    /// node has moved, the stored path is brought up to date.
+    pub fn go_to_bookmark(&mut self, name: &str) -> anyhow::Result<()> {
+        let store = self.bookmark_store()?;
+        let bookmark = store
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("No bookmark named {}", name))?;
+        let target = store.file_path(bookmark);
+        if self.file_path().as_deref() == Some(target.as_path()) {
+            // A node bookmarked this session is followed even if edits
+            // changed its YAMLPath; otherwise the stored path is resolved,
+            // tolerating keys renamed or moved since
+            let moved = self
+                .bookmarked_nodes
+                .get(name)
+                .and_then(|&id| self.tree.path_of(id));
+            let path = match moved {
+                Some(path) => path,
+                None => super::bookmarks::resolve(self.tree.root(), &bookmark.path)?,
+            };
+            if let Some(node) = self.tree.get_node(&path) {
+                self.bookmarked_nodes.insert(name.to_string(), node.id());
+            }
+            self.record_jump();
+            self.cursor.set_path(path);
+            match self.path_to_string(self.cursor.path(), "bracket") {
+                Some(current) if current != bookmark.path => self.add_bookmark(name),
+                _ => Ok(()),
+            }
+        } else {
+            self.request_buffer_command(crate::app::BufferCommand::Jump {
+                file: target.to_string_lossy().to_string(),
+                bookmark: name.to_string(),
+            });
+            Ok(())
+        }
+    }
+
+    /// Moves the cursor to the first node matching a YAMLPath, recording a jump.
+    pub fn jump_to_yamlpath(&mut self, query: &str) -> anyhow::Result<()> {
+        use crate::yamlpath::{Evaluator, Parser};
+
+        let path = Parser::parse(query).map_err(|e| anyhow::anyhow!("{}", e))?;
+        let target = Evaluator::new(self.tree.root())
+            .evaluate_paths(&path.segments)
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Not found: {}", query))?;
+        self.record_jump();
+        self.cursor.set_path(target);
+        Ok(())
+    }
+
+    /// Returns the pinned paths, in pin order.
+    pub fn pins(&self) -> &[Vec<usize>] {
+        &self.pins
+    }
+
+    /// Pins the node at the cursor to the pin panel and returns its number.
+    pub fn pin_cursor(&mut self) -> anyhow::Result<usize> {
+        let path = self.cursor.path().to_vec();
+        if path.is_empty() {
+            anyhow::bail!("Can't pin the root");
+        }
+        if let Some(index) = self.pins.iter().position(|p| *p == path) {
+            anyhow::bail!("Already pinned as {}", index + 1);
+        }
+        if self.pins.len() >= MAX_PINS {
+            anyhow::bail!("All {} pins are in use (:unpin one first)", MAX_PINS);
+        }
+        self.pins.push(path);
+        self.track_nodes();
+        Ok(self.pins.len())
+    }
+
+    /// Removes pin `number` (1-based), or the pin at the cursor when `None`.
+    /// Returns the removed path.
+    pub fn unpin(&mut self, number: Option<usize>) -> anyhow::Result<Vec<usize>> {
+        let index = match number {
+            Some(n) if (1..=self.pins.len()).contains(&n) => n - 1,
+            Some(n) => anyhow::bail!("No pin {}", n),
+            None => self
+                .pins
+                .iter()
+                .position(|p| p.as_slice() == self.cursor.path())
+                .ok_or_else(|| anyhow::anyhow!("The cursor isn't on a pinned node"))?,
+        };
+        if self.pins.len() == 1 {
+            self.pin_targets.clear();
+        }
+        Ok(self.pins.remove(index))
+    }
+
+    /// Removes every pin and returns how many there were.
+    pub fn clear_pins(&mut self) -> usize {
+        self.pin_targets.clear();
+        std::mem::take(&mut self.pins).len()
+    }
+
+    /// Moves the cursor to pin `number` (1-based), recording a jump.
+    pub fn jump_to_pin(&mut self, number: usize) -> anyhow::Result<()> {
+        let Some(path) = number.checked_sub(1).and_then(|i| self.pins.get(i)) else {
+            anyhow::bail!("No pin {}", number);
+        };
+        let path = path.clone();
+        self.record_jump();
+        self.reveal_path(path);
+        Ok(())
+    }
+
+    /// Returns the watch expressions, in the order they were added.
+    pub fn watches(&self) -> &[Watch] {
+        &self.watches
+    }
+
+    /// Adds a watch for the YAMLPath `expression` and returns its number.
+    pub fn add_watch(&mut self, expression: &str) -> anyhow::Result<usize> {
+        if let Some(index) = self
+            .watches
+            .iter()
+            .position(|w| w.expression() == expression)
+        {
+            anyhow::bail!("Already watched as {}", index + 1);
+        }
+        if self.watches.len() >= MAX_WATCHES {
+            anyhow::bail!(
+                "All {} watches are in use (:unwatch one first)",
+                MAX_WATCHES
+            );
+        }
+        self.watches.push(Watch::new(expression, self.tree.root())?);
+        Ok(self.watches.len())
+    }
+
+    /// Removes watch `number` (1-based) and returns it.
+    pub fn remove_watch(&mut self, number: usize) -> anyhow::Result<Watch> {
+        match number.checked_sub(1) {
+            Some(index) if index < self.watches.len() => Ok(self.watches.remove(index)),
+            _ => anyhow::bail!("No watch {}", number),
+        }
+    }
+
+    /// Removes every watch and returns how many there were.
+    pub fn clear_watches(&mut self) -> usize {
+        std::mem::take(&mut self.watches).len()
+    }
+
+    /// Opens the `:bookmarks` picker for the current project.
+    pub fn open_bookmark_picker(&mut self) -> anyhow::Result<()> {
+        use super::bookmarks::BookmarkPickerState;
+
+        let store = self.bookmark_store()?;
+        if store.list().is_empty() {
+            anyhow::bail!("No bookmarks (use :bookmark add <name>)");
+        }
+        self.bookmark_picker = Some(BookmarkPickerState::new(store.list().to_vec()));
+        Ok(())
+    }
+
+    /// Returns the bookmark picker, if open.
+    pub fn bookmark_picker(&self) -> Option<&super::bookmarks::BookmarkPickerState> {
+        self.bookmark_picker.as_ref()
+    }
+
+    /// Returns the bookmark picker mutably, if open.
+    pub fn bookmark_picker_mut(&mut self) -> Option<&mut super::bookmarks::BookmarkPickerState> {
+        self.bookmark_picker.as_mut()
+    }
+
+    /// Closes the bookmark picker without jumping.
+    pub fn close_bookmark_picker(&mut self) {
+        self.bookmark_picker = None;
+    }
+
+    /// Closes the bookmark picker and jumps to the selected bookmark.
+    pub fn bookmark_picker_select(&mut self) -> anyhow::Result<()> {
+        let name = self
+            .bookmark_picker
+            .take()
+            .and_then(|picker| picker.selected().map(|b| b.name.clone()));
+        match name {
+            Some(name) => self.go_to_bookmark(&name),
+            None => Ok(()),
+        }
+    }
+
+    /// Opens the `:backups` browser over the current file's backups.
+    pub fn open_backup_picker(&mut self) -> anyhow::Result<()> {
+        use super::backups::{find_backups, load_backup, BackupPickerState};
+
+        let path = self
+            .file_path()
+            .ok_or_else(|| anyhow::anyhow!("No file name"))?;
+        let backups = find_backups(&path)
+            .iter()
+            .map(|backup| load_backup(backup, self.tree.root()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if backups.is_empty() {
+            anyhow::bail!("No backups of this file (:set create_backup makes one on each save)");
+        }
+        self.backup_picker = Some(BackupPickerState::new(backups));
+        Ok(())
+    }
+
+    /// Returns the backup browser, if open.
+    pub fn backup_picker(&self) -> Option<&super::backups::BackupPickerState> {
+        self.backup_picker.as_ref()
+    }
+
+    /// Returns the backup browser mutably, if open.
+    pub fn backup_picker_mut(&mut self) -> Option<&mut super::backups::BackupPickerState> {
+        self.backup_picker.as_mut()
+    }
+
+    /// Closes the backup browser without restoring.
+    pub fn close_backup_picker(&mut self) {
+        self.backup_picker = None;
+    }
+
+    /// Closes the backup browser and replaces the document with the selected
+    /// backup, as one change that `u` undoes.
+    pub fn restore_backup(&mut self) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        let Some(backup) = self
+            .backup_picker
+            .take()
+            .and_then(|picker| picker.selected().cloned())
+        else {
+            return Ok(());
+        };
+        if backup.changes.is_empty() {
+            self.set_message(
+                "The buffer already matches this backup".to_string(),
+                MessageLevel::Info,
+            );
+            return Ok(());
+        }
+
+        let mut root = backup.root;
+        root.mark_all_modified();
+        crate::document::ops::Op::Replace {
+            path: Vec::new(),
+            node: root,
+        }
+        .apply(&mut self.tree)?;
+        self.track_nodes();
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        if self.tree.get_node(self.cursor.path()).is_none() {
+            let first = self.tree_view.lines().first().map(|l| l.path.clone());
+            self.cursor.set_path(first.unwrap_or_default());
+        }
+        self.checkpoint();
+        self.set_message(
+            format!("Restored {}; :w to save, u to undo", backup.path.display()),
+            MessageLevel::Info,
+        );
+        Ok(())
+    }
+
+    /// Opens the `:containers` picker over the document's containers.
+    pub fn open_container_picker(&mut self) -> anyhow::Result<()> {
+        use super::k8s::{containers, ContainerPickerState};
+
+        self.ensure_k8s()?;
+        let found = containers(self.tree.root());
+        if found.is_empty() {
+            anyhow::bail!("No containers in this document");
+        }
+        self.container_picker = Some(ContainerPickerState::new(found));
+        Ok(())
+    }
+
+    /// Returns the container picker, if open.
+    pub fn container_picker(&self) -> Option<&super::k8s::ContainerPickerState> {
+        self.container_picker.as_ref()
+    }
+
+    /// Returns the container picker mutably, if open.
+    pub fn container_picker_mut(&mut self) -> Option<&mut super::k8s::ContainerPickerState> {
+        self.container_picker.as_mut()
+    }
+
+    /// Closes the container picker without jumping.
+    pub fn close_container_picker(&mut self) {
+        self.container_picker = None;
+    }
+
+    /// Closes the container picker and jumps to the selected container.
+    pub fn container_picker_select(&mut self) {
+        let path = self
+            .container_picker
+            .take()
+            .and_then(|picker| picker.selected().map(|c| c.path.clone()));
+        if let Some(path) = path {
+            self.record_jump();
+            self.reveal_path(path);
+        }
+    }
+
+    /// Opens the `:graph` overlay over the document's CI pipeline.
+    pub fn open_pipeline_graph(&mut self) -> anyhow::Result<()> {
+        use super::pipeline::{pipeline, GraphState};
+
+        let Some(found) = pipeline(self.tree.root()) else {
+            anyhow::bail!("No GitHub Actions or GitLab CI jobs in this document");
+        };
+        self.pipeline_graph = Some(GraphState::new(found));
+        Ok(())
+    }
+
+    /// Returns the pipeline graph, if open.
+    pub fn pipeline_graph(&self) -> Option<&super::pipeline::GraphState> {
+        self.pipeline_graph.as_ref()
+    }
+
+    /// Returns the pipeline graph mutably, if open.
+    pub fn pipeline_graph_mut(&mut self) -> Option<&mut super::pipeline::GraphState> {
+        self.pipeline_graph.as_mut()
+    }
+
+    /// Closes the pipeline graph without jumping.
+    pub fn close_pipeline_graph(&mut self) {
+        self.pipeline_graph = None;
+    }
+
+    /// Closes the pipeline graph and jumps to the selected job's definition.
+    pub fn pipeline_graph_select(&mut self) {
+        let path = self
+            .pipeline_graph
+            .take()
+            .and_then(|graph| graph.selected().map(|job| job.path.clone()));
+        if let Some(path) = path {
+            self.record_jump();
+            self.reveal_path(path);
+        }
+    }
+
+    /// Sets the tag of every container image, or only of images whose
+    /// repository is `image` (in full or its last segment), for `:images`.
+    ///
+    /// `tag` may be written with or without its `:`; one starting with `@`
+    /// is a digest. The edit is one undo step. Returns the number of images
+    /// changed.
+    pub fn set_image_tags(&mut self, image: Option<&str>, tag: &str) -> anyhow::Result<usize> {
+        self.ensure_k8s()?;
+        self.ensure_writable()?;
+        self.load_all_deferred()?;
+        use super::k8s::{containers, image_matches, split_image};
+        use crate::document::node::{YamlString, YamlValue};
+
+        let tag = if tag.starts_with('@') {
+            tag.to_string()
+        } else {
+            format!(":{}", tag.trim_start_matches(':'))
+        };
+        let mut changed = 0;
+        for container in containers(self.tree.root()) {
+            if container.image.is_empty()
+                || image.is_some_and(|image| !image_matches(&container.image, image))
+            {
+                continue;
+            }
+            let updated = format!("{}{}", split_image(&container.image).0, tag);
+            if updated == container.image {
+                continue;
+            }
+            if let Some(YamlValue::Object(fields)) = self
+                .tree
+                .get_node_mut(&container.path)
+                .map(|n| n.value_mut())
+            {
+                if let Some(node) = fields.get_mut("image") {
+                    *node.value_mut() = YamlValue::String(YamlString::Plain(updated));
+                    changed += 1;
+                }
+            }
+        }
+
+        if changed > 0 {
+            self.mark_dirty();
+            self.rebuild_tree_view();
+            self.checkpoint();
+        }
+        Ok(changed)
+    }
+
+    /// Sets an env var in the container under the cursor (`:env NAME=value`).
+    ///
+    /// An existing entry gets the new `value` (dropping any `valueFrom`);
+    /// otherwise one is appended, creating the `env` list if needed. Returns
+    /// true if the variable was added.
+    pub fn set_container_env(&mut self, name: &str, value: &str) -> anyhow::Result<bool> {
+        self.ensure_k8s()?;
+        self.ensure_writable()?;
+        use super::k8s::enclosing_container;
+        use crate::document::node::{YamlString, YamlValue};
+        use anyhow::bail;
+
+        let Some(path) = enclosing_container(self.tree.root(), self.cursor.path()) else {
+            bail!("Put the cursor in a container");
+        };
+        let string = |s: &str| YamlNode::new(YamlValue::String(YamlString::Plain(s.to_string())));
+        let Some(YamlValue::Object(fields)) = self.tree.get_node_mut(&path).map(|n| n.value_mut())
+        else {
+            bail!("Put the cursor in a container");
+        };
+        let env = fields
+            .entry("env".to_string())
+            .or_insert_with(|| YamlNode::new(YamlValue::Array(Vec::new())));
+        if matches!(env.value(), YamlValue::Null) {
+            *env = YamlNode::new(YamlValue::Array(Vec::new()));
+        }
+        let YamlValue::Array(vars) = env.value_mut() else {
+            bail!("The container's env is not a list");
+        };
+
+        let existing = vars.iter().position(|var| match var.value() {
+            YamlValue::Object(entry) => matches!(
+                entry.get("name").map(|n| n.value()),
+                Some(YamlValue::String(s)) if s.as_str() == name
+            ),
+            _ => false,
+        });
+        let added = match existing {
+            Some(index) => {
+                if let YamlValue::Object(entry) = vars[index].value_mut() {
+                    entry.shift_remove("valueFrom");
+                    match entry.get_mut("value") {
+                        Some(node) => {
+                            *node.value_mut() =
+                                YamlValue::String(YamlString::Plain(value.to_string()))
+                        }
+                        None => {
+                            entry.insert("value".to_string(), string(value));
+                        }
+                    }
+                }
+                false
             }
-        } else {
-            self.set_message(
-                "Can only rename object keys, not array elements".to_string(),
-                MessageLevel::Error,
-            );
-        }
-    }
+            None => {
+                let mut entry = indexmap::IndexMap::new();
+                entry.insert("name".to_string(), string(name));
+                entry.insert("value".to_string(), string(value));
+                vars.push(YamlNode::new(YamlValue::Object(entry)));
+                true
+            }
+        };
 
-    /// Commits the rename operation, updating the key name in the object.
-    pub fn commit_rename(&mut self) -> anyhow::Result<()> {
-        use crate::document::node::YamlValue;
-        use anyhow::anyhow;
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        Ok(added)
+    }
 
-        let new_key = self
-            .edit_buffer
-            .as_ref()
-            .ok_or_else(|| anyhow!("No edit buffer"))?
-            .clone();
+    /// Loads the notes sidecar for the current file.
+    pub fn load_notes(&mut self) {
+        use super::notes::{notes_file_path, NoteStore};
 
-        if new_key.is_empty() {
-            return Err(anyhow!("Key cannot be empty"));
+        if self.safe_mode {
+            return;
+        }
+        let Some(filename) = self.filename.clone() else {
+            return;
+        };
+        match NoteStore::load_from(&notes_file_path(std::path::Path::new(&filename))) {
+            Ok(notes) => self.notes = notes,
+            Err(e) => self.set_message(format!("{}", e), MessageLevel::Warning),
         }
+        self.refresh_note_markers();
+    }
 
-        let original_key = self
-            .rename_original_key
-            .as_ref()
-            .ok_or_else(|| anyhow!("No original key stored"))?
-            .clone();
+    /// Returns the notes of the current file.
+    pub fn notes(&self) -> &super::notes::NoteStore {
+        &self.notes
+    }
 
-        // If key didn't change, just exit
-        if new_key == original_key {
-            self.cancel_rename();
-            return Ok(());
-        }
+    /// Returns the note on the node under the cursor.
+    pub fn note_at_cursor(&self) -> Option<&str> {
+        let path = self.path_to_string(self.cursor.path(), "bracket")?;
+        self.notes.get(&path)
+    }
 
-        let current_path = self.cursor.path().to_vec();
-        if current_path.is_empty() {
-            return Err(anyhow!("Cannot rename root"));
+    /// Attaches a note to the node under the cursor and saves the sidecar.
+    ///
+    /// The document itself is not modified.
+    pub fn set_note(&mut self, text: &str) -> anyhow::Result<()> {
+        let path = self
+            .path_to_string(self.cursor.path(), "bracket")
+            .ok_or_else(|| anyhow::anyhow!("Cannot annotate this node"))?;
+        let mut notes = self.notes.clone();
+        notes.set(&path, text);
+        self.save_notes(notes)
+    }
+
+    /// Removes the note on the node under the cursor and saves the sidecar.
+    pub fn remove_note(&mut self) -> anyhow::Result<()> {
+        let path = self
+            .path_to_string(self.cursor.path(), "bracket")
+            .unwrap_or_default();
+        let mut notes = self.notes.clone();
+        if !notes.remove(&path) {
+            anyhow::bail!("No note on this node");
         }
+        self.save_notes(notes)
+    }
 
-        let parent_path = &current_path[..current_path.len() - 1];
-        let current_index = current_path[current_path.len() - 1];
+    /// Writes `notes` to the sidecar and makes them current.
+    fn save_notes(&mut self, notes: super::notes::NoteStore) -> anyhow::Result<()> {
+        use super::notes::notes_file_path;
 
-        // Get parent and verify it's still an object
-        let parent = if parent_path.is_empty() {
-            self.tree.root_mut()
-        } else {
-            self.tree
-                .get_node_mut(parent_path)
-                .ok_or_else(|| anyhow!("Parent node not found"))?
-        };
+        let filename = self
+            .filename
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("Notes need a file name (use :w <filename>)"))?;
+        // Kept for the session only in safe mode
+        if !self.safe_mode {
+            notes.save_to(&notes_file_path(std::path::Path::new(filename)))?;
+        }
+        self.notes = notes;
+        self.refresh_note_markers();
+        Ok(())
+    }
 
-        if let YamlValue::Object(entries) = parent.value_mut() {
-            // Check if new key already exists
-            if entries.iter().any(|(k, _)| k == &new_key) {
-                return Err(anyhow!("Key '{}' already exists", new_key));
-            }
+    /// Resolves each note's YAMLPath to a node and marks it in the tree view.
+    ///
+    /// Notes whose node no longer exists keep their entry but show no marker.
+    pub fn refresh_note_markers(&mut self) {
+        let markers = self
+            .resolved_notes()
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        self.tree_view.set_note_markers(markers);
+    }
 
-            // Update the key at the current index by removing and reinserting
-            if let Some((_old_key, value)) = entries.shift_remove_index(current_index) {
-                // Reinsert with new key at the same position
-                entries.shift_insert(current_index, new_key.clone(), value);
+    /// Returns each note whose node still exists, with the node's tree path.
+    fn resolved_notes(&self) -> Vec<(Vec<usize>, &super::notes::Note)> {
+        use crate::yamlpath::{Evaluator, Parser};
 
-                self.mark_dirty();
-                self.rebuild_tree_view();
-                self.checkpoint();
-                self.set_message(
-                    format!("Renamed '{}' to '{}'", original_key, new_key),
-                    MessageLevel::Info,
-                );
-            } else {
-                return Err(anyhow!("Invalid object index"));
+        self.notes
+            .list()
+            .iter()
+            .filter_map(|note| {
+                let query = Parser::parse(&note.path).ok()?;
+                let path = Evaluator::new(self.tree.root())
+                    .evaluate_paths(&query.segments)
+                    .into_iter()
+                    .next()?;
+                Some((path, note))
+            })
+            .collect()
+    }
+
+    /// Opens the `:tasks` panel listing TODO and FIXME markers in comments and notes.
+    pub fn open_task_list(&mut self) -> anyhow::Result<()> {
+        use super::tasks::{find_marker, scan_comments, Task, TaskListState, TaskSource};
+
+        let mut tasks = scan_comments(self.tree.root());
+        for (path, note) in self.resolved_notes() {
+            if let Some(marker) = find_marker(&note.text) {
+                tasks.push(Task {
+                    marker,
+                    text: note.text.trim().to_string(),
+                    path,
+                    source: TaskSource::Note,
+                });
             }
-        } else {
-            return Err(anyhow!("Parent is not an object"));
         }
-
-        self.cancel_rename();
+        if tasks.is_empty() {
+            anyhow::bail!("No TODO or FIXME in comments or notes");
+        }
+        tasks.sort_by(|a, b| a.path.cmp(&b.path));
+        self.task_list = Some(TaskListState::new(tasks));
         Ok(())
     }
 
-    /// Cancels the rename operation and clears related state.
-    pub fn cancel_rename(&mut self) {
-        self.is_renaming_key = false;
-        self.rename_original_key = None;
-        self.edit_buffer = None;
-        self.edit_cursor = 0;
+    /// Returns the task panel, if open.
+    pub fn task_list(&self) -> Option<&super::tasks::TaskListState> {
+        self.task_list.as_ref()
     }
 
-    /// Returns whether we're currently in rename mode.
-    pub fn is_renaming_key(&self) -> bool {
-        self.is_renaming_key
+    /// Returns the task panel mutably, if open.
+    pub fn task_list_mut(&mut self) -> Option<&mut super::tasks::TaskListState> {
+        self.task_list.as_mut()
     }
 
-    /// Starts a comment addition operation at the current cursor position.
-    ///
-    /// Opens an edit prompt for entering the comment text. The comment will be
-    /// added at the cursor position with a default position of Above.
-    ///
-    /// For now, this implementation adds comments with CommentPosition::Above.
-    /// A future enhancement could prompt the user to select the position type
-    /// (Above/Line/Below).
-    pub fn start_add_comment_operation(&mut self) {
-        use crate::document::node::YamlValue;
+    /// Closes the task panel without jumping.
+    pub fn close_task_list(&mut self) {
+        self.task_list = None;
+    }
 
-        // Clear any previous messages so the edit area is visible
-        self.clear_message();
+    /// Closes the task panel and moves the cursor to the selected task,
+    /// expanding collapsed parents so it is visible.
+    pub fn task_list_select(&mut self) {
+        let Some(path) = self
+            .task_list
+            .take()
+            .and_then(|panel| panel.selected().map(|task| task.path.clone()))
+        else {
+            return;
+        };
+        let mut expanded = self.tree_view.expanded_paths().clone();
+        expanded.extend((0..path.len()).map(|depth| path[..depth].to_vec()));
+        self.tree_view.set_expanded_paths(expanded);
+        self.rebuild_tree_view();
+        self.record_jump();
+        self.cursor.set_path(path);
+    }
 
-        // Verify cursor is on a valid node (not a comment)
-        let current_path = self.cursor.path().to_vec();
-        if let Some(current_node) = self.tree.get_node(&current_path) {
-            match current_node.value() {
-                YamlValue::Comment(_) => {
-                    self.set_message(
-                        "Cannot add comment to comment node".to_string(),
-                        MessageLevel::Error,
-                    );
-                }
-                _ => {
-                    // Valid node - start comment entry
-                    // Enter Insert mode with empty edit buffer
-                    self.edit_buffer = Some(String::new());
-                    self.edit_cursor = 0;
-                    self.set_mode(EditorMode::Insert);
-                    self.reset_cursor_blink();
-                    // Set mode indicator message with prompt
-                    self.set_message(
-                        "-- INSERT (Comment) -- Enter text and press Enter to save, Esc to cancel"
-                            .to_string(),
-                        MessageLevel::Info,
-                    );
-                    // Store that we're in comment-add mode
-                    // We'll use the add_mode_stage for this
-                    self.add_mode_stage = AddModeStage::AwaitingComment;
-                }
-            }
-        } else {
-            self.set_message("No node at cursor".to_string(), MessageLevel::Error);
+    /// Lints the file as loaded, or as `:w` would write it once edited, with
+    /// the enabled rules, replacing the `lint` diagnostics and the quickfix
+    /// list. The quickfix panel opens when there are problems. Returns the
+    /// number of problems.
+    pub fn run_lint(&mut self) -> anyhow::Result<usize> {
+        use super::lint::{lint, LINT_SOURCE};
+
+        let text = match self.tree.original_source() {
+            Some(source) if !self.dirty => source.to_string(),
+            _ => crate::file::saver::yaml_text(&self.tree)?,
+        };
+        let rules: Vec<_> = self
+            .lint_rules()
+            .into_iter()
+            .filter_map(|(rule, enabled)| enabled.then_some(rule))
+            .collect();
+        let problems = lint(&text, self.tree.root(), &rules, self.lint_line_length)?;
+        let count = problems.len();
+        self.set_problems(LINT_SOURCE, problems);
+        let entries = self.diagnostic_entries(Some(LINT_SOURCE));
+        self.set_quickfix("Lint", entries);
+        Ok(count)
+    }
+
+    /// Returns every lint rule and whether it is enabled.
+    pub fn lint_rules(&self) -> Vec<(super::lint::Rule, bool)> {
+        super::lint::Rule::ALL
+            .into_iter()
+            .map(|rule| {
+                (
+                    rule,
+                    !self.lint_disabled.iter().any(|name| name == rule.name()),
+                )
+            })
+            .collect()
+    }
+
+    /// Enables or disables a lint rule by name for later `:lint` runs.
+    pub fn set_lint_rule(&mut self, name: &str, enable: bool) -> anyhow::Result<()> {
+        use super::lint::Rule;
+
+        if Rule::from_name(name).is_none() {
+            let names: Vec<_> = Rule::ALL.iter().map(|rule| rule.name()).collect();
+            anyhow::bail!("Unknown lint rule: {} (rules: {})", name, names.join(", "));
+        }
+        self.lint_disabled.retain(|disabled| disabled != name);
+        if !enable {
+            self.lint_disabled.push(name.to_string());
         }
+        Ok(())
     }
 
-    /// Commits a comment add operation after receiving the comment text.
+    /// Starts resolving repeated keys (`:dedupe`), prompting for the first.
     ///
-    /// Creates a new Comment node with the entered text and inserts it into
-    /// the tree near the current cursor position. Currently adds comments with
-    /// CommentPosition::Above.
-    pub fn commit_add_comment(&mut self) -> anyhow::Result<()> {
-        use crate::document::node::{CommentNode, CommentPosition, YamlNode, YamlValue};
-        use anyhow::anyhow;
+    /// Fails if there are none or the buffer is read-only.
+    pub fn start_dedupe(&mut self) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        if crate::document::duplicates::find(self.tree.root()).is_empty() {
+            anyhow::bail!("No duplicate keys");
+        }
+        self.record_jump();
+        self.dedupe = Some(super::dedupe::DedupeSession::default());
+        self.prompt_dedupe();
+        Ok(())
+    }
 
-        // Get the comment text from edit buffer
-        let comment_text = self
-            .edit_buffer
-            .clone()
-            .ok_or_else(|| anyhow!("No comment text in buffer"))?;
+    /// Returns whether the `:dedupe` prompt is waiting for an answer.
+    pub fn dedupe_pending(&self) -> bool {
+        self.dedupe.is_some()
+    }
 
-        // Empty comments are allowed
-        // Create a comment node with Above position (TODO: prompt for position)
-        let comment_node = YamlNode::new(YamlValue::Comment(CommentNode::new(
-            comment_text,
-            CommentPosition::Above,
-        )));
+    /// Answers the `:dedupe` prompt for the current repeated key: resolves
+    /// it as one undoable edit, or skips it when `resolution` is `None`, and
+    /// moves on to the next.
+    pub fn answer_dedupe(
+        &mut self,
+        resolution: Option<super::dedupe::Resolution>,
+    ) -> anyhow::Result<()> {
+        use crate::document::duplicates::find;
+        use crate::document::ops::apply_all;
 
-        // Get current path
-        let current_path = self.cursor.path().to_vec();
+        let Some(mut session) = self.dedupe else {
+            return Ok(());
+        };
+        let Some(resolution) = resolution else {
+            session.skipped += 1;
+            self.dedupe = Some(session);
+            self.prompt_dedupe();
+            return Ok(());
+        };
+        let Some(duplicate) = find(self.tree.root()).into_iter().nth(session.skipped) else {
+            self.prompt_dedupe();
+            return Ok(());
+        };
+        let parent_path = &duplicate.paths[0][..duplicate.paths[0].len() - 1];
+        let parent = self
+            .tree
+            .get_node(parent_path)
+            .ok_or_else(|| anyhow::anyhow!("No mapping at {:?}", parent_path))?;
+        let (ops, kept) = resolution.ops(&duplicate, parent);
+        if let Err(e) = apply_all(&mut self.tree, &ops) {
+            self.dedupe = None;
+            return Err(e);
+        }
+        self.cursor.set_path(kept);
+        self.clear_search_results();
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        session.resolved += 1;
+        self.dedupe = Some(session);
+        self.prompt_dedupe();
+        Ok(())
+    }
 
-        // Insert comment as sibling before current node
-        // This requires getting the parent and inserting at the current index
-        if current_path.is_empty() {
-            // Can't add comment to root
-            return Err(anyhow!("Cannot add comment to root node"));
+    /// Closes the `:dedupe` prompt, leaving the remaining keys as they are.
+    pub fn stop_dedupe(&mut self) {
+        if let Some(session) = self.dedupe.take() {
+            let remaining = crate::document::duplicates::find(self.tree.root()).len();
+            self.set_message(
+                format!(
+                    "Resolved {} duplicate key(s), {} left",
+                    session.resolved, remaining
+                ),
+                MessageLevel::Info,
+            );
         }
+    }
 
-        let parent_path = &current_path[..current_path.len() - 1];
-        let current_index = current_path[current_path.len() - 1];
-
-        let parent = if parent_path.is_empty() {
-            self.tree.root_mut()
-        } else {
-            self.tree
-                .get_node_mut(parent_path)
-                .ok_or_else(|| anyhow!("Parent node not found"))?
+    /// Shows the `:dedupe` prompt for the next repeated key, or ends the
+    /// session when every key has been resolved or skipped.
+    fn prompt_dedupe(&mut self) {
+        let Some(session) = self.dedupe else {
+            return;
         };
+        let duplicates = crate::document::duplicates::find(self.tree.root());
+        let Some(duplicate) = duplicates.get(session.skipped) else {
+            self.dedupe = None;
+            let message = match session.skipped {
+                0 => format!("Resolved {} duplicate key(s)", session.resolved),
+                skipped => format!(
+                    "Resolved {} duplicate key(s), skipped {}",
+                    session.resolved, skipped
+                ),
+            };
+            self.set_message(message, MessageLevel::Info);
+            return;
+        };
+        let location = self
+            .path_to_string(&duplicate.paths[0], "bracket")
+            .unwrap_or_else(|| "$".to_string());
+        let count = duplicate.paths.len();
+        self.reveal_path(duplicate.paths[1].clone());
+        self.set_message(
+            format!(
+                "Duplicate key {} ({} entries): keep (f)irst, (l)ast, (r)ename the others, (s)kip, (q)uit",
+                location, count
+            ),
+            MessageLevel::Warning,
+        );
+    }
 
-        match parent.value_mut() {
-            YamlValue::Array(arr) => {
-                // Insert comment before current element in array
-                arr.insert(current_index, comment_node);
-                self.mark_dirty();
-                self.rebuild_tree_view();
-                self.checkpoint();
+    /// Returns the diagnostics from `source`, or all of them, as quickfix
+    /// entries.
+    fn diagnostic_entries(&self, source: Option<&str>) -> Vec<super::quickfix::QuickfixEntry> {
+        self.diagnostics
+            .items()
+            .iter()
+            .filter(|d| source.is_none_or(|source| d.source == source))
+            .cloned()
+            .map(Into::into)
+            .collect()
+    }
+
+    /// Replaces the quickfix list, opening its panel if it has entries.
+    pub fn set_quickfix(&mut self, title: &str, entries: Vec<super::quickfix::QuickfixEntry>) {
+        let list = super::quickfix::QuickfixList::new(title, entries);
+        self.quickfix_open = !list.entries.is_empty();
+        self.quickfix = Some(list);
+    }
+
+    /// Opens the quickfix panel (`:copen`).
+    ///
+    /// With no source the current list is shown, or every diagnostic if
+    /// there is none. `diagnostics` lists every diagnostic (schema, value
+    /// checks, lint) and `search` every match of the last search, including
+    /// those inside collapsed nodes. Returns the number of entries.
+    pub fn open_quickfix(&mut self, source: Option<&str>) -> anyhow::Result<usize> {
+        use super::diagnostics::Severity;
+        use super::quickfix::QuickfixEntry;
+
+        match source {
+            None if self.quickfix.is_some() => {}
+            None | Some("diagnostics") => {
+                let entries = self.diagnostic_entries(None);
+                self.set_quickfix("Diagnostics", entries);
             }
-            YamlValue::Object(entries) => {
-                // Find a unique __comment_N__ key
-                let mut n = 0;
-                loop {
-                    let candidate = format!("__comment_{}__", n);
-                    if !entries.contains_key(&candidate) {
-                        break;
-                    }
-                    n += 1;
+            Some("search") => {
+                use crate::editor::search::SearchPattern;
+
+                if self.search_buffer.is_empty() {
+                    anyhow::bail!("No previous search");
                 }
-                let key = format!("__comment_{}__", n);
-                entries.shift_insert(current_index, key, comment_node);
-                self.mark_dirty();
-                self.rebuild_tree_view();
-                self.checkpoint();
-            }
-            YamlValue::MultiDoc(docs) => {
-                // Insert comment before current document
-                docs.insert(current_index, comment_node);
-                self.mark_dirty();
-                self.rebuild_tree_view();
-                self.checkpoint();
+                let pattern = SearchPattern::parse(&self.search_buffer, self.regex_search)?;
+                let mut matches = self.search_tree(&pattern);
+                matches.retain(|path| self.tree_view.passes_filter(path));
+                let entries = matches
+                    .iter()
+                    .map(|path| QuickfixEntry {
+                        path: path.clone(),
+                        location: self
+                            .path_to_string(path, "bracket")
+                            .unwrap_or_else(|| "$".to_string()),
+                        severity: Severity::Info,
+                        message: self
+                            .tree
+                            .get_node(path)
+                            .map(|node| match node.value() {
+                                value if value.is_object() => "(mapping)".to_string(),
+                                value if value.is_array() => "(list)".to_string(),
+                                value => super::usages::scalar_text(value).unwrap_or_default(),
+                            })
+                            .unwrap_or_default(),
+                    })
+                    .collect();
+                self.set_quickfix("Search", entries);
             }
-            _ => {
-                return Err(anyhow!("Parent is not a container"));
+            Some(other) => {
+                anyhow::bail!("Unknown quickfix source: {} (diagnostics, search)", other)
             }
         }
+        let count = self.quickfix.as_ref().map_or(0, |list| list.entries.len());
+        if count == 0 {
+            self.quickfix_open = false;
+            anyhow::bail!("Quickfix list is empty");
+        }
+        self.quickfix_open = true;
+        Ok(count)
+    }
 
-        // Clear add mode state
-        self.add_mode_stage = AddModeStage::None;
-        self.edit_buffer = None;
-        self.edit_cursor = 0;
+    /// Returns the quickfix list, if any.
+    pub fn quickfix(&self) -> Option<&super::quickfix::QuickfixList> {
+        self.quickfix.as_ref()
+    }
+
+    /// Returns the quickfix list while its panel is open.
+    pub fn quickfix_panel(&self) -> Option<&super::quickfix::QuickfixList> {
+        self.quickfix.as_ref().filter(|_| self.quickfix_open)
+    }
+
+    /// Returns the quickfix list mutably while its panel is open.
+    pub fn quickfix_panel_mut(&mut self) -> Option<&mut super::quickfix::QuickfixList> {
+        self.quickfix.as_mut().filter(|_| self.quickfix_open)
+    }
+
+    /// Closes the quickfix panel, keeping the list (`:cclose`).
+    pub fn close_quickfix_panel(&mut self) {
+        self.quickfix_open = false;
+    }
+
+    /// Closes the quickfix panel and moves the cursor to the selected entry.
+    pub fn quickfix_select(&mut self) {
+        self.quickfix_open = false;
+        let Some(path) = self
+            .quickfix
+            .as_mut()
+            .and_then(|list| list.choose().map(|entry| entry.path.clone()))
+        else {
+            return;
+        };
+        self.record_jump();
+        self.reveal_path(path);
+    }
+
+    /// Moves the cursor to the next quickfix entry (`]q`, `:cnext`), or the
+    /// previous one (`[q`, `:cprev`), wrapping around. Returns the entry,
+    /// numbered like `(2/5) ...`.
+    pub fn quickfix_step(&mut self, forward: bool) -> anyhow::Result<String> {
+        let list = self
+            .quickfix
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("No quickfix list (:lint, :copen)"))?;
+        let Some(entry) = list.step(forward).cloned() else {
+            anyhow::bail!("Quickfix list is empty");
+        };
+        let message = format!("{} {}", list.position(), entry.message);
+        self.record_jump();
+        self.reveal_path(entry.path);
+        Ok(message)
+    }
+
+    /// Opens the `:facet` panel counting the values of a dotted field across
+    /// the records of a multi-document or JSONL file.
+    pub fn open_facet_panel(&mut self, field: &str) -> anyhow::Result<()> {
+        use super::facet::{compute_facets, FacetPanelState};
+        use crate::document::node::YamlValue;
 
+        let YamlValue::MultiDoc(records) = self.tree.root().value() else {
+            anyhow::bail!("Facets need a multi-document or JSONL file");
+        };
+        let values = compute_facets(records, field);
+        if values.iter().all(|v| v.value.is_none()) {
+            anyhow::bail!("No record has '{}'", field);
+        }
+        self.facet_panel = Some(FacetPanelState::new(
+            field.to_string(),
+            values,
+            records.len(),
+        ));
         Ok(())
     }
 
-    /// Cancels a comment add operation.
-    pub fn cancel_add_comment(&mut self) {
-        self.add_mode_stage = AddModeStage::None;
-        self.edit_buffer = None;
-        self.edit_cursor = 0;
+    /// Returns the facet panel, if open.
+    pub fn facet_panel(&self) -> Option<&super::facet::FacetPanelState> {
+        self.facet_panel.as_ref()
     }
 
-    /// Commits a container add operation after receiving the key name.
-    ///
-    /// Called when user finishes entering a key name for adding a container
-    /// to an object. Retrieves the temporarily stored container from clipboard
-    /// and inserts it with the provided key.
-    pub fn commit_container_add(&mut self) -> anyhow::Result<()> {
-        use anyhow::anyhow;
+    /// Returns the facet panel mutably, if open.
+    pub fn facet_panel_mut(&mut self) -> Option<&mut super::facet::FacetPanelState> {
+        self.facet_panel.as_mut()
+    }
 
-        // Get the container from temporary storage
-        let container_node = self
-            .temp_container
-            .take()
-            .ok_or_else(|| anyhow!("No container to add"))?;
+    /// Closes the facet panel without filtering.
+    pub fn close_facet_panel(&mut self) {
+        self.facet_panel = None;
+    }
 
-        // Get the key from add_key_buffer
-        let key = self.add_key_buffer.clone();
-        if key.is_empty() {
-            return Err(anyhow!("Key cannot be empty"));
+    /// Closes the facet panel and narrows the view to the records with the
+    /// selected value. Returns how many records are shown.
+    pub fn facet_panel_select(&mut self) -> Option<usize> {
+        let panel = self.facet_panel.take()?;
+        let facet = panel.selected()?;
+        let records = facet.records.iter().map(|&i| vec![i]).collect();
+        self.set_view_filter(records, format!("{}={}", panel.field, facet.label()));
+        Some(facet.records.len())
+    }
+
+    /// Narrows the tree view to `paths`, their ancestors and descendants,
+    /// described by `label` in the status line. The cursor moves to the
+    /// first shown line if its node is filtered out.
+    pub fn set_view_filter(&mut self, paths: HashSet<Vec<usize>>, label: String) {
+        self.tree_view.set_filter(Some(paths));
+        self.view_filter_label = Some(label);
+        self.rebuild_tree_view();
+        let lines = self.tree_view.lines();
+        if self.tree_view.line_of(self.cursor.path()).is_none() {
+            if let Some(first) = lines.first() {
+                let path = first.path.clone();
+                self.cursor.set_path(path);
+            }
         }
+    }
 
-        // Get insertion point
-        let insertion_path = self
-            .add_insertion_point
-            .as_ref()
-            .ok_or_else(|| anyhow!("No insertion point set"))?
-            .clone();
+    /// Narrows the view to the nodes matching `query` and their ancestors and
+    /// descendants (`:filter`), expanding collapsed ancestors so every match
+    /// shows. A query starting with `$` is a YAMLPath; anything else is a `/`
+    /// search pattern. Replaces any active filter. Returns the number of
+    /// matches.
+    pub fn filter_view(&mut self, query: &str) -> anyhow::Result<usize> {
+        use crate::editor::search::SearchPattern;
 
-        // Insert the container with the key
-        self.tree
-            .insert_node_in_object(&insertion_path, key.clone(), container_node.clone())?;
+        let paths = if query.starts_with('$') {
+            self.query_paths(query)?
+        } else {
+            let pattern = SearchPattern::parse(query, self.regex_search)?;
+            self.search_tree(&pattern)
+        };
+        if paths.is_empty() {
+            anyhow::bail!("No matches for {}", query);
+        }
+
+        let mut expanded = self.tree_view.expanded_paths().clone();
+        for path in &paths {
+            expanded.extend((1..path.len()).map(|depth| path[..depth].to_vec()));
+        }
+        self.tree_view.set_expanded_paths(expanded);
+        let count = paths.len();
+        self.set_view_filter(paths.into_iter().collect(), query.to_string());
+        Ok(count)
+    }
 
-        self.tree_view_mut()
-            .update_paths_after_insertion(&insertion_path);
+    /// Shows the whole tree again (`:nofilter`). Returns false if the view
+    /// was not filtered.
+    pub fn clear_view_filter(&mut self) -> bool {
+        if self.view_filter_label.take().is_none() {
+            return false;
+        }
+        self.tree_view.set_filter(None);
         self.rebuild_tree_view();
-        self.cursor.set_path(insertion_path.clone());
-        self.mark_dirty();
-        self.checkpoint();
+        true
+    }
 
-        let container_type = match container_node.value() {
-            YamlValue::Object(_) => "object",
-            YamlValue::Array(_) => "array",
-            _ => "container",
-        };
-        self.set_message(
-            format!("Added empty {} '{}'", container_type, key),
-            MessageLevel::Info,
-        );
+    /// Returns the description of the active view filter, if any.
+    pub fn view_filter_label(&self) -> Option<&str> {
+        self.view_filter_label.as_deref()
+    }
 
-        // Clear add operation state
-        self.cancel_add_operation();
+    /// Starts review mode over the changes since the file on disk, or since
+    /// git `HEAD` when `head` is true (`:review`, `:review head`).
+    pub fn start_review(&mut self, head: bool) -> anyhow::Result<()> {
+        use super::review::ReviewState;
+        use crate::document::parser::parse_yaml_auto;
+
+        let filename = self
+            .filename
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No file name"))?;
+        let (base, label) = if head {
+            let path = self
+                .file_path()
+                .ok_or_else(|| anyhow::anyhow!("No file name"))?;
+            let dir = path.parent().unwrap_or(std::path::Path::new("."));
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let output = std::process::Command::new("git")
+                .arg("-C")
+                .arg(dir)
+                .arg("show")
+                .arg(format!("HEAD:./{}", name))
+                .output()
+                .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e))?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "git show failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            (
+                parse_yaml_auto(&String::from_utf8_lossy(&output.stdout))?,
+                "HEAD",
+            )
+        } else {
+            let tree = self.load_from_disk(&filename)?;
+            (tree.root().clone(), "disk")
+        };
 
+        let review = ReviewState::new(base, label, self.tree.root());
+        if review.changes().is_empty() {
+            self.set_message(format!("No changes against {}", label), MessageLevel::Info);
+            return Ok(());
+        }
+        self.review = Some(review);
+        self.mode = EditorMode::Review;
+        self.record_jump();
+        self.focus_review_change();
         Ok(())
     }
 
-    // Visual mode, marks, jump list, and repeat command accessors
+    /// Returns the review in progress, if any.
+    pub fn review(&self) -> Option<&super::review::ReviewState> {
+        self.review.as_ref()
+    }
 
-    /// Returns a reference to the jump list.
-    pub fn jumplist(&self) -> &JumpList {
-        &self.jumplist
+    /// Moves to the next change under review.
+    pub fn review_next(&mut self) {
+        if let Some(review) = self.review.as_mut() {
+            review.next();
+        }
+        self.focus_review_change();
     }
 
-    /// Returns a mutable reference to the jump list.
-    pub fn jumplist_mut(&mut self) -> &mut JumpList {
-        &mut self.jumplist
+    /// Moves to the previous change under review.
+    pub fn review_previous(&mut self) {
+        if let Some(review) = self.review.as_mut() {
+            review.previous();
+        }
+        self.focus_review_change();
     }
 
-    /// Records the current cursor position in the jump list.
-    ///
-    /// This should be called before any "big jump" (gg, G, search, marks)
-    /// but not for regular movement commands (j, k, h, l).
-    pub fn record_jump(&mut self) {
-        let cursor = self.cursor.path().to_vec();
-        self.jumplist.record_jump(cursor);
+    /// Keeps the current change and moves to the next one, finishing the
+    /// review once every change has been kept.
+    pub fn review_keep(&mut self) {
+        if let Some(review) = self.review.as_mut() {
+            review.keep();
+        }
+        self.finish_review_if_done();
     }
 
-    /// Jumps backward in the jump list.
-    ///
-    /// Returns true if successful, false if already at oldest jump.
-    pub fn jump_backward(&mut self) -> bool {
-        if let Some(path) = self.jumplist.jump_backward() {
-            self.cursor.set_path(path.clone());
-            true
-        } else {
-            false
+    /// Reverts the current change to the base version as one undo step.
+    pub fn review_revert(&mut self) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        use super::review::ChangeKind;
+
+        let Some(change) = self.review.as_ref().and_then(|r| r.current()).cloned() else {
+            return Ok(());
+        };
+        let path = super::review::revert(self.tree.root_mut(), &change)?;
+        if change.kind == ChangeKind::Modified {
+            // The node was swapped for the base version in place
+            self.track_nodes();
         }
+        self.finish_review_revert(path)
     }
 
-    /// Jumps forward in the jump list.
-    ///
-    /// Returns true if successful, false if already at newest jump.
-    pub fn jump_forward(&mut self) -> bool {
-        if let Some(path) = self.jumplist.jump_forward() {
-            self.cursor.set_path(path.clone());
-            true
-        } else {
-            false
+    fn finish_review_revert(&mut self, focus: Vec<usize>) -> anyhow::Result<()> {
+        self.mark_dirty();
+        self.rebuild_tree_view();
+        self.checkpoint();
+        if let Some(review) = self.review.as_mut() {
+            review.note_reverted();
+            review.refresh(self.tree.root());
         }
+        self.cursor.set_path(focus);
+        self.finish_review_if_done();
+        Ok(())
     }
 
-    /// Returns a reference to the mark set.
-    pub fn marks(&self) -> &MarkSet {
-        &self.marks
+    /// Leaves review mode with the cursor on the current change so it can be
+    /// edited by hand.
+    pub fn review_edit(&mut self) {
+        self.focus_review_change();
+        self.close_review();
     }
 
-    /// Returns a mutable reference to the mark set.
-    pub fn marks_mut(&mut self) -> &mut MarkSet {
-        &mut self.marks
+    /// Leaves review mode, leaving any remaining changes in the buffer.
+    pub fn close_review(&mut self) {
+        if let Some(review) = self.review.take() {
+            let (kept, reverted) = review.counts();
+            self.set_message(
+                format!("Review closed: {} kept, {} reverted", kept, reverted),
+                MessageLevel::Info,
+            );
+        }
+        self.mode = EditorMode::Normal;
     }
 
-    /// Returns whether we're waiting for a mark name after 'm'.
-    pub fn pending_mark_set(&self) -> bool {
-        self.pending_mark_set
+    fn finish_review_if_done(&mut self) {
+        let Some(review) = self.review.as_ref() else {
+            return;
+        };
+        if review.remaining() > 0 {
+            self.focus_review_change();
+            return;
+        }
+        let (kept, reverted) = review.counts();
+        self.review = None;
+        self.mode = EditorMode::Normal;
+        self.set_message(
+            format!("Review done: {} kept, {} reverted", kept, reverted),
+            MessageLevel::Info,
+        );
     }
 
-    /// Sets the pending mark set state.
-    pub fn set_pending_mark_set(&mut self, pending: bool) {
-        self.pending_mark_set = pending;
+    /// Moves the cursor to the current change, or for a removed node to
+    /// where it used to be, expanding collapsed parents.
+    fn focus_review_change(&mut self) {
+        let Some(change) = self.review.as_ref().and_then(|r| r.current()) else {
+            return;
+        };
+        let location = if change.kind == super::review::ChangeKind::Removed {
+            &change.location[..change.location.len() - 1]
+        } else {
+            &change.location[..]
+        };
+        let Some(mut path) = self.resolve_location(location) else {
+            return;
+        };
+        if change.kind == super::review::ChangeKind::Removed {
+            // Land on the node now in its place, if any
+            path.push(change.insert_at);
+            if self.tree.get_node(&path).is_none() {
+                path.pop();
+            }
+        }
+        let mut expanded = self.tree_view.expanded_paths().clone();
+        expanded.extend((0..path.len()).map(|depth| path[..depth].to_vec()));
+        self.tree_view.set_expanded_paths(expanded);
+        self.rebuild_tree_view();
+        self.cursor.set_path(path);
     }
 
-    /// Returns whether we're waiting for a mark name after '\''.
-    pub fn pending_mark_jump(&self) -> bool {
-        self.pending_mark_jump
+    /// Resolves a location of keys and indices to a tree path in the buffer.
+    fn resolve_location(&self, location: &[super::review::Step]) -> Option<Vec<usize>> {
+        super::review::resolve(self.tree.root(), location)
     }
 
-    /// Sets the pending mark jump state.
-    pub fn set_pending_mark_jump(&mut self, pending: bool) {
-        self.pending_mark_jump = pending;
+    /// Returns the keys and indices leading to a tree path, which stay valid
+    /// when keys are reordered.
+    fn location_of(&self, path: &[usize]) -> Option<Vec<super::review::Step>> {
+        use super::review::Step;
+
+        let mut node = self.tree.root();
+        let mut location = Vec::with_capacity(path.len());
+        for &index in path {
+            let (step, child) = match node.value() {
+                YamlValue::Object(entries) => {
+                    let (key, child) = entries.get_index(index)?;
+                    (Step::Key(key.clone()), child)
+                }
+                YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+                    (Step::Index(index), items.get(index)?)
+                }
+                _ => return None,
+            };
+            location.push(step);
+            node = child;
+        }
+        Some(location)
     }
 
-    /// Sets a mark at the current cursor position.
+    /// Stages the changes at and below the cursor node, or below each node
+    /// matching a YAMLPath, for `:w --staged` (`:stage`).
     ///
-    /// # Arguments
-    ///
-    /// * `name` - The mark name (a-z)
-    pub fn set_mark(&mut self, name: char) {
-        let cursor = self.cursor.path().to_vec();
-        self.marks.set_mark(name, cursor);
+    /// Changes are found against the file on disk. Returns the number of
+    /// changes newly staged.
+    pub fn stage(&mut self, query: Option<&str>) -> anyhow::Result<usize> {
+        use super::review::diff;
+
+        let paths = match query {
+            Some(query) => self.query_paths(query)?,
+            None => vec![self.cursor.path().to_vec()],
+        };
+        let locations: Vec<_> = paths
+            .iter()
+            .filter_map(|path| self.location_of(path))
+            .collect();
+        let base = self.disk_root()?;
+
+        let is_under = |location: &[super::review::Step], prefixes: &[Vec<super::review::Step>]| {
+            prefixes.iter().any(|prefix| location.starts_with(prefix))
+        };
+        let count = diff(&base, self.tree.root())
+            .iter()
+            .filter(|change| {
+                is_under(&change.location, &locations) && !is_under(&change.location, &self.staged)
+            })
+            .count();
+        if count == 0 {
+            anyhow::bail!("No unstaged changes here");
+        }
+        for location in locations {
+            if !self.staged.contains(&location) {
+                self.staged.push(location);
+            }
+        }
+        Ok(count)
     }
 
-    /// Jumps to a previously set mark.
-    ///
-    /// # Arguments
-    ///
-    /// * `name` - The mark name (a-z)
-    ///
-    /// # Returns
+    /// Empties the staging queue (`:unstage`), returning the number of
+    /// locations that were staged.
+    pub fn unstage(&mut self) -> usize {
+        std::mem::take(&mut self.staged).len()
+    }
+
+    /// Returns the staged locations.
+    pub fn staged(&self) -> &[Vec<super::review::Step>] {
+        &self.staged
+    }
+
+    /// Writes only the staged changes to disk (`:w --staged`), leaving the
+    /// rest in the buffer.
     ///
-    /// Returns true if the mark exists and the jump was successful,
-    /// false if the mark doesn't exist.
-    pub fn jump_to_mark(&mut self, name: char) -> bool {
-        if let Some(path) = self.marks.get_mark(name) {
-            self.cursor.set_path(path.clone());
-            true
-        } else {
-            false
+    /// Returns the number of changes written and the number still unsaved.
+    /// The buffer is marked clean if nothing is left.
+    pub fn write_staged(&mut self) -> anyhow::Result<(usize, usize)> {
+        self.ensure_writable()?;
+        use super::review::{apply_staged, diff};
+        use crate::file::exporter::save_file;
+
+        if self.staged.is_empty() {
+            anyhow::bail!("Nothing staged (use :stage)");
+        }
+        let filename = self
+            .filename
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No file name"))?;
+        let base = self.disk_root()?;
+        let (root, written) = apply_staged(&base, self.tree.root(), &self.staged);
+        if written == 0 {
+            anyhow::bail!("Staged nodes no longer differ from disk");
+        }
+
+        let left = diff(&root, self.tree.root()).len();
+        let tree = YamlTree::with_source(root, self.tree.original_source().map(String::from));
+        save_file(&filename, &tree, &self.to_config())?;
+        self.staged.clear();
+        self.record_disk_state();
+        if left == 0 {
+            self.clear_dirty();
+            self.write_undo_history();
+        }
+        Ok((written, left))
+    }
+
+    /// Loads the current file from disk.
+    fn disk_root(&self) -> anyhow::Result<YamlNode> {
+        let filename = self
+            .filename
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("No file name"))?;
+        Ok(self.load_from_disk(filename)?.root().clone())
+    }
+
+    /// Loads a file whole, checked against the buffer's load limits.
+    fn load_from_disk(&self, filename: &str) -> anyhow::Result<crate::document::tree::YamlTree> {
+        crate::file::loader::load_yaml_file_lazy(filename, usize::MAX, &self.load_limits)
+    }
+
+    /// Opens the note popup for the node under the cursor.
+    pub fn show_note_at_cursor(&mut self) -> anyhow::Result<()> {
+        use super::notes::Note;
+
+        let path = self
+            .path_to_string(self.cursor.path(), "bracket")
+            .unwrap_or_default();
+        let text = self
+            .notes
+            .get(&path)
+            .ok_or_else(|| anyhow::anyhow!("No note on this node (use :note <text>)"))?;
+        self.note_popup = Some(vec![Note {
+            text: text.to_string(),
+            path,
+        }]);
+        Ok(())
+    }
+
+    /// Opens the note popup listing every note in the file.
+    pub fn show_all_notes(&mut self) -> anyhow::Result<()> {
+        if self.notes.is_empty() {
+            anyhow::bail!("No notes (use :note <text>)");
         }
+        self.note_popup = Some(self.notes.list().to_vec());
+        Ok(())
+    }
+
+    /// Returns the notes shown in the popup, if open.
+    pub fn note_popup(&self) -> Option<&[super::notes::Note]> {
+        self.note_popup.as_deref()
+    }
+
+    /// Closes the note popup.
+    pub fn close_note_popup(&mut self) {
+        self.note_popup = None;
+    }
+
+    /// Returns the absolute path of the current file.
+    fn file_path(&self) -> Option<std::path::PathBuf> {
+        let name = std::path::Path::new(self.filename.as_deref()?).file_name()?;
+        self.file_dir().map(|dir| dir.join(name))
     }
 
     /// Yanks nodes from cursor to mark (motion-to-mark: y'a).
@@ -4314,6 +10424,7 @@ impl EditorState {
     /// Calculates the range of visible nodes between cursor and mark,
     /// then deletes all nodes in that range.
     pub fn delete_to_mark(&mut self, mark_path: &[usize], count: u32) -> anyhow::Result<()> {
+        self.ensure_writable()?;
         let range = self.calculate_range_to_mark(mark_path)?;
         self.delete_nodes_in_range(&range, count)
     }
@@ -4328,14 +10439,14 @@ impl EditorState {
         let lines = self.tree_view.lines();
 
         // Find cursor and mark positions in visible lines
-        let cursor_idx = lines
-            .iter()
-            .position(|l| l.path == cursor_path)
+        let cursor_idx = self
+            .tree_view
+            .line_of(cursor_path)
             .ok_or_else(|| anyhow!("Cursor position not found in visible lines"))?;
 
-        let mark_idx = lines
-            .iter()
-            .position(|l| l.path.as_slice() == mark_path)
+        let mark_idx = self
+            .tree_view
+            .line_of(mark_path)
             .ok_or_else(|| anyhow!("Mark position not found in visible lines"))?;
 
         // Calculate range (inclusive, handles both directions)
@@ -4436,7 +10547,6 @@ impl EditorState {
 
         for path in sorted_range {
             self.tree.delete_node(&path)?;
-            self.tree_view_mut().update_paths_after_deletion(&path);
         }
 
         self.mark_dirty();
@@ -4478,24 +10588,119 @@ impl EditorState {
         self.mode = EditorMode::Visual;
     }
 
+    /// Enters visual block mode on the field under the cursor (Ctrl-v).
+    ///
+    /// The cursor must be on a key of a mapping inside a list. Moving to
+    /// other elements of that list then selects the same key in each of them,
+    /// so `y` yanks a column and `p` pastes one element by element.
+    pub fn enter_visual_block_mode(&mut self) -> anyhow::Result<()> {
+        let path = self.cursor.path().to_vec();
+        if self.column_key(&path).is_none() {
+            anyhow::bail!("Visual block needs the cursor on a key of a list element");
+        }
+        self.visual_anchor = Some(path.clone());
+        self.visual_selection = vec![path];
+        self.visual_block = true;
+        self.mode = EditorMode::Visual;
+        Ok(())
+    }
+
+    /// Returns whether visual mode is selecting a column (Ctrl-v).
+    pub fn is_visual_block(&self) -> bool {
+        self.visual_block
+    }
+
     /// Exits visual mode and returns to normal mode.
     pub fn exit_visual_mode(&mut self) {
+        self.clear_visual_selection();
+        self.mode = EditorMode::Normal;
+    }
+
+    /// Drops the visual selection without changing modes, once a `:` command
+    /// typed from visual mode has had a chance to use it.
+    pub fn clear_visual_selection(&mut self) {
         self.visual_anchor = None;
         self.visual_selection.clear();
-        self.mode = EditorMode::Normal;
+        self.visual_block = false;
+    }
+
+    /// Returns the key at `path` when it is a field of a mapping that is an
+    /// element of a list.
+    fn column_key(&self, path: &[usize]) -> Option<String> {
+        use crate::document::node::YamlValue;
+
+        if path.len() < 2 {
+            return None;
+        }
+        let list = self.tree.get_node(&path[..path.len() - 2])?;
+        if !matches!(list.value(), YamlValue::Array(_)) {
+            return None;
+        }
+        match self.tree.get_node(&path[..path.len() - 1])?.value() {
+            YamlValue::Object(fields) => fields
+                .get_index(path[path.len() - 1])
+                .map(|(key, _)| key.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the list, key and element range covered by the visual block.
+    fn visual_block_range(&self) -> Option<(Vec<usize>, String, usize, usize)> {
+        let anchor = self.visual_anchor.as_ref()?;
+        let key = self.column_key(anchor)?;
+        let list_path = anchor[..anchor.len() - 2].to_vec();
+        let start = anchor[list_path.len()];
+        let cursor = self.cursor.path();
+        let end = if cursor.len() > list_path.len() && cursor.starts_with(&list_path) {
+            cursor[list_path.len()]
+        } else {
+            return None;
+        };
+        Some((list_path, key, start.min(end), start.max(end)))
+    }
+
+    /// Returns the path of `key` in each object element of the list in
+    /// `first..=last`, skipping elements that don't have it.
+    fn column_paths(
+        &self,
+        list_path: &[usize],
+        key: &str,
+        first: usize,
+        last: usize,
+    ) -> Vec<Vec<usize>> {
+        use crate::document::node::YamlValue;
+
+        (first..=last)
+            .filter_map(|index| {
+                let mut element = list_path.to_vec();
+                element.push(index);
+                match self.tree.get_node(&element)?.value() {
+                    YamlValue::Object(fields) => {
+                        element.push(fields.get_index_of(key)?);
+                        Some(element)
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
     }
 
     /// Updates the visual selection based on current cursor position.
     pub fn update_visual_selection(&mut self) {
+        if self.visual_block {
+            // Keep the previous block while the cursor is outside the list
+            if let Some((list_path, key, first, last)) = self.visual_block_range() {
+                self.visual_selection = self.column_paths(&list_path, &key, first, last);
+            }
+            return;
+        }
         if let Some(anchor) = &self.visual_anchor {
             // Calculate selection range based on visible lines
             let lines = self.tree_view.lines();
 
             // Find indices of anchor and cursor in visible lines
-            let anchor_idx = lines.iter().position(|line| &line.path == anchor);
-            let cursor_idx = lines
-                .iter()
-                .position(|line| line.path == *self.cursor.path());
+            let anchor_idx = self.tree_view.line_of(anchor);
+            let cursor_idx = self.tree_view.line_of(self.cursor.path());
 
             if let (Some(a_idx), Some(c_idx)) = (anchor_idx, cursor_idx) {
                 let (start, end) = if a_idx <= c_idx {
@@ -4573,7 +10778,10 @@ impl EditorState {
         }
 
         let count = nodes.len();
-        let content = RegisterContent::new(nodes, keys);
+        let content = match (self.visual_block, keys.first()) {
+            (true, Some(Some(key))) => RegisterContent::column(key, nodes),
+            _ => RegisterContent::new(nodes, keys),
+        };
 
         // Determine target register
         let target_register = self.pending_register;
@@ -4585,6 +10793,11 @@ impl EditorState {
             } else {
                 self.registers.set_named(reg, content.clone());
             }
+            // The + and * registers are the system clipboard
+            if matches!(reg, '+' | '*') {
+                self.check_memory();
+                self.mirror_to_clipboard(&content);
+            }
         } else {
             // Unnamed register
             self.registers.set_unnamed(content.clone());
@@ -4625,6 +10838,7 @@ impl EditorState {
     ///
     /// Returns the number of nodes deleted.
     pub fn delete_visual_selection(&mut self) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
         if self.visual_selection.is_empty() {
             return Ok(0);
         }
@@ -5146,6 +11360,28 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_deletion_preserves_sibling_expansion() {
+        let tree = YamlTree::new(
+            crate::document::parser::parse_yaml_auto(
+                "item1:\n  nested: 1\nitem2:\n  nested: 2\nitem3:\n  nested: 3\n",
+            )
+            .unwrap(),
+        );
+        let mut state = EditorState::new_with_default_theme(tree);
+        state.cursor_mut().set_path(vec![2]);
+        state.toggle_expand_at_cursor();
+        assert!(!state.tree_view().is_expanded(&[2]));
+
+        // Delete item2; item3 moves up and stays collapsed
+        state.cursor_mut().set_path(vec![1]);
+        state.delete_node_at_cursor().unwrap();
+
+        assert!(state.tree_view().is_expanded(&[0]));
+        assert!(!state.tree_view().is_expanded(&[1]));
+        assert!(!state.tree_view().is_expanded(&[2]));
+    }
+
     #[test]
     fn test_edit_invalid_number_rejected() {
         use crate::document::node::{YamlNode, YamlNumber, YamlValue};
@@ -0,0 +1,256 @@
+//! Vim-style search and replace (`:%s/pattern/replacement/flags`).
+//!
+//! The pattern is a [`Regex`] matched against every string scalar in the
+//! document, whether or not it is visible, and with the `k` flag against
+//! mapping keys too. All replacements form one undo step.
+//!
+//! Flags:
+//!
+//! - `g` replace every match in a value, not just the first
+//! - `i` / `I` ignore case / match case (the default)
+//! - `k` also replace in keys
+//! - `n` only count the matches, changing nothing
+
+use super::regex::Regex;
+use crate::document::node::{YamlNode, YamlValue};
+use anyhow::{bail, Result};
+
+/// A parsed `:%s` command.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::substitute::Substitution;
+///
+/// let sub = Substitution::parse("%s/Never/Always/gk").unwrap();
+/// assert_eq!(sub.pattern, "Never");
+/// assert_eq!(sub.replacement, "Always");
+/// assert!(sub.global && sub.keys && !sub.count_only);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Substitution {
+    /// Regular expression to search for
+    pub pattern: String,
+    /// Replacement text; `&` and `\1`..`\9` refer to the match
+    pub replacement: String,
+    /// Replace every match in a value (`g`)
+    pub global: bool,
+    /// Ignore case (`i`)
+    pub ignore_case: bool,
+    /// Also replace in mapping keys (`k`)
+    pub keys: bool,
+    /// Count matches without replacing (`n`)
+    pub count_only: bool,
+}
+
+/// One change found by [`Substitution::plan`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// Replace the string value of the node at `path`
+    Value {
+        /// Path of the string node
+        path: Vec<usize>,
+        /// The value after replacement
+        text: String,
+    },
+    /// Rename the key of the node at `path`
+    Key {
+        /// Path of the node whose key changes
+        path: Vec<usize>,
+        /// The key after replacement
+        key: String,
+    },
+}
+
+impl Change {
+    /// Returns the path of the changed node.
+    pub fn path(&self) -> &[usize] {
+        match self {
+            Change::Value { path, .. } | Change::Key { path, .. } => path,
+        }
+    }
+}
+
+impl Substitution {
+    /// Parses a `%s/pattern/replacement/flags` command.
+    ///
+    /// Any punctuation character may be used as the delimiter instead of
+    /// `/` (`%s#a/b#c#`), and `\` escapes it inside the pattern or
+    /// replacement. The replacement and flags may be omitted.
+    pub fn parse(command: &str) -> Result<Self> {
+        let Some(rest) = command.strip_prefix("%s") else {
+            bail!("Usage: :%s/pattern/replacement/[gikn]");
+        };
+        let mut chars = rest.chars();
+        let delimiter = match chars.next() {
+            Some(c) if !c.is_alphanumeric() && !c.is_whitespace() && c != '\\' => c,
+            _ => bail!("Usage: :%s/pattern/replacement/[gikn]"),
+        };
+
+        let mut fields = vec![String::new()];
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                match chars.next() {
+                    Some(next) if next == delimiter => fields.last_mut().unwrap().push(next),
+                    Some(next) => {
+                        let field = fields.last_mut().unwrap();
+                        field.push('\\');
+                        field.push(next);
+                    }
+                    None => fields.last_mut().unwrap().push('\\'),
+                }
+            } else if c == delimiter && fields.len() < 3 {
+                fields.push(String::new());
+            } else {
+                fields.last_mut().unwrap().push(c);
+            }
+        }
+
+        let mut fields = fields.into_iter();
+        let pattern = fields.next().unwrap_or_default();
+        if pattern.is_empty() {
+            bail!("Empty pattern");
+        }
+        let mut sub = Substitution {
+            pattern,
+            replacement: fields.next().unwrap_or_default(),
+            global: false,
+            ignore_case: false,
+            keys: false,
+            count_only: false,
+        };
+        for flag in fields.next().unwrap_or_default().trim().chars() {
+            match flag {
+                'g' => sub.global = true,
+                'i' => sub.ignore_case = true,
+                'I' => sub.ignore_case = false,
+                'k' => sub.keys = true,
+                'n' => sub.count_only = true,
+                other => bail!("Unknown flag: {}", other),
+            }
+        }
+        Ok(sub)
+    }
+
+    /// Compiles the pattern.
+    pub fn regex(&self) -> Result<Regex> {
+        Regex::with_case(&self.pattern, self.ignore_case)
+    }
+
+    /// Finds every change the substitution would make under `root`, in
+    /// document order, along with the total number of matches replaced.
+    pub fn plan(&self, root: &YamlNode) -> Result<(Vec<Change>, usize)> {
+        let regex = self.regex()?;
+        let mut changes = Vec::new();
+        let mut count = 0;
+        self.walk(&regex, root, &mut Vec::new(), &mut changes, &mut count);
+        Ok((changes, count))
+    }
+
+    fn walk(
+        &self,
+        regex: &Regex,
+        node: &YamlNode,
+        path: &mut Vec<usize>,
+        changes: &mut Vec<Change>,
+        count: &mut usize,
+    ) {
+        match node.value() {
+            YamlValue::String(s) => {
+                let (text, n) = regex.replace(s.as_str(), &self.replacement, self.global);
+                if n > 0 {
+                    *count += n;
+                    changes.push(Change::Value {
+                        path: path.clone(),
+                        text,
+                    });
+                }
+            }
+            YamlValue::Object(entries) => {
+                for (i, (key, child)) in entries.iter().enumerate() {
+                    path.push(i);
+                    if self.keys && !node_is_comment(child) {
+                        let (new_key, n) = regex.replace(key, &self.replacement, self.global);
+                        if n > 0 {
+                            *count += n;
+                            changes.push(Change::Key {
+                                path: path.clone(),
+                                key: new_key,
+                            });
+                        }
+                    }
+                    self.walk(regex, child, path, changes, count);
+                    path.pop();
+                }
+            }
+            YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+                for (i, child) in items.iter().enumerate() {
+                    path.push(i);
+                    self.walk(regex, child, path, changes, count);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn node_is_comment(node: &YamlNode) -> bool {
+    matches!(node.value(), YamlValue::Comment(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    #[test]
+    fn test_parse() {
+        let sub = Substitution::parse("%s#a/b#c\\#d#gi").unwrap();
+        assert_eq!(sub.pattern, "a/b");
+        assert_eq!(sub.replacement, "c#d");
+        assert!(sub.global && sub.ignore_case);
+
+        let sub = Substitution::parse("%s/\\d+/").unwrap();
+        assert_eq!(sub.pattern, "\\d+");
+        assert_eq!(sub.replacement, "");
+        assert!(!sub.global);
+
+        assert!(Substitution::parse("%s//x/").is_err());
+        assert!(Substitution::parse("%s/a/b/z").is_err());
+        assert!(Substitution::parse("%sxaxbx").is_err());
+    }
+
+    #[test]
+    fn test_plan() {
+        let root = parse_yaml_auto("old_name: old old\nlist:\n  - old\n  - 5\n").unwrap();
+
+        let sub = Substitution::parse("%s/old/new/").unwrap();
+        let (changes, count) = sub.plan(&root).unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(
+            changes,
+            [
+                Change::Value {
+                    path: vec![0],
+                    text: "new old".to_string()
+                },
+                Change::Value {
+                    path: vec![1, 0],
+                    text: "new".to_string()
+                },
+            ]
+        );
+
+        let sub = Substitution::parse("%s/old/new/gk").unwrap();
+        let (changes, count) = sub.plan(&root).unwrap();
+        assert_eq!(count, 4);
+        assert_eq!(
+            changes[0],
+            Change::Key {
+                path: vec![0],
+                key: "new_name".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Task list built from TODO and FIXME markers (`:tasks`).
+//!
+//! Big config files collect cleanup work in comments (`# TODO: drop after
+//! the migration`) that is easy to lose track of. `:tasks` scans every
+//! comment in the document, plus the notes attached with `:note`, for the
+//! words `TODO` and `FIXME`, and lists them in document order in a panel
+//! where `Enter` jumps to the node.
+
+use crate::document::node::{YamlNode, YamlValue};
+
+/// Words that mark a task. Matched as whole, upper-case words.
+pub const TASK_MARKERS: [&str; 2] = ["TODO", "FIXME"];
+
+/// Where a task was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskSource {
+    /// A YAML comment in the document
+    Comment,
+    /// A note attached with `:note`
+    Note,
+}
+
+/// A TODO or FIXME found in a comment or note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    /// The marker found (`TODO` or `FIXME`)
+    pub marker: &'static str,
+    /// The comment or note text, trimmed
+    pub text: String,
+    /// Path of the comment node, or of the node the note is attached to
+    pub path: Vec<usize>,
+    /// Whether the task came from a comment or a note
+    pub source: TaskSource,
+}
+
+/// Returns the first task marker in `text`, if any.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::tasks::find_marker;
+///
+/// assert_eq!(find_marker("FIXME: wrong port"), Some("FIXME"));
+/// assert_eq!(find_marker("see TODO.md"), Some("TODO"));
+/// assert_eq!(find_marker("TODOS are done"), None);
+/// assert_eq!(find_marker("todo"), None);
+/// ```
+pub fn find_marker(text: &str) -> Option<&'static str> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .find_map(|word| TASK_MARKERS.iter().find(|m| **m == word).copied())
+}
+
+/// Collects the tasks in the comments under `root`, in document order.
+pub fn scan_comments(root: &YamlNode) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    walk(root, &mut Vec::new(), &mut tasks);
+    tasks
+}
+
+fn walk(node: &YamlNode, path: &mut Vec<usize>, tasks: &mut Vec<Task>) {
+    let children: Box<dyn Iterator<Item = &YamlNode>> = match node.value() {
+        YamlValue::Comment(comment) => {
+            if let Some(marker) = find_marker(comment.content()) {
+                tasks.push(Task {
+                    marker,
+                    text: comment.content().trim().to_string(),
+                    path: path.clone(),
+                    source: TaskSource::Comment,
+                });
+            }
+            return;
+        }
+        YamlValue::Object(entries) => Box::new(entries.values()),
+        YamlValue::Array(items) | YamlValue::MultiDoc(items) => Box::new(items.iter()),
+        _ => return,
+    };
+    for (i, child) in children.enumerate() {
+        path.push(i);
+        walk(child, path, tasks);
+        path.pop();
+    }
+}
+
+/// Selection state for the `:tasks` panel.
+#[derive(Debug, Clone)]
+pub struct TaskListState {
+    /// Tasks in document order
+    pub tasks: Vec<Task>,
+    /// Index of the highlighted task
+    pub selected_index: usize,
+}
+
+impl TaskListState {
+    /// Creates a panel over `tasks` with the first one selected.
+    pub fn new(tasks: Vec<Task>) -> Self {
+        Self {
+            tasks,
+            selected_index: 0,
+        }
+    }
+
+    /// Moves the selection up, wrapping to the bottom.
+    pub fn previous(&mut self) {
+        if !self.tasks.is_empty() {
+            self.selected_index = (self.selected_index + self.tasks.len() - 1) % self.tasks.len();
+        }
+    }
+
+    /// Moves the selection down, wrapping to the top.
+    pub fn next(&mut self) {
+        if !self.tasks.is_empty() {
+            self.selected_index = (self.selected_index + 1) % self.tasks.len();
+        }
+    }
+
+    /// Returns the highlighted task.
+    pub fn selected(&self) -> Option<&Task> {
+        self.tasks.get(self.selected_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    #[test]
+    fn test_scan_comments() {
+        let root = parse_yaml_auto(
+            "# TODO: split this file\nname: web\nports:\n  # FIXME wrong port\n  - 80\n  # just a comment\n  - 443\n",
+        )
+        .unwrap();
+        let tasks = scan_comments(&root);
+        let found: Vec<_> = tasks.iter().map(|t| (t.marker, t.text.as_str())).collect();
+        assert_eq!(
+            found,
+            [
+                ("TODO", "TODO: split this file"),
+                ("FIXME", "FIXME wrong port")
+            ]
+        );
+        assert!(tasks.iter().all(|t| t.source == TaskSource::Comment));
+        assert!(tasks[0].path < tasks[1].path);
+    }
+
+    #[test]
+    fn test_panel_wraps() {
+        let task = |marker| Task {
+            marker,
+            text: String::new(),
+            path: vec![],
+            source: TaskSource::Note,
+        };
+        let mut panel = TaskListState::new(vec![task("TODO"), task("FIXME")]);
+        panel.previous();
+        assert_eq!(panel.selected().unwrap().marker, "FIXME");
+        panel.next();
+        assert_eq!(panel.selected().unwrap().marker, "TODO");
+    }
+}
@@ -7,12 +7,42 @@
 //! # Architecture
 //!
 //! - `EditorSnapshot`: Captures tree and cursor state at a point in time
-//! - `UndoNode`: Tree node containing snapshot, parent, children, and metadata
+//! - `UndoNode`: Tree node holding the changes to and from its parent state,
+//!   the cursor, and metadata
 //! - `UndoTree`: Manages the tree structure and navigation
+//!
+//! Only the current state is kept as a whole document. Every other state is
+//! reached by replaying [`Change`]s: when a checkpoint is added, the new
+//! document is diffed against the current one, and the node stores both the
+//! changes that lead to it (for redo) and the ones that lead back (for
+//! undo). An edit to one value in a large file costs the size of that value,
+//! not of the file.
+//!
+//! # Persistent undo
+//!
+//! With `persistent_undo` enabled, the whole undo tree is written to a hidden
+//! sidecar file next to the document (`.file.yaml.undo`) on every save and
+//! read back when the file is opened again, like vim's `undofile`. The
+//! sidecar records a hash of the file as saved; if the file was changed by
+//! something else in the meantime, the stale history is ignored. Sidecars
+//! are compressed and checksummed, and kept under `undo_file_limit_mb` by
+//! leaving out the oldest states.
 
+use crate::document::node::{YamlNode, YamlValue};
+use crate::document::ops::Op;
 use crate::document::tree::YamlTree;
+use crate::editor::memory::{format_size, node_size};
+use crate::file::store;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::time::SystemTime;
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Version of the undo file format.
+const UNDO_FILE_VERSION: u32 = 2;
 
 /// Snapshot of editor state at a specific point in time.
 ///
@@ -27,40 +57,343 @@ pub struct EditorSnapshot {
     pub expanded_paths: HashSet<Vec<usize>>,
 }
 
+/// One step from a state to a neighbouring one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Change {
+    /// A structural edit.
+    Op(Op),
+    /// Sets the metadata, anchor, alias target, tag and formatting of the
+    /// node at `path` to those of `node`, leaving its value alone.
+    ///
+    /// Inserting or deleting an entry marks its container as modified, so
+    /// a container whose entries changed always gets one of these after
+    /// its ops, to leave it exactly as it was in the target state.
+    Shell { path: Vec<usize>, node: YamlNode },
+}
+
+impl Change {
+    /// Applies the change to `tree`.
+    ///
+    /// The anchor registry is not updated.
+    pub fn apply(&self, tree: &mut YamlTree) -> Result<()> {
+        match self {
+            Change::Op(op) => op.apply(tree).map(drop),
+            Change::Shell { path, node } => {
+                let target = tree
+                    .get_node_mut(path)
+                    .with_context(|| format!("No node at {:?}", path))?;
+                target.metadata = node.metadata.clone();
+                target.anchor = node.anchor.clone();
+                target.alias_target = node.alias_target.clone();
+                target.tag = node.tag.clone();
+                target.original_formatting = node.original_formatting.clone();
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the estimated size of the change, in bytes.
+    fn size(&self) -> usize {
+        let (path, node, key) = match self {
+            Change::Op(Op::Insert { path, key, node }) => (path, Some(node), key.as_ref()),
+            Change::Op(Op::Replace { path, node }) | Change::Shell { path, node } => {
+                (path, Some(node), None)
+            }
+            Change::Op(Op::RenameKey { path, key }) => (path, None, Some(key)),
+            Change::Op(Op::Delete { path }) | Change::Op(Op::Move { from: path, .. }) => {
+                (path, None, None)
+            }
+        };
+        size_of::<Change>()
+            + path.capacity() * size_of::<usize>()
+            + node.map_or(0, node_size)
+            + key.map_or(0, String::capacity)
+    }
+}
+
+/// Returns the changes that turn `old` into `new`.
+///
+/// Containers are compared entry by entry, so an edit deep in a document
+/// yields ops on just the nodes that differ. A mapping whose remaining keys
+/// were reordered is replaced whole.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::document::tree::YamlTree;
+/// use yamlquill::editor::undo::diff;
+///
+/// let old = parse_yaml_auto("a: 1\nb: [x, y]\n").unwrap();
+/// let new = parse_yaml_auto("a: 1\nb: [x, z]\nc: true\n").unwrap();
+/// let changes = diff(&old, &new);
+///
+/// let mut tree = YamlTree::new(old);
+/// for change in &changes {
+///     change.apply(&mut tree).unwrap();
+/// }
+/// assert_eq!(tree.root(), &new);
+/// ```
+pub fn diff(old: &YamlNode, new: &YamlNode) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_node(old, new, &mut Vec::new(), &mut changes);
+    changes
+}
+
+fn diff_node(old: &YamlNode, new: &YamlNode, path: &mut Vec<usize>, out: &mut Vec<Change>) {
+    if identical(old, new) {
+        return;
+    }
+    let restructured = match (&old.value, &new.value) {
+        (YamlValue::Object(_), YamlValue::Object(_)) => diff_object(old, new, path, out),
+        (YamlValue::Array(a), YamlValue::Array(b))
+        | (YamlValue::MultiDoc(a), YamlValue::MultiDoc(b)) => diff_items(a, b, path, out),
+        _ => None,
+    };
+    match restructured {
+        None => out.push(Change::Op(Op::Replace {
+            path: path.clone(),
+            node: new.clone(),
+        })),
+        Some(restructured) => {
+            if restructured || !same_shell(old, new) {
+                out.push(Change::Shell {
+                    path: path.clone(),
+                    node: shell(new),
+                });
+            }
+        }
+    }
+}
+
+/// Diffs two mappings. Returns whether entries were added, removed or
+/// renamed, or `None` if the node has to be replaced instead.
+fn diff_object(
+    old: &YamlNode,
+    new: &YamlNode,
+    path: &mut Vec<usize>,
+    out: &mut Vec<Change>,
+) -> Option<bool> {
+    let (YamlValue::Object(a), YamlValue::Object(b)) = (&old.value, &new.value) else {
+        return None;
+    };
+    let mut restructured = false;
+    let mut renamed = None;
+
+    if !a.keys().eq(b.keys()) {
+        let differing: Vec<usize> = (0..a.len().min(b.len()))
+            .filter(|&i| a.get_index(i).map(|(k, _)| k) != b.get_index(i).map(|(k, _)| k))
+            .collect();
+        if a.len() == b.len() && differing.len() == 1 {
+            let index = differing[0];
+            let (key, _) = b.get_index(index)?;
+            if a.contains_key(key) {
+                return None;
+            }
+            renamed = Some(index);
+            out.push(Change::Op(Op::RenameKey {
+                path: child_path(path, index),
+                key: key.clone(),
+            }));
+        } else {
+            // The keys both sides share must be in the same order
+            if !a
+                .keys()
+                .filter(|k| b.contains_key(*k))
+                .eq(b.keys().filter(|k| a.contains_key(*k)))
+            {
+                return None;
+            }
+            for (index, (key, _)) in a.iter().enumerate().rev() {
+                if !b.contains_key(key) {
+                    out.push(Change::Op(Op::Delete {
+                        path: child_path(path, index),
+                    }));
+                }
+            }
+            for (index, (key, node)) in b.iter().enumerate() {
+                if !a.contains_key(key) {
+                    out.push(Change::Op(Op::Insert {
+                        path: child_path(path, index),
+                        key: Some(key.clone()),
+                        node: node.clone(),
+                    }));
+                }
+            }
+        }
+        restructured = true;
+    }
+
+    for (index, (key, node)) in b.iter().enumerate() {
+        let before = match renamed {
+            Some(i) if i == index => a.get_index(index).map(|(_, n)| n),
+            _ => a.get(key),
+        };
+        if let Some(before) = before {
+            path.push(index);
+            diff_node(before, node, path, out);
+            path.pop();
+        }
+    }
+    Some(restructured)
+}
+
+/// Diffs two sequences: items past the common prefix and suffix are diffed
+/// pairwise, and the rest are deleted or inserted. Returns whether the
+/// length changed.
+fn diff_items(
+    a: &[YamlNode],
+    b: &[YamlNode],
+    path: &mut Vec<usize>,
+    out: &mut Vec<Change>,
+) -> Option<bool> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| identical(x, y)).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| identical(x, y))
+        .count();
+    let (old_len, new_len) = (a.len() - prefix - suffix, b.len() - prefix - suffix);
+    let paired = old_len.min(new_len);
+
+    for i in prefix..prefix + paired {
+        path.push(i);
+        diff_node(&a[i], &b[i], path, out);
+        path.pop();
+    }
+    for i in (prefix + paired..prefix + old_len).rev() {
+        out.push(Change::Op(Op::Delete {
+            path: child_path(path, i),
+        }));
+    }
+    for (i, node) in b
+        .iter()
+        .enumerate()
+        .take(prefix + new_len)
+        .skip(prefix + paired)
+    {
+        out.push(Change::Op(Op::Insert {
+            path: child_path(path, i),
+            key: None,
+            node: node.clone(),
+        }));
+    }
+    Some(old_len != new_len)
+}
+
+fn child_path(path: &[usize], index: usize) -> Vec<usize> {
+    let mut child = path.to_vec();
+    child.push(index);
+    child
+}
+
+/// Returns `node` without its value, for a [`Change::Shell`].
+fn shell(node: &YamlNode) -> YamlNode {
+    let mut shell = YamlNode::new(YamlValue::Null);
+    shell.metadata = node.metadata.clone();
+    shell.anchor = node.anchor.clone();
+    shell.alias_target = node.alias_target.clone();
+    shell.tag = node.tag.clone();
+    shell.original_formatting = node.original_formatting.clone();
+    shell
+}
+
+/// Returns true if `a` and `b` are equal and their mappings have their keys
+/// in the same order, which node equality doesn't check.
+fn identical(a: &YamlNode, b: &YamlNode) -> bool {
+    if !same_shell(a, b) {
+        return false;
+    }
+    match (&a.value, &b.value) {
+        (YamlValue::Object(x), YamlValue::Object(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .zip(y)
+                    .all(|((k1, v1), (k2, v2))| k1 == k2 && identical(v1, v2))
+        }
+        (YamlValue::Array(x), YamlValue::Array(y))
+        | (YamlValue::MultiDoc(x), YamlValue::MultiDoc(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(v1, v2)| identical(v1, v2))
+        }
+        (x, y) => x == y,
+    }
+}
+
+fn same_shell(a: &YamlNode, b: &YamlNode) -> bool {
+    a.metadata == b.metadata
+        && a.anchor == b.anchor
+        && a.alias_target == b.alias_target
+        && a.tag == b.tag
+        && a.original_formatting == b.original_formatting
+}
+
+/// Applies `changes` to `tree` in order and rebuilds its anchor registry.
+fn replay(tree: &mut YamlTree, changes: &[Change]) -> Result<()> {
+    for change in changes {
+        change.apply(tree)?;
+    }
+    tree.build_anchor_registry();
+    Ok(())
+}
+
 /// A node in the undo tree.
 ///
 /// Each node represents a state in the edit history and tracks:
-/// - The snapshot of editor state
+/// - The changes from its parent's state to this one, and back
+/// - The cursor, expanded paths and original source of this state
 /// - Parent node (for undo navigation)
 /// - Child nodes (for redo navigation with branching)
 /// - Timestamp when this state was created
 /// - Sequence number for chronological ordering
+/// - Estimated size of its changes, for the memory budget
 #[derive(Debug, Clone)]
 pub struct UndoNode {
-    pub snapshot: EditorSnapshot,
     pub parent: Option<usize>,
     pub children: Vec<usize>,
     pub timestamp: SystemTime,
     pub seq: u64,
+    pub size: usize,
+    pub cursor_path: Vec<usize>,
+    /// Shared with the parent when unchanged
+    pub expanded_paths: Arc<HashSet<Vec<usize>>>,
+    /// Shared between all states with the same source
+    pub source: Option<Arc<str>>,
+    /// Turns the parent's tree into this one
+    pub forward: Vec<Change>,
+    /// Turns this tree back into the parent's
+    pub backward: Vec<Change>,
 }
 
 impl UndoNode {
-    /// Creates a new undo node.
+    /// Creates a new undo node with no changes.
     ///
     /// # Arguments
     ///
     /// * `snapshot` - The editor state at this point
     /// * `parent` - Index of parent node (None for root)
     /// * `seq` - Sequence number for chronological ordering
-    pub fn new(snapshot: EditorSnapshot, parent: Option<usize>, seq: u64) -> Self {
+    pub fn new(snapshot: &EditorSnapshot, parent: Option<usize>, seq: u64) -> Self {
         Self {
-            snapshot,
             parent,
             children: Vec::new(),
             timestamp: SystemTime::now(),
             seq,
+            size: 0,
+            cursor_path: snapshot.cursor_path.clone(),
+            expanded_paths: Arc::new(snapshot.expanded_paths.clone()),
+            source: snapshot.tree.shared_source(),
+            forward: Vec::new(),
+            backward: Vec::new(),
         }
     }
+
+    /// Sets the changes to and from the parent state.
+    fn set_changes(&mut self, forward: Vec<Change>, backward: Vec<Change>) {
+        self.size = forward.iter().chain(&backward).map(Change::size).sum();
+        self.forward = forward;
+        self.backward = backward;
+    }
 }
 
 /// Branching undo tree for managing edit history.
@@ -82,12 +415,15 @@ impl UndoNode {
 ///   |
 ///   4
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct UndoTree {
     pub nodes: Vec<UndoNode>,
     current: usize,
     next_seq: u64,
     limit: usize,
+    /// The document at the current node
+    tree: YamlTree,
+    tree_size: usize,
 }
 
 impl UndoTree {
@@ -98,12 +434,14 @@ impl UndoTree {
     /// * `initial_snapshot` - The starting state (root node)
     /// * `limit` - Maximum number of nodes to keep
     pub fn new(initial_snapshot: EditorSnapshot, limit: usize) -> Self {
-        let root = UndoNode::new(initial_snapshot, None, 0);
+        let root = UndoNode::new(&initial_snapshot, None, 0);
         Self {
             nodes: vec![root],
             current: 0,
             next_seq: 1,
             limit,
+            tree_size: node_size(initial_snapshot.tree.root()),
+            tree: initial_snapshot.tree,
         }
     }
 
@@ -127,6 +465,11 @@ impl UndoTree {
         self.limit
     }
 
+    /// Returns the document at the current node.
+    pub fn current_tree(&self) -> &YamlTree {
+        &self.tree
+    }
+
     /// Adds a new checkpoint to the undo tree.
     ///
     /// Creates a new node as a child of the current node. If the current node
@@ -139,7 +482,18 @@ impl UndoTree {
         let seq = self.next_seq;
         self.next_seq += 1;
 
-        let new_node = UndoNode::new(snapshot, Some(self.current), seq);
+        let forward = diff(self.tree.root(), snapshot.tree.root());
+        let backward = diff(snapshot.tree.root(), self.tree.root());
+        debug_assert!(
+            check_changes(&self.tree, &snapshot.tree, &forward, &backward),
+            "undo changes don't reproduce the checkpointed tree"
+        );
+
+        let mut new_node = UndoNode::new(&snapshot, Some(self.current), seq);
+        new_node.set_changes(forward, backward);
+        if *new_node.expanded_paths == *self.nodes[self.current].expanded_paths {
+            new_node.expanded_paths = Arc::clone(&self.nodes[self.current].expanded_paths);
+        }
         let new_index = self.nodes.len();
 
         // Add new node as child of current
@@ -148,45 +502,473 @@ impl UndoTree {
 
         // Move current pointer to new node
         self.current = new_index;
+        self.tree_size = node_size(snapshot.tree.root());
+        self.tree = snapshot.tree;
 
         // TODO: Implement pruning when limit exceeded
     }
 
+    /// Returns the estimated size of the history, in bytes: every state's
+    /// changes, plus the copy of the current document.
+    pub fn memory(&self) -> usize {
+        self.tree_size + self.nodes.iter().map(|node| node.size).sum::<usize>()
+    }
+
+    /// Returns the estimated size of the current document, in bytes.
+    pub fn current_size(&self) -> usize {
+        self.tree_size
+    }
+
+    /// Drops the oldest state: the root, along with every branch that
+    /// doesn't lead to the current state.
+    ///
+    /// Returns false if the current state is the root, so nothing is left
+    /// to drop.
+    pub fn drop_oldest(&mut self) -> bool {
+        // The root's child on the way to the current node becomes the root
+        let mut new_root = self.current;
+        while let Some(parent) = self.nodes[new_root].parent {
+            if parent == 0 {
+                break;
+            }
+            new_root = parent;
+        }
+        if new_root == 0 {
+            return false;
+        }
+
+        let keep = self.subtree(new_root);
+        self.keep_only(keep);
+        true
+    }
+
+    /// Drops the state at `index` and every state after it on its branch.
+    fn drop_branch(&mut self, index: usize) {
+        let dropped = self.subtree(index);
+        let keep = (0..self.nodes.len())
+            .filter(|i| !dropped.contains(i))
+            .collect();
+        self.keep_only(keep);
+    }
+
+    /// Returns the state at `index` and every state after it.
+    fn subtree(&self, index: usize) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = vec![index];
+        while let Some(index) = stack.pop() {
+            found.push(index);
+            stack.extend(self.nodes[index].children.iter().copied());
+        }
+        found
+    }
+
+    /// Keeps only the states at `keep`, which must include the current one.
+    /// A state whose parent is dropped becomes a root.
+    fn keep_only(&mut self, mut keep: Vec<usize>) {
+        keep.sort_unstable();
+        let mut remap = vec![usize::MAX; self.nodes.len()];
+        for (new, &old) in keep.iter().enumerate() {
+            remap[old] = new;
+        }
+
+        let mut nodes: Vec<Option<UndoNode>> = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.nodes = keep
+            .iter()
+            .map(|&old| {
+                let mut node = nodes[old].take().expect("each node is kept once");
+                node.parent = node.parent.map(|p| remap[p]).filter(|&p| p != usize::MAX);
+                node.children = node
+                    .children
+                    .iter()
+                    .map(|&c| remap[c])
+                    .filter(|&c| c != usize::MAX)
+                    .collect();
+                if node.parent.is_none() {
+                    node.set_changes(Vec::new(), Vec::new());
+                }
+                node
+            })
+            .collect();
+        self.current = remap[self.current];
+    }
+
     /// Undoes to the parent node.
     ///
-    /// Returns the snapshot to restore, or None if already at root.
-    pub fn undo(&mut self) -> Option<EditorSnapshot> {
+    /// Returns the snapshot to restore, or None if already at root. If the
+    /// changes back to the parent don't apply (a corrupt undo file), the
+    /// states before the current one are dropped and an error is returned.
+    pub fn undo(&mut self) -> Result<Option<EditorSnapshot>> {
         let current_node = &self.nodes[self.current];
-
-        if let Some(parent_idx) = current_node.parent {
-            self.current = parent_idx;
-            Some(self.nodes[parent_idx].snapshot.clone())
-        } else {
-            None
+        let Some(parent_idx) = current_node.parent else {
+            return Ok(None);
+        };
+        let mut tree = self.tree.clone();
+        if let Err(e) = replay(&mut tree, &current_node.backward) {
+            while self.drop_oldest() {}
+            return Err(e.context("Undo history doesn't apply; dropped the older states"));
         }
+        self.tree = tree;
+        self.current = parent_idx;
+        Ok(Some(self.current_snapshot()))
     }
 
     /// Redoes to a child node.
     ///
     /// Follows the newest branch (child with highest sequence number).
-    /// Returns the snapshot to restore, or None if no children exist.
-    pub fn redo(&mut self) -> Option<EditorSnapshot> {
+    /// Returns the snapshot to restore, or None if no children exist. If the
+    /// changes to the child don't apply, its branch is dropped and an error
+    /// is returned.
+    pub fn redo(&mut self) -> Result<Option<EditorSnapshot>> {
         let current_node = &self.nodes[self.current];
 
-        if current_node.children.is_empty() {
-            return None;
-        }
-
         // Find child with highest sequence number (newest branch)
-        let newest_child_idx = current_node
+        let Some(newest_child_idx) = current_node
             .children
             .iter()
             .max_by_key(|&&child_idx| self.nodes[child_idx].seq)
             .copied()
-            .unwrap(); // Safe because we checked is_empty
+        else {
+            return Ok(None);
+        };
 
+        let mut tree = self.tree.clone();
+        if let Err(e) = replay(&mut tree, &self.nodes[newest_child_idx].forward) {
+            self.drop_branch(newest_child_idx);
+            return Err(e.context("Redo history doesn't apply; dropped that branch"));
+        }
+        self.tree = tree;
         self.current = newest_child_idx;
-        Some(self.nodes[newest_child_idx].snapshot.clone())
+        Ok(Some(self.current_snapshot()))
+    }
+
+    /// Returns the current state, after the tree has been moved to it.
+    fn current_snapshot(&mut self) -> EditorSnapshot {
+        let node = &self.nodes[self.current];
+        self.tree.set_shared_source(node.source.clone());
+        self.tree_size = node_size(self.tree.root());
+        EditorSnapshot {
+            tree: self.tree.clone(),
+            cursor_path: node.cursor_path.clone(),
+            expanded_paths: (*node.expanded_paths).clone(),
+        }
+    }
+
+    /// Returns the tree of a neighbour of the current node.
+    fn neighbour_tree(&self, index: usize) -> Result<YamlTree> {
+        let node = &self.nodes[index];
+        let changes = if node.parent == Some(self.current) {
+            &node.forward
+        } else {
+            &self.nodes[self.current].backward
+        };
+        let mut tree = self.tree.clone();
+        replay(&mut tree, changes)?;
+        Ok(tree)
+    }
+}
+
+/// Checks that stored nodes form a tree rooted at the first node: every
+/// parent and child index is in range, each node is listed once among its
+/// parent's children and nowhere else, and every node leads back to the root.
+fn check_links(nodes: &[StoredNode]) -> Result<()> {
+    match nodes.first() {
+        Some(root) if root.parent.is_none() => {}
+        _ => bail!("history has no root state"),
+    }
+    let mut listed = vec![0usize; nodes.len()];
+    for (index, node) in nodes.iter().enumerate() {
+        for &child in &node.children {
+            if child >= nodes.len() {
+                bail!("state {} has a child {} out of range", index, child);
+            }
+            if nodes[child].parent != Some(index) {
+                bail!(
+                    "state {} lists {} as a child of another state",
+                    index,
+                    child
+                );
+            }
+            listed[child] += 1;
+        }
+    }
+    for (index, node) in nodes.iter().enumerate().skip(1) {
+        match node.parent {
+            Some(parent) if parent < nodes.len() && listed[index] == 1 => {}
+            _ => bail!("state {} is not linked to a parent", index),
+        }
+        // Each step goes to a parent, so a path of more steps than there
+        // are states has gone round a cycle
+        let mut at = index;
+        for _ in 0..nodes.len() {
+            match nodes[at].parent {
+                Some(parent) => at = parent,
+                None => break,
+            }
+        }
+        if at != 0 {
+            bail!("state {} doesn't lead back to the first state", index);
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if `forward` turns `old` into `new` and `backward` turns it
+/// back.
+fn check_changes(old: &YamlTree, new: &YamlTree, forward: &[Change], backward: &[Change]) -> bool {
+    let mut there = old.clone();
+    let mut back = new.clone();
+    replay(&mut there, forward).is_ok()
+        && identical(there.root(), new.root())
+        && replay(&mut back, backward).is_ok()
+        && identical(back.root(), old.root())
+}
+
+/// Returns the sidecar path used to persist undo history for `path`.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::undo::undo_file_path;
+/// use std::path::{Path, PathBuf};
+///
+/// assert_eq!(
+///     undo_file_path(Path::new("conf/app.yaml")),
+///     PathBuf::from("conf/.app.yaml.undo")
+/// );
+/// ```
+pub fn undo_file_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.undo", name))
+}
+
+/// Returns a hex digest (64-bit FNV-1a) of the document as saved, recorded
+/// in its undo file to tell whether the history still applies.
+///
+/// It only has to notice edits made by other programs; undo files are read
+/// from trusted directories alone, so forged collisions are out of scope.
+fn file_hash(contents: &[u8]) -> String {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let hash = contents.iter().fold(OFFSET, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    });
+    format!("{:016x}", hash)
+}
+
+/// On-disk form of an undo tree.
+#[derive(Serialize, Deserialize)]
+struct UndoFile {
+    version: u32,
+    /// Hash of the document file when the history was written
+    file_hash: String,
+    current: usize,
+    next_seq: u64,
+    /// Distinct original sources, shared between states
+    sources: Vec<String>,
+    nodes: Vec<StoredNode>,
+    /// The document at the current node
+    root: YamlNode,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredNode {
+    parent: Option<usize>,
+    children: Vec<usize>,
+    seq: u64,
+    /// Seconds since the Unix epoch
+    timestamp: u64,
+    cursor_path: Vec<usize>,
+    expanded_paths: Vec<Vec<usize>>,
+    /// Index into `UndoFile::sources`
+    source: Option<usize>,
+    forward: Vec<Change>,
+    backward: Vec<Change>,
+}
+
+impl UndoTree {
+    /// Writes the undo tree to `path`, compressed (see [`crate::file::store`]).
+    ///
+    /// `file_contents` is the document as just saved; it is hashed so that
+    /// [`UndoTree::load_from`] can tell whether the history still applies.
+    /// If the history would be over `limit` bytes before compression (0 for
+    /// no limit), the oldest states are left out until it fits. Returns how
+    /// many were left out.
+    pub fn save_to(&self, path: &Path, file_contents: &[u8], limit: usize) -> Result<usize> {
+        let mut trimmed: Option<UndoTree> = None;
+        loop {
+            let history = trimmed.as_ref().unwrap_or(self);
+            let toml = toml::to_string(&history.to_file(file_contents))
+                .context("Failed to serialize undo history")?;
+            if limit == 0 || toml.len() <= limit {
+                std::fs::write(path, store::encode(toml.as_bytes())?)
+                    .with_context(|| format!("Failed to write undo file: {}", path.display()))?;
+                return Ok(self.len() - history.len());
+            }
+
+            // Drop a share of the states in proportion to the overshoot
+            let history = trimmed.get_or_insert_with(|| self.clone());
+            let count = (history.len() * (toml.len() - limit) / toml.len()).max(1);
+            if !(0..count).fold(false, |dropped, _| history.drop_oldest() || dropped) {
+                bail!(
+                    "Undo history is over the {} limit even without past states",
+                    format_size(limit)
+                );
+            }
+        }
+    }
+
+    /// Returns the on-disk form of the tree.
+    fn to_file(&self, file_contents: &[u8]) -> UndoFile {
+        let mut sources: Vec<String> = Vec::new();
+        let nodes =
+            self.nodes
+                .iter()
+                .map(|node| {
+                    let source = node.source.as_deref().map(|src| {
+                        match sources.iter().position(|s| s == src) {
+                            Some(i) => i,
+                            None => {
+                                sources.push(src.to_string());
+                                sources.len() - 1
+                            }
+                        }
+                    });
+                    let mut expanded_paths: Vec<Vec<usize>> =
+                        node.expanded_paths.iter().cloned().collect();
+                    expanded_paths.sort();
+                    StoredNode {
+                        parent: node.parent,
+                        children: node.children.clone(),
+                        seq: node.seq,
+                        timestamp: node
+                            .timestamp
+                            .duration_since(UNIX_EPOCH)
+                            .map(|d| d.as_secs())
+                            .unwrap_or(0),
+                        cursor_path: node.cursor_path.clone(),
+                        expanded_paths,
+                        source,
+                        forward: node.forward.clone(),
+                        backward: node.backward.clone(),
+                    }
+                })
+                .collect();
+
+        UndoFile {
+            version: UNDO_FILE_VERSION,
+            file_hash: file_hash(file_contents),
+            current: self.current,
+            next_seq: self.next_seq,
+            sources,
+            nodes,
+            root: self.tree.root().clone(),
+        }
+    }
+
+    /// Reads an undo tree written by [`UndoTree::save_to`].
+    ///
+    /// Returns `Ok(None)` if there is no undo file, if it was written for
+    /// different file contents than `file_contents`, or by a version that
+    /// stored history differently. History over `size_limit` bytes (0 for no
+    /// limit) is refused without being decompressed.
+    pub fn load_from(
+        path: &Path,
+        file_contents: &[u8],
+        limit: usize,
+        size_limit: usize,
+    ) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let stored = std::fs::read(path)
+            .with_context(|| format!("Failed to read undo file: {}", path.display()))?;
+        let contents = store::decode(&stored, size_limit)
+            .and_then(|data| Ok(String::from_utf8(data)?))
+            .with_context(|| format!("Corrupt undo file: {}", path.display()))?;
+        // Check the version first: older files don't parse as this one
+        let version = toml::from_str::<toml::Table>(&contents)
+            .ok()
+            .and_then(|table| table.get("version")?.as_integer());
+        if version != Some(UNDO_FILE_VERSION as i64) {
+            return Ok(None);
+        }
+        let file: UndoFile = toml::from_str(&contents)
+            .with_context(|| format!("Corrupt undo file: {}", path.display()))?;
+
+        if file.file_hash != file_hash(file_contents) || file.current >= file.nodes.len() {
+            return Ok(None);
+        }
+        check_links(&file.nodes)
+            .with_context(|| format!("Corrupt undo file: {}", path.display()))?;
+
+        let sources: Vec<Arc<str>> = file.sources.into_iter().map(Arc::from).collect();
+        let nodes: Vec<UndoNode> = file
+            .nodes
+            .into_iter()
+            .map(|stored| {
+                let mut node = UndoNode {
+                    parent: stored.parent,
+                    children: stored.children,
+                    timestamp: UNIX_EPOCH + Duration::from_secs(stored.timestamp),
+                    seq: stored.seq,
+                    size: 0,
+                    cursor_path: stored.cursor_path,
+                    expanded_paths: Arc::new(stored.expanded_paths.into_iter().collect()),
+                    source: stored.source.and_then(|i| sources.get(i).cloned()),
+                    forward: Vec::new(),
+                    backward: Vec::new(),
+                };
+                node.set_changes(stored.forward, stored.backward);
+                node
+            })
+            .collect();
+
+        let mut tree = YamlTree::new(file.root);
+        tree.set_shared_source(nodes[file.current].source.clone());
+        Ok(Some(Self {
+            nodes,
+            current: file.current,
+            next_seq: file.next_seq,
+            limit,
+            tree_size: node_size(tree.root()),
+            tree,
+        }))
+    }
+
+    /// Replaces the tree of the current state.
+    ///
+    /// Used after loading persisted history so that the current state is the
+    /// document as parsed from disk, with its original source intact. The
+    /// changes to and from the neighbouring states are taken again against
+    /// the new tree.
+    pub fn set_current_tree(&mut self, tree: YamlTree) {
+        let current = self.current;
+        let mut neighbours: Vec<usize> = self.nodes[current].children.clone();
+        neighbours.extend(self.nodes[current].parent);
+        for index in neighbours {
+            let Ok(other) = self.neighbour_tree(index) else {
+                continue;
+            };
+            let (to_other, from_other) = (
+                diff(tree.root(), other.root()),
+                diff(other.root(), tree.root()),
+            );
+            if self.nodes[index].parent == Some(current) {
+                self.nodes[index].set_changes(to_other, from_other);
+            } else {
+                let node = &mut self.nodes[current];
+                node.set_changes(from_other, to_other);
+            }
+        }
+        self.nodes[current].source = tree.shared_source();
+        self.tree_size = node_size(tree.root());
+        self.tree = tree;
     }
 }
 
@@ -204,7 +986,7 @@ mod tests {
             expanded_paths: HashSet::new(),
         };
 
-        let node = UndoNode::new(snapshot, None, 0);
+        let node = UndoNode::new(&snapshot, None, 0);
 
         assert_eq!(node.seq, 0);
         assert_eq!(node.parent, None);
@@ -276,7 +1058,7 @@ mod tests {
         undo_tree.add_checkpoint(snapshot2);
 
         // Now at node 1, undo to node 0
-        let result = undo_tree.undo();
+        let result = undo_tree.undo().unwrap();
         assert!(result.is_some());
         assert_eq!(undo_tree.current(), 0);
 
@@ -296,7 +1078,7 @@ mod tests {
         let mut undo_tree = UndoTree::new(snapshot, 50);
 
         // Already at root, cannot undo
-        let result = undo_tree.undo();
+        let result = undo_tree.undo().unwrap();
         assert!(result.is_none());
         assert_eq!(undo_tree.current(), 0);
     }
@@ -320,10 +1102,10 @@ mod tests {
             expanded_paths: HashSet::new(),
         };
         undo_tree.add_checkpoint(snapshot2);
-        undo_tree.undo();
+        undo_tree.undo().unwrap();
 
         // Now redo back to node 1
-        let result = undo_tree.redo();
+        let result = undo_tree.redo().unwrap();
         assert!(result.is_some());
         assert_eq!(undo_tree.current(), 1);
 
@@ -343,7 +1125,7 @@ mod tests {
         let mut undo_tree = UndoTree::new(snapshot, 50);
 
         // No children, cannot redo
-        let result = undo_tree.redo();
+        let result = undo_tree.redo().unwrap();
         assert!(result.is_none());
     }
 
@@ -368,7 +1150,7 @@ mod tests {
         undo_tree.add_checkpoint(snapshot2);
 
         // Undo and create second branch (newer)
-        undo_tree.undo();
+        undo_tree.undo().unwrap();
         let tree3 = YamlTree::new(YamlNode::new(YamlValue::Boolean(false)));
         let snapshot3 = EditorSnapshot {
             tree: tree3,
@@ -378,14 +1160,302 @@ mod tests {
         undo_tree.add_checkpoint(snapshot3);
 
         // Undo again
-        undo_tree.undo();
+        undo_tree.undo().unwrap();
 
         // Redo should go to newest branch (node 2, not node 1)
-        let result = undo_tree.redo();
+        let result = undo_tree.redo().unwrap();
         assert!(result.is_some());
         assert_eq!(undo_tree.current(), 2);
 
         let snapshot = result.unwrap();
         assert_eq!(snapshot.cursor_path, vec![1]);
     }
+
+    #[test]
+    fn test_drop_oldest_keeps_path_to_current() {
+        let mut undo_tree = UndoTree::new(snapshot(YamlValue::Null, None), 50);
+        undo_tree.add_checkpoint(snapshot(YamlValue::Boolean(true), None));
+        undo_tree.undo().unwrap();
+        undo_tree.add_checkpoint(snapshot(YamlValue::Boolean(false), None));
+        undo_tree.add_checkpoint(snapshot(YamlValue::Alias("x".to_string()), None));
+        assert_eq!(undo_tree.len(), 4);
+        let size = undo_tree.memory();
+
+        // The root and the branch off it go; the other branch is re-rooted
+        assert!(undo_tree.drop_oldest());
+        assert_eq!(undo_tree.len(), 2);
+        assert_eq!(undo_tree.current(), 1);
+        assert!(undo_tree.memory() < size);
+        let restored = undo_tree.undo().unwrap().unwrap();
+        assert_eq!(restored.tree.root().value(), &YamlValue::Boolean(false));
+        assert!(undo_tree.undo().unwrap().is_none());
+        undo_tree.redo().unwrap();
+
+        assert!(undo_tree.drop_oldest());
+        assert_eq!(undo_tree.len(), 1);
+        assert_eq!(undo_tree.current(), 0);
+        assert!(!undo_tree.drop_oldest());
+    }
+
+    fn snapshot(value: YamlValue, source: Option<&str>) -> EditorSnapshot {
+        EditorSnapshot {
+            tree: YamlTree::with_source(YamlNode::new(value), source.map(str::to_string)),
+            cursor_path: vec![0],
+            expanded_paths: HashSet::from([vec![0], vec![0, 1]]),
+        }
+    }
+
+    #[test]
+    fn test_history_that_doesnt_apply_is_dropped() {
+        let broken = || vec![Change::Op(Op::Delete { path: vec![9] })];
+        let mut undo_tree = UndoTree::new(snapshot(YamlValue::Null, None), 50);
+        undo_tree.add_checkpoint(snapshot(YamlValue::Boolean(true), None));
+        undo_tree.add_checkpoint(snapshot(YamlValue::Boolean(false), None));
+
+        undo_tree.nodes[2].set_changes(Vec::new(), broken());
+        assert!(undo_tree.undo().is_err());
+        assert_eq!(undo_tree.len(), 1);
+        assert_eq!(
+            undo_tree.current_tree().root().value(),
+            &YamlValue::Boolean(false)
+        );
+        assert!(undo_tree.undo().unwrap().is_none());
+
+        undo_tree.add_checkpoint(snapshot(YamlValue::Boolean(true), None));
+        undo_tree.undo().unwrap();
+        undo_tree.nodes[1].set_changes(broken(), Vec::new());
+        assert!(undo_tree.redo().is_err());
+        assert_eq!(undo_tree.len(), 1);
+        assert!(undo_tree.redo().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_load_rejects_broken_links() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".a.yaml.undo");
+        let mut undo_tree = UndoTree::new(snapshot(YamlValue::Null, None), 50);
+        undo_tree.add_checkpoint(snapshot(YamlValue::Boolean(true), None));
+        undo_tree.add_checkpoint(snapshot(YamlValue::Boolean(false), None));
+
+        let breakages: [fn(&mut UndoFile); 3] = [
+            |file| file.nodes[1].children = vec![7],
+            |file| file.nodes[2].parent = Some(0),
+            |file| {
+                file.nodes[1].parent = Some(2);
+                file.nodes[0].children.clear();
+                file.nodes[2].children = vec![1];
+            },
+        ];
+        for breakage in breakages {
+            let mut file = undo_tree.to_file(b"false\n");
+            breakage(&mut file);
+            let toml = toml::to_string(&file).unwrap();
+            std::fs::write(&path, store::encode(toml.as_bytes()).unwrap()).unwrap();
+            let error = UndoTree::load_from(&path, b"false\n", 50, 0).unwrap_err();
+            assert!(format!("{:#}", error).starts_with("Corrupt undo file"));
+        }
+    }
+
+    #[test]
+    fn test_persist_roundtrip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".a.yaml.undo");
+
+        let mut undo_tree = UndoTree::new(snapshot(YamlValue::Null, Some("null\n")), 50);
+        undo_tree.add_checkpoint(snapshot(YamlValue::Boolean(true), Some("null\n")));
+        undo_tree.undo().unwrap();
+        undo_tree.add_checkpoint(snapshot(YamlValue::Boolean(false), None));
+        undo_tree.save_to(&path, b"false\n", 0).unwrap();
+
+        let mut loaded = UndoTree::load_from(&path, b"false\n", 50, 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded.current(), 2);
+        assert_eq!(loaded.nodes[2].source, None);
+
+        let previous = loaded.undo().unwrap().unwrap();
+        assert_eq!(previous.tree.root().value(), &YamlValue::Null);
+        assert_eq!(previous.tree.original_source(), Some("null\n"));
+        assert!(previous.expanded_paths.contains(&vec![0, 1]));
+        assert_eq!(
+            loaded.redo().unwrap().unwrap().tree.root().value(),
+            &YamlValue::Boolean(false)
+        );
+    }
+
+    #[test]
+    fn test_persisted_history_ignored_when_file_changed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".a.yaml.undo");
+
+        let undo_tree = UndoTree::new(snapshot(YamlValue::Null, None), 50);
+        undo_tree.save_to(&path, b"a: 1\n", 0).unwrap();
+
+        assert!(UndoTree::load_from(&path, b"a: 2\n", 50, 0)
+            .unwrap()
+            .is_none());
+        assert!(UndoTree::load_from(&dir.path().join("missing"), b"", 50, 0)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_persist_preserves_document_details() {
+        let source =
+            "# header\nbase: &base\n  x: 1.5\nuse: *base\nscript: |\n  echo hi\nempty: null\n";
+        let root = crate::document::parser::parse_yaml_auto(source).unwrap();
+        let tree = YamlTree::with_source(root, Some(source.to_string()));
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".a.yaml.undo");
+
+        let undo_tree = UndoTree::new(
+            EditorSnapshot {
+                tree: tree.clone(),
+                cursor_path: vec![],
+                expanded_paths: HashSet::new(),
+            },
+            50,
+        );
+        undo_tree.save_to(&path, source.as_bytes(), 0).unwrap();
+
+        let loaded = UndoTree::load_from(&path, source.as_bytes(), 50, 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.current_tree().root(), tree.root());
+    }
+
+    fn parsed(yaml: &str) -> EditorSnapshot {
+        EditorSnapshot {
+            tree: YamlTree::new(crate::document::parser::parse_yaml_auto(yaml).unwrap()),
+            cursor_path: vec![],
+            expanded_paths: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_covers_each_kind_of_edit() {
+        let steps = [
+            "a: 1\nb: [x, y, z]\nc: {d: 1}\n",
+            // Edit a value deep inside
+            "a: 1\nb: [x, y, z]\nc: {d: 2}\n",
+            // Rename a key
+            "a: 1\nb: [x, y, z]\ne: {d: 2}\n",
+            // Insert and delete entries and items
+            "b: [x, z]\ne: {d: 2}\nf: new\n",
+            "b: [w, x, z, v]\ne: {d: 2}\nf: new\n",
+            // Reorder keys, change kinds
+            "f: new\ne: {d: 2}\nb: [w, x, z, v]\n",
+            "f: [1]\ne: null\nb: {}\n",
+        ];
+        let mut undo_tree = UndoTree::new(parsed(steps[0]), 50);
+        let mut trees = vec![undo_tree.current_tree().clone()];
+        for step in &steps[1..] {
+            let snapshot = parsed(step);
+            trees.push(snapshot.tree.clone());
+            undo_tree.add_checkpoint(snapshot);
+        }
+
+        for tree in trees.iter().rev().skip(1) {
+            assert!(identical(
+                undo_tree.undo().unwrap().unwrap().tree.root(),
+                tree.root()
+            ));
+        }
+        for tree in &trees[1..] {
+            assert!(identical(
+                undo_tree.redo().unwrap().unwrap().tree.root(),
+                tree.root()
+            ));
+        }
+    }
+
+    #[test]
+    fn test_small_edit_stores_small_changes() {
+        let yaml: String = (0..500)
+            .map(|i| format!("key{}: {}\n", i, "v".repeat(100)))
+            .collect();
+        let mut undo_tree = UndoTree::new(parsed(&yaml), 50);
+        let document = undo_tree.memory();
+
+        undo_tree.add_checkpoint(parsed(&yaml.replacen("key0: v", "key0: w", 1)));
+        assert!(undo_tree.memory() - document < document / 50);
+        assert_eq!(undo_tree.nodes[1].forward.len(), 1);
+
+        let restored = undo_tree.undo().unwrap().unwrap();
+        assert_eq!(restored.tree.root(), parsed(&yaml).tree.root());
+    }
+
+    #[test]
+    fn test_reloaded_history_follows_the_tree_from_disk() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".a.yaml.undo");
+
+        let mut undo_tree = UndoTree::new(parsed("a: 1\n"), 50);
+        undo_tree.add_checkpoint(parsed("a: 1\nb: 2\n"));
+        undo_tree.save_to(&path, b"a: 1\nb: 2\n", 0).unwrap();
+
+        // As parsed from disk, the nodes carry their source positions
+        let source = "a: 1\nb: 2\n";
+        let root = crate::document::parser::parse_yaml_auto(source).unwrap();
+        let tree = YamlTree::with_source(root, Some(source.to_string()));
+        let mut loaded = UndoTree::load_from(&path, source.as_bytes(), 50, 0)
+            .unwrap()
+            .unwrap();
+        loaded.set_current_tree(tree.clone());
+
+        let previous = loaded.undo().unwrap().unwrap();
+        assert_eq!(previous.tree.root(), parsed("a: 1\n").tree.root());
+        assert_eq!(loaded.redo().unwrap().unwrap().tree.root(), tree.root());
+    }
+
+    #[test]
+    fn test_saved_history_is_compressed_and_capped() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".a.yaml.undo");
+        let steps: Vec<String> = (0..20)
+            .map(|n| {
+                (0..200)
+                    .map(|i| format!("key{}: value {} {}\n", i, i, n))
+                    .collect()
+            })
+            .collect();
+        let mut undo_tree = UndoTree::new(parsed(&steps[0]), 50);
+        for step in &steps[1..] {
+            undo_tree.add_checkpoint(parsed(step));
+        }
+        let saved = steps.last().unwrap().as_bytes();
+
+        assert_eq!(undo_tree.save_to(&path, saved, 0).unwrap(), 0);
+        let full = std::fs::metadata(&path).unwrap().len() as usize;
+        let toml = toml::to_string(&undo_tree.to_file(saved)).unwrap();
+        assert!(full < toml.len() / 4);
+
+        // History too big to load is refused before it is decompressed
+        let error = UndoTree::load_from(&path, saved, 50, toml.len() / 2).unwrap_err();
+        assert!(format!("{:#}", error).contains("over the"));
+
+        // Over the limit, the oldest states are left out
+        let limit = toml.len() / 2;
+        let dropped = undo_tree.save_to(&path, saved, limit).unwrap();
+        assert!(dropped > 0);
+        assert!((std::fs::metadata(&path).unwrap().len() as usize) < full);
+        let loaded = UndoTree::load_from(&path, saved, 50, limit)
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.len(), undo_tree.len() - dropped);
+        assert!(identical(
+            loaded.current_tree().root(),
+            undo_tree.current_tree().root()
+        ));
+
+        assert!(undo_tree.save_to(&path, saved, 16).is_err());
+
+        // A damaged file is reported, not loaded
+        let mut stored = std::fs::read(&path).unwrap();
+        let middle = stored.len() / 2;
+        stored[middle] ^= 0xff;
+        std::fs::write(&path, stored).unwrap();
+        assert!(UndoTree::load_from(&path, saved, 50, 0).is_err());
+    }
 }
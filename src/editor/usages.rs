@@ -0,0 +1,390 @@
+//! Finding the other places a scalar value is used (`:usages` on a value).
+//!
+//! Strings, numbers and booleans match when their text is the same, so the
+//! port `8080` also finds the string `"8080"` in an environment variable.
+
+use crate::document::node::{YamlNode, YamlValue};
+use crate::document::tree::YamlTree;
+use crate::ui::tree_view::format_collapsed_preview;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One occurrence of the searched value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Usage {
+    /// File the value is in, or `None` for the buffer being edited
+    pub file: Option<PathBuf>,
+    /// Path of the node in its document
+    pub path: Vec<usize>,
+    /// YAMLPath of the node (`$["spec"]["ports"][0]`), for opening other files
+    pub yamlpath: String,
+    /// Where the value is, prefixed by the file for other files
+    /// (`.spec.ports[0]`, `charts/api.yaml:.port`)
+    pub location: String,
+    /// Preview of the node's value, showing how it's written
+    pub preview: String,
+}
+
+/// Returns the text a scalar is matched by, or `None` for containers,
+/// nulls and empty strings.
+pub fn scalar_text(value: &YamlValue) -> Option<String> {
+    let text = match value {
+        YamlValue::String(s) => s.as_str().to_string(),
+        YamlValue::Number(n) => n.to_string(),
+        YamlValue::Boolean(b) => b.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// Finds the scalars under `root` whose text is `text`, in document order.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::editor::usages::find;
+///
+/// let root = parse_yaml_auto("port: 8080\nenv:\n  PORT: '8080'\n").unwrap();
+/// let found = find(&root, "8080");
+/// assert_eq!(found.len(), 2);
+/// assert_eq!(found[1].location, ".env.PORT");
+/// assert_eq!(found[1].yamlpath, "$[\"env\"][\"PORT\"]");
+/// ```
+pub fn find(root: &YamlNode, text: &str) -> Vec<Usage> {
+    find_matching(root, &|node| {
+        scalar_text(node.value()).as_deref() == Some(text)
+    })
+}
+
+/// Returns whether two scalars are the same value: the same type as well as
+/// the same text, so `8080` and `'8080'` differ.
+pub fn same_scalar(a: &YamlValue, b: &YamlValue) -> bool {
+    match (a, b) {
+        (YamlValue::String(a), YamlValue::String(b)) => a.as_str() == b.as_str(),
+        (YamlValue::Number(a), YamlValue::Number(b)) => a == b,
+        (YamlValue::Boolean(a), YamlValue::Boolean(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Finds the scalars under `root` that `matches` accepts, in document order.
+fn find_matching(root: &YamlNode, matches: &dyn Fn(&YamlNode) -> bool) -> Vec<Usage> {
+    let mut found = Vec::new();
+    let mut path = Vec::new();
+    let mut segments = Vec::new();
+    walk(root, matches, &mut path, &mut segments, &mut found);
+    found
+}
+
+/// A step on the way to a node: a mapping key or a list index.
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+fn walk<'a>(
+    node: &'a YamlNode,
+    matches: &dyn Fn(&YamlNode) -> bool,
+    path: &mut Vec<usize>,
+    segments: &mut Vec<Segment<'a>>,
+    found: &mut Vec<Usage>,
+) {
+    match node.value() {
+        YamlValue::Object(entries) => {
+            for (index, (key, child)) in entries.iter().enumerate() {
+                path.push(index);
+                segments.push(Segment::Key(key));
+                walk(child, matches, path, segments, found);
+                segments.pop();
+                path.pop();
+            }
+        }
+        YamlValue::Array(items) | YamlValue::MultiDoc(items) => {
+            for (index, item) in items.iter().enumerate() {
+                path.push(index);
+                segments.push(Segment::Index(index));
+                walk(item, matches, path, segments, found);
+                segments.pop();
+                path.pop();
+            }
+        }
+        _ => {
+            if matches(node) {
+                found.push(Usage {
+                    file: None,
+                    path: path.clone(),
+                    yamlpath: yamlpath(segments),
+                    location: location(segments),
+                    preview: format_collapsed_preview(node, 40),
+                });
+            }
+        }
+    }
+}
+
+fn location(segments: &[Segment]) -> String {
+    if segments.is_empty() {
+        return ".".to_string();
+    }
+    let mut result = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Key(key) => {
+                result.push('.');
+                result.push_str(key);
+            }
+            Segment::Index(index) => result.push_str(&format!("[{}]", index)),
+        }
+    }
+    result
+}
+
+fn yamlpath(segments: &[Segment]) -> String {
+    let mut result = String::from("$");
+    for segment in segments {
+        match segment {
+            Segment::Key(key) => {
+                result.push_str("[\"");
+                for ch in key.chars() {
+                    if ch == '"' || ch == '\\' {
+                        result.push('\\');
+                    }
+                    result.push(ch);
+                }
+                result.push_str("\"]");
+            }
+            Segment::Index(index) => result.push_str(&format!("[{}]", index)),
+        }
+    }
+    result
+}
+
+/// Finds `text` in the YAML files under `root`, other than `skip`.
+///
+/// Hidden directories and `node_modules` are not searched, and files that
+/// don't parse are passed over. Locations are relative to `root`.
+pub fn find_in_files(root: &Path, skip: Option<&Path>, text: &str) -> Vec<Usage> {
+    let mut files = Vec::new();
+    collect_yaml_files(root, &mut files);
+    files.sort();
+
+    let skip = skip.and_then(|path| path.canonicalize().ok());
+    let mut found = Vec::new();
+    for file in files {
+        if skip.is_some() && file.canonicalize().ok() == skip {
+            continue;
+        }
+        let Ok(source) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let Ok(document) = crate::document::parser::parse_yaml_auto(&source) else {
+            continue;
+        };
+        let name = file
+            .strip_prefix(root)
+            .unwrap_or(&file)
+            .display()
+            .to_string();
+        for mut usage in find(&document, text) {
+            usage.location = format!("{}:{}", name, usage.location);
+            usage.file = Some(file.clone());
+            found.push(usage);
+        }
+    }
+    found
+}
+
+fn collect_yaml_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "node_modules" {
+                collect_yaml_files(&path, files);
+            }
+        } else if name.ends_with(".yaml") || name.ends_with(".yml") {
+            files.push(path);
+        }
+    }
+}
+
+/// A planned `:extract`: a repeated value defined once under an anchor,
+/// with its other uses replaced by aliases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extraction {
+    /// Name of the new anchor
+    pub name: String,
+    /// Top-level mapping to define the value in, or `None` to anchor its
+    /// first use
+    pub key: Option<String>,
+    /// The node to define the value from
+    pub source: Vec<usize>,
+    /// The uses to replace with aliases
+    pub aliases: Vec<Vec<usize>>,
+}
+
+/// Plans extracting the value at `source` as `&name`, defined in the
+/// top-level mapping `key` or else at its first use, and returns the plan
+/// with one preview row per affected path.
+///
+/// Only uses of the same type are replaced, and nodes that already have an
+/// anchor are left alone.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::document::tree::YamlTree;
+/// use yamlquill::editor::usages::plan_extract;
+///
+/// let tree = YamlTree::new(parse_yaml_auto("a: 8080\nb: '8080'\nc: 8080\n").unwrap());
+/// let (plan, rows) = plan_extract(&tree, &[2], "port", None).unwrap();
+/// assert_eq!(plan.aliases, vec![vec![2]]);
+/// assert_eq!(rows[0].preview, "&port 8080");
+/// assert_eq!(rows[1].location, ".c");
+/// ```
+pub fn plan_extract(
+    tree: &YamlTree,
+    source: &[usize],
+    name: &str,
+    key: Option<&str>,
+) -> anyhow::Result<(Extraction, Vec<Usage>)> {
+    use anyhow::bail;
+
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!("Anchor names use letters, digits, - and _");
+    }
+    let root = tree.root();
+    let node = tree
+        .get_node(source)
+        .filter(|node| node.alias_target().is_none())
+        .ok_or_else(|| anyhow::anyhow!("Not on a scalar value"))?;
+    let text = scalar_text(node.value()).ok_or_else(|| anyhow::anyhow!("Not on a scalar value"))?;
+    if let Some(anchor) = node.anchor() {
+        bail!("{} already has anchor &{}", text, anchor);
+    }
+    if tree.anchor_registry().get_anchor_path(name).is_some() {
+        bail!("Anchor &{} already exists", name);
+    }
+
+    let value = node.value();
+    let uses = find_matching(root, &|other| {
+        other.anchor().is_none() && same_scalar(other.value(), value)
+    });
+    if uses.len() < 2 {
+        bail!("{} is only used once", text);
+    }
+
+    let Some(definition) = key else {
+        // The first use becomes the definition
+        let mut rows = uses;
+        rows[0].preview = format!("&{} {}", name, rows[0].preview);
+        for row in &mut rows[1..] {
+            row.preview = format!("*{}", name);
+        }
+        let extraction = Extraction {
+            name: name.to_string(),
+            key: None,
+            source: rows[0].path.clone(),
+            aliases: rows[1..].iter().map(|row| row.path.clone()).collect(),
+        };
+        return Ok((extraction, rows));
+    };
+
+    let YamlValue::Object(entries) = root.value() else {
+        bail!("Variables need a mapping at the top level");
+    };
+    if let Some((index, _, vars)) = entries.get_full(definition) {
+        let YamlValue::Object(vars) = vars.value() else {
+            bail!("'{}' is not a mapping", definition);
+        };
+        if vars.contains_key(name) {
+            bail!("Key '{}' already exists in {}", name, definition);
+        }
+        if uses.iter().any(|usage| usage.path[0] <= index) {
+            bail!("{} must come before the first use of {}", definition, text);
+        }
+    }
+
+    let mut rows = vec![Usage {
+        file: None,
+        path: Vec::new(),
+        yamlpath: String::new(),
+        location: format!(".{}.{}", definition, name),
+        preview: format!("&{} {}", name, format_collapsed_preview(node, 40)),
+    }];
+    rows.extend(uses.into_iter().map(|row| Usage {
+        preview: format!("*{}", name),
+        ..row
+    }));
+    let aliases = rows[1..].iter().map(|row| row.path.clone()).collect();
+    Ok((
+        Extraction {
+            name: name.to_string(),
+            key: Some(definition.to_string()),
+            source: source.to_vec(),
+            aliases,
+        },
+        rows,
+    ))
+}
+
+/// Selection state for the `:usages` results panel.
+#[derive(Debug, Clone)]
+pub struct UsagesPanelState {
+    /// The value searched for
+    pub value: String,
+    /// Where it was found, this document's uses first
+    pub usages: Vec<Usage>,
+    /// Index of the highlighted usage
+    pub selected_index: usize,
+    /// The `:extract` the panel previews, applied by `Enter`
+    pub extraction: Option<Extraction>,
+}
+
+impl UsagesPanelState {
+    /// Creates a panel over `usages` with the first one selected.
+    pub fn new(value: String, usages: Vec<Usage>) -> Self {
+        Self {
+            value,
+            usages,
+            selected_index: 0,
+            extraction: None,
+        }
+    }
+
+    /// Creates a panel previewing `extraction`, one row per affected path.
+    pub fn preview(value: String, extraction: Extraction, rows: Vec<Usage>) -> Self {
+        Self {
+            extraction: Some(extraction),
+            ..Self::new(value, rows)
+        }
+    }
+
+    /// Moves the selection up, wrapping to the bottom.
+    pub fn previous(&mut self) {
+        if !self.usages.is_empty() {
+            self.selected_index = (self.selected_index + self.usages.len() - 1) % self.usages.len();
+        }
+    }
+
+    /// Moves the selection down, wrapping to the top.
+    pub fn next(&mut self) {
+        if !self.usages.is_empty() {
+            self.selected_index = (self.selected_index + 1) % self.usages.len();
+        }
+    }
+
+    /// Returns the highlighted usage.
+    pub fn selected(&self) -> Option<&Usage> {
+        self.usages.get(self.selected_index)
+    }
+}
@@ -0,0 +1,126 @@
+//! Watch expressions (`:watch`).
+//!
+//! A watch is a YAMLPath expression that is evaluated again every time the
+//! tree changes. The watch panel lists each expression with its current
+//! value, and an entry whose value changed in the last edit flashes and
+//! shows what it was before, so a bulk edit can be checked against the
+//! values it was meant to touch.
+
+use crate::document::node::YamlNode;
+use crate::ui::tree_view::format_collapsed_preview;
+use crate::yamlpath::{Evaluator, Parser, YamlPath};
+use anyhow::Result;
+use std::time::{Duration, Instant};
+
+/// Maximum number of watch expressions.
+pub const MAX_WATCHES: usize = 9;
+
+/// How long a watch flashes after its value changes.
+pub const FLASH_DURATION: Duration = Duration::from_millis(1500);
+
+/// Longest value text kept for a watch.
+const MAX_VALUE_CHARS: usize = 80;
+
+/// A YAMLPath expression and the value it evaluated to last.
+#[derive(Debug, Clone)]
+pub struct Watch {
+    expression: String,
+    path: YamlPath,
+    value: String,
+    previous: Option<String>,
+    changed_at: Option<Instant>,
+}
+
+impl Watch {
+    /// Parses `expression` and evaluates it against `root`.
+    pub fn new(expression: &str, root: &YamlNode) -> Result<Self> {
+        let path = Parser::parse(expression)
+            .map_err(|e| anyhow::anyhow!("Invalid YAMLPath '{}': {}", expression, e))?;
+        let mut watch = Self {
+            expression: expression.to_string(),
+            path,
+            value: String::new(),
+            previous: None,
+            changed_at: None,
+        };
+        watch.value = watch.value_in(root);
+        Ok(watch)
+    }
+
+    /// Returns the expression as typed.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Returns the text of the current value.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the value before the last change, if it has changed.
+    pub fn previous(&self) -> Option<&str> {
+        self.previous.as_deref()
+    }
+
+    /// Returns true while the watch should flash after a change.
+    pub fn is_flashing(&self, now: Instant) -> bool {
+        self.changed_at
+            .is_some_and(|at| now.saturating_duration_since(at) < FLASH_DURATION)
+    }
+
+    /// Evaluates the expression again and returns true if the value changed.
+    pub fn evaluate(&mut self, root: &YamlNode) -> bool {
+        let value = self.value_in(root);
+        if value == self.value {
+            return false;
+        }
+        self.previous = Some(std::mem::replace(&mut self.value, value));
+        self.changed_at = Some(Instant::now());
+        true
+    }
+
+    /// Formats what the expression matches: a single value as its preview,
+    /// several as a count followed by their previews.
+    fn value_in(&self, root: &YamlNode) -> String {
+        let nodes = Evaluator::new(root).evaluate(&self.path.segments);
+        let previews: Vec<String> = nodes
+            .iter()
+            .map(|node| format_collapsed_preview(node, MAX_VALUE_CHARS))
+            .collect();
+        let text = match previews.len() {
+            0 => "(no match)".to_string(),
+            1 => previews[0].clone(),
+            n => format!("{} matches: {}", n, previews.join(", ")),
+        };
+        match text.char_indices().nth(MAX_VALUE_CHARS) {
+            Some((end, _)) => format!("{}...", &text[..end]),
+            None => text,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    #[test]
+    fn test_evaluate_tracks_changes() {
+        let root = parse_yaml_auto("a: 1\nb: [x, y]\n").unwrap();
+        let mut watch = Watch::new("$.a", &root).unwrap();
+        assert_eq!(watch.value(), "1");
+        assert!(!watch.evaluate(&root));
+        assert!(!watch.is_flashing(Instant::now()));
+
+        let changed = parse_yaml_auto("a: 2\n").unwrap();
+        assert!(watch.evaluate(&changed));
+        assert_eq!(watch.value(), "2");
+        assert_eq!(watch.previous(), Some("1"));
+        assert!(watch.is_flashing(Instant::now()));
+        assert!(!watch.is_flashing(Instant::now() + FLASH_DURATION));
+
+        let many = Watch::new("$.b[*]", &root).unwrap();
+        assert_eq!(many.value(), "2 matches: x, y");
+        assert!(Watch::new("$[", &root).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Split windows side by side.
+//!
+//! `:vsplit other.yaml` opens a second file next to the current one. Each
+//! pane shows a buffer — a complete [`EditorState`](super::state::EditorState)
+//! with its own cursor, undo history, folds and dirty flag — and key input
+//! goes to the focused pane only. `Ctrl-w` followed by `w`, `h`/`l` or `q`
+//! moves focus between panes or closes one.
+//!
+//! Window commands are raised from inside the focused pane's key handling,
+//! which only sees that pane's state, so they are queued on the state with
+//! [`EditorState::request_window_command`](super::state::EditorState::request_window_command)
+//! and applied by the [`App`](crate::app::App) after the key has been handled
+//! (the same way theme changes reach the UI).
+
+/// A request to change the window layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowCommand {
+    /// Open a file in a new pane to the right of the focused one (`:vsplit`)
+    VSplit(String),
+    /// Focus the next pane, wrapping around (`Ctrl-w w`)
+    FocusNext,
+    /// Focus the pane to the left (`Ctrl-w h`)
+    FocusLeft,
+    /// Focus the pane to the right (`Ctrl-w l`)
+    FocusRight,
+    /// Close the focused pane; its buffer stays open (`Ctrl-w q`, `:close`)
+    Close,
+    /// Close every pane except the focused one (`Ctrl-w o`, `:only`)
+    Only,
+}
+
+/// The panes on screen, left to right, and which one has focus.
+///
+/// Each pane shows one of the [`App`](crate::app::App)'s buffers, by index.
+/// There is always at least one pane.
+#[derive(Debug, Clone)]
+pub struct Windows {
+    panes: Vec<usize>,
+    focus: usize,
+}
+
+impl Windows {
+    /// Creates a layout with a single pane showing `buffer`.
+    pub fn new(buffer: usize) -> Self {
+        Self {
+            panes: vec![buffer],
+            focus: 0,
+        }
+    }
+
+    /// Returns the buffer shown in each pane, left to right.
+    pub fn panes(&self) -> &[usize] {
+        &self.panes
+    }
+
+    /// Returns the number of panes.
+    pub fn len(&self) -> usize {
+        self.panes.len()
+    }
+
+    /// Returns true if there are no panes (never the case).
+    pub fn is_empty(&self) -> bool {
+        self.panes.is_empty()
+    }
+
+    /// Returns the index of the focused pane.
+    pub fn focus_index(&self) -> usize {
+        self.focus
+    }
+
+    /// Returns the buffer shown in the focused pane.
+    pub fn focused_buffer(&self) -> usize {
+        self.panes[self.focus]
+    }
+
+    /// Shows `buffer` in the focused pane.
+    pub fn set_focused_buffer(&mut self, buffer: usize) {
+        self.panes[self.focus] = buffer;
+    }
+
+    /// Adds a pane showing `buffer` to the right of the focused one and focuses it.
+    pub fn split(&mut self, buffer: usize) {
+        self.focus += 1;
+        self.panes.insert(self.focus, buffer);
+    }
+
+    /// Closes the focused pane and focuses its left neighbour.
+    ///
+    /// Returns false, leaving the layout unchanged, if it is the last pane.
+    pub fn close_focused(&mut self) -> bool {
+        if self.panes.len() == 1 {
+            return false;
+        }
+        self.panes.remove(self.focus);
+        self.focus = self.focus.saturating_sub(1);
+        true
+    }
+
+    /// Closes every pane except the focused one.
+    pub fn only(&mut self) {
+        self.panes = vec![self.focused_buffer()];
+        self.focus = 0;
+    }
+
+    /// Focuses the next pane, wrapping around.
+    pub fn focus_next(&mut self) {
+        self.focus = (self.focus + 1) % self.panes.len();
+    }
+
+    /// Focuses the pane to the left, if any.
+    pub fn focus_left(&mut self) {
+        self.focus = self.focus.saturating_sub(1);
+    }
+
+    /// Focuses the pane to the right, if any.
+    pub fn focus_right(&mut self) {
+        self.focus = (self.focus + 1).min(self.panes.len() - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_focus_movement() {
+        let mut windows = Windows::new(0);
+        windows.split(1);
+        windows.split(2);
+        assert_eq!(windows.panes(), [0, 1, 2]);
+        assert_eq!(windows.focus_index(), 2);
+
+        windows.focus_right();
+        assert_eq!(windows.focus_index(), 2);
+        windows.focus_next();
+        assert_eq!(windows.focus_index(), 0);
+        windows.focus_left();
+        assert_eq!(windows.focus_index(), 0);
+    }
+
+    #[test]
+    fn test_close_and_only() {
+        let mut windows = Windows::new(0);
+        windows.split(1);
+        windows.split(2);
+        windows.focus_left();
+
+        assert!(windows.close_focused());
+        assert_eq!(windows.panes(), [0, 2]);
+        assert_eq!(windows.focused_buffer(), 0);
+
+        windows.focus_right();
+        windows.only();
+        assert_eq!(windows.panes(), [2]);
+        assert!(!windows.close_focused());
+    }
+
+    #[test]
+    fn test_set_focused_buffer() {
+        let mut windows = Windows::new(0);
+        windows.split(0);
+        windows.set_focused_buffer(3);
+        assert_eq!(windows.panes(), [0, 3]);
+    }
+}
@@ -0,0 +1,307 @@
+//! Printable renderings of the document.
+//!
+//! Exports render the tree view as it currently looks — same colors, same
+//! folds — so a view of a config can be pasted into docs or chat:
+//!
+//! - [`render_html`]: a standalone, syntax-highlighted HTML page (`:export html`)
+//! - [`render_ansi`]: text with ANSI color escapes (`--render-ansi`)
+//! - [`outline`]: a Markdown bullet list of keys (`:export outline`)
+//! - [`share`]: the HTML rendering served live over HTTP (`:share`)
+
+pub mod outline;
+pub mod share;
+
+use crate::theme::colors::ThemeColors;
+use crate::ui::tree_view::{line_spans, TreeViewState};
+use ratatui::style::{Color, Modifier, Style};
+use std::fmt::Write;
+
+/// Renders the visible tree lines as a standalone HTML document.
+///
+/// Collapsed containers stay collapsed and show their preview, exactly as in
+/// the editor.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::document::tree::YamlTree;
+/// use yamlquill::export::render_html;
+/// use yamlquill::theme::colors::ThemeColors;
+/// use yamlquill::ui::tree_view::TreeViewState;
+///
+/// let tree = YamlTree::new(parse_yaml_auto("name: web\n").unwrap());
+/// let mut view = TreeViewState::new();
+/// view.rebuild(&tree);
+///
+/// let html = render_html(&view, &ThemeColors::default_dark(), "config.yaml");
+/// assert!(html.contains("<title>config.yaml</title>"));
+/// assert!(html.contains("name: "));
+/// ```
+pub fn render_html(tree_view: &TreeViewState, colors: &ThemeColors, title: &str) -> String {
+    let background = css_color(colors.background).unwrap_or("#1e1e1e".to_string());
+    let foreground = css_color(colors.foreground).unwrap_or("#d4d4d4".to_string());
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>{}</title>", escape_html(title));
+    html.push_str("</head>\n<body>\n");
+    let _ = writeln!(
+        html,
+        "<pre style=\"background: {}; color: {}; padding: 1em; font-family: monospace;\">",
+        background, foreground
+    );
+
+    for line in tree_view.lines() {
+        for span in line_spans(line, colors, false) {
+            let css = css_style(span.style);
+            let text = escape_html(&span.content);
+            if css.is_empty() {
+                html.push_str(&text);
+            } else {
+                let _ = write!(html, "<span style=\"{}\">{}</span>", css, text);
+            }
+        }
+        html.push('\n');
+    }
+
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
+
+/// Renders the visible tree lines as text with ANSI color escapes.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::document::tree::YamlTree;
+/// use yamlquill::export::render_ansi;
+/// use yamlquill::theme::colors::ThemeColors;
+/// use yamlquill::ui::tree_view::TreeViewState;
+///
+/// let tree = YamlTree::new(parse_yaml_auto("name: web\n").unwrap());
+/// let mut view = TreeViewState::new();
+/// view.rebuild(&tree);
+///
+/// let text = render_ansi(&view, &ThemeColors::default_dark());
+/// assert!(text.contains("name: "));
+/// assert!(text.contains("\x1b["));
+/// ```
+pub fn render_ansi(tree_view: &TreeViewState, colors: &ThemeColors) -> String {
+    let mut out = String::new();
+    for line in tree_view.lines() {
+        for span in line_spans(line, colors, false) {
+            let sgr = sgr_codes(span.style);
+            if sgr.is_empty() {
+                out.push_str(&span.content);
+            } else {
+                let _ = write!(out, "\x1b[{}m{}\x1b[0m", sgr.join(";"), span.content);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn css_style(style: Style) -> String {
+    let mut css = Vec::new();
+    if let Some(fg) = style.fg.and_then(css_color) {
+        css.push(format!("color: {}", fg));
+    }
+    if let Some(bg) = style.bg.and_then(css_color) {
+        css.push(format!("background: {}", bg));
+    }
+    if style.add_modifier.contains(Modifier::BOLD) {
+        css.push("font-weight: bold".to_string());
+    }
+    if style.add_modifier.contains(Modifier::ITALIC) {
+        css.push("font-style: italic".to_string());
+    }
+    if style.add_modifier.contains(Modifier::DIM) {
+        css.push("opacity: 0.7".to_string());
+    }
+    css.join("; ")
+}
+
+/// Converts a terminal color to CSS, using the xterm palette for named colors.
+///
+/// Returns `None` for `Color::Reset`, which means "the terminal's default".
+fn css_color(color: Color) -> Option<String> {
+    let (r, g, b) = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(i) => indexed_rgb(i),
+        Color::Black => indexed_rgb(0),
+        Color::Red => indexed_rgb(1),
+        Color::Green => indexed_rgb(2),
+        Color::Yellow => indexed_rgb(3),
+        Color::Blue => indexed_rgb(4),
+        Color::Magenta => indexed_rgb(5),
+        Color::Cyan => indexed_rgb(6),
+        Color::Gray => indexed_rgb(7),
+        Color::DarkGray => indexed_rgb(8),
+        Color::LightRed => indexed_rgb(9),
+        Color::LightGreen => indexed_rgb(10),
+        Color::LightYellow => indexed_rgb(11),
+        Color::LightBlue => indexed_rgb(12),
+        Color::LightMagenta => indexed_rgb(13),
+        Color::LightCyan => indexed_rgb(14),
+        Color::White => indexed_rgb(15),
+    };
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+/// Returns the RGB value of an xterm 256-color palette entry.
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    const BASE: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x00, 0x00),
+        (0x00, 0xcd, 0x00),
+        (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee),
+        (0xcd, 0x00, 0xcd),
+        (0x00, 0xcd, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+    match index {
+        0..=15 => BASE[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            (level(i / 36), level((i / 6) % 6), level(i % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn sgr_codes(style: Style) -> Vec<String> {
+    let mut codes = Vec::new();
+    if style.add_modifier.contains(Modifier::BOLD) {
+        codes.push("1".to_string());
+    }
+    if style.add_modifier.contains(Modifier::DIM) {
+        codes.push("2".to_string());
+    }
+    if style.add_modifier.contains(Modifier::ITALIC) {
+        codes.push("3".to_string());
+    }
+    if let Some(fg) = style.fg.and_then(|c| sgr_color(c, false)) {
+        codes.push(fg);
+    }
+    if let Some(bg) = style.bg.and_then(|c| sgr_color(c, true)) {
+        codes.push(bg);
+    }
+    codes
+}
+
+fn sgr_color(color: Color, background: bool) -> Option<String> {
+    let offset = if background { 10 } else { 0 };
+    let named = |code: u8| Some((code + offset).to_string());
+    match color {
+        Color::Reset => None,
+        Color::Black => named(30),
+        Color::Red => named(31),
+        Color::Green => named(32),
+        Color::Yellow => named(33),
+        Color::Blue => named(34),
+        Color::Magenta => named(35),
+        Color::Cyan => named(36),
+        Color::Gray => named(37),
+        Color::DarkGray => named(90),
+        Color::LightRed => named(91),
+        Color::LightGreen => named(92),
+        Color::LightYellow => named(93),
+        Color::LightBlue => named(94),
+        Color::LightMagenta => named(95),
+        Color::LightCyan => named(96),
+        Color::White => named(97),
+        Color::Indexed(i) => Some(format!("{};5;{}", 38 + offset, i)),
+        Color::Rgb(r, g, b) => Some(format!("{};2;{};{};{}", 38 + offset, r, g, b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+    use crate::document::tree::YamlTree;
+
+    fn view(yaml: &str) -> (YamlTree, TreeViewState) {
+        let tree = YamlTree::new(parse_yaml_auto(yaml).unwrap());
+        let mut view = TreeViewState::new();
+        view.expand_all(&tree);
+        view.rebuild(&tree);
+        (tree, view)
+    }
+
+    #[test]
+    fn test_html_escapes_content() {
+        let (_, view) = view("tag: \"<b>&\"\n");
+        let html = render_html(&view, &ThemeColors::default_dark(), "a<b");
+
+        assert!(html.contains("<title>a&lt;b</title>"));
+        assert!(html.contains("&lt;b&gt;&amp;"));
+        assert!(!html.contains("<b>&"));
+    }
+
+    #[test]
+    fn test_html_respects_folds() {
+        let (tree, mut view) = view("spec:\n  replicas: 2\n");
+        let expanded = render_html(&view, &ThemeColors::default_dark(), "t");
+        assert!(expanded.contains("▼ "));
+
+        view.toggle_expand(&[0]);
+        view.rebuild(&tree);
+        let collapsed = render_html(&view, &ThemeColors::default_dark(), "t");
+        assert!(collapsed.contains("▶ "));
+        assert_eq!(collapsed.lines().count() + 1, expanded.lines().count());
+    }
+
+    #[test]
+    fn test_ansi_uses_theme_colors() {
+        let (_, view) = view("count: 3\n");
+        let colors = ThemeColors::default_dark();
+        let text = render_ansi(&view, &colors);
+
+        // Keys are light blue and numbers magenta in the default dark theme
+        assert!(text.contains("\x1b[94mcount: \x1b[0m"));
+        assert!(text.contains("\x1b[35m3\x1b[0m"));
+    }
+
+    #[test]
+    fn test_css_color_conversion() {
+        assert_eq!(
+            css_color(Color::Rgb(1, 2, 255)),
+            Some("#0102ff".to_string())
+        );
+        assert_eq!(css_color(Color::Indexed(16)), Some("#000000".to_string()));
+        assert_eq!(css_color(Color::Indexed(255)), Some("#eeeeee".to_string()));
+        assert_eq!(css_color(Color::Reset), None);
+    }
+}
@@ -0,0 +1,178 @@
+//! Markdown outline export (`:export outline`).
+//!
+//! Writes the shape of the document as a nested bullet list of keys, for
+//! documenting a config file in a README. Unlike the HTML export this walks
+//! the whole document, not just the lines that are currently unfolded.
+
+use crate::document::node::{YamlNode, YamlValue};
+use crate::document::tree::YamlTree;
+
+/// Options for [`render_outline`].
+#[derive(Debug, Clone, Default)]
+pub struct OutlineOptions {
+    /// Deepest level to include (1 = top-level keys only); `None` for no limit
+    pub max_depth: Option<usize>,
+    /// Whether to show scalar values next to their keys
+    pub values: bool,
+}
+
+impl OutlineOptions {
+    /// Parses `:export outline` arguments: `depth=N` and `values`.
+    pub fn from_args<'a>(args: impl IntoIterator<Item = &'a str>) -> anyhow::Result<Self> {
+        let mut options = Self::default();
+        for arg in args {
+            if arg == "values" {
+                options.values = true;
+            } else if let Some(depth) = arg.strip_prefix("depth=") {
+                let depth: usize = depth
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid depth: {}", depth))?;
+                options.max_depth = Some(depth);
+            } else {
+                anyhow::bail!("Unknown outline option: {}", arg);
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Renders the document as a Markdown bullet list of keys.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::document::tree::YamlTree;
+/// use yamlquill::export::outline::{render_outline, OutlineOptions};
+///
+/// let tree = YamlTree::new(parse_yaml_auto("spec:\n  replicas: 2\n").unwrap());
+/// let options = OutlineOptions { max_depth: None, values: true };
+///
+/// assert_eq!(render_outline(&tree, &options), "- `spec`\n  - `replicas`: `2`\n");
+/// ```
+pub fn render_outline(tree: &YamlTree, options: &OutlineOptions) -> String {
+    let mut out = String::new();
+    match tree.root().value() {
+        YamlValue::MultiDoc(docs) => {
+            for (i, doc) in docs.iter().enumerate() {
+                push_item(&mut out, 0, &format!("Document {}", i + 1), None);
+                outline_children(&mut out, doc, 1, options);
+            }
+        }
+        _ => outline_children(&mut out, tree.root(), 0, options),
+    }
+    out
+}
+
+fn outline_children(out: &mut String, node: &YamlNode, depth: usize, options: &OutlineOptions) {
+    if options.max_depth.is_some_and(|max| depth >= max) {
+        return;
+    }
+    match node.value() {
+        YamlValue::Object(entries) => {
+            for (key, child) in entries.iter().filter(|(_, child)| !child.is_comment()) {
+                push_entry(out, depth, &code(key), child, options);
+            }
+        }
+        YamlValue::Array(items) => {
+            for (i, child) in items.iter().filter(|child| !child.is_comment()).enumerate() {
+                push_entry(out, depth, &format!("[{}]", i), child, options);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn push_entry(
+    out: &mut String,
+    depth: usize,
+    label: &str,
+    node: &YamlNode,
+    options: &OutlineOptions,
+) {
+    let value = match node.value() {
+        YamlValue::Object(_) | YamlValue::Array(_) | YamlValue::MultiDoc(_) => None,
+        scalar if options.values => Some(code(&scalar_text(scalar))),
+        _ => None,
+    };
+    push_item(out, depth, label, value.as_deref());
+    outline_children(out, node, depth + 1, options);
+}
+
+fn push_item(out: &mut String, depth: usize, label: &str, value: Option<&str>) {
+    out.push_str(&"  ".repeat(depth));
+    out.push_str("- ");
+    out.push_str(label);
+    if let Some(value) = value {
+        out.push_str(": ");
+        out.push_str(value);
+    }
+    out.push('\n');
+}
+
+/// Returns a one-line rendering of a scalar; multi-line strings are cut at the first line.
+fn scalar_text(value: &YamlValue) -> String {
+    let text = value.to_string();
+    match text.split_once('\n') {
+        Some((first, _)) => format!("{}…", first),
+        None => text,
+    }
+}
+
+/// Wraps text in a Markdown code span, widening the fence if the text contains backticks.
+fn code(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    fn outline(yaml: &str, max_depth: Option<usize>, values: bool) -> String {
+        let tree = YamlTree::new(parse_yaml_auto(yaml).unwrap());
+        render_outline(&tree, &OutlineOptions { max_depth, values })
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        let options = OutlineOptions::from_args(["depth=2", "values"]).unwrap();
+        assert_eq!(options.max_depth, Some(2));
+        assert!(options.values);
+
+        assert!(OutlineOptions::from_args(["depth=x"]).is_err());
+        assert!(OutlineOptions::from_args(["bogus"]).is_err());
+    }
+
+    #[test]
+    fn test_keys_only_by_default() {
+        let yaml = "name: web\nports:\n  - 80\n  - 443\n";
+        assert_eq!(
+            outline(yaml, None, false),
+            "- `name`\n- `ports`\n  - [0]\n  - [1]\n"
+        );
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let yaml = "spec:\n  template:\n    image: nginx\n";
+        assert_eq!(outline(yaml, Some(2), false), "- `spec`\n  - `template`\n");
+        assert_eq!(outline(yaml, Some(1), false), "- `spec`\n");
+    }
+
+    #[test]
+    fn test_comments_are_skipped() {
+        let yaml = "# header\nname: web\n";
+        assert_eq!(outline(yaml, None, true), "- `name`: `web`\n");
+    }
+
+    #[test]
+    fn test_multiline_value_is_truncated() {
+        let yaml = "script: |\n  echo one\n  echo two\n";
+        assert_eq!(outline(yaml, None, true), "- `script`: `echo one…`\n");
+    }
+}
@@ -0,0 +1,177 @@
+//! Read-only live view of a buffer over HTTP (`:share`).
+//!
+//! The server runs on a background thread and serves the latest published
+//! HTML rendering of the tree view. The page reloads itself every second, so
+//! someone following along sees edits and folds shortly after they happen.
+//! Pages are only served under a random token path, so a server listening on
+//! a shared network doesn't hand the buffer to whoever finds the port.
+
+use anyhow::{Context, Result};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long the accept loop sleeps between polls for connections.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Tag added to published pages so browsers reload them.
+const REFRESH_TAG: &str = "<meta http-equiv=\"refresh\" content=\"1\">\n";
+
+/// A running share server; dropping it stops the server.
+#[derive(Debug)]
+pub struct ShareServer {
+    addr: SocketAddr,
+    token: String,
+    page: Arc<Mutex<String>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ShareServer {
+    /// Starts serving on `addr` (`127.0.0.1:0` picks a free local port).
+    pub fn start(addr: &str) -> Result<Self> {
+        let listener =
+            TcpListener::bind(addr).with_context(|| format!("Cannot listen on {}", addr))?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let token = random_token();
+        let page = Arc::new(Mutex::new(String::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let (thread_page, thread_stop, path) = (page.clone(), stop.clone(), format!("/{}", token));
+        thread::spawn(move || {
+            while !thread_stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        // A client that misbehaves only loses its own request
+                        let _ = respond(stream, &path, &thread_page);
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                    Err(_) => thread::sleep(POLL_INTERVAL),
+                }
+            }
+        });
+
+        Ok(Self {
+            addr,
+            token,
+            page,
+            stop,
+        })
+    }
+
+    /// Returns the URL of the shared page.
+    pub fn url(&self) -> String {
+        format!("http://{}/{}", self.addr, self.token)
+    }
+
+    /// Replaces the served page with `html` (a document from
+    /// [`render_html`](crate::export::render_html)).
+    pub fn publish(&self, html: &str) {
+        let page = match html.find("<head>\n") {
+            Some(i) => format!("{}{}{}", &html[..i + 7], REFRESH_TAG, &html[i + 7..]),
+            None => html.to_string(),
+        };
+        if let Ok(mut current) = self.page.lock() {
+            *current = page;
+        }
+    }
+}
+
+impl Drop for ShareServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Answers one HTTP request: the page at `path`, 404 for anything else.
+fn respond(mut stream: TcpStream, path: &str, page: &Mutex<String>) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&chunk[..n]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut parts = request.split_whitespace();
+    let target = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => target,
+        _ => "",
+    };
+
+    let (status, body) = if target == path {
+        let body = page.lock().map(|p| p.clone()).unwrap_or_default();
+        ("200 OK", body)
+    } else {
+        ("404 Not Found", "Not found\n".to_string())
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Returns 16 hex digits from the standard library's randomly seeded hasher.
+fn random_token() -> String {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_serves_published_page_under_token() {
+        let server = ShareServer::start("127.0.0.1:0").unwrap();
+        server.publish("<!DOCTYPE html>\n<html>\n<head>\n<title>x</title>\n</head>\nname: web\n");
+        let path = format!("/{}", server.token);
+        assert!(server.url().ends_with(&path));
+
+        let response = get(server.addr, &path);
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("<head>\n<meta http-equiv=\"refresh\""));
+        assert!(response.contains("name: web"));
+
+        server.publish("<html>\n<head>\n</head>\nname: api\n");
+        assert!(get(server.addr, &path).contains("name: api"));
+
+        let response = get(server.addr, "/");
+        assert!(response.starts_with("HTTP/1.1 404"));
+        assert!(!response.contains("name: api"));
+    }
+
+    #[test]
+    fn test_tokens_differ() {
+        assert_ne!(random_token(), random_token());
+        assert_eq!(random_token().len(), 16);
+    }
+}
@@ -0,0 +1,354 @@
+//! Writing documents as JSON or TOML.
+//!
+//! `:w` picks the output format from the file extension (a trailing `.gz`
+//! is looked through), so `:w config.json` converts the buffer instead of
+//! writing YAML under a `.json` name. Aliases and merge keys are expanded,
+//! since neither format has them, and comments are dropped. Values a format
+//! can't hold (null in TOML, NaN in JSON, several documents) are reported
+//! with their path instead of being written some other way.
+//!
+//! A file whose name doesn't say what it is keeps the format it already has
+//! on disk, so a TOML file called `Pipfile` is written back as TOML.
+
+use crate::config::Config;
+use crate::document::duplicates::written_key;
+use crate::document::lazy::load_deferred;
+use crate::document::node::{YamlNode, YamlNumber, YamlValue};
+use crate::document::tree::{YamlTree, MERGE_KEY};
+use crate::file::saver::{create_backup, write_file_atomic};
+use crate::file::serializer::Style;
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+
+/// A format the buffer can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl Format {
+    /// Returns the format for a file name: `.json` and `.toml` (optionally
+    /// followed by `.gz`) are converted, anything else is YAML.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        Self::from_extension(path).unwrap_or(Format::Yaml)
+    }
+
+    /// Returns the format a file's extension names, if it names one.
+    pub fn from_extension(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref();
+        let path = match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("gz") => path.with_extension(""),
+            _ => path.to_path_buf(),
+        };
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "yaml" | "yml" => Some(Format::Yaml),
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format of a file's contents.
+    ///
+    /// JSON is recognized when the whole text parses as a JSON object or
+    /// array, TOML when it parses as a non-empty TOML table but not as a YAML
+    /// collection (`key = value` lines are a valid YAML string). Anything
+    /// else is YAML.
+    pub fn sniff(contents: &str) -> Self {
+        let trimmed = contents.trim_start();
+        if (trimmed.starts_with('{') || trimmed.starts_with('['))
+            && serde_json::from_str::<serde_json::Value>(contents).is_ok()
+        {
+            return Format::Json;
+        }
+        let is_toml = toml::from_str::<toml::Table>(contents).is_ok_and(|t| !t.is_empty());
+        let is_yaml_collection = serde_yaml::from_str::<serde_yaml::Value>(contents)
+            .is_ok_and(|v| v.is_mapping() || v.is_sequence());
+        if is_toml && !is_yaml_collection {
+            Format::Toml
+        } else {
+            Format::Yaml
+        }
+    }
+
+    /// Returns the format of a file: its extension if that names one,
+    /// otherwise what its contents look like.
+    pub fn detect(path: impl AsRef<Path>, contents: &str) -> Self {
+        Self::from_extension(path).unwrap_or_else(|| Self::sniff(contents))
+    }
+
+    /// Returns the format's display name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Yaml => "YAML",
+            Format::Json => "JSON",
+            Format::Toml => "TOML",
+        }
+    }
+}
+
+/// Saves `tree` to `path` in the format its extension asks for, or in the
+/// format the file already has if the extension doesn't say.
+///
+/// The text is written in the style configured for the format
+/// (`yaml_style`, `json_style`; see [`crate::file::serializer`]), so by
+/// default YAML keeps its formatting. Nothing is written if conversion
+/// fails. Returns the format written.
+pub fn save_file<P: AsRef<Path>>(path: P, tree: &YamlTree, config: &Config) -> Result<Format> {
+    save_file_styled(path, tree, config, None)
+}
+
+/// Saves like [`save_file`], in `style` instead of the configured one if
+/// given (`:w --style`).
+pub fn save_file_styled<P: AsRef<Path>>(
+    path: P,
+    tree: &YamlTree,
+    config: &Config,
+    style: Option<Style>,
+) -> Result<Format> {
+    let path = path.as_ref();
+    let format = Format::from_extension(path).unwrap_or_else(|| {
+        std::fs::read_to_string(path)
+            .map(|contents| Format::sniff(&contents))
+            .unwrap_or(Format::Yaml)
+    });
+    let style = match style {
+        Some(style) => style,
+        None => Style::parse(match format {
+            Format::Json => &config.json_style,
+            Format::Yaml | Format::Toml => &config.yaml_style,
+        })?,
+    };
+    let text = style.serializer().write(tree, format)?;
+
+    if config.create_backup && path.exists() {
+        create_backup(path)?;
+    }
+    let compress = path.to_string_lossy().ends_with(".gz");
+    write_file_atomic(path, text.as_bytes(), compress)?;
+    Ok(format)
+}
+
+/// Converts a document to pretty-printed JSON.
+pub fn to_json(tree: &YamlTree) -> Result<String> {
+    let value = json_value(single_document(tree, Format::Json)?, tree, ".")?;
+    let mut text = serde_json::to_string_pretty(&value).context("Failed to write JSON")?;
+    text.push('\n');
+    Ok(text)
+}
+
+/// Converts a document to JSON on a single line.
+pub fn to_json_compact(tree: &YamlTree) -> Result<String> {
+    let value = json_value(single_document(tree, Format::Json)?, tree, ".")?;
+    let mut text = serde_json::to_string(&value).context("Failed to write JSON")?;
+    text.push('\n');
+    Ok(text)
+}
+
+/// Converts a document to TOML; the top level must be a mapping.
+pub fn to_toml(tree: &YamlTree) -> Result<String> {
+    let root = single_document(tree, Format::Toml)?;
+    let toml::Value::Table(table) = toml_value(root, tree, ".")? else {
+        bail!("TOML needs a mapping at the top level");
+    };
+    toml::to_string(&table).context("Failed to write TOML")
+}
+
+fn single_document(tree: &YamlTree, format: Format) -> Result<&YamlNode> {
+    match tree.root().value() {
+        YamlValue::MultiDoc(_) => bail!(
+            "{} holds a single document; this file has several",
+            format.name()
+        ),
+        _ => Ok(tree.root()),
+    }
+}
+
+/// Follows an alias to the node it refers to.
+fn resolve<'a>(node: &'a YamlNode, tree: &'a YamlTree, path: &str) -> Result<&'a YamlNode> {
+    match node.value() {
+        YamlValue::Alias(name) => tree
+            .anchor_registry()
+            .get_anchor_path(name)
+            .and_then(|anchor| tree.get_node(anchor))
+            .with_context(|| format!("Alias *{} at {} has no anchor", name, path)),
+        _ => Ok(node),
+    }
+}
+
+/// Calls `f` with each entry of a mapping, expanding merge keys and skipping comments.
+///
+/// Repeated keys are passed as written, so the last entry wins, as it does
+/// for other YAML readers.
+fn for_each_entry(
+    node: &YamlNode,
+    tree: &YamlTree,
+    mut f: impl FnMut(&str, &YamlNode) -> Result<()>,
+) -> Result<()> {
+    let YamlValue::Object(entries) = node.value() else {
+        return Ok(());
+    };
+    for (key, value) in entries {
+        if key == MERGE_KEY || key.starts_with("__comment_") {
+            continue;
+        }
+        f(written_key(key), value)?;
+    }
+    for (key, value) in tree.merged_entries(node) {
+        f(&key, &value)?;
+    }
+    Ok(())
+}
+
+fn key_path(path: &str, key: &str) -> String {
+    match path {
+        "." => format!(".{}", key),
+        _ => format!("{}.{}", path, key),
+    }
+}
+
+fn index_path(path: &str, index: usize) -> String {
+    match path {
+        "." => format!("[{}]", index),
+        _ => format!("{}[{}]", path, index),
+    }
+}
+
+fn json_value(node: &YamlNode, tree: &YamlTree, path: &str) -> Result<serde_json::Value> {
+    use serde_json::Value;
+
+    let node = resolve(node, tree, path)?;
+    Ok(match node.value() {
+        YamlValue::Null => Value::Null,
+        YamlValue::Boolean(b) => Value::Bool(*b),
+        YamlValue::Number(YamlNumber::Integer(i)) => Value::from(*i),
+        YamlValue::Number(YamlNumber::Float(f)) => serde_json::Number::from_f64(*f)
+            .map(Value::Number)
+            .with_context(|| format!("{} at {} has no JSON equivalent", f, path))?,
+        YamlValue::String(s) => Value::String(s.as_str().to_string()),
+        YamlValue::Array(items) => Value::Array(
+            items
+                .iter()
+                .filter(|item| !matches!(item.value(), YamlValue::Comment(_)))
+                .enumerate()
+                .map(|(i, item)| json_value(item, tree, &index_path(path, i)))
+                .collect::<Result<_>>()?,
+        ),
+        YamlValue::Object(_) => {
+            let mut map = serde_json::Map::new();
+            for_each_entry(node, tree, |key, value| {
+                map.insert(
+                    key.to_string(),
+                    json_value(value, tree, &key_path(path, key))?,
+                );
+                Ok(())
+            })?;
+            Value::Object(map)
+        }
+        YamlValue::Alias(name) => bail!("Alias *{} at {} refers to another alias", name, path),
+        YamlValue::MultiDoc(_) => bail!("Nested documents at {}", path),
+        YamlValue::Comment(_) => bail!("Comment at {} is not a value", path),
+        YamlValue::Deferred(source) => json_value(&load_deferred(source)?, tree, path)?,
+    })
+}
+
+fn toml_value(node: &YamlNode, tree: &YamlTree, path: &str) -> Result<toml::Value> {
+    use toml::Value;
+
+    let node = resolve(node, tree, path)?;
+    Ok(match node.value() {
+        YamlValue::Null => bail!("null at {} has no TOML equivalent", path),
+        YamlValue::Boolean(b) => Value::Boolean(*b),
+        YamlValue::Number(YamlNumber::Integer(i)) => Value::Integer(*i),
+        YamlValue::Number(YamlNumber::Float(f)) => Value::Float(*f),
+        YamlValue::String(s) => Value::String(s.as_str().to_string()),
+        YamlValue::Array(items) => Value::Array(
+            items
+                .iter()
+                .filter(|item| !matches!(item.value(), YamlValue::Comment(_)))
+                .enumerate()
+                .map(|(i, item)| toml_value(item, tree, &index_path(path, i)))
+                .collect::<Result<_>>()?,
+        ),
+        YamlValue::Object(_) => {
+            let mut table = toml::Table::new();
+            for_each_entry(node, tree, |key, value| {
+                table.insert(
+                    key.to_string(),
+                    toml_value(value, tree, &key_path(path, key))?,
+                );
+                Ok(())
+            })?;
+            Value::Table(table)
+        }
+        YamlValue::Alias(name) => bail!("Alias *{} at {} refers to another alias", name, path),
+        YamlValue::MultiDoc(_) => bail!("Nested documents at {}", path),
+        YamlValue::Comment(_) => bail!("Comment at {} is not a value", path),
+        YamlValue::Deferred(source) => toml_value(&load_deferred(source)?, tree, path)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    fn tree(yaml: &str) -> YamlTree {
+        YamlTree::new(parse_yaml_auto(yaml).unwrap())
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path("a.json"), Format::Json);
+        assert_eq!(Format::from_path("a.TOML"), Format::Toml);
+        assert_eq!(Format::from_path("a.json.gz"), Format::Json);
+        assert_eq!(Format::from_path("a.yaml.gz"), Format::Yaml);
+        assert_eq!(Format::from_path("a.yml"), Format::Yaml);
+        assert_eq!(Format::from_path("json"), Format::Yaml);
+        assert_eq!(Format::from_extension("Pipfile"), None);
+    }
+
+    #[test]
+    fn test_sniff_format() {
+        assert_eq!(Format::sniff("{\"a\": [1, 2]}\n"), Format::Json);
+        assert_eq!(Format::sniff("  [1, 2]"), Format::Json);
+        assert_eq!(Format::sniff("[a, b]\n"), Format::Yaml);
+        assert_eq!(Format::sniff("[server]\nport = 8080\n"), Format::Toml);
+        assert_eq!(Format::sniff("name = \"x\"\n"), Format::Toml);
+        assert_eq!(Format::sniff("a: 1\nb: [x]\n"), Format::Yaml);
+        assert_eq!(Format::sniff(""), Format::Yaml);
+        assert_eq!(Format::detect("a.yaml", "{\"a\": 1}"), Format::Yaml);
+        assert_eq!(
+            Format::detect("Pipfile", "[packages]\nx = \"*\"\n"),
+            Format::Toml
+        );
+    }
+
+    #[test]
+    fn test_aliases_and_merges_are_expanded() {
+        let tree = tree("base: &b\n  x: 1\nref: *b\nchild:\n  <<: *b\n  y: 2\n");
+        let json: serde_json::Value = serde_json::from_str(&to_json(&tree).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"base": {"x": 1}, "ref": {"x": 1}, "child": {"y": 2, "x": 1}})
+        );
+    }
+
+    #[test]
+    fn test_unrepresentable_values_name_their_path() {
+        let err = to_toml(&tree("a:\n  b: [1, null]\n")).unwrap_err();
+        assert_eq!(err.to_string(), "null at .a.b[1] has no TOML equivalent");
+
+        let mut nan = tree("a: [0.5]\n");
+        *nan.get_node_mut(&[0, 0]).unwrap().value_mut() =
+            YamlValue::Number(YamlNumber::Float(f64::NAN));
+        let err = to_json(&nan).unwrap_err();
+        assert_eq!(err.to_string(), "NaN at .a[0] has no JSON equivalent");
+
+        assert!(to_toml(&tree("- 1\n")).is_err());
+        assert!(to_json(&tree("a: 1\n---\nb: 2\n")).is_err());
+    }
+}
@@ -2,9 +2,16 @@
 //!
 //! This module provides functions to load YAML documents from files or stdin,
 //! parsing them into `YamlNode` structures that can be edited by yamlquill.
+//! JSON and TOML files are converted on the way in, so they can be edited
+//! the same way; [`save_file`](crate::file::exporter::save_file) writes them
+//! back in their own format. A YAML key written twice in one mapping keeps
+//! both entries (see [`crate::document::duplicates`]).
 
+use crate::document::lazy::parse_yaml_lazy;
+use crate::document::limits::LoadLimits;
 use crate::document::parser::{parse_value, parse_yaml, parse_yaml_auto};
 use crate::document::tree::YamlTree;
+use crate::file::exporter::Format;
 use anyhow::{Context, Result};
 use std::fs;
 use std::path::Path;
@@ -12,7 +19,8 @@ use std::path::Path;
 /// Loads and parses a YAML file from the filesystem.
 ///
 /// This function reads a file from disk and parses its contents as YAML,
-/// returning a `YamlTree` structure ready for editing.
+/// returning a `YamlTree` structure ready for editing. Files named `.json`
+/// or `.toml`, or whose contents look like JSON or TOML, are converted.
 ///
 /// # Arguments
 ///
@@ -24,7 +32,7 @@ use std::path::Path;
 /// - `Ok(YamlTree)` if the file was successfully loaded and parsed
 /// - `Err(anyhow::Error)` if:
 ///   - The file could not be read (doesn't exist, permission denied, etc.)
-///   - The file contents are not valid YAML
+///   - The file contents are not valid YAML (or JSON or TOML)
 ///
 /// # Examples
 ///
@@ -44,21 +52,75 @@ use std::path::Path;
 /// - The file contents are not valid YAML
 pub fn load_yaml_file<P: AsRef<Path>>(path: P) -> Result<YamlTree> {
     let path = path.as_ref();
+    let limits = LoadLimits::default();
+    check_file_size(path, &limits)?;
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+
+    parse_file_contents(path, contents, usize::MAX, &limits)
+}
+
+/// Loads a file like [`load_yaml_file`], parsing YAML files of at least
+/// `lazy_load_threshold` bytes lazily and checking files against `limits`
+/// instead of the default ones.
+///
+/// Only the top level of a lazily loaded file is parsed up front; each
+/// nested section is parsed the first time it is expanded (see
+/// [`crate::document::lazy`]). Lazily loaded files are only checked
+/// against the size limit.
+///
+/// # Examples
+///
+/// ```no_run
+/// use yamlquill::document::limits::LoadLimits;
+/// use yamlquill::file::loader::load_yaml_file_lazy;
+/// use std::path::Path;
+///
+/// // Files of 100MB or more load lazily
+/// let tree = load_yaml_file_lazy(Path::new("dump.yaml"), 100 << 20, &LoadLimits::default())
+///     .unwrap();
+/// ```
+pub fn load_yaml_file_lazy<P: AsRef<Path>>(
+    path: P,
+    lazy_load_threshold: usize,
+    limits: &LoadLimits,
+) -> Result<YamlTree> {
+    let path = path.as_ref();
+    check_file_size(path, limits)?;
     let contents = fs::read_to_string(path)
         .with_context(|| format!("Failed to read file: {}", path.display()))?;
 
-    let node = parse_yaml_auto(&contents)?;
-    Ok(YamlTree::with_source(node, Some(contents)))
+    parse_file_contents(path, contents, lazy_load_threshold, limits)
+}
+
+/// Refuses files over the size limit before reading them.
+fn check_file_size(path: &Path, limits: &LoadLimits) -> Result<()> {
+    match fs::metadata(path) {
+        Ok(metadata) => limits
+            .check_size(metadata.len())
+            .map_err(|e| refusal(path, e)),
+        // Reading reports a missing or unreadable file
+        Err(_) => Ok(()),
+    }
+}
+
+/// Explains why a file isn't loaded, leading with its name.
+fn refusal(path: &Path, error: anyhow::Error) -> anyhow::Error {
+    anyhow::anyhow!("Not loading {}: {}", path.display(), error)
 }
 
 /// Loads and parses a YAML file with automatic gzip decompression.
 ///
 /// This function reads a file from disk and parses its contents as YAML.
 /// If the file has a `.gz` extension, it will automatically decompress it first.
+/// The file is checked against `limits`; a compressed file is decompressed
+/// no further than the size limit, so a small file that inflates to a huge
+/// one is refused without being held in memory.
 ///
 /// # Arguments
 ///
 /// * `path` - The path to the YAML file to load (may be gzip-compressed)
+/// * `limits` - The limits the file's contents are checked against
 ///
 /// # Returns
 ///
@@ -71,14 +133,15 @@ pub fn load_yaml_file<P: AsRef<Path>>(path: P) -> Result<YamlTree> {
 /// # Examples
 ///
 /// ```no_run
+/// use yamlquill::document::limits::LoadLimits;
 /// use yamlquill::file::loader::load_yaml_file_auto;
 /// use std::path::Path;
 ///
 /// // Load regular YAML file
-/// let tree = load_yaml_file_auto(Path::new("config.yaml")).unwrap();
+/// let tree = load_yaml_file_auto(Path::new("config.yaml"), &LoadLimits::default()).unwrap();
 ///
 /// // Load gzip-compressed YAML file
-/// let tree = load_yaml_file_auto(Path::new("config.yaml.gz")).unwrap();
+/// let tree = load_yaml_file_auto(Path::new("config.yaml.gz"), &LoadLimits::default()).unwrap();
 /// ```
 ///
 /// # Errors
@@ -87,29 +150,128 @@ pub fn load_yaml_file<P: AsRef<Path>>(path: P) -> Result<YamlTree> {
 /// - The file path does not exist
 /// - The file cannot be read (permissions, etc.)
 /// - The gzip decompression fails (for .gz files)
+/// - The file, or what it decompresses to, is over the size limit
 /// - The file contents are not valid YAML
-pub fn load_yaml_file_auto<P: AsRef<Path>>(path: P) -> Result<YamlTree> {
+pub fn load_yaml_file_auto<P: AsRef<Path>>(path: P, limits: &LoadLimits) -> Result<YamlTree> {
     use flate2::read::GzDecoder;
     use std::io::Read;
 
     let path = path.as_ref();
+    check_file_size(path, limits)?;
     let contents = if path.extension().and_then(|s| s.to_str()) == Some("gz") {
-        // Decompress gzip file
+        // Decompress gzip file, one byte past the limit at most
         let file = fs::File::open(path)
             .with_context(|| format!("Failed to open gzip file: {}", path.display()))?;
-        let mut decoder = GzDecoder::new(file);
-        let mut contents = String::new();
-        decoder
-            .read_to_string(&mut contents)
+        let limit = match limits.max_file_size_mb {
+            0 => u64::MAX,
+            mb => mb as u64 * 1024 * 1024,
+        };
+        let mut data = Vec::new();
+        GzDecoder::new(file)
+            .take(limit.saturating_add(1))
+            .read_to_end(&mut data)
             .context("Failed to decompress gzip file")?;
-        contents
+        if data.len() as u64 > limit {
+            return Err(refusal(
+                path,
+                anyhow::anyhow!(
+                    "it decompresses to more than {} MB (max_file_size_mb)",
+                    limits.max_file_size_mb
+                ),
+            ));
+        }
+        String::from_utf8(data).context("Failed to decompress gzip file")?
     } else {
         fs::read_to_string(path)
             .with_context(|| format!("Failed to read file: {}", path.display()))?
     };
 
-    let node = parse_yaml_auto(&contents)?;
-    Ok(YamlTree::with_source(node, Some(contents)))
+    parse_file_contents(path, contents, usize::MAX, limits)
+}
+
+/// Parses a file's contents in the format [`Format::detect`] finds.
+///
+/// Only YAML keeps its source text for format-preserving saves; JSON and
+/// TOML have nothing to splice into a YAML file. YAML of at least
+/// `lazy_load_threshold` bytes is parsed lazily; smaller YAML is checked
+/// against `limits` first.
+fn parse_file_contents(
+    path: &Path,
+    contents: String,
+    lazy_load_threshold: usize,
+    limits: &LoadLimits,
+) -> Result<YamlTree> {
+    limits
+        .check_size(contents.len() as u64)
+        .map_err(|e| refusal(path, e))?;
+    match Format::detect(path, &contents) {
+        Format::Yaml if contents.len() >= lazy_load_threshold => {
+            let node = parse_yaml_lazy(&contents)?;
+            Ok(YamlTree::with_source(node, Some(contents)))
+        }
+        Format::Yaml => {
+            limits.check(&contents).map_err(|e| refusal(path, e))?;
+            let node = parse_yaml_auto(&contents)?;
+            Ok(YamlTree::with_source(node, Some(contents)))
+        }
+        Format::Json => {
+            let value: serde_json::Value = serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse JSON: {}", path.display()))?;
+            Ok(YamlTree::new(parse_value(&json_to_yaml(value))))
+        }
+        Format::Toml => {
+            let table: toml::Table = toml::from_str(&contents)
+                .with_context(|| format!("Failed to parse TOML: {}", path.display()))?;
+            Ok(YamlTree::new(parse_value(&toml_to_yaml(
+                toml::Value::Table(table),
+            ))))
+        }
+    }
+}
+
+fn json_to_yaml(value: serde_json::Value) -> serde_yaml::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => serde_yaml::Value::Null,
+        Value::Bool(b) => serde_yaml::Value::Bool(b),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => serde_yaml::Value::from(i),
+            (None, Some(u)) => serde_yaml::Value::from(u),
+            _ => serde_yaml::Value::from(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => serde_yaml::Value::String(s),
+        Value::Array(items) => {
+            serde_yaml::Value::Sequence(items.into_iter().map(json_to_yaml).collect())
+        }
+        Value::Object(map) => serde_yaml::Value::Mapping(
+            map.into_iter()
+                .map(|(k, v)| (serde_yaml::Value::String(k), json_to_yaml(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Converts TOML to YAML values; dates and times become strings.
+fn toml_to_yaml(value: toml::Value) -> serde_yaml::Value {
+    use toml::Value;
+
+    match value {
+        Value::String(s) => serde_yaml::Value::String(s),
+        Value::Integer(i) => serde_yaml::Value::from(i),
+        Value::Float(f) => serde_yaml::Value::from(f),
+        Value::Boolean(b) => serde_yaml::Value::Bool(b),
+        Value::Datetime(d) => serde_yaml::Value::String(d.to_string()),
+        Value::Array(items) => {
+            serde_yaml::Value::Sequence(items.into_iter().map(toml_to_yaml).collect())
+        }
+        Value::Table(table) => serde_yaml::Value::Mapping(
+            table
+                .into_iter()
+                .map(|(k, v)| (serde_yaml::Value::String(k), toml_to_yaml(v)))
+                .collect(),
+        ),
+    }
 }
 
 /// Helper function to parse multi-document YAML content (newline-delimited JSON).
@@ -450,7 +612,7 @@ valid: false"#;
         encoder.finish().unwrap();
 
         // Load and verify
-        let tree = load_yaml_file_auto(&gz_path).unwrap();
+        let tree = load_yaml_file_auto(&gz_path, &LoadLimits::default()).unwrap();
 
         // Verify structure
         if let YamlValue::Object(entries) = tree.root().value() {
@@ -460,6 +622,38 @@ valid: false"#;
         }
     }
 
+    #[test]
+    fn test_gzipped_file_over_the_limit_is_refused() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        // Two megabytes of comment lines compress to a few kilobytes
+        let dir = tempfile::TempDir::new().unwrap();
+        let gz_path = dir.path().join("bomb.yaml.gz");
+        let mut encoder = GzEncoder::new(fs::File::create(&gz_path).unwrap(), Compression::best());
+        for _ in 0..(2 << 20) / 16 {
+            encoder.write_all(b"# padding ....\n").unwrap();
+        }
+        encoder.write_all(b"a: 1\n").unwrap();
+        encoder.finish().unwrap();
+        assert!(fs::metadata(&gz_path).unwrap().len() < 1 << 20);
+
+        let limits = LoadLimits {
+            max_file_size_mb: 1,
+            ..LoadLimits::default()
+        };
+        let error = load_yaml_file_auto(&gz_path, &limits).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("more than 1 MB (max_file_size_mb)"),
+            "{}",
+            error
+        );
+        assert!(load_yaml_file_auto(&gz_path, &LoadLimits::default()).is_ok());
+    }
+
     #[test]
     #[ignore = "YAMLL format auto-detection not implemented (use parse_yamll_content directly)"]
     fn test_load_gzipped_jsonl_file() {
@@ -491,4 +685,38 @@ valid: false"#;
             panic!("Expected MultiDoc");
         }
     }
+
+    #[test]
+    fn test_load_json_and_toml_files() {
+        use crate::file::saver::yaml_text;
+        use tempfile::TempDir;
+
+        let dir = TempDir::new().unwrap();
+        let json = dir.path().join("app.json");
+        fs::write(&json, r#"{"name": "web", "ports": [80, 443], "tls": null}"#).unwrap();
+        let tree = load_yaml_file(&json).unwrap();
+        assert!(tree.original_source().is_none());
+        assert_eq!(
+            yaml_text(&tree).unwrap().trim_end(),
+            "name: web\nports:\n- 80\n- 443\ntls: null"
+        );
+
+        // No extension: the contents give it away
+        let toml = dir.path().join("Pipfile");
+        fs::write(
+            &toml,
+            "[source]\nurl = \"https://pypi.org\"\n\n[packages]\nflask = \"*\"\n",
+        )
+        .unwrap();
+        let tree = load_yaml_file(&toml).unwrap();
+        assert_eq!(
+            yaml_text(&tree).unwrap().trim_end(),
+            "source:\n  url: https://pypi.org\npackages:\n  flask: '*'"
+        );
+
+        let broken = dir.path().join("broken.json");
+        fs::write(&broken, "{\"a\": ").unwrap();
+        let err = load_yaml_file(&broken).unwrap_err();
+        assert!(err.to_string().starts_with("Failed to parse JSON"));
+    }
 }
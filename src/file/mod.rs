@@ -3,5 +3,8 @@
 //! This module provides functionality to load JSON files from disk or stdin,
 //! and save JSON trees back to files with atomic write operations and optional backups.
 
+pub mod exporter;
 pub mod loader;
 pub mod saver;
+pub mod serializer;
+pub mod store;
@@ -4,27 +4,35 @@
 //! atomic write operations and optional backup creation.
 
 use crate::config::Config;
-use crate::document::node::{CommentNode, CommentPosition, YamlNode, YamlNumber, YamlValue};
+use crate::document::duplicates::{is_duplicate_key, restore_keys, written_key};
+use crate::document::lazy::load_deferred;
+use crate::document::node::{
+    CollectionStyle, CommentNode, CommentPosition, YamlNode, YamlNumber, YamlValue,
+};
 use crate::document::parser::scan_for_comments;
 use crate::document::tree::YamlTree;
 use anyhow::{Context, Result};
 use indexmap::IndexMap;
 use serde_yaml::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
 /// Checks if any non-comment node in the tree has been modified.
 ///
 /// Walks the tree depth-first, skipping comment nodes. Returns `true` if any
-/// non-comment leaf node has `is_modified() == true`. Container nodes are not
-/// checked directly because they may be marked modified only due to comment
-/// injection during parsing.
+/// non-comment node has `is_modified() == true`. A mapping or sequence is
+/// marked modified when a child is inserted or deleted, which is the only
+/// trace a deletion leaves.
 fn has_non_comment_modifications(node: &YamlNode) -> bool {
     match node.value() {
         YamlValue::Comment(_) => false,
-        YamlValue::Object(entries) => entries.values().any(has_non_comment_modifications),
-        YamlValue::Array(elements) => elements.iter().any(has_non_comment_modifications),
+        YamlValue::Object(entries) => {
+            node.is_modified() || entries.values().any(has_non_comment_modifications)
+        }
+        YamlValue::Array(elements) => {
+            node.is_modified() || elements.iter().any(has_non_comment_modifications)
+        }
         YamlValue::MultiDoc(docs) => docs.iter().any(has_non_comment_modifications),
         _ => node.is_modified(),
     }
@@ -98,6 +106,31 @@ fn collect_tree_comments_recursive(node: &YamlNode, comments: &mut Vec<(String,
     }
 }
 
+/// Checks that nothing in a subtree was edited since it was read from
+/// `original`: no node was modified, and every comment was read from it and
+/// still says what it said there. Deleting a comment marks its parent
+/// modified, so a deleted comment counts as an edit too.
+fn is_untouched(original: &str, node: &YamlNode) -> bool {
+    if needs_reserialization(node) {
+        return false;
+    }
+    let original_comments = scan_for_comments(original);
+    let original_by_line: HashMap<usize, &str> = original_comments
+        .iter()
+        .map(|c| (c.line, c.content.trim()))
+        .collect();
+    collect_tree_comments_with_lines(node)
+        .iter()
+        .all(|(content, line)| {
+            line.and_then(|line| original_by_line.get(&line)) == Some(&content.as_str())
+        })
+}
+
+/// Writes `text` with CRLF line breaks, for files that were read with them.
+fn crlf_line_breaks(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\n', "\r\n")
+}
+
 /// Applies comment edits to the original source text.
 ///
 /// Uses source line numbers stored in comment nodes to map tree comments
@@ -256,26 +289,15 @@ fn parse_section_ranges(original: &str) -> Vec<SectionRange> {
                 end_line: 0, // will be filled in
             });
             pending_gap_start = None;
-        } else if !sections.is_empty() {
-            let trimmed = line.trim();
-            // Check if this line is inter-section gap content (blank or indent-0 comment)
-            let is_gap = trimmed.is_empty()
-                || (trimmed.starts_with('#') && !line.starts_with(' ') && !line.starts_with('\t'));
-
-            if is_gap && pending_gap_start.is_none() {
-                // Check if remaining lines before next key are all gap lines
-                // If so, this starts a gap belonging to the next section
-                let all_gap = lines[i..]
-                    .iter()
-                    .take_while(|l| extract_top_level_key(l).is_none())
-                    .all(|l| {
-                        let t = l.trim();
-                        t.is_empty()
-                            || (t.starts_with('#') && !l.starts_with(' ') && !l.starts_with('\t'))
-                    });
-                if all_gap {
-                    pending_gap_start = Some(i);
-                }
+        } else if !sections.is_empty() && is_gap_line(line) && pending_gap_start.is_none() {
+            // Check if remaining lines before next key are all gap lines
+            // If so, this starts a gap belonging to the next section
+            let all_gap = lines[i..]
+                .iter()
+                .take_while(|l| extract_top_level_key(l).is_none())
+                .all(|l| is_gap_line(l));
+            if all_gap {
+                pending_gap_start = Some(i);
             }
         }
     }
@@ -292,6 +314,44 @@ fn parse_section_ranges(original: &str) -> Vec<SectionRange> {
     sections
 }
 
+/// Checks if a line is inter-section gap content (blank or indent-0 comment).
+fn is_gap_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty()
+        || (trimmed.starts_with('#') && !line.starts_with(' ') && !line.starts_with('\t'))
+}
+
+/// Finds the gap comments that were deleted from the tree.
+///
+/// Returns the indexes of the indent-0 comment lines in the preamble, in the
+/// gaps before sections and after the last one, that no comment in the tree
+/// was read from. Deleting a comment marks its parent modified, but these
+/// lines are copied from the original around the sections, so they have to
+/// be left out by hand.
+fn deleted_gap_comments(
+    lines: &[&str],
+    sections: &[SectionRange],
+    root: &YamlNode,
+) -> HashSet<usize> {
+    let kept: HashSet<usize> = collect_tree_comments_with_lines(root)
+        .into_iter()
+        .filter_map(|(_, line)| line)
+        .collect();
+    // A section's own lines run from its key to its last non-gap line
+    let mut in_section = vec![false; lines.len()];
+    for section in sections {
+        let mut content =
+            (section.start_line..section.end_line).filter(|&i| !is_gap_line(lines[i]));
+        if let Some(first) = content.next() {
+            let last = content.next_back().unwrap_or(first);
+            in_section[first..=last].fill(true);
+        }
+    }
+    (0..lines.len())
+        .filter(|&i| !in_section[i] && lines[i].starts_with('#') && !kept.contains(&(i + 1)))
+        .collect()
+}
+
 /// Re-serializes a single modified top-level key-value pair.
 ///
 /// Wraps the key+value in a one-entry mapping, serializes via serde_yaml,
@@ -302,14 +362,8 @@ fn serialize_section(key: &str, node: &YamlNode, tree: &YamlTree) -> Result<Stri
     wrapper_map.insert(key.to_string(), node.clone());
     let wrapper_node = YamlNode::new(YamlValue::Object(wrapper_map));
 
-    // Convert to serde_yaml value
-    let value = convert_to_serde_value(&wrapper_node, tree)?;
-
-    // Serialize
-    let yaml_str = serde_yaml::to_string(&value).context("Failed to serialize section")?;
-
-    // Inject comments
-    let yaml_with_comments = inject_comments_structural(&yaml_str, &wrapper_node, tree);
+    // Serialize and inject comments
+    let yaml_with_comments = serialize_with_comments(&wrapper_node, tree)?;
 
     // Strip trailing newline to avoid double-newlines when joining sections
     let result = yaml_with_comments.trim_end_matches('\n').to_string();
@@ -324,7 +378,7 @@ fn collect_modified_leaves<'a>(
     results: &mut Vec<(Vec<String>, &'a YamlNode)>,
 ) {
     match node.value() {
-        YamlValue::Comment(_) | YamlValue::Alias(_) => {}
+        YamlValue::Comment(_) => {}
         YamlValue::Object(entries) => {
             for (key, value) in entries {
                 if key.starts_with("__comment_") {
@@ -355,8 +409,23 @@ fn collect_modified_leaves<'a>(
     }
 }
 
-/// Formats a node's value as a YAML scalar for inline replacement.
+/// Formats a node's value as a YAML scalar for inline replacement, led by
+/// its tag and anchor if it has them. An alias is written as `*name`.
 fn format_node_value_for_yaml(node: &YamlNode) -> String {
+    if let YamlValue::Alias(name) = node.value() {
+        return format!("*{}", name);
+    }
+    let mut value = format_scalar_for_yaml(node);
+    if let Some(anchor) = node.anchor() {
+        value = format!("&{} {}", anchor, value);
+    }
+    match node.tag() {
+        Some(tag) => format!("{} {}", tag, value),
+        None => value,
+    }
+}
+
+fn format_scalar_for_yaml(node: &YamlNode) -> String {
     match node.value() {
         YamlValue::Null => "null".to_string(),
         YamlValue::Boolean(b) => if *b { "true" } else { "false" }.to_string(),
@@ -748,6 +817,10 @@ fn patch_section_from_source(
         if matches!(mod_node.value(), YamlValue::Object(_) | YamlValue::Array(_)) {
             return None;
         }
+        // Block scalars span lines, so they can't be patched in place either
+        if matches!(mod_node.value(), YamlValue::String(s) if s.is_multiline()) {
+            return None;
+        }
     }
 
     // Separate modifications into value edits and new key insertions
@@ -791,7 +864,7 @@ fn patch_section_from_source(
     all_insertions.extend(comment_insertions);
 
     // Sort by position descending so later inserts don't shift earlier positions
-    all_insertions.sort_by(|a, b| b.0.cmp(&a.0));
+    all_insertions.sort_by_key(|b| std::cmp::Reverse(b.0));
     for (pos, line) in all_insertions {
         section_lines.insert(pos, line);
     }
@@ -821,15 +894,26 @@ fn save_with_section_preservation(original: &str, tree: &YamlTree) -> Option<Str
     let section_map: HashMap<&str, &SectionRange> =
         sections.iter().map(|s| (s.key.as_str(), s)).collect();
 
+    // Sections are found by key, so keys written twice need the slow path
+    if section_map.len() < sections.len() || root_entries.keys().any(|k| is_duplicate_key(k)) {
+        return None;
+    }
+
     // Find preamble: lines before the first section
     let preamble_end = sections.first().map(|s| s.start_line).unwrap_or(0);
 
+    // Comments deleted from around the sections stay out of the copied lines
+    let deleted = deleted_gap_comments(&lines, &sections, tree.root());
+    let original_lines = |range: std::ops::Range<usize>| {
+        range
+            .filter(|i| !deleted.contains(i))
+            .map(|i| lines[i].to_string())
+    };
+
     let mut result_parts: Vec<String> = Vec::new();
 
     // Emit preamble (lines before first section)
-    for line in lines.iter().take(preamble_end) {
-        result_parts.push(line.to_string());
-    }
+    result_parts.extend(original_lines(0..preamble_end));
 
     // Walk root Object entries in tree order
     for (key, value) in root_entries {
@@ -863,9 +947,7 @@ fn save_with_section_preservation(original: &str, tree: &YamlTree) -> Option<Str
         if let Some(section) = section_map.get(key.as_str()) {
             if !needs_reserialization(value) {
                 // Unmodified section: emit original lines verbatim
-                for line in lines.iter().take(section.end_line).skip(section.start_line) {
-                    result_parts.push(line.to_string());
-                }
+                result_parts.extend(original_lines(section.start_line..section.end_line));
             } else {
                 // Modified section: emit any leading gap lines (blank/comment),
                 // then re-serialize
@@ -879,11 +961,10 @@ fn save_with_section_preservation(original: &str, tree: &YamlTree) -> Option<Str
                     .map(|(i, _)| i)
                     .unwrap_or(section.start_line);
                 // Emit leading gap lines (comments/blank lines before the key)
-                for line in lines.iter().take(key_line).skip(section.start_line) {
-                    result_parts.push(line.to_string());
-                }
-                // Try anchor-preserving patch for sections with anchors/aliases
-                let patched = if section_has_anchors_or_aliases(value) {
+                result_parts.extend(original_lines(section.start_line..key_line));
+                // Try anchor-preserving patch for sections with anchors/aliases.
+                // Line patching can't rewrite flow collections.
+                let patched = if section_has_anchors_or_aliases(value) && !has_flow_style(value) {
                     patch_section_from_source(value, &lines, key_line, section.end_line)
                 } else {
                     None
@@ -923,6 +1004,101 @@ fn save_with_section_preservation(original: &str, tree: &YamlTree) -> Option<Str
     Some(output)
 }
 
+/// Splits multi-document source into the text of each document, from its
+/// `---` line to the next one.
+///
+/// Comments and directives before the first `---` go with the first
+/// document, as does everything before it when the first document has no
+/// `---` of its own.
+fn document_ranges(original: &str) -> Vec<std::ops::Range<usize>> {
+    let mut starts = Vec::new();
+    let mut implicit_first = false;
+    let mut offset = 0;
+    for line in original.split_inclusive('\n') {
+        let text = line.trim_end();
+        if is_document_start(text) {
+            starts.push(if starts.is_empty() && !implicit_first {
+                0
+            } else {
+                offset
+            });
+        } else if starts.is_empty()
+            && !text.is_empty()
+            && !text.starts_with('#')
+            && !text.starts_with('%')
+        {
+            implicit_first = true;
+        }
+        offset += line.len();
+    }
+    if implicit_first {
+        starts.insert(0, 0);
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &start)| start..starts.get(i + 1).copied().unwrap_or(original.len()))
+        .collect()
+}
+
+/// Checks if a line starts a document (`---`, maybe followed by content).
+fn is_document_start(line: &str) -> bool {
+    line.strip_prefix("---")
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+}
+
+/// Saves a multi-document stream, keeping the original text of each
+/// document that wasn't edited.
+///
+/// An edited document is re-serialized after the lines that start it
+/// (directives, comments and a bare `---`). Returns `None` if the source
+/// can't be split into as many documents as the tree has.
+fn save_with_document_preservation(original: &str, tree: &YamlTree) -> Option<String> {
+    let YamlValue::MultiDoc(documents) = tree.root().value() else {
+        return None;
+    };
+    let ranges = document_ranges(original);
+    if ranges.len() != documents.len() {
+        return None;
+    }
+
+    let mut output = String::new();
+    for (i, (node, range)) in documents.iter().zip(ranges).enumerate() {
+        let source = &original[range];
+        if is_untouched(original, node) {
+            output.push_str(source);
+            continue;
+        }
+        // Keep the lines up to the `---`, and the `---` line itself unless
+        // content follows it there; the first document may have none
+        let mut head = String::new();
+        let mut started = false;
+        for line in source.split_inclusive('\n') {
+            let text = line.trim_end();
+            if is_document_start(text) {
+                let rest = text[3..].trim_start();
+                if rest.is_empty() || rest.starts_with('#') {
+                    head.push_str(line);
+                } else {
+                    head.push_str("---\n");
+                }
+                started = true;
+                break;
+            }
+            head.push_str(line);
+        }
+        if !started {
+            head.clear();
+        }
+        if !head.ends_with('\n') && !head.is_empty() {
+            head.push('\n');
+        }
+        output.push_str(&head);
+        output.push_str(&document_text(node, tree, i).ok()?);
+    }
+    Some(output)
+}
+
 /// Converts a YamlNode tree to a serde_yaml::Value.
 ///
 /// This function recursively traverses the YamlNode structure and converts
@@ -952,7 +1128,7 @@ fn save_with_section_preservation(original: &str, tree: &YamlTree) -> Option<Str
 /// # V1 Limitations
 ///
 /// - Alias nodes return an error (Phase 3 will add support)
-/// - MultiDoc nodes return an error (use save_yamll instead)
+/// - MultiDoc nodes return an error (use yaml_text instead)
 /// - All strings output as plain style (Phase 4 will preserve literal/folded)
 fn convert_to_serde_value(node: &YamlNode, tree: &YamlTree) -> Result<Value> {
     let value = match node.value() {
@@ -1013,7 +1189,7 @@ fn convert_to_serde_value(node: &YamlNode, tree: &YamlTree) -> Result<Value> {
         }
 
         YamlValue::MultiDoc(_) => {
-            anyhow::bail!("Cannot serialize MultiDoc nodes - use save_yamll instead")
+            anyhow::bail!("Cannot serialize MultiDoc nodes - use yaml_text instead")
         }
 
         YamlValue::Comment(c) => {
@@ -1021,9 +1197,356 @@ fn convert_to_serde_value(node: &YamlNode, tree: &YamlTree) -> Result<Value> {
             // For now, skip them in the structure
             Value::String(format!("# {}", c.content))
         }
+
+        YamlValue::Deferred(source) => {
+            return convert_to_serde_value(&load_deferred(source)?, tree);
+        }
+    };
+
+    Ok(match node.tag() {
+        Some(tag) => Value::Tagged(Box::new(serde_yaml::value::TaggedValue {
+            tag: serde_yaml::value::Tag::new(tag_marker(tag)),
+            value,
+        })),
+        None => value,
+    })
+}
+
+const TAG_MARKER: &str = "__yamlquill_tag_";
+
+/// Returns the name serde_yaml is given for `tag`.
+///
+/// serde_yaml writes every tag as `!name`, so `!!str` would come out as
+/// `!str`. The tag is hex-encoded into a plain name instead and put back by
+/// [`restore_tags`] once the text is written.
+fn tag_marker(tag: &str) -> String {
+    let hex: String = tag.bytes().map(|b| format!("{:02x}", b)).collect();
+    format!("{}{}__", TAG_MARKER, hex)
+}
+
+/// Replaces the tag markers in serialized text with the tags they encode.
+fn restore_tags(text: &str) -> String {
+    let marker = format!("!{}", TAG_MARKER);
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(&marker) {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + marker.len()..];
+        let decoded = after.find("__").and_then(|end| {
+            let hex = &after[..end];
+            let bytes = (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                .collect::<Option<Vec<u8>>>()?;
+            Some((String::from_utf8(bytes).ok()?, end + 2))
+        });
+        match decoded {
+            Some((tag, len)) => {
+                output.push_str(&tag);
+                rest = &after[len..];
+            }
+            None => {
+                output.push_str(&marker);
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Serializes a subtree as YAML, keeping its comments and collection styles.
+///
+/// Aliases are resolved against `tree`. The text ends with a line break.
+pub fn serialize_subtree(node: &YamlNode, tree: &YamlTree) -> Result<String> {
+    let mut text = serialize_with_comments(node, tree)?;
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+/// Serializes a node with serde_yaml and merges its comments back in.
+///
+/// serde_yaml only writes block collections and picks its own scalar style,
+/// so flow-style collections and literal/folded strings are swapped for
+/// placeholder scalars before serializing and written out afterwards.
+fn serialize_with_comments(node: &YamlNode, tree: &YamlTree) -> Result<String> {
+    let mut placeholders = Vec::new();
+    let replaced;
+    let node = if has_styled_nodes(node) {
+        replaced = replace_styled_nodes(node, tree, &mut placeholders)?;
+        &replaced
+    } else {
+        node
     };
 
-    Ok(value)
+    let value = convert_to_serde_value(node, tree)?;
+    let yaml_str = serde_yaml::to_string(&value).context("Failed to serialize YAML")?;
+    let mut output = inject_comments_structural(&yaml_str, node, tree);
+    for (i, placeholder) in placeholders.iter().enumerate() {
+        output = fill_placeholder(&output, &placeholder_name(i), placeholder);
+    }
+    Ok(restore_keys(&restore_tags(&output)))
+}
+
+/// Text written in place of a placeholder scalar.
+enum Placeholder {
+    /// A flow collection, written inline
+    Flow(String),
+    /// A block scalar header (`|`, `>-`, ...) and its lines, indented below it
+    Block(String, Vec<String>),
+}
+
+/// Checks if any collection in the subtree is in flow style.
+fn has_flow_style(node: &YamlNode) -> bool {
+    match node.value() {
+        YamlValue::Object(entries) => {
+            node.style() == CollectionStyle::Flow || entries.values().any(has_flow_style)
+        }
+        YamlValue::Array(elements) | YamlValue::MultiDoc(elements) => {
+            node.style() == CollectionStyle::Flow || elements.iter().any(has_flow_style)
+        }
+        _ => false,
+    }
+}
+
+/// Checks if the subtree holds flow collections, literal/folded strings,
+/// anchored scalars or aliases.
+fn has_styled_nodes(node: &YamlNode) -> bool {
+    match node.value() {
+        YamlValue::Object(entries) => {
+            node.style() == CollectionStyle::Flow || entries.values().any(has_styled_nodes)
+        }
+        YamlValue::Array(elements) | YamlValue::MultiDoc(elements) => {
+            node.style() == CollectionStyle::Flow || elements.iter().any(has_styled_nodes)
+        }
+        YamlValue::String(s) => block_scalar(s).is_some() || node.anchor().is_some(),
+        YamlValue::Alias(_) => true,
+        YamlValue::Comment(_) | YamlValue::Deferred(_) => false,
+        _ => node.anchor().is_some(),
+    }
+}
+
+fn placeholder_name(index: usize) -> String {
+    format!("__yamlquill_styled_{}__", index)
+}
+
+/// Returns a copy of `node` with each outermost flow-style collection, each
+/// literal/folded string, each anchored scalar and each alias of a scalar
+/// replaced by a placeholder scalar, pushing what to write in its place onto
+/// `placeholders`.
+///
+/// Aliases of collections are left to be resolved into copies.
+fn replace_styled_nodes(
+    node: &YamlNode,
+    tree: &YamlTree,
+    placeholders: &mut Vec<Placeholder>,
+) -> Result<YamlNode> {
+    let anchored = |text: String| match node.anchor() {
+        Some(anchor) => format!("&{} {}", anchor, text),
+        None => text,
+    };
+    let placeholder = match node.value() {
+        value if value.is_container() && node.style() == CollectionStyle::Flow => {
+            // The node's own tag stays on the placeholder, ahead of the text
+            let text = match convert_to_serde_value(node, tree)? {
+                Value::Tagged(tagged) => flow_text(&tagged.value),
+                value => flow_text(&value),
+            };
+            Some(Placeholder::Flow(text))
+        }
+        YamlValue::String(s) => match block_scalar(s) {
+            Some((header, lines)) => Some(Placeholder::Block(anchored(header), lines)),
+            None if node.anchor().is_some() => {
+                Some(Placeholder::Flow(anchored(format_scalar_for_yaml(node))))
+            }
+            None => None,
+        },
+        YamlValue::Alias(name) => {
+            let scalar = tree
+                .anchor_registry()
+                .get_anchor_path(name)
+                .and_then(|path| tree.get_node(path))
+                .is_some_and(|anchor| !anchor.value().is_container());
+            scalar.then(|| Placeholder::Flow(format!("*{}", name)))
+        }
+        YamlValue::Null | YamlValue::Boolean(_) | YamlValue::Number(_)
+            if node.anchor().is_some() =>
+        {
+            Some(Placeholder::Flow(anchored(format_scalar_for_yaml(node))))
+        }
+        _ => None,
+    };
+    if let Some(placeholder) = placeholder {
+        placeholders.push(placeholder);
+        let mut replaced = YamlNode::new(YamlValue::String(
+            crate::document::node::YamlString::Plain(placeholder_name(placeholders.len() - 1)),
+        ));
+        replaced.anchor = node.anchor.clone();
+        replaced.tag = node.tag.clone();
+        return Ok(replaced);
+    }
+    let replace_all = |children: &[YamlNode], placeholders: &mut Vec<Placeholder>| {
+        children
+            .iter()
+            .map(|child| replace_styled_nodes(child, tree, placeholders))
+            .collect::<Result<Vec<_>>>()
+    };
+    let value = match node.value() {
+        YamlValue::Object(entries) => YamlValue::Object(
+            entries
+                .iter()
+                .map(|(key, child)| {
+                    Ok((
+                        key.clone(),
+                        replace_styled_nodes(child, tree, placeholders)?,
+                    ))
+                })
+                .collect::<Result<_>>()?,
+        ),
+        YamlValue::Array(elements) => YamlValue::Array(replace_all(elements, placeholders)?),
+        YamlValue::MultiDoc(elements) => YamlValue::MultiDoc(replace_all(elements, placeholders)?),
+        _ => return Ok(node.clone()),
+    };
+    Ok(YamlNode {
+        value,
+        metadata: node.metadata.clone(),
+        anchor: node.anchor.clone(),
+        alias_target: node.alias_target.clone(),
+        tag: node.tag.clone(),
+        original_formatting: node.original_formatting.clone(),
+        id: node.id,
+    })
+}
+
+/// Replaces the placeholder scalar `name` in `output`.
+///
+/// Block scalar lines are indented two spaces past the key (or sequence
+/// item) that holds the placeholder.
+fn fill_placeholder(output: &str, name: &str, placeholder: &Placeholder) -> String {
+    let Some(pos) = output.find(name) else {
+        return output.to_string();
+    };
+    let (header, lines) = match placeholder {
+        Placeholder::Flow(text) => return output.replacen(name, text, 1),
+        Placeholder::Block(header, lines) => (header, lines),
+    };
+
+    let line_start = output[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let prefix = &output[line_start..pos];
+    let mut column = prefix.len() - prefix.trim_start_matches(' ').len();
+    while prefix[column..].starts_with("- ") {
+        column += 2;
+    }
+    let indent = if prefix.trim_end().ends_with(':') || prefix.is_empty() {
+        column + 2
+    } else {
+        column
+    };
+
+    // Anything after the placeholder (an inline comment) stays on the header line
+    let after = pos + name.len();
+    let line_end = output[after..]
+        .find('\n')
+        .map_or(output.len(), |i| after + i);
+    let mut filled = format!("{}{}{}", &output[..pos], header, &output[after..line_end]);
+    for line in lines {
+        filled.push('\n');
+        if !line.is_empty() {
+            filled.push_str(&" ".repeat(indent));
+            filled.push_str(line);
+        }
+    }
+    filled.push_str(&output[line_end..]);
+    filled
+}
+
+/// Returns the header and lines to write a literal or folded string as a
+/// block scalar, or `None` for plain strings and text a block scalar can't
+/// hold without an indentation indicator.
+fn block_scalar(s: &crate::document::node::YamlString) -> Option<(String, Vec<String>)> {
+    use crate::document::node::YamlString;
+
+    let (indicator, text) = match s {
+        YamlString::Plain(_) => return None,
+        YamlString::Literal(text) => ('|', text),
+        YamlString::Folded(text) => ('>', text),
+    };
+    let body = text.trim_end_matches('\n');
+    let folded = indicator == '>';
+    if body.is_empty()
+        || body.starts_with([' ', '\t'])
+        || body
+            .chars()
+            .any(|c| c.is_control() && c != '\n' && c != '\t')
+        || (folded && (body.contains("\n ") || body.contains("\n\t")))
+    {
+        return None;
+    }
+
+    let trailing = text.len() - body.len();
+    let chomp = match trailing {
+        0 => "-",
+        1 => "",
+        _ => "+",
+    };
+
+    // Folding turns a single line break into a space, so each run of line
+    // breaks is written with one extra
+    let written = if folded {
+        let mut written = String::new();
+        let mut chars = body.chars().peekable();
+        while let Some(c) = chars.next() {
+            written.push(c);
+            if c == '\n' && chars.peek() != Some(&'\n') {
+                written.push('\n');
+            }
+        }
+        written
+    } else {
+        body.to_string()
+    };
+    let mut lines: Vec<String> = written.split('\n').map(str::to_string).collect();
+    lines.extend(std::iter::repeat_n(
+        String::new(),
+        trailing.saturating_sub(1),
+    ));
+    Some((format!("{}{}", indicator, chomp), lines))
+}
+
+/// Writes a value in YAML flow style: `{a: 1, b: [x, y]}`.
+fn flow_text(value: &Value) -> String {
+    match value {
+        Value::Mapping(map) => {
+            let parts: Vec<String> = map
+                .iter()
+                .map(|(k, v)| format!("{}: {}", flow_text(k), flow_text(v)))
+                .collect();
+            format!("{{{}}}", parts.join(", "))
+        }
+        Value::Sequence(items) => {
+            let parts: Vec<String> = items.iter().map(flow_text).collect();
+            format!("[{}]", parts.join(", "))
+        }
+        Value::Tagged(tagged) => format!("{} {}", tagged.tag, flow_text(&tagged.value)),
+        Value::String(s) => {
+            let plain = serde_yaml::to_string(value).unwrap_or_default();
+            let plain = plain.trim_end_matches('\n');
+            // Quote strings that span lines or contain flow indicators
+            if plain.contains('\n')
+                || (!plain.starts_with(['"', '\'']) && plain.contains([',', '[', ']', '{', '}']))
+            {
+                format!("\"{}\"", escape_yaml_string(s))
+            } else {
+                plain.to_string()
+            }
+        }
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim_end_matches('\n')
+            .to_string(),
+    }
 }
 
 /// Injects comments from the tree into serialized YAML by walking both structures
@@ -1309,7 +1832,7 @@ fn get_line_indent(line: &str) -> String {
 }
 
 /// Creates a backup of a file by copying it with a .bak extension.
-fn create_backup<P: AsRef<Path>>(path: P) -> Result<()> {
+pub(crate) fn create_backup<P: AsRef<Path>>(path: P) -> Result<()> {
     let path = path.as_ref();
     let mut backup_path = path.to_path_buf();
     let original_name = backup_path
@@ -1328,8 +1851,8 @@ fn create_backup<P: AsRef<Path>>(path: P) -> Result<()> {
 /// then renames) to prevent data loss on crashes. Optionally creates a backup
 /// of the original file before writing.
 ///
-/// For multi-document YAML documents (YamlValue::MultiDoc), automatically uses
-/// line-by-line format via save_yamll.
+/// Multi-document YAML documents (YamlValue::MultiDoc) are written with `---`
+/// separators. [`yaml_text`] returns the text without writing it.
 ///
 /// # Arguments
 ///
@@ -1378,16 +1901,47 @@ pub fn save_yaml_file<P: AsRef<Path>>(path: P, tree: &YamlTree, config: &Config)
     // Determine if we should compress based on target filename
     let should_compress = path.to_string_lossy().ends_with(".gz");
 
-    // Check if this is a multi-document YAML document
-    if matches!(tree.root().value(), YamlValue::MultiDoc(_)) {
-        return save_yamll(path, tree, config, should_compress);
-    }
-
     // Create backup if requested and file exists
     if config.create_backup && path.exists() {
         create_backup(path)?;
     }
 
+    write_file_atomic(path, yaml_text(tree)?.as_bytes(), should_compress)
+}
+
+/// Returns the text [`save_yaml_file`] writes for `tree`.
+///
+/// A loaded document that hasn't been edited is written back byte for byte.
+/// In an edited one, unedited parts keep their original formatting and
+/// comments, and line breaks stay CRLF if the file used them; multi-document
+/// trees are written with `---` separators.
+pub fn yaml_text(tree: &YamlTree) -> Result<String> {
+    let Some(original) = tree.original_source() else {
+        return edited_yaml_text(tree);
+    };
+    if is_untouched(original, tree.root()) {
+        return Ok(original.to_string());
+    }
+    let text = edited_yaml_text(tree)?;
+    if original.contains("\r\n") {
+        Ok(crlf_line_breaks(&text))
+    } else {
+        Ok(text)
+    }
+}
+
+/// Returns the text for `tree` when something in it was edited.
+fn edited_yaml_text(tree: &YamlTree) -> Result<String> {
+    // Check if this is a multi-document YAML document
+    if matches!(tree.root().value(), YamlValue::MultiDoc(_)) {
+        if let Some(original) = tree.original_source() {
+            if let Some(updated) = save_with_document_preservation(original, tree) {
+                return Ok(updated);
+            }
+        }
+        return multi_document_text(tree);
+    }
+
     // Fast path: if only comments were edited, patch the original source directly
     if let Some(original) = tree.original_source() {
         if !has_non_comment_modifications(tree.root()) {
@@ -1397,7 +1951,7 @@ pub fn save_yaml_file<P: AsRef<Path>>(path: P, tree: &YamlTree, config: &Config)
                     let tree_keys: Vec<&str> = entries
                         .keys()
                         .filter(|k| !k.starts_with("__comment_"))
-                        .map(|k| k.as_str())
+                        .map(|k| written_key(k))
                         .collect();
                     let original_sections = parse_section_ranges(original);
                     let orig_keys: Vec<&str> =
@@ -1409,8 +1963,7 @@ pub fn save_yaml_file<P: AsRef<Path>>(path: P, tree: &YamlTree, config: &Config)
 
             if structure_unchanged {
                 if let Some(updated) = apply_comment_edits_to_source(original, tree.root()) {
-                    write_file_atomic(path, updated.as_bytes(), should_compress)?;
-                    return Ok(());
+                    return Ok(updated);
                 }
             }
         }
@@ -1418,26 +1971,52 @@ pub fn save_yaml_file<P: AsRef<Path>>(path: P, tree: &YamlTree, config: &Config)
         // Medium path: section-level preservation for structural edits
         if matches!(tree.root().value(), YamlValue::Object(_)) {
             if let Some(updated) = save_with_section_preservation(original, tree) {
-                write_file_atomic(path, updated.as_bytes(), should_compress)?;
-                return Ok(());
+                return Ok(updated);
             }
         }
     }
 
-    // Slow path: full re-serialization
-    // Convert YamlNode to serde_yaml::Value (comments are skipped, aliases resolved)
-    let value = convert_to_serde_value(tree.root(), tree)?;
-
-    // Serialize to YAML string
-    let yaml_str = serde_yaml::to_string(&value).context("Failed to serialize YAML")?;
+    // Slow path: full re-serialization, merging comments back into the output
+    serialize_with_comments(tree.root(), tree)
+}
 
-    // Merge comments from the tree back into the serialized output
-    let yaml_with_comments = inject_comments_structural(&yaml_str, tree.root(), tree);
+/// Returns `tree` as plain block YAML, ignoring how it was written.
+///
+/// Comments, flow collections, quoting and block scalar styles are dropped,
+/// and aliases are written out as copies of their anchors, so the output
+/// depends only on the data: the same document always comes out the same.
+pub fn canonical_yaml_text(tree: &YamlTree) -> Result<String> {
+    each_document(tree, |node| {
+        let value = convert_to_serde_value(node, tree)?;
+        let text = serde_yaml::to_string(&value).context("Failed to serialize YAML")?;
+        Ok(restore_tags(&text))
+    })
+}
 
-    // Write atomically (compressed or uncompressed)
-    write_file_atomic(path, yaml_with_comments.as_bytes(), should_compress)?;
+/// Returns `tree` in YAML flow style, one line per document:
+/// `{name: demo, ports: [80, 443]}`.
+pub fn flow_yaml_text(tree: &YamlTree) -> Result<String> {
+    each_document(tree, |node| {
+        let value = convert_to_serde_value(node, tree)?;
+        Ok(format!("{}\n", restore_tags(&flow_text(&value))))
+    })
+}
 
-    Ok(())
+/// Writes each document of `tree` with `write`, with `---` between the
+/// documents of a multi-document stream.
+fn each_document(
+    tree: &YamlTree,
+    mut write: impl FnMut(&YamlNode) -> Result<String>,
+) -> Result<String> {
+    let YamlValue::MultiDoc(documents) = tree.root().value() else {
+        return write(tree.root());
+    };
+    let mut output = String::new();
+    for node in documents {
+        output.push_str("---\n");
+        output.push_str(&write(node)?);
+    }
+    Ok(output)
 }
 
 /// Writes data to a file atomically, optionally compressing with gzip.
@@ -1458,7 +2037,11 @@ pub fn save_yaml_file<P: AsRef<Path>>(path: P, tree: &YamlTree, config: &Config)
 /// - Creating the temp file fails
 /// - Writing or compressing fails
 /// - Renaming the temp file fails
-fn write_file_atomic<P: AsRef<Path>>(path: P, data: &[u8], compress: bool) -> Result<()> {
+pub(crate) fn write_file_atomic<P: AsRef<Path>>(
+    path: P,
+    data: &[u8],
+    compress: bool,
+) -> Result<()> {
     use flate2::write::GzEncoder;
     use flate2::Compression;
     use std::io::Write;
@@ -1485,55 +2068,43 @@ fn write_file_atomic<P: AsRef<Path>>(path: P, data: &[u8], compress: bool) -> Re
     Ok(())
 }
 
-/// Saves a multi-document YAML document to a file.
+/// Serializes a multi-document YAML document.
 ///
 /// Documents are separated by `---` directives as per YAML 1.2 spec.
-fn save_yamll<P: AsRef<Path>>(
-    path: P,
-    tree: &YamlTree,
-    config: &Config,
-    compress: bool,
-) -> Result<()> {
-    let path = path.as_ref();
-
-    // Create backup if requested and file exists
-    if config.create_backup && path.exists() {
-        create_backup(path)?;
-    }
-
+fn multi_document_text(tree: &YamlTree) -> Result<String> {
     let mut output = String::new();
 
     if let YamlValue::MultiDoc(documents) = tree.root().value() {
         for (i, node) in documents.iter().enumerate() {
             // Add document separator before each document
             output.push_str("---\n");
-
-            // Convert to serde_yaml::Value
-            let value = convert_to_serde_value(node, tree)
-                .with_context(|| format!("Failed to convert document {} to YAML", i + 1))?;
-
-            // Serialize to YAML with proper formatting
-            let yaml = serde_yaml::to_string(&value)
-                .with_context(|| format!("Failed to serialize document {}", i + 1))?;
-
-            // Validate the serialized YAML
-            serde_yaml::from_str::<serde_yaml::Value>(&yaml).with_context(|| {
-                format!(
-                    "Generated invalid YAML at document {} - this is a bug in yamlquill's serialization",
-                    i + 1
-                )
-            })?;
-
-            output.push_str(&yaml);
-
-            // serde_yaml::to_string adds a trailing newline, so we don't need to add another
+            output.push_str(&document_text(node, tree, i)?);
         }
     }
 
-    // Write atomically with optional compression
-    write_file_atomic(path, output.as_bytes(), compress)?;
+    Ok(output)
+}
 
-    Ok(())
+/// Serializes the `index`th document of a multi-document stream.
+fn document_text(node: &YamlNode, tree: &YamlTree, index: usize) -> Result<String> {
+    // Convert to serde_yaml::Value
+    let value = convert_to_serde_value(node, tree)
+        .with_context(|| format!("Failed to convert document {} to YAML", index + 1))?;
+
+    // Serialize to YAML with proper formatting
+    let yaml = serde_yaml::to_string(&value)
+        .with_context(|| format!("Failed to serialize document {}", index + 1))?;
+
+    // Validate the serialized YAML
+    serde_yaml::from_str::<serde_yaml::Value>(&yaml).with_context(|| {
+        format!(
+            "Generated invalid YAML at document {} - this is a bug in yamlquill's serialization",
+            index + 1
+        )
+    })?;
+
+    // serde_yaml::to_string adds a trailing newline, so we don't need to add another
+    Ok(restore_keys(&restore_tags(&yaml)))
 }
 
 /// Serializes a node with format preservation for unmodified nodes.
@@ -1685,6 +2256,9 @@ pub fn serialize_node_compact(node: &YamlNode) -> String {
         YamlValue::Null => "null".to_string(),
         YamlValue::Alias(name) => format!("*{}", name),
         YamlValue::Comment(c) => format!("\"# {}\"", escape_yaml_string(&c.content)),
+        YamlValue::Deferred(source) => load_deferred(source)
+            .map(|node| serialize_node_compact(&node))
+            .unwrap_or_else(|_| format!("\"{}\"", escape_yaml_string(&source.text))),
     }
 }
 
@@ -1774,6 +2348,9 @@ pub fn serialize_node_jq_style(
         YamlValue::Null => "null".to_string(),
         YamlValue::Alias(name) => format!("*{}", name),
         YamlValue::Comment(c) => format!("\"# {}\"", escape_yaml_string(&c.content)),
+        YamlValue::Deferred(source) => load_deferred(source)
+            .map(|node| serialize_node_jq_style(&node, indent_size, current_depth))
+            .unwrap_or_else(|_| format!("\"{}\"", escape_yaml_string(&source.text))),
     }
 }
 
@@ -1873,6 +2450,9 @@ pub fn serialize_node(node: &YamlNode, indent_size: usize, current_depth: usize)
         YamlValue::Null => "null".to_string(),
         YamlValue::Alias(name) => format!("*{}", name),
         YamlValue::Comment(c) => format!("\"# {}\"", escape_yaml_string(&c.content)),
+        YamlValue::Deferred(source) => load_deferred(source)
+            .map(|node| serialize_node(&node, indent_size, current_depth))
+            .unwrap_or_else(|_| format!("\"{}\"", escape_yaml_string(&source.text))),
     }
 }
 
@@ -2696,6 +3276,22 @@ items:
         );
     }
 
+    #[test]
+    fn test_format_preserving_save_after_nested_delete() {
+        use crate::document::parser::parse_yaml_auto;
+
+        let yaml = "# app\nmetadata:\n  name: web\n  labels:\n    tier: front\nspec:\n  replicas: 1  # keep\n";
+        let mut tree =
+            YamlTree::with_source(parse_yaml_auto(yaml).unwrap(), Some(yaml.to_string()));
+        // metadata.labels
+        tree.delete_node(&[1, 1]).unwrap();
+
+        assert_eq!(
+            yaml_text(&tree).unwrap(),
+            "# app\nmetadata:\n  name: web\nspec:\n  replicas: 1  # keep\n"
+        );
+    }
+
     #[test]
     fn test_format_preserving_save_with_comment_edit() {
         use crate::document::parser::parse_yaml_auto;
@@ -3089,6 +3685,30 @@ services:
         assert_eq!(parsed["application"]["debug"], true);
     }
 
+    #[test]
+    fn test_new_scalar_anchor_and_aliases_are_written() {
+        use crate::document::parser::parse_yaml_auto;
+
+        let yaml = "web:\n  port: 8080\nprobe:\n  port: 8080\nports:\n- 8080\n- 80\n";
+        let mut tree =
+            YamlTree::with_source(parse_yaml_auto(yaml).unwrap(), Some(yaml.to_string()));
+        tree.get_node_mut(&[0, 0])
+            .unwrap()
+            .set_anchor(Some("port".to_string()));
+        // One alias is line-patched, the one in a list is re-serialized
+        for path in [vec![1, 0], vec![2, 0]] {
+            let mut alias = YamlNode::new(YamlValue::Alias("port".to_string()));
+            alias.set_alias_target(Some("port".to_string()));
+            *tree.get_node_mut(&path).unwrap() = alias;
+        }
+        tree.build_anchor_registry();
+
+        assert_eq!(
+            yaml_text(&tree).unwrap(),
+            "web:\n  port: &port 8080\nprobe:\n  port: *port\nports:\n- *port\n- 80\n"
+        );
+    }
+
     #[test]
     fn test_medium_path_preserves_anchors_aliases() {
         use crate::document::parser::parse_yaml_auto;
@@ -3873,4 +4493,59 @@ defaults:
             saved
         );
     }
+
+    #[test]
+    fn test_untouched_document_saves_verbatim() {
+        // The key-set check of the comment fast path can't see `?` keys
+        let original = "? [a, b]\n:   pair   # note\n";
+        let tree = YamlTree::with_source(
+            crate::document::parser::parse_yaml_auto(original).unwrap(),
+            Some(original.to_string()),
+        );
+        assert_eq!(yaml_text(&tree).unwrap(), original);
+    }
+
+    #[test]
+    fn test_crlf_line_breaks_kept_after_edit() {
+        let original = "a: 1\r\nb:\r\n  - x\r\n";
+        let mut tree = YamlTree::with_source(
+            crate::document::parser::parse_yaml_auto(original).unwrap(),
+            Some(original.to_string()),
+        );
+        *tree.get_node_mut(&[0]).unwrap().value_mut() = YamlValue::Number(YamlNumber::Integer(2));
+        assert_eq!(yaml_text(&tree).unwrap(), "a: 2\r\nb:\r\n  - x\r\n");
+    }
+
+    #[test]
+    fn test_deleted_comments_are_not_written_back() {
+        let original = "# top\na: 1\n\n# mid\nb:\n  - x\n# end\n";
+        let mut tree = YamlTree::with_source(
+            crate::document::parser::parse_yaml_auto(original).unwrap(),
+            Some(original.to_string()),
+        );
+        tree.delete_node(&[0]).unwrap();
+        assert_eq!(
+            yaml_text(&tree).unwrap(),
+            "a: 1\n\n# mid\nb:\n  - x\n# end\n"
+        );
+        // Comments after the last section and between sections, too
+        tree.delete_node(&[3]).unwrap();
+        tree.delete_node(&[1]).unwrap();
+        assert_eq!(yaml_text(&tree).unwrap(), "a: 1\n\nb:\n  - x\n");
+    }
+
+    #[test]
+    fn test_document_ranges() {
+        let text = "# head\n%YAML 1.2\n---\na: 1\n...\n--- # two\nb: 2\n---text\n";
+        let ranges = document_ranges(text);
+        assert_eq!(
+            ranges.iter().map(|r| &text[r.clone()]).collect::<Vec<_>>(),
+            [
+                "# head\n%YAML 1.2\n---\na: 1\n...\n",
+                "--- # two\nb: 2\n---text\n"
+            ]
+        );
+        // A first document without its own `---`
+        assert_eq!(document_ranges("a: 1\n---\nb: 2\n"), [0..5, 5..14]);
+    }
 }
@@ -0,0 +1,180 @@
+//! Serializer backends (`:w --style`).
+//!
+//! How much of the original text a save keeps is a choice of [`Style`]:
+//!
+//! - `preserving` keeps the formatting, comments and styles recorded when
+//!   the file was loaded, re-writing only what was edited
+//! - `canonical` writes plain block YAML (or pretty-printed JSON) from the
+//!   data alone, so the same document always comes out the same
+//! - `compact` writes flow YAML or minified JSON, one line per document
+//!
+//! Each style is a [`Serializer`] that can write each format. The style used
+//! for a save comes from `:w --style`, or else from the `yaml_style` and
+//! `json_style` settings. TOML has a single layout and is written the same
+//! way in every style.
+
+use crate::document::tree::YamlTree;
+use crate::file::exporter::{to_json, to_json_compact, to_toml, Format};
+use crate::file::saver::{canonical_yaml_text, flow_yaml_text, yaml_text};
+use anyhow::{bail, Result};
+
+/// How closely a save follows the document's original formatting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Preserving,
+    Canonical,
+    Compact,
+}
+
+impl Style {
+    /// Every style, in the order they are listed.
+    pub const ALL: [Style; 3] = [Style::Preserving, Style::Canonical, Style::Compact];
+
+    /// Parses a style name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::file::serializer::Style;
+    ///
+    /// assert_eq!(Style::parse("canonical").unwrap(), Style::Canonical);
+    /// assert!(Style::parse("pretty").is_err());
+    /// ```
+    pub fn parse(name: &str) -> Result<Self> {
+        match Self::ALL.into_iter().find(|style| style.name() == name) {
+            Some(style) => Ok(style),
+            None => bail!(
+                "Unknown style '{}' (use preserving, canonical or compact)",
+                name
+            ),
+        }
+    }
+
+    /// Returns the style's name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Style::Preserving => "preserving",
+            Style::Canonical => "canonical",
+            Style::Compact => "compact",
+        }
+    }
+
+    /// Returns the backend that writes this style.
+    pub fn serializer(self) -> &'static dyn Serializer {
+        match self {
+            Style::Preserving => &Preserving,
+            Style::Canonical => &Canonical,
+            Style::Compact => &Compact,
+        }
+    }
+}
+
+/// Writes a document as text in one style.
+pub trait Serializer {
+    /// Returns the document as YAML.
+    fn yaml(&self, tree: &YamlTree) -> Result<String>;
+
+    /// Returns the document as JSON.
+    fn json(&self, tree: &YamlTree) -> Result<String>;
+
+    /// Returns the document as TOML.
+    fn toml(&self, tree: &YamlTree) -> Result<String> {
+        to_toml(tree)
+    }
+
+    /// Returns the document in `format`.
+    fn write(&self, tree: &YamlTree, format: Format) -> Result<String> {
+        match format {
+            Format::Yaml => self.yaml(tree),
+            Format::Json => self.json(tree),
+            Format::Toml => self.toml(tree),
+        }
+    }
+}
+
+/// Keeps the original text of everything that wasn't edited.
+pub struct Preserving;
+
+impl Serializer for Preserving {
+    fn yaml(&self, tree: &YamlTree) -> Result<String> {
+        yaml_text(tree)
+    }
+
+    fn json(&self, tree: &YamlTree) -> Result<String> {
+        to_json(tree)
+    }
+}
+
+/// Writes block YAML and pretty-printed JSON from the data alone.
+pub struct Canonical;
+
+impl Serializer for Canonical {
+    fn yaml(&self, tree: &YamlTree) -> Result<String> {
+        canonical_yaml_text(tree)
+    }
+
+    fn json(&self, tree: &YamlTree) -> Result<String> {
+        to_json(tree)
+    }
+}
+
+/// Writes flow YAML and minified JSON.
+pub struct Compact;
+
+impl Serializer for Compact {
+    fn yaml(&self, tree: &YamlTree) -> Result<String> {
+        flow_yaml_text(tree)
+    }
+
+    fn json(&self, tree: &YamlTree) -> Result<String> {
+        to_json_compact(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    #[test]
+    fn test_each_style_writes_each_format() {
+        let source = "# app\nname: demo   # inline\nports: [80, 443]\nbase: &b {x: 1}\nuse: *b\n";
+        let tree = YamlTree::with_source(parse_yaml_auto(source).unwrap(), Some(source.into()));
+
+        assert_eq!(Style::Preserving.serializer().yaml(&tree).unwrap(), source);
+        assert_eq!(
+            Style::Canonical.serializer().yaml(&tree).unwrap(),
+            "name: demo\nports:\n- 80\n- 443\nbase:\n  x: 1\nuse:\n  x: 1\n"
+        );
+        assert_eq!(
+            Style::Compact.serializer().yaml(&tree).unwrap(),
+            "{name: demo, ports: [80, 443], base: {x: 1}, use: {x: 1}}\n"
+        );
+        assert_eq!(
+            Style::Compact.serializer().json(&tree).unwrap(),
+            "{\"name\":\"demo\",\"ports\":[80,443],\"base\":{\"x\":1},\"use\":{\"x\":1}}\n"
+        );
+        assert!(Style::Canonical
+            .serializer()
+            .write(&tree, Format::Json)
+            .unwrap()
+            .starts_with("{\n  \"name\": \"demo\""));
+        assert_eq!(
+            Style::Compact.serializer().toml(&tree).unwrap(),
+            to_toml(&tree).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_documents_stay_separate() {
+        let tree = YamlTree::new(parse_yaml_auto("---\na: 1\n---\nb: [2]\n").unwrap());
+        assert_eq!(
+            Style::Compact.serializer().yaml(&tree).unwrap(),
+            "---\n{a: 1}\n---\n{b: [2]}\n"
+        );
+        assert_eq!(
+            Style::Canonical.serializer().yaml(&tree).unwrap(),
+            "---\na: 1\n---\nb:\n- 2\n"
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Compressed storage for editor state files (undo history).
+//!
+//! State files can grow with every session on a large document, so they are
+//! written deflate-compressed behind a small header:
+//!
+//! ```text
+//! magic "YQZ\x01" | codec (1 = deflate) | CRC-32 of the data | data length | compressed data
+//! ```
+//!
+//! The checksum and length are checked on read, so a truncated or damaged
+//! file is reported as corrupt instead of being half-loaded, and no more than
+//! the length (and the caller's limit) is ever decompressed. Files without
+//! the header are returned as they are, which keeps plain files written by
+//! older versions readable.
+
+use anyhow::{bail, Context, Result};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use std::io::{Read, Write};
+
+const MAGIC: &[u8; 4] = b"YQZ\x01";
+const CODEC_DEFLATE: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + 8;
+
+/// Compresses `data` into the state file format.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::file::store::{decode, encode};
+///
+/// let data = "history = []\n".repeat(1000);
+/// let stored = encode(data.as_bytes()).unwrap();
+/// assert!(stored.len() < data.len() / 10);
+/// assert_eq!(decode(&stored, 0).unwrap(), data.as_bytes());
+/// ```
+pub fn encode(data: &[u8]) -> Result<Vec<u8>> {
+    let mut crc = Crc::new();
+    crc.update(data);
+
+    let mut out = Vec::with_capacity(HEADER_LEN + data.len() / 4);
+    out.extend_from_slice(MAGIC);
+    out.push(CODEC_DEFLATE);
+    out.extend_from_slice(&crc.sum().to_le_bytes());
+    out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    let mut encoder = DeflateEncoder::new(out, Compression::default());
+    encoder.write_all(data).context("Failed to compress")?;
+    encoder.finish().context("Failed to compress")
+}
+
+/// Returns the data stored by [`encode`], checking its length and checksum.
+///
+/// Data over `limit` bytes (0 for no limit) is refused without being
+/// decompressed. Bytes that don't start with the header are returned
+/// unchanged.
+pub fn decode(stored: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let over_limit = |len: u64| limit != 0 && len > limit as u64;
+    let Some(rest) = stored.strip_prefix(MAGIC) else {
+        if over_limit(stored.len() as u64) {
+            bail!("State file is over the {} limit", format_limit(limit));
+        }
+        return Ok(stored.to_vec());
+    };
+    if rest.len() < HEADER_LEN - MAGIC.len() {
+        bail!("Corrupt state file: truncated header");
+    }
+    let (codec, rest) = (rest[0], &rest[1..]);
+    if codec != CODEC_DEFLATE {
+        bail!("Unsupported state file compression ({})", codec);
+    }
+    let checksum = u32::from_le_bytes(rest[..4].try_into().expect("four bytes"));
+    let len = u64::from_le_bytes(rest[4..12].try_into().expect("eight bytes"));
+
+    if over_limit(len) {
+        bail!("State file is over the {} limit", format_limit(limit));
+    }
+
+    // Decompress at most one byte past the length, which is enough to see
+    // that the data is longer than the header says
+    let mut data = Vec::new();
+    DeflateDecoder::new(&rest[12..])
+        .take(len.saturating_add(1))
+        .read_to_end(&mut data)
+        .context("Corrupt state file: failed to decompress")?;
+    let mut crc = Crc::new();
+    crc.update(&data);
+    if data.len() as u64 != len || crc.sum() != checksum {
+        bail!("Corrupt state file: checksum mismatch");
+    }
+    Ok(data)
+}
+
+/// Formats a size limit for an error, in MB when it is a whole number of them.
+fn format_limit(bytes: usize) -> String {
+    if bytes.is_multiple_of(1 << 20) {
+        format!("{} MB", bytes >> 20)
+    } else {
+        format!("{} byte", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_damage_is_detected() {
+        let mut stored = encode(b"current = 3\nnodes = []\n").unwrap();
+        assert_eq!(decode(&stored, 0).unwrap(), b"current = 3\nnodes = []\n");
+
+        // A flipped length byte
+        stored[HEADER_LEN - 1] ^= 1;
+        assert!(decode(&stored, 0)
+            .unwrap_err()
+            .to_string()
+            .contains("checksum"));
+
+        // Truncation
+        let stored = encode(&[b'x'; 4096]).unwrap();
+        assert!(decode(&stored[..stored.len() / 2], 0).is_err());
+        assert!(decode(&stored[..8], 0).is_err());
+
+        // Files written before compression are read as they are
+        assert_eq!(decode(b"version = 2\n", 0).unwrap(), b"version = 2\n");
+    }
+
+    #[test]
+    fn test_decompression_is_bounded() {
+        let data = vec![0u8; 1 << 20];
+        let stored = encode(&data).unwrap();
+        assert!(decode(&stored, 1 << 20).is_ok());
+        assert!(decode(&stored, 1000)
+            .unwrap_err()
+            .to_string()
+            .contains("over the 1000 byte limit"));
+
+        // A header that understates the length stops the decompression
+        let mut stored = stored;
+        stored[HEADER_LEN - 8..HEADER_LEN].copy_from_slice(&16u64.to_le_bytes());
+        assert!(decode(&stored, 1000)
+            .unwrap_err()
+            .to_string()
+            .contains("checksum"));
+    }
+}
@@ -0,0 +1,311 @@
+//! Headless end-to-end testing.
+//!
+//! [`Harness`] runs the editor without a terminal: scripted keys go through
+//! the same [`InputHandler`] as real ones, the screen is drawn on ratatui's
+//! `TestBackend`, and the document is serialized exactly as `:w` would write
+//! it. Nothing depends on time (the cursor doesn't blink, files aren't
+//! polled), so the same script always gives the same screen.
+//!
+//! ```
+//! use yamlquill::harness::Harness;
+//!
+//! let mut harness = Harness::new("name: web\nreplicas: 3\n").unwrap();
+//! harness.keys("je<BS>5<CR>").unwrap();
+//! assert!(harness.screen().unwrap().contains("replicas: 5"));
+//! assert_eq!(harness.document().unwrap(), "name: web\nreplicas: 5\n");
+//! ```
+//!
+//! Keys use vim notation: plain characters stand for themselves, and
+//! `<Esc>`, `<CR>`, `<Tab>`, `<BS>`, `<Del>`, `<Up>`, `<C-x>`, `<A-x>`,
+//! `<F1>`, `<lt>` (a literal `<`) and friends name the rest.
+//!
+//! Snapshots are plain text files. [`Harness::assert_snapshot`] compares the
+//! screen and document with one, creating it when missing; run the tests with
+//! `YAMLQUILL_UPDATE_SNAPSHOTS=1` to rewrite snapshots after an intended change.
+
+use crate::app::App;
+use crate::document::parser::parse_yaml_auto;
+use crate::document::tree::YamlTree;
+use crate::editor::external::run_filter;
+use crate::editor::state::EditorState;
+use crate::file::loader::load_yaml_file;
+use crate::file::saver::yaml_text;
+use crate::input::InputHandler;
+use crate::theme::get_builtin_theme;
+use crate::ui::UI;
+use anyhow::{bail, Context, Result};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use std::fs;
+use std::path::Path;
+use termion::event::{Event, Key};
+
+/// Environment variable that makes [`Harness::assert_snapshot`] rewrite snapshots.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "YAMLQUILL_UPDATE_SNAPSHOTS";
+
+/// An editor session driven by scripted keys and drawn off-screen.
+pub struct Harness {
+    app: App,
+    handler: InputHandler,
+    ui: UI,
+    terminal: Terminal<TestBackend>,
+    quit: bool,
+}
+
+impl Harness {
+    /// Screen size used unless [`with_size`](Self::with_size) says otherwise.
+    pub const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+    /// Starts a session on `yaml`, as if it had been loaded from an unnamed file.
+    pub fn new(yaml: &str) -> Result<Self> {
+        let tree = YamlTree::with_source(parse_yaml_auto(yaml)?, Some(yaml.to_string()));
+        Ok(Self::from_state(EditorState::new_with_default_theme(tree)))
+    }
+
+    /// Starts a session on the file at `path`, so `:w` writes back to it.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut state = EditorState::new_with_default_theme(load_yaml_file(path)?);
+        state.set_filename(path.display().to_string());
+        Ok(Self::from_state(state))
+    }
+
+    /// Starts a session on an already configured editor state.
+    pub fn from_state(state: EditorState) -> Self {
+        let (width, height) = Self::DEFAULT_SIZE;
+        Self {
+            app: App::new(state),
+            handler: InputHandler::new(),
+            ui: UI::new(get_builtin_theme("default-dark").expect("built-in theme")),
+            terminal: Terminal::new(TestBackend::new(width, height)).expect("test backend"),
+            quit: false,
+        }
+    }
+
+    /// Draws on a `width` x `height` screen instead.
+    pub fn with_size(mut self, width: u16, height: u16) -> Self {
+        self.terminal = Terminal::new(TestBackend::new(width, height)).expect("test backend");
+        self
+    }
+
+    /// Presses each key of `script` (see the [module docs](self) for notation).
+    ///
+    /// Keys after one that quits the editor are ignored.
+    pub fn keys(&mut self, script: &str) -> Result<&mut Self> {
+        for key in parse_keys(script)? {
+            self.press(key)?;
+        }
+        Ok(self)
+    }
+
+    /// Presses one key, then runs whatever the event loop would after it.
+    ///
+    /// Shell filters (`:!cmd`) run right away; `ge` stays queued for the test
+    /// to take with [`EditorState::take_pending_external_edit`].
+    pub fn press(&mut self, key: Key) -> Result<&mut Self> {
+        self.event(Event::Key(key))
+    }
+
+    /// Sends any terminal event (mouse reports, for example), then runs
+    /// whatever the event loop would after it.
+    pub fn event(&mut self, event: Event) -> Result<&mut Self> {
+        if self.quit {
+            return Ok(self);
+        }
+        if self.handler.handle_event(event, self.app.focused_mut())? {
+            self.quit = self.app.focused().picked().is_some() || self.app.quit();
+        }
+        self.app.handle_pending();
+        if let Some(theme) = self.app.focused_mut().take_pending_theme() {
+            self.ui.set_theme(&theme);
+        }
+        if let Some(filter) = self.app.focused_mut().take_pending_filter() {
+            let output = run_filter(&filter.command, &filter.input);
+            self.app.focused_mut().finish_filter(&filter, output);
+        }
+        Ok(self)
+    }
+
+    /// Returns whether a key quit the editor.
+    pub fn has_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Returns the session's buffers and windows.
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// Returns the session's buffers and windows mutably.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Returns the focused buffer.
+    pub fn state(&self) -> &EditorState {
+        self.app.focused()
+    }
+
+    /// Returns the focused buffer mutably.
+    pub fn state_mut(&mut self) -> &mut EditorState {
+        self.app.focused_mut()
+    }
+
+    /// Draws the screen and returns it as text, one line per row.
+    ///
+    /// Trailing spaces are trimmed so snapshots don't depend on them.
+    pub fn screen(&mut self) -> Result<String> {
+        self.ui.render_app(&mut self.terminal, &mut self.app)?;
+        let buffer = self.terminal.backend().buffer();
+        let area = buffer.area;
+        Ok((area.top()..area.bottom())
+            .map(|y| {
+                let row: String = (area.left()..area.right())
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect();
+                row.trim_end().to_string() + "\n"
+            })
+            .collect())
+    }
+
+    /// Returns the focused buffer's document as `:w` would write it.
+    pub fn document(&self) -> Result<String> {
+        yaml_text(self.state().tree())
+    }
+
+    /// Returns the screen followed by the document, in snapshot format.
+    pub fn snapshot(&mut self) -> Result<String> {
+        Ok(format!(
+            "--- screen ---\n{}--- document ---\n{}",
+            self.screen()?,
+            self.document()?
+        ))
+    }
+
+    /// Compares [`snapshot`](Self::snapshot) with the file at `path`.
+    ///
+    /// A missing file is created, as is every file when
+    /// `YAMLQUILL_UPDATE_SNAPSHOTS` is set. Panics with both versions when
+    /// they differ, so it reads like `assert_eq!` in test output.
+    pub fn assert_snapshot(&mut self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let actual = self.snapshot().expect("snapshot");
+        let update = std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some();
+        if update || !path.exists() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).expect("create snapshot directory");
+            }
+            fs::write(path, &actual).expect("write snapshot");
+            return;
+        }
+        let expected = fs::read_to_string(path).expect("read snapshot");
+        assert!(
+            expected == actual,
+            "snapshot {} differs (set {} to update)\n--- expected\n{}--- actual\n{}",
+            path.display(),
+            UPDATE_SNAPSHOTS_VAR,
+            expected,
+            actual
+        );
+    }
+}
+
+/// Parses a key script in vim notation (`dd`, `:w<CR>`, `<C-r>`).
+pub fn parse_keys(script: &str) -> Result<Vec<Key>> {
+    let mut keys = Vec::new();
+    let mut rest = script;
+    while let Some(c) = rest.chars().next() {
+        let name = rest
+            .strip_prefix('<')
+            .and_then(|tail| tail.split_once('>'))
+            .map(|(name, _)| name);
+        match name {
+            Some(name) if !name.is_empty() && !name.contains('<') => {
+                keys.push(named_key(name).with_context(|| format!("Unknown key <{}>", name))?);
+                rest = &rest[name.len() + 2..];
+            }
+            _ => {
+                keys.push(Key::Char(c));
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// Returns the key named `name` in `<name>`, ignoring case.
+fn named_key(name: &str) -> Result<Key> {
+    let lower = name.to_ascii_lowercase();
+    let modified = |prefix: &str| {
+        let rest = name.get(prefix.len()..)?;
+        let mut chars = rest.chars();
+        let c = chars.next()?;
+        chars.next().is_none().then_some(c)
+    };
+    Ok(match lower.as_str() {
+        "esc" => Key::Esc,
+        "cr" | "enter" | "return" => Key::Char('\n'),
+        "tab" => Key::Char('\t'),
+        "s-tab" => Key::BackTab,
+        "bs" | "backspace" => Key::Backspace,
+        "del" | "delete" => Key::Delete,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "insert" => Key::Insert,
+        "space" => Key::Char(' '),
+        "lt" => Key::Char('<'),
+        _ if lower.starts_with("c-") => Key::Ctrl(modified("c-").context("expected one key")?),
+        _ if lower.starts_with("a-") || lower.starts_with("m-") => {
+            Key::Alt(modified("a-").context("expected one key")?)
+        }
+        _ if lower.starts_with('f') => match lower[1..].parse() {
+            Ok(n @ 1..=12) => Key::F(n),
+            _ => bail!("no such function key"),
+        },
+        _ => bail!("no such key"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse_keys("dd:w<CR>").unwrap(),
+            vec![
+                Key::Char('d'),
+                Key::Char('d'),
+                Key::Char(':'),
+                Key::Char('w'),
+                Key::Char('\n'),
+            ]
+        );
+        assert_eq!(
+            parse_keys("<C-r><Esc><s-tab><F2><A-x><lt>").unwrap(),
+            vec![
+                Key::Ctrl('r'),
+                Key::Esc,
+                Key::BackTab,
+                Key::F(2),
+                Key::Alt('x'),
+                Key::Char('<'),
+            ]
+        );
+        // A `<` that doesn't start a key name is itself
+        assert_eq!(
+            parse_keys("a<b").unwrap(),
+            vec![Key::Char('a'), Key::Char('<'), Key::Char('b')]
+        );
+        assert_eq!(parse_keys("<>").unwrap()[0], Key::Char('<'));
+        assert!(parse_keys("<Nope>").is_err());
+        assert!(parse_keys("<C-ab>").is_err());
+    }
+}
@@ -1,11 +1,15 @@
 //! Input event handler for polling and processing keyboard events.
 
+use super::keylog::{default_keylog_path, KeyLog};
 use super::keys::{map_key_event, InputEvent};
 use crate::editor::mode::EditorMode;
 use crate::editor::state::EditorState;
+use crate::export::outline::OutlineOptions;
 use anyhow::{Context, Result};
 use std::fs::File;
 use std::io::{self, Stdin};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
 use std::time::Duration;
 use termion::event::{Event, Key, MouseButton, MouseEvent};
 use termion::input::{Events, TermRead};
@@ -19,6 +23,8 @@ enum EventSource {
     Stdin(Events<Stdin>),
     /// Reading from /dev/tty (when stdin was piped)
     Tty(Events<File>),
+    /// Events read by a background thread ([`InputHandler::read_in_background`])
+    Channel(Receiver<io::Result<Event>>),
 }
 
 /// Handles terminal input events and updates editor state.
@@ -30,6 +36,13 @@ pub struct InputHandler {
     events: EventSource,
     /// True if waiting for register name after " key
     awaiting_register: bool,
+    /// True if waiting for a window command after Ctrl-w
+    awaiting_window: bool,
+    /// Key log being written (`:set keylog`)
+    keylog: Option<KeyLog>,
+    /// Where the cursor was before a `]` or `[` sibling motion, for a `q`
+    /// that turns it into `]q`/`[q`
+    bracket_origin: Option<Vec<usize>>,
 }
 
 impl InputHandler {
@@ -46,6 +59,9 @@ impl InputHandler {
         Self {
             events: EventSource::Stdin(io::stdin().events()),
             awaiting_register: false,
+            awaiting_window: false,
+            keylog: None,
+            bracket_origin: None,
         }
     }
 
@@ -61,12 +77,43 @@ impl InputHandler {
         Ok(Self {
             events: EventSource::Tty(tty_file.events()),
             awaiting_register: false,
+            awaiting_window: false,
+            keylog: None,
+            bracket_origin: None,
         })
     }
 
+    /// Moves reading to a background thread so [`poll_event`](Self::poll_event)
+    /// returns when its timeout elapses.
+    ///
+    /// The event loop can then do other work, such as answering `--listen`
+    /// requests, while no keys are pressed. The thread keeps reading the
+    /// terminal, so programs that need it (`$EDITOR`) can't be run afterwards.
+    pub fn read_in_background(&mut self) {
+        let (sender, receiver) = mpsc::channel();
+        let source = std::mem::replace(&mut self.events, EventSource::Channel(receiver));
+        let events: Box<dyn Iterator<Item = io::Result<Event>> + Send> = match source {
+            EventSource::Stdin(events) => Box::new(events),
+            EventSource::Tty(events) => Box::new(events),
+            EventSource::Channel(receiver) => {
+                self.events = EventSource::Channel(receiver);
+                return;
+            }
+        };
+        thread::spawn(move || {
+            for event in events {
+                if sender.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
     /// Polls for a terminal event with a timeout.
     ///
     /// Returns Some(Event) if an event occurred, None if timeout elapsed.
+    /// The timeout only applies after [`read_in_background`](Self::read_in_background);
+    /// otherwise this waits for the next event.
     ///
     /// # Arguments
     ///
@@ -85,7 +132,7 @@ impl InputHandler {
     /// let mut handler = InputHandler::new();
     /// let event = handler.poll_event(Duration::from_millis(100)).unwrap();
     /// ```
-    pub fn poll_event(&mut self, _timeout: Duration) -> Result<Option<Event>> {
+    pub fn poll_event(&mut self, timeout: Duration) -> Result<Option<Event>> {
         // Use the stored events iterator to maintain position in the input buffer.
         // This prevents character loss during rapid input (paste operations).
         match &mut self.events {
@@ -99,6 +146,10 @@ impl InputHandler {
                     return Ok(Some(event_result?));
                 }
             }
+            EventSource::Channel(receiver) => match receiver.recv_timeout(timeout) {
+                Ok(event_result) => return Ok(Some(event_result?)),
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => {}
+            },
         }
 
         Ok(None)
@@ -139,11 +190,20 @@ impl InputHandler {
     /// assert!(should_quit);
     /// ```
     pub fn handle_event(&mut self, event: Event, state: &mut EditorState) -> Result<bool> {
+        if let Some(keylog) = &mut self.keylog {
+            if let Err(e) = keylog.record(state.mode(), &event) {
+                use crate::editor::state::MessageLevel;
+                state.set_message(format!("{:#} (key log stopped)", e), MessageLevel::Error);
+                self.keylog = None;
+            }
+        }
+
         // Handle register selection if awaiting register
         if self.awaiting_register {
             if let Event::Key(Key::Char(c)) = event {
-                // Check if it's a valid register (a-z, A-Z, 0-9, ")
-                if c.is_ascii_alphanumeric() || c == '"' {
+                // Check if it's a valid register (a-z, A-Z, 0-9, ", and the
+                // system clipboard + / *)
+                if c.is_ascii_alphanumeric() || matches!(c, '"' | '+' | '*') {
                     // Uppercase letters enable append mode
                     if c.is_ascii_uppercase() {
                         state.set_pending_register(c.to_ascii_lowercase(), true);
@@ -159,6 +219,142 @@ impl InputHandler {
             return Ok(false);
         }
 
+        // Handle window command if waiting after Ctrl-w
+        if self.awaiting_window {
+            use crate::editor::window::WindowCommand;
+            self.awaiting_window = false;
+            let command = match event {
+                Event::Key(Key::Char('w')) | Event::Key(Key::Ctrl('w')) => {
+                    Some(WindowCommand::FocusNext)
+                }
+                Event::Key(Key::Char('h')) | Event::Key(Key::Left) => {
+                    Some(WindowCommand::FocusLeft)
+                }
+                Event::Key(Key::Char('l')) | Event::Key(Key::Right) => {
+                    Some(WindowCommand::FocusRight)
+                }
+                Event::Key(Key::Char('q')) | Event::Key(Key::Char('c')) => {
+                    Some(WindowCommand::Close)
+                }
+                Event::Key(Key::Char('o')) => Some(WindowCommand::Only),
+                _ => None,
+            };
+            if let Some(command) = command {
+                state.request_window_command(command);
+            }
+            return Ok(false);
+        }
+
+        // A write was blocked because the file changed on disk: wait for
+        // overwrite, reload, diff or cancel
+        if state.save_conflict().is_some() {
+            use crate::editor::state::MessageLevel;
+            match event {
+                Event::Key(Key::Char('o')) => {
+                    let command = state.take_save_conflict().unwrap_or_default();
+                    state.record_disk_state();
+                    return self.execute_command(&command, state);
+                }
+                Event::Key(Key::Char('r')) => {
+                    state.take_save_conflict();
+                    match state.reload_from_disk() {
+                        Ok(()) => {
+                            let filename = state.filename().unwrap_or_default().to_string();
+                            state.set_message(
+                                format!("\"{}\" reloaded", filename),
+                                MessageLevel::Info,
+                            );
+                        }
+                        Err(e) => {
+                            state.set_message(
+                                format!("Error reloading file: {}", e),
+                                MessageLevel::Error,
+                            );
+                        }
+                    }
+                }
+                Event::Key(Key::Char('d')) => {
+                    // Review against the new version; saving afterwards writes the result
+                    state.take_save_conflict();
+                    state.record_disk_state();
+                    if let Err(e) = state.start_review(false) {
+                        state.set_message(e.to_string(), MessageLevel::Error);
+                    }
+                }
+                Event::Key(Key::Char('c')) | Event::Key(Key::Esc) => {
+                    state.take_save_conflict();
+                    state.set_message("Not written".to_string(), MessageLevel::Info);
+                }
+                _ => {}
+            }
+            return Ok(false);
+        }
+
+        // :dedupe: resolve, skip or stop at each repeated key
+        if state.dedupe_pending() {
+            use crate::editor::dedupe::Resolution;
+            use crate::editor::state::MessageLevel;
+            let result = match event {
+                Event::Key(Key::Char('s')) => state.answer_dedupe(None),
+                Event::Key(Key::Char('q')) | Event::Key(Key::Esc) => {
+                    state.stop_dedupe();
+                    Ok(())
+                }
+                Event::Key(Key::Char(c)) => match Resolution::from_answer(c) {
+                    Some(resolution) => state.answer_dedupe(Some(resolution)),
+                    None => Ok(()),
+                },
+                _ => Ok(()),
+            };
+            if let Err(e) = result {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+
+        // f: letters narrow the keys on screen, labels and Enter jump
+        if state.key_jump().is_some() {
+            match event {
+                Event::Key(Key::Char('\n')) => state.key_jump_accept(),
+                Event::Key(Key::Backspace) => state.key_jump_backspace(),
+                Event::Key(Key::Char(c)) if !c.is_control() => state.key_jump_input(c),
+                _ => state.finish_key_jump(None),
+            }
+            return Ok(false);
+        }
+
+        // s: letters spell a label, Backspace takes one back
+        if state.hints().is_some() {
+            match event {
+                Event::Key(Key::Backspace) => state.hints_backspace(),
+                Event::Key(Key::Char(c)) if !c.is_control() => state.hints_input(c),
+                _ => state.finish_hints(None),
+            }
+            return Ok(false);
+        }
+
+        // The file changed on disk: wait for reload or ignore
+        if state.external_change_pending() {
+            use crate::editor::state::MessageLevel;
+            match event {
+                Event::Key(Key::Char('r')) => match state.reload_from_disk() {
+                    Ok(()) => {
+                        let filename = state.filename().unwrap_or_default().to_string();
+                        state.set_message(format!("\"{}\" reloaded", filename), MessageLevel::Info);
+                    }
+                    Err(e) => {
+                        state.set_message(
+                            format!("Error reloading file: {}", e),
+                            MessageLevel::Error,
+                        );
+                    }
+                },
+                Event::Key(Key::Char('i')) | Event::Key(Key::Esc) => state.ignore_external_change(),
+                _ => {}
+            }
+            return Ok(false);
+        }
+
         // Handle mark setting if waiting for mark name after 'm'
         if state.pending_mark_set() {
             state.set_pending_mark_set(false);
@@ -241,22 +437,49 @@ impl InputHandler {
             return Ok(false);
         }
 
+        // Shift+wheel arrives as a modified wheel button termion doesn't decode
+        if let Event::Unsupported(bytes) = &event {
+            if state.enable_mouse() && !state.show_help() {
+                if let Some(direction) = shift_wheel_direction(bytes) {
+                    let ticks = state.mouse_scroll_lines() as isize;
+                    state.scroll_horizontal(direction * ticks);
+                }
+            }
+            return Ok(false);
+        }
+
         // Handle mouse events if mouse is enabled
         if let Event::Mouse(mouse_event) = event {
             if state.enable_mouse() {
+                let ticks = state.mouse_scroll_lines();
+                // The registers overlay scrolls like the help overlay
+                if state.registers_scroll().is_some() {
+                    match mouse_event {
+                        MouseEvent::Press(MouseButton::WheelUp, _, _) => {
+                            for _ in 0..ticks {
+                                state.scroll_registers_up();
+                            }
+                        }
+                        MouseEvent::Press(MouseButton::WheelDown, _, _) => {
+                            for _ in 0..ticks {
+                                state.scroll_registers_down();
+                            }
+                        }
+                        _ => {}
+                    }
+                    return Ok(false);
+                }
                 // Check if help is shown - mouse scrolls help overlay
                 if state.show_help() {
                     match mouse_event {
                         MouseEvent::Press(MouseButton::WheelUp, _, _) => {
-                            // Scroll help up 3 lines
-                            for _ in 0..3 {
+                            for _ in 0..ticks {
                                 state.scroll_help_up();
                             }
                             return Ok(false);
                         }
                         MouseEvent::Press(MouseButton::WheelDown, _, _) => {
-                            // Scroll help down 3 lines
-                            for _ in 0..3 {
+                            for _ in 0..ticks {
                                 state.scroll_help_down();
                             }
                             return Ok(false);
@@ -267,19 +490,30 @@ impl InputHandler {
                         }
                     }
                 } else {
-                    // Help not shown - scroll main viewport
+                    // Help not shown - scroll the main viewport, not the cursor
+                    let ticks = ticks as isize;
                     match mouse_event {
                         MouseEvent::Press(MouseButton::WheelUp, _, _) => {
-                            // Scroll up 3 lines
-                            for _ in 0..3 {
-                                state.move_cursor_up();
-                            }
+                            state.scroll_viewport(-ticks);
                             return Ok(false);
                         }
                         MouseEvent::Press(MouseButton::WheelDown, _, _) => {
-                            // Scroll down 3 lines
-                            for _ in 0..3 {
-                                state.move_cursor_down();
+                            state.scroll_viewport(ticks);
+                            return Ok(false);
+                        }
+                        MouseEvent::Press(MouseButton::WheelLeft, _, _) => {
+                            state.scroll_horizontal(-ticks);
+                            return Ok(false);
+                        }
+                        MouseEvent::Press(MouseButton::WheelRight, _, _) => {
+                            state.scroll_horizontal(ticks);
+                            return Ok(false);
+                        }
+                        MouseEvent::Press(MouseButton::Left, x, y) => {
+                            // Termion reports 1-based cells
+                            let (column, row) = (x.saturating_sub(1), y.saturating_sub(1));
+                            if *state.mode() == EditorMode::Normal {
+                                state.click_at(column, row);
                             }
                             return Ok(false);
                         }
@@ -295,6 +529,47 @@ impl InputHandler {
         }
 
         if let Event::Key(key) = event {
+            // Handle the multi-line editor for strings spanning lines
+            if *state.mode() == EditorMode::Insert && state.multiline_edit().is_some() {
+                use crate::editor::state::MessageLevel;
+                match key {
+                    Key::Ctrl('s') => match state.commit_multiline_edit() {
+                        Ok(_) => {
+                            state.set_mode(EditorMode::Normal);
+                            state.set_message("Value updated".to_string(), MessageLevel::Info);
+                        }
+                        Err(e) => {
+                            state.set_message(format!("Invalid value: {}", e), MessageLevel::Error);
+                            state.set_mode(EditorMode::Normal);
+                        }
+                    },
+                    Key::Esc => {
+                        state.cancel_editing();
+                        state.set_mode(EditorMode::Normal);
+                        state.set_message("Edit cancelled".to_string(), MessageLevel::Info);
+                    }
+                    _ => {
+                        let Some(buffer) = state.multiline_edit_mut() else {
+                            return Ok(false);
+                        };
+                        match key {
+                            Key::Char(c) => buffer.insert_char(c),
+                            Key::Backspace => buffer.backspace(),
+                            Key::Delete | Key::Ctrl('d') => buffer.delete(),
+                            Key::Ctrl('k') => buffer.kill_to_end(),
+                            Key::Left => buffer.move_left(),
+                            Key::Right => buffer.move_right(),
+                            Key::Up => buffer.move_up(),
+                            Key::Down => buffer.move_down(),
+                            Key::Home | Key::Ctrl('a') => buffer.home(),
+                            Key::End | Key::Ctrl('e') => buffer.end(),
+                            _ => {}
+                        }
+                    }
+                }
+                return Ok(false);
+            }
+
             // Handle insert mode separately for character input
             if *state.mode() == EditorMode::Insert {
                 match key {
@@ -378,6 +653,9 @@ impl InputHandler {
                         }
                         return Ok(false);
                     }
+                    Key::Char('\t') if state.complete_edit_reference() => {
+                        return Ok(false);
+                    }
                     Key::Char(c) => {
                         state.push_to_edit_buffer(c);
                         return Ok(false);
@@ -445,6 +723,53 @@ impl InputHandler {
                 }
             }
 
+            // Handle the query REPL pane: expressions are evaluated as they are typed
+            if *state.mode() == EditorMode::Repl {
+                match key {
+                    Key::Char('\n') => state.repl_mut().submit(),
+                    Key::Ctrl('f') => {
+                        state.promote_repl_results();
+                        return Ok(false);
+                    }
+                    Key::Char(c) => state.repl_mut().insert_char(c),
+                    Key::Backspace => state.repl_mut().backspace(),
+                    Key::Left => state.repl_mut().move_left(),
+                    Key::Right => state.repl_mut().move_right(),
+                    Key::Up => {
+                        state.repl_mut().history_prev();
+                    }
+                    Key::Down => {
+                        state.repl_mut().history_next();
+                    }
+                    Key::Esc => {
+                        state.close_repl();
+                        return Ok(false);
+                    }
+                    _ => return Ok(false),
+                }
+                state.evaluate_repl();
+                return Ok(false);
+            }
+
+            // Handle review mode: keep, revert or edit each change
+            if *state.mode() == EditorMode::Review {
+                match key {
+                    Key::Char('y') => state.review_keep(),
+                    Key::Char('r') => {
+                        if let Err(e) = state.review_revert() {
+                            use crate::editor::state::MessageLevel;
+                            state.set_message(e.to_string(), MessageLevel::Error);
+                        }
+                    }
+                    Key::Char('e') => state.review_edit(),
+                    Key::Char('n') | Key::Char('j') | Key::Down => state.review_next(),
+                    Key::Char('p') | Key::Char('k') | Key::Up => state.review_previous(),
+                    Key::Char('q') | Key::Esc => state.close_review(),
+                    _ => {}
+                }
+                return Ok(false);
+            }
+
             // Handle command mode separately for character input
             if *state.mode() == EditorMode::Command {
                 match key {
@@ -453,7 +778,13 @@ impl InputHandler {
                         let command = state.command_buffer().to_string();
                         state.clear_command_buffer();
                         state.set_mode(EditorMode::Normal);
-                        return self.execute_command(&command, state);
+                        if self.keylog_command(command.trim(), state) {
+                            return Ok(false);
+                        }
+                        // A selection carried over from visual mode only lasts one command
+                        let result = self.execute_command(&command, state);
+                        state.clear_visual_selection();
+                        return result;
                     }
                     Key::Char('\t') => {
                         // Tab completion
@@ -469,12 +800,14 @@ impl InputHandler {
                         // Exit command mode if buffer is now empty
                         if state.command_buffer().is_empty() {
                             state.set_mode(EditorMode::Normal);
+                            state.clear_visual_selection();
                         }
                         return Ok(false);
                     }
                     Key::Esc => {
                         state.clear_command_buffer();
                         state.set_mode(EditorMode::Normal);
+                        state.clear_visual_selection();
                         return Ok(false);
                     }
                     _ => return Ok(false),
@@ -488,7 +821,9 @@ impl InputHandler {
                         // Exit search mode
                         state.set_mode(EditorMode::Normal);
                         use crate::editor::state::MessageLevel;
-                        if let Some((_current, total)) = state.search_results_info() {
+                        if let Err(e) = state.execute_search() {
+                            state.set_message(e.to_string(), MessageLevel::Error);
+                        } else if let Some((_current, total)) = state.search_results_info() {
                             state.set_message(
                                 format!("Found {} matches", total),
                                 MessageLevel::Info,
@@ -501,12 +836,12 @@ impl InputHandler {
                     }
                     Key::Char(c) => {
                         state.push_to_search_buffer(c);
-                        state.execute_search();
+                        update_incremental_search(state);
                         return Ok(false);
                     }
                     Key::Backspace => {
                         state.pop_from_search_buffer();
-                        state.execute_search();
+                        update_incremental_search(state);
                         return Ok(false);
                     }
                     Key::Esc => {
@@ -518,6 +853,163 @@ impl InputHandler {
                 }
             }
 
+            // If the bookmark picker is shown, handle navigation and selection
+            if let Some(picker) = state.bookmark_picker_mut() {
+                match key {
+                    Key::Up | Key::Char('k') => picker.previous(),
+                    Key::Down | Key::Char('j') => picker.next(),
+                    Key::Char('\n') => {
+                        if let Err(e) = state.bookmark_picker_select() {
+                            state.set_message(
+                                e.to_string(),
+                                crate::editor::state::MessageLevel::Error,
+                            );
+                        }
+                    }
+                    Key::Esc | Key::Char('q') => state.close_bookmark_picker(),
+                    _ => {}
+                }
+                return Ok(false);
+            }
+
+            // If the pipeline graph is shown, handle navigation and selection
+            if let Some(graph) = state.pipeline_graph_mut() {
+                match key {
+                    Key::Up | Key::Char('k') => graph.previous(),
+                    Key::Down | Key::Char('j') => graph.next(),
+                    Key::Char('\n') => state.pipeline_graph_select(),
+                    Key::Esc | Key::Char('q') => state.close_pipeline_graph(),
+                    _ => {}
+                }
+                return Ok(false);
+            }
+
+            // If the usages panel is shown, handle navigation and selection
+            if let Some(panel) = state.usages_panel_mut() {
+                match key {
+                    Key::Up | Key::Char('k') => panel.previous(),
+                    Key::Down | Key::Char('j') => panel.next(),
+                    Key::Char('\n') => state.usages_panel_select(),
+                    Key::Esc | Key::Char('q') => state.close_usages_panel(),
+                    _ => {}
+                }
+                return Ok(false);
+            }
+
+            // If the registers overlay is shown, handle scrolling and closing
+            if state.registers_scroll().is_some() {
+                match key {
+                    Key::Down | Key::Char('j') => state.scroll_registers_down(),
+                    Key::Up | Key::Char('k') => state.scroll_registers_up(),
+                    Key::Esc | Key::Char('q') => state.close_registers(),
+                    _ => {}
+                }
+                return Ok(false);
+            }
+
+            // If the mark picker is shown, handle navigation and selection
+            if let Some(picker) = state.mark_picker_mut() {
+                match key {
+                    Key::Up | Key::Char('k') => picker.previous(),
+                    Key::Down | Key::Char('j') => picker.next(),
+                    Key::Char('\n') => state.mark_picker_select(),
+                    Key::Esc | Key::Char('q') => state.close_mark_picker(),
+                    _ => {}
+                }
+                return Ok(false);
+            }
+
+            // If the backup browser is shown, handle navigation and restoring
+            if let Some(picker) = state.backup_picker_mut() {
+                match key {
+                    Key::Up | Key::Char('k') => picker.previous(),
+                    Key::Down | Key::Char('j') => picker.next(),
+                    Key::Char('\n') => {
+                        if let Err(e) = state.restore_backup() {
+                            state.set_message(
+                                e.to_string(),
+                                crate::editor::state::MessageLevel::Error,
+                            );
+                        }
+                    }
+                    Key::Esc | Key::Char('q') => state.close_backup_picker(),
+                    _ => {}
+                }
+                return Ok(false);
+            }
+
+            // If the container picker is shown, handle navigation and selection
+            if let Some(picker) = state.container_picker_mut() {
+                match key {
+                    Key::Up | Key::Char('k') => picker.previous(),
+                    Key::Down | Key::Char('j') => picker.next(),
+                    Key::Char('\n') => state.container_picker_select(),
+                    Key::Esc | Key::Char('q') => state.close_container_picker(),
+                    _ => {}
+                }
+                return Ok(false);
+            }
+
+            if let Some(panel) = state.facet_panel_mut() {
+                match key {
+                    Key::Up | Key::Char('k') => panel.previous(),
+                    Key::Down | Key::Char('j') => panel.next(),
+                    Key::Char('\n') => {
+                        if let Some(count) = state.facet_panel_select() {
+                            state.set_message(
+                                format!("Showing {} record(s) (:nofilter to show all)", count),
+                                crate::editor::state::MessageLevel::Info,
+                            );
+                        }
+                    }
+                    Key::Esc | Key::Char('q') => state.close_facet_panel(),
+                    _ => {}
+                }
+                return Ok(false);
+            }
+
+            // If the task panel is shown, handle navigation and jumping
+            if let Some(panel) = state.task_list_mut() {
+                match key {
+                    Key::Up | Key::Char('k') => panel.previous(),
+                    Key::Down | Key::Char('j') => panel.next(),
+                    Key::Char('\n') => state.task_list_select(),
+                    Key::Esc | Key::Char('q') => state.close_task_list(),
+                    _ => {}
+                }
+                return Ok(false);
+            }
+
+            // If the quickfix panel is shown, handle navigation and jumping
+            if let Some(panel) = state.quickfix_panel_mut() {
+                match key {
+                    Key::Up | Key::Char('k') => panel.previous(),
+                    Key::Down | Key::Char('j') => panel.next(),
+                    Key::Char('\n') => state.quickfix_select(),
+                    Key::Esc | Key::Char('q') => state.close_quickfix_panel(),
+                    _ => {}
+                }
+                return Ok(false);
+            }
+
+            // Any key closes the note popup
+            if state.note_popup().is_some() {
+                state.close_note_popup();
+                return Ok(false);
+            }
+
+            // Any key closes the `K` popup
+            if state.explanation().is_some() {
+                state.close_explanation();
+                return Ok(false);
+            }
+
+            // Any key closes the `gD` popup
+            if state.node_diff().is_some() {
+                state.close_node_diff();
+                return Ok(false);
+            }
+
             // If theme picker is shown, handle navigation and selection
             if state.show_theme_picker() {
                 match key {
@@ -569,7 +1061,8 @@ impl InputHandler {
             // Handle digit input in Normal mode for count prefix
             if *state.mode() == EditorMode::Normal {
                 if let Key::Char(c) = key {
-                    if c.is_ascii_digit() {
+                    // ...except after 'g', where g1-g9 jump to pins
+                    if c.is_ascii_digit() && state.pending_command() != Some('g') {
                         let digit = c.to_digit(10).unwrap();
                         // '0' can only be part of count if count already started
                         // '0' by itself would be a command (go to start of line in vim)
@@ -634,6 +1127,52 @@ impl InputHandler {
                         }
                     }
 
+                    // ] / [ - next/previous sibling right away; a q after
+                    // them takes the motion back and steps the quickfix list
+                    if let Some(bracket @ (']' | '[')) = state.pending_command() {
+                        state.clear_pending_command();
+                        let origin = self.bracket_origin.take();
+                        if c == 'q' {
+                            use crate::editor::state::MessageLevel;
+                            state.clear_pending();
+                            if let Some(origin) = origin {
+                                state.cursor_mut().set_path(origin);
+                            }
+                            match state.quickfix_step(bracket == ']') {
+                                Ok(message) => state.set_message(message, MessageLevel::Info),
+                                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                            }
+                            return Ok(false);
+                        }
+                        // Anything else is handled as usual
+                    }
+                    if matches!(c, ']' | '[') && state.pending_command().is_none() {
+                        state.clear_pending();
+                        state.clear_search_results();
+                        self.bracket_origin = Some(state.cursor().path().to_vec());
+                        if c == ']' {
+                            state.move_to_next_sibling();
+                        } else {
+                            state.move_to_previous_sibling();
+                        }
+                        state.set_pending_command(c);
+                        return Ok(false);
+                    }
+
+                    // f - jump to a key on screen by typing its start
+                    if c == 'f' && state.pending_command().is_none() {
+                        state.clear_pending();
+                        state.start_key_jump();
+                        return Ok(false);
+                    }
+
+                    // s - jump to any line on screen by its label
+                    if c == 's' && state.pending_command().is_none() {
+                        state.clear_pending();
+                        state.start_hints();
+                        return Ok(false);
+                    }
+
                     // Handle screen positioning commands (zz, zt, zb)
                     if state.pending_command() == Some('z') {
                         match c {
@@ -652,6 +1191,15 @@ impl InputHandler {
                                 state.cursor_to_bottom_of_screen();
                                 return Ok(false);
                             }
+                            'd' => {
+                                // zd - show the children cut off at the depth limit
+                                state.clear_pending();
+                                if let Err(e) = state.expand_deeper_at_cursor() {
+                                    use crate::editor::state::MessageLevel;
+                                    state.set_message(e.to_string(), MessageLevel::Error);
+                                }
+                                return Ok(false);
+                            }
                             'a' => {
                                 // za - toggle fold at cursor
                                 state.clear_pending();
@@ -686,9 +1234,52 @@ impl InputHandler {
                         }
                     }
 
-                    // Handle navigation commands (gp)
+                    // gt<type> - the type key after gt
+                    if state.pending_command() == Some('t') {
+                        use crate::editor::coerce::ScalarType;
+                        use crate::editor::state::MessageLevel;
+                        state.clear_pending();
+                        // gt+ / gt- shift a timestamp, gt= re-formats it
+                        if matches!(c, '+' | '-' | '=') {
+                            let prefill = if c == '=' {
+                                "time ".to_string()
+                            } else {
+                                format!("time {}", c)
+                            };
+                            state.clear_message();
+                            state.set_command_buffer(prefill);
+                            state.set_mode(EditorMode::Command);
+                            return Ok(false);
+                        }
+                        let target = ScalarType::ALL.into_iter().find(|t| t.key() == c);
+                        let result = match target {
+                            Some(target) => state
+                                .change_type_at_cursor(target)
+                                .map(|_| format!("Changed to {}", target.name())),
+                            None => Err(anyhow::anyhow!("Unknown type key: {}", c)),
+                        };
+                        match result {
+                            Ok(message) => state.set_message(message, MessageLevel::Info),
+                            Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                        }
+                        return Ok(false);
+                    }
+
+                    // Handle navigation commands (gp), gs, gf and ge
                     if state.pending_command() == Some('g') {
                         match c {
+                            't' => {
+                                // gt - change the type of the scalar at the cursor
+                                use crate::editor::state::MessageLevel;
+                                state.clear_pending();
+                                state.set_pending_command('t');
+                                state.set_message(
+                                    "Change type: s)tr i)nt f)loat b)ool n)ull, or +/-) shift =) format a timestamp"
+                                        .to_string(),
+                                    MessageLevel::Info,
+                                );
+                                return Ok(false);
+                            }
                             'p' => {
                                 // gp - jump to parent node
                                 state.clear_pending();
@@ -696,12 +1287,131 @@ impl InputHandler {
                                 state.move_to_parent();
                                 return Ok(false);
                             }
-                            _ => {
-                                // Not a 'g' navigation command, continue with normal processing
+                            'f' => {
+                                // gf - toggle block/flow style of the node at the cursor
+                                use crate::document::node::CollectionStyle;
+                                use crate::editor::state::MessageLevel;
+                                state.clear_pending();
+                                match state.toggle_flow_style_at_cursor() {
+                                    Ok(CollectionStyle::Flow) => state
+                                        .set_message("Flow style".to_string(), MessageLevel::Info),
+                                    Ok(CollectionStyle::Block) => state
+                                        .set_message("Block style".to_string(), MessageLevel::Info),
+                                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                                }
+                                return Ok(false);
                             }
-                        }
-                    }
-                }
+                            'e' => {
+                                // ge - edit the subtree at the cursor in $EDITOR
+                                state.clear_pending();
+                                if let Err(e) = state.request_external_edit() {
+                                    use crate::editor::state::MessageLevel;
+                                    state.set_message(e.to_string(), MessageLevel::Error);
+                                }
+                                return Ok(false);
+                            }
+                            '1'..='9' => {
+                                // g1-g9 - jump to a pinned path
+                                state.clear_pending();
+                                let number = c.to_digit(10).unwrap_or(1) as usize;
+                                if let Err(e) = state.jump_to_pin(number) {
+                                    use crate::editor::state::MessageLevel;
+                                    state.set_message(e.to_string(), MessageLevel::Error);
+                                }
+                                return Ok(false);
+                            }
+                            'J' => {
+                                // gJ - join the list at the cursor into one string
+                                use crate::editor::state::MessageLevel;
+                                state.clear_pending();
+                                match state.join_list_at_cursor(" ") {
+                                    Ok(n) => state.set_message(
+                                        format!("Joined {} items", n),
+                                        MessageLevel::Info,
+                                    ),
+                                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                                }
+                                return Ok(false);
+                            }
+                            'S' => {
+                                // gS - prompt for a delimiter to split the string at
+                                state.clear_pending();
+                                state.set_command_buffer("split-string ".to_string());
+                                state.set_mode(EditorMode::Command);
+                                return Ok(false);
+                            }
+                            '[' => {
+                                // g[ - wrap the node at the cursor in a new list
+                                use crate::editor::state::MessageLevel;
+                                state.clear_pending();
+                                match state.wrap_node(None) {
+                                    Ok(()) => state.set_message(
+                                        "Wrapped in a list".to_string(),
+                                        MessageLevel::Info,
+                                    ),
+                                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                                }
+                                return Ok(false);
+                            }
+                            ']' => {
+                                // g] - replace a one-child container with its child
+                                use crate::editor::state::MessageLevel;
+                                state.clear_pending();
+                                match state.unwrap_node() {
+                                    Ok(()) => state
+                                        .set_message("Unwrapped".to_string(), MessageLevel::Info),
+                                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                                }
+                                return Ok(false);
+                            }
+                            '{' => {
+                                // g{ - prompt for the key to wrap the node under
+                                state.clear_pending();
+                                state.set_command_buffer("wrap-object ".to_string());
+                                state.set_mode(EditorMode::Command);
+                                return Ok(false);
+                            }
+                            'D' => {
+                                // gD - diff the node at the cursor against the file on disk
+                                state.clear_pending();
+                                if let Err(e) = state.show_node_diff() {
+                                    use crate::editor::state::MessageLevel;
+                                    state.set_message(e.to_string(), MessageLevel::Error);
+                                }
+                                return Ok(false);
+                            }
+                            'b' => {
+                                // gb - decode the base64 string at the cursor, or encode it
+                                use crate::editor::state::MessageLevel;
+                                state.clear_pending();
+                                match state.toggle_base64_at_cursor() {
+                                    Ok(true) => state.set_message(
+                                        "Decoded from base64".to_string(),
+                                        MessageLevel::Info,
+                                    ),
+                                    Ok(false) => state.set_message(
+                                        "Encoded as base64".to_string(),
+                                        MessageLevel::Info,
+                                    ),
+                                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                                }
+                                return Ok(false);
+                            }
+                            's' => {
+                                // gs - sort the keys of the mapping at the cursor
+                                state.clear_pending();
+                                if let Err(e) = state.sort_keys_at_cursor(false, false) {
+                                    use crate::editor::state::MessageLevel;
+                                    state.set_message(e.to_string(), MessageLevel::Error);
+                                }
+                                return Ok(false);
+                            }
+                            _ => {
+                                // Not a 'g' navigation command, continue with normal processing
+                            }
+                        }
+                    }
+                }
 
                 // Handle key input during AwaitingKey stage (before Insert mode)
                 use crate::editor::state::AddModeStage;
@@ -779,8 +1489,17 @@ impl InputHandler {
                     state.clear_pending();
                     state.clear_search_results();
                     use crate::editor::state::MessageLevel;
+                    if state.reject_read_only() {
+                        return Ok(false);
+                    }
                     state.start_editing();
-                    if state.edit_buffer().is_some() {
+                    if state.multiline_edit().is_some() {
+                        state.set_mode(EditorMode::Insert);
+                        state.set_message(
+                            "-- INSERT -- (Ctrl-s to save, Esc to cancel)".to_string(),
+                            MessageLevel::Info,
+                        );
+                    } else if state.edit_buffer().is_some() {
                         state.set_mode(EditorMode::Insert);
                         state.set_message("-- INSERT --".to_string(), MessageLevel::Info);
                     } else {
@@ -833,6 +1552,24 @@ impl InputHandler {
                     state.clear_search_results();
                     state.toggle_help();
                 }
+                InputEvent::Explain => {
+                    state.clear_pending();
+                    if let Err(e) = state.explain_at_cursor() {
+                        state.set_message(e.to_string(), crate::editor::state::MessageLevel::Error);
+                    }
+                }
+                InputEvent::Indent | InputEvent::Outdent => {
+                    state.clear_pending();
+                    state.clear_search_results();
+                    let result = if input_event == InputEvent::Indent {
+                        state.indent_node()
+                    } else {
+                        state.outdent_node()
+                    };
+                    if let Err(e) = result {
+                        state.set_message(e.to_string(), crate::editor::state::MessageLevel::Error);
+                    }
+                }
                 InputEvent::ExitMode => {
                     state.clear_pending();
                     state.clear_search_results();
@@ -907,9 +1644,15 @@ impl InputHandler {
                     if state.mode() == &EditorMode::Visual {
                         state.clear_pending();
                         state.clear_search_results();
+                        let block = state.is_visual_block();
                         let count = state.yank_visual_selection();
                         if count > 0 {
-                            if count > 1 {
+                            if block {
+                                state.set_message(
+                                    format!("Column of {} values yanked", count),
+                                    MessageLevel::Info,
+                                );
+                            } else if count > 1 {
                                 state.set_message(
                                     format!("{} nodes yanked", count),
                                     MessageLevel::Info,
@@ -1033,6 +1776,22 @@ impl InputHandler {
                     state.clear_pending();
                     state.clear_search_results();
                     use crate::editor::state::MessageLevel;
+                    // Column registers fill a key across list elements instead
+                    if state.paste_register_is_column() {
+                        match state.paste_column() {
+                            Ok((key, count)) => {
+                                use crate::editor::repeat::RepeatableCommand;
+                                state.set_last_command(RepeatableCommand::Paste { before: false });
+                                state.set_message(
+                                    format!("Pasted '{}' into {} elements", key, count),
+                                    MessageLevel::Info,
+                                );
+                            }
+                            Err(e) => state
+                                .set_message(format!("Paste failed: {}", e), MessageLevel::Error),
+                        }
+                        return Ok(false);
+                    }
                     // In visual mode, delete selection first, then paste
                     if state.mode() == &EditorMode::Visual {
                         let _ = state.delete_visual_selection();
@@ -1054,6 +1813,22 @@ impl InputHandler {
                     state.clear_pending();
                     state.clear_search_results();
                     use crate::editor::state::MessageLevel;
+                    // Column registers fill a key across list elements instead
+                    if state.paste_register_is_column() {
+                        match state.paste_column() {
+                            Ok((key, count)) => {
+                                use crate::editor::repeat::RepeatableCommand;
+                                state.set_last_command(RepeatableCommand::Paste { before: true });
+                                state.set_message(
+                                    format!("Pasted '{}' into {} elements", key, count),
+                                    MessageLevel::Info,
+                                );
+                            }
+                            Err(e) => state
+                                .set_message(format!("Paste failed: {}", e), MessageLevel::Error),
+                        }
+                        return Ok(false);
+                    }
                     // In visual mode, delete selection first, then paste before
                     if state.mode() == &EditorMode::Visual {
                         let _ = state.delete_visual_selection();
@@ -1077,12 +1852,17 @@ impl InputHandler {
                     if state.pending_command() == Some('Z') {
                         state.clear_pending();
                         state.clear_search_results();
+                        if state.reject_read_only() || state.block_conflicting_save("x") {
+                            return Ok(false);
+                        }
                         // Save the file
                         if let Some(filename) = state.filename() {
-                            use crate::file::saver::save_yaml_file;
-                            match save_yaml_file(filename, state.tree(), &state.to_config()) {
+                            use crate::file::exporter::save_file;
+                            match save_file(filename, state.tree(), &state.to_config()) {
                                 Ok(_) => {
                                     state.clear_dirty();
+                                    state.write_undo_history();
+                                    state.record_disk_state();
                                     return Ok(true); // Quit after saving
                                 }
                                 Err(e) => {
@@ -1164,26 +1944,32 @@ impl InputHandler {
                     state.clear_pending();
                     state.clear_search_results();
                     use crate::editor::state::MessageLevel;
-                    if state.undo() {
-                        state.set_message("Undo".to_string(), MessageLevel::Info);
-                    } else {
-                        state.set_message(
+                    if state.reject_read_only() {
+                        return Ok(false);
+                    }
+                    match state.try_undo() {
+                        Ok(true) => state.set_message("Undo".to_string(), MessageLevel::Info),
+                        Ok(false) => state.set_message(
                             "Already at oldest change".to_string(),
                             MessageLevel::Info,
-                        );
+                        ),
+                        Err(e) => state.set_message(format!("{:#}", e), MessageLevel::Error),
                     }
                 }
                 InputEvent::Redo => {
                     state.clear_pending();
                     state.clear_search_results();
                     use crate::editor::state::MessageLevel;
-                    if state.redo() {
-                        state.set_message("Redo".to_string(), MessageLevel::Info);
-                    } else {
-                        state.set_message(
+                    if state.reject_read_only() {
+                        return Ok(false);
+                    }
+                    match state.try_redo() {
+                        Ok(true) => state.set_message("Redo".to_string(), MessageLevel::Info),
+                        Ok(false) => state.set_message(
                             "Already at newest change".to_string(),
                             MessageLevel::Info,
-                        );
+                        ),
+                        Err(e) => state.set_message(format!("{:#}", e), MessageLevel::Error),
                     }
                 }
                 InputEvent::Add => {
@@ -1204,6 +1990,12 @@ impl InputHandler {
                 InputEvent::Rename => {
                     state.clear_pending();
                     state.clear_search_results();
+                    if state.mode() == &EditorMode::Visual {
+                        // Prompt for a template; the selection carries over to the command
+                        state.set_command_buffer("rename ".to_string());
+                        state.set_mode(EditorMode::Command);
+                        return Ok(false);
+                    }
                     state.start_rename_operation();
                 }
                 InputEvent::NextSibling => {
@@ -1275,6 +2067,9 @@ impl InputHandler {
                     state.clear_search_results();
                     state.set_pending_command('z');
                 }
+                InputEvent::WindowPrefix => {
+                    self.awaiting_window = true;
+                }
                 InputEvent::RegisterSelect => {
                     // " key pressed - wait for register name
                     self.awaiting_register = true;
@@ -1295,6 +2090,17 @@ impl InputHandler {
                     state.enter_visual_mode();
                     state.set_message("-- VISUAL --".to_string(), MessageLevel::Info);
                 }
+                InputEvent::EnterVisualBlockMode => {
+                    state.clear_pending();
+                    state.clear_search_results();
+                    use crate::editor::state::MessageLevel;
+                    match state.enter_visual_block_mode() {
+                        Ok(()) => {
+                            state.set_message("-- VISUAL BLOCK --".to_string(), MessageLevel::Info)
+                        }
+                        Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                    }
+                }
                 InputEvent::MarkSet => {
                     state.clear_pending();
                     state.clear_search_results();
@@ -1337,6 +2143,11 @@ impl InputHandler {
                     state.clear_search_results();
                     use crate::editor::state::MessageLevel;
 
+                    // In --pick mode Enter selects the node and exits
+                    if state.pick_mode().is_some() && state.pick_at_cursor() {
+                        return Ok(true);
+                    }
+
                     // Get the current node and check if it's an alias
                     let current_path = state.cursor().path().to_vec();
                     let alias_target = state
@@ -1398,6 +2209,12 @@ impl InputHandler {
                 InputEvent::AddComment => {
                     state.clear_pending();
                     state.clear_search_results();
+                    if state.mode() == &EditorMode::Visual {
+                        // Prompt for a value; the selection carries over to the command
+                        state.set_command_buffer("change ".to_string());
+                        state.set_mode(EditorMode::Command);
+                        return Ok(false);
+                    }
                     use crate::editor::state::MessageLevel;
 
                     // Check if cursor is on a value node (not a comment)
@@ -1432,9 +2249,84 @@ impl InputHandler {
         Ok(false)
     }
 
-    fn execute_command(&self, command: &str, state: &mut EditorState) -> Result<bool> {
+    /// Handles the key log settings (`:set keylog[=FILE]`, `:set nokeylog`,
+    /// `:set keylogredact`, `:set nokeylogredact`, `:set keylog?`).
+    ///
+    /// The log belongs to the handler rather than a buffer, so it keeps
+    /// running across `:e` and splits. Returns false for any other command.
+    fn keylog_command(&mut self, command: &str, state: &mut EditorState) -> bool {
+        use crate::editor::state::MessageLevel;
+
+        let Some(setting) = command.strip_prefix("set ").map(str::trim) else {
+            return false;
+        };
+        let redact = self.keylog.as_ref().is_some_and(KeyLog::redact);
+        let path = match setting {
+            "keylog" => default_keylog_path(),
+            _ if setting.starts_with("keylog=") => {
+                let (_, file) = setting.split_once('=').unwrap_or_default();
+                match file.trim() {
+                    "" => default_keylog_path(),
+                    file => std::path::PathBuf::from(file),
+                }
+            }
+            "nokeylog" => {
+                let message = match self.keylog.take() {
+                    Some(log) => format!("Key log saved to {}", log.path().display()),
+                    None => "No key log running".to_string(),
+                };
+                state.set_message(message, MessageLevel::Info);
+                return true;
+            }
+            "keylogredact" | "nokeylogredact" => {
+                let redact = setting == "keylogredact";
+                match &mut self.keylog {
+                    Some(log) => {
+                        log.set_redact(redact);
+                        let message = if redact {
+                            "Typed text is redacted from the key log"
+                        } else {
+                            "Typed text is logged"
+                        };
+                        state.set_message(message.to_string(), MessageLevel::Info);
+                    }
+                    None => state.set_message(
+                        "No key log running (:set keylog starts one)".to_string(),
+                        MessageLevel::Error,
+                    ),
+                }
+                return true;
+            }
+            "keylog?" => {
+                let message = match &self.keylog {
+                    Some(log) => format!(
+                        "keylog is {}{}",
+                        log.path().display(),
+                        if log.redact() { " (redacted)" } else { "" }
+                    ),
+                    None => "keylog is off".to_string(),
+                };
+                state.set_message(message, MessageLevel::Info);
+                return true;
+            }
+            _ => return false,
+        };
+        match KeyLog::create(&path, redact) {
+            Ok(log) => {
+                self.keylog = Some(log);
+                state.set_message(
+                    format!("Logging keys to {} (:set nokeylog stops)", path.display()),
+                    MessageLevel::Info,
+                );
+            }
+            Err(e) => state.set_message(format!("{:#}", e), MessageLevel::Error),
+        }
+        true
+    }
+
+    pub(crate) fn execute_command(&self, command: &str, state: &mut EditorState) -> Result<bool> {
         use crate::editor::state::MessageLevel;
-        use crate::file::saver::save_yaml_file;
+        use crate::file::exporter::{save_file, save_file_styled};
 
         let command = command.trim();
 
@@ -1445,19 +2337,23 @@ impl InputHandler {
         }
 
         if let Some(theme_name) = command.strip_prefix("theme ") {
-            use crate::theme::get_builtin_theme;
+            use crate::theme::{list_themes, load_theme};
             let theme_name = theme_name.trim();
-            if get_builtin_theme(theme_name).is_some() {
-                state.request_theme_change(theme_name.to_string());
-                state.set_message(
-                    format!("Switched to theme: {}", theme_name),
-                    MessageLevel::Info,
-                );
-            } else {
-                state.set_message(
-                    format!("Unknown theme: {} (use :theme to list)", theme_name),
-                    MessageLevel::Error,
-                );
+            match load_theme(theme_name) {
+                Ok(_) => {
+                    state.request_theme_change(theme_name.to_string());
+                    state.set_message(
+                        format!("Switched to theme: {}", theme_name),
+                        MessageLevel::Info,
+                    );
+                }
+                Err(_) if !list_themes().iter().any(|t| t == theme_name) => {
+                    state.set_message(
+                        format!("Unknown theme: {} (use :theme to list)", theme_name),
+                        MessageLevel::Error,
+                    );
+                }
+                Err(e) => state.set_message(format!("{:#}", e), MessageLevel::Error),
             }
             return Ok(false);
         }
@@ -1486,6 +2382,80 @@ impl InputHandler {
             } else {
                 settings.push("nocreate_backup");
             }
+            if state.resolve_merge() {
+                settings.push("resolvemerge");
+            } else {
+                settings.push("noresolvemerge");
+            }
+            if state.persistent_undo() {
+                settings.push("undofile");
+            } else {
+                settings.push("noundofile");
+            }
+            if state.reload_on_change() {
+                settings.push("autoread");
+            } else {
+                settings.push("noautoread");
+            }
+            if state.regex_search() {
+                settings.push("regexsearch");
+            } else {
+                settings.push("noregexsearch");
+            }
+            if state.search_collapsed() {
+                settings.push("searchcollapsed");
+            } else {
+                settings.push("nosearchcollapsed");
+            }
+            if state.show_breadcrumbs() {
+                settings.push("breadcrumbs");
+            } else {
+                settings.push("nobreadcrumbs");
+            }
+            if state.show_type_badges() {
+                settings.push("typebadges");
+            } else {
+                settings.push("notypebadges");
+            }
+            if state.cloudformation() {
+                settings.push("cloudformation");
+            } else {
+                settings.push("nocloudformation");
+            }
+            if state.k8s() {
+                settings.push("k8s");
+            } else {
+                settings.push("nok8s");
+            }
+            if state.decode_secrets() {
+                settings.push("decodesecrets");
+            } else {
+                settings.push("nodecodesecrets");
+            }
+            let resolve_env = match state.resolve_env() {
+                Some(Some(file)) => format!("resolve-env={}", file),
+                Some(None) => "resolve-env".to_string(),
+                None => "noresolve-env".to_string(),
+            };
+            settings.push(&resolve_env);
+            if state.prometheus() {
+                settings.push("prometheus");
+            } else {
+                settings.push("noprometheus");
+            }
+            if state.auto_validate() {
+                settings.push("autovalidate");
+            } else {
+                settings.push("noautovalidate");
+            }
+            let scrolloff = format!("scrolloff={}", state.scrolloff());
+            settings.push(&scrolloff);
+            let maxdepth = format!("maxdepth={}", state.max_render_depth());
+            settings.push(&maxdepth);
+            let mousescroll = format!("mousescroll={}", state.mouse_scroll_lines());
+            settings.push(&mousescroll);
+            let memorybudget = format!("memorybudget={}", state.memory_budget());
+            settings.push(&memorybudget);
             state.set_message(
                 format!("Settings: {}", settings.join(", ")),
                 MessageLevel::Info,
@@ -1493,6 +2463,58 @@ impl InputHandler {
             return Ok(false);
         }
 
+        // Handle :profile export|import <file>
+        if command == "profile" || command.starts_with("profile ") {
+            let args = command["profile".len()..].trim();
+            let (action, file) = args.split_once(' ').unwrap_or((args, ""));
+            let file = file.trim();
+            match (action, file) {
+                (_, "") => state.set_message(
+                    "Usage: :profile export|import <file>".to_string(),
+                    MessageLevel::Error,
+                ),
+                ("export", file) => match state.export_profile(file) {
+                    Ok(()) => state
+                        .set_message(format!("Profile written to {}", file), MessageLevel::Info),
+                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                },
+                ("import", file) => match state.import_profile(file) {
+                    Ok(import) => {
+                        let mut text = match import.changed.len() {
+                            0 => format!("Imported {}: settings already match", file),
+                            1 => format!("Imported {}: 1 setting changed", file),
+                            n => format!("Imported {}: {} settings changed", file, n),
+                        };
+                        if !import.conflicts.is_empty() {
+                            text.push_str(&format!(
+                                "; replaced your {}",
+                                import.conflicts.join(", ")
+                            ));
+                        }
+                        if !import.unknown.is_empty() {
+                            text.push_str(&format!(
+                                "; ignored unknown {}",
+                                import.unknown.join(", ")
+                            ));
+                        }
+                        text.push_str(" (:set save keeps them)");
+                        let level = if import.conflicts.is_empty() && import.unknown.is_empty() {
+                            MessageLevel::Info
+                        } else {
+                            MessageLevel::Warning
+                        };
+                        state.set_message(text, level);
+                    }
+                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                },
+                _ => state.set_message(
+                    "Usage: :profile export|import <file>".to_string(),
+                    MessageLevel::Error,
+                ),
+            }
+            return Ok(false);
+        }
+
         if command == "set save" {
             // Save current settings to config file
             match state.save_config() {
@@ -1548,6 +2570,115 @@ impl InputHandler {
                         state
                             .set_message(format!("create_backup is {}", value), MessageLevel::Info);
                     }
+                    "resolvemerge" => {
+                        let value = if state.resolve_merge() { "on" } else { "off" };
+                        state.set_message(format!("resolvemerge is {}", value), MessageLevel::Info);
+                    }
+                    "undofile" | "udf" => {
+                        let value = if state.persistent_undo() { "on" } else { "off" };
+                        state.set_message(format!("undofile is {}", value), MessageLevel::Info);
+                    }
+                    "autoread" | "ar" => {
+                        let value = if state.reload_on_change() {
+                            "on"
+                        } else {
+                            "off"
+                        };
+                        state.set_message(format!("autoread is {}", value), MessageLevel::Info);
+                    }
+                    "regexsearch" => {
+                        let value = if state.regex_search() { "on" } else { "off" };
+                        state.set_message(format!("regexsearch is {}", value), MessageLevel::Info);
+                    }
+                    "searchcollapsed" => {
+                        let value = if state.search_collapsed() {
+                            "on"
+                        } else {
+                            "off"
+                        };
+                        state.set_message(
+                            format!("searchcollapsed is {}", value),
+                            MessageLevel::Info,
+                        );
+                    }
+                    "breadcrumbs" => {
+                        let value = if state.show_breadcrumbs() {
+                            "on"
+                        } else {
+                            "off"
+                        };
+                        state.set_message(format!("breadcrumbs is {}", value), MessageLevel::Info);
+                    }
+                    "typebadges" => {
+                        let value = if state.show_type_badges() {
+                            "on"
+                        } else {
+                            "off"
+                        };
+                        state.set_message(format!("typebadges is {}", value), MessageLevel::Info);
+                    }
+                    "cloudformation" | "cfn" => {
+                        let value = if state.cloudformation() { "on" } else { "off" };
+                        state.set_message(
+                            format!("cloudformation is {}", value),
+                            MessageLevel::Info,
+                        );
+                    }
+                    "k8s" | "kubernetes" => {
+                        let value = if state.k8s() { "on" } else { "off" };
+                        state.set_message(format!("k8s is {}", value), MessageLevel::Info);
+                    }
+                    "resolve-env" => {
+                        let value = match state.resolve_env() {
+                            Some(Some(file)) => format!("on ({} and the environment)", file),
+                            Some(None) => "on (the environment)".to_string(),
+                            None => "off".to_string(),
+                        };
+                        state.set_message(format!("resolve-env is {}", value), MessageLevel::Info);
+                    }
+                    "decodesecrets" => {
+                        let value = if state.decode_secrets() { "on" } else { "off" };
+                        state
+                            .set_message(format!("decodesecrets is {}", value), MessageLevel::Info);
+                    }
+                    "prometheus" | "prom" => {
+                        let value = if state.prometheus() { "on" } else { "off" };
+                        state.set_message(format!("prometheus is {}", value), MessageLevel::Info);
+                    }
+                    "autovalidate" | "av" => {
+                        let value = if state.auto_validate() { "on" } else { "off" };
+                        state.set_message(format!("autovalidate is {}", value), MessageLevel::Info);
+                    }
+                    "scrolloff" | "so" => {
+                        state.set_message(
+                            format!("scrolloff={}", state.scrolloff()),
+                            MessageLevel::Info,
+                        );
+                    }
+                    "maxdepth" => {
+                        state.set_message(
+                            format!("maxdepth={}", state.max_render_depth()),
+                            MessageLevel::Info,
+                        );
+                    }
+                    "memorybudget" => {
+                        state.set_message(
+                            format!("memorybudget={}", state.memory_budget()),
+                            MessageLevel::Info,
+                        );
+                    }
+                    "mousescroll" => {
+                        state.set_message(
+                            format!("mousescroll={}", state.mouse_scroll_lines()),
+                            MessageLevel::Info,
+                        );
+                    }
+                    "statusline" | "stl" => {
+                        state.set_message(
+                            format!("statusline={}", state.statusline()),
+                            MessageLevel::Info,
+                        );
+                    }
                     _ => {
                         state.set_message(
                             format!("Unknown setting: {}", setting_name),
@@ -1604,17 +2735,296 @@ impl InputHandler {
                         MessageLevel::Info,
                     );
                 }
-                _ => {
-                    state.set_message(format!("Unknown setting: {}", setting), MessageLevel::Error);
+                "resolvemerge" => {
+                    state.set_resolve_merge(true);
+                    state.set_message(
+                        "Merge key resolution enabled".to_string(),
+                        MessageLevel::Info,
+                    );
                 }
-            }
-            return Ok(false);
-        }
-
-        // Handle :path and :jp commands
-        if let Some(query) = command.strip_prefix("path ") {
-            let query = query.trim();
-            if query.is_empty() {
+                "noresolvemerge" => {
+                    state.set_resolve_merge(false);
+                    state.set_message(
+                        "Merge key resolution disabled".to_string(),
+                        MessageLevel::Info,
+                    );
+                }
+                "undofile" | "udf" => {
+                    state.set_persistent_undo(true);
+                    state.set_message("Persistent undo enabled".to_string(), MessageLevel::Info);
+                }
+                "noundofile" | "noudf" => {
+                    state.set_persistent_undo(false);
+                    state.set_message("Persistent undo disabled".to_string(), MessageLevel::Info);
+                }
+                "autoread" | "ar" => {
+                    state.set_reload_on_change(true);
+                    state.set_message(
+                        "Reload on external change enabled".to_string(),
+                        MessageLevel::Info,
+                    );
+                }
+                "noautoread" | "noar" => {
+                    state.set_reload_on_change(false);
+                    state.set_message(
+                        "Reload on external change disabled".to_string(),
+                        MessageLevel::Info,
+                    );
+                }
+                "regexsearch" => {
+                    state.set_regex_search(true);
+                    state.set_message("Regex search enabled".to_string(), MessageLevel::Info);
+                }
+                "noregexsearch" => {
+                    state.set_regex_search(false);
+                    state.set_message("Regex search disabled".to_string(), MessageLevel::Info);
+                }
+                "searchcollapsed" => {
+                    state.set_search_collapsed(true);
+                    state.set_message(
+                        "Search includes collapsed nodes".to_string(),
+                        MessageLevel::Info,
+                    );
+                }
+                "nosearchcollapsed" => {
+                    state.set_search_collapsed(false);
+                    state.set_message(
+                        "Search limited to visible lines".to_string(),
+                        MessageLevel::Info,
+                    );
+                }
+                "breadcrumbs" => {
+                    state.set_show_breadcrumbs(true);
+                    state.set_message("Breadcrumbs shown".to_string(), MessageLevel::Info);
+                }
+                "nobreadcrumbs" => {
+                    state.set_show_breadcrumbs(false);
+                    state.set_message("Breadcrumbs hidden".to_string(), MessageLevel::Info);
+                }
+                "typebadges" => {
+                    state.set_show_type_badges(true);
+                    state.set_message("Type badges shown".to_string(), MessageLevel::Info);
+                }
+                "notypebadges" => {
+                    state.set_show_type_badges(false);
+                    state.set_message("Type badges hidden".to_string(), MessageLevel::Info);
+                }
+                "cloudformation" | "cfn" => {
+                    state.set_cloudformation(true);
+                    state.set_message("CloudFormation mode on".to_string(), MessageLevel::Info);
+                }
+                "nocloudformation" | "nocfn" => {
+                    state.set_cloudformation(false);
+                    state.set_message("CloudFormation mode off".to_string(), MessageLevel::Info);
+                }
+                "k8s" | "kubernetes" => {
+                    state.set_k8s(true);
+                    state.set_message(
+                        "Kubernetes commands on (:containers, :images, :env)".to_string(),
+                        MessageLevel::Info,
+                    );
+                }
+                "nok8s" | "nokubernetes" => {
+                    state.set_k8s(false);
+                    state.set_message("Kubernetes commands off".to_string(), MessageLevel::Info);
+                }
+                "decodesecrets" => {
+                    state.set_decode_secrets(true);
+                    state.set_message(
+                        "Secret values shown decoded".to_string(),
+                        MessageLevel::Info,
+                    );
+                }
+                "resolve-env" => match state.set_resolve_env(None) {
+                    Ok(count) => state.set_message(
+                        format!(
+                            "Previewing placeholders with {} environment variables",
+                            count
+                        ),
+                        MessageLevel::Info,
+                    ),
+                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                },
+                "noresolve-env" => {
+                    state.clear_resolve_env();
+                    state.set_message("Placeholder preview off".to_string(), MessageLevel::Info);
+                }
+                _ if setting.starts_with("resolve-env=") => {
+                    let (_, file) = setting.split_once('=').unwrap_or_default();
+                    match state.set_resolve_env(Some(file.trim())) {
+                        Ok(count) => state.set_message(
+                            format!(
+                                "Previewing placeholders with {} and the environment ({} variables)",
+                                file.trim(),
+                                count
+                            ),
+                            MessageLevel::Info,
+                        ),
+                        Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                    }
+                }
+                "nodecodesecrets" => {
+                    state.set_decode_secrets(false);
+                    state.set_message(
+                        "Secret values shown as written".to_string(),
+                        MessageLevel::Info,
+                    );
+                }
+                "prometheus" | "prom" => {
+                    state.set_prometheus(true);
+                    state.set_message("Prometheus mode on".to_string(), MessageLevel::Info);
+                }
+                "noprometheus" | "noprom" => {
+                    state.set_prometheus(false);
+                    state.set_message("Prometheus mode off".to_string(), MessageLevel::Info);
+                }
+                "autovalidate" | "av" => {
+                    state.set_auto_validate(true);
+                    let message = if state.schema_path().is_some() {
+                        "Validating against the schema after each edit"
+                    } else {
+                        "Auto-validate on; set a schema with :validate <schema> or a modeline"
+                    };
+                    state.set_message(message.to_string(), MessageLevel::Info);
+                }
+                "noautovalidate" | "noav" => {
+                    state.set_auto_validate(false);
+                    state.set_message("Auto-validate off".to_string(), MessageLevel::Info);
+                }
+                _ if setting.starts_with("scrolloff=") || setting.starts_with("so=") => {
+                    let (_, value) = setting.split_once('=').unwrap_or_default();
+                    match value.trim().parse::<usize>() {
+                        Ok(lines) => {
+                            state.set_scrolloff(lines);
+                            state.set_message(format!("scrolloff={}", lines), MessageLevel::Info);
+                        }
+                        Err(_) => state.set_message(
+                            format!("Invalid scrolloff: {} (expected a number)", value),
+                            MessageLevel::Error,
+                        ),
+                    }
+                }
+                _ if setting.starts_with("maxdepth=") => {
+                    let (_, value) = setting.split_once('=').unwrap_or_default();
+                    match value.trim().parse::<usize>() {
+                        Ok(depth) => {
+                            state.set_max_render_depth(depth);
+                            state.set_message(format!("maxdepth={}", depth), MessageLevel::Info);
+                        }
+                        Err(_) => state.set_message(
+                            format!("Invalid maxdepth: {} (expected a number)", value),
+                            MessageLevel::Error,
+                        ),
+                    }
+                }
+                _ if setting.starts_with("memorybudget=") => {
+                    let (_, value) = setting.split_once('=').unwrap_or_default();
+                    match value.trim().parse::<usize>() {
+                        Ok(megabytes) => {
+                            state.set_memory_budget(megabytes);
+                            if !state.memory_degraded() {
+                                state.set_message(
+                                    format!("memorybudget={}", megabytes),
+                                    MessageLevel::Info,
+                                );
+                            }
+                        }
+                        Err(_) => state.set_message(
+                            format!(
+                                "Invalid memorybudget: {} (expected megabytes, 0 for no limit)",
+                                value
+                            ),
+                            MessageLevel::Error,
+                        ),
+                    }
+                }
+                _ if setting.starts_with("mousescroll=") => {
+                    let (_, value) = setting.split_once('=').unwrap_or_default();
+                    match value.trim().parse::<usize>() {
+                        Ok(lines) if lines > 0 => {
+                            state.set_mouse_scroll_lines(lines);
+                            state.set_message(format!("mousescroll={}", lines), MessageLevel::Info);
+                        }
+                        _ => state.set_message(
+                            format!(
+                                "Invalid mousescroll: {} (expected a positive number)",
+                                value
+                            ),
+                            MessageLevel::Error,
+                        ),
+                    }
+                }
+                _ if setting.starts_with("statusline=") || setting.starts_with("stl=") => {
+                    let (_, value) = setting.split_once('=').unwrap_or_default();
+                    state.set_statusline(value.to_string());
+                    state.set_message(format!("statusline={}", value), MessageLevel::Info);
+                }
+                _ => {
+                    state.set_message(format!("Unknown setting: {}", setting), MessageLevel::Error);
+                }
+            }
+            return Ok(false);
+        }
+
+        // Handle :%s/pattern/replacement/flags
+        if command.starts_with("%s") {
+            if let Err(e) = state.substitute(command) {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+
+        // Handle :path-set <query> <value> and :path-delete <query>
+        if let Some(args) = command.strip_prefix("path-set ") {
+            let (query, value) = crate::yamlpath::Parser::split_query(args);
+            if query.is_empty() || value.is_empty() {
+                state.set_message(
+                    "Usage: :path-set <yamlpath> <value>".to_string(),
+                    MessageLevel::Error,
+                );
+                return Ok(false);
+            }
+            match state.path_set(query, value) {
+                Ok(0) => state.set_message(
+                    format!("No scalar matches for {}", query),
+                    MessageLevel::Warning,
+                ),
+                Ok(count) => state.set_message(
+                    format!(
+                        "Set {} {} to {}",
+                        count,
+                        if count == 1 { "node" } else { "nodes" },
+                        value
+                    ),
+                    MessageLevel::Info,
+                ),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+        if let Some(query) = command.strip_prefix("path-delete ") {
+            let query = query.trim();
+            match state.path_delete(query) {
+                Ok(0) => {
+                    state.set_message(format!("No matches for {}", query), MessageLevel::Warning)
+                }
+                Ok(count) => state.set_message(
+                    format!(
+                        "Deleted {} {}",
+                        count,
+                        if count == 1 { "node" } else { "nodes" }
+                    ),
+                    MessageLevel::Info,
+                ),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+
+        // Handle :path and :jp commands
+        if let Some(query) = command.strip_prefix("path ") {
+            let query = query.trim();
+            if query.is_empty() {
                 state.set_message("Usage: :path <jsonpath>".to_string(), MessageLevel::Error);
             } else {
                 state.execute_jsonpath_search(query);
@@ -1622,39 +3032,1098 @@ impl InputHandler {
             return Ok(false);
         }
 
-        if let Some(query) = command.strip_prefix("jp ") {
-            let query = query.trim();
-            if query.is_empty() {
-                state.set_message("Usage: :jp <jsonpath>".to_string(), MessageLevel::Error);
-            } else {
-                state.execute_jsonpath_search(query);
+        if let Some(query) = command.strip_prefix("jp ") {
+            let query = query.trim();
+            if query.is_empty() {
+                state.set_message("Usage: :jp <jsonpath>".to_string(), MessageLevel::Error);
+            } else {
+                state.execute_jsonpath_search(query);
+            }
+            return Ok(false);
+        }
+
+        // Handle :find command with query
+        if let Some(query) = command.strip_prefix("find ") {
+            let query = query.trim();
+            if query.is_empty() {
+                state.set_mode(EditorMode::Search);
+                state.set_search_forward(true);
+                state.clear_search_buffer();
+            } else {
+                // Execute text search immediately
+                state.clear_search_buffer();
+                for ch in query.chars() {
+                    state.push_to_search_buffer(ch);
+                }
+                if let Err(e) = state.execute_search() {
+                    state.set_message(e.to_string(), MessageLevel::Error);
+                }
+            }
+            return Ok(false);
+        }
+
+        // Handle :find with no arguments (enter search mode)
+        if command == "find" {
+            state.set_mode(EditorMode::Search);
+            state.set_search_forward(true);
+            state.clear_search_buffer();
+            return Ok(false);
+        }
+
+        // Handle :validate command
+        if command == "validate" || command.starts_with("validate ") {
+            let argument = command["validate".len()..].trim();
+            let schema_path = if argument.is_empty() {
+                state.schema_path().map(|s| s.to_string())
+            } else {
+                Some(argument.to_string())
+            };
+            match schema_path {
+                Some(schema_path) => {
+                    if let Err(e) = state.validate_schema(&schema_path) {
+                        state.set_message(format!("Validation failed: {}", e), MessageLevel::Error);
+                    }
+                }
+                None => {
+                    state.set_message(
+                        "Usage: :validate <schema.json>".to_string(),
+                        MessageLevel::Error,
+                    );
+                }
+            }
+            return Ok(false);
+        }
+
+        // Handle :vsplit <file>
+        if command == "vsplit" || command == "vs" {
+            state.set_message("Usage: :vsplit <filename>".to_string(), MessageLevel::Error);
+            return Ok(false);
+        }
+        if let Some(filename) = command
+            .strip_prefix("vsplit ")
+            .or_else(|| command.strip_prefix("vs "))
+        {
+            use crate::editor::window::WindowCommand;
+            state.request_window_command(WindowCommand::VSplit(filename.trim().to_string()));
+            return Ok(false);
+        }
+
+        // Handle :close and :only
+        if command == "close" || command == "clo" {
+            state.request_window_command(crate::editor::window::WindowCommand::Close);
+            return Ok(false);
+        }
+        if command == "only" || command == "on" {
+            state.request_window_command(crate::editor::window::WindowCommand::Only);
+            return Ok(false);
+        }
+
+        // Handle :bookmark add/rm <name>, :bookmark <name> and the :bookmarks picker
+        if command == "bookmarks" || command == "bookmark" || command.starts_with("bookmark ") {
+            let args: Vec<&str> = command.split_whitespace().skip(1).collect();
+            let result = match args.as_slice() {
+                [] => state.open_bookmark_picker(),
+                ["add", name] => state.add_bookmark(name).map(|_| {
+                    state.set_message(format!("Bookmark {} added", name), MessageLevel::Info)
+                }),
+                ["rm" | "del", name] => state.remove_bookmark(name).map(|_| {
+                    state.set_message(format!("Bookmark {} removed", name), MessageLevel::Info)
+                }),
+                [name] => state.go_to_bookmark(name),
+                _ => Err(anyhow::anyhow!(
+                    "Usage: :bookmark add <name> | rm <name> | <name>"
+                )),
+            };
+            if let Err(e) = result {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+
+        // Handle :facet <field> (value counts across records)
+        if let Some(rest) = command.strip_prefix("facet") {
+            if rest.is_empty() || rest.starts_with(' ') {
+                let field = rest.trim();
+                if field.is_empty() {
+                    state.set_message("Usage: :facet <field>".to_string(), MessageLevel::Error);
+                } else if let Err(e) = state.open_facet_panel(field) {
+                    state.set_message(e.to_string(), MessageLevel::Error);
+                }
+                return Ok(false);
+            }
+        }
+
+        // Handle :sample (show a sample of the records)
+        if let Some(rest) = command.strip_prefix("sample") {
+            if rest.is_empty() || rest.starts_with(' ') {
+                use crate::editor::sample::{random_seed, Sample};
+
+                match rest.trim() {
+                    "" => {
+                        let text = match state.sample_info() {
+                            Some(info) => format!(
+                                "Showing {} of {} records ({} sample)",
+                                info.shown,
+                                info.total,
+                                info.sample.mode.name()
+                            ),
+                            None => "Showing every record".to_string(),
+                        };
+                        state.set_message(text, MessageLevel::Info);
+                    }
+                    "off" | "all" => match state.load_all_records() {
+                        Some(total) => state.set_message(
+                            format!("Loaded all {} records", total),
+                            MessageLevel::Info,
+                        ),
+                        None => state.set_message("Not sampled".to_string(), MessageLevel::Info),
+                    },
+                    args => {
+                        match Sample::parse(args)
+                            .and_then(|sample| state.apply_sample(sample, random_seed()))
+                        {
+                            Ok(info) => state.set_message(
+                                format!(
+                                    "Showing {} of {} records (:sample off loads all)",
+                                    info.shown, info.total
+                                ),
+                                MessageLevel::Info,
+                            ),
+                            Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                        }
+                    }
+                }
+                return Ok(false);
+            }
+        }
+
+        // Handle :filter <query> (show only matching subtrees)
+        if let Some(rest) = command.strip_prefix("filter") {
+            if rest.is_empty() || rest.starts_with(' ') {
+                let query = rest.trim();
+                if query.is_empty() {
+                    state.set_message(
+                        "Usage: :filter <yamlpath or pattern>".to_string(),
+                        MessageLevel::Error,
+                    );
+                } else {
+                    match state.filter_view(query) {
+                        Ok(count) => state.set_message(
+                            format!("{} match(es) shown (:nofilter to show all)", count),
+                            MessageLevel::Info,
+                        ),
+                        Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                    }
+                }
+                return Ok(false);
+            }
+        }
+
+        // Handle :nofilter (show the whole tree again)
+        if command == "nofilter" {
+            if state.clear_view_filter() {
+                state.set_message("Filter cleared".to_string(), MessageLevel::Info);
+            } else {
+                state.set_message("No filter active".to_string(), MessageLevel::Info);
+            }
+            return Ok(false);
+        }
+
+        // Handle :type <type> (coerce the scalar at the cursor)
+        if let Some(rest) = command.strip_prefix("type") {
+            if rest.is_empty() || rest.starts_with(' ') {
+                use crate::editor::coerce::ScalarType;
+                match ScalarType::parse(rest.trim()) {
+                    Some(target) => match state.change_type_at_cursor(target) {
+                        Ok(()) => state.set_message(
+                            format!("Changed to {}", target.name()),
+                            MessageLevel::Info,
+                        ),
+                        Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                    },
+                    None => state.set_message(
+                        "Usage: :type str|int|float|bool|null".to_string(),
+                        MessageLevel::Error,
+                    ),
+                }
+                return Ok(false);
+            }
+        }
+
+        // Handle :tag [<tag>] and :notag / :tag! (explicit YAML tag at the cursor)
+        if command == "notag" || command == "tag!" {
+            match state.set_tag_at_cursor(None) {
+                Ok(()) => state.set_message("Tag removed".to_string(), MessageLevel::Info),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+        if let Some(rest) = command.strip_prefix("tag") {
+            if rest.is_empty() || rest.starts_with(' ') {
+                let tag = rest.trim();
+                if tag.is_empty() {
+                    let current = state
+                        .tree()
+                        .get_node(state.cursor().path())
+                        .and_then(|node| node.tag().map(str::to_string));
+                    let text = match current {
+                        Some(tag) => format!("Tag: {}", tag),
+                        None => "No tag (usage: :tag !Ref, :notag to remove)".to_string(),
+                    };
+                    state.set_message(text, MessageLevel::Info);
+                } else {
+                    match state.set_tag_at_cursor(Some(tag.to_string())) {
+                        Ok(()) => state.set_message(format!("Tagged {}", tag), MessageLevel::Info),
+                        Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                    }
+                }
+                return Ok(false);
+            }
+        }
+
+        // Handle :pin (pin the cursor's node to the pin panel)
+        if command == "pin" {
+            match state.pin_cursor() {
+                Ok(number) => state.set_message(
+                    format!("Pinned as {} (g{} jumps here)", number, number),
+                    MessageLevel::Info,
+                ),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+
+        // Handle :unpin [N|all]
+        if let Some(rest) = command.strip_prefix("unpin") {
+            if rest.is_empty() || rest.starts_with(' ') {
+                let result = match rest.trim() {
+                    "" => state.unpin(None).map(|_| "Unpinned".to_string()),
+                    "all" | "!" => Ok(format!("Removed {} pin(s)", state.clear_pins())),
+                    arg => match arg.parse::<usize>() {
+                        Ok(n) => state.unpin(Some(n)).map(|_| format!("Unpinned {}", n)),
+                        Err(_) => Err(anyhow::anyhow!("Usage: :unpin [N|all]")),
+                    },
+                };
+                match result {
+                    Ok(message) => state.set_message(message, MessageLevel::Info),
+                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                }
+                return Ok(false);
+            }
+        }
+
+        // Handle :watch [yamlpath] (the cursor's path when none is given)
+        if let Some(rest) = command.strip_prefix("watch") {
+            if rest.is_empty() || rest.starts_with(' ') {
+                let expression = match rest.trim() {
+                    "" if state.cursor().path().is_empty() => None,
+                    "" => state.path_to_string(state.cursor().path(), "bracket"),
+                    query => Some(query.to_string()),
+                };
+                let result = match expression {
+                    Some(expression) => state
+                        .add_watch(&expression)
+                        .map(|n| format!("Watching {} as {}", expression, n)),
+                    None => Err(anyhow::anyhow!("Usage: :watch <yamlpath>")),
+                };
+                match result {
+                    Ok(message) => state.set_message(message, MessageLevel::Info),
+                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                }
+                return Ok(false);
+            }
+        }
+
+        // Handle :unwatch N|all
+        if let Some(rest) = command.strip_prefix("unwatch") {
+            if rest.is_empty() || rest.starts_with(' ') {
+                let result = match rest.trim() {
+                    "all" | "!" => Ok(format!("Removed {} watch(es)", state.clear_watches())),
+                    arg => match arg.parse::<usize>() {
+                        Ok(n) => state
+                            .remove_watch(n)
+                            .map(|w| format!("Stopped watching {}", w.expression())),
+                        Err(_) => Err(anyhow::anyhow!("Usage: :unwatch N|all")),
+                    },
+                };
+                match result {
+                    Ok(message) => state.set_message(message, MessageLevel::Info),
+                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                }
+                return Ok(false);
+            }
+        }
+
+        // Handle :tasks (TODO/FIXME panel)
+        // Handle :memory
+        if command == "memory" || command == "mem" {
+            state.show_memory();
+            return Ok(false);
+        }
+
+        // Handle the quickfix list: :copen [diagnostics|search], :cclose,
+        // :cnext and :cprev
+        if command == "copen" || command.starts_with("copen ") {
+            let source = command["copen".len()..].trim();
+            if let Err(e) = state.open_quickfix((!source.is_empty()).then_some(source)) {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+        if command == "cclose" || command == "ccl" {
+            state.close_quickfix_panel();
+            return Ok(false);
+        }
+        if matches!(
+            command,
+            "cnext" | "cn" | "cprev" | "cp" | "cprevious" | "cN"
+        ) {
+            match state.quickfix_step(matches!(command, "cnext" | "cn")) {
+                Ok(message) => state.set_message(message, MessageLevel::Info),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+
+        // Handle :dedupe (prompts for each repeated key)
+        if command == "dedupe" {
+            if let Err(e) = state.start_dedupe() {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+
+        // Handle :lint, :lint enable|disable <rule> and :lint rules
+        if command == "lint" {
+            match state.run_lint() {
+                Ok(0) => state.set_message("No lint problems".to_string(), MessageLevel::Info),
+                Ok(count) => state.set_message(
+                    format!("{} lint problem(s) (]q/[q to step through)", count),
+                    MessageLevel::Warning,
+                ),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+        if let Some(args) = command.strip_prefix("lint ") {
+            let mut words = args.split_whitespace();
+            match (words.next(), words.next(), words.next()) {
+                (Some("rules"), None, _) => {
+                    let rules: Vec<_> = state
+                        .lint_rules()
+                        .into_iter()
+                        .map(|(rule, enabled)| {
+                            if enabled {
+                                rule.name().to_string()
+                            } else {
+                                format!("no{}", rule.name())
+                            }
+                        })
+                        .collect();
+                    state.set_message(rules.join(" "), MessageLevel::Info);
+                }
+                (Some(action @ ("enable" | "disable")), Some(rule), None) => {
+                    let enable = action == "enable";
+                    let done = if enable { "Enabled" } else { "Disabled" };
+                    match state.set_lint_rule(rule, enable) {
+                        Ok(()) => state.set_message(
+                            format!("{} {} (:set save keeps it)", done, rule),
+                            MessageLevel::Info,
+                        ),
+                        Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                    }
+                }
+                _ => state.set_message(
+                    "Usage: :lint [rules|enable <rule>|disable <rule>]".to_string(),
+                    MessageLevel::Error,
+                ),
+            }
+            return Ok(false);
+        }
+
+        if command == "tasks" || command == "todo" {
+            if let Err(e) = state.open_task_list() {
+                state.set_message(e.to_string(), MessageLevel::Info);
+            }
+            return Ok(false);
+        }
+
+        // Handle :sort, :sort! (reverse) and :sort r (recursive)
+        if let Some(rest) = command.strip_prefix("sort") {
+            let (reverse, flags) = match rest.strip_prefix('!') {
+                Some(flags) => (true, flags),
+                None => (false, rest),
+            };
+            if flags.is_empty() || flags.starts_with(' ') {
+                let result = match flags.trim() {
+                    "" => state.sort_keys_at_cursor(reverse, false),
+                    "r" => state.sort_keys_at_cursor(reverse, true),
+                    _ => Err(anyhow::anyhow!("Usage: :sort[!] [r]")),
+                };
+                if let Err(e) = result {
+                    state.set_message(e.to_string(), MessageLevel::Error);
+                }
+                return Ok(false);
+            }
+        }
+
+        // Handle :sum, :count, :min, :max and :avg [yamlpath]
+        let (name, query) = command.split_once(' ').unwrap_or((command, ""));
+        if let Some(aggregate) = crate::editor::aggregate::Aggregate::from_name(name) {
+            let query = Some(query.trim()).filter(|q| !q.is_empty());
+            match state.aggregate(aggregate, query) {
+                Ok((result, n)) => state.set_message(
+                    format!(
+                        "{} = {} ({} number{}, yanked)",
+                        aggregate.name(),
+                        result,
+                        n,
+                        if n == 1 { "" } else { "s" }
+                    ),
+                    MessageLevel::Info,
+                ),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+
+        // Handle :preview-field [add|remove <field> | clear]
+        if command == "preview-field" || command.starts_with("preview-field ") {
+            let args: Vec<&str> = command.split_whitespace().skip(1).collect();
+            match args.as_slice() {
+                [] => {
+                    let fields = state.preview_fields();
+                    let text = if fields.is_empty() {
+                        "No preview fields (:preview-field add <field>)".to_string()
+                    } else {
+                        format!("Preview fields: {}", fields.join(", "))
+                    };
+                    state.set_message(text, MessageLevel::Info);
+                }
+                ["add", field] => {
+                    if state.add_preview_field(field) {
+                        state.set_message(format!("Pinned {}", field), MessageLevel::Info);
+                    } else {
+                        state.set_message(
+                            format!("{} is already pinned", field),
+                            MessageLevel::Warning,
+                        );
+                    }
+                }
+                ["remove" | "rm", field] => {
+                    if state.remove_preview_field(field) {
+                        state.set_message(format!("Unpinned {}", field), MessageLevel::Info);
+                    } else {
+                        state.set_message(format!("{} is not pinned", field), MessageLevel::Error);
+                    }
+                }
+                ["clear"] => {
+                    state.set_preview_fields(Vec::new());
+                    state.set_message("Preview fields cleared".to_string(), MessageLevel::Info);
+                }
+                _ => state.set_message(
+                    "Usage: :preview-field [add|remove <field> | clear]".to_string(),
+                    MessageLevel::Error,
+                ),
+            }
+            return Ok(false);
+        }
+
+        // Handle :filldown <key> over the visual selection
+        if let Some(key) = command.strip_prefix("filldown") {
+            if key.is_empty() || key.starts_with(' ') {
+                match key.trim() {
+                    "" => {
+                        state.set_message("Usage: :filldown <key>".to_string(), MessageLevel::Error)
+                    }
+                    key => match state.fill_down(key) {
+                        Ok(n) => state.set_message(
+                            format!(
+                                "Filled '{}' into {} element{}",
+                                key,
+                                n,
+                                if n == 1 { "" } else { "s" }
+                            ),
+                            MessageLevel::Info,
+                        ),
+                        Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                    },
+                }
+                return Ok(false);
+            }
+        }
+
+        // Handle :time +1d|-2h|iso|utc|date|epoch and :now [format]
+        if let Some(change) = command.strip_prefix("time ") {
+            match state.change_time_at_cursor(change) {
+                Ok(value) => {
+                    state.set_message(format!("Time set to {}", value), MessageLevel::Info)
+                }
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+        if command == "time" {
+            state.set_message(
+                "Usage: :time +1d|-2h30m|iso|utc|date|epoch".to_string(),
+                MessageLevel::Error,
+            );
+            return Ok(false);
+        }
+        if command == "now" || command.starts_with("now ") {
+            use crate::editor::datetime::Format;
+            let name = command["now".len()..].trim();
+            let format = if name.is_empty() {
+                Some(Format::Utc)
+            } else {
+                Format::parse(name)
+            };
+            match format {
+                Some(format) => match state.set_now_at_cursor(format) {
+                    Ok(value) => {
+                        state.set_message(format!("Time set to {}", value), MessageLevel::Info)
+                    }
+                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                },
+                None => state.set_message(
+                    "Usage: :now [iso|utc|date|epoch]".to_string(),
+                    MessageLevel::Error,
+                ),
+            }
+            return Ok(false);
+        }
+
+        // Handle :b64 encode|decode
+        if command == "b64" || command.starts_with("b64 ") {
+            let result = match command["b64".len()..].trim() {
+                "encode" | "e" => state
+                    .encode_base64_at_cursor()
+                    .map(|_| "Encoded as base64".to_string()),
+                "decode" | "d" => state
+                    .decode_base64_at_cursor()
+                    .map(|_| "Decoded from base64".to_string()),
+                _ => Err(anyhow::anyhow!("Usage: :b64 encode|decode")),
+            };
+            match result {
+                Ok(message) => state.set_message(message, MessageLevel::Info),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+
+        // Handle :bump major|minor|patch
+        if command == "bump" || command.starts_with("bump ") {
+            use crate::editor::semver::Part;
+            match Part::parse(command["bump".len()..].trim()) {
+                Some(part) => match state.bump_version_at_cursor(part) {
+                    Ok(version) => {
+                        state.set_message(format!("Bumped to {}", version), MessageLevel::Info)
+                    }
+                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                },
+                None => state.set_message(
+                    "Usage: :bump major|minor|patch".to_string(),
+                    MessageLevel::Error,
+                ),
+            }
+            return Ok(false);
+        }
+
+        // Handle :join [separator] and :split-string [delimiter]; quotes keep spaces
+        for (name, join) in [("join", true), ("split-string", false)] {
+            let Some(argument) = command.strip_prefix(name) else {
+                continue;
+            };
+            if !argument.is_empty() && !argument.starts_with(' ') {
+                continue;
+            }
+            let argument = argument.trim();
+            let argument = ['"', '\'']
+                .iter()
+                .find_map(|q| argument.strip_prefix(*q)?.strip_suffix(*q))
+                .unwrap_or(argument);
+            let result = if join {
+                let separator = if argument.is_empty() { " " } else { argument };
+                state
+                    .join_list_at_cursor(separator)
+                    .map(|n| format!("Joined {} items", n))
+            } else {
+                state
+                    .split_string_at_cursor(argument)
+                    .map(|n| format!("Split into {} items", n))
+            };
+            match result {
+                Ok(message) => state.set_message(message, MessageLevel::Info),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+
+        // Handle :change <value> over the visual selection
+        if command == "change" {
+            state.set_message("Usage: :change <value>".to_string(), MessageLevel::Error);
+            return Ok(false);
+        }
+        if let Some(value) = command.strip_prefix("change ") {
+            match state.change_selection(value.trim()) {
+                Ok(n) => state.set_message(
+                    format!("Changed {} value{}", n, if n == 1 { "" } else { "s" }),
+                    MessageLevel::Info,
+                ),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+
+        // Handle :rename <template> over the visual selection
+        if command == "rename" {
+            state.set_message("Usage: :rename <template>".to_string(), MessageLevel::Error);
+            return Ok(false);
+        }
+        if let Some(template) = command.strip_prefix("rename ") {
+            match state.rename_selection(template.trim()) {
+                Ok(n) => state.set_message(
+                    format!("Renamed {} key{}", n, if n == 1 { "" } else { "s" }),
+                    MessageLevel::Info,
+                ),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+
+        // Handle :import env [file] [--filter <regex>]
+        if command == "import" || command.starts_with("import ") {
+            let mut words = command.split_whitespace().skip(1);
+            let mut file = None;
+            let mut filter = None;
+            let mut usage = words.next() != Some("env");
+            while let Some(word) = words.next() {
+                match word {
+                    "--filter" => filter = words.next(),
+                    _ if word.starts_with("--filter=") => filter = word.strip_prefix("--filter="),
+                    _ if file.is_none() => file = Some(word),
+                    _ => usage = true,
+                }
+            }
+            if usage {
+                state.set_message(
+                    "Usage: :import env [file] [--filter <regex>]".to_string(),
+                    MessageLevel::Error,
+                );
+                return Ok(false);
+            }
+            match state.import_env(file, filter) {
+                Ok((added, updated)) => state.set_message(
+                    format!("Imported {} new, {} updated", added, updated),
+                    MessageLevel::Info,
+                ),
+                Err(e) => state.set_message(format!("{:#}", e), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+
+        // Handle :usages [value|project] for scalar values, and
+        // :usages [chart-dir] and :usages unused [chart-dir] for Helm values
+        if command == "usages" || command.starts_with("usages ") {
+            let args: Vec<&str> = command.split_whitespace().skip(1).collect();
+            let value_search = match args.as_slice() {
+                [] => state.usages_searches_value().then_some(false),
+                ["value"] => Some(false),
+                ["project"] => Some(true),
+                _ => None,
+            };
+            if let Some(project) = value_search {
+                match state.find_value_usages(project) {
+                    Ok((value, 0)) => {
+                        state.set_message(format!("No other uses of {}", value), MessageLevel::Info)
+                    }
+                    Ok((value, count)) => state.set_message(
+                        format!(
+                            "{} other use{} of {}",
+                            count,
+                            if count == 1 { "" } else { "s" },
+                            value
+                        ),
+                        MessageLevel::Info,
+                    ),
+                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                }
+                return Ok(false);
+            }
+            match args.as_slice() {
+                ["unused", rest @ ..] if rest.len() <= 1 => {
+                    match state.flag_unused_values(rest.first().copied()) {
+                        Ok(unused) if unused.is_empty() => state.set_message(
+                            "Every values key is used by a template".to_string(),
+                            MessageLevel::Info,
+                        ),
+                        Ok(unused) => state.set_message(
+                            format!(
+                                "{} unused values key{} marked W: {}",
+                                unused.len(),
+                                if unused.len() == 1 { "" } else { "s" },
+                                unused.join(", ")
+                            ),
+                            MessageLevel::Warning,
+                        ),
+                        Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                    }
+                }
+                [] | [_] => match state.helm_usages(args.first().copied()) {
+                    Ok(usages) if usages.is_empty() => state.set_message(
+                        "Not used by any template".to_string(),
+                        MessageLevel::Warning,
+                    ),
+                    Ok(usages) => {
+                        let mut locations: Vec<String> = Vec::new();
+                        for usage in &usages {
+                            let location = format!("{}:{}", usage.file, usage.line);
+                            if !locations.contains(&location) {
+                                locations.push(location);
+                            }
+                        }
+                        state.set_message(
+                            format!("Used in {}", locations.join(", ")),
+                            MessageLevel::Info,
+                        )
+                    }
+                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                },
+                _ => state.set_message(
+                    "Usage: :usages [value|project] or :usages [unused] [chart-dir]".to_string(),
+                    MessageLevel::Error,
+                ),
+            }
+            return Ok(false);
+        }
+
+        // Handle :wrap-array and :wrap-object <key>
+        if command == "wrap-array"
+            || command == "wrap-object"
+            || command.starts_with("wrap-object ")
+        {
+            let key = command.strip_prefix("wrap-object").map(str::trim);
+            let result = match key {
+                Some("") => Err(anyhow::anyhow!("Usage: :wrap-object <key>")),
+                Some(key) => state
+                    .wrap_node(Some(key))
+                    .map(|_| format!("Wrapped under {}", key)),
+                None => state
+                    .wrap_node(None)
+                    .map(|_| "Wrapped in a list".to_string()),
+            };
+            match result {
+                Ok(message) => state.set_message(message, MessageLevel::Info),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
             }
             return Ok(false);
         }
 
-        // Handle :find command with query
-        if let Some(query) = command.strip_prefix("find ") {
-            let query = query.trim();
-            if query.is_empty() {
-                state.set_mode(EditorMode::Search);
-                state.set_search_forward(true);
-                state.clear_search_buffer();
+        // Handle :unwrap and :splice
+        if command == "unwrap" || command == "splice" {
+            let result = if command == "unwrap" {
+                state.unwrap_node().map(|_| "Unwrapped".to_string())
             } else {
-                // Execute text search immediately
-                state.clear_search_buffer();
-                for ch in query.chars() {
-                    state.push_to_search_buffer(ch);
+                state
+                    .splice_node()
+                    .map(|n| format!("Spliced {} child{}", n, if n == 1 { "" } else { "ren" }))
+            };
+            match result {
+                Ok(message) => state.set_message(message, MessageLevel::Info),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+
+        // Handle :extract <name> [key] (define a repeated value once)
+        if command == "extract" || command.starts_with("extract ") {
+            let args: Vec<&str> = command.split_whitespace().skip(1).collect();
+            match args.as_slice() {
+                [name] | [name, _] => match state.preview_extract(name, args.get(1).copied()) {
+                    Ok((value, count)) => state.set_message(
+                        format!(
+                            "Extract {} as &{}: {} use{} become{} *{} (Enter applies)",
+                            value,
+                            name,
+                            count,
+                            if count == 1 { "" } else { "s" },
+                            if count == 1 { "s" } else { "" },
+                            name
+                        ),
+                        MessageLevel::Info,
+                    ),
+                    Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                },
+                _ => state.set_message(
+                    "Usage: :extract <name> [key]".to_string(),
+                    MessageLevel::Error,
+                ),
+            }
+            return Ok(false);
+        }
+
+        // Handle :graph (CI pipeline jobs)
+        if command == "graph" {
+            if let Err(e) = state.open_pipeline_graph() {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+
+        // Handle :marks
+        if command == "backups" {
+            if let Err(e) = state.open_backup_picker() {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+
+        if command == "marks" {
+            if let Err(e) = state.open_mark_picker() {
+                state.set_message(e.to_string(), MessageLevel::Info);
+            }
+            return Ok(false);
+        }
+
+        // Handle :registers (also :reg and :display, as in vim)
+        if matches!(command, "registers" | "reg" | "display" | "di") {
+            if let Err(e) = state.open_registers() {
+                state.set_message(e.to_string(), MessageLevel::Info);
+            }
+            return Ok(false);
+        }
+
+        // Handle the Kubernetes commands :containers, :images and :env
+        if command == "containers" {
+            if let Err(e) = state.open_container_picker() {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+        if command == "images" || command.starts_with("images ") {
+            let args: Vec<&str> = command.split_whitespace().skip(1).collect();
+            let result = match args.as_slice() {
+                [spec] => match spec.split_once('=') {
+                    Some((image, tag)) if !image.is_empty() && !tag.is_empty() => {
+                        state.set_image_tags(Some(image), tag)
+                    }
+                    Some(_) => Err(anyhow::anyhow!("Usage: :images [<image>=]<tag>")),
+                    None => state.set_image_tags(None, spec),
+                },
+                _ => Err(anyhow::anyhow!("Usage: :images [<image>=]<tag>")),
+            };
+            match result {
+                Ok(n) => state.set_message(
+                    format!("Retagged {} image{}", n, if n == 1 { "" } else { "s" }),
+                    MessageLevel::Info,
+                ),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+        if command == "env" || command.starts_with("env ") {
+            let assignment = command["env".len()..].trim();
+            match assignment.split_once('=') {
+                Some((name, value)) if !name.trim().is_empty() => {
+                    let name = name.trim();
+                    match state.set_container_env(name, value) {
+                        Ok(added) => state.set_message(
+                            format!("{} {}", if added { "Added" } else { "Set" }, name),
+                            MessageLevel::Info,
+                        ),
+                        Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+                    }
                 }
-                state.execute_search();
+                _ => state.set_message(
+                    "Usage: :env <NAME>=<value>".to_string(),
+                    MessageLevel::Error,
+                ),
             }
             return Ok(false);
         }
 
-        // Handle :find with no arguments (enter search mode)
-        if command == "find" {
-            state.set_mode(EditorMode::Search);
-            state.set_search_forward(true);
-            state.clear_search_buffer();
+        // Handle :stage [path], :unstage and :w --staged
+        if command == "stage" || command.starts_with("stage ") {
+            let query = command
+                .strip_prefix("stage")
+                .map(str::trim)
+                .filter(|q| !q.is_empty());
+            match state.stage(query) {
+                Ok(n) => state.set_message(
+                    format!("Staged {} change{}", n, if n == 1 { "" } else { "s" }),
+                    MessageLevel::Info,
+                ),
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Warning),
+            }
+            return Ok(false);
+        }
+        if command == "unstage" {
+            state.unstage();
+            state.set_message("Staging cleared".to_string(), MessageLevel::Info);
+            return Ok(false);
+        }
+        if command == "w --staged" {
+            if state.block_conflicting_save(command) {
+                return Ok(false);
+            }
+            match state.write_staged() {
+                Ok((written, left)) => {
+                    let filename = state.filename().unwrap_or_default().to_string();
+                    state.set_message(
+                        format!(
+                            "\"{}\" written: {} staged change{}, {} left unsaved",
+                            filename,
+                            written,
+                            if written == 1 { "" } else { "s" },
+                            left
+                        ),
+                        MessageLevel::Info,
+                    );
+                }
+                Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+
+        // Handle :review and :review head
+        if command == "review" || command == "review head" {
+            if let Err(e) = state.start_review(command.ends_with("head")) {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+
+        // Handle :note <text>, :note rm, :note (show) and :notes (list)
+        if command == "notes" {
+            if let Err(e) = state.show_all_notes() {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+        if command == "note" || command.starts_with("note ") {
+            let result = match command["note".len()..].trim() {
+                "" => state.show_note_at_cursor(),
+                "rm" | "del" => state
+                    .remove_note()
+                    .map(|_| state.set_message("Note removed".to_string(), MessageLevel::Info)),
+                text => state
+                    .set_note(text)
+                    .map(|_| state.set_message("Note added".to_string(), MessageLevel::Info)),
+            };
+            if let Err(e) = result {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+
+        // Handle :repl command
+        if command == "repl" {
+            state.open_repl();
+            return Ok(false);
+        }
+
+        // Handle :edit-external (same as ge)
+        if command == "edit-external" {
+            if let Err(e) = state.request_external_edit() {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+
+        // Handle :!cmd (filter the node) and :%!cmd (filter the document)
+        let filter = match command.strip_prefix("%!") {
+            Some(filter) => Some((filter, true)),
+            None => command.strip_prefix('!').map(|filter| (filter, false)),
+        };
+        if let Some((filter, whole_document)) = filter {
+            if let Err(e) = state.request_filter(filter, whole_document) {
+                state.set_message(e.to_string(), MessageLevel::Error);
+            }
+            return Ok(false);
+        }
+
+        // Handle :share [port|host:port], :share stop
+        if command == "share" || command.starts_with("share ") {
+            match command["share".len()..].trim() {
+                "stop" => {
+                    let message = if state.stop_share() {
+                        "Sharing stopped"
+                    } else {
+                        "Not sharing"
+                    };
+                    state.set_message(message.to_string(), MessageLevel::Info);
+                }
+                "" if state.share_url().is_some() => {
+                    let url = state.share_url().unwrap_or_default();
+                    state.set_message(format!("Sharing at {}", url), MessageLevel::Info);
+                }
+                addr => {
+                    let addr = Some(addr).filter(|a| !a.is_empty());
+                    match state.start_share(addr) {
+                        Ok(url) => state.set_message(
+                            format!("Sharing read-only view at {} (:share stop to end)", url),
+                            MessageLevel::Info,
+                        ),
+                        Err(e) => state.set_message(format!("{:#}", e), MessageLevel::Error),
+                    }
+                }
+            }
+            return Ok(false);
+        }
+
+        // Handle :export command
+        if command == "export" || command.starts_with("export ") {
+            let mut args = command["export".len()..].split_whitespace();
+            match (args.next(), args.next()) {
+                (Some("html"), Some(path)) => match state.export_html(path) {
+                    Ok(()) => {
+                        state.set_message(format!("Exported to {}", path), MessageLevel::Info)
+                    }
+                    Err(e) => state.set_message(format!("{}", e), MessageLevel::Error),
+                },
+                (Some("outline"), Some(path)) => {
+                    let result = OutlineOptions::from_args(args)
+                        .and_then(|options| state.export_outline(path, &options));
+                    match result {
+                        Ok(()) => {
+                            state.set_message(format!("Exported to {}", path), MessageLevel::Info)
+                        }
+                        Err(e) => state.set_message(format!("{}", e), MessageLevel::Error),
+                    }
+                }
+                (Some(format), Some(_)) => state.set_message(
+                    format!("Unknown export format: {}", format),
+                    MessageLevel::Error,
+                ),
+                _ => state.set_message(
+                    "Usage: :export html|outline <file>".to_string(),
+                    MessageLevel::Error,
+                ),
+            }
+            return Ok(false);
+        }
+
+        // Handle :trust / :untrust commands
+        if command == "trust" {
+            match state.trust_current_location() {
+                Ok(dir) => {
+                    state.set_message(format!("Trusted {}", dir.display()), MessageLevel::Info)
+                }
+                Err(e) => state.set_message(format!("Trust failed: {}", e), MessageLevel::Error),
+            }
+            return Ok(false);
+        }
+        if command == "untrust" {
+            match state.untrust_current_location() {
+                Ok(Some(dir)) => state.set_message(
+                    format!("Revoked trust for {}", dir.display()),
+                    MessageLevel::Info,
+                ),
+                Ok(None) => {
+                    state.set_message("Location is not trusted".to_string(), MessageLevel::Info)
+                }
+                Err(e) => state.set_message(format!("Untrust failed: {}", e), MessageLevel::Error),
+            }
             return Ok(false);
         }
 
@@ -1674,77 +4143,58 @@ impl InputHandler {
         match command {
             "e!" => {
                 // Reload from disk, discarding changes
-                if let Some(filename) = state.filename().map(|s| s.to_string()) {
-                    use crate::file::loader::load_yaml_file;
-                    match load_yaml_file(&filename) {
-                        Ok(tree) => {
-                            state.reload_tree(tree);
-                            state.set_message(
-                                format!("\"{}\" reloaded", filename),
-                                MessageLevel::Info,
-                            );
-                        }
-                        Err(e) => {
-                            state.set_message(
-                                format!("Error reloading file: {}", e),
-                                MessageLevel::Error,
-                            );
-                        }
+                match state.reload_from_disk() {
+                    Ok(()) => {
+                        let filename = state.filename().unwrap_or_default().to_string();
+                        state.set_message(format!("\"{}\" reloaded", filename), MessageLevel::Info);
                     }
-                } else {
-                    state.set_message("No file name".to_string(), MessageLevel::Error);
-                }
-                Ok(false)
-            }
-            cmd if cmd.starts_with("e ") => {
-                // :e filename - load a different file
-                let filename = cmd[2..].trim().to_string();
-                if filename.is_empty() {
-                    state.set_message("No file name specified".to_string(), MessageLevel::Error);
-                    return Ok(false);
-                }
-
-                // Check for unsaved changes
-                if state.is_dirty() {
-                    state.set_message(
-                        "No write since last change (add ! to override)".to_string(),
-                        MessageLevel::Error,
-                    );
-                    return Ok(false);
-                }
-
-                use crate::file::loader::load_yaml_file;
-                match load_yaml_file(&filename) {
-                    Ok(tree) => {
-                        state.reload_tree(tree);
-                        state.set_filename(filename.clone());
-                        state.set_message(format!("\"{}\" loaded", filename), MessageLevel::Info);
+                    Err(e) if state.filename().is_none() => {
+                        state.set_message(e.to_string(), MessageLevel::Error);
                     }
                     Err(e) => {
-                        state
-                            .set_message(format!("Error loading file: {}", e), MessageLevel::Error);
+                        state.set_message(
+                            format!("Error reloading file: {}", e),
+                            MessageLevel::Error,
+                        );
                     }
                 }
                 Ok(false)
             }
-            cmd if cmd.starts_with("e! ") => {
-                // :e! filename - load a different file, discarding changes
-                let filename = cmd[3..].trim().to_string();
+            cmd if cmd.starts_with("e ") || cmd.starts_with("e! ") => {
+                // :e filename - open a file in another buffer (the current one stays open)
+                let filename = cmd.trim_start_matches("e!").trim_start_matches('e').trim();
                 if filename.is_empty() {
                     state.set_message("No file name specified".to_string(), MessageLevel::Error);
                     return Ok(false);
                 }
-
-                use crate::file::loader::load_yaml_file;
-                match load_yaml_file(&filename) {
-                    Ok(tree) => {
-                        state.reload_tree(tree);
-                        state.set_filename(filename.clone());
-                        state.set_message(format!("\"{}\" loaded", filename), MessageLevel::Info);
+                use crate::app::BufferCommand;
+                state.request_buffer_command(BufferCommand::Edit(filename.to_string()));
+                Ok(false)
+            }
+            "bn" | "bnext" => {
+                state.request_buffer_command(crate::app::BufferCommand::Next);
+                Ok(false)
+            }
+            "bp" | "bprev" | "bprevious" => {
+                state.request_buffer_command(crate::app::BufferCommand::Prev);
+                Ok(false)
+            }
+            "ls" | "buffers" => {
+                state.request_buffer_command(crate::app::BufferCommand::List);
+                Ok(false)
+            }
+            cmd if cmd.starts_with("b ") || cmd.starts_with("buffer ") => {
+                let arg = cmd.split_once(' ').map(|(_, n)| n.trim()).unwrap_or("");
+                match arg.parse::<usize>() {
+                    Ok(number) => {
+                        use crate::app::BufferCommand;
+                        state.request_buffer_command(BufferCommand::Goto(number));
                     }
-                    Err(e) => {
-                        state
-                            .set_message(format!("Error loading file: {}", e), MessageLevel::Error);
+                    Err(_) => {
+                        state.set_message(
+                            format!("Invalid buffer number: {}", arg),
+                            MessageLevel::Error,
+                        );
                     }
                 }
                 Ok(false)
@@ -1763,36 +4213,71 @@ impl InputHandler {
                 }
                 Ok(true)
             }
-            "q!" => Ok(true),
+            "q!" => {
+                state.request_force_quit();
+                Ok(true)
+            }
             "undo" => {
-                if state.undo() {
-                    state.set_message("Undo".to_string(), MessageLevel::Info);
-                } else {
-                    state.set_message("Already at oldest change".to_string(), MessageLevel::Info);
+                if state.reject_read_only() {
+                    return Ok(false);
+                }
+                match state.try_undo() {
+                    Ok(true) => state.set_message("Undo".to_string(), MessageLevel::Info),
+                    Ok(false) => state
+                        .set_message("Already at oldest change".to_string(), MessageLevel::Info),
+                    Err(e) => state.set_message(format!("{:#}", e), MessageLevel::Error),
                 }
                 Ok(false)
             }
             "redo" => {
-                if state.redo() {
-                    state.set_message("Redo".to_string(), MessageLevel::Info);
-                } else {
-                    state.set_message("Already at newest change".to_string(), MessageLevel::Info);
+                if state.reject_read_only() {
+                    return Ok(false);
+                }
+                match state.try_redo() {
+                    Ok(true) => state.set_message("Redo".to_string(), MessageLevel::Info),
+                    Ok(false) => state
+                        .set_message("Already at newest change".to_string(), MessageLevel::Info),
+                    Err(e) => state.set_message(format!("{:#}", e), MessageLevel::Error),
                 }
                 Ok(false)
             }
             cmd if cmd.starts_with("w ") => {
-                // :w filename - save to new file and update internal filename
-                let filename = cmd[2..].trim().to_string();
-                if filename.is_empty() {
-                    state.set_message("No file name specified".to_string(), MessageLevel::Error);
+                // :w [--style NAME] filename - save to new file and update internal filename
+                let (style, filename) = match split_style_option(cmd[2..].trim()) {
+                    Ok(split) => split,
+                    Err(e) => {
+                        state.set_message(e.to_string(), MessageLevel::Error);
+                        return Ok(false);
+                    }
+                };
+                let current = state.filename().map(|s| s.to_string());
+                let filename = match (filename.is_empty(), style, current) {
+                    (false, _, _) => filename.to_string(),
+                    // `:w --style NAME` rewrites the current file
+                    (true, Some(_), Some(current)) => {
+                        if state.reject_read_only() {
+                            return Ok(false);
+                        }
+                        current
+                    }
+                    _ => {
+                        state
+                            .set_message("No file name specified".to_string(), MessageLevel::Error);
+                        return Ok(false);
+                    }
+                };
+                if state.filename() == Some(filename.as_str()) && state.block_conflicting_save(cmd)
+                {
                     return Ok(false);
                 }
 
-                match save_yaml_file(&filename, state.tree(), &state.to_config()) {
-                    Ok(_) => {
+                match save_file_styled(&filename, state.tree(), &state.to_config(), style) {
+                    Ok(format) => {
                         state.set_filename(filename.clone());
                         state.clear_dirty();
-                        state.set_message(format!("\"{}\" written", filename), MessageLevel::Info);
+                        state.set_message(written_message(&filename, format), MessageLevel::Info);
+                        state.write_undo_history();
+                        state.record_disk_state();
                     }
                     Err(e) => {
                         state.set_message(format!("Error saving file: {}", e), MessageLevel::Error);
@@ -1800,15 +4285,46 @@ impl InputHandler {
                 }
                 Ok(false)
             }
+            "view" if state.sample_info().is_some() => {
+                state.set_message(
+                    "Sampled buffer stays read-only (:sample off loads every record)".to_string(),
+                    MessageLevel::Error,
+                );
+                Ok(false)
+            }
+            "view" => {
+                let read_only = !state.is_read_only();
+                state.set_read_only(read_only);
+                let message = if read_only {
+                    "Read-only"
+                } else {
+                    "Editing enabled"
+                };
+                state.set_message(message.to_string(), MessageLevel::Info);
+                Ok(false)
+            }
+            "w!" | "wq!" | "x!" => {
+                // Write even if the file changed on disk
+                if state.reject_read_only() {
+                    return Ok(false);
+                }
+                state.record_disk_state();
+                self.execute_command(command.trim_end_matches('!'), state)
+            }
             "w" => {
+                if state.reject_read_only() || state.block_conflicting_save(command) {
+                    return Ok(false);
+                }
                 if let Some(filename) = state.filename().map(|s| s.to_string()) {
-                    match save_yaml_file(&filename, state.tree(), &state.to_config()) {
-                        Ok(_) => {
+                    match save_file(&filename, state.tree(), &state.to_config()) {
+                        Ok(format) => {
                             state.clear_dirty();
                             state.set_message(
-                                format!("\"{}\" written", filename),
+                                written_message(&filename, format),
                                 MessageLevel::Info,
                             );
+                            state.write_undo_history();
+                            state.record_disk_state();
                         }
                         Err(e) => {
                             state.set_message(
@@ -1839,11 +4355,17 @@ impl InputHandler {
                     state.set_message("No file name specified".to_string(), MessageLevel::Error);
                     return Ok(false);
                 }
+                if state.filename() == Some(filename.as_str()) && state.block_conflicting_save(cmd)
+                {
+                    return Ok(false);
+                }
 
-                match save_yaml_file(&filename, state.tree(), &state.to_config()) {
+                match save_file(&filename, state.tree(), &state.to_config()) {
                     Ok(_) => {
                         state.set_filename(filename);
                         state.clear_dirty();
+                        state.write_undo_history();
+                        state.record_disk_state();
                         Ok(true)
                     }
                     Err(e) => {
@@ -1853,10 +4375,15 @@ impl InputHandler {
                 }
             }
             "wq" | "x" => {
+                if state.reject_read_only() || state.block_conflicting_save(command) {
+                    return Ok(false);
+                }
                 if let Some(filename) = state.filename().map(|s| s.to_string()) {
-                    match save_yaml_file(&filename, state.tree(), &state.to_config()) {
+                    match save_file(&filename, state.tree(), &state.to_config()) {
                         Ok(_) => {
                             state.clear_dirty();
+                            state.write_undo_history();
+                            state.record_disk_state();
                             Ok(true)
                         }
                         Err(e) => {
@@ -1887,6 +4414,51 @@ impl InputHandler {
     }
 }
 
+/// Returns the message shown after `:w`, naming the format unless it is YAML.
+/// Splits a leading `--style NAME` (or `--style=NAME`) off `:w` arguments.
+fn split_style_option(args: &str) -> Result<(Option<crate::file::serializer::Style>, &str)> {
+    use crate::file::serializer::Style;
+
+    let Some(rest) = args.strip_prefix("--style") else {
+        return Ok((None, args));
+    };
+    let rest = rest.strip_prefix('=').unwrap_or(rest).trim_start();
+    let (name, filename) = rest.split_once(' ').unwrap_or((rest, ""));
+    if name.is_empty() {
+        anyhow::bail!("Usage: :w --style preserving|canonical|compact [file]");
+    }
+    Ok((Some(Style::parse(name)?), filename.trim()))
+}
+
+fn written_message(filename: &str, format: crate::file::exporter::Format) -> String {
+    match format {
+        crate::file::exporter::Format::Yaml => format!("\"{}\" written", filename),
+        _ => format!("\"{}\" written as {}", filename, format.name()),
+    }
+}
+
+/// Re-runs the search as the pattern is typed, showing a compile error (an
+/// unfinished regex, say) next to the prompt instead of matching nothing.
+fn update_incremental_search(state: &mut EditorState) {
+    use crate::editor::state::MessageLevel;
+    match state.execute_search() {
+        Ok(()) => state.clear_message(),
+        Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+    }
+}
+
+/// Decodes an SGR mouse report for Shift+wheel (`ESC [ < 68/69 ; x ; y M`)
+/// into a horizontal direction: -1 for left (Shift+wheel up), 1 for right.
+fn shift_wheel_direction(bytes: &[u8]) -> Option<isize> {
+    let report = std::str::from_utf8(bytes).ok()?.strip_prefix("\x1b[<")?;
+    let (button, _) = report.split_once(';')?;
+    match button {
+        "68" => Some(-1),
+        "69" => Some(1),
+        _ => None,
+    }
+}
+
 impl Default for InputHandler {
     fn default() -> Self {
         Self::new()
@@ -1941,6 +4513,71 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_enter_picks_in_pick_mode() {
+        use crate::editor::state::PickOutput;
+        use indexmap::IndexMap;
+
+        let mut handler = InputHandler::new();
+        let mut obj = IndexMap::new();
+        obj.insert(
+            "name".to_string(),
+            YamlNode::new(YamlValue::String(YamlString::Plain("web".to_string()))),
+        );
+        let tree = YamlTree::new(YamlNode::new(YamlValue::Object(obj)));
+        let mut state = EditorState::new_with_default_theme(tree);
+        state.cursor_mut().set_path(vec![0]);
+
+        // Without pick mode, Enter does not quit
+        let should_quit = handler
+            .handle_event(Event::Key(Key::Char('\n')), &mut state)
+            .unwrap();
+        assert!(!should_quit);
+        assert_eq!(state.picked(), None);
+
+        state.set_pick_mode(Some(PickOutput::Path));
+        let should_quit = handler
+            .handle_event(Event::Key(Key::Char('\n')), &mut state)
+            .unwrap();
+        assert!(should_quit);
+        assert_eq!(state.picked(), Some(".name"));
+    }
+
+    #[test]
+    fn test_repl_keys() {
+        use crate::document::parser::parse_yaml_auto;
+
+        let mut handler = InputHandler::new();
+        let tree = YamlTree::new(parse_yaml_auto("a: 1\nb: 2\n").unwrap());
+        let mut state = EditorState::new_with_default_theme(tree);
+        state.cursor_mut().set_path(vec![0]);
+
+        for c in ":repl\n$.b\n".chars() {
+            handler
+                .handle_event(Event::Key(Key::Char(c)), &mut state)
+                .unwrap();
+        }
+        assert_eq!(*state.mode(), EditorMode::Repl);
+        assert_eq!(state.repl().history(), ["$.b"]);
+        assert_eq!(state.repl().results(), [vec![1]]);
+
+        // Typing over the input and recalling history
+        handler
+            .handle_event(Event::Key(Key::Backspace), &mut state)
+            .unwrap();
+        assert_eq!(state.repl().input(), "$.");
+        handler
+            .handle_event(Event::Key(Key::Up), &mut state)
+            .unwrap();
+        assert_eq!(state.repl().input(), "$.b");
+        assert_eq!(state.cursor().path(), &[0]);
+
+        handler
+            .handle_event(Event::Key(Key::Esc), &mut state)
+            .unwrap();
+        assert_eq!(*state.mode(), EditorMode::Normal);
+    }
+
     #[test]
     fn test_enter_insert_mode() {
         let mut handler = InputHandler::new();
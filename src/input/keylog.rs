@@ -0,0 +1,195 @@
+//! Key-sequence logger for bug reports (`:set keylog`).
+//!
+//! Every event the input handler receives is appended to a log file with
+//! the time since logging started, the mode it arrived in, the key in the
+//! same `<C-r>`/`<CR>` notation the test harness reads, and the
+//! [`InputEvent`] it maps to. With redaction on, characters typed as text
+//! (insert, command, search and REPL input) are logged as `_`, so a log can
+//! be attached to an issue without leaking values.
+
+use super::keys::{map_key_event, InputEvent};
+use crate::editor::mode::EditorMode;
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use termion::event::{Event, Key, MouseButton, MouseEvent};
+
+/// Where `:set keylog` writes when no file is given.
+pub fn default_keylog_path() -> PathBuf {
+    std::env::temp_dir().join("yamlquill-keys.log")
+}
+
+/// An open key log.
+pub struct KeyLog {
+    file: File,
+    path: PathBuf,
+    redact: bool,
+    started: Instant,
+}
+
+impl KeyLog {
+    /// Creates (or truncates) the log at `path` and writes its header.
+    pub fn create(path: &Path, redact: bool) -> Result<Self> {
+        let mut file = File::create(path)
+            .with_context(|| format!("Cannot write key log {}", path.display()))?;
+        writeln!(
+            file,
+            "# yamlquill {} key log{}",
+            env!("CARGO_PKG_VERSION"),
+            if redact { " (typed text redacted)" } else { "" }
+        )?;
+        writeln!(file, "# time(ms)  mode     key          event")?;
+        Ok(Self {
+            file,
+            path: path.to_path_buf(),
+            redact,
+            started: Instant::now(),
+        })
+    }
+
+    /// Returns the file being written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns whether typed text is redacted.
+    pub fn redact(&self) -> bool {
+        self.redact
+    }
+
+    /// Sets whether typed text is redacted from now on.
+    pub fn set_redact(&mut self, redact: bool) {
+        self.redact = redact;
+    }
+
+    /// Appends `event`, received in `mode`, to the log.
+    ///
+    /// Each line is flushed right away so the log survives a crash.
+    pub fn record(&mut self, mode: &EditorMode, event: &Event) -> Result<()> {
+        let event = match event {
+            Event::Key(Key::Char(c)) if self.redact && is_text_entry(mode) && !c.is_control() => {
+                Event::Key(Key::Char('_'))
+            }
+            event => event.clone(),
+        };
+        let input_event = match map_key_event(event.clone(), mode) {
+            InputEvent::Unknown => String::new(),
+            input_event => format!("{:?}", input_event),
+        };
+        writeln!(
+            self.file,
+            "{:>10}  {:<7}  {:<11}  {}",
+            self.started.elapsed().as_millis(),
+            mode.to_string(),
+            event_notation(&event),
+            input_event
+        )
+        .and_then(|_| self.file.flush())
+        .with_context(|| format!("Cannot write key log {}", self.path.display()))
+    }
+}
+
+/// Returns true in modes where keys are typed as text.
+fn is_text_entry(mode: &EditorMode) -> bool {
+    matches!(
+        mode,
+        EditorMode::Insert | EditorMode::Command | EditorMode::Search | EditorMode::Repl
+    )
+}
+
+/// Writes an event in the key notation of [`crate::harness::parse_keys`].
+///
+/// # Example
+///
+/// ```
+/// use termion::event::{Event, Key};
+/// use yamlquill::input::keylog::event_notation;
+///
+/// assert_eq!(event_notation(&Event::Key(Key::Ctrl('r'))), "<C-r>");
+/// assert_eq!(event_notation(&Event::Key(Key::Char('\n'))), "<CR>");
+/// assert_eq!(event_notation(&Event::Key(Key::Char('j'))), "j");
+/// ```
+pub fn event_notation(event: &Event) -> String {
+    match event {
+        Event::Key(key) => key_notation(key),
+        Event::Mouse(MouseEvent::Press(button, x, y)) => {
+            let button = match button {
+                MouseButton::Left => "Left",
+                MouseButton::Right => "Right",
+                MouseButton::Middle => "Middle",
+                MouseButton::WheelUp => "WheelUp",
+                MouseButton::WheelDown => "WheelDown",
+                MouseButton::WheelLeft => "WheelLeft",
+                MouseButton::WheelRight => "WheelRight",
+            };
+            format!("<{}@{},{}>", button, x, y)
+        }
+        Event::Mouse(MouseEvent::Release(x, y)) => format!("<Release@{},{}>", x, y),
+        Event::Mouse(MouseEvent::Hold(x, y)) => format!("<Drag@{},{}>", x, y),
+        Event::Unsupported(bytes) => format!("<Unsupported {:02x?}>", bytes),
+    }
+}
+
+fn key_notation(key: &Key) -> String {
+    let named = match key {
+        Key::Esc => "Esc",
+        Key::Char('\n') => "CR",
+        Key::Char('\t') => "Tab",
+        Key::Char(' ') => "Space",
+        Key::Char('<') => "lt",
+        Key::BackTab => "S-Tab",
+        Key::Backspace => "BS",
+        Key::Delete => "Del",
+        Key::Up => "Up",
+        Key::Down => "Down",
+        Key::Left => "Left",
+        Key::Right => "Right",
+        Key::Home => "Home",
+        Key::End => "End",
+        Key::PageUp => "PageUp",
+        Key::PageDown => "PageDown",
+        Key::Insert => "Insert",
+        Key::Char(c) => return c.to_string(),
+        Key::Ctrl(c) => return format!("<C-{}>", c),
+        Key::Alt(c) => return format!("<A-{}>", c),
+        Key::F(n) => return format!("<F{}>", n),
+        _ => "Unknown",
+    };
+    format!("<{}>", named)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_redacts_typed_text() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("keys.log");
+        let mut log = KeyLog::create(&path, true).unwrap();
+        log.record(&EditorMode::Normal, &Event::Key(Key::Char('j')))
+            .unwrap();
+        log.record(&EditorMode::Insert, &Event::Key(Key::Char('s')))
+            .unwrap();
+        log.record(&EditorMode::Insert, &Event::Key(Key::Char('\n')))
+            .unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<Vec<&str>> = text
+            .lines()
+            .skip(2)
+            .map(|line| line.split_whitespace().skip(1).collect())
+            .collect();
+        assert_eq!(lines[0], vec!["NORMAL", "j", "MoveDown"]);
+        assert_eq!(lines[1][..2], ["INSERT", "_"]);
+        assert_eq!(lines[2][..2], ["INSERT", "<CR>"]);
+        assert!(text.starts_with("# yamlquill"));
+        assert!(text
+            .lines()
+            .next()
+            .unwrap()
+            .ends_with("(typed text redacted)"));
+    }
+}
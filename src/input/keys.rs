@@ -95,6 +95,8 @@ pub enum InputEvent {
     RegisterSelect,
     /// Enter visual mode (v)
     EnterVisualMode,
+    /// Enter visual block mode, selecting one key across list elements (Ctrl-v)
+    EnterVisualBlockMode,
     /// Set mark (m)
     MarkSet,
     /// Jump to mark (')
@@ -105,10 +107,18 @@ pub enum InputEvent {
     JumpForward,
     /// Jump to anchor definition (Enter on alias)
     JumpToAnchor,
+    /// Window command prefix (Ctrl-w)
+    WindowPrefix,
     /// Repeat last command (.)
     Repeat,
     /// Add a comment to the current node (c)
     AddComment,
+    /// Explain the value under the cursor (K)
+    Explain,
+    /// Move the node into the container above it (>)
+    Indent,
+    /// Move the node out of its container (<)
+    Outdent,
     /// Insert a character in insert mode
     InsertCharacter(char),
     /// Backspace in insert mode
@@ -163,6 +173,8 @@ pub fn map_key_event(event: Event, mode: &EditorMode) -> InputEvent {
             Key::Ctrl('r') => InputEvent::Redo,
             Key::Ctrl('o') => InputEvent::JumpBackward,
             Key::Ctrl('i') => InputEvent::JumpForward,
+            Key::Ctrl('w') => InputEvent::WindowPrefix,
+            Key::Ctrl('v') => InputEvent::EnterVisualBlockMode,
             // Regular keys
             Key::Char('q') => InputEvent::Quit,
             Key::Char('j') => InputEvent::MoveDown,
@@ -207,6 +219,9 @@ pub fn map_key_event(event: Event, mode: &EditorMode) -> InputEvent {
             Key::Char('\'') => InputEvent::MarkJump,
             Key::Char('.') => InputEvent::Repeat,
             Key::Char('c') => InputEvent::AddComment,
+            Key::Char('K') => InputEvent::Explain,
+            Key::Char('>') => InputEvent::Indent,
+            Key::Char('<') => InputEvent::Outdent,
             Key::Down => InputEvent::MoveDown,
             Key::Up => InputEvent::MoveUp,
             Key::Left => InputEvent::MoveLeft,
@@ -244,12 +259,19 @@ pub fn map_key_event(event: Event, mode: &EditorMode) -> InputEvent {
             Key::Char('y') => InputEvent::Yank,
             Key::Char('p') => InputEvent::Paste,
             Key::Char('P') => InputEvent::PasteBefore,
+            Key::Char('c') => InputEvent::AddComment,
+            Key::Char('r') => InputEvent::Rename,
+            Key::Char(':') => InputEvent::EnterCommandMode,
             Key::Down => InputEvent::MoveDown,
             Key::Up => InputEvent::MoveUp,
             Key::Left => InputEvent::MoveLeft,
             Key::Right => InputEvent::MoveRight,
             _ => InputEvent::Unknown,
         },
+        EditorMode::Repl | EditorMode::Review => match key {
+            Key::Esc => InputEvent::ExitMode,
+            _ => InputEvent::Unknown,
+        },
     }
 }
 
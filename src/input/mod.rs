@@ -1,7 +1,9 @@
 //! Input handling for keyboard events and vim-style keybindings.
 
 pub mod handler;
+pub mod keylog;
 pub mod keys;
+pub mod rpc;
 
 pub use handler::InputHandler;
 pub use keys::InputEvent;
@@ -0,0 +1,364 @@
+//! JSON-RPC control socket (`--listen <socket>`).
+//!
+//! A running editor can be driven by other programs — scripts, window
+//! manager bindings, tests — over a Unix socket. Each request is one line of
+//! JSON-RPC 2.0 and gets one line back:
+//!
+//! ```text
+//! → {"jsonrpc": "2.0", "id": 1, "method": "goto", "params": {"path": "$.spec.replicas"}}
+//! ← {"jsonrpc":"2.0","id":1,"result":{"path":"$[\"spec\"][\"replicas\"]"}}
+//! ```
+//!
+//! Methods act on the focused buffer:
+//!
+//! | Method | Params | Result |
+//! |--------|--------|--------|
+//! | `open` | `file` | `{buffer}`: the buffer's number, as in `:ls` |
+//! | `goto` | `path` (YAMLPath) | `{path}`: where the cursor landed |
+//! | `set-value` | `path`, `value` (scalar) | `{changed}`: number of nodes set |
+//! | `query` | `path` | list of `{path, value}` for every match |
+//! | `save` | `file` (optional) | `{message}`, as shown by `:w` |
+//! | `status` | | `{file, dirty, cursor, mode}` |
+//!
+//! The socket is served from the event loop, so requests are applied
+//! between key presses and show up on screen right away.
+
+use crate::app::{App, BufferCommand};
+use crate::editor::state::MessageLevel;
+use crate::input::InputHandler;
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::io::{ErrorKind, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+
+/// JSON-RPC error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Any other failure (file not found, invalid YAMLPath, ...).
+const REQUEST_FAILED: i64 = -32000;
+
+/// An error answered to a client.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    /// JSON-RPC error code
+    pub code: i64,
+    /// Description shown to the client
+    pub message: String,
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(e: anyhow::Error) -> Self {
+        Self {
+            code: REQUEST_FAILED,
+            message: format!("{:#}", e),
+        }
+    }
+}
+
+fn invalid_params(message: &str) -> RpcError {
+    RpcError {
+        code: INVALID_PARAMS,
+        message: message.to_string(),
+    }
+}
+
+/// A listening control socket and its connected clients.
+#[derive(Debug)]
+pub struct RpcServer {
+    listener: UnixListener,
+    path: PathBuf,
+    clients: Vec<Client>,
+}
+
+#[derive(Debug)]
+struct Client {
+    stream: UnixStream,
+    /// Bytes received that don't make up a whole line yet
+    pending: Vec<u8>,
+}
+
+impl RpcServer {
+    /// Listens on the socket at `path`.
+    ///
+    /// A stale socket left by an editor that didn't exit cleanly is
+    /// replaced; one that still answers is an error, and so is anything at
+    /// `path` that isn't a socket.
+    pub fn bind(path: impl AsRef<Path>) -> Result<Self> {
+        use std::os::unix::fs::FileTypeExt;
+
+        let path = path.as_ref().to_path_buf();
+        if let Ok(metadata) = std::fs::symlink_metadata(&path) {
+            if !metadata.file_type().is_socket() {
+                bail!(
+                    "Cannot listen on {}: it exists and is not a socket",
+                    path.display()
+                );
+            }
+            if UnixStream::connect(&path).is_err() {
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("Cannot remove stale socket {}", path.display()))?;
+            }
+        }
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("Cannot listen on {}", path.display()))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            path,
+            clients: Vec::new(),
+        })
+    }
+
+    /// Returns the socket path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Accepts new clients and answers every complete request with `dispatch`.
+    ///
+    /// Never blocks; meant to be called on every turn of the event loop.
+    /// Returns the number of requests answered.
+    pub fn poll(
+        &mut self,
+        mut dispatch: impl FnMut(&str, &Value) -> Result<Value, RpcError>,
+    ) -> usize {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                self.clients.push(Client {
+                    stream,
+                    pending: Vec::new(),
+                });
+            }
+        }
+
+        let mut answered = 0;
+        self.clients.retain_mut(|client| {
+            let open = client.read_available();
+            while let Some(line) = client.next_line() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let response = respond(&line, &mut dispatch);
+                answered += 1;
+                if writeln!(client.stream, "{}", response).is_err() {
+                    return false;
+                }
+            }
+            open
+        });
+        answered
+    }
+}
+
+impl Drop for RpcServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl Client {
+    /// Reads whatever has arrived; returns false once the client has gone.
+    fn read_available(&mut self) -> bool {
+        let mut chunk = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return false,
+                Ok(n) => self.pending.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return true,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return false,
+            }
+        }
+    }
+
+    fn next_line(&mut self) -> Option<String> {
+        let end = self.pending.iter().position(|&b| b == b'\n')?;
+        let line: Vec<u8> = self.pending.drain(..=end).collect();
+        Some(String::from_utf8_lossy(&line).into_owned())
+    }
+}
+
+/// Answers one request line with one response object.
+fn respond(
+    line: &str,
+    dispatch: &mut impl FnMut(&str, &Value) -> Result<Value, RpcError>,
+) -> Value {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return error_response(Value::Null, PARSE_ERROR, &e.to_string()),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return error_response(id, INVALID_REQUEST, "Missing method");
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    match dispatch(method, &params) {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(e) => error_response(id, e.code, &e.message),
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+/// Runs one request against the focused buffer.
+///
+/// `handler` runs `save` the way `:w` does, so the result and messages match.
+pub fn dispatch(
+    app: &mut App,
+    handler: &InputHandler,
+    method: &str,
+    params: &Value,
+) -> Result<Value, RpcError> {
+    let param = |name: &str| params.get(name).and_then(Value::as_str);
+    let required = |name: &str| {
+        param(name).ok_or_else(|| invalid_params(&format!("Missing string param '{}'", name)))
+    };
+
+    match method {
+        "open" => {
+            app.apply_buffer_command(BufferCommand::Edit(required("file")?.to_string()))?;
+            Ok(json!({"buffer": app.focused_buffer_index() + 1}))
+        }
+        "goto" => {
+            let state = app.focused_mut();
+            state.jump_to_yamlpath(required("path")?)?;
+            Ok(json!({"path": state.compute_path_string("bracket")}))
+        }
+        "set-value" => {
+            let value = match params.get("value") {
+                Some(Value::String(s)) => format!("\"{}\"", s),
+                Some(value @ (Value::Number(_) | Value::Bool(_) | Value::Null)) => {
+                    value.to_string()
+                }
+                _ => return Err(invalid_params("'value' must be a scalar")),
+            };
+            let changed = app.focused_mut().path_set(required("path")?, &value)?;
+            Ok(json!({ "changed": changed }))
+        }
+        "query" => {
+            let state = app.focused();
+            let matches = state
+                .query(required("path")?)?
+                .into_iter()
+                .map(|(path, yaml)| {
+                    let value: Value = serde_yaml::from_str(&yaml).unwrap_or(Value::Null);
+                    json!({"path": path, "value": value})
+                })
+                .collect();
+            Ok(Value::Array(matches))
+        }
+        "save" => {
+            let command = match param("file") {
+                Some(file) => format!("w {}", file),
+                None => "w".to_string(),
+            };
+            let state = app.focused_mut();
+            state.clear_message();
+            handler.execute_command(&command, state)?;
+            match state.message() {
+                Some(message) if message.level == MessageLevel::Error => {
+                    Err(anyhow::anyhow!("{}", message.text).into())
+                }
+                message => Ok(json!({"message": message.map(|m| m.text.clone())})),
+            }
+        }
+        "status" => {
+            let state = app.focused();
+            Ok(json!({
+                "file": state.filename(),
+                "dirty": state.is_dirty(),
+                "cursor": state.compute_path_string("bracket"),
+                "mode": state.mode().to_string(),
+            }))
+        }
+        _ => Err(RpcError {
+            code: METHOD_NOT_FOUND,
+            message: format!("Unknown method '{}'", method),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn echo(method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "echo" => Ok(params.clone()),
+            _ => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: "nope".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_respond() {
+        let response = respond(
+            r#"{"jsonrpc": "2.0", "id": 7, "method": "echo", "params": [1]}"#,
+            &mut echo,
+        );
+        assert_eq!(response, json!({"jsonrpc": "2.0", "id": 7, "result": [1]}));
+
+        let response = respond(r#"{"id": 8, "method": "other"}"#, &mut echo);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(response["id"], 8);
+
+        assert_eq!(respond("{oops", &mut echo)["error"]["code"], PARSE_ERROR);
+        assert_eq!(
+            respond(r#"{"id": 9}"#, &mut echo)["error"]["code"],
+            INVALID_REQUEST
+        );
+    }
+
+    #[test]
+    fn test_socket_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("yamlquill.sock");
+        let mut server = RpcServer::bind(&path).unwrap();
+
+        let mut client = UnixStream::connect(&path).unwrap();
+        // Two requests in one write, the second split across writes
+        client
+            .write_all(b"{\"id\": 1, \"method\": \"echo\", \"params\": \"a\"}\n{\"id\": 2, ")
+            .unwrap();
+        client
+            .write_all(b"\"method\": \"echo\", \"params\": \"b\"}\n")
+            .unwrap();
+        assert_eq!(server.poll(echo), 2);
+
+        let mut reply = String::new();
+        client.set_nonblocking(true).unwrap();
+        let _ = client.read_to_string(&mut reply);
+        let lines: Vec<Value> = reply
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines[0]["result"], "a");
+        assert_eq!(lines[1]["result"], "b");
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_bind_refuses_files_that_arent_sockets() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("app.yaml");
+        std::fs::write(&path, "replicas: 1\n").unwrap();
+
+        let error = RpcServer::bind(&path).unwrap_err();
+        assert!(error.to_string().contains("is not a socket"), "{}", error);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "replicas: 1\n");
+
+        // A socket nobody answers on is replaced
+        let socket = dir.path().join("stale.sock");
+        drop(UnixListener::bind(&socket).unwrap());
+        assert!(RpcServer::bind(&socket).is_ok());
+    }
+}
@@ -1,8 +1,13 @@
+pub mod app;
+pub mod cli;
 pub mod config;
 pub mod document;
 pub mod editor;
+pub mod export;
 pub mod file;
+pub mod harness;
 pub mod input;
+pub mod schema;
 pub mod theme;
 pub mod ui;
 pub mod yamlpath;
@@ -2,17 +2,23 @@ use anyhow::{Context, Result};
 use clap::Parser;
 use ratatui::{backend::TermionBackend, Terminal};
 use std::io::{self, IsTerminal, Write};
+use std::os::fd::AsFd;
 use std::time::Duration;
 use termion::input::MouseTerminal;
-use termion::raw::IntoRawMode;
+use termion::raw::{IntoRawMode, RawTerminal};
 use termion::screen::IntoAlternateScreen;
 
+use yamlquill::app::App;
 use yamlquill::document::node::{YamlNode, YamlValue};
 use yamlquill::document::tree::YamlTree;
-use yamlquill::editor::state::EditorState;
-use yamlquill::file::loader::{load_yaml_file, load_yaml_from_stdin};
+use yamlquill::editor::external;
+use yamlquill::editor::revalidate::IDLE_BUDGET;
+use yamlquill::editor::sample::{random_seed, Sample, SampleMode};
+use yamlquill::editor::state::{EditorState, PickOutput};
+use yamlquill::file::loader::{load_yaml_file_lazy, load_yaml_from_stdin};
+use yamlquill::input::rpc::{self, RpcServer};
 use yamlquill::input::InputHandler;
-use yamlquill::theme::get_builtin_theme;
+use yamlquill::theme::{get_builtin_theme, load_theme};
 use yamlquill::ui::UI;
 
 /// YAMLQuill - A terminal-based structural YAML editor
@@ -27,6 +33,74 @@ struct Cli {
     /// Theme name (default: default-dark)
     #[arg(short, long, default_value = "default-dark")]
     theme: String,
+
+    /// Print a syntax-highlighted rendering of the file to stdout and exit
+    #[arg(long)]
+    render_ansi: bool,
+
+    /// Pick a node interactively and print its path (or value) to stdout on Enter
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "path"
+    )]
+    pick: Option<PickArg>,
+
+    /// Accept JSON-RPC commands on a Unix socket at PATH (see README, "Remote control")
+    #[arg(long, value_name = "PATH")]
+    listen: Option<String>,
+
+    /// Apply an edit without starting the editor: QUERY=VALUE or del(QUERY) (repeatable)
+    #[arg(
+        long,
+        value_name = "EDIT",
+        conflicts_with_all = ["render_ansi", "pick", "listen"]
+    )]
+    apply: Vec<String>,
+
+    /// With --apply, write the result back to the file instead of printing it
+    #[arg(long, requires = "apply")]
+    in_place: bool,
+
+    /// Open the file for viewing only; edits are refused until :view
+    #[arg(long, conflicts_with = "apply")]
+    read_only: bool,
+
+    /// Show only N records of a multi-document or JSONL file (:sample off loads the rest)
+    #[arg(long, value_name = "N", conflicts_with = "apply")]
+    sample: Option<usize>,
+
+    /// Which records --sample keeps
+    #[arg(long, value_enum, default_value = "random", requires = "sample")]
+    sample_from: SampleArg,
+
+    /// Start with default settings, ignoring the user config, project configs and
+    /// modelines, and without reading or saving undo history, notes or bookmarks
+    #[arg(long, visible_alias = "safe")]
+    clean: bool,
+}
+
+/// Which records `--sample` keeps.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum SampleArg {
+    /// The first N records
+    Head,
+    /// The last N records
+    Tail,
+    /// N records picked at random
+    Random,
+}
+
+/// What `--pick` prints for the selected node.
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum PickArg {
+    /// The node's path, e.g. `.spec.replicas`
+    Path,
+    /// The node's value (containers as YAML)
+    Value,
 }
 
 /// Set up a panic hook that restores the terminal before displaying panic information.
@@ -65,9 +139,18 @@ fn main() -> Result<()> {
 
     // Load file or create empty document BEFORE terminal setup
     // (stdin might be used for YAML data, so we need to read it before taking over the terminal)
-    let (tree, filename, _stdin_was_piped) = if let Some(file_path) = cli.file {
-        // Load from file
-        let tree = load_yaml_file(&file_path)?;
+    let (tree, filename, stdin_was_piped) = if let Some(file_path) = cli.file {
+        // Load from file, lazily if it's over the configured size
+        let config = if cli.clean {
+            yamlquill::config::Config::default()
+        } else {
+            yamlquill::config::Config::load()
+        };
+        let tree = load_yaml_file_lazy(
+            &file_path,
+            config.lazy_load_threshold,
+            &config.load_limits(),
+        )?;
         (tree, Some(file_path), false)
     } else {
         // No filename provided - check if stdin has piped data
@@ -111,9 +194,118 @@ fn main() -> Result<()> {
         }
     };
 
+    // Batch mode: apply the edits, then print or save and exit
+    if !cli.apply.is_empty() {
+        use yamlquill::cli::batch::{self, Edit};
+        use yamlquill::config::Config;
+
+        if filename.is_none() && !stdin_was_piped {
+            anyhow::bail!("--apply needs a file or YAML on stdin");
+        }
+        let edits = cli
+            .apply
+            .iter()
+            .map(|text| Edit::parse(text))
+            .collect::<Result<Vec<_>>>()?;
+        let file = filename.as_deref();
+        let config = if cli.clean {
+            Config::default()
+        } else {
+            Config::load()
+        };
+        if let Some(text) = batch::run(tree, file, &edits, cli.in_place, &config)? {
+            print!("{}", text);
+        }
+        return Ok(());
+    }
+
+    // Non-interactive rendering: print the fully expanded tree and exit
+    if cli.render_ansi {
+        let colors = load_theme(&cli.theme)?.colors;
+        let mut view = yamlquill::ui::tree_view::TreeViewState::new();
+        view.expand_all(&tree);
+        view.rebuild(&tree);
+        print!("{}", yamlquill::export::render_ansi(&view, &colors));
+        return Ok(());
+    }
+
+    let sample = cli.sample.map(|count| Sample {
+        mode: match cli.sample_from {
+            SampleArg::Head => SampleMode::Head,
+            SampleArg::Tail => SampleMode::Tail,
+            SampleArg::Random => SampleMode::Random,
+        },
+        count,
+    });
+
+    let pick = cli.pick.map(|what| match what {
+        PickArg::Path => PickOutput::Path,
+        PickArg::Value => PickOutput::Value,
+    });
+
+    if pick.is_some() {
+        // Draw on the terminal directly so stdout only carries the picked result
+        let tty = termion::get_tty().context("Failed to open /dev/tty")?;
+        let state = run_tui(
+            tty,
+            tree,
+            filename,
+            stdin_was_piped,
+            &cli.theme,
+            pick,
+            cli.listen.as_deref(),
+            cli.read_only,
+            sample,
+            cli.clean,
+        )?;
+        match state.picked() {
+            Some(picked) => {
+                println!("{}", picked);
+                Ok(())
+            }
+            None => std::process::exit(1),
+        }
+    } else {
+        run_tui(
+            io::stdout(),
+            tree,
+            filename,
+            stdin_was_piped,
+            &cli.theme,
+            None,
+            cli.listen.as_deref(),
+            cli.read_only,
+            sample,
+            cli.clean,
+        )?;
+        Ok(())
+    }
+}
+
+/// Runs the interactive editor, drawing on `output`, and returns the final editor state.
+#[allow(clippy::too_many_arguments)]
+fn run_tui<W: Write + AsFd>(
+    output: W,
+    tree: YamlTree,
+    filename: Option<String>,
+    stdin_was_piped: bool,
+    cli_theme: &str,
+    pick: Option<PickOutput>,
+    listen: Option<&str>,
+    read_only: bool,
+    sample: Option<Sample>,
+    clean: bool,
+) -> Result<EditorState> {
+    // Bound first so a bad path fails before the terminal is taken over
+    let mut rpc_server = listen.map(RpcServer::bind).transpose()?;
+
+    // Taken before raw mode is entered, to give the terminal back for
+    // `$EDITOR`; with `--listen` the terminal is read in the background instead
+    let suspender = rpc_server.is_none().then(Suspender::new).flatten();
+
     // Setup terminal
     // Termion can use /dev/tty directly when stdin is piped, no redirection needed
-    let stdout = io::stdout()
+    let stdout = output
         .into_raw_mode()
         .context("Failed to enable raw mode")?;
     let stdout = MouseTerminal::from(stdout);
@@ -127,44 +319,100 @@ fn main() -> Result<()> {
 
     // Load config
     use yamlquill::config::Config;
-    let config = Config::load();
+    let config = if clean {
+        Config::default()
+    } else {
+        Config::load()
+    };
 
     // Initialize components
     // CLI theme overrides config theme
-    let theme_name = if !cli.theme.is_empty() {
-        &cli.theme
+    let theme_name = if !cli_theme.is_empty() {
+        cli_theme
     } else {
         &config.theme
     };
 
-    let theme = get_builtin_theme(theme_name).unwrap_or_else(|| {
-        eprintln!(
-            "Warning: Theme '{}' not found, using default-dark",
-            theme_name
-        );
+    let theme = load_theme(theme_name).unwrap_or_else(|e| {
+        eprintln!("Warning: {:#}, using default-dark", e);
         get_builtin_theme("default-dark").unwrap()
     });
     let mut ui = UI::new(theme);
-    let mut input_handler = if _stdin_was_piped {
+    let mut input_handler = if stdin_was_piped {
         InputHandler::new_with_tty()
             .context("Failed to open /dev/tty for keyboard input when stdin was piped")?
     } else {
         InputHandler::new()
     };
+    // Requests must be answered while no keys are pressed
+    if rpc_server.is_some() {
+        input_handler.read_in_background();
+    }
 
     let mut state = EditorState::new(tree, theme_name.to_string());
     if let Some(name) = filename {
         state.set_filename(name);
     }
+    state.set_read_only(read_only);
+    state.set_safe_mode(clean);
 
     // Apply config settings (theme already set in constructor)
-    state.set_show_line_numbers(config.show_line_numbers);
-    state.set_relative_line_numbers(config.relative_line_numbers);
-    state.set_enable_mouse(config.enable_mouse);
-    state.set_create_backup(config.create_backup);
+    state.apply_config(&config);
+
+    // Project config and modeline overrides, if the location is trusted
+    if clean {
+        // Trust and bookmarks are kept in memory for the session
+        state.set_bookmark_dir(None);
+    } else {
+        state.set_trust_store(yamlquill::config::trust::TrustStore::load());
+    }
+    state.load_buffer_settings();
+
+    // Undo history from a previous session (`persistent_undo`)
+    state.load_undo_history();
+
+    // Review notes kept next to the file
+    state.load_notes();
+
+    if clean {
+        state.set_message(
+            "Safe mode: default settings, nothing read from or saved to your config".to_string(),
+            yamlquill::editor::state::MessageLevel::Info,
+        );
+    }
+
+    if let Some(sample) = sample {
+        use yamlquill::editor::state::MessageLevel;
+        match state.apply_sample(sample, random_seed()) {
+            Ok(info) => state.set_message(
+                format!(
+                    "Showing {} of {} records (:sample off loads all)",
+                    info.shown, info.total
+                ),
+                MessageLevel::Info,
+            ),
+            Err(e) => state.set_message(e.to_string(), MessageLevel::Error),
+        }
+    }
+
+    if let Some(pick) = pick {
+        state.set_pick_mode(Some(pick));
+        state.set_message(
+            "Pick a node: Enter to select, :q to cancel".to_string(),
+            yamlquill::editor::state::MessageLevel::Info,
+        );
+    }
 
     // Main event loop
-    let result = run_event_loop(&mut terminal, &mut ui, &mut input_handler, &mut state);
+    let mut app = App::new(state);
+    let result = run_event_loop(
+        &mut terminal,
+        &mut ui,
+        &mut input_handler,
+        &mut app,
+        suspender.as_ref(),
+        rpc_server.as_mut(),
+    );
 
     // Cleanup
     // Termion handles cleanup automatically through Drop guards
@@ -172,36 +420,142 @@ fn main() -> Result<()> {
     write!(terminal.backend_mut(), "{}", termion::cursor::Show)?;
     terminal.backend_mut().flush()?;
 
-    result
+    result.map(|_| app.into_focused())
 }
 
-fn run_event_loop<B: ratatui::backend::Backend>(
+/// Hands the terminal over to another program (`$EDITOR`) and takes it back.
+///
+/// Holds its own raw-mode handle on the tty, taken before the drawing
+/// output's, so suspending restores the terminal settings from before the
+/// editor started.
+struct Suspender {
+    tty: RawTerminal<std::fs::File>,
+}
+
+// Mouse reporting modes turned on by termion's `MouseTerminal`
+const MOUSE_ON: &str = "\x1b[?1000h\x1b[?1002h\x1b[?1015h\x1b[?1006h";
+const MOUSE_OFF: &str = "\x1b[?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l";
+
+impl Suspender {
+    fn new() -> Option<Self> {
+        let tty = termion::get_tty().ok()?.into_raw_mode().ok()?;
+        Some(Self { tty })
+    }
+
+    /// Leaves the alternate screen, mouse reporting and raw mode.
+    fn suspend(&self, output: &mut impl Write) -> io::Result<()> {
+        write!(
+            output,
+            "{}{}{}",
+            MOUSE_OFF,
+            termion::screen::ToMainScreen,
+            termion::cursor::Show
+        )?;
+        output.flush()?;
+        self.tty.suspend_raw_mode()
+    }
+
+    /// Restores the terminal as it was before [`Suspender::suspend`].
+    fn resume(&self, output: &mut impl Write) -> io::Result<()> {
+        self.tty.activate_raw_mode()?;
+        write!(
+            output,
+            "{}{}{}",
+            termion::screen::ToAlternateScreen,
+            MOUSE_ON,
+            termion::cursor::Hide
+        )?;
+        output.flush()
+    }
+}
+
+fn run_event_loop<B: ratatui::backend::Backend + Write>(
     terminal: &mut Terminal<B>,
     ui: &mut UI,
     input_handler: &mut InputHandler,
-    state: &mut EditorState,
+    app: &mut App,
+    suspender: Option<&Suspender>,
+    mut rpc_server: Option<&mut RpcServer>,
 ) -> Result<()>
 where
     B::Error: Send + Sync + 'static,
 {
     loop {
         // Check for pending theme changes
-        if let Some(theme_name) = state.take_pending_theme() {
+        if let Some(theme_name) = app.focused_mut().take_pending_theme() {
             ui.set_theme(&theme_name);
         }
 
+        // Notice changes made to the file by other programs
+        app.focused_mut().poll_external_change();
+
+        // Keep a `:share` view up to date
+        app.focused_mut().poll_share();
+
         // Update cursor blink state
-        state.update_cursor_blink();
+        app.focused_mut().update_cursor_blink();
 
         // Render UI
-        ui.render(terminal, state)?;
+        ui.render_app(terminal, app)?;
 
-        // Handle input
-        if let Some(event) = input_handler.poll_event(Duration::from_millis(100))? {
-            let should_quit = input_handler.handle_event(event, state)?;
-            if should_quit {
+        // Handle input. While validation is queued, only glance for input so
+        // the idle work keeps going between keystrokes.
+        let timeout = if app.focused().revalidation_pending() {
+            Duration::from_millis(1)
+        } else {
+            Duration::from_millis(100)
+        };
+        if let Some(event) = input_handler.poll_event(timeout)? {
+            let should_quit = input_handler.handle_event(event, app.focused_mut())?;
+            // Quitting a split closes just that pane (a pick always ends the session)
+            if should_quit && (app.focused().picked().is_some() || app.quit()) {
                 break;
             }
+            app.handle_pending();
+        } else {
+            // Idle: re-validate a slice of the document (`:set autovalidate`)
+            app.focused_mut().run_idle_validation(IDLE_BUDGET);
+        }
+
+        // Answer `--listen` requests
+        if let Some(server) = rpc_server.as_deref_mut() {
+            if server.poll(|method, params| rpc::dispatch(app, input_handler, method, params)) > 0 {
+                app.handle_pending();
+            }
+        }
+
+        // Hand the terminal to $EDITOR for `ge`
+        if let Some(text) = app.focused_mut().take_pending_external_edit() {
+            let edit = match suspender {
+                Some(suspender) => {
+                    suspender.suspend(terminal.backend_mut())?;
+                    let edit = external::edit_text(&text);
+                    suspender.resume(terminal.backend_mut())?;
+                    terminal.clear()?;
+                    edit
+                }
+                None if rpc_server.is_some() => Err(anyhow::anyhow!(
+                    "The terminal is busy serving --listen; can't run the editor"
+                )),
+                None => Err(anyhow::anyhow!("No terminal to run the editor in")),
+            };
+            app.focused_mut().finish_external_edit(&text, edit);
+        }
+
+        // Pipe through a shell command for `:!cmd`, showing the shell while it
+        // runs in case it asks for something on the terminal
+        if let Some(filter) = app.focused_mut().take_pending_filter() {
+            let output = match suspender {
+                Some(suspender) => {
+                    suspender.suspend(terminal.backend_mut())?;
+                    let output = external::run_filter(&filter.command, &filter.input);
+                    suspender.resume(terminal.backend_mut())?;
+                    terminal.clear()?;
+                    output
+                }
+                None => external::run_filter(&filter.command, &filter.input),
+            };
+            app.focused_mut().finish_filter(&filter, output);
         }
     }
 
@@ -0,0 +1,1139 @@
+//! JSON Schema validation for YAML documents.
+//!
+//! This module validates a `YamlTree` against a JSON Schema (draft 7 and
+//! later), reporting each violation as a `Diagnostic` that points at the
+//! offending node. Schemas may be written in JSON or YAML.
+//!
+//! # Supported Keywords
+//!
+//! - `type`, `enum`, `const`
+//! - `properties`, `patternProperties`, `additionalProperties`, `propertyNames`,
+//!   `required`, `minProperties`, `maxProperties`
+//! - `dependencies`, `dependentRequired`, `dependentSchemas`
+//! - `items` (single schema or tuple), `additionalItems`, `prefixItems`,
+//!   `minItems`, `maxItems`, `uniqueItems`, `contains`
+//! - `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `multipleOf`
+//! - `minLength`, `maxLength`, `pattern`
+//! - `allOf`, `anyOf`, `oneOf`, `not`, `if`/`then`/`else`
+//! - `$ref` to local definitions (`#/definitions/...`, `#/$defs/...`)
+//!
+//! Annotation-only keywords (`title`, `description`, `format`, ...) are ignored.
+//! Patterns are matched with [`crate::editor::regex`], which covers the
+//! common ECMA-262 syntax; a pattern it can't compile (lookarounds, `\p{..}`)
+//! is reported where it applies rather than skipped.
+//!
+//! # Example
+//!
+//! ```
+//! use yamlquill::document::parser::parse_yaml_auto;
+//! use yamlquill::document::tree::YamlTree;
+//! use yamlquill::schema::Schema;
+//!
+//! let schema = Schema::from_str(r#"{"type": "object", "required": ["name"]}"#).unwrap();
+//! let tree = YamlTree::new(parse_yaml_auto("age: 3\n").unwrap());
+//!
+//! let diagnostics = schema.validate(&tree);
+//! assert_eq!(diagnostics.len(), 1);
+//! assert!(diagnostics[0].message.contains("name"));
+//! ```
+
+use crate::document::duplicates::written_key;
+use crate::document::node::{YamlNode, YamlNumber, YamlString, YamlValue};
+use crate::document::tree::YamlTree;
+use crate::editor::diagnostics::Diagnostic;
+use crate::editor::regex::Regex;
+use anyhow::{anyhow, Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::path::Path;
+
+/// Diagnostic source name used for schema violations.
+pub const SCHEMA_SOURCE: &str = "schema";
+
+/// Maximum `$ref` nesting, guarding against self-referencing schemas.
+const MAX_REF_DEPTH: usize = 64;
+
+/// A parsed JSON Schema.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    root: Value,
+}
+
+impl Schema {
+    /// Parses a schema from JSON or YAML text.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(text: &str) -> Result<Self> {
+        let root: Value = serde_yaml::from_str(text).context("Failed to parse schema")?;
+        match root {
+            Value::Mapping(_) | Value::Bool(_) => Ok(Self { root }),
+            _ => Err(anyhow!("Schema must be an object or a boolean")),
+        }
+    }
+
+    /// Loads a schema from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read schema: {}", path.display()))?;
+        Self::from_str(&text)
+    }
+
+    /// Validates the tree and returns one diagnostic per violation.
+    pub fn validate(&self, tree: &YamlTree) -> Vec<Diagnostic> {
+        let mut validator = Validator {
+            root: &self.root,
+            tree,
+            diagnostics: Vec::new(),
+        };
+        validator.validate(&self.root, tree.root(), &[], "$", 0);
+        validator.diagnostics
+    }
+
+    /// Splits validation of `tree` into units that can be checked one at a
+    /// time: the root's own keywords, then each top-level entry.
+    ///
+    /// Keywords that look at the root as a whole (`$ref`, combinators,
+    /// `enum`, `uniqueItems`, ...) can't be split, and neither can a scalar
+    /// root; those give a single [`ValidationUnit::Whole`].
+    pub fn units(&self, tree: &YamlTree) -> Vec<ValidationUnit> {
+        const WHOLE_ROOT_KEYWORDS: &[&str] = &[
+            "$ref",
+            "allOf",
+            "anyOf",
+            "oneOf",
+            "not",
+            "if",
+            "enum",
+            "const",
+            "uniqueItems",
+            "contains",
+            "dependencies",
+            "dependentSchemas",
+        ];
+        let splittable = match &self.root {
+            Value::Mapping(map) => !WHOLE_ROOT_KEYWORDS.iter().any(|k| map.contains_key(*k)),
+            _ => false,
+        };
+        let count = match tree.root().value() {
+            YamlValue::Object(entries) if splittable => entries.len(),
+            YamlValue::Array(items) if splittable => items.len(),
+            _ => return vec![ValidationUnit::Whole],
+        };
+        std::iter::once(ValidationUnit::Root)
+            .chain((0..count).map(ValidationUnit::Entry))
+            .collect()
+    }
+
+    /// Validates one unit of [`Schema::units`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::document::parser::parse_yaml_auto;
+    /// use yamlquill::document::tree::YamlTree;
+    /// use yamlquill::schema::{Schema, ValidationUnit};
+    ///
+    /// let schema = Schema::from_str(
+    ///     r#"{"required": ["name"], "properties": {"port": {"type": "integer"}}}"#,
+    /// ).unwrap();
+    /// let tree = YamlTree::new(parse_yaml_auto("port: http\n").unwrap());
+    ///
+    /// let root = schema.validate_unit(&tree, ValidationUnit::Root);
+    /// assert!(root[0].message.contains("name"));
+    /// let port = schema.validate_unit(&tree, ValidationUnit::Entry(0));
+    /// assert_eq!(port[0].path, vec![0]);
+    /// ```
+    pub fn validate_unit(&self, tree: &YamlTree, unit: ValidationUnit) -> Vec<Diagnostic> {
+        let Value::Mapping(schema) = &self.root else {
+            return self.validate(tree);
+        };
+        let mut validator = Validator {
+            root: &self.root,
+            tree,
+            diagnostics: Vec::new(),
+        };
+        let root = tree.root();
+        match (unit, root.value()) {
+            (ValidationUnit::Root, YamlValue::Object(entries)) => {
+                validator.check_type(schema, root, &[], "$");
+                validator.check_object_bounds(schema, entries, &[], "$");
+            }
+            (ValidationUnit::Root, YamlValue::Array(items)) => {
+                validator.check_type(schema, root, &[], "$");
+                validator.check_array_bounds(schema, items, &[], "$", 0);
+            }
+            (ValidationUnit::Entry(i), YamlValue::Object(entries)) => {
+                if let Some((key, child)) = entries.get_index(i) {
+                    validator.check_property(schema, key, child, &[i], "$", 0);
+                }
+            }
+            (ValidationUnit::Entry(i), YamlValue::Array(items)) => {
+                if let Some(item) = items.get(i).filter(|item| !item.is_comment()) {
+                    let position = items[..i].iter().filter(|n| !n.is_comment()).count();
+                    validator.check_item(schema, position, item, &[i], "$", 0);
+                }
+            }
+            _ => return self.validate(tree),
+        }
+        validator.diagnostics
+    }
+}
+
+/// A part of the document that [`Schema::validate_unit`] checks on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationUnit {
+    /// The whole document
+    Whole,
+    /// The root's own keywords (`type`, `required`, bounds), not its entries
+    Root,
+    /// The top-level entry (key or item) at this index
+    Entry(usize),
+}
+
+impl ValidationUnit {
+    /// Returns the path of the node the unit reports on, and whether its
+    /// diagnostics cover that node's whole subtree or just the node.
+    pub fn scope(self) -> (Vec<usize>, bool) {
+        match self {
+            ValidationUnit::Whole => (Vec::new(), true),
+            ValidationUnit::Root => (Vec::new(), false),
+            ValidationUnit::Entry(i) => (vec![i], true),
+        }
+    }
+}
+
+/// Walks a document alongside a schema, collecting violations.
+struct Validator<'a> {
+    root: &'a Value,
+    tree: &'a YamlTree,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Validator<'a> {
+    fn report(&mut self, path: &[usize], location: &str, message: String) {
+        self.diagnostics.push(Diagnostic::error(
+            SCHEMA_SOURCE,
+            path.to_vec(),
+            location.to_string(),
+            message,
+        ));
+    }
+
+    /// Returns true if `node` is valid against `schema`, without recording anything.
+    fn is_valid(&self, schema: &Value, node: &YamlNode, depth: usize) -> bool {
+        let mut probe = Validator {
+            root: self.root,
+            tree: self.tree,
+            diagnostics: Vec::new(),
+        };
+        probe.validate(schema, node, &[], "$", depth);
+        probe.diagnostics.is_empty()
+    }
+
+    /// Follows alias nodes to the anchored node they refer to.
+    fn resolve<'n>(&self, node: &'n YamlNode) -> &'n YamlNode
+    where
+        'a: 'n,
+    {
+        let mut current = node;
+        for _ in 0..MAX_REF_DEPTH {
+            match current.value() {
+                YamlValue::Alias(name) => {
+                    match self
+                        .tree
+                        .anchor_registry()
+                        .get_anchor_path(name)
+                        .and_then(|p| self.tree.get_node(p))
+                    {
+                        Some(target) => current = target,
+                        None => return current,
+                    }
+                }
+                _ => return current,
+            }
+        }
+        current
+    }
+
+    fn validate(
+        &mut self,
+        schema: &Value,
+        node: &YamlNode,
+        path: &[usize],
+        location: &str,
+        depth: usize,
+    ) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                self.report(path, location, "not allowed by schema".to_string());
+                return;
+            }
+            Value::Mapping(map) => map,
+            _ => return,
+        };
+        let node = self.resolve(node);
+        // Sections of a lazily loaded file are checked once they're loaded
+        if node.value().is_deferred() {
+            return;
+        }
+
+        if let Some(reference) = get_str(schema, "$ref") {
+            if depth >= MAX_REF_DEPTH {
+                self.report(path, location, format!("$ref too deep: {}", reference));
+                return;
+            }
+            match resolve_ref(self.root, reference) {
+                Some(target) => self.validate(target, node, path, location, depth + 1),
+                None => self.report(path, location, format!("unresolved $ref: {}", reference)),
+            }
+        }
+
+        self.check_type(schema, node, path, location);
+        self.check_enum_const(schema, node, path, location);
+        self.check_combinators(schema, node, path, location, depth);
+
+        match node.value() {
+            YamlValue::Object(entries) => {
+                self.check_object(schema, entries, path, location, depth);
+                self.check_dependent_schemas(schema, node, entries, path, location, depth);
+            }
+            YamlValue::Array(items) => self.check_array(schema, items, path, location, depth),
+            YamlValue::Number(n) => self.check_number(schema, n, path, location),
+            YamlValue::String(s) => self.check_string(schema, s.as_str(), path, location),
+            _ => {}
+        }
+    }
+
+    fn check_type(&mut self, schema: &Mapping, node: &YamlNode, path: &[usize], location: &str) {
+        let Some(expected) = schema.get("type") else {
+            return;
+        };
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Sequence(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+            _ => return,
+        };
+        if !types.iter().any(|t| matches_type(node, t)) {
+            self.report(
+                path,
+                location,
+                format!(
+                    "expected {}, found {}",
+                    types.join(" or "),
+                    type_name(node.value())
+                ),
+            );
+        }
+    }
+
+    fn check_enum_const(
+        &mut self,
+        schema: &Mapping,
+        node: &YamlNode,
+        path: &[usize],
+        location: &str,
+    ) {
+        if let Some(Value::Sequence(options)) = schema.get("enum") {
+            let value = to_value(node, self.tree);
+            if !options.iter().any(|opt| values_equal(opt, &value)) {
+                let allowed: Vec<String> = options.iter().map(display_value).collect();
+                self.report(
+                    path,
+                    location,
+                    format!("must be one of: {}", allowed.join(", ")),
+                );
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if !values_equal(expected, &to_value(node, self.tree)) {
+                self.report(
+                    path,
+                    location,
+                    format!("must equal {}", display_value(expected)),
+                );
+            }
+        }
+    }
+
+    fn check_combinators(
+        &mut self,
+        schema: &Mapping,
+        node: &YamlNode,
+        path: &[usize],
+        location: &str,
+        depth: usize,
+    ) {
+        if let Some(Value::Sequence(all)) = schema.get("allOf") {
+            for sub in all {
+                self.validate(sub, node, path, location, depth + 1);
+            }
+        }
+        if let Some(Value::Sequence(any)) = schema.get("anyOf") {
+            if !any.iter().any(|sub| self.is_valid(sub, node, depth + 1)) {
+                self.report(
+                    path,
+                    location,
+                    "does not match any allowed schema".to_string(),
+                );
+            }
+        }
+        if let Some(Value::Sequence(one)) = schema.get("oneOf") {
+            let matches = one
+                .iter()
+                .filter(|sub| self.is_valid(sub, node, depth + 1))
+                .count();
+            if matches != 1 {
+                self.report(
+                    path,
+                    location,
+                    format!("must match exactly one schema (matched {})", matches),
+                );
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            if self.is_valid(not, node, depth + 1) {
+                self.report(path, location, "matches a disallowed schema".to_string());
+            }
+        }
+        if let Some(condition) = schema.get("if") {
+            let branch = if self.is_valid(condition, node, depth + 1) {
+                schema.get("then")
+            } else {
+                schema.get("else")
+            };
+            if let Some(branch) = branch {
+                self.validate(branch, node, path, location, depth + 1);
+            }
+        }
+    }
+
+    fn check_object(
+        &mut self,
+        schema: &Mapping,
+        entries: &indexmap::IndexMap<String, YamlNode>,
+        path: &[usize],
+        location: &str,
+        depth: usize,
+    ) {
+        self.check_object_bounds(schema, entries, path, location);
+        for (i, (key, child)) in entries.iter().enumerate() {
+            let child_path: Vec<usize> = path.iter().copied().chain(std::iter::once(i)).collect();
+            self.check_property(schema, key, child, &child_path, location, depth);
+        }
+    }
+
+    /// Checks the keywords about a mapping's keys: property counts and
+    /// `required`.
+    fn check_object_bounds(
+        &mut self,
+        schema: &Mapping,
+        entries: &indexmap::IndexMap<String, YamlNode>,
+        path: &[usize],
+        location: &str,
+    ) {
+        let count = entries
+            .keys()
+            .filter(|k| !k.starts_with("__comment_"))
+            .count();
+        if let Some(min) = get_u64(schema, "minProperties") {
+            if (count as u64) < min {
+                self.report(
+                    path,
+                    location,
+                    format!("must have at least {} properties", min),
+                );
+            }
+        }
+        if let Some(max) = get_u64(schema, "maxProperties") {
+            if (count as u64) > max {
+                self.report(
+                    path,
+                    location,
+                    format!("must have at most {} properties", max),
+                );
+            }
+        }
+
+        if let Some(Value::Sequence(required)) = schema.get("required") {
+            for key in required.iter().filter_map(|k| k.as_str()) {
+                if !entries.contains_key(key) {
+                    self.report(
+                        path,
+                        location,
+                        format!("missing required property '{}'", key),
+                    );
+                }
+            }
+        }
+
+        // Properties required when another is present: the list form of
+        // `dependencies` (draft 7) and `dependentRequired` (2019-09)
+        for keyword in ["dependencies", "dependentRequired"] {
+            let Some(Value::Mapping(dependencies)) = schema.get(keyword) else {
+                continue;
+            };
+            for (name, required) in dependencies {
+                let (Some(name), Value::Sequence(required)) = (name.as_str(), required) else {
+                    continue;
+                };
+                if !entries.contains_key(name) {
+                    continue;
+                }
+                for key in required.iter().filter_map(|k| k.as_str()) {
+                    if !entries.contains_key(key) {
+                        self.report(
+                            path,
+                            location,
+                            format!("property '{}' requires property '{}'", name, key),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks the mapping against the schemas that apply when a property is
+    /// present: the schema form of `dependencies` (draft 7) and
+    /// `dependentSchemas` (2019-09).
+    fn check_dependent_schemas(
+        &mut self,
+        schema: &Mapping,
+        node: &YamlNode,
+        entries: &indexmap::IndexMap<String, YamlNode>,
+        path: &[usize],
+        location: &str,
+        depth: usize,
+    ) {
+        for keyword in ["dependencies", "dependentSchemas"] {
+            let Some(Value::Mapping(dependencies)) = schema.get(keyword) else {
+                continue;
+            };
+            for (name, sub) in dependencies {
+                let Some(name) = name.as_str() else {
+                    continue;
+                };
+                if matches!(sub, Value::Mapping(_) | Value::Bool(_)) && entries.contains_key(name) {
+                    self.validate(sub, node, path, location, depth + 1);
+                }
+            }
+        }
+    }
+
+    /// Returns whether `pattern` matches somewhere in `text`, or `None`
+    /// after reporting a pattern that doesn't compile at `location`.
+    fn pattern_matches(
+        &mut self,
+        pattern: &str,
+        text: &str,
+        path: &[usize],
+        location: &str,
+    ) -> Option<bool> {
+        match Regex::new(pattern) {
+            Ok(regex) => Some(regex.is_match(text)),
+            Err(e) => {
+                self.report(
+                    path,
+                    location,
+                    format!("unsupported pattern '{}': {}", pattern, e),
+                );
+                None
+            }
+        }
+    }
+
+    /// Checks one entry of a mapping at `location` against `propertyNames`
+    /// and its property schemas, or `additionalProperties` if neither
+    /// `properties` nor `patternProperties` has one.
+    fn check_property(
+        &mut self,
+        schema: &Mapping,
+        key: &str,
+        child: &YamlNode,
+        child_path: &[usize],
+        location: &str,
+        depth: usize,
+    ) {
+        if key.starts_with("__comment_") {
+            return;
+        }
+        let child_location = format!("{}.{}", location, key);
+        let name = written_key(key);
+        if let Some(names) = schema.get("propertyNames") {
+            let name_node = YamlNode::new(YamlValue::String(YamlString::Plain(name.to_string())));
+            if !self.is_valid(names, &name_node, depth + 1) {
+                self.report(
+                    child_path,
+                    &child_location,
+                    format!("property name '{}' is not allowed by propertyNames", name),
+                );
+            }
+        }
+
+        let mut matched = false;
+        if let Some(sub) = match schema.get("properties") {
+            Some(Value::Mapping(props)) => props.get(key),
+            _ => None,
+        } {
+            self.validate(sub, child, child_path, &child_location, depth + 1);
+            matched = true;
+        }
+        if let Some(Value::Mapping(patterns)) = schema.get("patternProperties") {
+            for (pattern, sub) in patterns {
+                let Some(pattern) = pattern.as_str() else {
+                    continue;
+                };
+                if self.pattern_matches(pattern, name, child_path, &child_location) == Some(true) {
+                    self.validate(sub, child, child_path, &child_location, depth + 1);
+                    matched = true;
+                }
+            }
+        }
+        if !matched {
+            match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    self.report(
+                        child_path,
+                        &child_location,
+                        format!("unexpected property '{}'", key),
+                    );
+                }
+                Some(sub @ Value::Mapping(_)) => {
+                    self.validate(sub, child, child_path, &child_location, depth + 1)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_array(
+        &mut self,
+        schema: &Mapping,
+        items: &[YamlNode],
+        path: &[usize],
+        location: &str,
+        depth: usize,
+    ) {
+        self.check_array_bounds(schema, items, path, location, depth);
+        // Comments are kept among the items; they are not items themselves
+        let elements = items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !item.is_comment());
+        for (position, (i, item)) in elements.enumerate() {
+            let child_path: Vec<usize> = path.iter().copied().chain(std::iter::once(i)).collect();
+            self.check_item(schema, position, item, &child_path, location, depth);
+        }
+    }
+
+    /// Checks the keywords about a sequence as a whole: item counts,
+    /// `uniqueItems` and `contains`.
+    fn check_array_bounds(
+        &mut self,
+        schema: &Mapping,
+        items: &[YamlNode],
+        path: &[usize],
+        location: &str,
+        depth: usize,
+    ) {
+        let items: Vec<&YamlNode> = items.iter().filter(|item| !item.is_comment()).collect();
+        if let Some(min) = get_u64(schema, "minItems") {
+            if (items.len() as u64) < min {
+                self.report(path, location, format!("must have at least {} items", min));
+            }
+        }
+        if let Some(max) = get_u64(schema, "maxItems") {
+            if (items.len() as u64) > max {
+                self.report(path, location, format!("must have at most {} items", max));
+            }
+        }
+        if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+            let values: Vec<Value> = items.iter().map(|n| to_value(n, self.tree)).collect();
+            let duplicate = values
+                .iter()
+                .enumerate()
+                .any(|(i, a)| values[i + 1..].iter().any(|b| values_equal(a, b)));
+            if duplicate {
+                self.report(path, location, "items must be unique".to_string());
+            }
+        }
+        if let Some(contains) = schema.get("contains") {
+            if !items
+                .iter()
+                .any(|item| self.is_valid(contains, item, depth + 1))
+            {
+                self.report(
+                    path,
+                    location,
+                    "no item matches the 'contains' schema".to_string(),
+                );
+            }
+        }
+    }
+
+    /// Checks item `index` of a sequence at `location` against its tuple or
+    /// `items` schema.
+    fn check_item(
+        &mut self,
+        schema: &Mapping,
+        index: usize,
+        item: &YamlNode,
+        child_path: &[usize],
+        location: &str,
+        depth: usize,
+    ) {
+        // Tuple validation: `prefixItems` (2020-12) or array-form `items` (draft 7)
+        let (tuple, rest) = match (schema.get("prefixItems"), schema.get("items")) {
+            (Some(Value::Sequence(prefix)), rest) => (Some(prefix), rest),
+            (None, Some(Value::Sequence(prefix))) => (Some(prefix), schema.get("additionalItems")),
+            (None, rest) => (None, rest),
+            (Some(_), rest) => (None, rest),
+        };
+        let sub = match tuple {
+            Some(prefix) if index < prefix.len() => Some(&prefix[index]),
+            _ => rest,
+        };
+        if let Some(sub) = sub {
+            let child_location = format!("{}[{}]", location, index);
+            self.validate(sub, item, child_path, &child_location, depth + 1);
+        }
+    }
+
+    fn check_number(&mut self, schema: &Mapping, n: &YamlNumber, path: &[usize], location: &str) {
+        let value = match n {
+            YamlNumber::Integer(i) => *i as f64,
+            YamlNumber::Float(f) => *f,
+        };
+        let display = format_number(value);
+
+        if let Some(min) = get_f64(schema, "minimum") {
+            if value < min {
+                self.report(
+                    path,
+                    location,
+                    format!("{} is less than minimum {}", display, format_number(min)),
+                );
+            }
+        }
+        if let Some(max) = get_f64(schema, "maximum") {
+            if value > max {
+                self.report(
+                    path,
+                    location,
+                    format!("{} is greater than maximum {}", display, format_number(max)),
+                );
+            }
+        }
+        if let Some(min) = get_f64(schema, "exclusiveMinimum") {
+            if value <= min {
+                self.report(
+                    path,
+                    location,
+                    format!("{} must be greater than {}", display, format_number(min)),
+                );
+            }
+        }
+        if let Some(max) = get_f64(schema, "exclusiveMaximum") {
+            if value >= max {
+                self.report(
+                    path,
+                    location,
+                    format!("{} must be less than {}", display, format_number(max)),
+                );
+            }
+        }
+        if let Some(factor) = get_f64(schema, "multipleOf") {
+            if factor > 0.0 {
+                let quotient = value / factor;
+                if (quotient - quotient.round()).abs() > 1e-9 {
+                    self.report(
+                        path,
+                        location,
+                        format!("{} is not a multiple of {}", display, format_number(factor)),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_string(&mut self, schema: &Mapping, s: &str, path: &[usize], location: &str) {
+        let len = s.chars().count() as u64;
+        if let Some(min) = get_u64(schema, "minLength") {
+            if len < min {
+                self.report(
+                    path,
+                    location,
+                    format!("must be at least {} characters", min),
+                );
+            }
+        }
+        if let Some(max) = get_u64(schema, "maxLength") {
+            if len > max {
+                self.report(
+                    path,
+                    location,
+                    format!("must be at most {} characters", max),
+                );
+            }
+        }
+        if let Some(pattern) = get_str(schema, "pattern") {
+            if self.pattern_matches(pattern, s, path, location) == Some(false) {
+                self.report(path, location, format!("must match pattern '{}'", pattern));
+            }
+        }
+    }
+}
+
+/// Resolves a local JSON pointer reference such as `#/definitions/port`.
+fn resolve_ref<'v>(root: &'v Value, reference: &str) -> Option<&'v Value> {
+    let pointer = reference.strip_prefix('#')?;
+    let mut current = root;
+    for segment in pointer.split('/').filter(|s| !s.is_empty()) {
+        let segment = segment.replace("~1", "/").replace("~0", "~");
+        current = match current {
+            Value::Mapping(map) => map.get(segment.as_str())?,
+            Value::Sequence(seq) => seq.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+fn get_str<'v>(schema: &'v Mapping, key: &str) -> Option<&'v str> {
+    schema.get(key).and_then(|v| v.as_str())
+}
+
+fn get_f64(schema: &Mapping, key: &str) -> Option<f64> {
+    schema.get(key).and_then(|v| v.as_f64())
+}
+
+fn get_u64(schema: &Mapping, key: &str) -> Option<u64> {
+    schema.get(key).and_then(|v| v.as_u64())
+}
+
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.is_finite() {
+        format!("{}", n as i64)
+    } else {
+        format!("{}", n)
+    }
+}
+
+/// Returns true if the node is an instance of the JSON Schema type name.
+fn matches_type(node: &YamlNode, type_name: &str) -> bool {
+    match (type_name, node.value()) {
+        ("object", YamlValue::Object(_)) => true,
+        ("array", YamlValue::Array(_)) => true,
+        ("string", YamlValue::String(_)) => true,
+        ("boolean", YamlValue::Boolean(_)) => true,
+        ("null", YamlValue::Null) => true,
+        ("number", YamlValue::Number(_)) => true,
+        ("integer", YamlValue::Number(YamlNumber::Integer(_))) => true,
+        ("integer", YamlValue::Number(YamlNumber::Float(f))) => f.fract() == 0.0,
+        _ => false,
+    }
+}
+
+/// Returns the JSON Schema type name of a value, for messages.
+fn type_name(value: &YamlValue) -> &'static str {
+    match value {
+        YamlValue::Object(_) => "object",
+        YamlValue::Array(_) | YamlValue::MultiDoc(_) => "array",
+        YamlValue::String(_) | YamlValue::Alias(_) | YamlValue::Comment(_) => "string",
+        YamlValue::Number(YamlNumber::Integer(_)) => "integer",
+        YamlValue::Number(YamlNumber::Float(_)) => "number",
+        YamlValue::Boolean(_) => "boolean",
+        YamlValue::Null => "null",
+        YamlValue::Deferred(_) => "unloaded section",
+    }
+}
+
+/// Converts a node to a `serde_yaml::Value` for equality checks, resolving aliases.
+fn to_value(node: &YamlNode, tree: &YamlTree) -> Value {
+    match node.value() {
+        YamlValue::Object(entries) => Value::Mapping(
+            entries
+                .iter()
+                .filter(|(k, _)| !k.starts_with("__comment_"))
+                .map(|(k, v)| (Value::String(k.clone()), to_value(v, tree)))
+                .collect(),
+        ),
+        YamlValue::Array(items) | YamlValue::MultiDoc(items) => Value::Sequence(
+            items
+                .iter()
+                .filter(|v| !v.is_comment())
+                .map(|v| to_value(v, tree))
+                .collect(),
+        ),
+        YamlValue::String(s) => Value::String(s.as_str().to_string()),
+        YamlValue::Number(YamlNumber::Integer(i)) => Value::Number((*i).into()),
+        YamlValue::Number(YamlNumber::Float(f)) => Value::Number((*f).into()),
+        YamlValue::Boolean(b) => Value::Bool(*b),
+        YamlValue::Null | YamlValue::Comment(_) => Value::Null,
+        YamlValue::Deferred(source) => serde_yaml::from_str(&source.text).unwrap_or(Value::Null),
+        YamlValue::Alias(name) => tree
+            .anchor_registry()
+            .get_anchor_path(name)
+            .and_then(|p| tree.get_node(p))
+            .filter(|target| !matches!(target.value(), YamlValue::Alias(_)))
+            .map(|target| to_value(target, tree))
+            .unwrap_or(Value::Null),
+    }
+}
+
+/// Compares two values, treating integers and floats with the same value as equal.
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        (Value::Sequence(x), Value::Sequence(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(a, b)| values_equal(a, b))
+        }
+        (Value::Mapping(x), Value::Mapping(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .all(|(k, v)| y.get(k).is_some_and(|other| values_equal(v, other)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Formats a schema value compactly for messages.
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("\"{}\"", s),
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        other => serde_yaml::to_string(other)
+            .map(|s| s.trim().replace('\n', " "))
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::parser::parse_yaml_auto;
+
+    fn check(schema: &str, yaml: &str) -> Vec<Diagnostic> {
+        let schema = Schema::from_str(schema).unwrap();
+        let tree = YamlTree::new(parse_yaml_auto(yaml).unwrap());
+        schema.validate(&tree)
+    }
+
+    #[test]
+    fn test_valid_document_has_no_diagnostics() {
+        let diags = check(
+            r#"{"type": "object", "properties": {"name": {"type": "string"}}}"#,
+            "name: web\n",
+        );
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn test_type_mismatch_points_at_node() {
+        let diags = check(
+            r#"{"properties": {"replicas": {"type": "integer"}}}"#,
+            "name: web\nreplicas: three\n",
+        );
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].path, vec![1]);
+        assert_eq!(diags[0].location, "$.replicas");
+        assert_eq!(diags[0].message, "expected integer, found string");
+    }
+
+    #[test]
+    fn test_required_and_additional_properties() {
+        let diags = check(
+            r#"{"required": ["image"], "properties": {"name": {}}, "additionalProperties": false}"#,
+            "name: web\nextra: 1\n",
+        );
+        let messages: Vec<&str> = diags.iter().map(|d| d.message.as_str()).collect();
+        assert!(messages.contains(&"missing required property 'image'"));
+        assert!(messages.contains(&"unexpected property 'extra'"));
+    }
+
+    #[test]
+    fn test_array_items_and_bounds() {
+        let diags = check(
+            r#"{"type": "array", "maxItems": 2, "items": {"type": "integer", "minimum": 0}}"#,
+            "- 1\n- -5\n- 3\n",
+        );
+        assert_eq!(diags.len(), 2);
+        assert!(diags.iter().any(|d| d.path == vec![1]));
+        assert!(diags.iter().any(|d| d.message.contains("at most 2 items")));
+    }
+
+    #[test]
+    fn test_enum_and_string_length() {
+        let diags = check(
+            r#"{"properties": {"policy": {"enum": ["Always", "Never"]}, "tag": {"maxLength": 3}}}"#,
+            "policy: Sometimes\ntag: latest\n",
+        );
+        assert_eq!(diags.len(), 2);
+        assert!(diags[0].message.contains("\"Always\""));
+    }
+
+    #[test]
+    fn test_local_ref_and_yaml_schema() {
+        let schema = "definitions:\n  port:\n    type: integer\n    maximum: 65535\nproperties:\n  port:\n    $ref: '#/definitions/port'\n";
+        let diags = check(schema, "port: 70000\n");
+        assert_eq!(diags.len(), 1);
+        assert!(diags[0].message.contains("maximum"));
+    }
+
+    #[test]
+    fn test_one_of_and_not() {
+        let schema = r#"{"properties": {"a": {"oneOf": [{"type": "integer"}, {"minimum": 0}]}, "b": {"not": {"type": "null"}}}}"#;
+        let diags = check(schema, "a: 5\nb: null\n");
+        assert_eq!(diags.len(), 2);
+    }
+
+    #[test]
+    fn test_aliases_are_validated_as_their_anchor() {
+        let diags = check(
+            r#"{"properties": {"b": {"type": "integer"}}}"#,
+            "a: &x hello\nb: *x\n",
+        );
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].path, vec![1]);
+    }
+
+    #[test]
+    fn test_comments_are_ignored() {
+        let diags = check(
+            r#"{"additionalProperties": false, "properties": {"a": {}}}"#,
+            "# leading comment\na: 1\n",
+        );
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn test_comments_in_sequences_are_not_items() {
+        let schema = r#"{"type": "array", "maxItems": 2, "uniqueItems": true,
+            "prefixItems": [{"type": "integer"}, {"maximum": 5}]}"#;
+        let yaml = "# ports\n- 1\n# the second\n- 9\n";
+        let diags = check(schema, yaml);
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(diags[0].path, vec![3]);
+        assert_eq!(diags[0].location, "$[1]");
+
+        // Checking item by item gives the same result
+        let schema = Schema::from_str(schema).unwrap();
+        let tree = YamlTree::new(parse_yaml_auto(yaml).unwrap());
+        let partial: Vec<Diagnostic> = schema
+            .units(&tree)
+            .into_iter()
+            .flat_map(|unit| schema.validate_unit(&tree, unit))
+            .collect();
+        assert_eq!(partial, diags);
+    }
+
+    #[test]
+    fn test_units_together_match_whole_validation() {
+        let schema = Schema::from_str(
+            r#"{"required": ["image"], "maxProperties": 2, "additionalProperties": false,
+                "properties": {"name": {"type": "string"}, "port": {"maximum": 10}}}"#,
+        )
+        .unwrap();
+        let tree = YamlTree::new(
+            parse_yaml_auto(
+                "name: 1
+# note
+port: 80
+extra: x
+",
+            )
+            .unwrap(),
+        );
+
+        let units = schema.units(&tree);
+        assert_eq!(units.len(), 5);
+        assert_eq!(units[0], ValidationUnit::Root);
+        let mut partial: Vec<Diagnostic> = units
+            .into_iter()
+            .flat_map(|unit| schema.validate_unit(&tree, unit))
+            .collect();
+        let mut whole = schema.validate(&tree);
+        partial.sort_by(|a, b| a.location.cmp(&b.location));
+        whole.sort_by(|a, b| a.location.cmp(&b.location));
+        assert_eq!(partial, whole);
+        assert_eq!(whole.len(), 5);
+
+        // Keywords about the root as a whole can't be split
+        let schema = Schema::from_str(r#"{"oneOf": [{"required": ["a"]}]}"#).unwrap();
+        assert_eq!(schema.units(&tree), vec![ValidationUnit::Whole]);
+    }
+
+    #[test]
+    fn test_pattern_and_pattern_properties() {
+        let diags = check(
+            r#"{"patternProperties": {"^x-": {"type": "string", "pattern": "^[a-z]+$"}},
+                "properties": {"name": {}}, "additionalProperties": false}"#,
+            "name: web\nx-team: Ops\nx-tier: db\nother: 1\n",
+        );
+        let found: Vec<(&str, &str)> = diags
+            .iter()
+            .map(|d| (d.location.as_str(), d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("$.x-team", "must match pattern '^[a-z]+$'"),
+                ("$.other", "unexpected property 'other'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_pattern_is_reported() {
+        let diags = check(r#"{"pattern": "(?<=a)b"}"#, "ab\n");
+        assert_eq!(diags.len(), 1);
+        assert!(diags[0]
+            .message
+            .starts_with("unsupported pattern '(?<=a)b'"));
+    }
+
+    #[test]
+    fn test_property_names() {
+        let diags = check(
+            r#"{"propertyNames": {"maxLength": 4}}"#,
+            "name: web\nreplicas: 3\n",
+        );
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].path, vec![1]);
+        assert_eq!(
+            diags[0].message,
+            "property name 'replicas' is not allowed by propertyNames"
+        );
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let schema = r#"{"dependencies": {"tls": ["cert"], "port": {"required": ["host"]}},
+                         "dependentRequired": {"name": ["image"]}}"#;
+        let messages: Vec<String> = check(schema, "tls: true\nport: 80\nname: web\n")
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "property 'tls' requires property 'cert'",
+                "property 'name' requires property 'image'",
+                "missing required property 'host'",
+            ]
+        );
+        assert!(check(schema, "other: 1\n").is_empty());
+
+        // A dependent schema looks at the whole root, so it can't be split
+        let schema =
+            Schema::from_str(r#"{"dependentSchemas": {"a": {"required": ["b"]}}}"#).unwrap();
+        let tree = YamlTree::new(parse_yaml_auto("a: 1\n").unwrap());
+        assert_eq!(schema.units(&tree), vec![ValidationUnit::Whole]);
+    }
+
+    #[test]
+    fn test_invalid_schema_is_rejected() {
+        assert!(Schema::from_str("42").is_err());
+    }
+}
@@ -3,8 +3,13 @@
 //! This module defines the [`ThemeColors`] struct which contains all color
 //! values used in the yamlquill terminal UI. Colors are organized into three
 //! categories: syntax highlighting, UI elements, and semantic colors.
+//!
+//! Colors are (de)serialized as strings in the format ratatui parses: names
+//! like `"LightBlue"` or `"dark gray"`, hex like `"#88c0d0"`, or an ANSI
+//! index like `"208"`.
 
 use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
 
 /// Defines all colors used in a yamlquill theme.
 ///
@@ -26,49 +31,83 @@ use ratatui::style::Color;
 /// let light = ThemeColors::default_light();
 /// println!("Background: {:?}", light.background);
 /// ```
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ThemeColors {
     // Syntax colors
     /// Color for JSON object keys.
+    #[serde(with = "color_string")]
     pub key: Color,
     /// Color for JSON string values.
+    #[serde(with = "color_string")]
     pub string: Color,
     /// Color for JSON number values.
+    #[serde(with = "color_string")]
     pub number: Color,
     /// Color for JSON boolean values (true/false).
+    #[serde(with = "color_string")]
     pub boolean: Color,
     /// Color for JSON null values.
+    #[serde(with = "color_string")]
     pub null: Color,
 
     // UI colors
     /// Main background color for the editor.
+    #[serde(with = "color_string")]
     pub background: Color,
     /// Main foreground/text color for the editor.
+    #[serde(with = "color_string")]
     pub foreground: Color,
     /// Color for the cursor position indicator.
+    #[serde(with = "color_string")]
     pub cursor: Color,
     /// Background color for the status line.
+    #[serde(with = "color_string")]
     pub status_line_bg: Color,
     /// Foreground/text color for the status line.
+    #[serde(with = "color_string")]
     pub status_line_fg: Color,
 
     // Semantic colors
     /// Color for error messages and indicators.
+    #[serde(with = "color_string")]
     pub error: Color,
     /// Color for warning messages and indicators.
+    #[serde(with = "color_string")]
     pub warning: Color,
     /// Color for informational messages and indicators.
+    #[serde(with = "color_string")]
     pub info: Color,
     /// Background color for search result highlights.
+    #[serde(with = "color_string")]
     pub search_highlight: Color,
     /// Color for collapsed previews (object/array content when collapsed).
+    #[serde(with = "color_string")]
     pub preview: Color,
     /// Background color for visual mode selection.
+    #[serde(with = "color_string")]
     pub visual_selection_bg: Color,
     /// Color for YAML comments.
+    #[serde(with = "color_string")]
     pub comment: Color,
 }
 
+/// (De)serializes a [`Color`] through its string form.
+mod color_string {
+    use ratatui::style::Color;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(color)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse()
+            .map_err(|_| D::Error::custom(format!("invalid color '{}'", text)))
+    }
+}
+
 impl ThemeColors {
     /// Returns the default dark color scheme.
     ///
@@ -4,6 +4,8 @@
 //! - Color definitions ([`colors`] module)
 //! - Theme data structure ([`Theme`])
 //! - Built-in theme access ([`get_builtin_theme`])
+//! - User themes loaded from TOML files in [`themes_dir`] ([`load_theme`],
+//!   [`list_themes`])
 //!
 //! # Built-in Themes
 //!
@@ -32,7 +34,11 @@
 
 pub mod colors;
 
+use anyhow::{anyhow, bail, Context, Result};
 use colors::ThemeColors;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 /// A color theme for the yamlquill terminal UI.
 ///
@@ -189,3 +195,101 @@ pub fn list_builtin_themes() -> Vec<String> {
     themes.sort();
     themes
 }
+
+/// Returns the directory user themes are loaded from:
+/// `~/.config/yamlquill/themes`.
+pub fn themes_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config").join("yamlquill").join("themes"))
+}
+
+/// Loads a user theme from a TOML file.
+///
+/// The file names any subset of the [`ThemeColors`] fields; the rest come
+/// from the built-in theme named by an optional top-level `extends` key
+/// (default `"default-dark"`). The theme takes its name from the file stem.
+///
+/// ```toml
+/// extends = "nord"
+/// key = "#88c0d0"
+/// string = "LightGreen"
+/// cursor = "208"
+/// ```
+pub fn load_theme_file(path: &Path) -> Result<Theme> {
+    let name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow!("Invalid theme file name: {}", path.display()))?
+        .to_string();
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read theme {}", path.display()))?;
+    let mut overrides: toml::Table =
+        toml::from_str(&text).with_context(|| format!("Invalid theme {}", path.display()))?;
+
+    let base_name = match overrides.remove("extends") {
+        Some(toml::Value::String(base)) => base,
+        Some(_) => bail!("Invalid theme {}: extends must be a string", path.display()),
+        None => "default-dark".to_string(),
+    };
+    let base = get_builtin_theme(&base_name).ok_or_else(|| {
+        anyhow!(
+            "Invalid theme {}: unknown base theme '{}'",
+            path.display(),
+            base_name
+        )
+    })?;
+
+    let mut table = toml::Table::try_from(&base.colors)?;
+    table.extend(overrides);
+    let colors = ThemeColors::deserialize(toml::Value::Table(table))
+        .with_context(|| format!("Invalid theme {}", path.display()))?;
+    Ok(Theme { name, colors })
+}
+
+/// Loads a theme by name: built-in themes first, then `<dir>/<name>.toml`.
+pub fn load_theme_from(dir: Option<&Path>, name: &str) -> Result<Theme> {
+    if let Some(theme) = get_builtin_theme(name) {
+        return Ok(theme);
+    }
+    if let Some(dir) = dir {
+        let path = dir.join(format!("{}.toml", name));
+        if path.is_file() {
+            return load_theme_file(&path);
+        }
+    }
+    bail!("Unknown theme: {}", name)
+}
+
+/// Loads a built-in or user theme by name. See [`load_theme_from`].
+pub fn load_theme(name: &str) -> Result<Theme> {
+    load_theme_from(themes_dir().as_deref(), name)
+}
+
+/// Like [`load_theme`], but returns `None` when the theme is unknown or
+/// fails to load.
+pub fn get_theme(name: &str) -> Option<Theme> {
+    load_theme(name).ok()
+}
+
+/// Returns the sorted names of the built-in themes plus every `*.toml`
+/// theme in `dir`.
+pub fn list_themes_in(dir: Option<&Path>) -> Vec<String> {
+    let mut themes = list_builtin_themes();
+    if let Some(entries) = dir.and_then(|dir| fs::read_dir(dir).ok()) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("toml") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    themes.push(stem.to_string());
+                }
+            }
+        }
+    }
+    themes.sort();
+    themes.dedup();
+    themes
+}
+
+/// Returns the names of all built-in and user themes.
+pub fn list_themes() -> Vec<String> {
+    list_themes_in(themes_dir().as_deref())
+}
@@ -0,0 +1,111 @@
+//! Backup browser popup (`:backups`).
+
+use crate::editor::backups::BackupPickerState;
+use crate::editor::review::ChangeKind;
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use crate::ui::tree_view::format_collapsed_preview;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Renders the backup browser popup.
+///
+/// Each row shows when a backup was written, its size and how many changes
+/// the buffer has against it. Below the list, the selected backup's changes
+/// are previewed: `-` lines are the backup's values, `+` lines the buffer's.
+pub fn render_backup_picker(f: &mut Frame, state: &BackupPickerState, colors: &ThemeColors) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Backups ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+    let width = (block.inner(area).width as usize).saturating_sub(2).max(10);
+
+    let mut lines = vec![Line::from("")];
+    for (idx, backup) in state.backups.iter().enumerate() {
+        let is_selected = idx == state.selected_index;
+        let cursor = if is_selected { "> " } else { "  " };
+        let name = backup
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let changes = match backup.changes.len() {
+            0 => "same as buffer".to_string(),
+            1 => "1 change".to_string(),
+            n => format!("{} changes", n),
+        };
+        let text = format!(
+            "{}{}  {}  {} bytes  {}",
+            cursor,
+            crate::editor::cron::format_time(backup.modified),
+            name,
+            backup.size,
+            changes
+        );
+        let style = if is_selected {
+            Style::default()
+                .fg(ratatui::style::Color::White)
+                .bg(colors.cursor)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(colors.foreground)
+        };
+        lines.push(Line::from(Span::styled(text, style)));
+    }
+
+    if let Some(backup) = state.selected() {
+        lines.push(Line::from(""));
+        for change in &backup.changes {
+            let kind_color = match change.kind {
+                ChangeKind::Added => colors.info,
+                ChangeKind::Removed => colors.error,
+                ChangeKind::Modified => colors.warning,
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    change.kind.to_string(),
+                    Style::default().fg(kind_color).add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  "),
+                Span::styled(change.relative_label(), Style::default().fg(colors.key)),
+            ]));
+            if let Some(base) = &change.base {
+                lines.push(Line::from(Span::styled(
+                    format!("- {}", format_collapsed_preview(base, width)),
+                    Style::default().fg(colors.error),
+                )));
+            }
+            if let Some(current) = &change.current {
+                lines.push(Line::from(Span::styled(
+                    format!("+ {}", format_collapsed_preview(current, width)),
+                    Style::default().fg(colors.info),
+                )));
+            }
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "↑/↓: Navigate  Enter: Restore into buffer  Esc: Cancel",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
@@ -0,0 +1,70 @@
+//! Bookmark picker popup (`:bookmarks`).
+
+use crate::editor::bookmarks::BookmarkPickerState;
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Renders the bookmark picker popup.
+///
+/// Each row shows the bookmark name, the file it points into (relative to
+/// the project root) and the node's path. The selected row is highlighted.
+pub fn render_bookmark_picker(f: &mut Frame, state: &BookmarkPickerState, colors: &ThemeColors) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Bookmarks ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+
+    let name_width = state
+        .bookmarks
+        .iter()
+        .map(|b| b.name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = vec![Line::from("")];
+    for (idx, bookmark) in state.bookmarks.iter().enumerate() {
+        let is_selected = idx == state.selected_index;
+        let cursor = if is_selected { "> " } else { "  " };
+        let name = format!("{}{:width$}  ", cursor, bookmark.name, width = name_width);
+        let location = format!("{}  {}", bookmark.file, bookmark.path);
+
+        let line = if is_selected {
+            let style = Style::default()
+                .fg(ratatui::style::Color::White)
+                .bg(colors.cursor)
+                .add_modifier(Modifier::BOLD);
+            Line::from(vec![
+                Span::styled(name, style),
+                Span::styled(location, style),
+            ])
+        } else {
+            Line::from(vec![
+                Span::styled(name, Style::default().fg(colors.key)),
+                Span::styled(location, Style::default().fg(colors.preview)),
+            ])
+        };
+        lines.push(line);
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "↑/↓: Navigate  Enter: Jump  Esc: Cancel",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
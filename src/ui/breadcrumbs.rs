@@ -0,0 +1,158 @@
+//! Breadcrumb bar above the tree view (`show_breadcrumbs`, `:set breadcrumbs`).
+//!
+//! Shows the cursor's path in dot notation
+//! (`spec.template.spec.containers[0].image`). Each segment can be clicked
+//! to jump to that ancestor, so the render returns where every segment was
+//! drawn.
+
+use crate::document::node::YamlValue;
+use crate::document::tree::YamlTree;
+use crate::theme::colors::ThemeColors;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+/// One segment of the cursor's path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breadcrumb {
+    /// The key, or `[i]` for a list element or record
+    pub label: String,
+    /// Path of the node the segment names
+    pub path: Vec<usize>,
+}
+
+/// A clickable run of cells that jumps to a node, such as a breadcrumb
+/// segment or a pinned path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClickTarget {
+    /// Screen row (0-based)
+    pub row: u16,
+    /// First screen column (0-based)
+    pub start: u16,
+    /// Column just past the target
+    pub end: u16,
+    /// Path of the node to jump to
+    pub path: Vec<usize>,
+}
+
+/// Returns the segments leading to `path`, from the outermost ancestor.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::parser::parse_yaml_auto;
+/// use yamlquill::document::tree::YamlTree;
+/// use yamlquill::ui::breadcrumbs::breadcrumbs;
+///
+/// let tree = YamlTree::new(parse_yaml_auto("spec:\n  ports: [80, 443]\n").unwrap());
+/// let labels: Vec<_> = breadcrumbs(&tree, &[0, 0, 1])
+///     .into_iter()
+///     .map(|c| c.label)
+///     .collect();
+/// assert_eq!(labels, ["spec", "ports", "[1]"]);
+/// ```
+pub fn breadcrumbs(tree: &YamlTree, path: &[usize]) -> Vec<Breadcrumb> {
+    let mut crumbs = Vec::with_capacity(path.len());
+    let mut current = tree.root();
+    for (depth, &index) in path.iter().enumerate() {
+        let (label, next) = match current.value() {
+            YamlValue::Object(entries) => match entries.get_index(index) {
+                Some((key, node)) => (key.clone(), node),
+                None => break,
+            },
+            YamlValue::Array(items) | YamlValue::MultiDoc(items) => match items.get(index) {
+                Some(node) => (format!("[{}]", index), node),
+                None => break,
+            },
+            _ => break,
+        };
+        crumbs.push(Breadcrumb {
+            label,
+            path: path[..=depth].to_vec(),
+        });
+        current = next;
+    }
+    crumbs
+}
+
+/// Joins breadcrumbs into a dotted path like `spec.containers[0].image`.
+pub fn dotted_path(crumbs: &[Breadcrumb]) -> String {
+    let mut text = String::new();
+    for (i, crumb) in crumbs.iter().enumerate() {
+        if i > 0 && !crumb.label.starts_with('[') {
+            text.push('.');
+        }
+        text.push_str(&crumb.label);
+    }
+    text
+}
+
+/// Renders the breadcrumb bar and returns where each segment landed.
+///
+/// Keys are joined with `.` and indexes follow their list directly. When the
+/// path is wider than the bar, the outermost segments are dropped behind a
+/// leading `…`.
+pub fn render_breadcrumbs(
+    f: &mut Frame,
+    area: Rect,
+    crumbs: &[Breadcrumb],
+    colors: &ThemeColors,
+) -> Vec<ClickTarget> {
+    let separator = |i: usize, crumb: &Breadcrumb| {
+        if i == 0 || crumb.label.starts_with('[') {
+            ""
+        } else {
+            "."
+        }
+    };
+    let width =
+        |i: usize, crumb: &Breadcrumb| separator(i, crumb).len() + crumb.label.chars().count();
+
+    // Drop outer segments until the rest fits next to the ellipsis
+    let available = area.width as usize;
+    let mut first = 0;
+    let total: usize = crumbs.iter().enumerate().map(|(i, c)| width(i, c)).sum();
+    let mut shown = total;
+    while first + 1 < crumbs.len() && shown > available {
+        shown -= width(first, &crumbs[first]);
+        first += 1;
+        shown += 1; // the ellipsis
+    }
+
+    let base = Style::default().bg(colors.status_line_bg);
+    let mut spans = Vec::new();
+    let mut targets = Vec::new();
+    let mut column = area.x;
+    if first > 0 {
+        spans.push(Span::styled("…", base.fg(colors.preview)));
+        column += 1;
+    }
+    for (i, crumb) in crumbs.iter().enumerate().skip(first) {
+        let sep = separator(i, crumb);
+        if !sep.is_empty() {
+            spans.push(Span::styled(sep, base.fg(colors.preview)));
+            column += sep.len() as u16;
+        }
+        let is_index = crumb.label.starts_with('[');
+        let mut style = base.fg(if is_index { colors.number } else { colors.key });
+        if i + 1 == crumbs.len() {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        let len = crumb.label.chars().count() as u16;
+        targets.push(ClickTarget {
+            row: area.y,
+            start: column,
+            end: column.saturating_add(len),
+            path: crumb.path.clone(),
+        });
+        column = column.saturating_add(len);
+        spans.push(Span::styled(crumb.label.clone(), style));
+    }
+
+    f.render_widget(Paragraph::new(Line::from(spans)).style(base), area);
+    targets
+}
@@ -0,0 +1,67 @@
+//! Container picker popup (`:containers`).
+
+use crate::editor::k8s::ContainerPickerState;
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Renders the container picker popup.
+///
+/// Each row shows the container's name and image. The selected row is
+/// highlighted.
+pub fn render_container_picker(f: &mut Frame, state: &ContainerPickerState, colors: &ThemeColors) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Containers ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+
+    let name_width = state
+        .containers
+        .iter()
+        .map(|c| c.name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = vec![Line::from("")];
+    for (idx, container) in state.containers.iter().enumerate() {
+        let is_selected = idx == state.selected_index;
+        let cursor = if is_selected { "> " } else { "  " };
+        let name = format!("{}{:width$}  ", cursor, container.name, width = name_width);
+        let image = container.image.clone();
+
+        let line = if is_selected {
+            let style = Style::default()
+                .fg(ratatui::style::Color::White)
+                .bg(colors.cursor)
+                .add_modifier(Modifier::BOLD);
+            Line::from(vec![Span::styled(name, style), Span::styled(image, style)])
+        } else {
+            Line::from(vec![
+                Span::styled(name, Style::default().fg(colors.key)),
+                Span::styled(image, Style::default().fg(colors.preview)),
+            ])
+        };
+        lines.push(line);
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "↑/↓: Navigate  Enter: Jump  Esc: Cancel",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
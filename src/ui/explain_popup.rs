@@ -0,0 +1,50 @@
+//! Value explanation popup (`K`).
+
+use crate::editor::explain::Explanation;
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Renders the explanation of the value under the cursor.
+pub fn render_explain_popup(f: &mut Frame, explanation: &Explanation, colors: &ThemeColors) {
+    let area = centered_rect(60, 40, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" {} ", explanation.title))
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+
+    let mut lines: Vec<Line> = explanation
+        .lines
+        .iter()
+        .map(|line| {
+            Line::from(Span::styled(
+                line.clone(),
+                Style::default().fg(colors.foreground),
+            ))
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Press any key to close",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
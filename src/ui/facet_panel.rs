@@ -0,0 +1,90 @@
+//! Facet panel popup (`:facet`).
+
+use crate::editor::facet::FacetPanelState;
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Renders the facet panel popup.
+///
+/// Each row shows a distinct value of the field, how many records have it
+/// and their share of all records. Records without the field are listed
+/// last as `(missing)`. The selected row is highlighted.
+pub fn render_facet_panel(f: &mut Frame, state: &FacetPanelState, colors: &ThemeColors) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(
+            " {} ({} values, {} records) ",
+            state.field,
+            state.values.len(),
+            state.total
+        ))
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+
+    let count_width = state
+        .values
+        .iter()
+        .map(|v| v.records.len().to_string().len())
+        .max()
+        .unwrap_or(1);
+
+    // Keep the selection in view when there are more values than rows
+    let rows = area.height.saturating_sub(5) as usize;
+    let first = state.selected_index.saturating_sub(rows.saturating_sub(1));
+
+    let mut lines = vec![Line::from("")];
+    for (idx, facet) in state.values.iter().enumerate().skip(first).take(rows) {
+        let is_selected = idx == state.selected_index;
+        let cursor = if is_selected { "> " } else { "  " };
+        let share = facet.records.len() * 100 / state.total.max(1);
+        let count = format!(
+            "{}{:>width$} {:>3}%  ",
+            cursor,
+            facet.records.len(),
+            share,
+            width = count_width
+        );
+
+        let line = if is_selected {
+            let style = Style::default()
+                .fg(ratatui::style::Color::White)
+                .bg(colors.cursor)
+                .add_modifier(Modifier::BOLD);
+            Line::from(vec![
+                Span::styled(count, style),
+                Span::styled(facet.label().to_string(), style),
+            ])
+        } else {
+            let value_style = match facet.value {
+                Some(_) => Style::default().fg(colors.string),
+                None => Style::default().fg(colors.comment),
+            };
+            Line::from(vec![
+                Span::styled(count, Style::default().fg(colors.number)),
+                Span::styled(facet.label().to_string(), value_style),
+            ])
+        };
+        lines.push(line);
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "↑/↓: Navigate  Enter: Show matching records  Esc: Cancel",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -95,6 +95,10 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             Span::styled("  zb            ", Style::default().fg(colors.number)),
             Span::raw("Move cursor to bottom of screen"),
         ]),
+        Line::from(vec![
+            Span::styled("  zd            ", Style::default().fg(colors.number)),
+            Span::raw("Expand past the depth limit (:set maxdepth=N)"),
+        ]),
         Line::from(vec![
             Span::styled("  }             ", Style::default().fg(colors.number)),
             Span::raw("Jump to next sibling"),
@@ -111,6 +115,14 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             Span::styled("  $             ", Style::default().fg(colors.number)),
             Span::raw("Jump to last sibling"),
         ]),
+        Line::from(vec![
+            Span::styled("  f             ", Style::default().fg(colors.number)),
+            Span::raw("Jump to a key on screen by typing its start"),
+        ]),
+        Line::from(vec![
+            Span::styled("  s             ", Style::default().fg(colors.number)),
+            Span::raw("Jump to any line on screen by its label"),
+        ]),
         Line::from(vec![
             Span::styled("  w             ", Style::default().fg(colors.number)),
             Span::raw("Next node at same or shallower depth"),
@@ -130,7 +142,11 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
         )]),
         Line::from(vec![
             Span::styled("  Scroll wheel  ", Style::default().fg(colors.number)),
-            Span::raw("Scroll viewport (3 lines per tick)"),
+            Span::raw("Scroll viewport (:set mousescroll=N lines per tick)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  Shift+wheel   ", Style::default().fg(colors.number)),
+            Span::raw("Scroll long lines sideways"),
         ]),
         Line::from(vec![
             Span::styled("  Trackpad      ", Style::default().fg(colors.number)),
@@ -145,6 +161,14 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             Span::styled("  e             ", Style::default().fg(colors.number)),
             Span::raw("Enter EDIT mode (edit values/keys)"),
         ]),
+        Line::from(vec![
+            Span::styled("  e then *2     ", Style::default().fg(colors.number)),
+            Span::raw("On a number: *2, +10, =1024*1024 compute the new value"),
+        ]),
+        Line::from(vec![
+            Span::styled("  Ctrl-s        ", Style::default().fg(colors.number)),
+            Span::raw("Save a multi-line string (Enter adds a line)"),
+        ]),
         Line::from(vec![
             Span::styled("  :             ", Style::default().fg(colors.number)),
             Span::raw("Enter COMMAND mode"),
@@ -182,6 +206,54 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             Span::styled("  p/P           ", Style::default().fg(colors.number)),
             Span::raw("Paste after/before cursor"),
         ]),
+        Line::from(vec![
+            Span::styled("  gf            ", Style::default().fg(colors.number)),
+            Span::raw("Toggle block/flow style ({a: 1}, [1, 2]) of the node"),
+        ]),
+        Line::from(vec![
+            Span::styled("  gs            ", Style::default().fg(colors.number)),
+            Span::raw("Sort keys of the mapping (:sort! reverse, :sort r recursive)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  gt{s,i,f,b,n} ", Style::default().fg(colors.number)),
+            Span::raw("Change the scalar's type (:type str|int|float|bool|null)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  gt+ gt- gt=   ", Style::default().fg(colors.number)),
+            Span::raw("Shift (:time +1d) or re-format (:time utc) a timestamp"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :now          ", Style::default().fg(colors.number)),
+            Span::raw("Set the value to the current time (:now date|epoch)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  gb / :b64     ", Style::default().fg(colors.number)),
+            Span::raw("Toggle base64 on a string (:b64 encode|decode)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :bump minor   ", Style::default().fg(colors.number)),
+            Span::raw("Bump the semantic version at the cursor (major|minor|patch)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :tag !Ref     ", Style::default().fg(colors.number)),
+            Span::raw("Set the node's YAML tag (:tag shows it, :notag removes it)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  gJ / gS       ", Style::default().fg(colors.number)),
+            Span::raw("Join a list into a string / split a string into a list"),
+        ]),
+        Line::from(vec![
+            Span::styled("  g[ / g{       ", Style::default().fg(colors.number)),
+            Span::raw("Wrap the node in a new list / a new mapping under a key"),
+        ]),
+        Line::from(vec![
+            Span::styled("  g] / :splice  ", Style::default().fg(colors.number)),
+            Span::raw("Unwrap a one-child container / splice children into parent"),
+        ]),
+        Line::from(vec![
+            Span::styled("  ge            ", Style::default().fg(colors.number)),
+            Span::raw("Edit the subtree in $EDITOR (:edit-external)"),
+        ]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Registers",
@@ -215,6 +287,14 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             Span::styled("  \"1p           ", Style::default().fg(colors.number)),
             Span::raw("Paste from last delete"),
         ]),
+        Line::from(vec![
+            Span::styled("  \"+p           ", Style::default().fg(colors.number)),
+            Span::raw("Paste system clipboard as YAML/JSON structure"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :registers    ", Style::default().fg(colors.number)),
+            Span::raw("List what each register holds"),
+        ]),
         Line::from(vec![
             Span::styled("  yy / dd       ", Style::default().fg(colors.number)),
             Span::raw("Yank/delete to unnamed register (syncs clipboard)"),
@@ -240,6 +320,14 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             Span::styled("  c             ", Style::default().fg(colors.number)),
             Span::raw("Add a comment above current node"),
         ]),
+        Line::from(vec![
+            Span::styled("  > / <         ", Style::default().fg(colors.number)),
+            Span::raw("Move node into the container above / out of its parent"),
+        ]),
+        Line::from(vec![
+            Span::styled("  K             ", Style::default().fg(colors.number)),
+            Span::raw("Explain value (cron, IP, CIDR range, port, version)"),
+        ]),
         Line::from(vec![
             Span::styled("  r             ", Style::default().fg(colors.number)),
             Span::raw("Rename object key (objects only)"),
@@ -261,6 +349,10 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             Span::styled("  v             ", Style::default().fg(colors.number)),
             Span::raw("Enter visual mode (select multiple nodes)"),
         ]),
+        Line::from(vec![
+            Span::styled("  Ctrl-v        ", Style::default().fg(colors.number)),
+            Span::raw("Visual block: select one key across list elements"),
+        ]),
         Line::from(vec![
             Span::styled("  j/k/h/l       ", Style::default().fg(colors.number)),
             Span::raw("Expand/shrink selection (in visual mode)"),
@@ -277,6 +369,54 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             Span::styled("  p/P           ", Style::default().fg(colors.number)),
             Span::raw("Replace selection with clipboard (in visual mode)"),
         ]),
+        Line::from(vec![
+            Span::styled("  c             ", Style::default().fg(colors.number)),
+            Span::raw("Set every selected value at once (in visual mode)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  r             ", Style::default().fg(colors.number)),
+            Span::raw("Rename selected keys with a template like app_{} (in visual mode)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :filldown k   ", Style::default().fg(colors.number)),
+            Span::raw("Copy key k from the first selected element to the rest"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :import env   ", Style::default().fg(colors.number)),
+            Span::raw("Import environment or .env variables into a mapping"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :usages       ", Style::default().fg(colors.number)),
+            Span::raw("Helm templates reading this values key (unused: dead keys)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :usages value ", Style::default().fg(colors.number)),
+            Span::raw("Other uses of the value under the cursor (project: all files)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :extract n [k]", Style::default().fg(colors.number)),
+            Span::raw("Anchor a repeated value as &n (or in mapping k), alias the rest"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :graph        ", Style::default().fg(colors.number)),
+            Span::raw("Draw CI job dependencies; Enter jumps to a job"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :containers   ", Style::default().fg(colors.number)),
+            Span::raw("List containers and images; Enter jumps (:set k8s)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :images x=tag ", Style::default().fg(colors.number)),
+            Span::raw("Retag image x, or every image with :images tag"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :env K=v      ", Style::default().fg(colors.number)),
+            Span::raw("Set an env var in the container under the cursor"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :sum :avg ... ", Style::default().fg(colors.number)),
+            Span::raw("Sum/count/min/max/avg of selected numbers (and yank it)"),
+        ]),
         Line::from(vec![
             Span::styled("  Esc           ", Style::default().fg(colors.number)),
             Span::raw("Exit visual mode"),
@@ -302,6 +442,10 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             Span::styled("  d'{a-z}       ", Style::default().fg(colors.number)),
             Span::raw("Delete from cursor to mark"),
         ]),
+        Line::from(vec![
+            Span::styled("  :marks        ", Style::default().fg(colors.number)),
+            Span::raw("List marks with their paths; Enter jumps"),
+        ]),
         Line::from(vec![
             Span::styled("  Ctrl-o        ", Style::default().fg(colors.number)),
             Span::raw("Jump backward in jump list"),
@@ -313,6 +457,71 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
         Line::from(vec![Span::raw(
             "  Jump list records: gg, G, line jumps, search, marks",
         )]),
+        Line::from(vec![
+            Span::styled("  :bookmark add ", Style::default().fg(colors.number)),
+            Span::raw("Named bookmark, kept per project (:bookmarks to pick)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :note <text>  ", Style::default().fg(colors.number)),
+            Span::raw("Attach a note in a sidecar file (:note shows, :notes lists)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :tasks        ", Style::default().fg(colors.number)),
+            Span::raw("List TODO/FIXME in comments and notes"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :lint         ", Style::default().fg(colors.number)),
+            Span::raw("List lint problems (:lint disable <rule> to skip one)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :dedupe       ", Style::default().fg(colors.number)),
+            Span::raw("Resolve keys written twice: keep (f)irst, (l)ast or (r)ename"),
+        ]),
+        Line::from(vec![
+            Span::styled("  ]q / [q       ", Style::default().fg(colors.number)),
+            Span::raw("Jump to the next/previous quickfix entry (:cnext/:cprev)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :copen        ", Style::default().fg(colors.number)),
+            Span::raw("Show the quickfix list (:copen diagnostics|search to refill)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  gD            ", Style::default().fg(colors.number)),
+            Span::raw("Pop up the node's changes since the file was saved"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :review       ", Style::default().fg(colors.number)),
+            Span::raw("Keep or revert each change vs disk (:review head for git)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :!cmd         ", Style::default().fg(colors.number)),
+            Span::raw("Pipe the subtree through a command (:%!cmd: whole file)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :share        ", Style::default().fg(colors.number)),
+            Span::raw("Serve a read-only live view (:share stop ends it)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :stage        ", Style::default().fg(colors.number)),
+            Span::raw("Stage changes here; :w --staged writes only those"),
+        ]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Split Windows",
+            Style::default().fg(colors.key).add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(vec![
+            Span::styled("  :vsplit <file>", Style::default().fg(colors.number)),
+            Span::raw("Open a file in a pane to the right"),
+        ]),
+        Line::from(vec![
+            Span::styled("  Ctrl-w w      ", Style::default().fg(colors.number)),
+            Span::raw("Focus next pane (Ctrl-w h/l: left/right)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  Ctrl-w q      ", Style::default().fg(colors.number)),
+            Span::raw("Close pane (:close); Ctrl-w o / :only keeps this one"),
+        ]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Repeat Command",
@@ -335,6 +544,10 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             Span::styled("  ?             ", Style::default().fg(colors.number)),
             Span::raw("Search backward in keys and values"),
         ]),
+        Line::from(vec![
+            Span::styled("  /\\v /\\g       ", Style::default().fg(colors.number)),
+            Span::raw("Regex search / key-path glob (e.g. /\\gspec.*.image)"),
+        ]),
         Line::from(vec![
             Span::styled("  n             ", Style::default().fg(colors.number)),
             Span::raw("Jump to next match (shows current/total)"),
@@ -364,6 +577,18 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             Span::styled("  :jp <query>   ", Style::default().fg(colors.number)),
             Span::raw("Short alias for :path"),
         ]),
+        Line::from(vec![
+            Span::styled("  :%s/re/to/g   ", Style::default().fg(colors.number)),
+            Span::raw("Replace in all string values (k: keys, i: case, n: count)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :path-set q v ", Style::default().fg(colors.number)),
+            Span::raw("Set every scalar matching q to v (one undo)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  :path-delete q", Style::default().fg(colors.number)),
+            Span::raw("Delete every node matching q (one undo)"),
+        ]),
         Line::from(vec![Span::raw(
             "  Supported: $, .prop, [index], [*], .., [start:end]",
         )]),
@@ -379,6 +604,20 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             ),
             Span::raw("Write (save) file"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :w!                   ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Write even if the file changed on disk"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :w --style <name>     ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Write as preserving, canonical or compact"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :q                    ",
@@ -405,7 +644,7 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
                 "  :e <file>             ",
                 Style::default().fg(colors.number),
             ),
-            Span::raw("Load a different file (warns if dirty)"),
+            Span::raw("Open a file in a new buffer"),
         ]),
         Line::from(vec![
             Span::styled(
@@ -416,10 +655,24 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
         ]),
         Line::from(vec![
             Span::styled(
-                "  :e! <file>            ",
+                "  :backups              ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Diff the file's backups; Enter restores"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :bn / :bp / :b <N>    ",
                 Style::default().fg(colors.number),
             ),
-            Span::raw("Load a different file, discarding changes"),
+            Span::raw("Next, previous or numbered buffer"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :ls                   ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("List buffers (% current, + modified)"),
         ]),
         Line::from(vec![
             Span::styled(
@@ -491,6 +744,118 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             ),
             Span::raw("Disable backup file creation"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set undofile         ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Keep undo history across sessions"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set autoread         ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Reload clean buffers changed on disk"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set regexsearch      ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Treat / patterns as regexes"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set nosearchcollapsed",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Search only the lines shown"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set breadcrumbs      ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Show the cursor path (click to jump)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set typebadges       ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Show each value's type after its line"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set decodesecrets    ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Show a Secret's data values decoded"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set resolve-env[=F]  ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Preview ${VAR} values (env, or .env file F)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set statusline=%s %t ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("File size/modified time; %n warns if newer on disk"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set keylog[=FILE]    ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Log keys for a bug report (:set keylogredact)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set cloudformation   ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Highlight intrinsics; Tab completes !Ref IDs"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set k8s              ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Enable :containers, :images, :env and quantity checks"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set prometheus       ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Check PromQL in expr; fold rules to names"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set autovalidate     ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Re-validate the schema while idle after edits"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set resolvemerge     ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Show keys inherited via << merge keys"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :set noresolvemerge   ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Hide merged keys"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :set save             ",
@@ -498,6 +863,13 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             ),
             Span::raw("Save settings to config file"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :profile export FILE  ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Share settings and theme (:profile import FILE)"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :undo                 ",
@@ -512,6 +884,13 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             ),
             Span::raw("Redo last undone change"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :view                 ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Toggle read-only mode"),
+        ]),
         Line::from(vec![
             Span::styled(
                 "  :format               ",
@@ -519,6 +898,97 @@ pub fn render_help_overlay(f: &mut Frame, colors: &ThemeColors, scroll: usize) {
             ),
             Span::raw("Reformat document with jq-style indentation"),
         ]),
+        Line::from(vec![
+            Span::styled(
+                "  :preview-field add <f>",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Show field f first in every record's preview"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :facet <field>        ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Count field values; Enter filters records"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :sample [head|tail] N ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Show N (random) records; :sample off for all"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :filter <query>       ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Show only matches of a YAMLPath or pattern"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :nofilter             ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Show the whole tree again"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :pin / :unpin [N|all] ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Pin the node to the pin panel; g1-g9 jump"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :watch [path] / :unwatch N ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Watch a YAMLPath's value; flashes when an edit changes it"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :memory               ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Show memory use (:set memorybudget=MB)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :validate <schema>    ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Validate against a JSON Schema (E/W in gutter)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :export html <file>   ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Export the tree (as folded) to HTML"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :export outline <file>",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Markdown list of keys (depth=N, values)"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :trust / :untrust     ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Allow or revoke .yamlquill.toml and modelines here"),
+        ]),
+        Line::from(vec![
+            Span::styled(
+                "  :repl                 ",
+                Style::default().fg(colors.number),
+            ),
+            Span::raw("Live YAMLPath queries (Ctrl-f: promote to n/N)"),
+        ]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Other",
@@ -0,0 +1,72 @@
+//! Mark picker popup (`:marks`).
+
+use crate::editor::marks::MarkPickerState;
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Renders the mark picker popup.
+///
+/// Each row shows the mark name, the marked node's path and a preview of
+/// its value. The selected row is highlighted.
+pub fn render_mark_picker(f: &mut Frame, state: &MarkPickerState, colors: &ThemeColors) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Marks ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+
+    let location_width = state
+        .marks
+        .iter()
+        .map(|m| m.location.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = vec![Line::from("")];
+    for (idx, mark) in state.marks.iter().enumerate() {
+        let is_selected = idx == state.selected_index;
+        let cursor = if is_selected { "> " } else { "  " };
+        let name = format!("{}{}  ", cursor, mark.name);
+        let location = format!("{:width$}  ", mark.location, width = location_width);
+
+        let line = if is_selected {
+            let style = Style::default()
+                .fg(ratatui::style::Color::White)
+                .bg(colors.cursor)
+                .add_modifier(Modifier::BOLD);
+            Line::from(vec![
+                Span::styled(name, style),
+                Span::styled(location, style),
+                Span::styled(mark.preview.clone(), style),
+            ])
+        } else {
+            Line::from(vec![
+                Span::styled(name, Style::default().fg(colors.key)),
+                Span::styled(location, Style::default().fg(colors.info)),
+                Span::styled(mark.preview.clone(), Style::default().fg(colors.preview)),
+            ])
+        };
+        lines.push(line);
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "↑/↓: Navigate  Enter: Jump  Esc: Cancel",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -33,7 +33,15 @@ pub fn render_message_area(f: &mut Frame, area: Rect, state: &EditorState, color
             if let Some((current, total)) = state.search_results_info() {
                 text.push_str(&format!(" ({}/{})", current, total));
             }
-            Line::from(vec![Span::styled(text, Style::default().fg(colors.info))])
+            let mut spans = vec![Span::styled(text, Style::default().fg(colors.info))];
+            // An invalid pattern's error is shown after the prompt
+            if let Some(message) = state.message().filter(|m| m.level == MessageLevel::Error) {
+                spans.push(Span::styled(
+                    format!("  {}", message.text),
+                    Style::default().fg(colors.error),
+                ));
+            }
+            Line::from(spans)
         }
         _ => {
             // Show message if present
@@ -1,20 +1,44 @@
+pub mod backup_picker;
+pub mod bookmark_picker;
+pub mod breadcrumbs;
+pub mod container_picker;
 pub mod edit_prompt;
+pub mod explain_popup;
+pub mod facet_panel;
 pub mod help_overlay;
 /// UI module for yamlquill terminal interface.
 ///
 /// This module provides the main UI structure for rendering the terminal interface,
 /// including layout management and widget composition.
 pub mod layout;
+pub mod mark_picker;
 pub mod message_area;
+pub mod multiline_editor;
+pub mod node_diff_popup;
+pub mod note_popup;
+pub mod pin_panel;
+pub mod pipeline_graph;
+pub mod quickfix;
+pub mod registers_overlay;
+pub mod repl_pane;
+pub mod review_pane;
 pub mod status_line;
+pub mod task_list;
 pub mod theme_picker;
 pub mod tree_view;
+pub mod usages_panel;
+pub mod watch_panel;
 
 use anyhow::Result;
 use ratatui::backend::Backend;
-use ratatui::layout::{Constraint, Direction, Layout};
-use ratatui::Terminal;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::widgets::{Block, Borders};
+use ratatui::{Frame, Terminal};
 
+use crate::app::App;
+use crate::document::node::{YamlString, YamlValue};
+use crate::editor::mode::EditorMode;
 use crate::editor::state::EditorState;
 use crate::theme::Theme;
 
@@ -79,9 +103,9 @@ impl UI {
     ///
     /// Returns true if the theme was successfully changed, false if the theme name is invalid.
     pub fn set_theme(&mut self, theme_name: &str) -> bool {
-        use crate::theme::get_builtin_theme;
+        use crate::theme::get_theme;
 
-        if let Some(new_theme) = get_builtin_theme(theme_name) {
+        if let Some(new_theme) = get_theme(theme_name) {
             self.theme = new_theme;
             true
         } else {
@@ -130,6 +154,41 @@ impl UI {
         terminal: &mut Terminal<B>,
         state: &mut EditorState,
     ) -> Result<()>
+    where
+        B::Error: Send + Sync + 'static,
+    {
+        self.render_panes(terminal, std::slice::from_mut(state), &[0], 0)
+    }
+
+    /// Renders the app's panes side by side.
+    ///
+    /// Each pane gets its own tree view and status line; the message area,
+    /// prompts and overlays belong to the focused pane.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if terminal drawing fails.
+    pub fn render_app<B: Backend>(&self, terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
+    where
+        B::Error: Send + Sync + 'static,
+    {
+        let windows = app.windows().clone();
+        self.render_panes(
+            terminal,
+            app.buffers_mut(),
+            windows.panes(),
+            windows.focus_index(),
+        )
+    }
+
+    /// Renders `buffers[panes[i]]` in the i-th pane from the left.
+    fn render_panes<B: Backend>(
+        &self,
+        terminal: &mut Terminal<B>,
+        buffers: &mut [EditorState],
+        panes: &[usize],
+        focus: usize,
+    ) -> Result<()>
     where
         B::Error: Send + Sync + 'static,
     {
@@ -137,70 +196,52 @@ impl UI {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
-                    Constraint::Min(1),    // Main view area
-                    Constraint::Length(1), // Status line
+                    Constraint::Min(2),    // Panes (tree view + status line each)
                     Constraint::Length(1), // Message area
                 ])
                 .split(f.area());
 
-            // Adjust scroll to ensure cursor is visible
-            let viewport_height = chunks[0].height as usize;
-            state.adjust_scroll_to_cursor(viewport_height);
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints(vec![Constraint::Ratio(1, panes.len() as u32); panes.len()])
+                .split(chunks[0]);
 
-            // Render tree view
-            tree_view::render_tree_view(
-                f,
-                chunks[0],
-                state.tree_view(),
-                state.cursor(),
-                &self.theme.colors,
-                state.show_line_numbers(),
-                state.relative_line_numbers(),
-                state.scroll_offset(),
-                state.visual_selection(),
-            );
+            for (i, (&buffer, &column)) in panes.iter().zip(columns.iter()).enumerate() {
+                let area = if i == 0 {
+                    column
+                } else {
+                    // Separate panes with a vertical rule
+                    let separator = Block::default()
+                        .borders(Borders::LEFT)
+                        .border_style(Style::default().fg(self.theme.colors.status_line_bg));
+                    let inner = separator.inner(column);
+                    f.render_widget(separator, column);
+                    inner
+                };
+                self.render_pane(f, area, &mut buffers[buffer]);
+            }
 
-            // Status line
-            status_line::render_status_line(f, chunks[1], state, &self.theme.colors);
+            let state = &buffers[panes[focus]];
+            self.render_message_row(f, chunks[1], state);
 
-            // Render key prompt if in AwaitingKey stage
-            use crate::editor::state::AddModeStage;
-            if matches!(state.add_mode_stage(), AddModeStage::AwaitingKey) {
-                // Render key prompt with cursor
-                edit_prompt::render_edit_prompt(
-                    f,
-                    chunks[2],
-                    state.add_key_buffer(),
-                    state.add_key_cursor_position(),
-                    state.cursor_visible(),
-                    &self.theme.colors,
-                    "Key: ",
-                );
-            } else if let Some(buffer) = state.edit_buffer() {
-                // Render edit prompt if in insert mode with active buffer
-                // If we're in AwaitingValue stage with a key, show the key as the prompt
-                let prompt = if matches!(state.add_mode_stage(), AddModeStage::AwaitingComment) {
-                    "Comment: ".to_string()
-                } else if matches!(state.add_mode_stage(), AddModeStage::AwaitingValue)
-                    && !state.add_key_buffer().is_empty()
+            // Multi-line editor overlay while editing a string spanning lines
+            if let Some(buffer) = state.multiline_edit() {
+                let label = match state
+                    .tree()
+                    .get_node(state.cursor().path())
+                    .map(|n| n.value())
                 {
-                    format!("{}: ", state.add_key_buffer())
-                } else {
-                    "Edit: ".to_string()
+                    Some(YamlValue::String(YamlString::Literal(_))) => "literal |",
+                    Some(YamlValue::String(YamlString::Folded(_))) => "folded >",
+                    _ => "string",
                 };
-
-                edit_prompt::render_edit_prompt(
+                multiline_editor::render_multiline_editor(
                     f,
-                    chunks[2],
                     buffer,
-                    state.edit_cursor_position(),
+                    label,
                     state.cursor_visible(),
                     &self.theme.colors,
-                    &prompt,
                 );
-            } else {
-                // Message area
-                message_area::render_message_area(f, chunks[2], state, &self.theme.colors);
             }
 
             // Help overlay (rendered on top if visible)
@@ -208,6 +249,76 @@ impl UI {
                 help_overlay::render_help_overlay(f, &self.theme.colors, state.help_scroll());
             }
 
+            // Bookmark picker overlay (rendered on top if visible)
+            if let Some(picker) = state.bookmark_picker() {
+                bookmark_picker::render_bookmark_picker(f, picker, &self.theme.colors);
+            }
+
+            // Pipeline graph overlay (rendered on top if visible)
+            if let Some(graph) = state.pipeline_graph() {
+                pipeline_graph::render_pipeline_graph(f, graph, &self.theme.colors);
+            }
+
+            // Usages panel overlay (rendered on top if visible)
+            if let Some(panel) = state.usages_panel() {
+                usages_panel::render_usages_panel(f, panel, &self.theme.colors);
+            }
+
+            // Registers overlay (rendered on top if visible)
+            if let Some(scroll) = state.registers_scroll() {
+                registers_overlay::render_registers_overlay(
+                    f,
+                    state.registers(),
+                    scroll,
+                    &self.theme.colors,
+                );
+            }
+
+            // Mark picker overlay (rendered on top if visible)
+            if let Some(picker) = state.mark_picker() {
+                mark_picker::render_mark_picker(f, picker, &self.theme.colors);
+            }
+
+            // Backup browser overlay (rendered on top if visible)
+            if let Some(picker) = state.backup_picker() {
+                backup_picker::render_backup_picker(f, picker, &self.theme.colors);
+            }
+
+            // Container picker overlay (rendered on top if visible)
+            if let Some(picker) = state.container_picker() {
+                container_picker::render_container_picker(f, picker, &self.theme.colors);
+            }
+
+            // Facet panel overlay (rendered on top if visible)
+            if let Some(panel) = state.facet_panel() {
+                facet_panel::render_facet_panel(f, panel, &self.theme.colors);
+            }
+
+            // Task panel overlay (rendered on top if visible)
+            if let Some(panel) = state.task_list() {
+                task_list::render_task_list(f, panel, &self.theme.colors);
+            }
+
+            // Quickfix panel overlay (rendered on top if visible)
+            if let Some(panel) = state.quickfix_panel() {
+                quickfix::render_quickfix(f, panel, &self.theme.colors);
+            }
+
+            // Note popup (rendered on top if visible)
+            if let Some(notes) = state.note_popup() {
+                note_popup::render_note_popup(f, notes, &self.theme.colors);
+            }
+
+            // `K` popup (rendered on top if visible)
+            if let Some(explanation) = state.explanation() {
+                explain_popup::render_explain_popup(f, explanation, &self.theme.colors);
+            }
+
+            // `gD` popup (rendered on top if visible)
+            if let Some(diff) = state.node_diff() {
+                node_diff_popup::render_node_diff_popup(f, diff, &self.theme.colors);
+            }
+
             // Theme picker overlay (rendered on top if visible)
             if state.show_theme_picker() {
                 if let Some(picker_state) = state.theme_picker_state() {
@@ -218,6 +329,161 @@ impl UI {
 
         Ok(())
     }
+
+    /// Renders one pane: its tree view, the REPL or review pane if open, and its status line.
+    fn render_pane(&self, f: &mut Frame, area: Rect, state: &mut EditorState) {
+        // Breadcrumb bar above the tree when enabled
+        let area = if state.show_breadcrumbs() && area.height > 2 {
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(1)])
+                .split(area);
+            let crumbs = breadcrumbs::breadcrumbs(state.tree(), state.cursor().path());
+            let targets = breadcrumbs::render_breadcrumbs(f, split[0], &crumbs, &self.theme.colors);
+            state.set_breadcrumb_targets(targets);
+            split[1]
+        } else {
+            state.set_breadcrumb_targets(Vec::new());
+            area
+        };
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(1),    // Main view area
+                Constraint::Length(1), // Status line
+            ])
+            .split(area);
+
+        // Split off the query REPL or review pane while it has focus
+        let pane_height = match state.mode() {
+            EditorMode::Repl => Some(repl_pane::REPL_PANE_HEIGHT),
+            EditorMode::Review => Some(review_pane::REVIEW_PANE_HEIGHT),
+            _ => None,
+        };
+        let (tree_area, bottom_area) = if let Some(height) = pane_height {
+            let height = height.min(chunks[0].height / 2);
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(height)])
+                .split(chunks[0]);
+            (split[0], Some(split[1]))
+        } else {
+            (chunks[0], None)
+        };
+
+        // Pin panel above the status line while anything is pinned
+        let (tree_area, pin_area) = if !state.pins().is_empty() {
+            let height = pin_panel::pin_panel_height(state.pins().len()).min(tree_area.height / 2);
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(height)])
+                .split(tree_area);
+            (split[0], Some(split[1]))
+        } else {
+            (tree_area, None)
+        };
+
+        // Watch panel between the tree and the pins while anything is watched
+        let (tree_area, watch_area) = if !state.watches().is_empty() {
+            let height =
+                watch_panel::watch_panel_height(state.watches().len()).min(tree_area.height / 2);
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(height)])
+                .split(tree_area);
+            (split[0], Some(split[1]))
+        } else {
+            (tree_area, None)
+        };
+
+        // Note indicators follow their nodes through edits
+        if !state.notes().is_empty() {
+            state.refresh_note_markers();
+        }
+
+        // Adjust scroll to ensure cursor is visible
+        let viewport_height = tree_area.height as usize;
+        state.adjust_scroll_to_cursor(viewport_height);
+
+        // Render tree view
+        tree_view::render_tree_view(
+            f,
+            tree_area,
+            state.tree_view(),
+            state.cursor(),
+            &self.theme.colors,
+            state.show_line_numbers(),
+            state.relative_line_numbers(),
+            state.scroll_offset(),
+            state.horizontal_scroll(),
+            state.visual_selection(),
+        );
+
+        let pin_targets = match pin_area {
+            Some(area) => pin_panel::render_pin_panel(f, area, state, &self.theme.colors),
+            None => Vec::new(),
+        };
+        state.set_pin_targets(pin_targets);
+
+        if let Some(area) = watch_area {
+            watch_panel::render_watch_panel(f, area, state, &self.theme.colors);
+        }
+
+        if let Some(area) = bottom_area {
+            if *state.mode() == EditorMode::Review {
+                review_pane::render_review_pane(f, area, state, &self.theme.colors);
+            } else {
+                repl_pane::render_repl_pane(f, area, state, &self.theme.colors);
+            }
+        }
+
+        // Status line
+        status_line::render_status_line(f, chunks[1], state, &self.theme.colors);
+    }
+
+    /// Renders the bottom row: an edit prompt while typing, otherwise messages.
+    fn render_message_row(&self, f: &mut Frame, area: Rect, state: &EditorState) {
+        // Render key prompt if in AwaitingKey stage
+        use crate::editor::state::AddModeStage;
+        if matches!(state.add_mode_stage(), AddModeStage::AwaitingKey) {
+            // Render key prompt with cursor
+            edit_prompt::render_edit_prompt(
+                f,
+                area,
+                state.add_key_buffer(),
+                state.add_key_cursor_position(),
+                state.cursor_visible(),
+                &self.theme.colors,
+                "Key: ",
+            );
+        } else if let Some(buffer) = state.edit_buffer() {
+            // Render edit prompt if in insert mode with active buffer
+            // If we're in AwaitingValue stage with a key, show the key as the prompt
+            let prompt = if matches!(state.add_mode_stage(), AddModeStage::AwaitingComment) {
+                "Comment: ".to_string()
+            } else if matches!(state.add_mode_stage(), AddModeStage::AwaitingValue)
+                && !state.add_key_buffer().is_empty()
+            {
+                format!("{}: ", state.add_key_buffer())
+            } else {
+                "Edit: ".to_string()
+            };
+
+            edit_prompt::render_edit_prompt(
+                f,
+                area,
+                buffer,
+                state.edit_cursor_position(),
+                state.cursor_visible(),
+                &self.theme.colors,
+                &prompt,
+            );
+        } else {
+            // Message area
+            message_area::render_message_area(f, area, state, &self.theme.colors);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -259,6 +525,65 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_render_repl_pane() {
+        use crate::document::parser::parse_yaml_auto;
+        use crate::document::tree::YamlTree;
+        use ratatui::backend::TestBackend;
+        use ratatui::Terminal;
+
+        let ui = UI::new(get_builtin_theme("default-dark").unwrap());
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        let tree = YamlTree::new(parse_yaml_auto("a: 1\nb: 2\n").unwrap());
+        let mut state = EditorState::new_with_default_theme(tree);
+        state.open_repl();
+        "$.b".chars().for_each(|c| state.repl_mut().insert_char(c));
+        state.evaluate_repl();
+
+        ui.render(&mut terminal, &mut state).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(text.contains("YAMLPath REPL"));
+        assert!(text.contains("1 match"));
+        assert!(text.contains(".b  2"));
+    }
+
+    #[test]
+    fn test_render_split_windows() {
+        use crate::document::parser::parse_yaml_auto;
+        use crate::document::tree::YamlTree;
+        use crate::editor::window::WindowCommand;
+        use ratatui::backend::TestBackend;
+        use ratatui::Terminal;
+        use std::fs;
+        use tempfile::TempDir;
+
+        let dir = TempDir::new().unwrap();
+        let right = dir.path().join("b.yaml");
+        fs::write(&right, "right: 2\n").unwrap();
+
+        let mut state = EditorState::new_with_default_theme(YamlTree::new(
+            parse_yaml_auto("left: 1\n").unwrap(),
+        ));
+        state.set_filename("a.yaml".to_string());
+        let mut app = App::new(state);
+        app.apply_window_command(WindowCommand::VSplit(right.display().to_string()))
+            .unwrap();
+
+        let ui = UI::new(get_builtin_theme("default-dark").unwrap());
+        let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+        ui.render_app(&mut terminal, &mut app).unwrap();
+
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| -> String { (0..80).map(|x| buffer[(x, y)].symbol()).collect() };
+        // Both trees share the first row; each pane has its own status line
+        assert!(row(0).contains("left: 1"));
+        assert!(row(0).contains("right: 2"));
+        assert!(row(8).contains("a.yaml"));
+        assert!(row(8).contains("b.yaml"));
+    }
+
     #[test]
     fn test_render_with_status_line() {
         use crate::document::node::{YamlNode, YamlValue};
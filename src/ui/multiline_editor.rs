@@ -0,0 +1,72 @@
+//! Multi-line editor overlay for strings spanning lines (block scalars).
+
+use crate::editor::multiline::MultilineBuffer;
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Renders the multi-line editor with a block cursor.
+///
+/// `label` names the string's style in the title. The view scrolls to keep
+/// the cursor visible; long lines are not wrapped.
+pub fn render_multiline_editor(
+    f: &mut Frame,
+    buffer: &MultilineBuffer,
+    label: &str,
+    cursor_visible: bool,
+    colors: &ThemeColors,
+) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Edit {} ", label))
+        .title_alignment(Alignment::Center)
+        .title_bottom(Line::from(" Ctrl-s save · Esc cancel ").alignment(Alignment::Center))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+    let inner = block.inner(area);
+
+    let text_style = Style::default().fg(colors.foreground);
+    let cursor_style = if cursor_visible {
+        Style::default().fg(colors.background).bg(colors.cursor)
+    } else {
+        text_style
+    };
+
+    let (row, col) = buffer.cursor();
+    let lines: Vec<Line> = buffer
+        .lines()
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if i != row {
+                return Line::from(Span::styled(line.clone(), text_style));
+            }
+            let before: String = line.chars().take(col).collect();
+            let at = line.chars().nth(col).unwrap_or(' ');
+            let after: String = line.chars().skip(col + 1).collect();
+            Line::from(vec![
+                Span::styled(before, text_style),
+                Span::styled(at.to_string(), cursor_style.add_modifier(Modifier::BOLD)),
+                Span::styled(after, text_style),
+            ])
+        })
+        .collect();
+
+    let scroll_y = row.saturating_sub(inner.height.saturating_sub(1) as usize);
+    let scroll_x = col.saturating_sub(inner.width.saturating_sub(1) as usize);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll_y as u16, scroll_x as u16)),
+        area,
+    );
+}
@@ -0,0 +1,76 @@
+//! Node diff popup (`gD`).
+
+use crate::editor::review::{ChangeKind, NodeDiff};
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use crate::ui::tree_view::format_collapsed_preview;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Renders how the node under the cursor differs from its base version.
+pub fn render_node_diff_popup(f: &mut Frame, diff: &NodeDiff, colors: &ThemeColors) {
+    let area = centered_rect(70, 50, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Changes since {}: {} ", diff.label, diff.path))
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+    let width = (block.inner(area).width as usize).saturating_sub(2).max(10);
+
+    let mut lines: Vec<Line> = Vec::new();
+    if diff.changes.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("No changes since {}", diff.label),
+            Style::default().fg(colors.foreground),
+        )));
+    }
+    for change in &diff.changes {
+        let kind_color = match change.kind {
+            ChangeKind::Added => colors.info,
+            ChangeKind::Removed => colors.error,
+            ChangeKind::Modified => colors.warning,
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                change.kind.to_string(),
+                Style::default().fg(kind_color).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("  "),
+            Span::styled(change.relative_label(), Style::default().fg(colors.key)),
+        ]));
+        if let Some(base) = &change.base {
+            lines.push(Line::from(Span::styled(
+                format!("- {}", format_collapsed_preview(base, width)),
+                Style::default().fg(colors.error),
+            )));
+        }
+        if let Some(current) = &change.current {
+            lines.push(Line::from(Span::styled(
+                format!("+ {}", format_collapsed_preview(current, width)),
+                Style::default().fg(colors.info),
+            )));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Press any key to close",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
@@ -0,0 +1,58 @@
+//! Note popup (`:note`, `:notes`).
+
+use crate::editor::notes::Note;
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Renders the note popup.
+///
+/// Each note is shown as its node's path followed by the wrapped note text.
+pub fn render_note_popup(f: &mut Frame, notes: &[Note], colors: &ThemeColors) {
+    let area = centered_rect(60, 50, f.area());
+    f.render_widget(Clear, area);
+
+    let title = if notes.len() == 1 {
+        " Note "
+    } else {
+        " Notes "
+    };
+    let block = Block::default()
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+
+    let mut lines = Vec::new();
+    for note in notes {
+        lines.push(Line::from(Span::styled(
+            note.path.clone(),
+            Style::default().fg(colors.key).add_modifier(Modifier::BOLD),
+        )));
+        lines.push(Line::from(Span::styled(
+            note.text.clone(),
+            Style::default().fg(colors.foreground),
+        )));
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(Span::styled(
+        "Press any key to close",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
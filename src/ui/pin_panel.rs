@@ -0,0 +1,69 @@
+//! Pin panel (`:pin`).
+//!
+//! Shown below the tree view while any path is pinned. Each row gives the
+//! pin's number (`g1` to `g9` jump there), its dotted path and a live
+//! preview of its value. Rows can be clicked to jump too.
+
+use crate::editor::state::EditorState;
+use crate::theme::colors::ThemeColors;
+use crate::ui::breadcrumbs::{breadcrumbs, dotted_path, ClickTarget};
+use crate::ui::tree_view::format_collapsed_preview;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Height of the pin panel for `pins` pinned paths, including its border.
+pub fn pin_panel_height(pins: usize) -> u16 {
+    pins as u16 + 1
+}
+
+/// Renders the pin panel into `area` and returns where each pin was drawn.
+pub fn render_pin_panel(
+    f: &mut Frame,
+    area: Rect,
+    state: &EditorState,
+    colors: &ThemeColors,
+) -> Vec<ClickTarget> {
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .title(" Pins  g1-g9: jump  :unpin: remove ")
+        .style(Style::default().bg(colors.background).fg(colors.foreground));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let mut lines = Vec::new();
+    let mut targets = Vec::new();
+    for (i, path) in state.pins().iter().enumerate().take(inner.height as usize) {
+        let label = dotted_path(&breadcrumbs(state.tree(), path));
+        let mut path_style = Style::default().fg(colors.key);
+        if path.as_slice() == state.cursor().path() {
+            path_style = path_style.add_modifier(Modifier::BOLD);
+        }
+        let used = label.chars().count() + 4;
+        let width = (inner.width as usize).saturating_sub(used).max(10);
+        let preview = state
+            .tree()
+            .get_node(path)
+            .map(|node| format_collapsed_preview(node, width))
+            .unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} ", i + 1), Style::default().fg(colors.number)),
+            Span::styled(label, path_style),
+            Span::raw("  "),
+            Span::styled(preview, Style::default().fg(colors.preview)),
+        ]));
+        targets.push(ClickTarget {
+            row: inner.y + i as u16,
+            start: inner.x,
+            end: inner.x + inner.width,
+            path: path.clone(),
+        });
+    }
+
+    f.render_widget(Paragraph::new(lines), inner);
+    targets
+}
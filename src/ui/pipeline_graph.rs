@@ -0,0 +1,66 @@
+//! CI pipeline graph popup (`:graph`).
+
+use crate::editor::pipeline::GraphState;
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Renders the pipeline graph popup.
+///
+/// Stage headings and the arrows between stages are dimmed; the selected
+/// job is highlighted and kept in view.
+pub fn render_pipeline_graph(f: &mut Frame, state: &GraphState, colors: &ThemeColors) {
+    let area = centered_rect(70, 80, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Pipeline ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+
+    let mut lines: Vec<Line> = state
+        .rows
+        .iter()
+        .enumerate()
+        .map(|(idx, row)| {
+            let style = if idx == state.selected_row {
+                Style::default()
+                    .fg(ratatui::style::Color::White)
+                    .bg(colors.cursor)
+                    .add_modifier(Modifier::BOLD)
+            } else if row.job.is_some() {
+                Style::default().fg(colors.key)
+            } else {
+                Style::default().fg(colors.preview)
+            };
+            Line::from(Span::styled(row.text.clone(), style))
+        })
+        .collect();
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "↑/↓: Navigate  Enter: Jump  Esc: Cancel",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    // Scroll just enough to keep the selected job visible
+    let height = area.height.saturating_sub(2) as usize;
+    let scroll = (state.selected_row + 1).saturating_sub(height);
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll as u16, 0)),
+        area,
+    );
+}
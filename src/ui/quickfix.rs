@@ -0,0 +1,77 @@
+//! Quickfix panel popup (`:copen`, `:lint`).
+
+use crate::editor::diagnostics::Severity;
+use crate::editor::quickfix::QuickfixList;
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Renders the quickfix panel popup.
+///
+/// Each row shows the severity (`E` in the error color, `W` in the warning
+/// color, `I` for search matches), the node's location and the message. The
+/// selected row is highlighted.
+pub fn render_quickfix(f: &mut Frame, state: &QuickfixList, colors: &ThemeColors) {
+    let area = centered_rect(80, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" {} ({}) ", state.title, state.entries.len()))
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+
+    // Keep the selection in view when there are more entries than rows
+    let rows = area.height.saturating_sub(5) as usize;
+    let first = state.selected_index.saturating_sub(rows.saturating_sub(1));
+
+    let mut lines = vec![Line::from("")];
+    for (idx, item) in state.entries.iter().enumerate().skip(first).take(rows) {
+        let is_selected = idx == state.selected_index;
+        let cursor = if is_selected { "> " } else { "  " };
+        let (marker, marker_color) = match item.severity {
+            Severity::Error => ("E", colors.error),
+            Severity::Warning => ("W", colors.warning),
+            Severity::Info => ("I", colors.info),
+        };
+
+        let line = if is_selected {
+            let style = Style::default()
+                .fg(ratatui::style::Color::White)
+                .bg(colors.cursor)
+                .add_modifier(Modifier::BOLD);
+            Line::from(vec![
+                Span::styled(format!("{}{} {} ", cursor, marker, item.location), style),
+                Span::styled(item.message.clone(), style),
+            ])
+        } else {
+            Line::from(vec![
+                Span::raw(cursor),
+                Span::styled(format!("{} ", marker), Style::default().fg(marker_color)),
+                Span::styled(
+                    format!("{} ", item.location),
+                    Style::default().fg(colors.comment),
+                ),
+                Span::styled(item.message.clone(), Style::default().fg(colors.foreground)),
+            ])
+        };
+        lines.push(line);
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "↑/↓: Navigate  Enter: Jump  Esc: Cancel",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -0,0 +1,116 @@
+//! Registers overlay (`:registers`).
+
+use crate::editor::registers::{RegisterContent, RegisterSet};
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use crate::ui::tree_view::format_collapsed_preview;
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Renders the registers overlay, starting at row `scroll`.
+///
+/// Each row shows a register's name, how many nodes it holds, and a
+/// preview of the nodes truncated to the overlay's width, like vim's
+/// `:registers`.
+pub fn render_registers_overlay(
+    f: &mut Frame,
+    registers: &RegisterSet,
+    scroll: usize,
+    colors: &ThemeColors,
+) {
+    let area = centered_rect(80, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Registers ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let header = Line::from(Span::styled(
+        format!(" {:<6}{:<10}Content", "Name", "Type"),
+        Style::default().fg(colors.key).add_modifier(Modifier::BOLD),
+    ));
+    f.render_widget(Paragraph::new(header), chunks[0]);
+
+    let preview_width = (inner.width as usize).saturating_sub(17).max(8);
+    let rows: Vec<Line> = registers
+        .filled()
+        .into_iter()
+        .map(|(name, content)| {
+            Line::from(vec![
+                Span::styled(
+                    format!(" \"{:<5}", name),
+                    Style::default().fg(colors.number),
+                ),
+                Span::styled(
+                    format!("{:<10}", content_type(content)),
+                    Style::default().fg(colors.info),
+                ),
+                Span::styled(
+                    preview(content, preview_width),
+                    Style::default().fg(colors.preview),
+                ),
+            ])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(rows).scroll((scroll as u16, 0)), chunks[1]);
+
+    let footer = Line::from(Span::styled(
+        "↑/↓ or j/k to scroll • Esc to close",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    ));
+    f.render_widget(Paragraph::new(footer), chunks[2]);
+}
+
+/// Describes what a register holds: `1 node`, `3 nodes` or `column`.
+fn content_type(content: &RegisterContent) -> String {
+    match content.nodes.len() {
+        _ if content.column => "column".to_string(),
+        1 => "1 node".to_string(),
+        n => format!("{} nodes", n),
+    }
+}
+
+/// Joins the previews of a register's nodes (with their keys) and
+/// truncates the result to `max_chars`.
+fn preview(content: &RegisterContent, max_chars: usize) -> String {
+    let text = content
+        .nodes
+        .iter()
+        .zip(&content.keys)
+        .map(|(node, key)| {
+            let value = format_collapsed_preview(node, max_chars).replace('\n', "⏎");
+            match key {
+                Some(key) => format!("{}: {}", key, value),
+                None => value,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    if text.chars().count() > max_chars {
+        let kept: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+        format!("{}…", kept)
+    } else {
+        text
+    }
+}
@@ -0,0 +1,80 @@
+//! Query REPL pane rendering.
+//!
+//! Shown below the tree view while the REPL has focus (`:repl`). The first
+//! line is the expression prompt; below it the pane lists each matching
+//! node's path with a short preview of its value.
+
+use crate::editor::state::EditorState;
+use crate::theme::colors::ThemeColors;
+use crate::ui::edit_prompt::render_edit_prompt;
+use crate::ui::tree_view::format_collapsed_preview;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Height of the REPL pane, including its border.
+pub const REPL_PANE_HEIGHT: u16 = 12;
+
+/// Renders the REPL pane into `area`.
+pub fn render_repl_pane(f: &mut Frame, area: Rect, state: &EditorState, colors: &ThemeColors) {
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .title(" YAMLPath REPL  Enter: keep  Ctrl-f: promote  Up/Down: history  Esc: close ")
+        .style(Style::default().bg(colors.background).fg(colors.foreground));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+
+    let repl = state.repl();
+    render_edit_prompt(
+        f,
+        chunks[0],
+        repl.input(),
+        repl.cursor(),
+        state.cursor_visible(),
+        colors,
+        "> ",
+    );
+
+    let mut lines = Vec::new();
+    if let Some(error) = repl.error() {
+        lines.push(Line::from(Span::styled(
+            error.to_string(),
+            Style::default().fg(colors.error),
+        )));
+    } else if !repl.input().trim().is_empty() {
+        let count = repl.results().len();
+        let noun = if count == 1 { "match" } else { "matches" };
+        lines.push(Line::from(Span::styled(
+            format!("{} {}", count, noun),
+            Style::default().fg(colors.info).add_modifier(Modifier::DIM),
+        )));
+    }
+
+    let visible = (chunks[1].height as usize).saturating_sub(lines.len());
+    for path in repl.results().iter().take(visible) {
+        let location = state
+            .path_to_string(path, "dot")
+            .unwrap_or_else(|| ".".to_string());
+        let preview = state
+            .tree()
+            .get_node(path)
+            .map(|node| format_collapsed_preview(node, 60))
+            .unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(location, Style::default().fg(colors.key)),
+            Span::raw("  "),
+            Span::styled(preview, Style::default().fg(colors.preview)),
+        ]));
+    }
+
+    f.render_widget(Paragraph::new(lines), chunks[1]);
+}
@@ -0,0 +1,81 @@
+//! Review pane rendering (`:review`).
+//!
+//! Shown below the tree view during a review. It names the change being
+//! reviewed and shows the base and buffer versions of the node.
+
+use crate::editor::review::ChangeKind;
+use crate::editor::state::EditorState;
+use crate::theme::colors::ThemeColors;
+use crate::ui::tree_view::format_collapsed_preview;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// Height of the review pane, including its border.
+pub const REVIEW_PANE_HEIGHT: u16 = 5;
+
+/// Renders the review pane into `area`.
+pub fn render_review_pane(f: &mut Frame, area: Rect, state: &EditorState, colors: &ThemeColors) {
+    let Some(review) = state.review() else {
+        return;
+    };
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .title(format!(
+            " Review vs {}  y: keep  r: revert  e: edit  n/p: next/prev  q: quit ",
+            review.label()
+        ))
+        .style(Style::default().bg(colors.background).fg(colors.foreground));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let Some(change) = review.current() else {
+        return;
+    };
+    let width = (inner.width as usize).saturating_sub(2).max(10);
+    let kind_color = match change.kind {
+        ChangeKind::Added => colors.info,
+        ChangeKind::Removed => colors.error,
+        ChangeKind::Modified => colors.warning,
+    };
+    let mut header = vec![
+        Span::styled(
+            format!("Change {}/{}  ", review.index() + 1, review.changes().len()),
+            Style::default().fg(colors.info).add_modifier(Modifier::DIM),
+        ),
+        Span::styled(
+            change.kind.to_string(),
+            Style::default().fg(kind_color).add_modifier(Modifier::BOLD),
+        ),
+        Span::raw("  "),
+        Span::styled(change.location_string(), Style::default().fg(colors.key)),
+    ];
+    if review.is_kept(change) {
+        header.push(Span::styled(
+            "  (kept)",
+            Style::default()
+                .fg(colors.info)
+                .add_modifier(Modifier::ITALIC),
+        ));
+    }
+
+    let mut lines = vec![Line::from(header)];
+    if let Some(base) = &change.base {
+        lines.push(Line::from(Span::styled(
+            format!("- {}", format_collapsed_preview(base, width)),
+            Style::default().fg(colors.error),
+        )));
+    }
+    if let Some(current) = &change.current {
+        lines.push(Line::from(Span::styled(
+            format!("+ {}", format_collapsed_preview(current, width)),
+            Style::default().fg(colors.info),
+        )));
+    }
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
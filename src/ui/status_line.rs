@@ -2,13 +2,17 @@
 //!
 //! The status line shows:
 //! - Current mode (NORMAL, INSERT, COMMAND)
+//! - Buffer number when several files are open, e.g. `[2/3]`
 //! - Filename (or "[No Name]" if unsaved)
 //! - Dirty indicator "[+]" for unsaved changes
+//! - Read-only indicator "[RO]" (`--read-only`, `:view`)
+//! - File size and modified time, and "[newer on disk]" when another program
+//!   changed the file (the `statusline` setting)
 //! - Cursor position (row/total)
 //!
 //! Example status line: `NORMAL | data.json [+]                    5/20`
 
-use crate::editor::state::EditorState;
+use crate::editor::state::{EditorState, FileInfo};
 use crate::theme::colors::ThemeColors;
 use ratatui::{
     layout::Rect,
@@ -46,9 +50,14 @@ use ratatui::{
 /// # }
 /// ```
 pub fn render_status_line(f: &mut Frame, area: Rect, state: &EditorState, colors: &ThemeColors) {
-    let mode_text = format!("{}", state.mode());
+    let mode_text = if state.is_visual_block() {
+        "VISUAL BLOCK".to_string()
+    } else {
+        format!("{}", state.mode())
+    };
     let filename = state.filename().unwrap_or("[No Name]");
     let dirty_indicator = if state.is_dirty() { " [+]" } else { "" };
+    let read_only_indicator = if state.is_read_only() { " [RO]" } else { "" };
 
     // Get current path if not at root
     let cursor_path = state.cursor().path();
@@ -59,8 +68,31 @@ pub fn render_status_line(f: &mut Frame, area: Rect, state: &EditorState, colors
         String::new()
     };
 
+    // Buffer number, once more than one file is open
+    let buffer_info = match state.buffer_position() {
+        Some((number, count)) if count > 1 => format!("[{}/{}] ", number, count),
+        _ => String::new(),
+    };
+
     // Build left side components
-    let mode_and_file = format!("{} | {}", mode_text, filename);
+    let mode_and_file = format!(
+        "{} | {}{}{}",
+        mode_text, buffer_info, filename, read_only_indicator
+    );
+
+    // File size, modified time and whether the file on disk is newer
+    let info = state.file_info();
+    let file_info = match info {
+        Some(info) => {
+            let text = format_file_info(state.statusline(), &info);
+            if text.is_empty() {
+                text
+            } else {
+                format!(" {}", text)
+            }
+        }
+        None => String::new(),
+    };
 
     // Show pending register if any
     let register_info = if let Some(reg) = state.get_pending_register() {
@@ -94,6 +126,20 @@ pub fn render_status_line(f: &mut Frame, area: Rect, state: &EditorState, colors
         String::new()
     };
 
+    // Show the active view filter and record sample
+    let mut filter_info = match state.view_filter_label() {
+        Some(label) => format!(" [Filter: {}]", label),
+        None => String::new(),
+    };
+    if let Some(info) = state.sample_info() {
+        filter_info.push_str(&format!(
+            " [Sample: {} {}/{}]",
+            info.sample.mode.name(),
+            info.shown,
+            info.total
+        ));
+    }
+
     // Get cursor position
     let row = state.cursor_position().0;
     let total = state.total_lines();
@@ -104,8 +150,10 @@ pub fn render_status_line(f: &mut Frame, area: Rect, state: &EditorState, colors
     let left_len = mode_and_file.len()
         + path_display.len()
         + dirty_indicator.len()
+        + file_info.len()
         + register_info.len()
-        + search_info.len();
+        + search_info.len()
+        + filter_info.len();
     let position_len = position.len();
 
     // Ensure we don't overflow
@@ -135,6 +183,15 @@ pub fn render_status_line(f: &mut Frame, area: Rect, state: &EditorState, colors
         spans.push(Span::styled(dirty_indicator, default_style));
     }
 
+    if !file_info.is_empty() {
+        let style = if info.is_some_and(|i| i.newer_on_disk) {
+            register_style
+        } else {
+            default_style
+        };
+        spans.push(Span::styled(file_info, style));
+    }
+
     if !register_info.is_empty() {
         spans.push(Span::styled(register_info, register_style));
     }
@@ -143,6 +200,10 @@ pub fn render_status_line(f: &mut Frame, area: Rect, state: &EditorState, colors
         spans.push(Span::styled(search_info, default_style));
     }
 
+    if !filter_info.is_empty() {
+        spans.push(Span::styled(filter_info, register_style));
+    }
+
     spans.push(Span::styled(" ".repeat(padding), default_style));
     spans.push(Span::styled(position, default_style));
 
@@ -153,6 +214,53 @@ pub fn render_status_line(f: &mut Frame, area: Rect, state: &EditorState, colors
     f.render_widget(status, area);
 }
 
+/// Expands a `statusline` format for a file: `%s` is its size, `%t` its
+/// modified time (UTC) and `%n` "[newer on disk]" when it changed after the
+/// buffer was loaded or saved. Empty expansions don't leave gaps.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::editor::state::FileInfo;
+/// use yamlquill::ui::status_line::format_file_info;
+///
+/// let info = FileInfo { size: 1536, modified: 1_760_000_000, newer_on_disk: false };
+/// assert_eq!(format_file_info("%s %t %n", &info), "1.5 KB 2025-10-09 08:53 UTC");
+/// assert_eq!(format_file_info("%n", &info), "");
+/// ```
+pub fn format_file_info(format: &str, info: &FileInfo) -> String {
+    let mut out = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') if info.size < 1024 => out.push_str(&format!("{} B", info.size)),
+            Some('s') => out.push_str(&crate::editor::memory::format_size(info.size as usize)),
+            Some('t') => {
+                let (year, month, day) =
+                    crate::editor::cron::civil_from_days(info.modified.div_euclid(86_400));
+                let seconds = info.modified.rem_euclid(86_400);
+                out.push_str(&format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+                    year,
+                    month,
+                    day,
+                    seconds / 3600,
+                    seconds % 3600 / 60
+                ));
+            }
+            Some('n') if info.newer_on_disk => out.push_str("[newer on disk]"),
+            Some('n') => {}
+            Some(other) => out.push(other),
+            None => out.push('%'),
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -217,6 +325,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_status_line_buffer_number() {
+        let backend = TestBackend::new(80, 3);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let tree = YamlTree::new(YamlNode::new(YamlValue::Null));
+        let mut state = EditorState::new_with_default_theme(tree);
+        state.set_filename("test.yaml".to_string());
+        let theme = theme::get_builtin_theme("default-dark").unwrap();
+
+        let render = |terminal: &mut Terminal<TestBackend>, state: &EditorState| -> String {
+            terminal
+                .draw(|f| render_status_line(f, f.area(), state, &theme.colors))
+                .unwrap();
+            let content = terminal.backend().buffer().content();
+            content.iter().take(80).map(|c| c.symbol()).collect()
+        };
+
+        state.set_buffer_position(1, 1);
+        assert!(render(&mut terminal, &state).contains("NORMAL | test.yaml"));
+
+        state.set_buffer_position(2, 3);
+        let text = render(&mut terminal, &state);
+        assert!(text.contains("NORMAL | [2/3] test.yaml"), "{}", text);
+    }
+
+    #[test]
+    fn test_status_line_file_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.yaml");
+        std::fs::write(&path, "a: 1\n").unwrap();
+        let loaded =
+            std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_760_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(loaded)
+            .unwrap();
+
+        let backend = TestBackend::new(100, 3);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let tree = YamlTree::new(YamlNode::new(YamlValue::Null));
+        let mut state = EditorState::new_with_default_theme(tree);
+        state.set_filename(path.to_string_lossy().to_string());
+        let theme = theme::get_builtin_theme("default-dark").unwrap();
+
+        let render = |terminal: &mut Terminal<TestBackend>, state: &EditorState| -> String {
+            terminal
+                .draw(|f| render_status_line(f, f.area(), state, &theme.colors))
+                .unwrap();
+            let content = terminal.backend().buffer().content();
+            content.iter().take(100).map(|c| c.symbol()).collect()
+        };
+
+        let text = render(&mut terminal, &state);
+        assert!(
+            text.contains("data.yaml 5 B 2025-10-09 08:53 UTC "),
+            "{}",
+            text
+        );
+        assert!(!text.contains("[newer on disk]"), "{}", text);
+
+        std::fs::write(&path, "a: 2\nb: 3\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(loaded + std::time::Duration::from_secs(60))
+            .unwrap();
+        state.poll_external_change();
+        let text = render(&mut terminal, &state);
+        assert!(
+            text.contains("10 B 2025-10-09 08:54 UTC [newer on disk]"),
+            "{}",
+            text
+        );
+
+        state.set_statusline(String::new());
+        let text = render(&mut terminal, &state);
+        assert!(!text.contains(" B "), "{}", text);
+    }
+
     #[test]
     fn test_status_line_dirty_indicator() {
         let backend = TestBackend::new(80, 3);
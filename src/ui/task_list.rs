@@ -0,0 +1,80 @@
+//! Task panel popup (`:tasks`).
+
+use crate::editor::tasks::{TaskListState, TaskSource};
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Renders the task panel popup.
+///
+/// Each row shows the marker (`TODO` in the warning color, `FIXME` in the
+/// error color), whether it came from a comment (`#`) or a note (`✎`), and
+/// the text. The selected row is highlighted.
+pub fn render_task_list(f: &mut Frame, state: &TaskListState, colors: &ThemeColors) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Tasks ({}) ", state.tasks.len()))
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+
+    // Keep the selection in view when there are more tasks than rows
+    let rows = area.height.saturating_sub(5) as usize;
+    let first = state.selected_index.saturating_sub(rows.saturating_sub(1));
+
+    let mut lines = vec![Line::from("")];
+    for (idx, task) in state.tasks.iter().enumerate().skip(first).take(rows) {
+        let is_selected = idx == state.selected_index;
+        let cursor = if is_selected { "> " } else { "  " };
+        let source = match task.source {
+            TaskSource::Comment => "# ",
+            TaskSource::Note => "✎ ",
+        };
+        let marker_color = if task.marker == "FIXME" {
+            colors.error
+        } else {
+            colors.warning
+        };
+
+        let line = if is_selected {
+            let style = Style::default()
+                .fg(ratatui::style::Color::White)
+                .bg(colors.cursor)
+                .add_modifier(Modifier::BOLD);
+            Line::from(vec![
+                Span::styled(format!("{}{:5} ", cursor, task.marker), style),
+                Span::styled(format!("{}{}", source, task.text), style),
+            ])
+        } else {
+            Line::from(vec![
+                Span::raw(cursor),
+                Span::styled(
+                    format!("{:5} ", task.marker),
+                    Style::default().fg(marker_color),
+                ),
+                Span::styled(source, Style::default().fg(colors.comment)),
+                Span::styled(task.text.clone(), Style::default().fg(colors.foreground)),
+            ])
+        };
+        lines.push(line);
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "↑/↓: Navigate  Enter: Jump  Esc: Cancel",
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
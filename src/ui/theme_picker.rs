@@ -90,7 +90,7 @@ pub fn render_theme_picker(f: &mut Frame, state: &ThemePickerState, colors: &The
 /// * `percent_x` - Percentage width (0-100)
 /// * `percent_y` - Percentage height (0-100)
 /// * `r` - The parent rectangle to center within
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+pub(crate) fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
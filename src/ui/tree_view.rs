@@ -5,9 +5,15 @@
 //! - `ValueType`: Classification of JSON value types
 //! - `TreeViewState`: Manages the list of visible lines and expand/collapse state
 
-use crate::document::node::{YamlNode, YamlValue};
+use crate::document::cloudformation::{is_intrinsic_key, is_intrinsic_tag};
+use crate::document::duplicates::written_key;
+use crate::document::node::{DeferredSource, YamlNode, YamlValue};
+use crate::document::prometheus::rule_name;
 use crate::document::tree::YamlTree;
-use std::collections::HashSet;
+use crate::editor::base64::{decode_text, is_secret_data};
+use crate::editor::diagnostics::Severity;
+use crate::editor::interpolate::{interpolate, Interpolated};
+use std::collections::{HashMap, HashSet};
 
 /// Represents a single line in the tree view display.
 ///
@@ -29,6 +35,22 @@ pub struct TreeViewLine {
     pub expandable: bool,
     /// Whether this value is currently expanded
     pub expanded: bool,
+    /// Whether this line was inherited through a merge key (`<<`) rather than
+    /// defined in the document at this location
+    pub merged: bool,
+    /// Short type name shown when type badges are on (see [`type_badge`])
+    pub type_badge: &'static str,
+    /// Explicit YAML tag on the value (e.g. `!Ref`), drawn dimmed before it
+    pub tag: Option<String>,
+    /// Whether the line calls a CloudFormation intrinsic, by tag (`!Ref`) or
+    /// key (`Fn::Join`); only set while CloudFormation mode is on
+    pub intrinsic: bool,
+    /// The value with its `${VAR}` placeholders filled in, shown dimmed
+    /// after it; only set while `:set resolve-env` is on
+    pub resolved: Option<Interpolated>,
+    /// Whether the node was expanded but its children lie past the depth
+    /// limit, so they aren't shown (see [`TreeViewState::set_max_depth`])
+    pub too_deep: bool,
 }
 
 /// Classification of JSON value types for display purposes.
@@ -48,6 +70,36 @@ pub enum ValueType {
     Null,
 }
 
+impl TreeViewLine {
+    /// Returns the width in columns of the line as drawn by [`line_spans`],
+    /// excluding the gutter and line numbers.
+    pub fn display_width(&self) -> usize {
+        let key_width = self.key.as_ref().map_or(0, |k| k.chars().count() + 2);
+        self.depth * 2 + 2 + key_width + self.value_preview.chars().count()
+    }
+}
+
+/// Returns the short type name of a value: `str`, `int`, `float`, `bool`,
+/// `null`, `map` or `seq`. Aliases show as `alias`; comments have none.
+///
+/// # Example
+///
+/// ```
+/// use yamlquill::document::node::{YamlNumber, YamlValue};
+/// use yamlquill::ui::tree_view::type_badge;
+///
+/// assert_eq!(type_badge(&YamlValue::Number(YamlNumber::Float(1.5))), "float");
+/// ```
+pub fn type_badge(value: &YamlValue) -> &'static str {
+    match value {
+        YamlValue::Object(_) => "map",
+        YamlValue::Array(_) | YamlValue::MultiDoc(_) => "seq",
+        YamlValue::Alias(_) => "alias",
+        YamlValue::Comment(_) => "",
+        scalar => crate::editor::coerce::ScalarType::of(scalar).map_or("", |t| t.name()),
+    }
+}
+
 impl ValueType {
     /// Determines the value type from a YamlValue.
     ///
@@ -71,6 +123,8 @@ impl ValueType {
             YamlValue::Alias(_) => ValueType::String, // Treat alias as string for display
             YamlValue::MultiDoc(_) => ValueType::Array, // Treat multi-document YAML root like array for display
             YamlValue::Comment(_) => ValueType::String, // Treat comment as string for display
+            YamlValue::Deferred(source) if source.is_sequence() => ValueType::Array,
+            YamlValue::Deferred(_) => ValueType::Object,
         }
     }
 }
@@ -99,7 +153,26 @@ impl ValueType {
 /// ```
 pub struct TreeViewState {
     lines: Vec<TreeViewLine>,
+    // Index of the first line showing each path, so finding the cursor's
+    // line doesn't scan the whole view
+    line_index: HashMap<Vec<usize>, usize>,
     expanded_paths: HashSet<Vec<usize>>,
+    resolve_merge: bool,
+    diagnostic_markers: HashMap<Vec<usize>, Severity>,
+    note_markers: HashSet<Vec<usize>>,
+    // Labels shown in the gutter while jumping (`f`)
+    jump_labels: HashMap<Vec<usize>, String>,
+    preview_fields: Vec<String>,
+    filter: Option<HashSet<Vec<usize>>>,
+    type_badges: bool,
+    cloudformation: bool,
+    prometheus: bool,
+    decode_secrets: bool,
+    env: Option<HashMap<String, String>>,
+    max_depth: usize,
+    // Nodes whose children are shown past the depth limit; the limit counts
+    // again from each of them
+    deeper: HashSet<Vec<usize>>,
 }
 
 impl TreeViewState {
@@ -107,7 +180,185 @@ impl TreeViewState {
     pub fn new() -> Self {
         Self {
             lines: Vec::new(),
+            line_index: HashMap::new(),
             expanded_paths: HashSet::new(),
+            resolve_merge: false,
+            diagnostic_markers: HashMap::new(),
+            note_markers: HashSet::new(),
+            jump_labels: HashMap::new(),
+            preview_fields: Vec::new(),
+            filter: None,
+            type_badges: false,
+            cloudformation: false,
+            prometheus: false,
+            decode_secrets: false,
+            env: None,
+            max_depth: 0,
+            deeper: HashSet::new(),
+        }
+    }
+
+    /// Returns the gutter markers (highest diagnostic severity per path).
+    pub fn diagnostic_markers(&self) -> &HashMap<Vec<usize>, Severity> {
+        &self.diagnostic_markers
+    }
+
+    /// Sets the gutter markers shown next to nodes with diagnostics.
+    pub fn set_diagnostic_markers(&mut self, markers: HashMap<Vec<usize>, Severity>) {
+        self.diagnostic_markers = markers;
+    }
+
+    /// Returns whether each line shows its value's type.
+    pub fn type_badges(&self) -> bool {
+        self.type_badges
+    }
+
+    /// Returns whether CloudFormation intrinsics are highlighted.
+    pub fn cloudformation(&self) -> bool {
+        self.cloudformation
+    }
+
+    /// Turns highlighting of CloudFormation intrinsics on or off.
+    ///
+    /// Call [`TreeViewState::rebuild`] afterwards to update the lines.
+    pub fn set_cloudformation(&mut self, enable: bool) {
+        self.cloudformation = enable;
+    }
+
+    /// Returns whether Prometheus rules are previewed by name.
+    pub fn prometheus(&self) -> bool {
+        self.prometheus
+    }
+
+    /// Turns previewing Prometheus rules by their `alert` or `record` name
+    /// on or off.
+    ///
+    /// Call [`TreeViewState::rebuild`] afterwards to update the lines.
+    pub fn set_prometheus(&mut self, enable: bool) {
+        self.prometheus = enable;
+    }
+
+    /// Returns whether the values of a Secret's `data` are shown decoded.
+    pub fn decode_secrets(&self) -> bool {
+        self.decode_secrets
+    }
+
+    /// Turns showing the base64 values of a Secret's `data` decoded on or
+    /// off. Only the display changes; the document keeps the encoded values.
+    ///
+    /// Call [`TreeViewState::rebuild`] afterwards to update the lines.
+    pub fn set_decode_secrets(&mut self, enable: bool) {
+        self.decode_secrets = enable;
+    }
+
+    /// Returns how many levels of nesting are shown, 0 for no limit.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Sets how many levels of nesting are shown before an expanded node is
+    /// summarized instead (`max_render_depth`); 0 for no limit.
+    ///
+    /// Call [`TreeViewState::rebuild`] afterwards to update the lines.
+    pub fn set_max_depth(&mut self, depth: usize) {
+        self.max_depth = depth;
+    }
+
+    /// Shows the children of the node at `path` although they lie past the
+    /// depth limit, counting the limit again from there, and expands it.
+    ///
+    /// Call [`TreeViewState::rebuild`] afterwards to update the lines.
+    pub fn expand_deeper(&mut self, path: &[usize]) {
+        self.deeper.insert(path.to_vec());
+        self.expanded_paths.insert(path.to_vec());
+    }
+
+    /// Returns whether the children of `depth`, counted from `base`, lie past
+    /// the depth limit.
+    fn past_limit(&self, depth: usize, base: usize) -> bool {
+        self.max_depth > 0 && depth + 1 - base >= self.max_depth
+    }
+
+    /// Returns the variables placeholders are previewed with, if on.
+    pub fn env(&self) -> Option<&HashMap<String, String>> {
+        self.env.as_ref()
+    }
+
+    /// Sets the variables `${VAR}` placeholders in string values are
+    /// previewed with, or turns the preview off (`None`).
+    ///
+    /// Call [`TreeViewState::rebuild`] afterwards to update the lines.
+    pub fn set_env(&mut self, env: Option<HashMap<String, String>>) {
+        self.env = env;
+    }
+
+    /// Shows or hides the type badge after each line.
+    pub fn set_type_badges(&mut self, enable: bool) {
+        self.type_badges = enable;
+    }
+
+    /// Returns the paths of nodes that have a note attached.
+    pub fn note_markers(&self) -> &HashSet<Vec<usize>> {
+        &self.note_markers
+    }
+
+    /// Sets the nodes shown with a note indicator.
+    pub fn set_note_markers(&mut self, markers: HashSet<Vec<usize>>) {
+        self.note_markers = markers;
+    }
+
+    /// Returns the jump labels shown in the gutter, by path.
+    pub fn jump_labels(&self) -> &HashMap<Vec<usize>, String> {
+        &self.jump_labels
+    }
+
+    /// Sets the jump labels shown in the gutter; none hides the gutter.
+    pub fn set_jump_labels(&mut self, labels: HashMap<Vec<usize>, String>) {
+        self.jump_labels = labels;
+    }
+
+    /// Returns whether mappings display entries inherited through merge keys.
+    pub fn resolve_merge(&self) -> bool {
+        self.resolve_merge
+    }
+
+    /// Sets whether mappings display entries inherited through merge keys.
+    ///
+    /// Call `rebuild()` afterwards to regenerate the visible lines.
+    pub fn set_resolve_merge(&mut self, resolve: bool) {
+        self.resolve_merge = resolve;
+    }
+
+    /// Returns the dotted field paths pinned to the front of each multi-document
+    /// record's collapsed preview.
+    pub fn preview_fields(&self) -> &[String] {
+        &self.preview_fields
+    }
+
+    /// Sets the fields pinned to record previews (`status.phase`, `items.0.id`).
+    ///
+    /// Call `rebuild()` afterwards to regenerate the visible lines.
+    pub fn set_preview_fields(&mut self, fields: Vec<String>) {
+        self.preview_fields = fields;
+    }
+
+    /// Returns the paths the view is narrowed to, if filtered.
+    pub fn filter(&self) -> Option<&HashSet<Vec<usize>>> {
+        self.filter.as_ref()
+    }
+
+    /// Narrows the view to the given nodes, their ancestors and their
+    /// descendants, or shows everything again with `None`.
+    ///
+    /// Call `rebuild()` afterwards to regenerate the visible lines.
+    pub fn set_filter(&mut self, paths: Option<HashSet<Vec<usize>>>) {
+        self.filter = paths;
+    }
+
+    /// Replaces every filtered path with `f(path)`, dropping those it maps to `None`.
+    pub fn remap_filter(&mut self, mut f: impl FnMut(&[usize]) -> Option<Vec<usize>>) {
+        if let Some(paths) = &self.filter {
+            self.filter = Some(paths.iter().filter_map(|path| f(path)).collect());
         }
     }
 
@@ -116,6 +367,11 @@ impl TreeViewState {
         &self.lines
     }
 
+    /// Returns the index of the first visible line showing the node at `path`.
+    pub fn line_of(&self, path: &[usize]) -> Option<usize> {
+        self.line_index.get(path).copied()
+    }
+
     /// Returns a reference to the set of expanded paths.
     pub fn expanded_paths(&self) -> &HashSet<Vec<usize>> {
         &self.expanded_paths
@@ -126,6 +382,16 @@ impl TreeViewState {
         self.expanded_paths = expanded_paths;
     }
 
+    /// Replaces every expanded path with `f(path)`, dropping those it maps to `None`.
+    pub fn remap_expanded_paths(&mut self, mut f: impl FnMut(&[usize]) -> Option<Vec<usize>>) {
+        self.expanded_paths = self
+            .expanded_paths
+            .iter()
+            .filter_map(|path| f(path))
+            .collect();
+        self.deeper = self.deeper.iter().filter_map(|path| f(path)).collect();
+    }
+
     /// Toggles the expand/collapse state of a node at the given path.
     ///
     /// After toggling, call `rebuild()` to regenerate the visible lines.
@@ -177,10 +443,38 @@ impl TreeViewState {
         // Handle multi-document YAML root specially - render as flat list
         match tree.root().value() {
             YamlValue::MultiDoc(lines) => {
-                self.render_multidoc_root(lines);
+                self.render_multidoc_root(tree, lines);
             }
             _ => {
-                self.build_lines(tree.root(), &[], 0);
+                self.build_lines(tree, tree.root(), &[], 0, 0, false);
+            }
+        }
+
+        if let Some(kept) = &self.filter {
+            let ancestors: HashSet<&[usize]> = kept
+                .iter()
+                .flat_map(|path| (0..path.len()).map(move |n| &path[..n]))
+                .collect();
+            self.lines.retain(|line| {
+                ancestors.contains(line.path.as_slice()) || is_under(kept, &line.path)
+            });
+        }
+
+        self.line_index.clear();
+        for (i, line) in self.lines.iter().enumerate() {
+            self.line_index.entry(line.path.clone()).or_insert(i);
+        }
+    }
+
+    /// Returns true if the view filter (if any) lets the node at `path` show.
+    pub fn passes_filter(&self, path: &[usize]) -> bool {
+        match &self.filter {
+            None => true,
+            Some(kept) => {
+                is_under(kept, path)
+                    || kept
+                        .iter()
+                        .any(|k| k.len() > path.len() && k.starts_with(path))
             }
         }
     }
@@ -189,13 +483,16 @@ impl TreeViewState {
     ///
     /// Each line in the multi-document YAML document is shown at depth 0, collapsed by default.
     /// Users can expand individual lines to see their contents.
-    fn render_multidoc_root(&mut self, lines: &[YamlNode]) {
+    fn render_multidoc_root(&mut self, tree: &YamlTree, lines: &[YamlNode]) {
         for (idx, node) in lines.iter().enumerate() {
             let path = vec![idx];
-            let is_expanded = self.is_expanded(&path);
+            let base = if self.deeper.contains(&path) { 1 } else { 0 };
+            let too_deep = self.is_expanded(&path) && self.past_limit(0, base);
+            let is_expanded = self.is_expanded(&path) && !too_deep;
 
-            // Show collapsed preview for the line itself
+            // Show collapsed preview for the line itself, led by any pinned fields
             let preview = format_collapsed_preview(node, 60);
+            let preview = self.add_pinned_fields(preview, node);
             // Add anchor badge if present
             let preview = self.add_anchor_badge(preview, node);
             self.lines.push(TreeViewLine {
@@ -206,11 +503,17 @@ impl TreeViewState {
                 value_preview: preview,
                 expandable: true,
                 expanded: is_expanded,
+                merged: false,
+                type_badge: type_badge(node.value()),
+                tag: node.tag().map(str::to_string),
+                intrinsic: self.cloudformation && node.tag().is_some_and(is_intrinsic_tag),
+                resolved: None,
+                too_deep,
             });
 
             // If expanded, render the contents of the line
             if is_expanded {
-                self.build_lines(node, &path, 1);
+                self.build_lines(tree, node, &path, 1, base, false);
             }
         }
     }
@@ -224,6 +527,16 @@ impl TreeViewState {
         self.expand_all_recursive(tree.root(), &[]);
     }
 
+    /// Expands containers nested at most `level` deep and collapses the rest.
+    ///
+    /// Mirrors vim's `foldlevel`: with level 0 every container is collapsed,
+    /// with level 1 only top-level containers are expanded, and so on.
+    pub fn set_fold_level(&mut self, tree: &YamlTree, level: usize) {
+        self.expanded_paths.clear();
+        self.expand_all(tree);
+        self.expanded_paths.retain(|path| path.len() <= level);
+    }
+
     fn expand_all_recursive(&mut self, node: &YamlNode, path: &[usize]) {
         match node.value() {
             YamlValue::Object(entries) => {
@@ -276,23 +589,62 @@ impl TreeViewState {
         }
     }
 
-    fn build_lines(&mut self, node: &YamlNode, path: &[usize], depth: usize) {
+    fn build_lines(
+        &mut self,
+        tree: &YamlTree,
+        node: &YamlNode,
+        path: &[usize],
+        depth: usize,
+        base: usize,
+        merged: bool,
+    ) {
         match node.value() {
             YamlValue::Object(entries) => {
-                for (i, (key, child)) in entries.iter().enumerate() {
+                // Inherited entries are listed after the mapping's own entries so
+                // that paths of real nodes keep pointing at the same indices.
+                let inherited = if self.resolve_merge {
+                    tree.merged_entries(node)
+                } else {
+                    Vec::new()
+                };
+                let secret_data = self.decode_secrets && is_secret_data(tree.root(), path);
+                let own = entries.iter().map(|(k, v)| (k, v, merged));
+                let extra = inherited.iter().map(|(k, v)| (k, v, true));
+
+                for (i, (key, child, merged)) in own.chain(extra).enumerate() {
                     let child_path: Vec<usize> =
                         path.iter().copied().chain(std::iter::once(i)).collect();
-                    let expanded = self.is_expanded(&child_path);
+                    let child_base = if self.deeper.contains(&child_path) {
+                        depth + 1
+                    } else {
+                        base
+                    };
+                    let too_deep = self.is_expanded(&child_path)
+                        && child.value().is_container()
+                        && self.past_limit(depth, child_base);
+                    let expanded = self.is_expanded(&child_path) && !too_deep;
 
                     // Check if this is a comment node
                     let is_comment = key.starts_with("__comment_");
 
-                    // For comments, hide the key and show content in preview
-                    let display_key = if is_comment { None } else { Some(key.clone()) };
+                    // For comments, hide the key and show content in preview;
+                    // a repeated key shows as written
+                    let display_key = if is_comment {
+                        None
+                    } else {
+                        Some(written_key(key).to_string())
+                    };
 
-                    // Always use collapsed preview for containers
+                    // Always use collapsed preview for containers; a Secret's
+                    // values show decoded when asked
+                    let decoded = match child.value() {
+                        YamlValue::String(s) if secret_data => decode_text(s.as_str()),
+                        _ => None,
+                    };
                     let value_preview = if child.value().is_container() {
                         format_collapsed_preview(child, 60)
+                    } else if let Some(text) = decoded {
+                        format_decoded_preview(&text)
                     } else {
                         self.get_value_preview(child.value())
                     };
@@ -305,12 +657,19 @@ impl TreeViewState {
                         key: display_key,
                         value_type: ValueType::from_yaml_value(child.value()),
                         value_preview,
-                        expandable: child.value().is_container(),
+                        expandable: child.value().is_container() || child.value().is_deferred(),
                         expanded,
+                        merged,
+                        type_badge: type_badge(child.value()),
+                        tag: child.tag().map(str::to_string),
+                        intrinsic: self.cloudformation
+                            && (child.tag().is_some_and(is_intrinsic_tag) || is_intrinsic_key(key)),
+                        resolved: self.resolve(child),
+                        too_deep,
                     });
 
                     if expanded && child.value().is_container() {
-                        self.build_lines(child, &child_path, depth + 1);
+                        self.build_lines(tree, child, &child_path, depth + 1, child_base, merged);
                     }
                 }
             }
@@ -318,10 +677,22 @@ impl TreeViewState {
                 for (i, child) in elements.iter().enumerate() {
                     let child_path: Vec<usize> =
                         path.iter().copied().chain(std::iter::once(i)).collect();
-                    let expanded = self.is_expanded(&child_path);
-
-                    // Always use collapsed preview for containers
-                    let value_preview = if child.value().is_container() {
+                    let child_base = if self.deeper.contains(&child_path) {
+                        depth + 1
+                    } else {
+                        base
+                    };
+                    let too_deep = self.is_expanded(&child_path)
+                        && child.value().is_container()
+                        && self.past_limit(depth, child_base);
+                    let expanded = self.is_expanded(&child_path) && !too_deep;
+
+                    // Always use collapsed preview for containers; a
+                    // Prometheus rule shows its name instead
+                    let rule = self.prometheus.then(|| rule_name(child)).flatten();
+                    let value_preview = if let Some((kind, name)) = rule {
+                        format!("{}: {}", kind, name)
+                    } else if child.value().is_container() {
                         format_collapsed_preview(child, 60)
                     } else {
                         self.get_value_preview(child.value())
@@ -335,12 +706,18 @@ impl TreeViewState {
                         key: Some(format!("[{}]", i)),
                         value_type: ValueType::from_yaml_value(child.value()),
                         value_preview,
-                        expandable: child.value().is_container(),
+                        expandable: child.value().is_container() || child.value().is_deferred(),
                         expanded,
+                        merged,
+                        type_badge: type_badge(child.value()),
+                        tag: child.tag().map(str::to_string),
+                        intrinsic: self.cloudformation && child.tag().is_some_and(is_intrinsic_tag),
+                        resolved: self.resolve(child),
+                        too_deep,
                     });
 
                     if expanded && child.value().is_container() {
-                        self.build_lines(child, &child_path, depth + 1);
+                        self.build_lines(tree, child, &child_path, depth + 1, child_base, merged);
                     }
                 }
             }
@@ -367,138 +744,47 @@ impl TreeViewState {
                     format!("# {}", c.content())
                 }
             }
+            YamlValue::Deferred(source) => format_deferred_preview(source),
         }
     }
 
-    /// Appends anchor badge to value preview if node has an anchor
-    fn add_anchor_badge(&self, preview: String, node: &YamlNode) -> String {
-        if let Some(anchor) = node.anchor() {
-            format!("{} &{}", preview, anchor)
-        } else {
-            preview
-        }
-    }
-
-    /// Updates expanded paths after inserting a node at the given path.
-    ///
-    /// When a node is inserted, all subsequent siblings and their descendants
-    /// need their indices shifted by 1. This method updates the expanded_paths
-    /// set to reflect the new indices after insertion.
-    ///
-    /// # Arguments
-    /// * `insertion_path` - The path where the new node was inserted
-    pub fn update_paths_after_insertion(&mut self, insertion_path: &[usize]) {
-        if insertion_path.is_empty() {
-            return;
-        }
-
-        let parent_path = &insertion_path[..insertion_path.len() - 1];
-        let insertion_idx = insertion_path[insertion_path.len() - 1];
-
-        // Collect paths that need updating
-        let paths_to_update: Vec<Vec<usize>> = self
-            .expanded_paths
+    /// Prefixes a record preview with the pinned fields it has, as
+    /// `status.phase: Running | id: 7 | {...}`.
+    fn add_pinned_fields(&self, preview: String, node: &YamlNode) -> String {
+        let pinned: Vec<String> = self
+            .preview_fields
             .iter()
-            .filter(|path| {
-                // Check if this path is affected by the insertion
-                if path.len() < parent_path.len() + 1 {
-                    return false;
-                }
-
-                // Check if path has the same parent
-                if &path[..parent_path.len()] != parent_path {
-                    return false;
-                }
-
-                // Check if the index at the insertion level is >= insertion_idx
-                path[parent_path.len()] >= insertion_idx
+            .filter_map(|field| {
+                let value = lookup_dotted(node, field)?;
+                let shown = if value.value().is_container() {
+                    format_collapsed_preview(value, 30)
+                } else {
+                    self.get_value_preview(value.value())
+                };
+                Some(format!("{}: {}", field, shown))
             })
-            .cloned()
             .collect();
-
-        // IMPORTANT: Remove ALL old paths first, then insert ALL new paths
-        // This prevents collisions where a new path matches an old path that hasn't been updated yet
-        // Example: when inserting at [0, 1], both [0, 3] and [0, 4] shift to [0, 4] and [0, 5]
-        // If we remove+insert one at a time, [0, 3]→[0, 4] would collide with existing [0, 4]
-
-        // First pass: remove all old paths
-        for old_path in &paths_to_update {
-            self.expanded_paths.remove(old_path);
-        }
-
-        // Second pass: insert all new paths
-        for old_path in paths_to_update {
-            let mut new_path = old_path.clone();
-            new_path[parent_path.len()] += 1;
-            self.expanded_paths.insert(new_path);
+        if pinned.is_empty() {
+            preview
+        } else {
+            format!("{} | {}", pinned.join(" | "), preview)
         }
     }
 
-    /// Updates expanded_paths after a node deletion.
-    ///
-    /// When a node is deleted, all subsequent siblings and their descendants
-    /// need their indices shifted down by 1. This method updates the expanded_paths
-    /// set to reflect the new indices after deletion. Also removes the deleted path
-    /// and all its descendants from expanded_paths.
-    ///
-    /// # Arguments
-    /// * `deletion_path` - The path of the node that was deleted
-    pub fn update_paths_after_deletion(&mut self, deletion_path: &[usize]) {
-        if deletion_path.is_empty() {
-            return;
-        }
-
-        let parent_path = &deletion_path[..deletion_path.len() - 1];
-        let deletion_idx = deletion_path[deletion_path.len() - 1];
-
-        // Collect paths to remove (deleted node and its descendants)
-        let paths_to_remove: Vec<Vec<usize>> = self
-            .expanded_paths
-            .iter()
-            .filter(|path| {
-                // Remove if this is the deleted node or a descendant
-                path.len() >= deletion_path.len() && &path[..deletion_path.len()] == deletion_path
-            })
-            .cloned()
-            .collect();
-
-        // Collect paths that need index shifting (subsequent siblings and descendants)
-        let paths_to_update: Vec<Vec<usize>> = self
-            .expanded_paths
-            .iter()
-            .filter(|path| {
-                // Check if this path is affected by the deletion
-                if path.len() < parent_path.len() + 1 {
-                    return false;
-                }
-
-                // Check if path has the same parent
-                if &path[..parent_path.len()] != parent_path {
-                    return false;
-                }
-
-                // Check if the index at the deletion level is > deletion_idx
-                // (nodes after the deleted node need to shift down)
-                path[parent_path.len()] > deletion_idx
-            })
-            .cloned()
-            .collect();
-
-        // First pass: remove deleted node and its descendants
-        for path in &paths_to_remove {
-            self.expanded_paths.remove(path);
-        }
-
-        // Second pass: remove all old paths that need shifting
-        for old_path in &paths_to_update {
-            self.expanded_paths.remove(old_path);
+    /// Interpolates a string value's placeholders while `resolve-env` is on.
+    fn resolve(&self, node: &YamlNode) -> Option<Interpolated> {
+        match (node.value(), &self.env) {
+            (YamlValue::String(s), Some(env)) => interpolate(s.as_str(), env),
+            _ => None,
         }
+    }
 
-        // Third pass: insert all shifted paths
-        for old_path in paths_to_update {
-            let mut new_path = old_path.clone();
-            new_path[parent_path.len()] -= 1;
-            self.expanded_paths.insert(new_path);
+    /// Appends anchor badge to value preview if node has an anchor
+    fn add_anchor_badge(&self, preview: String, node: &YamlNode) -> String {
+        if let Some(anchor) = node.anchor() {
+            format!("{} &{}", preview, anchor)
+        } else {
+            preview
         }
     }
 }
@@ -560,7 +846,7 @@ use ratatui::{
 /// tree_view.rebuild(&tree);
 ///
 /// terminal.draw(|f| {
-///     render_tree_view(f, f.area(), &tree_view, &cursor, &colors, true, false, 0, &[]);
+///     render_tree_view(f, f.area(), &tree_view, &cursor, &colors, true, false, 0, 0, &[]);
 /// }).unwrap();
 /// ```
 #[allow(clippy::too_many_arguments)]
@@ -573,6 +859,7 @@ pub fn render_tree_view(
     show_line_numbers: bool,
     relative_line_numbers: bool,
     scroll_offset: usize,
+    horizontal_scroll: usize,
     visual_selection: &[Vec<usize>],
 ) {
     let mut lines_to_render = Vec::new();
@@ -583,13 +870,15 @@ pub fn render_tree_view(
     };
 
     // Find the cursor line number for relative numbering
-    let cursor_line_num = tree_view
-        .lines()
-        .iter()
-        .position(|l| l.path == cursor.path())
-        .unwrap_or(0);
+    let cursor_line_num = tree_view.line_of(cursor.path()).unwrap_or(0);
 
     let viewport_height = area.height as usize;
+    let show_gutter = !tree_view.diagnostic_markers().is_empty();
+    let label_width = tree_view
+        .jump_labels()
+        .values()
+        .map(|label| label.chars().count())
+        .max();
 
     for (line_num, line) in tree_view
         .lines()
@@ -605,6 +894,34 @@ pub fn render_tree_view(
 
         let mut spans = Vec::new();
 
+        // Jump labels (only drawn while jumping)
+        if let Some(width) = label_width {
+            match tree_view.jump_labels().get(&line.path) {
+                Some(label) => {
+                    spans.push(Span::styled(
+                        format!("{:<width$}", label, width = width),
+                        Style::default()
+                            .fg(colors.background)
+                            .bg(colors.warning)
+                            .add_modifier(Modifier::BOLD),
+                    ));
+                    spans.push(Span::raw(" "));
+                }
+                None => spans.push(Span::raw(" ".repeat(width + 1))),
+            }
+        }
+
+        // Diagnostic gutter (only drawn while there are diagnostics)
+        if show_gutter {
+            let marker = match tree_view.diagnostic_markers().get(&line.path) {
+                Some(Severity::Error) => Span::styled("E ", Style::default().fg(colors.error)),
+                Some(Severity::Warning) => Span::styled("W ", Style::default().fg(colors.warning)),
+                Some(Severity::Info) => Span::styled("I ", Style::default().fg(colors.info)),
+                None => Span::raw("  "),
+            };
+            spans.push(marker);
+        }
+
         // Line number
         if show_line_numbers {
             let display_num = if relative_line_numbers {
@@ -629,66 +946,45 @@ pub fn render_tree_view(
             ));
         }
 
-        // Indentation
-        spans.push(Span::raw("  ".repeat(line.depth)));
-
-        // Expand/collapse indicator or cursor indicator for scalars
-        if line.expandable {
-            let indicator = if line.expanded { "▼ " } else { "▶ " };
-            spans.push(Span::raw(indicator));
-        } else if is_cursor {
-            spans.push(Span::raw("▶ "));
-        } else {
-            spans.push(Span::raw("  "));
+        let mut content = line_spans(line, colors, is_cursor);
+        if tree_view.type_badges() && !line.type_badge.is_empty() {
+            content.push(Span::styled(
+                format!(" {}", line.type_badge),
+                Style::default()
+                    .fg(colors.comment)
+                    .add_modifier(Modifier::DIM | Modifier::ITALIC),
+            ));
         }
-
-        // Key (if object property) - highlight only the key when cursor is on this line
-        if let Some(key) = &line.key {
-            let key_style = if is_cursor {
-                // White text on cursor background for readability
+        if line.too_deep {
+            content.push(Span::styled(
+                " ⋯ depth limit (zd expands deeper)",
                 Style::default()
-                    .fg(Color::White)
-                    .bg(colors.cursor)
-                    .add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(colors.key)
-            };
-            spans.push(Span::styled(format!("{}: ", key), key_style));
+                    .fg(colors.warning)
+                    .add_modifier(Modifier::ITALIC),
+            ));
         }
-
-        // Value - highlight when cursor is on this line
-        // Check if this is a comment (no key and starts with #)
-        let is_comment = line.key.is_none() && line.value_preview.starts_with('#');
-
-        let value_style = if is_cursor {
-            // All values on cursor line get same highlight as keys for consistent visibility
-            Style::default()
-                .fg(Color::White)
-                .bg(colors.cursor)
-                .add_modifier(Modifier::BOLD)
-        } else if is_comment {
-            // Comments use special comment color and dim modifier
-            Style::default()
-                .fg(colors.comment)
-                .add_modifier(Modifier::DIM)
-        } else {
-            let value_color = if line.expandable {
-                // All containers use preview color (they show collapsed preview format)
-                colors.preview
+        if let Some(resolved) = &line.resolved {
+            let style = if resolved.missing.is_empty() {
+                Style::default().fg(colors.comment)
             } else {
-                // Scalars use their type-specific colors
-                match line.value_type {
-                    ValueType::String => colors.string,
-                    ValueType::Number => colors.number,
-                    ValueType::Boolean => colors.boolean,
-                    ValueType::Null => colors.null,
-                    ValueType::Object | ValueType::Array => colors.foreground,
-                }
+                Style::default().fg(colors.warning)
             };
-            Style::default().fg(value_color)
-        };
-
-        spans.push(Span::styled(&line.value_preview, value_style));
+            content.push(Span::styled(
+                format!(" → {}", resolved.text.replace('\n', "⏎")),
+                style.add_modifier(Modifier::DIM),
+            ));
+            if !resolved.missing.is_empty() {
+                content.push(Span::styled(
+                    format!(" (unset: {})", resolved.missing.join(", ")),
+                    style,
+                ));
+            }
+        }
+        if tree_view.note_markers().contains(&line.path) {
+            content.push(Span::styled(" ✎", Style::default().fg(colors.info)));
+        }
+        // The gutter and line numbers stay put while the content scrolls sideways
+        spans.extend(skip_columns(content, horizontal_scroll));
 
         // Apply visual selection background if this line is selected
         let final_line = if is_selected {
@@ -714,6 +1010,141 @@ pub fn render_tree_view(
     f.render_widget(paragraph, area);
 }
 
+/// Returns true if `path` or one of its ancestors is in `paths`.
+fn is_under(paths: &HashSet<Vec<usize>>, path: &[usize]) -> bool {
+    (0..=path.len()).any(|n| paths.contains(&path[..n]))
+}
+
+/// Follows a dotted path of keys (and list indexes) from `node`.
+pub fn lookup_dotted<'a>(node: &'a YamlNode, path: &str) -> Option<&'a YamlNode> {
+    path.split('.')
+        .try_fold(node, |node, segment| match node.value() {
+            YamlValue::Object(fields) => fields.get(segment),
+            YamlValue::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+/// Drops the first `columns` characters from a line's spans.
+fn skip_columns(spans: Vec<Span<'_>>, mut columns: usize) -> Vec<Span<'_>> {
+    let mut kept = Vec::with_capacity(spans.len());
+    for span in spans {
+        let len = span.content.chars().count();
+        if columns >= len {
+            columns -= len;
+        } else if columns > 0 {
+            let rest: String = span.content.chars().skip(columns).collect();
+            kept.push(Span::styled(rest, span.style));
+            columns = 0;
+        } else {
+            kept.push(span);
+        }
+    }
+    kept
+}
+
+/// Builds the styled spans for one tree line: indentation, fold indicator, key and value.
+///
+/// Shared by the tree view and the printable exports so both render lines the same way.
+pub fn line_spans<'a>(
+    line: &'a TreeViewLine,
+    colors: &ThemeColors,
+    is_cursor: bool,
+) -> Vec<Span<'a>> {
+    let mut spans = Vec::new();
+
+    // Indentation
+    spans.push(Span::raw("  ".repeat(line.depth)));
+
+    // Expand/collapse indicator or cursor indicator for scalars
+    if line.expandable {
+        let indicator = if line.expanded { "▼ " } else { "▶ " };
+        spans.push(Span::raw(indicator));
+    } else if is_cursor {
+        spans.push(Span::raw("▶ "));
+    } else {
+        spans.push(Span::raw("  "));
+    }
+
+    // Key (if object property) - highlight only the key when cursor is on this line
+    if let Some(key) = &line.key {
+        let key_style = if is_cursor {
+            // White text on cursor background for readability
+            Style::default()
+                .fg(Color::White)
+                .bg(colors.cursor)
+                .add_modifier(Modifier::BOLD)
+        } else if line.intrinsic && line.tag.is_none() {
+            // Long-form intrinsic (`Fn::Join:`)
+            Style::default()
+                .fg(colors.info)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(colors.key)
+        };
+        spans.push(Span::styled(format!("{}: ", key), key_style));
+    }
+
+    // Value - highlight when cursor is on this line
+    // Check if this is a comment (no key and starts with #)
+    let is_comment = line.key.is_none() && line.value_preview.starts_with('#');
+
+    let value_style = if is_cursor {
+        // All values on cursor line get same highlight as keys for consistent visibility
+        Style::default()
+            .fg(Color::White)
+            .bg(colors.cursor)
+            .add_modifier(Modifier::BOLD)
+    } else if is_comment {
+        // Comments use special comment color and dim modifier
+        Style::default()
+            .fg(colors.comment)
+            .add_modifier(Modifier::DIM)
+    } else {
+        let value_color = if line.expandable {
+            // All containers use preview color (they show collapsed preview format)
+            colors.preview
+        } else {
+            // Scalars use their type-specific colors
+            match line.value_type {
+                ValueType::String => colors.string,
+                ValueType::Number => colors.number,
+                ValueType::Boolean => colors.boolean,
+                ValueType::Null => colors.null,
+                ValueType::Object | ValueType::Array => colors.foreground,
+            }
+        };
+        Style::default().fg(value_color)
+    };
+
+    if let Some(tag) = &line.tag {
+        let tag_style = if line.intrinsic {
+            Style::default()
+                .fg(colors.info)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+                .fg(colors.comment)
+                .add_modifier(Modifier::DIM)
+        };
+        spans.push(Span::styled(format!("{} ", tag), tag_style));
+    }
+    spans.push(Span::styled(&line.value_preview, value_style));
+
+    // Entries inherited through a merge key are read-only, so set them apart
+    if line.merged && !is_cursor {
+        spans = spans
+            .into_iter()
+            .map(|span| {
+                let style = span.style.add_modifier(Modifier::DIM | Modifier::ITALIC);
+                span.style(style)
+            })
+            .collect();
+    }
+
+    spans
+}
+
 /// Formats a number as an integer if it has no fractional part, otherwise as a float.
 #[allow(dead_code)]
 fn format_number(n: f64) -> String {
@@ -755,6 +1186,16 @@ fn format_yaml_string_preview(s: &crate::document::node::YamlString) -> String {
     }
 }
 
+/// Formats a decoded Secret value: its first line, marked as decoded.
+fn format_decoded_preview(text: &str) -> String {
+    let first_line = text.lines().next().unwrap_or("");
+    if text.lines().count() > 1 {
+        format!("{}... (decoded)", first_line)
+    } else {
+        format!("{} (decoded)", first_line)
+    }
+}
+
 fn format_number_yaml(n: &crate::document::node::YamlNumber) -> String {
     use crate::document::node::YamlNumber;
     match n {
@@ -789,9 +1230,24 @@ pub fn format_collapsed_preview(node: &YamlNode, max_chars: usize) -> String {
         YamlValue::Null => "null".to_string(),
         YamlValue::Alias(name) => format!("*{}", name),
         YamlValue::Comment(c) => format!("# {}", c.content),
+        YamlValue::Deferred(source) => format_deferred_preview(source),
     }
 }
 
+/// Formats the preview of a section that hasn't been loaded, with its size.
+fn format_deferred_preview(source: &DeferredSource) -> String {
+    let brackets = if source.is_sequence() {
+        "[…]"
+    } else {
+        "{…}"
+    };
+    format!(
+        "{} not loaded ({})",
+        brackets,
+        crate::editor::memory::format_size(source.text.len())
+    )
+}
+
 fn format_collapsed_object(
     fields: &indexmap::IndexMap<String, YamlNode>,
     max_chars: usize,
@@ -813,13 +1269,15 @@ fn format_collapsed_object(
         }
 
         // Add key
-        preview.push_str(key);
+        preview.push_str(written_key(key));
         preview.push_str(": ");
 
         // Add value
         let value_str = match value.value() {
             YamlValue::Object(_) => "{…}".to_string(),
             YamlValue::Array(_) | YamlValue::MultiDoc(_) => "[…]".to_string(),
+            YamlValue::Deferred(source) if source.is_sequence() => "[…]".to_string(),
+            YamlValue::Deferred(_) => "{…}".to_string(),
             YamlValue::String(s) => {
                 let s_str = s.as_str();
                 let quoted = format!("\"{}\"", s_str);
@@ -874,6 +1332,8 @@ fn format_collapsed_array(elements: &[YamlNode], max_chars: usize) -> String {
         let value_str = match element.value() {
             YamlValue::Object(_) => "{…}".to_string(),
             YamlValue::Array(_) | YamlValue::MultiDoc(_) => "[…]".to_string(),
+            YamlValue::Deferred(source) if source.is_sequence() => "[…]".to_string(),
+            YamlValue::Deferred(_) => "{…}".to_string(),
             YamlValue::String(s) => {
                 let s_str = s.as_str();
                 let quoted = format!("\"{}\"", s_str);
@@ -972,6 +1432,29 @@ mod tests {
         assert_eq!(state.lines()[1].key, Some("age".to_string()));
     }
 
+    #[test]
+    fn test_line_of_follows_rebuilds() {
+        let root = crate::document::parser::parse_yaml_auto("a:\n  b: 1\nc: 2\n").unwrap();
+        let tree = YamlTree::new(root);
+        let mut state = TreeViewState::new();
+        state.rebuild(&tree);
+        assert_eq!(state.line_of(&[1]), Some(1));
+        assert_eq!(state.line_of(&[0, 0]), None);
+
+        state.toggle_expand(&[0]);
+        state.rebuild(&tree);
+        assert_eq!(state.line_of(&[0, 0]), Some(1));
+        assert_eq!(state.line_of(&[1]), Some(2));
+
+        state.set_filter(Some(HashSet::from([vec![1]])));
+        state.rebuild(&tree);
+        assert_eq!(state.line_of(&[1]), Some(0));
+        assert_eq!(state.line_of(&[0]), None);
+        for (i, line) in state.lines().iter().enumerate() {
+            assert_eq!(state.line_of(&line.path), Some(i));
+        }
+    }
+
     #[test]
     fn test_rebuild_with_array() {
         let tree = YamlTree::new(YamlNode::new(YamlValue::Array(vec![
@@ -1109,7 +1592,18 @@ mod tests {
 
         terminal
             .draw(|f| {
-                render_tree_view(f, f.area(), &state, &cursor, &colors, false, false, 0, &[]);
+                render_tree_view(
+                    f,
+                    f.area(),
+                    &state,
+                    &cursor,
+                    &colors,
+                    false,
+                    false,
+                    0,
+                    0,
+                    &[],
+                );
             })
             .unwrap();
 
@@ -1164,7 +1658,18 @@ mod tests {
 
         terminal
             .draw(|f| {
-                render_tree_view(f, f.area(), &state, &cursor, &colors, false, false, 0, &[]);
+                render_tree_view(
+                    f,
+                    f.area(),
+                    &state,
+                    &cursor,
+                    &colors,
+                    false,
+                    false,
+                    0,
+                    0,
+                    &[],
+                );
             })
             .unwrap();
 
@@ -1238,7 +1743,18 @@ mod tests {
 
         terminal
             .draw(|f| {
-                render_tree_view(f, f.area(), &state, &cursor, &colors, false, false, 0, &[]);
+                render_tree_view(
+                    f,
+                    f.area(),
+                    &state,
+                    &cursor,
+                    &colors,
+                    false,
+                    false,
+                    0,
+                    0,
+                    &[],
+                );
             })
             .unwrap();
 
@@ -1489,110 +2005,19 @@ mod tests {
     }
 
     #[test]
-    fn test_update_paths_after_deletion() {
-        use crate::document::node::{YamlNode, YamlValue};
-        use crate::document::tree::YamlTree;
-
-        // Create array with 4 objects
-        let _tree = YamlTree::new(YamlNode::new(YamlValue::Array(vec![
-            YamlNode::new(YamlValue::Object(
-                vec![(
-                    "name".to_string(),
-                    YamlNode::new(YamlValue::String(YamlString::Plain("Alice".to_string()))),
-                )]
-                .into_iter()
-                .collect(),
-            )),
-            YamlNode::new(YamlValue::Object(
-                vec![(
-                    "name".to_string(),
-                    YamlNode::new(YamlValue::String(YamlString::Plain("Bob".to_string()))),
-                )]
-                .into_iter()
-                .collect(),
-            )),
-            YamlNode::new(YamlValue::Object(
-                vec![(
-                    "name".to_string(),
-                    YamlNode::new(YamlValue::String(YamlString::Plain("Charlie".to_string()))),
-                )]
-                .into_iter()
-                .collect(),
-            )),
-            YamlNode::new(YamlValue::Object(
-                vec![(
-                    "name".to_string(),
-                    YamlNode::new(YamlValue::String(YamlString::Plain("Dave".to_string()))),
-                )]
-                .into_iter()
-                .collect(),
-            )),
-        ])));
-
-        let mut state = TreeViewState::new();
-
-        // Expand objects at [0, 1], [0, 2], and [0, 3]
-        state.toggle_expand(&[0, 1]);
-        state.toggle_expand(&[0, 2]);
-        state.toggle_expand(&[0, 3]);
-
-        assert!(state.is_expanded(&[0, 1]));
-        assert!(state.is_expanded(&[0, 2]));
-        assert!(state.is_expanded(&[0, 3]));
-
-        // Delete node at [0, 1] (Bob)
-        state.update_paths_after_deletion(&[0, 1]);
-
-        // [0, 2] (Charlie) should now be at [0, 1] and still expanded
-        assert!(state.is_expanded(&[0, 1]));
-
-        // [0, 3] should now be at [0, 2] (Dave shifted down)
-        assert!(state.is_expanded(&[0, 2]));
-
-        // Old paths should not exist
-        assert!(!state.is_expanded(&[0, 3]));
-    }
-
-    #[test]
-    fn test_deletion_preserves_sibling_expansion() {
-        use crate::document::node::{YamlNode, YamlValue};
-        use crate::document::tree::YamlTree;
+    fn test_diagnostic_gutter_markers() {
+        use ratatui::backend::TestBackend;
+        use ratatui::Terminal;
 
-        // Create nested structure where we'll delete one object but others should stay expanded
-        let mut tree = YamlTree::new(YamlNode::new(YamlValue::Object(
+        let tree = YamlTree::new(YamlNode::new(YamlValue::Object(
             vec![
                 (
-                    "item1".to_string(),
-                    YamlNode::new(YamlValue::Object(
-                        vec![(
-                            "nested".to_string(),
-                            YamlNode::new(YamlValue::Number(YamlNumber::Integer(1))),
-                        )]
-                        .into_iter()
-                        .collect(),
-                    )),
-                ),
-                (
-                    "item2".to_string(),
-                    YamlNode::new(YamlValue::Object(
-                        vec![(
-                            "nested".to_string(),
-                            YamlNode::new(YamlValue::Number(YamlNumber::Integer(2))),
-                        )]
-                        .into_iter()
-                        .collect(),
-                    )),
+                    "a".to_string(),
+                    YamlNode::new(YamlValue::Number(YamlNumber::Integer(1))),
                 ),
                 (
-                    "item3".to_string(),
-                    YamlNode::new(YamlValue::Object(
-                        vec![(
-                            "nested".to_string(),
-                            YamlNode::new(YamlValue::Number(YamlNumber::Integer(3))),
-                        )]
-                        .into_iter()
-                        .collect(),
-                    )),
+                    "b".to_string(),
+                    YamlNode::new(YamlValue::Number(YamlNumber::Integer(2))),
                 ),
             ]
             .into_iter()
@@ -1600,35 +2025,34 @@ mod tests {
         )));
 
         let mut state = TreeViewState::new();
-
-        // Expand root and all items
-        state.toggle_expand(&[]);
-        state.toggle_expand(&[0]);
-        state.toggle_expand(&[1]);
-        state.toggle_expand(&[2]);
-        state.rebuild(&tree);
-
-        // Verify all are expanded
-        assert!(state.is_expanded(&[]));
-        assert!(state.is_expanded(&[0]));
-        assert!(state.is_expanded(&[1]));
-        assert!(state.is_expanded(&[2]));
-
-        // Delete item2 at index [1]
-        tree.delete_node(&[1]).unwrap();
-        state.update_paths_after_deletion(&[1]);
         state.rebuild(&tree);
+        state.set_diagnostic_markers(HashMap::from([(vec![1], Severity::Error)]));
 
-        // Root should still be expanded
-        assert!(state.is_expanded(&[]));
-
-        // item1 at [0] should still be expanded (unaffected)
-        assert!(state.is_expanded(&[0]));
+        let backend = TestBackend::new(40, 4);
+        let mut terminal = Terminal::new(backend).unwrap();
+        let colors = ThemeColors::default_dark();
+        let cursor = Cursor::new();
 
-        // item3 was at [2], now should be at [1] and still expanded
-        assert!(state.is_expanded(&[1]));
+        terminal
+            .draw(|f| {
+                render_tree_view(
+                    f,
+                    f.area(),
+                    &state,
+                    &cursor,
+                    &colors,
+                    false,
+                    false,
+                    0,
+                    0,
+                    &[],
+                );
+            })
+            .unwrap();
 
-        // Old path [2] should not be expanded
-        assert!(!state.is_expanded(&[2]));
+        let buffer = terminal.backend().buffer().clone();
+        let row = |y: u16| -> String { (0..40).map(|x| buffer[(x, y)].symbol()).collect() };
+        assert!(row(0).starts_with("  "));
+        assert!(row(1).starts_with("E "));
     }
 }
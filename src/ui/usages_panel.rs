@@ -0,0 +1,94 @@
+//! Results panel for `:usages` on a value, also used to preview `:extract`.
+
+use crate::editor::usages::UsagesPanelState;
+use crate::theme::colors::ThemeColors;
+use crate::ui::theme_picker::centered_rect;
+use ratatui::{
+    layout::Alignment,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Renders the `:usages` results panel.
+///
+/// Each row shows where the value is used and how it's written there, or
+/// for an `:extract` preview, what it becomes. The selected row is
+/// highlighted and kept in view.
+pub fn render_usages_panel(f: &mut Frame, state: &UsagesPanelState, colors: &ThemeColors) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let (title, hint) = match &state.extraction {
+        Some(extraction) => (
+            format!(" Extract {} as &{} ", state.value, extraction.name),
+            "↑/↓: Navigate  Enter: Extract  Esc: Cancel",
+        ),
+        None => (
+            format!(" Usages of {} ", state.value),
+            "↑/↓: Navigate  Enter: Jump  Esc: Cancel",
+        ),
+    };
+    let block = Block::default()
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors.info))
+        .style(Style::default().bg(colors.background));
+
+    let location_width = state
+        .usages
+        .iter()
+        .map(|u| u.location.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = Vec::new();
+    for (idx, usage) in state.usages.iter().enumerate() {
+        let is_selected = idx == state.selected_index;
+        let cursor = if is_selected { "> " } else { "  " };
+        let location = format!(
+            "{}{:width$}  ",
+            cursor,
+            usage.location,
+            width = location_width
+        );
+
+        let line = if is_selected {
+            let style = Style::default()
+                .fg(ratatui::style::Color::White)
+                .bg(colors.cursor)
+                .add_modifier(Modifier::BOLD);
+            Line::from(vec![
+                Span::styled(location, style),
+                Span::styled(usage.preview.clone(), style),
+            ])
+        } else {
+            Line::from(vec![
+                Span::styled(location, Style::default().fg(colors.key)),
+                Span::styled(usage.preview.clone(), Style::default().fg(colors.preview)),
+            ])
+        };
+        lines.push(line);
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        hint,
+        Style::default()
+            .fg(colors.info)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    // Scroll just enough to keep the selected usage visible
+    let height = area.height.saturating_sub(2) as usize;
+    let scroll = (state.selected_index + 1).saturating_sub(height);
+
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll as u16, 0)),
+        area,
+    );
+}
@@ -0,0 +1,66 @@
+//! Watch panel (`:watch`).
+//!
+//! Shown below the tree view while any expression is watched. Each row gives
+//! the watch's number, its YAMLPath expression and its current value. A
+//! value that just changed flashes for a moment and keeps showing what it
+//! was before the change.
+
+use crate::editor::state::EditorState;
+use crate::theme::colors::ThemeColors;
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use std::time::Instant;
+
+/// Height of the watch panel for `watches` expressions, including its border.
+pub fn watch_panel_height(watches: usize) -> u16 {
+    watches as u16 + 1
+}
+
+/// Renders the watch panel into `area`.
+pub fn render_watch_panel(f: &mut Frame, area: Rect, state: &EditorState, colors: &ThemeColors) {
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .title(" Watches  :unwatch N: remove ")
+        .style(Style::default().bg(colors.background).fg(colors.foreground));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let now = Instant::now();
+    let lines: Vec<Line> = state
+        .watches()
+        .iter()
+        .enumerate()
+        .take(inner.height as usize)
+        .map(|(i, watch)| {
+            let mut value_style = Style::default().fg(colors.preview);
+            if watch.is_flashing(now) {
+                value_style = value_style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            }
+            let mut spans = vec![
+                Span::styled(format!("{} ", i + 1), Style::default().fg(colors.number)),
+                Span::styled(
+                    watch.expression().to_string(),
+                    Style::default().fg(colors.key),
+                ),
+                Span::raw("  "),
+                Span::styled(watch.value().to_string(), value_style),
+            ];
+            if let Some(previous) = watch.previous() {
+                spans.push(Span::styled(
+                    format!("  (was {})", previous),
+                    Style::default()
+                        .fg(colors.comment)
+                        .add_modifier(Modifier::DIM),
+                ));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
@@ -24,6 +24,41 @@ impl Parser {
         parser.parse_path()
     }
 
+    /// Splits a command argument into a leading query and the rest.
+    ///
+    /// The query ends at the first whitespace outside brackets and quotes,
+    /// so `$['my key'] new value` splits after `]`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use yamlquill::yamlpath::Parser;
+    ///
+    /// assert_eq!(
+    ///     Parser::split_query("$..['pull policy'] Always"),
+    ///     ("$..['pull policy']", "Always")
+    /// );
+    /// ```
+    pub fn split_query(input: &str) -> (&str, &str) {
+        let input = input.trim_start();
+        let mut depth = 0usize;
+        let mut quote = None;
+        for (i, c) in input.char_indices() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '\'' | '"') => quote = Some(c),
+                (None, '[') => depth += 1,
+                (None, ']') => depth = depth.saturating_sub(1),
+                (None, c) if c.is_whitespace() && depth == 0 => {
+                    return (&input[..i], input[i..].trim_start());
+                }
+                _ => {}
+            }
+        }
+        (input, "")
+    }
+
     fn parse_path(&mut self) -> Result<YamlPath, YamlPathError> {
         let mut segments = Vec::new();
 
@@ -324,6 +359,16 @@ impl Parser {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_split_query() {
+        assert_eq!(Parser::split_query("$.a.b  x y"), ("$.a.b", "x y"));
+        assert_eq!(
+            Parser::split_query("$[\"a b\"][0] 1"),
+            ("$[\"a b\"][0]", "1")
+        );
+        assert_eq!(Parser::split_query("$..name"), ("$..name", ""));
+    }
+
     #[test]
     fn test_parse_root() {
         let result = Parser::parse("$");
@@ -0,0 +1,67 @@
+//! Tests for the aggregate commands (`:sum`, `:count`, `:min`, `:max`, `:avg`).
+
+use yamlquill::document::node::{YamlNumber, YamlValue};
+use yamlquill::harness::Harness;
+
+const WEIGHTS: &str = "\
+weights:
+  a: 40
+  b: 35.5
+  c: 24.5
+  note: heavy
+limits: [3, 9]
+";
+
+fn message(h: &Harness) -> String {
+    h.state()
+        .message()
+        .map(|m| m.text.clone())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_sum_over_yamlpath_argument() {
+    let mut h = Harness::new(WEIGHTS).unwrap();
+    h.keys(":sum $.weights.*<CR>").unwrap();
+    assert_eq!(message(&h), "sum = 100 (3 numbers, yanked)");
+
+    // The result is in the unnamed register
+    let register = h.state().get_unnamed_register();
+    assert_eq!(
+        register.nodes[0].value(),
+        &YamlValue::Number(YamlNumber::Float(100.0))
+    );
+}
+
+#[test]
+fn test_aggregates_over_visual_selection() {
+    let mut h = Harness::new(WEIGHTS).unwrap();
+    // Select the `limits` list, whose numbers are nested one level down
+    h.keys("Gkk").unwrap();
+    assert_eq!(h.state().cursor().path(), &[1]);
+    h.keys("v:max<CR>").unwrap();
+    assert_eq!(message(&h), "max = 9 (2 numbers, yanked)");
+    assert!(h.state().visual_selection().is_empty());
+
+    h.keys("V:count<CR>").unwrap();
+    assert_eq!(message(&h), "count = 2 (2 numbers, yanked)");
+}
+
+#[test]
+fn test_aggregates_over_last_path_search() {
+    let mut h = Harness::new(WEIGHTS).unwrap();
+    h.keys(":path $.limits[*]<CR>:avg<CR>").unwrap();
+    assert_eq!(message(&h), "avg = 6 (2 numbers, yanked)");
+    h.keys(":min<CR>").unwrap();
+    assert_eq!(message(&h), "min = 3 (2 numbers, yanked)");
+}
+
+#[test]
+fn test_aggregate_without_input_or_numbers() {
+    let mut h = Harness::new(WEIGHTS).unwrap();
+    h.keys(":sum<CR>").unwrap();
+    assert!(message(&h).starts_with("Nothing to sum"), "{}", message(&h));
+
+    h.keys(":avg $.weights.note<CR>").unwrap();
+    assert_eq!(message(&h), "No numbers to avg");
+}
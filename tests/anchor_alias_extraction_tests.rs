@@ -3,7 +3,7 @@
 //! These tests validate the hybrid parsing approach that uses Scanner
 //! to extract anchor/alias names and correlates them with the parsed tree.
 
-use yamlquill::document::node::{YamlNumber, YamlString, YamlValue};
+use yamlquill::document::node::YamlValue;
 use yamlquill::document::parser::parse_yaml_auto;
 
 #[test]
@@ -594,7 +594,7 @@ production:
 "#;
     let node = parse_yaml_auto(yaml).unwrap();
     let tree = YamlTree::new(node);
-    let mut state = EditorState::new_with_default_theme(tree);
+    let state = EditorState::new_with_default_theme(tree);
 
     // Try to look up a non-existent anchor
     let anchor_path = state
@@ -0,0 +1,62 @@
+//! Tests for browsing and restoring backups with `:backups`.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::editor::review::ChangeKind;
+use yamlquill::harness::Harness;
+
+fn message(h: &Harness) -> Option<String> {
+    h.state().message().map(|m| m.text.clone())
+}
+
+#[test]
+fn test_backups_diff_and_restore() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 3\nimage: nginx\n").unwrap();
+    let mut h = Harness::open(&path).unwrap();
+
+    // Saving with create_backup keeps the previous version as app.yaml.bak
+    h.keys(":set create_backup<CR>e<BS>5<CR>:w<CR>").unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("app.yaml.bak")).unwrap(),
+        "replicas: 3\nimage: nginx\n"
+    );
+
+    h.keys(":backups<CR>").unwrap();
+    let picker = h.state().backup_picker().unwrap();
+    assert_eq!(picker.backups.len(), 1);
+    let changes = &picker.backups[0].changes;
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].kind, ChangeKind::Modified);
+    assert_eq!(changes[0].relative_label(), ".replicas");
+    assert!(h.screen().unwrap().contains("app.yaml.bak"));
+
+    h.keys("<CR>").unwrap();
+    assert!(h.state().backup_picker().is_none());
+    assert!(h.state().is_dirty());
+    assert_eq!(h.document().unwrap(), "replicas: 3\nimage: nginx\n");
+    // Restoring doesn't write the file
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "replicas: 5\nimage: nginx\n"
+    );
+
+    h.keys("u").unwrap();
+    assert_eq!(h.document().unwrap(), "replicas: 5\nimage: nginx\n");
+}
+
+#[test]
+fn test_backups_without_any() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 3\n").unwrap();
+    let mut h = Harness::open(&path).unwrap();
+    h.keys(":backups<CR>").unwrap();
+    assert!(h.state().backup_picker().is_none());
+    assert!(message(&h).unwrap().starts_with("No backups of this file"));
+
+    let mut h = Harness::new("replicas: 3\n").unwrap();
+    h.keys(":backups<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("No file name"));
+}
@@ -0,0 +1,73 @@
+//! Tests for `:b64 encode`/`:b64 decode`, the `gb` toggle and showing a
+//! Secret's values decoded.
+
+use yamlquill::harness::Harness;
+
+const SECRET: &str = "\
+apiVersion: v1
+kind: Secret
+metadata:
+  name: db-main
+data:
+  username: YWRtaW4=
+  password: cGFzc3dvcmQ=
+";
+
+fn message(h: &Harness) -> Option<String> {
+    h.state().message().map(|m| m.text.clone())
+}
+
+#[test]
+fn test_encode_decode_and_toggle() {
+    let mut h = Harness::new(SECRET).unwrap();
+    h.keys(":path $.data.username<CR>:b64 decode<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Decoded from base64"));
+    assert!(h.document().unwrap().contains("  username: admin\n"));
+
+    h.keys(":b64 encode<CR>").unwrap();
+    assert!(h.document().unwrap().contains("  username: YWRtaW4=\n"));
+
+    h.keys(":path $.data.password<CR>gb").unwrap();
+    assert!(h.document().unwrap().contains("  password: password\n"));
+    h.keys("gb").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Encoded as base64"));
+    assert!(h.document().unwrap().contains("  password: cGFzc3dvcmQ=\n"));
+
+    // Each change is one undo step
+    h.keys("u").unwrap();
+    assert!(h.document().unwrap().contains("  password: password\n"));
+}
+
+#[test]
+fn test_b64_errors() {
+    let mut h = Harness::new(SECRET).unwrap();
+    h.keys(":path $.metadata<CR>:b64 encode<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Not a string (move to a string value)")
+    );
+
+    h.keys(":path $.metadata.name<CR>:b64 decode<CR>").unwrap();
+    assert!(message(&h).unwrap().starts_with("Not base64"));
+
+    h.keys(":b64<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Usage: :b64 encode|decode"));
+    assert!(!h.state().is_dirty());
+}
+
+#[test]
+fn test_decoded_preview() {
+    let mut h = Harness::new(SECRET).unwrap().with_size(60, 12);
+    assert!(!h.screen().unwrap().contains("admin"));
+
+    h.keys(":set decodesecrets<CR>").unwrap();
+    let screen = h.screen().unwrap();
+    assert!(screen.contains("admin (decoded)"));
+    assert!(screen.contains("password (decoded)"));
+    // Only the display changes
+    assert!(!h.state().is_dirty());
+    assert!(h.document().unwrap().contains("  username: YWRtaW4=\n"));
+
+    h.keys(":set nodecodesecrets<CR>").unwrap();
+    assert!(h.screen().unwrap().contains("YWRtaW4="));
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::cli::batch::{run, Edit};
+use yamlquill::config::Config;
+use yamlquill::file::loader::load_yaml_file;
+
+const DEPLOYMENT: &str = "\
+# Web deployment
+metadata:
+  name: web
+  annotations:
+    owner: team-a
+spec:
+  replicas: 1  # scaled by CI
+  image: nginx:1.24
+";
+
+fn edits(texts: &[&str]) -> Vec<Edit> {
+    texts.iter().map(|t| Edit::parse(t).unwrap()).collect()
+}
+
+#[test]
+fn test_apply_prints_result_and_keeps_formatting() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("deploy.yaml");
+    fs::write(&path, DEPLOYMENT).unwrap();
+    let file = path.to_str().unwrap();
+
+    let text = run(
+        load_yaml_file(&path).unwrap(),
+        Some(file),
+        &edits(&["$.spec.replicas=3", "$.spec.image=nginx:1.25"]),
+        false,
+        &Config::default(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        text,
+        DEPLOYMENT
+            .replace("replicas: 1", "replicas: 3")
+            .replace("1.24", "1.25")
+    );
+    // Printing leaves the file alone
+    assert_eq!(fs::read_to_string(&path).unwrap(), DEPLOYMENT);
+}
+
+#[test]
+fn test_apply_in_place_writes_the_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("deploy.yaml");
+    fs::write(&path, DEPLOYMENT).unwrap();
+    let file = path.to_str().unwrap();
+
+    let printed = run(
+        load_yaml_file(&path).unwrap(),
+        Some(file),
+        &edits(&["del($.metadata.annotations)"]),
+        true,
+        &Config::default(),
+    )
+    .unwrap();
+    assert_eq!(printed, None);
+    let saved: serde_yaml::Value =
+        serde_yaml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(saved["metadata"]["annotations"], serde_yaml::Value::Null);
+    assert_eq!(saved["metadata"]["name"], "web");
+}
+
+#[test]
+fn test_failed_edit_writes_nothing() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("deploy.yaml");
+    fs::write(&path, DEPLOYMENT).unwrap();
+    let file = path.to_str().unwrap();
+
+    let err = run(
+        load_yaml_file(&path).unwrap(),
+        Some(file),
+        &edits(&["$.spec.replicas=3", "del($.spec.missing)"]),
+        true,
+        &Config::default(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "--apply 'del($.spec.missing)': $.spec.missing matches nothing"
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), DEPLOYMENT);
+}
+
+#[test]
+fn test_apply_to_json_prints_json() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("package.json");
+    fs::write(&path, "{\"name\": \"web\", \"private\": false}\n").unwrap();
+
+    let text = run(
+        load_yaml_file(&path).unwrap(),
+        path.to_str(),
+        &edits(&["$.private=true"]),
+        false,
+        &Config::default(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(text, "{\n  \"name\": \"web\",\n  \"private\": true\n}\n");
+}
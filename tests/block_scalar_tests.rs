@@ -0,0 +1,100 @@
+//! Integration tests for editing literal and folded strings in the multi-line editor.
+
+use std::fs;
+use tempfile::TempDir;
+use termion::event::{Event, Key};
+use yamlquill::config::Config;
+use yamlquill::document::node::{YamlString, YamlValue};
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::editor::mode::EditorMode;
+use yamlquill::editor::state::EditorState;
+use yamlquill::file::loader::load_yaml_file;
+use yamlquill::file::saver::save_yaml_file;
+use yamlquill::input::InputHandler;
+
+fn press(handler: &mut InputHandler, state: &mut EditorState, keys: &[Key]) {
+    for key in keys {
+        handler.handle_event(Event::Key(*key), state).unwrap();
+    }
+}
+
+fn typed(text: &str) -> Vec<Key> {
+    text.chars().map(Key::Char).collect()
+}
+
+/// Loads `yaml` from a file, presses `keys`, saves, and returns the file.
+fn edit_and_save(yaml: &str, keys: &[Key]) -> String {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, yaml).unwrap();
+    let mut state = EditorState::new_with_default_theme(load_yaml_file(&path).unwrap());
+    let mut handler = InputHandler::new();
+    press(&mut handler, &mut state, keys);
+    save_yaml_file(&path, state.tree(), &Config::default()).unwrap();
+    fs::read_to_string(&path).unwrap()
+}
+
+#[test]
+fn test_parser_records_block_style() {
+    let root = parse_yaml_auto("a: |\n  x\n  y\nb: >-\n  p\n  q\nc: '3'\nd: |\n  42\n").unwrap();
+    let YamlValue::Object(entries) = root.value() else {
+        panic!("not a mapping");
+    };
+    assert_eq!(
+        entries["a"].value(),
+        &YamlValue::String(YamlString::Literal("x\ny\n".to_string()))
+    );
+    assert_eq!(
+        entries["b"].value(),
+        &YamlValue::String(YamlString::Folded("p q".to_string()))
+    );
+    // A block scalar is a string even when it looks like a number
+    assert_eq!(
+        entries["d"].value(),
+        &YamlValue::String(YamlString::Literal("42\n".to_string()))
+    );
+}
+
+#[test]
+fn test_edit_literal_lines() {
+    let mut keys = vec![Key::Char('j'), Key::Char('e'), Key::Up, Key::End];
+    keys.extend(typed("\ncheck"));
+    keys.push(Key::Ctrl('s'));
+    let saved = edit_and_save("name: web\nscript: |\n  build\n  test\n", &keys);
+    assert_eq!(saved, "name: web\nscript: |\n  build\n  check\n  test\n");
+}
+
+#[test]
+fn test_folded_style_is_kept() {
+    let mut keys = typed("jjje too");
+    keys.push(Key::Ctrl('s'));
+    let saved = edit_and_save(
+        "jobs:\n  - name: lint\n    about: >\n      Checks the\n      code.\n\n      Fast.\n",
+        &keys,
+    );
+    assert_eq!(
+        saved,
+        "jobs:\n- name: lint\n  about: >\n    Checks the code.\n\n    Fast. too\n"
+    );
+}
+
+#[test]
+fn test_cancel_and_plain_strings() {
+    let mut state = EditorState::new_with_default_theme(yamlquill::document::tree::YamlTree::new(
+        parse_yaml_auto("a: |\n  x\nb: plain\n").unwrap(),
+    ));
+    let mut handler = InputHandler::new();
+
+    press(&mut handler, &mut state, &[Key::Char('e')]);
+    assert_eq!(*state.mode(), EditorMode::Insert);
+    assert_eq!(state.multiline_edit().unwrap().lines(), ["x"]);
+    press(&mut handler, &mut state, &[Key::Char('!'), Key::Esc]);
+    assert_eq!(*state.mode(), EditorMode::Normal);
+    assert!(state.multiline_edit().is_none());
+    assert!(!state.is_dirty());
+
+    // Single-line strings keep the edit prompt
+    press(&mut handler, &mut state, &[Key::Char('j'), Key::Char('e')]);
+    assert!(state.multiline_edit().is_none());
+    assert_eq!(state.edit_buffer(), Some("plain"));
+}
@@ -0,0 +1,191 @@
+//! Integration tests for persistent per-project bookmarks.
+
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use termion::event::{Event, Key};
+use yamlquill::app::App;
+use yamlquill::editor::state::EditorState;
+use yamlquill::file::loader::load_yaml_file;
+use yamlquill::input::InputHandler;
+
+/// A project with a `.git` marker, two files and a separate bookmark store.
+struct Project {
+    dir: TempDir,
+    store: TempDir,
+}
+
+impl Project {
+    fn new() -> Self {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(
+            dir.path().join("values.yaml"),
+            "database:\n  host: localhost\n  url: postgres://prod\n",
+        )
+        .unwrap();
+        fs::create_dir(dir.path().join("charts")).unwrap();
+        fs::write(
+            dir.path().join("charts/web.yaml"),
+            "replicas: 2\nimage: web\n",
+        )
+        .unwrap();
+        Self {
+            dir,
+            store: TempDir::new().unwrap(),
+        }
+    }
+
+    fn open(&self, file: &str) -> EditorState {
+        let path = self.dir.path().join(file);
+        let mut state = EditorState::new_with_default_theme(load_yaml_file(&path).unwrap());
+        state.set_filename(path.display().to_string());
+        state.set_bookmark_dir(Some(self.store.path().to_path_buf()));
+        state
+    }
+}
+
+fn command(handler: &mut InputHandler, app: &mut App, cmd: &str) {
+    let keys = std::iter::once(':')
+        .chain(cmd.chars())
+        .chain(std::iter::once('\n'));
+    for c in keys {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), app.focused_mut())
+            .unwrap();
+        app.handle_pending();
+    }
+}
+
+#[test]
+fn test_bookmark_survives_restart_and_edits() {
+    let project = Project::new();
+    let mut state = project.open("values.yaml");
+    state.cursor_mut().set_path(vec![0, 1]);
+    state.add_bookmark("prod-db-url").unwrap();
+
+    // A new session sees the bookmark, stored relative to the project root
+    let store = project.open("charts/web.yaml").bookmark_store().unwrap();
+    assert_eq!(store.root(), project.dir.path());
+    let bookmark = store.get("prod-db-url").unwrap();
+    assert_eq!(bookmark.file, "values.yaml");
+    assert_eq!(bookmark.path, "$[\"database\"][\"url\"]");
+
+    // Keys added above the node don't break the bookmark
+    fs::write(
+        project.dir.path().join("values.yaml"),
+        "name: app\ndatabase:\n  port: 5432\n  host: localhost\n  url: postgres://prod\n",
+    )
+    .unwrap();
+    let mut state = project.open("values.yaml");
+    state.go_to_bookmark("prod-db-url").unwrap();
+    assert_eq!(state.cursor().path(), &[1, 2]);
+}
+
+#[test]
+fn test_bookmark_in_other_file_opens_buffer() {
+    let project = Project::new();
+    let mut web = project.open("charts/web.yaml");
+    web.cursor_mut().set_path(vec![1]);
+    web.add_bookmark("web-image").unwrap();
+
+    let mut handler = InputHandler::new();
+    let mut app = App::new(project.open("values.yaml"));
+    command(&mut handler, &mut app, "bookmark web-image");
+
+    assert_eq!(app.buffers().len(), 2);
+    let focused = app.focused();
+    assert!(Path::new(focused.filename().unwrap()).ends_with("charts/web.yaml"));
+    assert_eq!(focused.cursor().path(), &[1]);
+}
+
+#[test]
+fn test_picker_and_remove() {
+    let project = Project::new();
+    let mut handler = InputHandler::new();
+    let mut app = App::new(project.open("values.yaml"));
+
+    app.focused_mut().cursor_mut().set_path(vec![0, 0]);
+    command(&mut handler, &mut app, "bookmark add host");
+    app.focused_mut().cursor_mut().set_path(vec![0, 1]);
+    command(&mut handler, &mut app, "bookmark add url");
+    app.focused_mut().cursor_mut().set_path(vec![0]);
+
+    command(&mut handler, &mut app, "bookmarks");
+    assert_eq!(app.focused().bookmark_picker().unwrap().bookmarks.len(), 2);
+    for key in [Key::Char('j'), Key::Char('\n')] {
+        handler
+            .handle_event(Event::Key(key), app.focused_mut())
+            .unwrap();
+        app.handle_pending();
+    }
+    assert!(app.focused().bookmark_picker().is_none());
+    assert_eq!(app.focused().cursor().path(), &[0, 1]);
+
+    command(&mut handler, &mut app, "bookmark rm host");
+    let store = app.focused().bookmark_store().unwrap();
+    assert!(store.get("host").is_none());
+
+    command(&mut handler, &mut app, "bookmark rm host");
+    assert!(app
+        .focused()
+        .message()
+        .unwrap()
+        .text
+        .contains("No bookmark"));
+}
+
+#[test]
+fn test_bookmark_follows_node_moved_by_edits() {
+    let project = Project::new();
+    fs::write(
+        project.dir.path().join("values.yaml"),
+        "servers:\n  - alpha\n  - beta\n",
+    )
+    .unwrap();
+    let mut state = project.open("values.yaml");
+    state.cursor_mut().set_path(vec![0, 1]);
+    state.add_bookmark("beta").unwrap();
+
+    // Deleting the item above moves beta to index 0
+    state.path_delete("$.servers[0]").unwrap();
+    state.go_to_bookmark("beta").unwrap();
+    assert_eq!(state.cursor().path(), &[0, 0]);
+
+    // The stored path is brought up to date for later sessions
+    let store = state.bookmark_store().unwrap();
+    assert_eq!(store.get("beta").unwrap().path, "$[\"servers\"][0]");
+}
+
+#[test]
+fn test_bookmark_reresolves_after_structural_drift() {
+    let project = Project::new();
+    let mut state = project.open("values.yaml");
+    state.cursor_mut().set_path(vec![0, 1]);
+    state.add_bookmark("prod-db-url").unwrap();
+
+    // Between sessions the parent key is renamed and moved down
+    fs::write(
+        project.dir.path().join("values.yaml"),
+        "name: app\nstorage:\n  postgres:\n    host: localhost\n    url: postgres://prod\n",
+    )
+    .unwrap();
+    let mut state = project.open("values.yaml");
+    state.go_to_bookmark("prod-db-url").unwrap();
+    assert_eq!(state.cursor().path(), &[1, 0, 1]);
+    let store = state.bookmark_store().unwrap();
+    assert_eq!(
+        store.get("prod-db-url").unwrap().path,
+        "$[\"storage\"][\"postgres\"][\"url\"]"
+    );
+
+    // A trailing key that is no longer unique can't be re-resolved
+    fs::write(
+        project.dir.path().join("values.yaml"),
+        "primary:\n  url: a\nreplica:\n  url: b\n",
+    )
+    .unwrap();
+    let mut state = project.open("values.yaml");
+    let err = state.go_to_bookmark("prod-db-url").unwrap_err();
+    assert!(err.to_string().starts_with("Not found"), "{}", err);
+}
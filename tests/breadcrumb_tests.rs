@@ -0,0 +1,76 @@
+//! Tests for the breadcrumb bar.
+
+use termion::event::{Event, MouseButton, MouseEvent};
+use yamlquill::harness::Harness;
+
+const POD: &str = "\
+spec:
+  template:
+    spec:
+      containers:
+        - name: web
+          image: nginx:1.25
+";
+
+fn harness_at_image() -> Harness {
+    let mut h = Harness::new(POD).unwrap().with_size(60, 12);
+    h.state_mut()
+        .jump_to_yamlpath("$.spec.template.spec.containers[0].image")
+        .unwrap();
+    h
+}
+
+#[test]
+fn test_breadcrumbs_off_by_default() {
+    let mut h = harness_at_image();
+    assert!(!h.state().show_breadcrumbs());
+    let screen = h.screen().unwrap();
+    assert!(!screen.lines().next().unwrap().contains("containers[0]"));
+}
+
+#[test]
+fn test_breadcrumbs_show_cursor_path() {
+    let mut h = harness_at_image();
+    h.keys(":set breadcrumbs<CR>").unwrap();
+    let screen = h.screen().unwrap();
+    assert_eq!(
+        screen.lines().next().unwrap().trim_end(),
+        "spec.template.spec.containers[0].image"
+    );
+}
+
+#[test]
+fn test_click_jumps_to_ancestor() {
+    let mut h = harness_at_image();
+    h.keys(":set breadcrumbs<CR>").unwrap();
+    h.screen().unwrap();
+
+    // "template" starts at column 5; termion cells are 1-based
+    h.event(Event::Mouse(MouseEvent::Press(MouseButton::Left, 7, 1)))
+        .unwrap();
+    assert_eq!(h.state().cursor().path(), &[0, 0]);
+
+    // Clicks between segments do nothing
+    h.keys("G").unwrap();
+    h.screen().unwrap();
+    let before = h.state().cursor().path().to_vec();
+    h.event(Event::Mouse(MouseEvent::Press(MouseButton::Left, 5, 1)))
+        .unwrap();
+    assert_eq!(h.state().cursor().path(), before.as_slice());
+
+    // Index segments jump to the list element
+    h.event(Event::Mouse(MouseEvent::Press(MouseButton::Left, 31, 1)))
+        .unwrap();
+    assert_eq!(h.state().cursor().path(), &[0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_long_paths_drop_outer_segments() {
+    let mut h = harness_at_image().with_size(24, 12);
+    h.keys(":set breadcrumbs<CR>").unwrap();
+    let screen = h.screen().unwrap();
+    assert_eq!(
+        screen.lines().next().unwrap().trim_end(),
+        "….containers[0].image"
+    );
+}
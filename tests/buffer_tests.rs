@@ -0,0 +1,96 @@
+//! Integration tests for the buffer list (`:e`, `:bn`, `:bp`, `:ls`).
+
+use std::fs;
+use tempfile::TempDir;
+use termion::event::{Event, Key};
+use yamlquill::app::App;
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::EditorState;
+use yamlquill::input::InputHandler;
+
+fn command(handler: &mut InputHandler, app: &mut App, cmd: &str) {
+    let keys = std::iter::once(':')
+        .chain(cmd.chars())
+        .chain(std::iter::once('\n'));
+    for c in keys {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), app.focused_mut())
+            .unwrap();
+        app.handle_pending();
+    }
+}
+
+fn setup() -> (TempDir, String, App) {
+    let dir = TempDir::new().unwrap();
+    let other = dir.path().join("other.yaml");
+    fs::write(&other, "name: other\n").unwrap();
+
+    let tree = YamlTree::new(parse_yaml_auto("name: first\n").unwrap());
+    let mut state = EditorState::new_with_default_theme(tree);
+    state.set_filename("first.yaml".to_string());
+    (dir, other.display().to_string(), App::new(state))
+}
+
+#[test]
+fn test_edit_opens_new_buffer_and_keeps_changes() {
+    let (_dir, other, mut app) = setup();
+    let mut handler = InputHandler::new();
+    app.focused_mut().mark_dirty();
+
+    command(&mut handler, &mut app, &format!("e {}", other));
+    assert_eq!(app.buffers().len(), 2);
+    assert_eq!(app.focused_buffer_index(), 1);
+    assert_eq!(app.focused().filename(), Some(other.as_str()));
+    assert_eq!(app.focused().buffer_position(), Some((2, 2)));
+    assert!(app.buffers()[0].is_dirty());
+
+    // Editing an open file switches to its buffer instead of loading it again
+    command(&mut handler, &mut app, "bp");
+    command(&mut handler, &mut app, &format!("e {}", other));
+    assert_eq!(app.buffers().len(), 2);
+    assert_eq!(app.focused_buffer_index(), 1);
+}
+
+#[test]
+fn test_bn_bp_and_ls() {
+    let (_dir, other, mut app) = setup();
+    let mut handler = InputHandler::new();
+    command(&mut handler, &mut app, &format!("e {}", other));
+
+    command(&mut handler, &mut app, "bn");
+    assert_eq!(app.focused().filename(), Some("first.yaml"));
+    command(&mut handler, &mut app, "bp");
+    assert_eq!(app.focused().filename(), Some(other.as_str()));
+    command(&mut handler, &mut app, "b 1");
+    assert_eq!(app.focused_buffer_index(), 0);
+
+    command(&mut handler, &mut app, "ls");
+    let message = app.focused().message().unwrap().text.clone();
+    assert!(message.starts_with("1% \"first.yaml\""), "{}", message);
+    assert!(message.contains("2 \""), "{}", message);
+}
+
+#[test]
+fn test_quit_refuses_with_hidden_changes() {
+    let (_dir, other, mut app) = setup();
+    let mut handler = InputHandler::new();
+    app.focused_mut().mark_dirty();
+    command(&mut handler, &mut app, &format!("e {}", other));
+
+    assert!(!app.quit());
+    assert_eq!(app.focused().filename(), Some("first.yaml"));
+
+    command(&mut handler, &mut app, "bn");
+    let should_quit = {
+        let mut quit = false;
+        for c in ":q!\n".chars() {
+            quit = handler
+                .handle_event(Event::Key(Key::Char(c)), app.focused_mut())
+                .unwrap();
+        }
+        quit
+    };
+    assert!(should_quit);
+    assert!(app.quit());
+}
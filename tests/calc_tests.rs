@@ -0,0 +1,51 @@
+//! Tests for expressions typed while editing a number.
+
+use yamlquill::harness::Harness;
+
+const YAML: &str = "\
+replicas: 3
+memory: 512
+name: api
+";
+
+fn message(h: &Harness) -> Option<String> {
+    h.state().message().map(|m| m.text.clone())
+}
+
+#[test]
+fn test_expression_edits_are_computed() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys("e<BS>*2<CR>").unwrap();
+    h.keys(":path $.memory<CR>e<BS><BS><BS>=1024*1024<CR>")
+        .unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "replicas: 6\nmemory: 1048576\nname: api\n"
+    );
+    h.keys("u").unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "replicas: 6\nmemory: 512\nname: api\n"
+    );
+}
+
+#[test]
+fn test_invalid_expression_keeps_editing() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys("e<BS>+ten<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Invalid value: Invalid expression '+ten': unexpected 't'")
+    );
+    assert_eq!(h.document().unwrap(), YAML);
+}
+
+#[test]
+fn test_expressions_only_apply_to_numbers() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys(":path $.name<CR>e<BS><BS><BS>*2<CR>").unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "replicas: 3\nmemory: 512\nname: '*2'\n"
+    );
+}
@@ -0,0 +1,46 @@
+//! Tests for structural clipboard paste (`"+p`).
+
+use yamlquill::harness::Harness;
+
+#[test]
+fn test_paste_yaml_mapping_as_sibling_keys() {
+    let mut h = Harness::new("name: app\nreplicas: 2\n").unwrap();
+    h.state_mut()
+        .paste_text("image: nginx:1.27\nports:\n  - 80\n", true)
+        .unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "name: app\nimage: nginx:1.27\nports:\n- 80\nreplicas: 2\n"
+    );
+
+    // One undo step for the whole paste
+    h.keys("u").unwrap();
+    assert_eq!(h.document().unwrap(), "name: app\nreplicas: 2\n");
+}
+
+#[test]
+fn test_paste_json_into_list() {
+    let mut h = Harness::new("items:\n  - a\n").unwrap();
+    h.keys("j").unwrap();
+    h.state_mut()
+        .paste_text(r#"[{"name": "b", "port": 80}, "c"]"#, true)
+        .unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "items:\n- a\n- name: b\n  port: 80\n- c\n"
+    );
+}
+
+#[test]
+fn test_paste_unparsable_text_as_string() {
+    let mut h = Harness::new("- a\n").unwrap();
+    h.state_mut().paste_text("{not: yaml", false).unwrap();
+    assert_eq!(h.document().unwrap().trim_end(), "- '{not: yaml'\n- a");
+}
+
+#[test]
+fn test_clipboard_register_is_selectable() {
+    let mut h = Harness::new("a: 1\n").unwrap();
+    h.keys("\"+").unwrap();
+    assert_eq!(h.state().get_pending_register(), Some('+'));
+}
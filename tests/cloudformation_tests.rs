@@ -0,0 +1,67 @@
+//! Tests for CloudFormation mode (`:set cloudformation`).
+
+use yamlquill::harness::Harness;
+
+const TEMPLATE: &str = "\
+Parameters:
+  Env:
+    Type: String
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+  BucketPolicy:
+    Type: AWS::S3::BucketPolicy
+    Properties:
+      Bucket: !Ref Bucket
+      Arn: !GetAtt Bucket.Arn
+      Name:
+        Fn::Join: ['-', [logs, x]]
+";
+
+fn intrinsic_lines(h: &Harness) -> Vec<String> {
+    h.state()
+        .tree_view()
+        .lines()
+        .iter()
+        .filter(|line| line.intrinsic)
+        .filter_map(|line| line.key.clone())
+        .collect()
+}
+
+#[test]
+fn test_intrinsics_are_highlighted_when_enabled() {
+    let mut h = Harness::new(TEMPLATE).unwrap();
+    assert!(intrinsic_lines(&h).is_empty());
+
+    h.keys(":set cfn<CR>").unwrap();
+    assert_eq!(intrinsic_lines(&h), vec!["Bucket", "Arn", "Fn::Join"]);
+
+    h.keys(":set nocloudformation<CR>").unwrap();
+    assert!(intrinsic_lines(&h).is_empty());
+}
+
+#[test]
+fn test_tab_completes_logical_ids() {
+    let mut h = Harness::new(TEMPLATE).unwrap();
+    h.keys(":set cloudformation<CR>:path $.Resources.BucketPolicy.Properties.Bucket<CR>")
+        .unwrap();
+    h.keys("e<BS><BS><BS><BS><Tab>").unwrap();
+    assert_eq!(h.state().edit_buffer(), Some("Bucket"));
+    h.keys("<Tab>").unwrap();
+    assert_eq!(h.state().edit_buffer(), Some("BucketPolicy"));
+    h.keys("<CR>").unwrap();
+    assert!(h
+        .document()
+        .unwrap()
+        .contains("      Bucket: !Ref BucketPolicy\n"));
+
+    // Parameters and pseudo parameters are valid !Ref targets too
+    h.keys("e<C-a><C-k>AWS::R<Tab>").unwrap();
+    assert_eq!(h.state().edit_buffer(), Some("AWS::Region"));
+    h.keys("<Esc>").unwrap();
+
+    // !GetAtt keeps the attribute
+    h.keys("je<C-a><C-d><C-d><C-d><C-d><C-d><C-d>BucketP<Tab>")
+        .unwrap();
+    assert_eq!(h.state().edit_buffer(), Some("BucketPolicy.Arn"));
+}
@@ -45,7 +45,7 @@ name: Alice
     let name_line = tree_view
         .lines()
         .iter()
-        .find(|line| line.key.as_ref().map_or(false, |k| k == "name"))
+        .find(|line| line.key.as_ref().is_some_and(|k| k == "name"))
         .expect("Should find name line");
     assert!(name_line.value_preview.contains("Alice"));
 }
@@ -84,11 +84,11 @@ age: 30
     assert!(tree_view
         .lines()
         .iter()
-        .any(|line| line.key.as_ref().map_or(false, |k| k == "name")));
+        .any(|line| line.key.as_ref().is_some_and(|k| k == "name")));
     assert!(tree_view
         .lines()
         .iter()
-        .any(|line| line.key.as_ref().map_or(false, |k| k == "age")));
+        .any(|line| line.key.as_ref().is_some_and(|k| k == "age")));
 }
 
 /// Test 3: Line (inline) comment renders inline with value
@@ -148,6 +148,7 @@ name: Alice
                 false,
                 false,
                 0,
+                0,
                 &[],
             );
         })
@@ -204,6 +205,7 @@ name: Alice
                 false,
                 false,
                 0,
+                0,
                 &[],
             );
         })
@@ -376,9 +376,9 @@ fn count_tree_nodes(state: &EditorState) -> usize {
 /// Recursively count nodes in a YamlNode tree
 fn count_yaml_nodes(node: &YamlNode) -> usize {
     match node.value() {
-        YamlValue::Object(map) => 1 + map.values().map(|v| count_yaml_nodes(v)).sum::<usize>(),
-        YamlValue::Array(arr) => 1 + arr.iter().map(|v| count_yaml_nodes(v)).sum::<usize>(),
-        YamlValue::MultiDoc(docs) => 1 + docs.iter().map(|v| count_yaml_nodes(v)).sum::<usize>(),
+        YamlValue::Object(map) => 1 + map.values().map(count_yaml_nodes).sum::<usize>(),
+        YamlValue::Array(arr) => 1 + arr.iter().map(count_yaml_nodes).sum::<usize>(),
+        YamlValue::MultiDoc(docs) => 1 + docs.iter().map(count_yaml_nodes).sum::<usize>(),
         _ => 1,
     }
 }
@@ -392,9 +392,9 @@ fn tree_contains_comment(state: &EditorState) -> bool {
 fn contains_comment_recursive(node: &YamlNode) -> bool {
     match node.value() {
         YamlValue::Comment(_) => true,
-        YamlValue::Object(map) => map.values().any(|v| contains_comment_recursive(v)),
-        YamlValue::Array(arr) => arr.iter().any(|v| contains_comment_recursive(v)),
-        YamlValue::MultiDoc(docs) => docs.iter().any(|v| contains_comment_recursive(v)),
+        YamlValue::Object(map) => map.values().any(contains_comment_recursive),
+        YamlValue::Array(arr) => arr.iter().any(contains_comment_recursive),
+        YamlValue::MultiDoc(docs) => docs.iter().any(contains_comment_recursive),
         _ => false,
     }
 }
@@ -91,14 +91,11 @@ age: 30
             let mut has_standalone = false;
             for (key, val) in map.iter() {
                 if key.starts_with("__comment_") {
-                    match val.value() {
-                        YamlValue::Comment(comment) => {
-                            if comment.position() == &CommentPosition::Standalone {
-                                has_standalone = true;
-                                assert_eq!(comment.content(), "This is a standalone comment");
-                            }
+                    if let YamlValue::Comment(comment) = val.value() {
+                        if comment.position() == &CommentPosition::Standalone {
+                            has_standalone = true;
+                            assert_eq!(comment.content(), "This is a standalone comment");
                         }
-                        _ => {}
                     }
                 }
             }
@@ -131,17 +128,14 @@ items:
                     let mut has_inline_comment = false;
 
                     for elem in elements {
-                        match elem.value() {
-                            YamlValue::Comment(comment) => {
-                                if comment.position() == &CommentPosition::Above {
-                                    has_above_comment = true;
-                                    assert_eq!(comment.content(), "Comment above first item");
-                                } else if comment.position() == &CommentPosition::Line {
-                                    has_inline_comment = true;
-                                    assert_eq!(comment.content(), "inline comment on banana");
-                                }
+                        if let YamlValue::Comment(comment) = elem.value() {
+                            if comment.position() == &CommentPosition::Above {
+                                has_above_comment = true;
+                                assert_eq!(comment.content(), "Comment above first item");
+                            } else if comment.position() == &CommentPosition::Line {
+                                has_inline_comment = true;
+                                assert_eq!(comment.content(), "inline comment on banana");
                             }
-                            _ => {}
                         }
                     }
 
@@ -209,16 +203,13 @@ normal: value  # real comment
 
             for (key, val) in map.iter() {
                 if key.starts_with("__comment_") {
-                    match val.value() {
-                        YamlValue::Comment(comment) => {
-                            let content = comment.content();
-                            if content.contains("Real comment") {
-                                found_real_comment = true;
-                            } else if content.contains("real comment") {
-                                found_inline_comment = true;
-                            }
+                    if let YamlValue::Comment(comment) = val.value() {
+                        let content = comment.content();
+                        if content.contains("Real comment") {
+                            found_real_comment = true;
+                        } else if content.contains("real comment") {
+                            found_inline_comment = true;
                         }
-                        _ => {}
                     }
                 }
             }
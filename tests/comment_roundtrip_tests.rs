@@ -86,14 +86,11 @@ age: 30
             let mut has_inline_comment = false;
             for (key, val) in map.iter() {
                 if key.starts_with("__comment_") {
-                    match val.value() {
-                        YamlValue::Comment(comment) => {
-                            if comment.position() == &CommentPosition::Line {
-                                has_inline_comment = true;
-                                assert_eq!(comment.content(), "inline comment");
-                            }
+                    if let YamlValue::Comment(comment) = val.value() {
+                        if comment.position() == &CommentPosition::Line {
+                            has_inline_comment = true;
+                            assert_eq!(comment.content(), "inline comment");
                         }
-                        _ => {}
                     }
                 }
             }
@@ -140,8 +137,8 @@ age: 30
             for (key, val) in map.iter() {
                 if key.starts_with("__comment_") {
                     comment_count += 1;
-                    match val.value() {
-                        YamlValue::Comment(comment) => match comment.position() {
+                    if let YamlValue::Comment(comment) = val.value() {
+                        match comment.position() {
                             CommentPosition::Above => {
                                 has_above = true;
                             }
@@ -149,8 +146,7 @@ age: 30
                                 has_inline = true;
                             }
                             _ => {}
-                        },
-                        _ => {}
+                        }
                     }
                 }
             }
@@ -48,9 +48,36 @@ fn test_custom_config() {
         undo_limit: 500,
         sync_unnamed_register: false,
         lazy_load_threshold: 52_428_800, // 50MB
+        max_file_size_mb: 1024,
+        max_parse_depth: 512,
+        max_node_count: 20_000_000,
+        max_alias_expansion: 1_000_000,
         enable_mouse: false,
         relative_line_numbers: false,
         preserve_formatting: true,
+        resolve_merge_keys: false,
+        persistent_undo: false,
+        reload_on_change: false,
+        regex_search: false,
+        search_collapsed: true,
+        show_breadcrumbs: false,
+        show_type_badges: false,
+        decode_secrets: false,
+        cloudformation: false,
+        k8s: false,
+        prometheus: false,
+        auto_validate: false,
+        scrolloff: 0,
+        max_render_depth: 64,
+        mouse_scroll_lines: 3,
+        memory_budget_mb: 2048,
+        yaml_style: "preserving".to_string(),
+        json_style: "preserving".to_string(),
+        undo_file_limit_mb: 64,
+        preview_fields: vec![],
+        statusline: String::new(),
+        lint_disabled: vec![],
+        lint_line_length: 120,
     };
 
     assert_eq!(config.theme, "gruvbox");
@@ -167,9 +194,36 @@ fn test_roundtrip_serialization() {
         undo_limit: 2000,
         sync_unnamed_register: false,
         lazy_load_threshold: 1_048_576, // 1MB
+        max_file_size_mb: 1024,
+        max_parse_depth: 512,
+        max_node_count: 20_000_000,
+        max_alias_expansion: 1_000_000,
         enable_mouse: false,
         relative_line_numbers: true,
         preserve_formatting: true,
+        resolve_merge_keys: false,
+        persistent_undo: false,
+        reload_on_change: false,
+        regex_search: false,
+        search_collapsed: true,
+        show_breadcrumbs: false,
+        show_type_badges: false,
+        decode_secrets: false,
+        cloudformation: false,
+        k8s: false,
+        prometheus: false,
+        auto_validate: false,
+        scrolloff: 0,
+        max_render_depth: 64,
+        mouse_scroll_lines: 3,
+        memory_budget_mb: 2048,
+        yaml_style: "preserving".to_string(),
+        json_style: "preserving".to_string(),
+        undo_file_limit_mb: 64,
+        preview_fields: vec![],
+        statusline: String::new(),
+        lint_disabled: vec![],
+        lint_line_length: 120,
     };
 
     // Serialize to TOML
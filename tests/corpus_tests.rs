@@ -0,0 +1,94 @@
+//! Corpus runner: every file in the corpus must save byte-identically.
+//!
+//! Runs with `cargo test --features corpus`. Files come from `tests/corpus/`,
+//! or from the directory in `YAMLQUILL_CORPUS` to try a collection of real
+//! files. Each is loaded, has an edit made and undone, and is saved; all
+//! files that don't come back unchanged are reported together.
+#![cfg(feature = "corpus")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use yamlquill::harness::Harness;
+
+fn corpus_dir() -> PathBuf {
+    match std::env::var_os("YAMLQUILL_CORPUS") {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus"),
+    }
+}
+
+/// Returns the YAML files under `dir`, recursively, in a stable order.
+fn yaml_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(yaml_files(&path));
+        } else if matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("yaml" | "yml")
+        ) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Returns why `path` doesn't round-trip, or None if it does.
+fn check(path: &Path) -> Option<String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => return Some(format!("unreadable: {}", e)),
+    };
+    let mut harness = match Harness::new(&text) {
+        Ok(harness) => harness,
+        // Not something the editor opens; nothing to preserve
+        Err(_) => return None,
+    };
+    if let Err(e) = harness.keys("je<End>x<CR><Esc>") {
+        return Some(format!("edit failed: {}", e));
+    }
+    if harness.state().is_dirty() && harness.keys("u").is_err() {
+        return Some("undo failed".to_string());
+    }
+    match harness.document() {
+        Ok(saved) if saved == text => None,
+        Ok(saved) => {
+            let line = text
+                .lines()
+                .zip(saved.lines())
+                .position(|(a, b)| a != b)
+                .unwrap_or_else(|| text.lines().count().min(saved.lines().count()));
+            Some(format!(
+                "differs from line {}:\n  original: {:?}\n  saved:    {:?}",
+                line + 1,
+                text.lines().nth(line).unwrap_or(""),
+                saved.lines().nth(line).unwrap_or("")
+            ))
+        }
+        Err(e) => Some(format!("save failed: {}", e)),
+    }
+}
+
+#[test]
+fn test_corpus_round_trips() {
+    let dir = corpus_dir();
+    let files = yaml_files(&dir);
+    assert!(!files.is_empty(), "no YAML files in {}", dir.display());
+
+    let failures: Vec<String> = files
+        .iter()
+        .filter_map(|path| check(path).map(|why| format!("{}: {}", path.display(), why)))
+        .collect();
+    assert!(
+        failures.is_empty(),
+        "{} of {} files don't round-trip:\n{}",
+        failures.len(),
+        files.len(),
+        failures.join("\n")
+    );
+}
@@ -0,0 +1,84 @@
+//! Tests for `:now`, `:time` and the `gt+`/`gt-`/`gt=` timestamp keys.
+
+use yamlquill::editor::datetime::DateTime;
+use yamlquill::harness::Harness;
+
+const YAML: &str = "\
+created: 2026-10-15T09:30:00+02:00
+expires: 2026-01-31
+epoch: 1760000000
+name: api
+";
+
+fn message(h: &Harness) -> Option<String> {
+    h.state().message().map(|m| m.text.clone())
+}
+
+#[test]
+fn test_shift_and_reformat() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys("gt+1d2h<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Time set to 2026-10-16T11:30:00+02:00")
+    );
+    h.keys("gt=utc<CR>").unwrap();
+    h.keys(":path $.expires<CR>gt+1mo<CR>").unwrap();
+    h.keys(":path $.epoch<CR>gt-1h<CR>").unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "created: 2026-10-16T09:30:00Z\nexpires: 2026-02-28\nepoch: 1759996400\nname: api\n"
+    );
+    h.keys(":time iso<CR>").unwrap();
+    assert!(h
+        .document()
+        .unwrap()
+        .contains("epoch: 2025-10-09T07:53:20Z\n"));
+
+    h.keys("u").unwrap();
+    assert!(h.document().unwrap().contains("epoch: 1759996400\n"));
+}
+
+#[test]
+fn test_time_errors() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys(":path $.expires<CR>:time +3h<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Invalid shift: a date has no time of day to move by 'h' (use y, mo, w or d)")
+    );
+    h.keys(":time weekly<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Unknown format 'weekly' (use iso, utc, date or epoch, or a shift like +1d)")
+    );
+    h.keys(":path $.name<CR>:time +1d<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Not a timestamp"));
+    assert_eq!(h.document().unwrap(), YAML);
+}
+
+#[test]
+fn test_now() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys(":path $.name<CR>:now<CR>").unwrap();
+    let value = message(&h).unwrap();
+    let value = value.strip_prefix("Time set to ").unwrap();
+    assert!(value.ends_with('Z'), "{}", value);
+    assert!(DateTime::parse(value).is_ok());
+
+    h.keys(":now date<CR>").unwrap();
+    let document = h.document().unwrap();
+    let date = document
+        .lines()
+        .last()
+        .unwrap()
+        .strip_prefix("name: ")
+        .unwrap();
+    assert!(DateTime::parse(date).unwrap().date_only);
+
+    h.keys(":now soon<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Usage: :now [iso|utc|date|epoch]")
+    );
+}
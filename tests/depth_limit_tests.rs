@@ -0,0 +1,62 @@
+//! Tests for the render depth limit (`max_render_depth`, `:set maxdepth`)
+//! and expanding past it with `zd`.
+
+use yamlquill::harness::Harness;
+
+const DEEP: &str = "\
+a:
+  b:
+    c:
+      d:
+        e: 1
+top: 2
+";
+
+fn keys(h: &Harness) -> Vec<String> {
+    h.state()
+        .tree_view()
+        .lines()
+        .iter()
+        .map(|line| line.key.clone().unwrap_or_default())
+        .collect()
+}
+
+#[test]
+fn test_subtrees_past_the_limit_are_summarized() {
+    let mut h = Harness::new(DEEP).unwrap().with_size(80, 10);
+    assert_eq!(keys(&h), ["a", "b", "c", "d", "e", "top"]);
+
+    h.keys(":set maxdepth=2<CR>").unwrap();
+    assert_eq!(keys(&h), ["a", "b", "top"]);
+    let b = &h.state().tree_view().lines()[1];
+    assert!(b.too_deep);
+    assert!(!b.expanded);
+    assert!(h
+        .screen()
+        .unwrap()
+        .contains("depth limit (zd expands deeper)"));
+
+    // The limit counts again from the node expanded deeper
+    h.keys("jzd").unwrap();
+    assert_eq!(keys(&h), ["a", "b", "c", "d", "top"]);
+    assert!(h.state().tree_view().lines()[3].too_deep);
+
+    // Expanding a cut-off node goes deeper too
+    h.keys("jjl").unwrap();
+    assert_eq!(keys(&h), ["a", "b", "c", "d", "e", "top"]);
+
+    h.keys(":set maxdepth=0<CR>").unwrap();
+    assert!(h.state().tree_view().lines().iter().all(|l| !l.too_deep));
+}
+
+#[test]
+fn test_zd_away_from_the_limit() {
+    let mut h = Harness::new(DEEP).unwrap();
+    h.keys("zd").unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "Not at the depth limit (:set maxdepth=64)"
+    );
+    h.keys(":set maxdepth?<CR>").unwrap();
+    assert_eq!(h.state().message().unwrap().text, "maxdepth=64");
+}
@@ -0,0 +1,107 @@
+//! Tests for keys written twice: kept on load, flagged, written back as
+//! they were, and resolved with `:dedupe`.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::harness::Harness;
+
+const YAML: &str = "\
+name: api
+replicas: 2
+env:
+  LEVEL: info
+  LEVEL: debug
+name: web
+";
+
+#[test]
+fn test_both_entries_are_kept_and_flagged() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("dup.yaml");
+    fs::write(&path, YAML).unwrap();
+
+    let mut h = Harness::open(&path).unwrap().with_size(100, 20);
+    let screen = h.screen().unwrap();
+    assert!(screen.contains("name: api"), "{}", screen);
+    assert!(screen.contains("name: web"), "{}", screen);
+    assert!(screen.contains("LEVEL: debug"), "{}", screen);
+
+    let diagnostics = h.state().diagnostics().items();
+    let paths: Vec<_> = diagnostics.iter().map(|d| d.path.clone()).collect();
+    assert_eq!(paths, [vec![2, 1], vec![3]]);
+    assert_eq!(
+        diagnostics[1].message,
+        "Key `name` is written 2 times; other YAML readers keep only the last (:dedupe)"
+    );
+
+    // Nothing is lost on save, edited or not
+    assert_eq!(h.document().unwrap(), YAML);
+    h.keys("je<End>3<CR>:w<CR>").unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    assert_eq!(saved.matches("name:").count(), 2, "{}", saved);
+    assert_eq!(saved.matches("LEVEL:").count(), 2, "{}", saved);
+    assert!(saved.contains("replicas: 23"), "{}", saved);
+}
+
+#[test]
+fn test_dedupe_resolves_each_key() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys(":dedupe<CR>").unwrap();
+    assert!(h.state().dedupe_pending());
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "Duplicate key $[\"name\"] (2 entries): keep (f)irst, (l)ast, (r)ename the others, (s)kip, (q)uit"
+    );
+    assert_eq!(h.state().cursor().path(), [3]);
+
+    // Keep the first name, then the last LEVEL
+    h.keys("f").unwrap();
+    assert!(h
+        .state()
+        .message()
+        .unwrap()
+        .text
+        .starts_with("Duplicate key $[\"env\"][\"LEVEL\"]"));
+    assert_eq!(h.state().cursor().path(), [2, 1]);
+    h.keys("l").unwrap();
+    assert!(!h.state().dedupe_pending());
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "Resolved 2 duplicate key(s)"
+    );
+    assert_eq!(
+        h.document().unwrap(),
+        "name: api\nreplicas: 2\nenv:\n  LEVEL: debug"
+    );
+    assert!(h.state().diagnostics().is_empty());
+
+    // Each resolution is its own undo step
+    h.keys("u").unwrap();
+    assert_eq!(h.state().diagnostics().items().len(), 1);
+    h.keys(":dedupe<CR>r").unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "name: api\nreplicas: 2\nenv:\n  LEVEL: info\n  LEVEL_2: debug"
+    );
+}
+
+#[test]
+fn test_dedupe_skip_and_quit() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys(":dedupe<CR>sl").unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "Resolved 1 duplicate key(s), skipped 1"
+    );
+    assert_eq!(h.state().diagnostics().items().len(), 1);
+
+    h.keys(":dedupe<CR><Esc>").unwrap();
+    assert!(!h.state().dedupe_pending());
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "Resolved 0 duplicate key(s), 1 left"
+    );
+
+    h.keys(":dedupe<CR>f:dedupe<CR>").unwrap();
+    assert_eq!(h.state().message().unwrap().text, "No duplicate keys");
+}
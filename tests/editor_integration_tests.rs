@@ -150,7 +150,7 @@ fn test_undo_after_edit_float() {
     let mut obj = IndexMap::new();
     obj.insert(
         "value".to_string(),
-        YamlNode::new(YamlValue::Number(YamlNumber::Float(3.14))),
+        YamlNode::new(YamlValue::Number(YamlNumber::Float(2.75))),
     );
     let tree = YamlTree::new(YamlNode::new(YamlValue::Object(obj)));
     let mut state = create_test_editor(tree);
@@ -176,7 +176,7 @@ fn test_undo_after_edit_float() {
     // Verify original value restored
     let restored_node = state.tree().get_node(&[0]).unwrap();
     if let YamlValue::Number(YamlNumber::Float(restored)) = restored_node.value() {
-        assert!((restored - 3.14).abs() < 0.001);
+        assert!((restored - 2.75).abs() < 0.001);
     } else {
         panic!("Expected Float after undo");
     }
@@ -386,7 +386,7 @@ fn test_undo_at_root_returns_false() {
     assert!(!undo_success, "Undo at root should return false");
 
     // Editor should still be in valid state
-    assert_eq!(state.tree().get_node(&[0]).is_some(), true);
+    assert!(state.tree().get_node(&[0]).is_some());
 }
 
 #[test]
@@ -503,7 +503,7 @@ fn test_register_preserves_float() {
     let mut obj = IndexMap::new();
     obj.insert(
         "float_value".to_string(),
-        YamlNode::new(YamlValue::Number(YamlNumber::Float(3.14))),
+        YamlNode::new(YamlValue::Number(YamlNumber::Float(2.75))),
     );
     obj.insert(
         "other".to_string(),
@@ -524,7 +524,7 @@ fn test_register_preserves_float() {
     let pasted_node = state.tree().get_node(&[2]).unwrap();
     match pasted_node.value() {
         YamlValue::Number(YamlNumber::Float(f)) => {
-            assert!((f - 3.14).abs() < 0.001);
+            assert!((f - 2.75).abs() < 0.001);
         }
         _ => panic!("Pasted node should be Float"),
     }
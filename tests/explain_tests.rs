@@ -0,0 +1,77 @@
+//! Tests for explaining the value under the cursor (`K`).
+
+use yamlquill::harness::Harness;
+
+#[test]
+fn test_k_explains_cron_schedule() {
+    let mut h = Harness::new(
+        "apiVersion: batch/v1\nkind: CronJob\nspec:\n  schedule: \"30 2 * * MON-FRI\"\n",
+    )
+    .unwrap();
+    h.keys(":path $.spec.schedule<CR>K").unwrap();
+    let explanation = h.state().explanation().unwrap();
+    assert_eq!(explanation.title, "Cron schedule 30 2 * * MON-FRI");
+    assert_eq!(
+        explanation.lines[0],
+        "At 02:30 on every day-of-week from Monday through Friday."
+    );
+    assert_eq!(explanation.lines[2], "Next runs:");
+    assert_eq!(explanation.lines.len(), 6);
+
+    // Any key closes the popup without acting on it
+    h.keys("j").unwrap();
+    assert!(h.state().explanation().is_none());
+    assert_eq!(h.state().cursor().path(), &[2, 0]);
+}
+
+#[test]
+fn test_k_on_other_values() {
+    let mut h = Harness::new("name: app\nschedule: \"61 * * * *\"\n").unwrap();
+    h.keys("K").unwrap();
+    assert!(h.state().explanation().is_none());
+    assert_eq!(
+        h.state().message().map(|m| m.text.clone()).as_deref(),
+        Some("Nothing to explain here")
+    );
+
+    h.keys("jK").unwrap();
+    assert_eq!(
+        h.state().message().map(|m| m.text.clone()).as_deref(),
+        Some("Not a valid cron schedule: minute 61 is outside 0-59")
+    );
+}
+
+#[test]
+fn test_k_explains_cidr_against_siblings() {
+    let mut h =
+        Harness::new("networking:\n  podSubnet: 10.0.0.0/16\n  serviceSubnet: 10.0.128.0/20\n")
+            .unwrap();
+    h.keys(":path $.networking.podSubnet<CR>K").unwrap();
+    let explanation = h.state().explanation().unwrap();
+    assert_eq!(explanation.title, "CIDR range 10.0.0.0/16");
+    assert!(explanation
+        .lines
+        .contains(&"Addresses: 65,536 (65,534 usable hosts)".to_string()));
+    assert_eq!(
+        explanation.lines.last().unwrap(),
+        "Overlaps serviceSubnet (10.0.128.0/20)"
+    );
+
+    // The later range carries the diagnostic
+    let diagnostics = h.state().diagnostics().items();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].path, vec![0, 1]);
+}
+
+#[test]
+fn test_k_explains_port_and_flags_out_of_range() {
+    let mut h = Harness::new("ports:\n- containerPort: 6379\n- containerPort: 65536\n").unwrap();
+    h.keys(":path $.ports[0].containerPort<CR>K").unwrap();
+    let explanation = h.state().explanation().unwrap();
+    assert_eq!(explanation.title, "Port 6379");
+    assert_eq!(explanation.lines, ["Registered port", "Usually Redis"]);
+
+    let diagnostics = h.state().diagnostics().items();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "Port 65536 is outside 0-65535");
+}
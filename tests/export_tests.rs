@@ -0,0 +1,181 @@
+//! Integration tests for printable exports.
+
+use tempfile::TempDir;
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::harness::Harness;
+
+fn state(yaml: &str) -> EditorState {
+    let tree = YamlTree::with_source(parse_yaml_auto(yaml).unwrap(), Some(yaml.to_string()));
+    let mut state = EditorState::new_with_default_theme(tree);
+    state.set_filename("deploy.yaml".to_string());
+    state
+}
+
+#[test]
+fn test_export_html_writes_highlighted_page() {
+    let dir = TempDir::new().unwrap();
+    let out = dir.path().join("out.html");
+    let state = state("name: web\nreplicas: 2\n");
+
+    state.export_html(out.to_str().unwrap()).unwrap();
+
+    let html = std::fs::read_to_string(&out).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>deploy.yaml</title>"));
+    assert!(html.contains("replicas: "));
+    assert!(html.contains("<span style=\"color: #"));
+}
+
+#[test]
+fn test_export_html_follows_current_folds() {
+    let dir = TempDir::new().unwrap();
+    let out = dir.path().join("out.html");
+    let mut state = state("spec:\n  replicas: 2\n  image: nginx\n");
+    state.tree_view_mut().toggle_expand(&[0]);
+    state.rebuild_tree_view();
+
+    state.export_html(out.to_str().unwrap()).unwrap();
+
+    let html = std::fs::read_to_string(&out).unwrap();
+    assert!(!html.contains("image: </span>"));
+}
+
+#[test]
+fn test_export_html_bad_path_is_an_error() {
+    let state = state("a: 1\n");
+    assert!(state.export_html("/nonexistent/dir/out.html").is_err());
+}
+
+#[test]
+fn test_export_outline_writes_markdown() {
+    use yamlquill::export::outline::OutlineOptions;
+
+    let dir = TempDir::new().unwrap();
+    let out = dir.path().join("outline.md");
+    let state = state("name: web\nspec:\n  replicas: 2\n  template:\n    image: nginx\n");
+    let options = OutlineOptions::from_args(["depth=2", "values"]).unwrap();
+
+    state
+        .export_outline(out.to_str().unwrap(), &options)
+        .unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&out).unwrap(),
+        "- `name`: `web`\n- `spec`\n  - `replicas`: `2`\n  - `template`\n"
+    );
+}
+
+#[test]
+fn test_write_infers_format_from_extension() {
+    let dir = TempDir::new().unwrap();
+    let json = dir.path().join("out.json");
+    let toml = dir.path().join("out.toml");
+    let mut harness = Harness::from_state(state(
+        "# app\nname: web\nports:\n  - 80\n  - 443\nlimits: {cpu: 2}\n",
+    ));
+
+    harness.keys(&format!(":w {}<CR>", json.display())).unwrap();
+    assert_eq!(
+        harness.state().message().unwrap().text,
+        format!("\"{}\" written as JSON", json.display())
+    );
+    assert_eq!(
+        std::fs::read_to_string(&json).unwrap(),
+        "{\n  \"name\": \"web\",\n  \"ports\": [\n    80,\n    443\n  ],\n  \"limits\": {\n    \"cpu\": 2\n  }\n}\n"
+    );
+
+    harness.keys(&format!(":w {}<CR>", toml.display())).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&toml).unwrap(),
+        "name = \"web\"\nports = [80, 443]\n\n[limits]\ncpu = 2\n"
+    );
+}
+
+#[test]
+fn test_write_unrepresentable_value_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    let toml = dir.path().join("out.toml");
+    let mut harness = Harness::from_state(state("a: 1\nb: null\n"));
+
+    harness.keys(&format!(":w {}<CR>", toml.display())).unwrap();
+    let message = harness.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert!(message.text.ends_with("null at .b has no TOML equivalent"));
+    assert!(!toml.exists());
+    assert_eq!(harness.state().filename(), Some("deploy.yaml"));
+}
+
+#[test]
+fn test_json_and_toml_files_are_written_back_in_their_format() {
+    let dir = TempDir::new().unwrap();
+    let json = dir.path().join("package.json");
+    std::fs::write(&json, "{\"name\": \"web\", \"version\": \"1.0.0\"}\n").unwrap();
+    let mut harness = Harness::open(&json).unwrap();
+    harness.keys("je<End><BS>1<CR><Esc>:w<CR>").unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&json).unwrap(),
+        "{\n  \"name\": \"web\",\n  \"version\": \"1.0.1\"\n}\n"
+    );
+
+    // Without an extension the file keeps the format it has on disk
+    let pipfile = dir.path().join("Pipfile");
+    std::fs::write(&pipfile, "[packages]\nflask = \"*\"\n").unwrap();
+    let mut harness = Harness::open(&pipfile).unwrap();
+    harness.keys("jjdd:w<CR>").unwrap();
+    assert_eq!(
+        harness.state().message().unwrap().text,
+        format!("\"{}\" written as TOML", pipfile.display())
+    );
+    assert_eq!(std::fs::read_to_string(&pipfile).unwrap(), "[packages]\n");
+
+    // An explicit name overrides it
+    let yaml = dir.path().join("Pipfile.yaml");
+    harness.keys(&format!(":w {}<CR>", yaml.display())).unwrap();
+    assert_eq!(std::fs::read_to_string(&yaml).unwrap(), "packages: {}");
+}
+
+#[test]
+fn test_write_style_overrides_the_configured_style() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("app.yaml");
+    let source = "# app\nname: web   # service\nports: [80, 443]\n";
+    std::fs::write(&file, source).unwrap();
+    let mut harness = Harness::open(&file).unwrap();
+
+    harness.keys(":w --style compact<CR>").unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&file).unwrap(),
+        "{name: web, ports: [80, 443]}\n"
+    );
+
+    let canonical = dir.path().join("canonical.yaml");
+    harness
+        .keys(&format!(":w --style=canonical {}<CR>", canonical.display()))
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&canonical).unwrap(),
+        "name: web\nports:\n- 80\n- 443\n"
+    );
+    assert_eq!(
+        harness.state().filename(),
+        Some(canonical.to_str().unwrap())
+    );
+
+    harness.keys(":w --style pretty<CR>").unwrap();
+    let message = harness.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert!(message.text.starts_with("Unknown style 'pretty'"));
+
+    // Per format, from the config
+    let json = dir.path().join("app.json");
+    let mut config = harness.state().to_config();
+    config.json_style = "compact".to_string();
+    harness.state_mut().apply_config(&config);
+    harness.keys(&format!(":w {}<CR>", json.display())).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&json).unwrap(),
+        "{\"name\":\"web\",\"ports\":[80,443]}\n"
+    );
+}
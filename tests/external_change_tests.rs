@@ -0,0 +1,241 @@
+//! Integration tests for noticing changes made to the file by other programs.
+
+use std::fs::{self, File};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+use termion::event::{Event, Key};
+use yamlquill::document::node::YamlValue;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::file::loader::load_yaml_file;
+use yamlquill::input::InputHandler;
+
+fn open(path: &Path) -> EditorState {
+    let mut state = EditorState::new_with_default_theme(load_yaml_file(path).unwrap());
+    state.set_filename(path.display().to_string());
+    state
+}
+
+/// Rewrites the file with a modification time clearly later than the original.
+fn change_on_disk(path: &Path, contents: &str) {
+    fs::write(path, contents).unwrap();
+    let later = SystemTime::now() + Duration::from_secs(10);
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(later)
+        .unwrap();
+}
+
+fn first_value(state: &EditorState) -> String {
+    match state.tree().get_node(&[0]).unwrap().value() {
+        YamlValue::Object(_) | YamlValue::Array(_) => "container".to_string(),
+        value => value.to_string(),
+    }
+}
+
+#[test]
+fn test_prompt_and_reload() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+    let mut state = open(&path);
+
+    assert!(!state.check_external_change());
+
+    change_on_disk(&path, "replicas: 5\n");
+    assert!(state.check_external_change());
+    assert!(state.external_change_pending());
+    assert_eq!(state.message().unwrap().level, MessageLevel::Warning);
+
+    // Other keys are swallowed until the prompt is answered
+    let mut handler = InputHandler::new();
+    handler
+        .handle_event(Event::Key(Key::Char('j')), &mut state)
+        .unwrap();
+    assert!(state.external_change_pending());
+
+    handler
+        .handle_event(Event::Key(Key::Char('r')), &mut state)
+        .unwrap();
+    assert!(!state.external_change_pending());
+    assert_eq!(first_value(&state), "5");
+    assert!(!state.check_external_change());
+}
+
+#[test]
+fn test_conflict_warning_and_ignore() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+    let mut state = open(&path);
+    state.mark_dirty();
+
+    change_on_disk(&path, "replicas: 5\n");
+    assert!(state.check_external_change());
+    let message = state.message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert!(message.text.contains("unsaved changes"));
+
+    let mut handler = InputHandler::new();
+    handler
+        .handle_event(Event::Key(Key::Char('i')), &mut state)
+        .unwrap();
+    assert!(!state.external_change_pending());
+    assert!(state.is_dirty());
+    assert_eq!(first_value(&state), "1");
+
+    // The ignored version is not reported again
+    assert!(!state.check_external_change());
+}
+
+#[test]
+fn test_reload_on_change_reloads_clean_buffer() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+    let mut state = open(&path);
+    state.set_reload_on_change(true);
+
+    change_on_disk(&path, "replicas: 5\n");
+    assert!(state.check_external_change());
+    assert!(!state.external_change_pending());
+    assert_eq!(first_value(&state), "5");
+}
+
+#[test]
+fn test_own_save_is_not_an_external_change() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+    let mut state = open(&path);
+    state.mark_dirty();
+
+    let mut handler = InputHandler::new();
+    for c in ":w\n".chars() {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), &mut state)
+            .unwrap();
+    }
+    assert!(!state.is_dirty());
+    assert!(!state.check_external_change());
+}
+
+fn command(handler: &mut InputHandler, state: &mut EditorState, keys: &str) -> bool {
+    let mut quit = false;
+    for c in keys.chars() {
+        quit = handler
+            .handle_event(Event::Key(Key::Char(c)), state)
+            .unwrap();
+    }
+    quit
+}
+
+/// Changes the first value in the buffer, as an edit would.
+fn edit_first_value(handler: &mut InputHandler, state: &mut EditorState, value: &str) {
+    handler
+        .handle_event(Event::Key(Key::Char('e')), state)
+        .unwrap();
+    handler
+        .handle_event(Event::Key(Key::Backspace), state)
+        .unwrap();
+    command(handler, state, &format!("{}\n", value));
+}
+
+#[test]
+fn test_save_over_newer_file_is_blocked() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+    let mut state = open(&path);
+    let mut handler = InputHandler::new();
+    edit_first_value(&mut handler, &mut state, "2");
+
+    change_on_disk(&path, "replicas: 5\n");
+    assert!(!command(&mut handler, &mut state, ":wq\n"));
+    assert_eq!(state.save_conflict(), Some("wq"));
+    assert!(state.message().unwrap().text.contains("(o)verwrite"));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "replicas: 5\n");
+
+    // Cancelling leaves both versions alone
+    command(&mut handler, &mut state, "c");
+    assert!(state.save_conflict().is_none());
+    assert!(state.is_dirty());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "replicas: 5\n");
+
+    // Overwriting runs the blocked command
+    command(&mut handler, &mut state, ":wq\n");
+    assert!(command(&mut handler, &mut state, "o"));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "replicas: 2\n");
+}
+
+#[test]
+fn test_save_conflict_reload_and_diff() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+    let mut state = open(&path);
+    let mut handler = InputHandler::new();
+    edit_first_value(&mut handler, &mut state, "2");
+
+    change_on_disk(&path, "replicas: 5\n");
+    command(&mut handler, &mut state, ":w\nr");
+    assert!(state.save_conflict().is_none());
+    assert!(!state.is_dirty());
+    assert_eq!(first_value(&state), "5");
+
+    edit_first_value(&mut handler, &mut state, "6");
+    change_on_disk(&path, "replicas: 7\n");
+    command(&mut handler, &mut state, ":w\nd");
+    let review = state.review().unwrap();
+    assert_eq!(review.changes().len(), 1);
+    assert_eq!(review.label(), "disk");
+
+    // The reviewed version is the new baseline, so the merge result saves
+    command(&mut handler, &mut state, "yq:w\n");
+    assert!(state.save_conflict().is_none());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "replicas: 6\n");
+}
+
+#[test]
+fn test_force_write_and_touched_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+    let mut state = open(&path);
+    let mut handler = InputHandler::new();
+    edit_first_value(&mut handler, &mut state, "2");
+
+    // Only the modification time changed
+    change_on_disk(&path, "replicas: 1\n");
+    assert!(!state.changed_on_disk());
+    command(&mut handler, &mut state, ":w\n");
+    assert!(state.save_conflict().is_none());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "replicas: 2\n");
+
+    edit_first_value(&mut handler, &mut state, "3");
+    change_on_disk(&path, "replicas: 5\n");
+    assert!(state.changed_on_disk());
+    command(&mut handler, &mut state, ":w!\n");
+    assert!(state.save_conflict().is_none());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "replicas: 3\n");
+}
+
+#[test]
+fn test_same_size_rewrite_with_same_time_is_a_change() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+    let state = open(&path);
+    let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+    fs::write(&path, "replicas: 5\n").unwrap();
+    File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+    assert!(state.changed_on_disk());
+}
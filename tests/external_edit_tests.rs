@@ -0,0 +1,97 @@
+//! Integration tests for editing a subtree in `$EDITOR` (`ge`, `:edit-external`).
+//!
+//! The event loop runs the editor; these tests stand in for it by handing
+//! the edited text straight to `finish_external_edit`.
+
+use std::fs;
+use tempfile::TempDir;
+use termion::event::{Event, Key};
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::external::ExternalEdit;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::input::InputHandler;
+
+fn state_for(yaml: &str) -> EditorState {
+    EditorState::new_with_default_theme(YamlTree::new(parse_yaml_auto(yaml).unwrap()))
+}
+
+fn keys(handler: &mut InputHandler, state: &mut EditorState, keys: &str) {
+    for c in keys.chars() {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), state)
+            .unwrap();
+    }
+}
+
+/// Returns an edit as the editor would leave it, in a real temporary file.
+fn edited(dir: &TempDir, text: &str) -> ExternalEdit {
+    let path = dir.path().join("edit.yaml");
+    fs::write(&path, text).unwrap();
+    ExternalEdit {
+        path,
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn test_edit_subtree() {
+    let dir = TempDir::new().unwrap();
+    let mut state = state_for("name: web\nports:\n  - 80\n");
+    let mut handler = InputHandler::new();
+
+    keys(&mut handler, &mut state, "jge");
+    let text = state.take_pending_external_edit().unwrap();
+    assert_eq!(text, "- 80\n");
+    assert!(state.take_pending_external_edit().is_none());
+
+    let edit = edited(&dir, "- 8080\n- 443\n");
+    let path = edit.path.clone();
+    state.finish_external_edit(&text, Ok(edit));
+    assert_eq!(state.message().unwrap().text, "Subtree updated");
+    assert!(!path.exists());
+    assert!(state.is_dirty());
+    assert_eq!(
+        state.tree().get_node(&[1, 1]).unwrap().value().to_string(),
+        "443"
+    );
+
+    // The whole replacement is one undo step
+    assert!(state.undo());
+    assert!(state.tree().get_node(&[1, 1]).is_none());
+}
+
+#[test]
+fn test_invalid_or_unchanged_text() {
+    let dir = TempDir::new().unwrap();
+    let mut state = state_for("a:\n  b: 1\n");
+    let mut handler = InputHandler::new();
+    keys(&mut handler, &mut state, ":edit-external\n");
+    let text = state.take_pending_external_edit().unwrap();
+    assert_eq!(text, "b: 1\n");
+
+    // Invalid YAML leaves the tree alone and keeps the file
+    let edit = edited(&dir, "b: [1\n");
+    let path = edit.path.clone();
+    state.finish_external_edit(&text, Ok(edit));
+    assert_eq!(state.message().unwrap().level, MessageLevel::Error);
+    assert!(state.message().unwrap().text.contains("kept in"));
+    assert!(path.exists());
+    assert!(!state.is_dirty());
+
+    state.finish_external_edit(&text, Ok(edited(&dir, "b: 1\n")));
+    assert_eq!(state.message().unwrap().text, "No changes");
+    assert!(!path.exists());
+
+    state.finish_external_edit(&text, Err(anyhow::anyhow!("vi exited with 1")));
+    assert_eq!(state.message().unwrap().text, "vi exited with 1");
+}
+
+#[test]
+fn test_aliases_are_not_edited() {
+    let mut state = state_for("base: &b 1\ncopy: *b\n");
+    let mut handler = InputHandler::new();
+    keys(&mut handler, &mut state, "jge");
+    assert!(state.take_pending_external_edit().is_none());
+    assert_eq!(state.message().unwrap().level, MessageLevel::Error);
+}
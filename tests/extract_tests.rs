@@ -0,0 +1,130 @@
+//! Tests for `:extract`, defining a repeated value once behind an anchor.
+
+use yamlquill::harness::Harness;
+
+const SERVICE: &str = "\
+web:
+  port: 8080
+  env:
+    PORT: '8080'
+probe:
+  port: 8080
+ports:
+- 8080
+";
+
+fn message(h: &Harness) -> Option<String> {
+    h.state().message().map(|m| m.text.clone())
+}
+
+fn rows(h: &Harness) -> Vec<(String, String)> {
+    h.state()
+        .usages_panel()
+        .map(|panel| {
+            panel
+                .usages
+                .iter()
+                .map(|u| (u.location.clone(), u.preview.clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_extract_anchors_first_use_and_aliases_the_rest() {
+    let mut h = Harness::new(SERVICE).unwrap();
+    h.keys(":path $.probe.port<CR>:extract port<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Extract 8080 as &port: 2 uses become *port (Enter applies)")
+    );
+    // The string '8080' is a different value and is left alone
+    assert_eq!(
+        rows(&h),
+        vec![
+            (".web.port".to_string(), "&port 8080".to_string()),
+            (".probe.port".to_string(), "*port".to_string()),
+            (".ports[0]".to_string(), "*port".to_string()),
+        ]
+    );
+    // Nothing changes until the preview is accepted
+    assert_eq!(h.document().unwrap(), SERVICE);
+
+    h.keys("<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Extracted 8080 as &port (2 aliases)")
+    );
+    assert_eq!(
+        h.document().unwrap(),
+        "\
+web:
+  port: &port 8080
+  env:
+    PORT: '8080'
+probe:
+  port: *port
+ports:
+- *port
+"
+    );
+
+    h.keys("u").unwrap();
+    assert_eq!(h.document().unwrap(), SERVICE);
+}
+
+#[test]
+fn test_extract_into_variables_mapping() {
+    let mut h = Harness::new(SERVICE).unwrap();
+    h.keys(":path $.web.port<CR>:extract port vars<CR>")
+        .unwrap();
+    assert_eq!(
+        rows(&h)[0],
+        (".vars.port".to_string(), "&port 8080".to_string())
+    );
+    assert_eq!(rows(&h).len(), 4);
+
+    h.keys("<CR>").unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "\
+vars:
+  port: &port 8080
+web:
+  port: *port
+  env:
+    PORT: '8080'
+probe:
+  port: *port
+ports:
+- *port
+"
+    );
+}
+
+#[test]
+fn test_extract_errors() {
+    let mut h = Harness::new(SERVICE).unwrap();
+    h.keys(":path $.web.env.PORT<CR>:extract port<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("8080 is only used once"));
+
+    h.keys(":path $.web.port<CR>:extract bad!name<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Anchor names use letters, digits, - and _")
+    );
+
+    h.keys(":extract port ports<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("'ports' is not a mapping"));
+
+    h.keys(":extract port probe<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Key 'port' already exists in probe")
+    );
+
+    // Esc cancels the preview
+    h.keys(":extract port<CR><Esc>").unwrap();
+    assert!(h.state().usages_panel().is_none());
+    assert_eq!(h.document().unwrap(), SERVICE);
+}
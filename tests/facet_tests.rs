@@ -0,0 +1,91 @@
+//! Tests for `:facet` value counts and record filtering.
+
+use yamlquill::editor::state::MessageLevel;
+use yamlquill::harness::Harness;
+
+const LOG: &str = "\
+---
+level: info
+msg: started
+---
+level: error
+msg: disk full
+---
+level: info
+msg: request
+---
+msg: no level
+---
+level: info
+msg: done
+";
+
+#[test]
+fn test_facet_panel_counts_values() {
+    let mut h = Harness::new(LOG).unwrap().with_size(100, 20);
+    h.keys(":facet level<CR>").unwrap();
+
+    let panel = h.state().facet_panel().unwrap();
+    let counts: Vec<_> = panel
+        .values
+        .iter()
+        .map(|v| (v.label(), v.records.len()))
+        .collect();
+    assert_eq!(counts, [("info", 3), ("error", 1), ("(missing)", 1)]);
+
+    let screen = h.screen().unwrap();
+    assert!(screen.contains("3  60%  info"), "{}", screen);
+}
+
+#[test]
+fn test_enter_filters_to_matching_records() {
+    let mut h = Harness::new(LOG).unwrap().with_size(100, 20);
+    h.keys(":facet level<CR>jj<CR>").unwrap();
+    assert!(h.state().facet_panel().is_none());
+
+    let shown: Vec<_> = h
+        .state()
+        .tree_view()
+        .lines()
+        .iter()
+        .map(|l| l.path.clone())
+        .collect();
+    assert_eq!(shown, [vec![3]]);
+    assert_eq!(h.state().cursor().path(), &[3]);
+    assert!(h.screen().unwrap().contains("[Filter: level=(missing)]"));
+
+    h.keys(":nofilter<CR>").unwrap();
+    assert_eq!(h.state().tree_view().lines().len(), 5);
+    assert!(!h.screen().unwrap().contains("[Filter:"));
+}
+
+#[test]
+fn test_filtered_record_can_be_expanded() {
+    let mut h = Harness::new(LOG).unwrap().with_size(100, 20);
+    h.keys(":facet level<CR>j<CR>").unwrap();
+    assert_eq!(h.state().cursor().path(), &[1]);
+
+    h.keys("l").unwrap();
+    let shown: Vec<_> = h
+        .state()
+        .tree_view()
+        .lines()
+        .iter()
+        .map(|l| l.path.clone())
+        .collect();
+    assert_eq!(shown, [vec![1], vec![1, 0], vec![1, 1]]);
+}
+
+#[test]
+fn test_facet_errors() {
+    let mut h = Harness::new("a: 1\n").unwrap();
+    h.keys(":facet a<CR>").unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert_eq!(message.text, "Facets need a multi-document or JSONL file");
+
+    let mut h = Harness::new(LOG).unwrap();
+    h.keys(":facet nope<CR>").unwrap();
+    assert_eq!(h.state().message().unwrap().text, "No record has 'nope'");
+    assert!(h.state().facet_panel().is_none());
+}
@@ -0,0 +1,65 @@
+//! Tests for `:filldown` over selected list elements.
+
+use yamlquill::harness::Harness;
+
+const INVENTORY: &str = "\
+- host: a
+  rack: r1
+- host: b
+- host: c
+  rack: r9
+";
+
+#[test]
+fn test_filldown_copies_key_into_selected_elements() {
+    let mut h = Harness::new(INVENTORY).unwrap();
+    // Select from the first element down to the third
+    h.keys("Vjjjjj:filldown rack<CR>").unwrap();
+    assert_eq!(
+        h.document().unwrap().trim_end(),
+        "\
+- host: a
+  rack: r1
+- host: b
+  rack: r1
+- host: c
+  rack: r1"
+    );
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text == "Filled 'rack' into 2 elements"));
+    assert!(h.state().visual_selection().is_empty());
+
+    // The whole fill is one undo step
+    h.keys("u").unwrap();
+    assert_eq!(h.document().unwrap(), INVENTORY);
+}
+
+#[test]
+fn test_filldown_only_touches_selection() {
+    let mut h = Harness::new(INVENTORY).unwrap();
+    // The first two elements only
+    h.keys("Vjjj:filldown rack<CR>").unwrap();
+    let doc = h.document().unwrap();
+    assert!(doc.contains("- host: b\n  rack: r1\n"), "{}", doc);
+    assert!(doc.contains("rack: r9"), "{}", doc);
+}
+
+#[test]
+fn test_filldown_errors() {
+    let mut h = Harness::new(INVENTORY).unwrap();
+    h.keys(":filldown rack<CR>").unwrap();
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text.contains("visual mode")));
+
+    // Starting from an element without the key
+    h.keys("jjjVj:filldown rack<CR>").unwrap();
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text == "First selected element has no 'rack'"));
+    assert_eq!(h.document().unwrap(), INVENTORY);
+}
@@ -0,0 +1,71 @@
+//! Tests for `:filter` and `:nofilter`.
+
+use yamlquill::editor::state::MessageLevel;
+use yamlquill::harness::Harness;
+
+const VALUES: &str = "\
+name: shop
+web:
+  image: nginx
+  replicas: 2
+worker:
+  image: busybox
+  queue: jobs
+db:
+  host: localhost
+";
+
+fn shown_paths(h: &Harness) -> Vec<Vec<usize>> {
+    h.state()
+        .tree_view()
+        .lines()
+        .iter()
+        .map(|l| l.path.clone())
+        .collect()
+}
+
+#[test]
+fn test_filter_by_yamlpath_keeps_matches_and_ancestors() {
+    let mut h = Harness::new(VALUES).unwrap();
+    h.keys(":filter $.*.image<CR>").unwrap();
+    assert_eq!(shown_paths(&h), [vec![1], vec![1, 0], vec![2], vec![2, 0]]);
+    assert_eq!(h.state().cursor().path(), &[1]);
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text.starts_with("2 match(es) shown")));
+    assert!(h.screen().unwrap().contains("[Filter: $.*.image]"));
+
+    h.keys(":nofilter<CR>").unwrap();
+    assert_eq!(h.state().tree_view().lines().len(), 9);
+    assert!(h.state().view_filter_label().is_none());
+}
+
+#[test]
+fn test_filter_by_pattern_expands_collapsed_ancestors() {
+    let mut h = Harness::new(VALUES).unwrap();
+    h.keys("jhjjh").unwrap();
+    h.keys(":filter host<CR>").unwrap();
+    assert_eq!(shown_paths(&h), [vec![3], vec![3, 0]]);
+
+    // Descendants of a match stay visible
+    h.keys(":filter worker<CR>").unwrap();
+    assert_eq!(shown_paths(&h), [vec![2], vec![2, 0], vec![2, 1]]);
+}
+
+#[test]
+fn test_filter_errors_leave_the_view_alone() {
+    let mut h = Harness::new(VALUES).unwrap();
+    h.keys(":filter nothing-here<CR>").unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert_eq!(message.text, "No matches for nothing-here");
+
+    h.keys(r":filter \v(<CR>").unwrap();
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text.contains("Invalid regex")));
+    assert!(h.state().view_filter_label().is_none());
+    assert_eq!(h.state().tree_view().lines().len(), 9);
+}
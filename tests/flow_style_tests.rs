@@ -0,0 +1,103 @@
+//! Integration tests for block and flow style (`gf`).
+
+use std::fs;
+use tempfile::TempDir;
+use termion::event::{Event, Key};
+use yamlquill::config::Config;
+use yamlquill::document::node::{CollectionStyle, YamlValue};
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::file::loader::load_yaml_file;
+use yamlquill::file::saver::save_yaml_file;
+use yamlquill::input::InputHandler;
+
+fn keys(handler: &mut InputHandler, state: &mut EditorState, keys: &str) {
+    for c in keys.chars() {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), state)
+            .unwrap();
+    }
+}
+
+fn state_for(yaml: &str) -> EditorState {
+    EditorState::new_with_default_theme(YamlTree::new(parse_yaml_auto(yaml).unwrap()))
+}
+
+/// Loads `yaml` from a file, presses `keys`, saves, and returns the file.
+fn edit_and_save(yaml: &str, keys_pressed: &str) -> String {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, yaml).unwrap();
+    let mut state = EditorState::new_with_default_theme(load_yaml_file(&path).unwrap());
+    let mut handler = InputHandler::new();
+    keys(&mut handler, &mut state, keys_pressed);
+    save_yaml_file(&path, state.tree(), &Config::default()).unwrap();
+    fs::read_to_string(&path).unwrap()
+}
+
+#[test]
+fn test_parser_records_style() {
+    let root = parse_yaml_auto("a: {x: 1}\nb: [1, 2]\nc:\n  - 1\n").unwrap();
+    let YamlValue::Object(entries) = root.value() else {
+        panic!("not a mapping");
+    };
+    assert_eq!(root.style(), CollectionStyle::Block);
+    assert_eq!(entries["a"].style(), CollectionStyle::Flow);
+    assert_eq!(entries["b"].style(), CollectionStyle::Flow);
+    assert_eq!(entries["c"].style(), CollectionStyle::Block);
+}
+
+#[test]
+fn test_block_to_flow() {
+    let saved = edit_and_save(
+        "name: web\nports:\n  - 80\n  - 443\nlabels:\n  app: web, api\n",
+        "jgf",
+    );
+    assert_eq!(
+        saved,
+        "name: web\nports: [80, 443]\nlabels:\n  app: web, api\n"
+    );
+
+    // Strings with flow indicators are quoted
+    let saved = edit_and_save("labels:\n  app: web, api\n  tier: front\n", "gf");
+    assert_eq!(saved, "labels: {app: \"web, api\", tier: front}\n");
+}
+
+#[test]
+fn test_flow_to_block() {
+    let saved = edit_and_save("name: web\nports: [80, 443]\n", "jgf");
+    assert_eq!(saved, "name: web\nports:\n- 80\n- 443\n");
+
+    // Untouched flow collections keep their style when the file is rewritten
+    let saved = edit_and_save("a: {x: 1}\nb:\n  c: 2\n", "Gkgf");
+    assert_eq!(saved, "a: {x: 1}\nb: {c: 2}\n");
+    let saved = edit_and_save("- {a: 1}\n- b: 2\n", "Gkgf");
+    assert_eq!(saved.trim_end(), "- {a: 1}\n- {b: 2}");
+}
+
+#[test]
+fn test_toggle_messages_and_undo() {
+    let mut state = state_for("list:\n  # note\n  - 1\nname: x\n");
+    let mut handler = InputHandler::new();
+
+    keys(&mut handler, &mut state, "gf");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Error);
+
+    // A scalar has no style
+    let mut state = state_for("list:\n  - 1\nname: x\n");
+    keys(&mut handler, &mut state, "Ggf");
+    assert_eq!(state.message().unwrap().text, "Not a mapping or sequence");
+
+    keys(&mut handler, &mut state, "gggf");
+    assert_eq!(state.message().unwrap().text, "Flow style");
+    assert_eq!(
+        state.tree().get_node(&[0]).unwrap().style(),
+        CollectionStyle::Flow
+    );
+    assert!(state.undo());
+    assert_eq!(
+        state.tree().get_node(&[0]).unwrap().style(),
+        CollectionStyle::Block
+    );
+}
@@ -0,0 +1,76 @@
+//! End-to-end tests driven through the headless harness.
+//!
+//! Snapshots live in `tests/snapshots/`; run with
+//! `YAMLQUILL_UPDATE_SNAPSHOTS=1 cargo test` to rewrite them after an
+//! intended change to the UI.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::harness::Harness;
+
+const DEPLOYMENT: &str = "\
+# Web tier
+name: web
+spec:
+  replicas: 3
+  ports:
+    - 80
+    - 443
+";
+
+fn snapshot_path(name: &str) -> String {
+    format!(
+        "{}/tests/snapshots/{}.snap",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    )
+}
+
+#[test]
+fn test_initial_screen() {
+    let mut harness = Harness::new(DEPLOYMENT).unwrap().with_size(50, 12);
+    harness.assert_snapshot(snapshot_path("initial_screen"));
+}
+
+#[test]
+fn test_edit_value() {
+    let mut harness = Harness::new(DEPLOYMENT).unwrap().with_size(50, 12);
+    harness.keys("jjje<BS>5<CR>").unwrap();
+    harness.assert_snapshot(snapshot_path("edit_value"));
+
+    harness.keys("u").unwrap();
+    assert_eq!(harness.document().unwrap(), DEPLOYMENT);
+}
+
+#[test]
+fn test_delete_and_fold() {
+    let mut harness = Harness::new(DEPLOYMENT).unwrap().with_size(50, 12);
+    harness.keys("jdd").unwrap();
+    harness.keys("h").unwrap();
+    harness.assert_snapshot(snapshot_path("delete_and_fold"));
+}
+
+#[test]
+fn test_command_error_is_shown() {
+    let mut harness = Harness::new(DEPLOYMENT).unwrap().with_size(50, 12);
+    harness.keys(":frobnicate<CR>").unwrap();
+    let screen = harness.screen().unwrap();
+    let message = screen.lines().last().unwrap();
+    assert!(message.contains("frobnicate"), "{}", screen);
+    assert!(!harness.has_quit());
+}
+
+#[test]
+fn test_write_and_quit() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, DEPLOYMENT).unwrap();
+
+    let mut harness = Harness::open(&path).unwrap();
+    harness.keys("je<BS><BS><BS>api<CR>:wq<CR>j").unwrap();
+    assert!(harness.has_quit());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        DEPLOYMENT.replace("web", "api")
+    );
+}
@@ -0,0 +1,101 @@
+//! Tests for Helm values awareness (`:usages`).
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::editor::diagnostics::Severity;
+use yamlquill::harness::Harness;
+
+const VALUES: &str = "\
+image:
+  repository: nginx
+  tag: '1.27'
+  pullPolicy: Always
+resources:
+  limits:
+    cpu: 100m
+legacy:
+  enabled: true
+";
+
+/// A chart with a values file and two templates.
+fn chart() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("values.yaml"), VALUES).unwrap();
+    fs::create_dir_all(dir.path().join("templates/tests")).unwrap();
+    fs::write(
+        dir.path().join("templates/deployment.yaml"),
+        "\
+spec:
+  containers:
+    - image: {{ .Values.image.repository }}:{{ .Values.image.tag }}
+      resources:
+        {{- toYaml .Values.resources | nindent 8 }}
+",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("templates/tests/smoke.yaml"),
+        "image: {{ index $.Values \"image\" \"tag\" }}\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn message(h: &Harness) -> String {
+    h.state()
+        .message()
+        .map(|m| m.text.clone())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_usages_lists_templates_reading_the_key() {
+    let chart = chart();
+    let mut h = Harness::open(chart.path().join("values.yaml")).unwrap();
+
+    // image.tag
+    h.keys("jj:usages<CR>").unwrap();
+    assert_eq!(
+        message(&h),
+        "Used in templates/deployment.yaml:3, templates/tests/smoke.yaml:1"
+    );
+
+    // resources.limits.cpu is covered by toYaml .Values.resources
+    h.keys("jjjj:usages<CR>").unwrap();
+    assert_eq!(message(&h), "Used in templates/deployment.yaml:5");
+
+    // legacy
+    h.keys("jj:usages<CR>").unwrap();
+    assert_eq!(message(&h), "Not used by any template");
+}
+
+#[test]
+fn test_usages_unused_flags_dead_keys() {
+    let chart = chart();
+    let mut h = Harness::open(chart.path().join("values.yaml")).unwrap();
+    h.keys(":usages unused<CR>").unwrap();
+    assert_eq!(
+        message(&h),
+        "2 unused values keys marked W: image.pullPolicy, legacy"
+    );
+    let diagnostics = h.state().diagnostics().items();
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+    assert_eq!(diagnostics[0].path, vec![0, 2]);
+    assert_eq!(diagnostics[1].location, "$.legacy");
+}
+
+#[test]
+fn test_usages_with_explicit_chart_dir() {
+    let chart = chart();
+    let mut h = Harness::new(VALUES).unwrap();
+    h.keys(":usages<CR>").unwrap();
+    assert!(message(&h).starts_with("No file name"), "{}", message(&h));
+
+    h.keys(&format!(":usages {}<CR>", chart.path().display()))
+        .unwrap();
+    assert!(message(&h).starts_with("Used in templates/deployment.yaml:3"));
+
+    h.keys(":usages /nonexistent<CR>").unwrap();
+    assert!(message(&h).starts_with("No templates directory"));
+}
@@ -0,0 +1,66 @@
+//! Tests for `s`, hint mode: a label on every line on screen.
+
+use yamlquill::editor::state::MessageLevel;
+use yamlquill::harness::Harness;
+
+fn yaml(keys: usize) -> String {
+    (0..keys).map(|i| format!("key{}: {}\n", i, i)).collect()
+}
+
+#[test]
+fn test_nearest_lines_get_single_letters() {
+    let mut h = Harness::new(&yaml(10)).unwrap();
+    h.keys("3js").unwrap();
+    let hints = h.state().hints().unwrap();
+    let first: Vec<_> = hints
+        .targets
+        .iter()
+        .take(4)
+        .map(|(path, label)| (path[0], label.as_str()))
+        .collect();
+    assert_eq!(first, [(4, "a"), (2, "s"), (5, "d"), (1, "f")]);
+    assert_eq!(h.state().message().unwrap().text, "s: type a label");
+
+    h.keys("f").unwrap();
+    assert!(h.state().hints().is_none());
+    assert_eq!(h.state().cursor().path(), [1]);
+}
+
+#[test]
+fn test_two_letter_labels_past_the_alphabet() {
+    let mut h = Harness::new(&yaml(60)).unwrap().with_size(80, 50);
+    h.screen().unwrap();
+    h.keys("s").unwrap();
+    let hints = h.state().hints().unwrap();
+    assert!(hints.targets.len() > 26, "{}", hints.targets.len());
+    let (path, label) = hints.targets.last().unwrap().clone();
+    assert_eq!(label.len(), 2);
+
+    // The first letter narrows the hints to what is left to type
+    h.keys(&label[..1]).unwrap();
+    let screen = h.screen().unwrap();
+    let second = &label[1..];
+    assert!(
+        screen.contains(&format!("\n{} {}", second, path[0] + 1)),
+        "{}",
+        screen
+    );
+    h.keys(second).unwrap();
+    assert_eq!(h.state().cursor().path(), path);
+
+    // Backspace takes a letter back, Esc leaves hint mode
+    h.keys(&format!("s{}<BS>", &label[..1])).unwrap();
+    assert_eq!(h.state().hints().unwrap().typed, "");
+    h.keys("<Esc>").unwrap();
+    assert!(h.state().hints().is_none());
+}
+
+#[test]
+fn test_unknown_label() {
+    let mut h = Harness::new(&yaml(3)).unwrap();
+    h.keys("sz").unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert_eq!(message.text, "No line labelled z");
+    assert_eq!(h.state().cursor().path(), [0]);
+}
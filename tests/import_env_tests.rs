@@ -0,0 +1,90 @@
+//! Tests for `:import env` from `.env` files and the environment.
+
+use std::io::Write;
+use yamlquill::harness::Harness;
+
+fn env_file(contents: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(contents.as_bytes()).unwrap();
+    file
+}
+
+#[test]
+fn test_import_env_file_updates_mapping() {
+    let file = env_file("export APP_NAME=shop\nAPP_PORT=8080\nOTHER=x\n");
+    let yaml = "settings:\n  APP_NAME: old\n  debug: true\n";
+    let mut h = Harness::new(yaml).unwrap();
+    h.keys(&format!(
+        ":import env {} --filter ^APP_<CR>",
+        file.path().display()
+    ))
+    .unwrap();
+    assert_eq!(
+        h.document().unwrap().trim_end(),
+        "\
+settings:
+  APP_NAME: shop
+  debug: true
+  APP_PORT: '8080'"
+    );
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text == "Imported 1 new, 1 updated"));
+
+    // The whole import is one undo step
+    h.keys("u").unwrap();
+    assert_eq!(h.document().unwrap(), yaml);
+}
+
+#[test]
+fn test_import_env_into_null_creates_mapping() {
+    let file = env_file("A=1\nB=two\n");
+    let mut h = Harness::new("name: app\nenv:\n").unwrap();
+    h.keys(&format!("j:import env {}<CR>", file.path().display()))
+        .unwrap();
+    assert_eq!(
+        h.document().unwrap().trim_end(),
+        "name: app\nenv:\n  A: '1'\n  B: two"
+    );
+}
+
+#[test]
+fn test_import_env_from_environment() {
+    std::env::set_var("YAMLQUILL_IMPORT_TEST", "from-env");
+    let mut h = Harness::new("env: {}\n").unwrap();
+    h.keys(":import env --filter=^YAMLQUILL_IMPORT_TEST$<CR>")
+        .unwrap();
+    assert!(
+        h.document()
+            .unwrap()
+            .contains("YAMLQUILL_IMPORT_TEST: from-env"),
+        "{}",
+        h.document().unwrap()
+    );
+}
+
+#[test]
+fn test_import_env_errors() {
+    let mut h = Harness::new("name: app\n").unwrap();
+    h.keys(":import json<CR>").unwrap();
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text.starts_with("Usage: :import env")));
+
+    let file = env_file("A=1\n");
+    h.keys(&format!(":import env {}<CR>", file.path().display()))
+        .unwrap();
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text == "Put the cursor on a mapping to import into"));
+
+    h.keys(":import env /no/such/file.env<CR>").unwrap();
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text.starts_with("Can't read /no/such/file.env")));
+    assert_eq!(h.document().unwrap(), "name: app\n");
+}
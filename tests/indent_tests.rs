@@ -0,0 +1,64 @@
+//! Tests for moving nodes in (`>`) and out (`<`) of containers.
+
+use yamlquill::harness::Harness;
+
+fn message(h: &Harness) -> Option<String> {
+    h.state().message().map(|m| m.text.clone())
+}
+
+#[test]
+fn test_indent_moves_entry_into_mapping_above() {
+    let yaml = "server:\n  host: a\nport: 80\n";
+    let mut h = Harness::new(yaml).unwrap();
+    h.keys(":path $.port<CR>>").unwrap();
+    assert_eq!(h.document().unwrap(), "server:\n  host: a\n  port: 80\n");
+    assert_eq!(h.state().cursor().path(), &[0, 1]);
+
+    // Outdenting puts it back after its old parent
+    h.keys("<").unwrap();
+    assert_eq!(h.document().unwrap(), yaml);
+    assert_eq!(h.state().cursor().path(), &[1]);
+
+    // Each move is one undo step
+    h.keys("uu").unwrap();
+    assert_eq!(h.document().unwrap(), yaml);
+    h.keys("u").unwrap();
+    assert_eq!(h.document().unwrap(), yaml);
+    h.keys("<C-r>").unwrap();
+    assert_eq!(h.document().unwrap(), "server:\n  host: a\n  port: 80\n");
+}
+
+#[test]
+fn test_entries_become_one_key_items_in_lists() {
+    let yaml = "steps:\n- run: build\nname: test\n";
+    let mut h = Harness::new(yaml).unwrap();
+    h.keys(":path $.name<CR>>").unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "steps:\n- run: build\n- name: test\n"
+    );
+    assert_eq!(h.state().cursor().path(), &[0, 1]);
+
+    // The one-key item outdents back into the mapping
+    h.keys("<").unwrap();
+    assert_eq!(h.document().unwrap(), yaml);
+
+    // A one-key item indents into the mapping item above as an entry
+    let mut h = Harness::new("- a: 1\n  b: 2\n- c: 3\n").unwrap();
+    h.keys(":path $[1]<CR>>").unwrap();
+    assert_eq!(h.document().unwrap().trim_end(), "- a: 1\n  b: 2\n  c: 3");
+}
+
+#[test]
+fn test_indent_and_outdent_errors() {
+    let mut h = Harness::new("a: 1\nb:\n  a: 2\n").unwrap();
+    h.keys(">").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Nothing above to indent into"));
+    h.keys("<").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Already at the top level"));
+
+    // `a` can't move out of `b` next to the existing `a`
+    h.keys(":path $.b.a<CR><").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Key 'a' already exists there"));
+    assert_eq!(h.document().unwrap(), "a: 1\nb:\n  a: 2\n");
+}
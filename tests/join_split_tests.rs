@@ -0,0 +1,50 @@
+//! Tests for joining lists into strings (`gJ`, `:join`) and splitting
+//! strings into lists (`gS`, `:split-string`).
+
+use yamlquill::harness::Harness;
+
+fn message(h: &Harness) -> Option<String> {
+    h.state().message().map(|m| m.text.clone())
+}
+
+#[test]
+fn test_join_and_split_round_trip() {
+    let yaml = "args:\n- --port\n- 8080\n- -v\n";
+    let mut h = Harness::new(yaml).unwrap();
+    // From an item, gJ joins the whole list
+    h.keys(":path $.args[1]<CR>gJ").unwrap();
+    assert_eq!(h.document().unwrap(), "args: --port 8080 -v\n");
+    assert_eq!(message(&h).as_deref(), Some("Joined 3 items"));
+    assert_eq!(h.state().cursor().path(), &[0]);
+
+    // gS prompts for the delimiter; none splits at whitespace
+    h.keys("gS<CR>").unwrap();
+    assert_eq!(h.document().unwrap(), "args:\n- --port\n- '8080'\n- -v\n");
+    assert_eq!(message(&h).as_deref(), Some("Split into 3 items"));
+
+    h.keys("uu").unwrap();
+    assert_eq!(h.document().unwrap(), yaml);
+}
+
+#[test]
+fn test_join_and_split_with_separators() {
+    let mut h = Harness::new("hosts: a, b,c\n").unwrap();
+    h.keys("gS,<CR>").unwrap();
+    assert_eq!(h.document().unwrap(), "hosts:\n- a\n- b\n- c\n");
+
+    h.keys(":join \", \"<CR>").unwrap();
+    assert_eq!(h.document().unwrap(), "hosts: a, b, c\n");
+
+    h.keys(":join<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Not on a list"));
+}
+
+#[test]
+fn test_join_refuses_nested_lists() {
+    let mut h = Harness::new("matrix:\n- [1, 2]\n- [3]\n").unwrap();
+    h.keys("gJ").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Only lists of scalars can be joined")
+    );
+}
@@ -0,0 +1,157 @@
+//! Tests for the Kubernetes commands (`:set k8s`).
+
+use yamlquill::harness::Harness;
+
+const MANIFESTS: &str = "\
+apiVersion: apps/v1
+kind: Deployment
+spec:
+  template:
+    spec:
+      containers:
+        - name: web
+          image: docker.io/library/nginx:1.25
+          env:
+            - name: MODE
+              valueFrom:
+                configMapKeyRef:
+                  name: settings
+                  key: mode
+        - name: cache
+          image: redis:7
+---
+apiVersion: v1
+kind: Pod
+spec:
+  containers:
+    - name: debug
+      image: nginx:1.25
+";
+
+fn message(h: &Harness) -> String {
+    h.state()
+        .message()
+        .map(|m| m.text.clone())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_commands_need_k8s_profile() {
+    let mut h = Harness::new(MANIFESTS).unwrap();
+    let before = h.document().unwrap();
+    h.keys(":containers<CR>").unwrap();
+    assert_eq!(message(&h), "Kubernetes commands are off (:set k8s)");
+    assert!(h.state().container_picker().is_none());
+    h.keys(":images 2.0<CR>").unwrap();
+    assert_eq!(message(&h), "Kubernetes commands are off (:set k8s)");
+    assert_eq!(h.document().unwrap(), before);
+}
+
+#[test]
+fn test_containers_picker_lists_and_jumps() {
+    let mut h = Harness::new(MANIFESTS).unwrap();
+    h.keys(":set k8s<CR>:containers<CR>").unwrap();
+    let picker = h.state().container_picker().unwrap();
+    let rows: Vec<_> = picker
+        .containers
+        .iter()
+        .map(|c| format!("{} {}", c.name, c.image))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            "web docker.io/library/nginx:1.25",
+            "cache redis:7",
+            "debug nginx:1.25"
+        ]
+    );
+
+    h.keys("j<CR>").unwrap();
+    assert!(h.state().container_picker().is_none());
+    assert_eq!(h.state().cursor().path(), &[0, 2, 0, 0, 0, 1]);
+}
+
+#[test]
+fn test_images_retags_matching_images() {
+    let mut h = Harness::new(MANIFESTS).unwrap();
+    h.keys(":set k8s<CR>:images nginx=1.27<CR>").unwrap();
+    assert_eq!(message(&h), "Retagged 2 images");
+    let doc = h.document().unwrap();
+    assert!(
+        doc.contains("image: docker.io/library/nginx:1.27\n"),
+        "{}",
+        doc
+    );
+    assert!(doc.contains("image: nginx:1.27\n"), "{}", doc);
+    assert!(doc.contains("image: redis:7\n"), "{}", doc);
+
+    // Every image, as one undo step
+    h.keys(":images @sha256:abc<CR>").unwrap();
+    assert_eq!(message(&h), "Retagged 3 images");
+    assert!(h.document().unwrap().contains("image: redis@sha256:abc\n"));
+    h.keys("u").unwrap();
+    assert_eq!(h.document().unwrap(), doc);
+}
+
+#[test]
+fn test_env_sets_var_in_container_under_cursor() {
+    let mut h = Harness::new(MANIFESTS).unwrap();
+    h.keys(":set k8s<CR>:containers<CR><CR>").unwrap();
+
+    // Replaces the valueFrom of an existing entry
+    h.keys(":env MODE=debug<CR>").unwrap();
+    assert_eq!(message(&h), "Set MODE");
+    let doc = h.document().unwrap();
+    assert!(
+        doc.contains(
+            "        env:\n        - name: MODE\n          value: debug\n      - name: cache"
+        ),
+        "{}",
+        doc
+    );
+
+    // Creates the env list of a container without one
+    h.keys(":containers<CR>j<CR>:env PORT=6379<CR>").unwrap();
+    assert_eq!(message(&h), "Added PORT");
+    let doc = h.document().unwrap();
+    assert!(
+        doc.contains(
+            "        image: redis:7\n        env:\n        - name: PORT\n          value: '6379'\n"
+        ),
+        "{}",
+        doc
+    );
+
+    h.keys("gg:env A=b<CR>").unwrap();
+    assert_eq!(message(&h), "Put the cursor in a container");
+}
+
+#[test]
+fn test_k8s_profile_checks_quantities() {
+    let yaml = "\
+resources:
+  limits:
+    memory: 2G
+";
+    let mut h = Harness::new(yaml).unwrap();
+    assert!(h.state().diagnostics().is_empty());
+
+    h.keys(":set k8s<CR>").unwrap();
+    let diagnostics = h.state().diagnostics().items();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].path, vec![0, 0, 0]);
+    assert_eq!(
+        diagnostics[0].message,
+        "2G is 2×10^9 bytes; did you mean 2Gi?"
+    );
+
+    // Fixing the value clears it; undo brings it back
+    h.keys(":path $.resources.limits.memory<CR>e<End>i<CR>")
+        .unwrap();
+    assert!(h.state().diagnostics().is_empty());
+    h.keys("u").unwrap();
+    assert_eq!(h.state().diagnostics().len(), 1);
+
+    h.keys(":set nok8s<CR>").unwrap();
+    assert!(h.state().diagnostics().is_empty());
+}
@@ -0,0 +1,102 @@
+//! Tests for `f`, jumping to a key on screen by typing its start.
+
+use yamlquill::editor::state::MessageLevel;
+use yamlquill::harness::Harness;
+
+const YAML: &str = "\
+name: api
+replicas: 2
+resources:
+  limits:
+    cpu: 1
+  requests:
+    cpu: 500m
+readinessProbe:
+  path: /health
+";
+
+#[test]
+fn test_single_match_jumps_at_once() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys("fp").unwrap();
+    assert!(h.state().key_jump().is_none());
+    assert_eq!(h.state().cursor().path(), [3, 0]);
+}
+
+#[test]
+fn test_labels_pick_among_matches() {
+    let mut h = Harness::new(YAML).unwrap().with_size(80, 16);
+    h.keys("fr").unwrap();
+    let jump = h.state().key_jump().unwrap();
+    assert_eq!(
+        jump.targets,
+        [
+            (vec![1], Some('a')),
+            (vec![2], Some('s')),
+            (vec![2, 1], Some('d')),
+            (vec![3], Some('f')),
+        ]
+    );
+    let screen = h.screen().unwrap();
+    // Labels take a gutter of their own, left of the line numbers
+    assert!(screen.contains("\nd 6   ▼ requests"), "{}", screen);
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "fr: 4 keys; type more, a label, or Enter for the next"
+    );
+
+    h.keys("d").unwrap();
+    assert_eq!(h.state().cursor().path(), [2, 1]);
+    assert!(h.screen().unwrap().contains("\n6   ▼ requests"));
+}
+
+#[test]
+fn test_letters_that_extend_a_match_narrow_instead_of_jumping() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys("fre").unwrap();
+    let labels: Vec<_> = h
+        .state()
+        .key_jump()
+        .unwrap()
+        .targets
+        .iter()
+        .map(|(_, label)| label.unwrap())
+        .collect();
+    // a, p, q and s continue "re"
+    assert_eq!(labels, ['d', 'f', 'g', 'h']);
+    h.keys("s").unwrap();
+    assert_eq!(h.state().cursor().path(), [2]);
+
+    // Backspace widens again; Enter takes the first match after the cursor
+    h.keys("fc<BS>c").unwrap();
+    assert_eq!(h.state().key_jump().unwrap().targets.len(), 2);
+    h.keys("<CR>").unwrap();
+    assert_eq!(h.state().cursor().path(), [2, 0, 0]);
+}
+
+#[test]
+fn test_no_match_and_escape() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys("fx").unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert_eq!(message.text, "No key on screen starts with x");
+    assert_eq!(h.state().cursor().path(), [0]);
+
+    h.keys("fr<Esc>j").unwrap();
+    assert!(h.state().key_jump().is_none());
+    assert_eq!(h.state().cursor().path(), [1]);
+}
+
+#[test]
+fn test_nothing_on_screen() {
+    for yaml in ["[]\n", "{}\n", "hello\n"] {
+        let mut h = Harness::new(yaml).unwrap();
+        h.keys("fa").unwrap();
+        assert!(h.state().key_jump().is_none());
+        assert_eq!(
+            h.state().message().unwrap().text,
+            "No key on screen starts with a"
+        );
+    }
+}
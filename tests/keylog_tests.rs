@@ -0,0 +1,65 @@
+//! Tests for the key logger (`:set keylog`).
+
+use tempfile::TempDir;
+use yamlquill::harness::{parse_keys, Harness};
+
+/// Returns the key column of each logged line.
+fn logged_keys(text: &str) -> Vec<String> {
+    text.lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| line.split_whitespace().nth(2).unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_keylog_records_replayable_keys() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("keys.log");
+    let mut h = Harness::new("a: 1\nb: 2\n").unwrap();
+    h.keys(&format!(":set keylog={}<CR>", path.display()))
+        .unwrap();
+    assert!(h
+        .state()
+        .message()
+        .unwrap()
+        .text
+        .starts_with("Logging keys to"));
+
+    h.keys("je<BS>7<CR><C-r>:set nokeylog<CR>").unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    let keys = logged_keys(&text);
+    assert_eq!(keys[..6], ["j", "e", "<BS>", "7", "<CR>", "<C-r>"]);
+    assert!(text.contains("NORMAL   j            MoveDown"), "{}", text);
+
+    // The notation is the harness's, so the keys parse back
+    let script: String = keys.concat();
+    assert_eq!(
+        parse_keys(&script).unwrap()[..6],
+        parse_keys("je<BS>7<CR><C-r>").unwrap()[..]
+    );
+
+    // Nothing more is logged once stopped
+    h.keys("k").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), text);
+}
+
+#[test]
+fn test_keylog_redacts_typed_text() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("keys.log");
+    let mut h = Harness::new("password: hunter2\n").unwrap();
+    h.keys(&format!(
+        ":set keylog={}<CR>:set keylogredact<CR>",
+        path.display()
+    ))
+    .unwrap();
+    h.keys("e<C-a><C-k>secret<CR>").unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    let keys = logged_keys(&text);
+    let start = keys.len() - 10;
+    assert_eq!(
+        keys[start..],
+        ["e", "<C-a>", "<C-k>", "_", "_", "_", "_", "_", "_", "<CR>"]
+    );
+    assert_eq!(h.document().unwrap(), "password: secret\n");
+}
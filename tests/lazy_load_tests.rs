@@ -0,0 +1,94 @@
+//! Tests for lazy loading of large files (`lazy_load_threshold`).
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::document::limits::LoadLimits;
+use yamlquill::editor::state::EditorState;
+use yamlquill::file::loader::load_yaml_file_lazy;
+use yamlquill::harness::Harness;
+
+const SOURCE: &str = "\
+name: demo
+spec:
+  # two for now
+  replicas: 2
+  ports:
+    - 80
+
+# kept as written
+status:
+  phase:   Running
+";
+
+/// Opens `SOURCE` with a threshold of zero, so it loads lazily.
+fn open_lazily() -> (TempDir, Harness) {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("big.yaml");
+    fs::write(&file, SOURCE).unwrap();
+    let mut state = EditorState::new_with_default_theme(
+        load_yaml_file_lazy(&file, 0, &LoadLimits::default()).unwrap(),
+    );
+    state.set_filename(file.display().to_string());
+    (dir, Harness::from_state(state))
+}
+
+#[test]
+fn test_sections_load_on_expand() {
+    let (_dir, mut harness) = open_lazily();
+    let screen = harness.screen().unwrap();
+    assert!(screen.contains("not loaded"));
+    assert!(!screen.contains("replicas"));
+
+    harness.keys(":path $.spec<CR>l").unwrap();
+    let screen = harness.screen().unwrap();
+    assert!(screen.contains("replicas"));
+    assert!(!harness.state().is_dirty());
+    assert_eq!(harness.document().unwrap(), SOURCE);
+}
+
+#[test]
+fn test_edits_in_loaded_section_keep_the_rest() {
+    let (_dir, mut harness) = open_lazily();
+    harness
+        .keys(":path $.spec<CR>l:path $.spec.replicas<CR>e<C-a><C-k>3<CR>")
+        .unwrap();
+    let document = harness.document().unwrap();
+    assert!(document.contains("replicas: 3"));
+    assert!(document.contains("# kept as written\nstatus:\n  phase:   Running\n"));
+
+    // A comment edit inside a loaded section lands on its own line
+    let (_dir, mut harness) = open_lazily();
+    harness.keys(":path $.spec<CR>l:path $.spec<CR>j").unwrap();
+    harness.keys("e<C-a><C-k>three soon<CR>").unwrap();
+    assert_eq!(
+        harness.document().unwrap(),
+        SOURCE.replace("# two for now", "# three soon")
+    );
+}
+
+#[test]
+fn test_bulk_edits_load_every_section() {
+    let (_dir, mut harness) = open_lazily();
+    harness.keys(":%s/Running/Stopped/<CR>").unwrap();
+    assert!(harness.document().unwrap().contains("Stopped"));
+
+    let (_dir, mut harness) = open_lazily();
+    harness.keys(":path-set $.spec.replicas 5<CR>").unwrap();
+    assert!(harness.document().unwrap().contains("replicas: 5"));
+    assert!(!harness.screen().unwrap().contains("not loaded"));
+}
+
+#[test]
+fn test_bulk_edits_name_sections_that_dont_parse() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("big.yaml");
+    fs::write(&file, "name: demo\nbroken:\n  ports: [80\n").unwrap();
+    let state = EditorState::new_with_default_theme(
+        load_yaml_file_lazy(&file, 0, &LoadLimits::default()).unwrap(),
+    );
+    let mut harness = Harness::from_state(state);
+    harness.keys(":%s/demo/prod/<CR>").unwrap();
+    let message = harness.state().message().unwrap();
+    assert!(message.text.contains(".broken failed to parse"));
+    assert!(harness.document().unwrap().contains("name: demo"));
+}
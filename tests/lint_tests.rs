@@ -0,0 +1,107 @@
+//! Tests for `:lint`, its quickfix panel and `]q`/`[q`.
+
+use yamlquill::editor::state::MessageLevel;
+use yamlquill::harness::Harness;
+
+const YAML: &str = "\
+name: api
+replicas: 2
+features:
+  tls: on
+  debug:
+name: web
+";
+
+#[test]
+fn test_lint_opens_the_quickfix_panel() {
+    let mut h = Harness::new(YAML).unwrap().with_size(100, 20);
+    h.keys(":lint<CR>").unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Warning);
+    assert_eq!(message.text, "3 lint problem(s) (]q/[q to step through)");
+
+    let panel = h.state().quickfix_panel().unwrap();
+    let items: Vec<_> = panel.entries.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        items,
+        [
+            "`on` is a boolean in YAML 1.1 but a string in YAML 1.2; quote it or use true/false (line 4, non-portable-booleans)",
+            "Empty value, read as null (line 5, empty-values)",
+            "Duplicate key `name` (line 6, duplicate-keys)",
+        ]
+    );
+    assert!(h.screen().unwrap().contains("Lint (3)"));
+
+    // Enter jumps to the selected problem
+    h.keys("j<CR>").unwrap();
+    assert!(h.state().quickfix_panel().is_none());
+    assert_eq!(h.state().cursor().path(), [2, 1]);
+}
+
+#[test]
+fn test_bracket_q_steps_through_problems() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys("]q").unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "No quickfix list (:lint, :copen)"
+    );
+
+    h.keys(":lint<CR><Esc>").unwrap();
+    assert!(h.state().quickfix_panel().is_none());
+    h.keys("G]q").unwrap();
+    assert_eq!(h.state().cursor().path(), [2, 0]);
+    assert!(h.state().message().unwrap().text.starts_with("(1/3) `on`"));
+    h.keys("]q").unwrap();
+    assert_eq!(h.state().cursor().path(), [2, 1]);
+    h.keys("]q]q").unwrap();
+    assert_eq!(h.state().cursor().path(), [2, 0]);
+    h.keys("[q").unwrap();
+    assert_eq!(h.state().cursor().path(), [3]);
+    assert!(h
+        .state()
+        .message()
+        .unwrap()
+        .text
+        .starts_with("(3/3) Duplicate"));
+
+    // ] and [ alone still move between siblings, one per press
+    h.keys("[").unwrap();
+    assert_eq!(h.state().cursor().path(), [2]);
+    h.keys("]").unwrap();
+    assert_eq!(h.state().cursor().path(), [3]);
+    h.keys("[[").unwrap();
+    assert_eq!(h.state().cursor().path(), [1]);
+    h.keys("]j").unwrap();
+    assert_eq!(h.state().cursor().path(), [2, 0]);
+}
+
+#[test]
+fn test_rules_can_be_disabled() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys(":lint disable empty-values<CR>").unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "Disabled empty-values (:set save keeps it)"
+    );
+    assert_eq!(h.state().to_config().lint_disabled, ["empty-values"]);
+    h.keys(":lint disable duplicate-keys<CR>:lint disable non-portable-booleans<CR>")
+        .unwrap();
+    h.keys(":lint<CR>").unwrap();
+    assert_eq!(h.state().message().unwrap().text, "No lint problems");
+    assert!(h.state().quickfix_panel().is_none());
+
+    h.keys(":lint rules<CR>").unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "noduplicate-keys noempty-values trailing-whitespace inconsistent-indent nonon-portable-booleans long-lines"
+    );
+
+    h.keys(":lint enable tabs<CR>").unwrap();
+    assert!(h
+        .state()
+        .message()
+        .unwrap()
+        .text
+        .starts_with("Unknown lint rule: tabs"));
+}
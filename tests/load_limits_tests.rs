@@ -0,0 +1,62 @@
+//! Tests for the resource limits checked when a file is opened.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::config::Config;
+use yamlquill::document::limits::LoadLimits;
+use yamlquill::file::loader::{load_yaml_file, load_yaml_file_lazy};
+
+/// Nine levels of anchors, each aliasing the one before ten times.
+fn billion_laughs() -> String {
+    let mut yaml = "a0: &a0 \"lol\"\n".to_string();
+    for level in 1..=9 {
+        let aliases = vec![format!("*a{}", level - 1); 10].join(", ");
+        yaml.push_str(&format!("a{}: &a{} [{}]\n", level, level, aliases));
+    }
+    yaml
+}
+
+#[test]
+fn test_billion_laughs_is_refused() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("lol.yaml");
+    fs::write(&path, billion_laughs()).unwrap();
+
+    let error = load_yaml_file(&path).unwrap_err().to_string();
+    assert_eq!(
+        error,
+        format!(
+            "Not loading {}: aliases expand to more than 1000000 nodes by line 7, as in a \"billion laughs\" attack (max_alias_expansion)",
+            path.display()
+        )
+    );
+
+    // With the limit off the file opens; its aliases stay aliases
+    let limits = LoadLimits {
+        max_alias_expansion: 0,
+        ..LoadLimits::default()
+    };
+    assert!(load_yaml_file_lazy(&path, usize::MAX, &limits).is_ok());
+}
+
+#[test]
+fn test_limits_come_from_the_config() {
+    let dir = TempDir::new().unwrap();
+    let deep = dir.path().join("deep.yaml");
+    fs::write(&deep, format!("{}1{}", "[".repeat(40), "]".repeat(40))).unwrap();
+    let big = dir.path().join("big.json");
+    fs::write(&big, format!("[{}0]", "0,".repeat(600_000))).unwrap();
+
+    let config: Config = toml::from_str("max_parse_depth = 32\nmax_file_size_mb = 1\n").unwrap();
+    let limits = config.load_limits();
+    let error = load_yaml_file_lazy(&deep, usize::MAX, &limits)
+        .unwrap_err()
+        .to_string();
+    assert!(error.ends_with("nesting is deeper than 32 levels at line 1 (max_parse_depth)"));
+    let error = load_yaml_file_lazy(&big, usize::MAX, &limits)
+        .unwrap_err()
+        .to_string();
+    assert!(error.ends_with("the file is 2 MB, over the 1 MB limit (max_file_size_mb)"));
+
+    assert!(load_yaml_file_lazy(&deep, usize::MAX, &Config::default().load_limits()).is_ok());
+}
@@ -46,3 +46,37 @@ fn test_clear_marks() {
     assert_eq!(marks.get_mark('a'), None);
     assert_eq!(marks.get_mark('b'), None);
 }
+
+#[test]
+fn test_iter_marks_in_name_order() {
+    let mut marks = MarkSet::new();
+    assert!(marks.is_empty());
+    marks.set_mark('c', vec![2]);
+    marks.set_mark('a', vec![0, 1]);
+
+    let listed: Vec<(char, &[usize])> = marks.iter().collect();
+    assert_eq!(listed, [('a', &[0, 1][..]), ('c', &[2][..])]);
+    assert_eq!(marks.len(), 2);
+}
+
+#[test]
+fn test_marks_picker_lists_and_jumps() {
+    use yamlquill::harness::Harness;
+
+    let mut h = Harness::new("name: app\nspec:\n  ports:\n    - 80\n    - 443\n").unwrap();
+    h.keys(":marks<CR>").unwrap();
+    assert!(h.state().mark_picker().is_none());
+
+    h.keys("mbjjjjmagg:marks<CR>").unwrap();
+    let picker = h.state().mark_picker().unwrap();
+    let rows: Vec<String> = picker
+        .marks
+        .iter()
+        .map(|m| format!("{} {} {}", m.name, m.location, m.preview))
+        .collect();
+    assert_eq!(rows, ["a .spec.ports[1] 443", "b .name app"]);
+
+    h.keys("<CR>").unwrap();
+    assert!(h.state().mark_picker().is_none());
+    assert_eq!(h.state().cursor().path(), &[1, 0, 1]);
+}
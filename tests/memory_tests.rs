@@ -0,0 +1,76 @@
+//! Tests for the memory budget (`memory_budget_mb`, `:memory`).
+
+use yamlquill::harness::Harness;
+
+/// A document of a bit under a megabyte.
+fn document() -> String {
+    (0..3000)
+        .map(|i| format!("key{}: {}\n", i, "v".repeat(200)))
+        .collect()
+}
+
+#[test]
+fn test_crossing_the_budget_drops_undo_and_degrades() {
+    let mut harness = Harness::new(&document()).unwrap();
+    let budget = harness.state().memory_usage().total() / (1 << 20) + 1;
+    harness
+        .keys(&format!(":set memorybudget={}<CR>", budget))
+        .unwrap();
+    assert!(!harness.state().memory_degraded());
+
+    // Each substitution rewrites every value, so its undo state is about
+    // the size of the document
+    let mut edits = 0;
+    while !harness.state().memory_degraded() && edits < 20 {
+        let (from, to) = if edits % 2 == 0 {
+            ("v", "w")
+        } else {
+            ("w", "v")
+        };
+        harness.keys(&format!(":%s/{}/{}/g<CR>", from, to)).unwrap();
+        edits += 1;
+    }
+    assert!(harness.state().memory_degraded());
+    let usage = harness.state().memory_usage();
+    assert!(usage.total() <= budget << 20);
+    assert!(usage.undo_states <= edits);
+
+    harness.keys(":path $<CR>E").unwrap();
+    let message = harness.state().message().unwrap().text.clone();
+    assert!(message.contains("Expand-all is off"), "{}", message);
+
+    harness.keys(":memory<CR>").unwrap();
+    let message = harness.state().message().unwrap().text.clone();
+    assert!(message.contains("over budget"), "{}", message);
+
+    harness.keys(":set memorybudget=0<CR>").unwrap();
+    assert!(!harness.state().memory_degraded());
+}
+
+#[test]
+fn test_memory_reports_usage() {
+    let mut harness = Harness::new("a: 1\n").unwrap();
+    harness.keys("yy:memory<CR>").unwrap();
+    let message = harness.state().message().unwrap().text.clone();
+    assert!(message.starts_with("Memory: "), "{}", message);
+    assert!(message.contains("undo"), "{}", message);
+    assert!(message.contains("budget 2.0 GB"), "{}", message);
+    assert!(!harness.state().memory_degraded());
+}
+
+#[test]
+fn test_small_edits_keep_undo_small() {
+    let mut harness = Harness::new(&document()).unwrap();
+    let before = harness.state().memory_usage();
+    for i in 0..10 {
+        harness
+            .keys(&format!(":path $.key{}<CR>e<C-a><C-k>edited<CR>", i))
+            .unwrap();
+    }
+    let after = harness.state().memory_usage();
+    assert_eq!(after.undo_states, 11);
+    assert!(after.undo - before.undo < before.undo / 10);
+
+    harness.keys("uuuuuuuuuu").unwrap();
+    assert_eq!(harness.document().unwrap(), document());
+}
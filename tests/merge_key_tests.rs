@@ -0,0 +1,208 @@
+//! Tests for YAML merge key (`<<`) resolution.
+//!
+//! Merged entries are only a view: the document keeps its literal `<<` entry
+//! so that saving writes the merge key back out unchanged.
+
+use std::fs;
+use tempfile::NamedTempFile;
+use yamlquill::config::Config;
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::file::saver::save_yaml_file;
+use yamlquill::harness::Harness;
+
+const MERGE_YAML: &str = r#"defaults: &defaults
+  image: nginx
+  replicas: 1
+  port: 80
+web:
+  <<: *defaults
+  replicas: 3
+"#;
+
+fn load(yaml: &str) -> YamlTree {
+    YamlTree::with_source(parse_yaml_auto(yaml).unwrap(), Some(yaml.to_string()))
+}
+
+#[test]
+fn test_merged_entries_skip_overridden_keys() {
+    let tree = load(MERGE_YAML);
+    let web = tree.get_node(&[1]).unwrap();
+
+    let merged = tree.merged_entries(web);
+    let keys: Vec<&str> = merged.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys, vec!["image", "port"]);
+}
+
+#[test]
+fn test_merged_entries_from_sequence_first_source_wins() {
+    let yaml = r#"a: &a
+  x: 1
+  y: 1
+b: &b
+  y: 2
+  z: 2
+c:
+  <<: [*a, *b]
+"#;
+    let tree = load(yaml);
+    let c = tree.get_node(&[2]).unwrap();
+
+    let merged = tree.merged_entries(c);
+    let pairs: Vec<(String, String)> = merged
+        .iter()
+        .map(|(k, v)| (k.clone(), v.value().to_string()))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("x".to_string(), "1".to_string()),
+            ("y".to_string(), "1".to_string()),
+            ("z".to_string(), "2".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_merged_entries_resolve_nested_merges() {
+    let yaml = r#"base: &base
+  a: 1
+mid: &mid
+  <<: *base
+  b: 2
+leaf:
+  <<: *mid
+"#;
+    let tree = load(yaml);
+    let leaf = tree.get_node(&[2]).unwrap();
+
+    let keys: Vec<String> = tree
+        .merged_entries(leaf)
+        .into_iter()
+        .map(|(k, _)| k)
+        .collect();
+    assert_eq!(keys, vec!["b".to_string(), "a".to_string()]);
+}
+
+#[test]
+fn test_merged_entries_without_merge_key() {
+    let tree = load("a:\n  b: 1\n");
+    assert!(tree.merged_entries(tree.get_node(&[0]).unwrap()).is_empty());
+}
+
+#[test]
+fn test_resolve_merge_shows_inherited_lines() {
+    let mut state = EditorState::new_with_default_theme(load(MERGE_YAML));
+    let before = state.tree_view().lines().len();
+
+    state.set_resolve_merge(true);
+    let lines = state.tree_view().lines();
+    assert_eq!(lines.len(), before + 2);
+
+    let merged: Vec<&str> = lines
+        .iter()
+        .filter(|l| l.merged)
+        .filter_map(|l| l.key.as_deref())
+        .collect();
+    assert_eq!(merged, vec!["image", "port"]);
+
+    // The literal merge key is still listed
+    assert!(lines.iter().any(|l| l.key.as_deref() == Some("<<")));
+
+    state.set_resolve_merge(false);
+    assert_eq!(state.tree_view().lines().len(), before);
+}
+
+#[test]
+fn test_merged_lines_are_read_only() {
+    let mut state = EditorState::new_with_default_theme(load(MERGE_YAML));
+    state.set_resolve_merge(true);
+
+    let merged_path = state
+        .tree_view()
+        .lines()
+        .iter()
+        .find(|l| l.merged)
+        .unwrap()
+        .path
+        .clone();
+    state.cursor_mut().set_path(merged_path);
+
+    state.start_editing();
+    assert!(state.edit_buffer().is_none());
+
+    let error = state.delete_node_at_cursor().unwrap_err();
+    assert!(error.to_string().contains("merged"));
+    assert!(!state.is_dirty());
+
+    let error = state.indent_node().unwrap_err();
+    assert!(error.to_string().contains("merged"));
+    let error = state.wrap_node(None).unwrap_err();
+    assert!(error.to_string().contains("merged"));
+    let error = state.unwrap_node().unwrap_err();
+    assert!(error.to_string().contains("merged"));
+    assert!(!state.is_dirty());
+}
+
+#[test]
+fn test_dd_on_merged_line_deletes_nothing() {
+    let mut h = Harness::new(MERGE_YAML).unwrap();
+    h.keys(":set resolvemerge<CR>").unwrap();
+    let merged = h
+        .state()
+        .tree_view()
+        .lines()
+        .iter()
+        .find(|l| l.merged)
+        .unwrap()
+        .path
+        .clone();
+    h.state_mut().cursor_mut().set_path(merged);
+
+    h.keys("dd").unwrap();
+    assert_eq!(h.document().unwrap(), MERGE_YAML);
+    let message = h.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert!(
+        message.text.contains("Cannot modify merged key"),
+        "{}",
+        message.text
+    );
+
+    // Nothing was deleted, so there is nothing for `.` to repeat
+    h.keys(".").unwrap();
+    assert_eq!(h.document().unwrap(), MERGE_YAML);
+}
+
+#[test]
+fn test_disabling_resolution_moves_cursor_off_merged_line() {
+    let mut state = EditorState::new_with_default_theme(load(MERGE_YAML));
+    state.set_resolve_merge(true);
+
+    let merged_path = state
+        .tree_view()
+        .lines()
+        .iter()
+        .find(|l| l.merged)
+        .unwrap()
+        .path
+        .clone();
+    state.cursor_mut().set_path(merged_path);
+
+    state.set_resolve_merge(false);
+    assert_eq!(state.cursor().path(), &[1]);
+}
+
+#[test]
+fn test_save_keeps_original_merge_key() {
+    let mut state = EditorState::new_with_default_theme(load(MERGE_YAML));
+    state.set_resolve_merge(true);
+
+    let temp_file = NamedTempFile::new().unwrap();
+    save_yaml_file(temp_file.path(), state.tree(), &Config::default()).unwrap();
+
+    let saved = fs::read_to_string(temp_file.path()).unwrap();
+    assert!(saved.contains("<<: *defaults"));
+    assert!(!saved.contains("image: nginx\n  replicas: 3"));
+}
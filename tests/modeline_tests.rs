@@ -0,0 +1,75 @@
+//! Tests for applying `# yamlquill:` modelines to an editor buffer.
+
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+
+fn state_for(yaml: &str) -> EditorState {
+    let tree = YamlTree::with_source(parse_yaml_auto(yaml).unwrap(), Some(yaml.to_string()));
+    EditorState::new_with_default_theme(tree)
+}
+
+#[test]
+fn test_modeline_sets_indent_and_schema() {
+    let mut state = state_for("# yamlquill: indent=4 schema=./schema.json\nname: app\n");
+    state.apply_modeline();
+
+    assert_eq!(state.indent_size(), 4);
+    assert_eq!(state.schema_path(), Some("./schema.json"));
+    assert_eq!(state.to_config().indent_size, 4);
+}
+
+#[test]
+fn test_modeline_fold_level_collapses_deeper_containers() {
+    let yaml = "a:\n  b:\n    c: 1\n# yamlquill: foldlevel=1\n";
+    let mut state = state_for(yaml);
+    // a, b, c and the modeline comment itself
+    assert_eq!(state.tree_view().lines().len(), 4);
+
+    state.apply_modeline();
+
+    // `a` is expanded, `b` is folded
+    let lines = state.tree_view().lines();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].expanded);
+    assert!(!lines[1].expanded);
+}
+
+#[test]
+fn test_modeline_rejected_options_warn() {
+    let mut state = state_for("# yamlquill: nonumber autocmd=evil\nkey: 1\n");
+    state.apply_modeline();
+
+    assert!(!state.show_line_numbers());
+    let message = state.message().unwrap();
+    assert_eq!(message.level, MessageLevel::Warning);
+    assert!(message.text.contains("autocmd=evil"));
+}
+
+#[test]
+fn test_modeline_settings_are_buffer_local() {
+    let mut state = state_for("# yamlquill: indent=6 nonumber\nkey: 1\n");
+    state.apply_modeline();
+    assert_eq!(state.indent_size(), 6);
+
+    // Loading a file without a modeline reverts the previous buffer's options
+    let yaml = "key: 2\n";
+    let tree = YamlTree::with_source(parse_yaml_auto(yaml).unwrap(), Some(yaml.to_string()));
+    state.reload_tree(tree);
+    state.apply_modeline();
+
+    assert_eq!(state.indent_size(), 2);
+    assert!(state.show_line_numbers());
+    assert!(state.modeline().is_none());
+}
+
+#[test]
+fn test_no_modeline_leaves_settings_untouched() {
+    let mut state = state_for("key: 1\n");
+    state.set_indent_size(3);
+    state.apply_modeline();
+
+    assert_eq!(state.indent_size(), 3);
+    assert!(state.modeline().is_none());
+    assert!(state.message().is_none());
+}
@@ -0,0 +1,86 @@
+//! Tests for mouse wheel scrolling.
+
+use termion::event::{Event, MouseButton, MouseEvent};
+use yamlquill::harness::Harness;
+
+fn wheel(button: MouseButton) -> Event {
+    Event::Mouse(MouseEvent::Press(button, 1, 1))
+}
+
+fn numbered_list(count: usize) -> String {
+    (0..count).map(|i| format!("- item{}\n", i)).collect()
+}
+
+#[test]
+fn test_wheel_scrolls_viewport_and_leaves_cursor_in_view() {
+    let mut h = Harness::new(&numbered_list(50)).unwrap();
+    h.screen().unwrap();
+    h.state_mut().cursor_mut().set_path(vec![10]);
+
+    h.event(wheel(MouseButton::WheelDown)).unwrap();
+    assert_eq!(h.state().scroll_offset(), 3);
+    assert_eq!(h.state().cursor().path(), &[10]);
+
+    // Scrolling up past the top stops at the first line
+    h.event(wheel(MouseButton::WheelUp)).unwrap();
+    h.event(wheel(MouseButton::WheelUp)).unwrap();
+    assert_eq!(h.state().scroll_offset(), 0);
+    assert_eq!(h.state().cursor().path(), &[10]);
+}
+
+#[test]
+fn test_wheel_drags_cursor_that_would_leave_the_view() {
+    let mut h = Harness::new(&numbered_list(50)).unwrap();
+    h.screen().unwrap();
+    assert_eq!(h.state().cursor().path(), &[0]);
+
+    h.event(wheel(MouseButton::WheelDown)).unwrap();
+    assert_eq!(h.state().scroll_offset(), 3);
+    assert_eq!(h.state().cursor().path(), &[3]);
+
+    // Rendering keeps the scrolled viewport instead of snapping back
+    let screen = h.screen().unwrap();
+    assert!(screen.contains("item3"), "{}", screen);
+    assert!(!screen.contains("item2\n"), "{}", screen);
+}
+
+#[test]
+fn test_mousescroll_setting_controls_lines_per_tick() {
+    let mut h = Harness::new(&numbered_list(50)).unwrap();
+    h.screen().unwrap();
+    h.keys(":set mousescroll=5<CR>").unwrap();
+    assert_eq!(h.state().mouse_scroll_lines(), 5);
+
+    h.event(wheel(MouseButton::WheelDown)).unwrap();
+    assert_eq!(h.state().scroll_offset(), 5);
+
+    h.keys(":set mousescroll=0<CR>").unwrap();
+    assert_eq!(h.state().mouse_scroll_lines(), 5);
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text.contains("Invalid mousescroll")));
+}
+
+#[test]
+fn test_horizontal_wheel_scrolls_long_lines() {
+    let long = "x".repeat(200);
+    let mut h = Harness::new(&format!("short: a\nlong: {}\n", long)).unwrap();
+
+    h.event(wheel(MouseButton::WheelRight)).unwrap();
+    assert_eq!(h.state().horizontal_scroll(), 3);
+
+    // Shift+wheel down arrives as an SGR report termion leaves undecoded
+    h.event(Event::Unsupported(b"\x1b[<69;5;5M".to_vec()))
+        .unwrap();
+    assert_eq!(h.state().horizontal_scroll(), 6);
+    let screen = h.screen().unwrap();
+    assert!(!screen.contains("short: a"), "{}", screen);
+    assert!(screen.contains("t: a"), "{}", screen);
+
+    h.event(wheel(MouseButton::WheelLeft)).unwrap();
+    h.event(Event::Unsupported(b"\x1b[<68;5;5M".to_vec()))
+        .unwrap();
+    h.event(wheel(MouseButton::WheelLeft)).unwrap();
+    assert_eq!(h.state().horizontal_scroll(), 0);
+}
@@ -11,7 +11,7 @@ use yamlquill::config::Config;
 use yamlquill::document::node::{YamlNumber, YamlString, YamlValue};
 use yamlquill::document::parser::parse_yaml_auto;
 use yamlquill::document::tree::YamlTree;
-use yamlquill::file::saver::save_yaml_file;
+use yamlquill::file::saver::{save_yaml_file, yaml_text};
 
 #[test]
 fn test_parse_single_document() {
@@ -276,3 +276,90 @@ true
         _ => panic!("Expected MultiDoc after round-trip"),
     }
 }
+
+#[test]
+fn test_preview_fields_pin_values_to_record_previews() {
+    use yamlquill::harness::Harness;
+
+    let yaml = "\
+---
+name: web
+status:
+  phase: Running
+---
+name: db
+---
+name: cache
+status:
+  phase: Pending
+";
+    let mut h = Harness::new(yaml).unwrap().with_size(100, 10);
+    h.keys(":preview-field add status.phase<CR>").unwrap();
+    assert_eq!(h.state().preview_fields(), &["status.phase".to_string()]);
+
+    let screen = h.screen().unwrap();
+    let rows: Vec<&str> = screen.lines().take(3).collect();
+    assert!(
+        rows[0].contains("status.phase: Running | (2) {"),
+        "{}",
+        screen
+    );
+    // Records without the field keep their plain preview
+    assert!(!rows[1].contains("status.phase"), "{}", screen);
+    assert!(
+        rows[2].contains("status.phase: Pending | (2) {"),
+        "{}",
+        screen
+    );
+
+    // Pinned fields are saved with the rest of the settings
+    assert_eq!(
+        h.state().to_config().preview_fields,
+        vec!["status.phase".to_string()]
+    );
+
+    h.keys(":preview-field add status.phase<CR>").unwrap();
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text.contains("already pinned")));
+    h.keys(":preview-field remove status.phase<CR>").unwrap();
+    assert!(!h.screen().unwrap().contains("status.phase:"));
+}
+
+#[test]
+fn test_edit_keeps_other_documents_verbatim() {
+    let yaml = "\
+# services
+---
+name: 'web'   # quoted
+ports: [80,  443]
+--- # worker
+name: worker
+replicas: 1
+";
+    let node = parse_yaml_auto(yaml).unwrap();
+    let mut tree = YamlTree::with_source(node, Some(yaml.to_string()));
+    let YamlValue::MultiDoc(docs) = tree.root().value() else {
+        panic!("Expected MultiDoc variant");
+    };
+    let YamlValue::Object(worker) = docs[1].value() else {
+        panic!("Expected an object");
+    };
+    let replicas = worker.get_index_of("replicas").unwrap();
+    *tree.get_node_mut(&[1, replicas]).unwrap().value_mut() =
+        YamlValue::Number(YamlNumber::Integer(3));
+
+    assert_eq!(
+        yaml_text(&tree).unwrap(),
+        "\
+# services
+---
+name: 'web'   # quoted
+ports: [80,  443]
+--- # worker
+name: worker
+replicas: 3
+"
+    );
+}
@@ -224,3 +224,38 @@ fn test_last_sibling_navigation() {
     // Should be at third key (index 2)
     assert_eq!(state.cursor().path(), &[2]);
 }
+
+#[test]
+fn test_scrolloff_keeps_context_around_cursor() {
+    use yamlquill::document::parser::parse_yaml_auto;
+
+    let yaml: String = (0..30).map(|i| format!("- {}\n", i)).collect();
+    let mut state =
+        EditorState::new_with_default_theme(YamlTree::new(parse_yaml_auto(&yaml).unwrap()));
+    state.set_scrolloff(3);
+    let viewport = 10;
+
+    // Moving down scrolls once the cursor is within 3 lines of the bottom
+    state.cursor_mut().set_path(vec![6]);
+    state.adjust_scroll_to_cursor(viewport);
+    assert_eq!(state.scroll_offset(), 0);
+    state.cursor_mut().set_path(vec![7]);
+    state.adjust_scroll_to_cursor(viewport);
+    assert_eq!(state.scroll_offset(), 1);
+
+    // Moving back up keeps 3 lines above
+    state.cursor_mut().set_path(vec![3]);
+    state.adjust_scroll_to_cursor(viewport);
+    assert_eq!(state.scroll_offset(), 0);
+
+    // The view doesn't scroll past the end of the document
+    state.cursor_mut().set_path(vec![29]);
+    state.adjust_scroll_to_cursor(viewport);
+    assert_eq!(state.scroll_offset(), 20);
+
+    // A margin larger than half the viewport keeps the cursor centered
+    state.set_scrolloff(99);
+    state.cursor_mut().set_path(vec![15]);
+    state.adjust_scroll_to_cursor(viewport);
+    assert_eq!(state.scroll_offset(), 11);
+}
@@ -0,0 +1,67 @@
+//! Tests for the `gD` node diff popup.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::editor::review::ChangeKind;
+use yamlquill::harness::Harness;
+
+const DEPLOYMENT: &str = "\
+name: api
+spec:
+  replicas: 3
+  image: nginx
+";
+
+fn open(dir: &TempDir) -> Harness {
+    let path = dir.path().join("deploy.yaml");
+    fs::write(&path, DEPLOYMENT).unwrap();
+    Harness::open(path).unwrap()
+}
+
+#[test]
+fn test_gd_shows_changes_under_the_node() {
+    let dir = TempDir::new().unwrap();
+    let mut h = open(&dir);
+    h.keys(":path $.spec.replicas<CR>e<BS>5<CR>").unwrap();
+    h.keys(":path $.name<CR>e<BS><BS><BS>web<CR>").unwrap();
+    h.keys(":path $.spec<CR>gD").unwrap();
+
+    let diff = h.state().node_diff().unwrap();
+    assert_eq!(diff.path, ".spec");
+    assert_eq!(diff.changes.len(), 1);
+    let change = &diff.changes[0];
+    assert_eq!(change.kind, ChangeKind::Modified);
+    assert_eq!(change.relative_label(), ".replicas");
+    assert_eq!(change.base.as_ref().unwrap().value().to_string(), "3");
+    assert_eq!(change.current.as_ref().unwrap().value().to_string(), "5");
+
+    h.keys("j").unwrap();
+    assert!(h.state().node_diff().is_none());
+    assert_eq!(h.state().cursor().path(), &[1]);
+}
+
+#[test]
+fn test_gd_on_unchanged_and_new_nodes() {
+    let dir = TempDir::new().unwrap();
+    let mut h = open(&dir);
+    h.keys(":path $.spec<CR>gD").unwrap();
+    assert!(h.state().node_diff().unwrap().changes.is_empty());
+
+    h.keys("<Esc>:path $.spec<CR>g[gD").unwrap();
+    let diff = h.state().node_diff().unwrap();
+    assert_eq!(diff.path, ".spec[0]");
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].kind, ChangeKind::Added);
+    assert_eq!(diff.changes[0].relative_label(), ".");
+}
+
+#[test]
+fn test_gd_needs_a_file() {
+    let mut h = Harness::new(DEPLOYMENT).unwrap();
+    h.keys("gD").unwrap();
+    assert!(h.state().node_diff().is_none());
+    assert_eq!(
+        h.state().message().map(|m| m.text.clone()).as_deref(),
+        Some("No file name")
+    );
+}
@@ -0,0 +1,97 @@
+//! Tests that marks, jumps, search results, diagnostics and folds follow
+//! their nodes when edits shift sibling indices.
+
+use yamlquill::editor::diagnostics::Diagnostic;
+use yamlquill::harness::Harness;
+
+const DOC: &str = "\
+items:
+  - name: alpha
+  - name: beta
+  - name: gamma
+";
+
+/// Returns the value at the cursor, as shown in the tree view.
+fn cursor_value(harness: &Harness) -> String {
+    let state = harness.state();
+    state
+        .tree()
+        .get_node(state.cursor().path())
+        .map(|node| node.value().to_string())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_mark_follows_node_after_delete_above() {
+    let mut harness = Harness::new(DOC).unwrap();
+    // Mark gamma's name, then delete alpha
+    harness.keys("jjjjjjma").unwrap();
+    assert_eq!(cursor_value(&harness), "gamma");
+    harness.keys("ggjdd").unwrap();
+
+    harness.keys("gg'a").unwrap();
+    assert_eq!(cursor_value(&harness), "gamma");
+    assert_eq!(harness.state().cursor().path(), &[0, 1, 0]);
+}
+
+#[test]
+fn test_mark_on_deleted_node_is_removed() {
+    let mut harness = Harness::new(DOC).unwrap();
+    harness.keys("jjjmadd").unwrap();
+    assert!(harness.state().marks().get_mark('a').is_none());
+}
+
+#[test]
+fn test_mark_follows_node_through_undo() {
+    let mut harness = Harness::new(DOC).unwrap();
+    harness.keys("jjjjjjmaggjdd").unwrap();
+    assert_eq!(harness.state().marks().get_mark('a'), Some(&vec![0, 1, 0]));
+    harness.keys("u").unwrap();
+    assert_eq!(harness.state().marks().get_mark('a'), Some(&vec![0, 2, 0]));
+}
+
+#[test]
+fn test_fold_follows_node_after_paste_above() {
+    let mut harness = Harness::new(DOC).unwrap();
+    // Collapse gamma, then paste a copy of alpha before alpha
+    harness.keys("jjjjjh").unwrap();
+    assert!(!harness.state().tree_view().is_expanded(&[0, 2]));
+    harness.keys("ggjyyP").unwrap();
+
+    let tree_view = harness.state().tree_view();
+    assert!(!tree_view.is_expanded(&[0, 3]));
+    assert!(tree_view.is_expanded(&[0, 2]));
+    assert!(tree_view.is_expanded(&[0, 1]));
+}
+
+#[test]
+fn test_search_results_and_diagnostics_follow_nodes() {
+    let mut harness = Harness::new(DOC).unwrap();
+    harness.state_mut().set_diagnostics(
+        "lint",
+        vec![Diagnostic::warning(
+            "lint",
+            vec![0, 2, 0],
+            "$.items[2].name".to_string(),
+            "check this".to_string(),
+        )],
+    );
+    harness.keys("/a<CR>").unwrap();
+    let matches = harness.state().search_results_info().unwrap().1;
+
+    harness.state_mut().cursor_mut().set_path(vec![0, 0]);
+    harness.state_mut().delete_node_at_cursor().unwrap();
+
+    let state = harness.state();
+    assert_eq!(state.diagnostics().items()[0].path, vec![0, 1, 0]);
+    assert_eq!(
+        state
+            .tree_view()
+            .diagnostic_markers()
+            .keys()
+            .collect::<Vec<_>>(),
+        vec![&vec![0, 1, 0]]
+    );
+    // alpha's match went with it; the others moved up
+    assert!(state.search_results_info().unwrap().1 < matches);
+}
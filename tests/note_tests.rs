@@ -0,0 +1,135 @@
+//! Integration tests for notes attached to nodes.
+
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use termion::event::{Event, Key};
+use yamlquill::editor::notes::notes_file_path;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::file::loader::load_yaml_file;
+use yamlquill::input::InputHandler;
+use yamlquill::theme::get_builtin_theme;
+use yamlquill::ui::UI;
+
+fn open(path: &Path) -> EditorState {
+    let mut state = EditorState::new_with_default_theme(load_yaml_file(path).unwrap());
+    state.set_filename(path.display().to_string());
+    state.load_notes();
+    state
+}
+
+fn type_command(handler: &mut InputHandler, state: &mut EditorState, command: &str) {
+    for c in format!(":{}\n", command).chars() {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), state)
+            .unwrap();
+    }
+}
+
+fn screen(state: &mut EditorState) -> String {
+    let ui = UI::new(get_builtin_theme("default-dark").unwrap());
+    let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+    ui.render(&mut terminal, state).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..12)
+        .map(|y| (0..60).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n")
+        .collect()
+}
+
+#[test]
+fn test_note_is_stored_in_sidecar() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "name: web\nreplicas: 5\n").unwrap();
+    let mut state = open(&path);
+    let mut handler = InputHandler::new();
+
+    handler
+        .handle_event(Event::Key(Key::Char('j')), &mut state)
+        .unwrap();
+    type_command(&mut handler, &mut state, "note Why 5? Staging runs 3");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Info);
+
+    // The document is untouched
+    assert!(!state.is_dirty());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "name: web\nreplicas: 5\n"
+    );
+
+    let sidecar = fs::read_to_string(notes_file_path(&path)).unwrap();
+    assert!(sidecar.contains("Why 5? Staging runs 3"));
+    assert!(sidecar.contains("replicas"));
+
+    let mut reopened = open(&path);
+    reopened.cursor_mut().set_path(vec![1]);
+    assert_eq!(reopened.note_at_cursor(), Some("Why 5? Staging runs 3"));
+}
+
+#[test]
+fn test_indicator_and_popup() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "name: web\nreplicas: 5\n").unwrap();
+    let mut state = open(&path);
+    let mut handler = InputHandler::new();
+
+    state.cursor_mut().set_path(vec![1]);
+    type_command(&mut handler, &mut state, "note check with ops");
+
+    let text = screen(&mut state);
+    let replicas_line = text.lines().find(|l| l.contains("replicas")).unwrap();
+    assert!(replicas_line.contains('✎'));
+    let name_line = text.lines().find(|l| l.contains("name")).unwrap();
+    assert!(!name_line.contains('✎'));
+
+    type_command(&mut handler, &mut state, "note");
+    assert!(state.note_popup().is_some());
+    assert!(screen(&mut state).contains("check with ops"));
+
+    // Any key closes the popup
+    handler
+        .handle_event(Event::Key(Key::Char('x')), &mut state)
+        .unwrap();
+    assert!(state.note_popup().is_none());
+
+    type_command(&mut handler, &mut state, "note rm");
+    assert!(state.notes().is_empty());
+    assert!(!notes_file_path(&path).exists());
+    assert!(!screen(&mut state).contains('✎'));
+}
+
+#[test]
+fn test_note_follows_node() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "name: web\nreplicas: 5\n").unwrap();
+    let mut state = open(&path);
+    let mut handler = InputHandler::new();
+
+    state.cursor_mut().set_path(vec![1]);
+    type_command(&mut handler, &mut state, "note pinned");
+
+    // Deleting the node above moves replicas to index 0; the note follows it
+    state.cursor_mut().set_path(vec![0]);
+    state.delete_node_at_cursor().unwrap();
+    let text = screen(&mut state);
+    let replicas_line = text.lines().find(|l| l.contains("replicas")).unwrap();
+    assert!(replicas_line.contains('✎'));
+
+    type_command(&mut handler, &mut state, "notes");
+    assert_eq!(state.note_popup().unwrap().len(), 1);
+}
+
+#[test]
+fn test_note_without_file_name() {
+    let mut state = EditorState::new_with_default_theme(yamlquill::document::tree::YamlTree::new(
+        yamlquill::document::parser::parse_yaml_auto("a: 1\n").unwrap(),
+    ));
+    let mut handler = InputHandler::new();
+    type_command(&mut handler, &mut state, "note hello");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Error);
+    assert!(state.notes().is_empty());
+}
@@ -0,0 +1,108 @@
+//! Integration tests for YAMLPath bulk edits (`:path-set`, `:path-delete`).
+
+use termion::event::{Event, Key};
+use yamlquill::document::node::YamlValue;
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::input::InputHandler;
+
+const PODS: &str = "\
+containers:
+  - name: web
+    imagePullPolicy: IfNotPresent
+  - name: sidecar
+    imagePullPolicy: Never
+replicas: 2
+";
+
+fn state_for(yaml: &str) -> EditorState {
+    EditorState::new_with_default_theme(YamlTree::new(parse_yaml_auto(yaml).unwrap()))
+}
+
+fn type_command(handler: &mut InputHandler, state: &mut EditorState, command: &str) {
+    for c in format!(":{}\n", command).chars() {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), state)
+            .unwrap();
+    }
+}
+
+fn value_at(state: &EditorState, path: &[usize]) -> String {
+    state.tree().get_node(path).unwrap().value().to_string()
+}
+
+#[test]
+fn test_path_set_all_matches_single_undo() {
+    let mut state = state_for(PODS);
+    let mut handler = InputHandler::new();
+
+    type_command(
+        &mut handler,
+        &mut state,
+        "path-set $..imagePullPolicy Always",
+    );
+    assert_eq!(value_at(&state, &[0, 0, 1]), "Always");
+    assert_eq!(value_at(&state, &[0, 1, 1]), "Always");
+    assert!(state.message().unwrap().text.contains("Set 2 nodes"));
+    assert!(state.is_dirty());
+
+    // One undo restores both
+    assert!(state.undo());
+    assert_eq!(value_at(&state, &[0, 0, 1]), "IfNotPresent");
+    assert_eq!(value_at(&state, &[0, 1, 1]), "Never");
+}
+
+#[test]
+fn test_path_set_types_values() {
+    let mut state = state_for(PODS);
+    let mut handler = InputHandler::new();
+
+    type_command(&mut handler, &mut state, "path-set $.replicas 5");
+    assert!(matches!(
+        state.tree().get_node(&[1]).unwrap().value(),
+        YamlValue::Number(_)
+    ));
+
+    type_command(&mut handler, &mut state, "path-set $.replicas \"5\"");
+    assert!(matches!(
+        state.tree().get_node(&[1]).unwrap().value(),
+        YamlValue::String(_)
+    ));
+
+    // Containers are skipped
+    type_command(&mut handler, &mut state, "path-set $.containers x");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Warning);
+}
+
+#[test]
+fn test_path_delete() {
+    let mut state = state_for(PODS);
+    let mut handler = InputHandler::new();
+    state.cursor_mut().set_path(vec![0, 1, 1]);
+
+    type_command(&mut handler, &mut state, "path-delete $..imagePullPolicy");
+    assert!(state.message().unwrap().text.contains("Deleted 2 nodes"));
+
+    assert_eq!(state.path_delete("$..imagePullPolicy").unwrap(), 0);
+    assert!(state.tree().get_node(state.cursor().path()).is_some());
+
+    assert!(state.undo());
+    assert_eq!(value_at(&state, &[0, 1, 1]), "Never");
+}
+
+#[test]
+fn test_path_edit_errors() {
+    let mut state = state_for(PODS);
+    let mut handler = InputHandler::new();
+
+    type_command(&mut handler, &mut state, "path-set $..imagePullPolicy");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Error);
+
+    type_command(&mut handler, &mut state, "path-delete nodollar");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Error);
+
+    type_command(&mut handler, &mut state, "path-delete $.missing");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Warning);
+    assert!(!state.is_dirty());
+}
@@ -0,0 +1,104 @@
+//! Integration tests for undo history persisted across sessions.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::config::trust::TrustStore;
+use yamlquill::config::Config;
+use yamlquill::document::node::{YamlNumber, YamlValue};
+use yamlquill::editor::state::EditorState;
+use yamlquill::editor::undo::undo_file_path;
+use yamlquill::file::loader::load_yaml_file;
+use yamlquill::file::saver::save_yaml_file;
+
+fn open(path: &std::path::Path, persistent_undo: bool) -> EditorState {
+    let tree = load_yaml_file(path).unwrap();
+    let mut state = EditorState::new_with_default_theme(tree);
+    state.set_filename(path.to_string_lossy().to_string());
+    state.set_persistent_undo(persistent_undo);
+    let mut trust = TrustStore::new();
+    trust.trust(path.parent().unwrap(), None);
+    state.set_trust_store(trust);
+    state.load_undo_history();
+    state
+}
+
+fn edit_and_save(state: &mut EditorState, path: &std::path::Path, value: i64) {
+    state.cursor_mut().set_path(vec![0]);
+    state.set_edit_buffer_for_test(value.to_string());
+    state.commit_editing().unwrap();
+    save_yaml_file(path, state.tree(), &Config::default()).unwrap();
+    state.write_undo_history();
+}
+
+fn replicas(state: &EditorState) -> YamlValue {
+    state.tree().get_node(&[0]).unwrap().value().clone()
+}
+
+#[test]
+fn test_undo_survives_reopen() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+
+    let mut state = open(&path, true);
+    edit_and_save(&mut state, &path, 3);
+    assert!(undo_file_path(&path).exists(), "{:?}", state.message());
+
+    let mut reopened = open(&path, true);
+    assert_eq!(
+        replicas(&reopened),
+        YamlValue::Number(YamlNumber::Integer(3))
+    );
+    assert!(reopened.undo());
+    assert_eq!(
+        replicas(&reopened),
+        YamlValue::Number(YamlNumber::Integer(1))
+    );
+}
+
+#[test]
+fn test_history_ignored_after_external_change() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+
+    let mut state = open(&path, true);
+    edit_and_save(&mut state, &path, 3);
+    fs::write(&path, "replicas: 9\n").unwrap();
+
+    let mut reopened = open(&path, true);
+    assert!(!reopened.undo());
+}
+
+#[test]
+fn test_disabled_by_default() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+
+    let mut state = open(&path, false);
+    edit_and_save(&mut state, &path, 3);
+
+    assert!(!undo_file_path(&path).exists());
+}
+
+#[test]
+fn test_history_ignored_until_trusted() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, "replicas: 1\n").unwrap();
+    let mut state = open(&path, true);
+    edit_and_save(&mut state, &path, 3);
+
+    let tree = load_yaml_file(&path).unwrap();
+    let mut untrusted = EditorState::new_with_default_theme(tree);
+    untrusted.set_filename(path.to_string_lossy().to_string());
+    untrusted.set_persistent_undo(true);
+    untrusted.load_undo_history();
+    assert!(untrusted
+        .message()
+        .unwrap()
+        .text
+        .starts_with("Ignored untrusted undo file"));
+    assert!(!untrusted.undo());
+}
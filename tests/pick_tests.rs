@@ -0,0 +1,44 @@
+//! Integration tests for `--pick` mode.
+
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::{EditorState, PickOutput};
+
+fn picker(yaml: &str, mode: PickOutput, cursor: Vec<usize>) -> EditorState {
+    let tree = YamlTree::new(parse_yaml_auto(yaml).unwrap());
+    let mut state = EditorState::new_with_default_theme(tree);
+    state.set_pick_mode(Some(mode));
+    state.cursor_mut().set_path(cursor);
+    state
+}
+
+const DOC: &str = "spec:\n  replicas: 3\n  containers:\n    - name: web\n      image: nginx\n";
+
+#[test]
+fn test_pick_path() {
+    let mut state = picker(DOC, PickOutput::Path, vec![0, 1, 0, 1]);
+    assert!(state.pick_at_cursor());
+    assert_eq!(state.picked(), Some(".spec.containers[0].image"));
+}
+
+#[test]
+fn test_pick_scalar_value_is_raw() {
+    let mut state = picker(DOC, PickOutput::Value, vec![0, 1, 0, 0]);
+    assert!(state.pick_at_cursor());
+    assert_eq!(state.picked(), Some("web"));
+}
+
+#[test]
+fn test_pick_container_value_is_yaml() {
+    let mut state = picker(DOC, PickOutput::Value, vec![0, 1, 0]);
+    assert!(state.pick_at_cursor());
+    assert_eq!(state.picked(), Some("name: web\nimage: nginx"));
+}
+
+#[test]
+fn test_nothing_picked_outside_pick_mode() {
+    let tree = YamlTree::new(parse_yaml_auto(DOC).unwrap());
+    let mut state = EditorState::new_with_default_theme(tree);
+    assert!(!state.pick_at_cursor());
+    assert_eq!(state.picked(), None);
+}
@@ -0,0 +1,110 @@
+//! Tests for the pin panel.
+
+use termion::event::{Event, MouseButton, MouseEvent};
+use yamlquill::editor::state::{MessageLevel, MAX_PINS};
+use yamlquill::harness::Harness;
+
+const VALUES: &str = "\
+server:
+  replicas: 3
+  image: nginx
+limits:
+  cpu: 500m
+";
+
+#[test]
+fn test_pin_and_jump() {
+    let mut h = Harness::new(VALUES).unwrap().with_size(60, 16);
+    h.keys("jj:pin<CR>").unwrap();
+    assert_eq!(h.state().pins(), &[vec![0, 1]]);
+    h.keys("G:pin<CR>").unwrap();
+    assert_eq!(h.state().pins().len(), 2);
+
+    h.keys("g1").unwrap();
+    assert_eq!(h.state().cursor().path(), &[0, 1]);
+    h.keys("g2").unwrap();
+    assert_eq!(h.state().cursor().path(), &[1, 0]);
+
+    h.keys("g3").unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.text, "No pin 3");
+    assert_eq!(message.level, MessageLevel::Error);
+}
+
+#[test]
+fn test_panel_shows_live_values() {
+    let mut h = Harness::new(VALUES).unwrap().with_size(60, 16);
+    h.keys("j:pin<CR>").unwrap();
+    let screen = h.screen().unwrap();
+    assert!(screen.contains("1 server.replicas  3"), "{}", screen);
+
+    // Edits show up in the panel
+    h.keys("e<BS>7<CR>").unwrap();
+    let screen = h.screen().unwrap();
+    assert!(screen.contains("1 server.replicas  7"), "{}", screen);
+}
+
+#[test]
+fn test_pins_follow_their_nodes() {
+    let mut h = Harness::new(VALUES).unwrap();
+    h.keys("G:pin<CR>").unwrap();
+    assert_eq!(h.state().pins(), &[vec![1, 0]]);
+
+    // Deleting the first mapping moves the pinned node up
+    h.keys("ggdd").unwrap();
+    assert_eq!(h.state().pins(), &[vec![0, 0]]);
+
+    // Deleting the pinned node drops the pin
+    h.keys("jdd").unwrap();
+    assert!(h.state().pins().is_empty());
+}
+
+#[test]
+fn test_unpin() {
+    let mut h = Harness::new(VALUES).unwrap();
+    h.keys("j:pin<CR>j:pin<CR>").unwrap();
+    h.keys(":unpin<CR>").unwrap();
+    assert_eq!(h.state().pins(), &[vec![0, 0]]);
+    h.keys(":unpin 1<CR>").unwrap();
+    assert!(h.state().pins().is_empty());
+
+    h.keys(":pin<CR>:pin<CR>").unwrap();
+    assert_eq!(h.state().message().unwrap().text, "Already pinned as 1");
+    h.keys(":unpin all<CR>").unwrap();
+    assert!(h.state().pins().is_empty());
+}
+
+#[test]
+fn test_pin_limit() {
+    let yaml: String = (0..=MAX_PINS).map(|i| format!("k{}: {}\n", i, i)).collect();
+    let mut h = Harness::new(&yaml).unwrap();
+    for _ in 0..MAX_PINS {
+        h.keys(":pin<CR>j").unwrap();
+    }
+    h.keys(":pin<CR>").unwrap();
+    assert_eq!(h.state().pins().len(), MAX_PINS);
+    assert!(h
+        .state()
+        .message()
+        .unwrap()
+        .text
+        .contains("pins are in use"));
+}
+
+#[test]
+fn test_click_pin_jumps() {
+    let mut h = Harness::new(VALUES).unwrap().with_size(60, 16);
+    h.keys("j:pin<CR>gg").unwrap();
+    let screen = h.screen().unwrap();
+    let row = screen
+        .lines()
+        .position(|l| l.starts_with("1 server.replicas"))
+        .unwrap() as u16;
+    h.event(Event::Mouse(MouseEvent::Press(
+        MouseButton::Left,
+        3,
+        row + 1,
+    )))
+    .unwrap();
+    assert_eq!(h.state().cursor().path(), &[0, 0]);
+}
@@ -0,0 +1,57 @@
+//! Tests for the CI pipeline graph (`:graph`).
+
+use yamlquill::harness::Harness;
+
+const WORKFLOW: &str = "\
+name: ci
+on: push
+jobs:
+  test:
+    needs: build
+    runs-on: ubuntu-latest
+  build:
+    runs-on: ubuntu-latest
+  deploy:
+    needs: [test]
+    runs-on: ubuntu-latest
+";
+
+#[test]
+fn test_graph_lists_jobs_in_dependency_order() {
+    let mut h = Harness::new(WORKFLOW).unwrap();
+    h.keys(":graph<CR>").unwrap();
+    let graph = h.state().pipeline_graph().unwrap();
+    let jobs: Vec<&str> = graph
+        .rows
+        .iter()
+        .filter(|row| row.job.is_some())
+        .map(|row| row.text.trim())
+        .collect();
+    assert_eq!(
+        jobs,
+        ["`-- build", "`-- test    <- build", "`-- deploy  <- test"]
+    );
+}
+
+#[test]
+fn test_enter_jumps_to_job_definition() {
+    let mut h = Harness::new(WORKFLOW).unwrap();
+    h.keys(":graph<CR>jj<CR>").unwrap();
+    assert!(h.state().pipeline_graph().is_none());
+    assert_eq!(h.state().cursor().path(), &[2, 2]);
+
+    h.keys(":graph<CR><Esc>").unwrap();
+    assert!(h.state().pipeline_graph().is_none());
+    assert_eq!(h.state().cursor().path(), &[2, 2]);
+}
+
+#[test]
+fn test_graph_needs_a_pipeline() {
+    let mut h = Harness::new("a: 1\n").unwrap();
+    h.keys(":graph<CR>").unwrap();
+    assert!(h.state().pipeline_graph().is_none());
+    assert_eq!(
+        h.state().message().map(|m| m.text.clone()).as_deref(),
+        Some("No GitHub Actions or GitLab CI jobs in this document")
+    );
+}
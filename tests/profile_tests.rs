@@ -0,0 +1,90 @@
+//! Tests for `:profile export` and `:profile import`.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::editor::state::MessageLevel;
+use yamlquill::harness::Harness;
+
+const YAML: &str = "name: api\nreplicas: 2\n";
+
+fn settings(h: &Harness) -> String {
+    toml::to_string(&h.state().to_config()).unwrap()
+}
+
+#[test]
+fn test_export_and_import_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let profile = dir.path().join("work.toml");
+    let profile = profile.display();
+
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys(":set scrolloff=4<CR>:set typebadges<CR>:preview-field add status.phase<CR>")
+        .unwrap();
+    h.keys(&format!(":profile export {}<CR>", profile)).unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        format!("Profile written to {}", profile)
+    );
+
+    let mut other = Harness::new(YAML).unwrap();
+    other
+        .keys(&format!(":profile import {}<CR>", profile))
+        .unwrap();
+    let message = other.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Info);
+    assert_eq!(
+        message.text,
+        format!(
+            "Imported {}: 3 settings changed (:set save keeps them)",
+            profile
+        )
+    );
+    assert!(other.state().show_type_badges());
+    assert_eq!(settings(&other), settings(&h));
+}
+
+#[test]
+fn test_import_reports_conflicts_and_unknown_keys() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("team.toml");
+    fs::write(
+        &path,
+        "theme = \"nord\"\nscrolloff = 2\nkeymap = \"emacs\"\n",
+    )
+    .unwrap();
+
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys(":set scrolloff=5<CR>").unwrap();
+    h.keys(&format!(":profile import {}<CR>", path.display()))
+        .unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Warning);
+    assert_eq!(
+        message.text,
+        format!(
+            "Imported {}: 2 settings changed; replaced your scrolloff; ignored unknown keymap (:set save keeps them)",
+            path.display()
+        )
+    );
+    assert_eq!(h.state().current_theme(), "nord");
+}
+
+#[test]
+fn test_invalid_profile_changes_nothing() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("bad.toml");
+    fs::write(&path, "scrolloff = 2\ntheme = \"no-such-theme\"\n").unwrap();
+
+    let mut h = Harness::new(YAML).unwrap();
+    let before = settings(&h);
+    h.keys(&format!(":profile import {}<CR>", path.display()))
+        .unwrap();
+    assert_eq!(h.state().message().unwrap().level, MessageLevel::Error);
+    assert_eq!(settings(&h), before);
+
+    h.keys(":profile import<CR>").unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "Usage: :profile export|import <file>"
+    );
+}
@@ -0,0 +1,58 @@
+//! Tests for Prometheus mode (`:set prometheus`).
+
+use yamlquill::harness::Harness;
+
+const RULES: &str = "\
+groups:
+  - name: api
+    rules:
+      - alert: HighErrorRate
+        expr: sum(rate(errors_total[5m])) > 1
+        labels:
+          severity: page
+      - record: job:errors:rate5m
+        expr: sum by (job) (rate(errors_total[5m])
+";
+
+fn previews(h: &Harness) -> Vec<String> {
+    h.state()
+        .tree_view()
+        .lines()
+        .iter()
+        .map(|line| line.value_preview.clone())
+        .collect()
+}
+
+#[test]
+fn test_rules_fold_to_names() {
+    let mut h = Harness::new(RULES).unwrap();
+    h.keys(":set prometheus<CR>").unwrap();
+    assert!(h.state().prometheus());
+
+    let previews = previews(&h);
+    assert_eq!(previews.len(), 6, "{:?}", previews);
+    assert_eq!(previews[4], "alert: HighErrorRate");
+    assert_eq!(previews[5], "record: job:errors:rate5m");
+}
+
+#[test]
+fn test_expr_fields_are_checked() {
+    let mut h = Harness::new(RULES).unwrap();
+    assert!(h.state().diagnostics().is_empty());
+
+    h.keys(":set prom<CR>").unwrap();
+    let diagnostics = h.state().diagnostics().items();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].path, vec![0, 0, 1, 1, 1]);
+    assert_eq!(diagnostics[0].message, "PromQL: expected ')' at column 37");
+
+    // Fixing the expression clears it; undo brings it back
+    h.keys(":path $.groups[0].rules[1].expr<CR>e<End>)<CR>")
+        .unwrap();
+    assert!(h.state().diagnostics().is_empty());
+    h.keys("u").unwrap();
+    assert_eq!(h.state().diagnostics().len(), 1);
+
+    h.keys(":set noprom<CR>").unwrap();
+    assert!(h.state().diagnostics().is_empty());
+}
@@ -0,0 +1,84 @@
+//! Tests for the quickfix list: `:copen`, `:cclose`, `:cnext`/`:cprev`
+//! and the sources that fill it.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::harness::Harness;
+
+const YAML: &str = "\
+name: api
+replicas: two
+ports:
+- 80
+- 443
+image: api:1.0
+";
+
+#[test]
+fn test_search_matches_fill_the_list() {
+    let mut h = Harness::new(YAML).unwrap().with_size(100, 20);
+    h.keys("/api<CR>:copen search<CR>").unwrap();
+    let panel = h.state().quickfix_panel().unwrap();
+    assert_eq!(panel.title, "Search");
+    let entries: Vec<_> = panel
+        .entries
+        .iter()
+        .map(|e| (e.location.as_str(), e.message.as_str()))
+        .collect();
+    assert_eq!(
+        entries,
+        [("$[\"name\"]", "api"), ("$[\"image\"]", "api:1.0")]
+    );
+    assert!(h.screen().unwrap().contains("Search (2)"));
+
+    h.keys("j<CR>").unwrap();
+    assert!(h.state().quickfix_panel().is_none());
+    assert_eq!(h.state().cursor().path(), [3]);
+
+    // The list stays after the panel closes; :copen shows it again
+    h.keys(":cprev<CR>").unwrap();
+    assert_eq!(h.state().message().unwrap().text, "(1/2) api");
+    assert_eq!(h.state().cursor().path(), [0]);
+    h.keys(":copen<CR>").unwrap();
+    assert_eq!(h.state().quickfix_panel().unwrap().selected_index, 0);
+    h.keys(":cclose<CR>").unwrap();
+    assert!(h.state().quickfix_panel().is_none());
+}
+
+#[test]
+fn test_validation_fills_the_list() {
+    let dir = TempDir::new().unwrap();
+    let schema = dir.path().join("schema.json");
+    fs::write(
+        &schema,
+        r#"{"type": "object", "properties": {"replicas": {"type": "integer"}}}"#,
+    )
+    .unwrap();
+
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys(&format!(":validate {}<CR>", schema.display()))
+        .unwrap();
+    assert!(h.state().quickfix_panel().is_none());
+    h.keys(":cnext<CR>").unwrap();
+    assert_eq!(h.state().cursor().path(), [1]);
+    assert!(h.state().message().unwrap().text.starts_with("(1/1) "));
+
+    h.keys(":copen diagnostics<CR>").unwrap();
+    assert_eq!(h.state().quickfix_panel().unwrap().title, "Diagnostics");
+
+    h.keys("<Esc>:copen nope<CR>").unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "Unknown quickfix source: nope (diagnostics, search)"
+    );
+}
+
+#[test]
+fn test_empty_sources() {
+    let mut h = Harness::new(YAML).unwrap();
+    h.keys(":copen<CR>").unwrap();
+    assert_eq!(h.state().message().unwrap().text, "Quickfix list is empty");
+    assert!(h.state().quickfix_panel().is_none());
+    h.keys(":cn<CR>").unwrap();
+    assert_eq!(h.state().message().unwrap().text, "Quickfix list is empty");
+}
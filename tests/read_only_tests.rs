@@ -0,0 +1,83 @@
+//! Tests for read-only buffers (`--read-only`, `:view`).
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::harness::Harness;
+
+const DOC: &str = "name: web\nports:\n  - 80\n  - 443\n";
+
+fn read_only(yaml: &str) -> Harness {
+    let mut harness = Harness::new(yaml).unwrap();
+    harness.state_mut().set_read_only(true);
+    harness
+}
+
+fn message(harness: &Harness) -> String {
+    harness
+        .state()
+        .message()
+        .map(|m| m.text.clone())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_edits_are_refused() {
+    for keys in ["dd", "yyp", "yyP", "e", "i", "a", "r", "u", "<C-r>"] {
+        let mut harness = read_only(DOC);
+        harness.keys(keys).unwrap();
+        assert!(
+            message(&harness).contains("Buffer is read-only"),
+            "{}: {}",
+            keys,
+            message(&harness)
+        );
+        assert!(!harness.state().is_dirty(), "{}", keys);
+        assert_eq!(harness.document().unwrap(), DOC, "{}", keys);
+    }
+}
+
+#[test]
+fn test_path_edits_are_refused() {
+    let mut harness = read_only(DOC);
+    assert!(harness.state_mut().path_set("$.name", "api").is_err());
+    assert!(harness.state_mut().path_delete("$.ports").is_err());
+    assert_eq!(harness.document().unwrap(), DOC);
+}
+
+#[test]
+fn test_view_toggles_read_only() {
+    let mut harness = Harness::new(DOC).unwrap();
+    harness.keys(":view<CR>").unwrap();
+    assert!(harness.state().is_read_only());
+    assert_eq!(message(&harness), "Read-only");
+    assert!(harness.screen().unwrap().contains("[RO]"));
+
+    harness.keys(":view<CR>").unwrap();
+    assert!(!harness.state().is_read_only());
+    assert_eq!(message(&harness), "Editing enabled");
+    assert!(!harness.screen().unwrap().contains("[RO]"));
+
+    harness.keys("dd").unwrap();
+    assert!(harness.state().is_dirty());
+}
+
+#[test]
+fn test_write_is_refused_but_save_as_works() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("doc.yaml");
+    fs::write(&path, DOC).unwrap();
+    let mut harness = Harness::open(&path).unwrap();
+    harness.keys("dd:view<CR>:w<CR>").unwrap();
+    assert_eq!(
+        message(&harness),
+        "Buffer is read-only (:view to allow edits)"
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), DOC);
+
+    let copy = dir.path().join("copy.yaml");
+    harness.keys(&format!(":w {}<CR>", copy.display())).unwrap();
+    assert_eq!(
+        fs::read_to_string(&copy).unwrap(),
+        "ports:\n  - 80\n  - 443\n"
+    );
+}
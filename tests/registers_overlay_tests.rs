@@ -0,0 +1,30 @@
+//! Tests for the registers overlay (`:registers`).
+
+use yamlquill::harness::Harness;
+
+#[test]
+fn test_registers_overlay_opens_scrolls_and_closes() {
+    let mut h = Harness::new("a: 1\nb: 2\nc: 3\n").unwrap();
+    h.keys(":registers<CR>").unwrap();
+    assert_eq!(h.state().registers_scroll(), None);
+    assert_eq!(
+        h.state().message().map(|m| m.text.clone()).as_deref(),
+        Some("All registers are empty")
+    );
+
+    h.keys("\"ayyj\"byy").unwrap();
+    h.keys(":reg<CR>").unwrap();
+    assert_eq!(h.state().registers_scroll(), Some(0));
+
+    // Keys scroll the overlay instead of moving the cursor
+    let cursor = h.state().cursor().path().to_vec();
+    h.keys("jjjjjj").unwrap();
+    let rows = h.state().registers().filled().len();
+    assert_eq!(h.state().registers_scroll(), Some(rows - 1));
+    h.keys("k").unwrap();
+    assert_eq!(h.state().registers_scroll(), Some(rows - 2));
+    assert_eq!(h.state().cursor().path(), cursor.as_slice());
+
+    h.keys("<Esc>").unwrap();
+    assert_eq!(h.state().registers_scroll(), None);
+}
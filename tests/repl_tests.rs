@@ -0,0 +1,46 @@
+//! Integration tests for the `:repl` query pane.
+
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::mode::EditorMode;
+use yamlquill::editor::state::EditorState;
+
+const DOC: &str = "services:\n  web:\n    port: 80\n  db:\n    port: 5432\n";
+
+fn repl_with(query: &str) -> EditorState {
+    let tree = YamlTree::new(parse_yaml_auto(DOC).unwrap());
+    let mut state = EditorState::new_with_default_theme(tree);
+    state.open_repl();
+    query.chars().for_each(|c| state.repl_mut().insert_char(c));
+    state.evaluate_repl();
+    state
+}
+
+#[test]
+fn test_live_results_do_not_move_cursor() {
+    let state = repl_with("$..port");
+    assert_eq!(*state.mode(), EditorMode::Repl);
+    assert_eq!(state.repl().results(), [vec![0, 0, 0], vec![0, 1, 0]]);
+    assert!(state.repl().error().is_none());
+    assert_eq!(state.cursor().path(), &[0]);
+}
+
+#[test]
+fn test_invalid_expression_reports_error() {
+    let state = repl_with("$.services[");
+    assert!(state.repl().results().is_empty());
+    assert!(state.repl().error().is_some());
+}
+
+#[test]
+fn test_promote_results_to_search() {
+    let mut state = repl_with("$..port");
+    assert_eq!(state.promote_repl_results(), 2);
+
+    assert_eq!(*state.mode(), EditorMode::Normal);
+    assert_eq!(state.cursor().path(), &[0, 0, 0]);
+    assert_eq!(state.repl().history(), ["$..port"]);
+
+    state.next_search_result();
+    assert_eq!(state.cursor().path(), &[0, 1, 0]);
+}
@@ -0,0 +1,59 @@
+//! Tests for previewing `${VAR}` placeholders with `:set resolve-env`.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::harness::Harness;
+
+const COMPOSE: &str = "\
+services:
+  web:
+    image: nginx:${NGINX_TAG}
+    ports:
+    - ${WEB_PORT:-8080}:80
+    environment:
+      DB_URL: postgres://${DB_HOST}/app
+";
+
+#[test]
+fn test_preview_from_dotenv_file() {
+    let dir = TempDir::new().unwrap();
+    let env = dir.path().join("prod.env");
+    fs::write(&env, "NGINX_TAG=1.27\nWEB_PORT=9000\n").unwrap();
+
+    let mut h = Harness::new(COMPOSE).unwrap().with_size(100, 12);
+    h.keys("zR").unwrap();
+    assert!(!h.screen().unwrap().contains("→"));
+
+    h.keys(&format!(":set resolve-env={}<CR>", env.display()))
+        .unwrap();
+    let screen = h.screen().unwrap();
+    assert!(screen.contains("nginx:${NGINX_TAG} → nginx:1.27"));
+    assert!(screen.contains("${WEB_PORT:-8080}:80 → 9000:80"));
+    assert!(screen.contains("→ postgres:///app (unset: DB_HOST)"));
+
+    // The document keeps its placeholders
+    assert!(!h.state().is_dirty());
+    assert_eq!(h.document().unwrap(), COMPOSE);
+
+    h.keys(":set resolve-env?<CR>").unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        format!("resolve-env is on ({} and the environment)", env.display())
+    );
+
+    h.keys(":set noresolve-env<CR>").unwrap();
+    assert!(!h.screen().unwrap().contains("→"));
+}
+
+#[test]
+fn test_missing_dotenv_file_is_an_error() {
+    let mut h = Harness::new(COMPOSE).unwrap();
+    h.keys(":set resolve-env=/no/such/.env<CR>").unwrap();
+    assert!(h
+        .state()
+        .message()
+        .unwrap()
+        .text
+        .starts_with("Can't read /no/such/.env"));
+    assert!(h.state().resolve_env().is_none());
+}
@@ -0,0 +1,74 @@
+//! Tests for idle-time schema re-validation (`:set autovalidate`).
+
+use std::fs;
+use std::time::Duration;
+use tempfile::TempDir;
+use yamlquill::harness::Harness;
+
+const SCHEMA: &str = r#"{
+  "required": ["name"],
+  "properties": {
+    "replicas": {"type": "integer"},
+    "port": {"maximum": 100}
+  }
+}"#;
+
+/// Opens a document with a schema and `autovalidate` on.
+fn harness() -> (TempDir, Harness) {
+    let dir = TempDir::new().unwrap();
+    let schema = dir.path().join("schema.json");
+    fs::write(&schema, SCHEMA).unwrap();
+    let mut harness = Harness::new("name: web\nreplicas: 2\nport: 80\n").unwrap();
+    let state = harness.state_mut();
+    state.set_schema_path(Some(schema.display().to_string()));
+    harness.keys(":set autovalidate<CR>").unwrap();
+    (dir, harness)
+}
+
+/// Runs idle validation until the queue is empty.
+fn drain(harness: &mut Harness) {
+    while harness.state_mut().run_idle_validation(Duration::ZERO) {}
+}
+
+#[test]
+fn test_edits_queue_validation_for_idle_time() {
+    let (_dir, mut harness) = harness();
+    assert!(harness.state().revalidation_pending());
+    drain(&mut harness);
+    assert!(harness.state().diagnostics().is_empty());
+
+    // The edit itself doesn't validate
+    harness.keys(":path $.port<CR>e<C-a><C-k>500<CR>").unwrap();
+    assert!(harness.state().diagnostics().is_empty());
+    assert!(harness.state().revalidation_pending());
+
+    // The cursor's section is checked first, one unit per idle turn
+    harness.state_mut().run_idle_validation(Duration::ZERO);
+    let diagnostics = harness.state().diagnostics().items();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].location, "$.port");
+    assert!(harness.state().revalidation_pending());
+
+    drain(&mut harness);
+    assert_eq!(harness.state().diagnostics().len(), 1);
+
+    harness.keys("u").unwrap();
+    drain(&mut harness);
+    assert!(harness.state().diagnostics().is_empty());
+}
+
+#[test]
+fn test_noautovalidate_leaves_diagnostics_alone() {
+    let (_dir, mut harness) = harness();
+    drain(&mut harness);
+    harness
+        .keys(":set noautovalidate<CR>:path $.port<CR>e<C-a><C-k>500<CR>")
+        .unwrap();
+    assert!(!harness.state().revalidation_pending());
+    assert!(!harness.state_mut().run_idle_validation(Duration::ZERO));
+    assert!(harness.state().diagnostics().is_empty());
+
+    // `:validate` still checks on demand
+    harness.keys(":validate<CR>").unwrap();
+    assert_eq!(harness.state().diagnostics().len(), 1);
+}
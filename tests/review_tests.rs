@@ -0,0 +1,143 @@
+//! Integration tests for stepping through changes with `:review`.
+
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use termion::event::{Event, Key};
+use yamlquill::config::Config;
+use yamlquill::editor::mode::EditorMode;
+use yamlquill::editor::review::ChangeKind;
+use yamlquill::editor::state::EditorState;
+use yamlquill::file::loader::load_yaml_file;
+use yamlquill::file::saver::save_yaml_file;
+use yamlquill::input::InputHandler;
+
+/// Opens `buffer` as the buffer while the file on disk holds `disk`.
+fn open_changed(dir: &TempDir, disk: &str, buffer: &str) -> (EditorState, std::path::PathBuf) {
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, buffer).unwrap();
+    let mut state = EditorState::new_with_default_theme(load_yaml_file(&path).unwrap());
+    state.set_filename(path.display().to_string());
+    fs::write(&path, disk).unwrap();
+    (state, path)
+}
+
+fn keys(handler: &mut InputHandler, state: &mut EditorState, keys: &str) {
+    for c in keys.chars() {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), state)
+            .unwrap();
+    }
+}
+
+fn saved(state: &EditorState, path: &Path) -> String {
+    save_yaml_file(path, state.tree(), &Config::default()).unwrap();
+    fs::read_to_string(path).unwrap()
+}
+
+#[test]
+fn test_review_lists_changes() {
+    let dir = TempDir::new().unwrap();
+    let (mut state, _) = open_changed(
+        &dir,
+        "name: web\nreplicas: 3\nports:\n  - 80\n",
+        "name: web\nreplicas: 5\nports:\n  - 80\n  - 443\ndebug: true\n",
+    );
+    let mut handler = InputHandler::new();
+    keys(&mut handler, &mut state, ":review\n");
+
+    assert_eq!(*state.mode(), EditorMode::Review);
+    let review = state.review().unwrap();
+    let found: Vec<_> = review
+        .changes()
+        .iter()
+        .map(|c| (c.kind, c.location_string()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (ChangeKind::Modified, "$[\"replicas\"]".to_string()),
+            (ChangeKind::Added, "$[\"ports\"][1]".to_string()),
+            (ChangeKind::Added, "$[\"debug\"]".to_string()),
+        ]
+    );
+    // The cursor starts on the first change
+    assert_eq!(state.cursor().path(), &[1]);
+}
+
+#[test]
+fn test_keep_and_revert() {
+    let dir = TempDir::new().unwrap();
+    let (mut state, path) = open_changed(
+        &dir,
+        "name: web\nreplicas: 3\nimage: nginx\n",
+        "name: api\nreplicas: 5\n",
+    );
+    let mut handler = InputHandler::new();
+    keys(&mut handler, &mut state, ":review\n");
+    assert_eq!(state.review().unwrap().changes().len(), 3);
+
+    // Keep the name, revert the replicas, restore the removed image
+    keys(&mut handler, &mut state, "yrr");
+    assert_eq!(*state.mode(), EditorMode::Normal);
+    assert_eq!(
+        state.message().unwrap().text,
+        "Review done: 1 kept, 2 reverted"
+    );
+    assert_eq!(
+        saved(&state, &path),
+        "name: api\nreplicas: 3\nimage: nginx\n"
+    );
+}
+
+#[test]
+fn test_revert_array_item_is_one_undo_step() {
+    let dir = TempDir::new().unwrap();
+    let (mut state, _) = open_changed(&dir, "- a\n- b\n- c\n", "- a\n- c\n- d\n");
+    let mut handler = InputHandler::new();
+    keys(&mut handler, &mut state, ":review\n");
+
+    let kinds: Vec<_> = state
+        .review()
+        .unwrap()
+        .changes()
+        .iter()
+        .map(|c| c.kind)
+        .collect();
+    assert_eq!(kinds, [ChangeKind::Removed, ChangeKind::Added]);
+
+    // Restore "b" in its old place
+    keys(&mut handler, &mut state, "r");
+    assert_eq!(state.review().unwrap().changes().len(), 1);
+    assert_eq!(
+        state.tree().get_node(&[1]).unwrap().value().to_string(),
+        "b"
+    );
+    assert_eq!(
+        state.tree().get_node(&[3]).unwrap().value().to_string(),
+        "d"
+    );
+
+    keys(&mut handler, &mut state, "q");
+    assert!(state.undo());
+    assert_eq!(
+        state.tree().get_node(&[2]).unwrap().value().to_string(),
+        "d"
+    );
+}
+
+#[test]
+fn test_no_changes_and_edit() {
+    let dir = TempDir::new().unwrap();
+    let (mut state, _) = open_changed(&dir, "a: 1\n", "a: 1\n");
+    let mut handler = InputHandler::new();
+    keys(&mut handler, &mut state, ":review\n");
+    assert_eq!(*state.mode(), EditorMode::Normal);
+    assert_eq!(state.message().unwrap().text, "No changes against disk");
+
+    let (mut state, _) = open_changed(&dir, "a: 1\nb:\n  c: 2\n", "a: 1\nb:\n  c: 3\n");
+    keys(&mut handler, &mut state, ":review\ne");
+    assert_eq!(*state.mode(), EditorMode::Normal);
+    assert!(state.review().is_none());
+    assert_eq!(state.cursor().path(), &[1, 0]);
+}
@@ -0,0 +1,307 @@
+//! Property tests for formatting preservation.
+//!
+//! Random documents are generated together with the value they should parse
+//! to, in a mix of the styles people write by hand: comments above and after
+//! entries, blank lines, quoted and plain scalars, flow collections, 2- and
+//! 4-space indents, and sequences indented or flush under their key. Each
+//! one is then checked to
+//!
+//! - parse to the value it was generated from,
+//! - save byte-identically when nothing was changed, including after an
+//!   edit that was undone, and
+//! - still hold the right value after a real edit.
+//!
+//! Cases come from a seeded generator, so a failure prints the seed and the
+//! document; set `YAMLQUILL_PROPTEST_SEED` to replay one case and
+//! `YAMLQUILL_PROPTEST_CASES` to run more (the default is 200).
+
+use serde_yaml::{Mapping, Value};
+use yamlquill::harness::Harness;
+
+const WORDS: &[&str] = &[
+    "web", "api", "nginx", "alpha", "beta", "gamma", "redis", "east", "west", "blue",
+];
+const KEYS: &[&str] = &[
+    "name", "image", "replicas", "port", "labels", "spec", "env", "tier", "host", "tags", "limits",
+    "enabled", "path", "mode", "owner",
+];
+
+/// Builds one random document and the value it stands for.
+struct Generator {
+    rng: fastrand::Rng,
+    /// Spaces per nesting level
+    step: usize,
+    /// Whether block sequences under a key are indented (`key:\n  - a`) or flush (`key:\n- a`)
+    indent_sequences: bool,
+}
+
+impl Generator {
+    fn new(seed: u64) -> Self {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let step = if rng.bool() { 2 } else { 4 };
+        let indent_sequences = rng.bool();
+        Self {
+            rng,
+            step,
+            indent_sequences,
+        }
+    }
+
+    fn document(&mut self) -> (String, Value) {
+        let mut out = String::new();
+        if self.rng.u8(..4) == 0 {
+            out.push_str("# Generated document\n");
+        }
+        let value = if self.rng.u8(..5) == 0 {
+            self.sequence(&mut out, 0, 0)
+        } else {
+            self.mapping(&mut out, 0, 0)
+        };
+        (out, value)
+    }
+
+    fn word(&mut self) -> &'static str {
+        WORDS[self.rng.usize(..WORDS.len())]
+    }
+
+    /// Returns a scalar as written and as parsed.
+    fn scalar(&mut self) -> (String, Value) {
+        match self.rng.u8(..9) {
+            0 => {
+                let n = self.rng.i64(-1000..10000);
+                (n.to_string(), Value::from(n))
+            }
+            1 => {
+                let n = self.rng.i64(0..100) as f64 + 0.25 * self.rng.u8(1..4) as f64;
+                (n.to_string(), Value::from(n))
+            }
+            2 => {
+                let b = self.rng.bool();
+                (b.to_string(), Value::Bool(b))
+            }
+            3 => ("null".to_string(), Value::Null),
+            4 => {
+                let s = format!("{} {}", self.word(), self.word());
+                (format!("'{}'", s), Value::String(s))
+            }
+            5 => {
+                let s = format!("{}: {}", self.word(), self.word());
+                (format!("\"{}\"", s), Value::String(s))
+            }
+            6 => {
+                let s = format!("{}-{}", self.word(), self.rng.u8(1..99));
+                (s.clone(), Value::String(s))
+            }
+            _ => {
+                let s = self.word().to_string();
+                (s.clone(), Value::String(s))
+            }
+        }
+    }
+
+    fn maybe_comment(&mut self, out: &mut String, indent: usize) {
+        if self.rng.u8(..6) == 0 {
+            out.push_str(&format!(
+                "{}# {} {}\n",
+                " ".repeat(indent),
+                self.word(),
+                self.word()
+            ));
+        }
+    }
+
+    fn inline_comment(&mut self) -> String {
+        if self.rng.u8(..6) == 0 {
+            format!("  # {}", self.word())
+        } else {
+            String::new()
+        }
+    }
+
+    fn flow(&mut self) -> (String, Value) {
+        let len = self.rng.usize(..4);
+        if self.rng.bool() {
+            let items: Vec<(String, Value)> = (0..len).map(|_| self.scalar()).collect();
+            let text = items.iter().map(|(t, _)| t.as_str()).collect::<Vec<_>>();
+            (
+                format!("[{}]", text.join(", ")),
+                Value::Sequence(items.into_iter().map(|(_, v)| v).collect()),
+            )
+        } else {
+            let mut map = Mapping::new();
+            let mut text = Vec::new();
+            for key in self.keys(len) {
+                let (t, v) = self.scalar();
+                text.push(format!("{}: {}", key, t));
+                map.insert(Value::from(key), v);
+            }
+            (format!("{{{}}}", text.join(", ")), Value::Mapping(map))
+        }
+    }
+
+    fn keys(&mut self, n: usize) -> Vec<&'static str> {
+        let mut keys = KEYS.to_vec();
+        self.rng.shuffle(&mut keys);
+        keys.truncate(n);
+        keys
+    }
+
+    fn mapping(&mut self, out: &mut String, indent: usize, depth: usize) -> Value {
+        let pad = " ".repeat(indent);
+        let mut map = Mapping::new();
+        let len = self.rng.usize(1..5);
+        for (i, key) in self.keys(len).into_iter().enumerate() {
+            if depth == 0 && i > 0 && self.rng.u8(..3) == 0 {
+                out.push('\n');
+            }
+            self.maybe_comment(out, indent);
+            let value = match self.rng.u8(..8) {
+                0 | 1 if depth < 3 => {
+                    out.push_str(&format!("{}{}:\n", pad, key));
+                    self.mapping(out, indent + self.step, depth + 1)
+                }
+                2 if depth < 3 => {
+                    out.push_str(&format!("{}{}:\n", pad, key));
+                    let indent = if self.indent_sequences {
+                        indent + self.step
+                    } else {
+                        indent
+                    };
+                    self.sequence(out, indent, depth + 1)
+                }
+                3 => {
+                    let (text, value) = self.flow();
+                    let comment = self.inline_comment();
+                    out.push_str(&format!("{}{}: {}{}\n", pad, key, text, comment));
+                    value
+                }
+                _ => {
+                    let (text, value) = self.scalar();
+                    let comment = self.inline_comment();
+                    out.push_str(&format!("{}{}: {}{}\n", pad, key, text, comment));
+                    value
+                }
+            };
+            map.insert(Value::from(key), value);
+        }
+        Value::Mapping(map)
+    }
+
+    fn sequence(&mut self, out: &mut String, indent: usize, depth: usize) -> Value {
+        let pad = " ".repeat(indent);
+        let mut items = Vec::new();
+        for _ in 0..self.rng.usize(1..4) {
+            self.maybe_comment(out, indent);
+            let value = if depth < 3 && self.rng.u8(..4) == 0 {
+                // A mapping item starts on the dash line: `- key: value`
+                let mut item = String::new();
+                let value = self.mapping(&mut item, indent + 2, depth + 1);
+                out.push_str(&format!("{}- {}", pad, &item[indent + 2..]));
+                value
+            } else {
+                let (text, value) = self.scalar();
+                let comment = self.inline_comment();
+                out.push_str(&format!("{}- {}{}\n", pad, text, comment));
+                value
+            };
+            items.push(value);
+        }
+        Value::Sequence(items)
+    }
+}
+
+fn env_number(name: &str) -> Option<u64> {
+    std::env::var(name).ok().and_then(|v| v.parse().ok())
+}
+
+/// Runs `check` on generated cases, naming the seed and document on failure.
+fn for_each_case(check: impl Fn(&str, &Value, u64) -> Result<(), String>) {
+    let seeds: Vec<u64> = match env_number("YAMLQUILL_PROPTEST_SEED") {
+        Some(seed) => vec![seed],
+        None => (0..env_number("YAMLQUILL_PROPTEST_CASES").unwrap_or(200)).collect(),
+    };
+    for seed in seeds {
+        let (text, value) = Generator::new(seed).document();
+        if let Err(e) = check(&text, &value, seed) {
+            panic!(
+                "case failed (replay with YAMLQUILL_PROPTEST_SEED={}): {}\n--- document\n{}",
+                seed, e, text
+            );
+        }
+    }
+}
+
+fn parsed(text: &str) -> Result<Value, String> {
+    serde_yaml::from_str(text).map_err(|e| format!("output doesn't parse: {}", e))
+}
+
+#[test]
+fn test_generated_documents_are_valid() {
+    for_each_case(|text, value, _| {
+        let actual = parsed(text)?;
+        if actual != *value {
+            return Err(format!("generator bug: parses to {:?}", actual));
+        }
+        Ok(())
+    });
+}
+
+#[test]
+fn test_unchanged_document_saves_identically() {
+    for_each_case(|text, _, _| {
+        let harness = Harness::new(text).map_err(|e| e.to_string())?;
+        let saved = harness.document().map_err(|e| e.to_string())?;
+        if saved != text {
+            return Err(format!("saved as\n{}", saved));
+        }
+        Ok(())
+    });
+}
+
+#[test]
+fn test_undone_edit_saves_identically() {
+    for_each_case(|text, _, seed| {
+        let mut harness = Harness::new(text).map_err(|e| e.to_string())?;
+        let lines = harness.state().tree_view().lines().len();
+        let down = "j".repeat(fastrand::Rng::with_seed(seed).usize(..lines));
+        harness
+            .keys(&format!("{}e<End>x<CR><Esc>", down))
+            .map_err(|e| e.to_string())?;
+        if harness.state().is_dirty() {
+            harness.keys("u").map_err(|e| e.to_string())?;
+        }
+        let saved = harness.document().map_err(|e| e.to_string())?;
+        if saved != text {
+            return Err(format!("saved as\n{}", saved));
+        }
+        Ok(())
+    });
+}
+
+#[test]
+fn test_edited_document_keeps_its_values() {
+    for_each_case(|text, value, _| {
+        let mut harness = Harness::new(text).map_err(|e| e.to_string())?;
+        // Set the first top-level value to a known string
+        let Value::Mapping(map) = value else {
+            return Ok(());
+        };
+        let (key, _) = map.iter().next().unwrap();
+        let key = key.as_str().unwrap();
+        harness
+            .state_mut()
+            .path_set(&format!("$.{}", key), "\"edited\"")
+            .map_err(|e| e.to_string())?;
+
+        let mut expected = map.clone();
+        if !expected[key].is_mapping() && !expected[key].is_sequence() {
+            expected.insert(Value::from(key), Value::from("edited"));
+        }
+        let saved = harness.document().map_err(|e| e.to_string())?;
+        let actual = parsed(&saved)?;
+        if actual != Value::Mapping(expected) {
+            return Err(format!("saved as\n{}", saved));
+        }
+        Ok(())
+    });
+}
@@ -0,0 +1,100 @@
+//! Integration tests for the `--listen` control socket methods.
+
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::app::App;
+use yamlquill::editor::state::EditorState;
+use yamlquill::file::loader::load_yaml_file;
+use yamlquill::input::rpc::dispatch;
+use yamlquill::input::InputHandler;
+
+fn app_for(dir: &TempDir, yaml: &str) -> App {
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, yaml).unwrap();
+    let mut state = EditorState::new_with_default_theme(load_yaml_file(&path).unwrap());
+    state.set_filename(path.to_string_lossy().to_string());
+    App::new(state)
+}
+
+#[test]
+fn test_goto_set_query_save() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app_for(&dir, "name: web\nspec:\n  replicas: 3\n");
+    let handler = InputHandler::new();
+
+    let result = dispatch(
+        &mut app,
+        &handler,
+        "goto",
+        &json!({"path": "$.spec.replicas"}),
+    );
+    assert_eq!(
+        result.unwrap(),
+        json!({"path": "$[\"spec\"][\"replicas\"]"})
+    );
+
+    let params = json!({"path": "$.spec.replicas", "value": 5});
+    assert_eq!(
+        dispatch(&mut app, &handler, "set-value", &params).unwrap(),
+        json!({"changed": 1})
+    );
+    // Strings stay strings even when they look like numbers
+    let params = json!({"path": "$.name", "value": "42"});
+    dispatch(&mut app, &handler, "set-value", &params).unwrap();
+
+    let result = dispatch(&mut app, &handler, "query", &json!({"path": "$.*"})).unwrap();
+    assert_eq!(
+        result,
+        json!([
+            {"path": "$[\"name\"]", "value": "42"},
+            {"path": "$[\"spec\"]", "value": {"replicas": 5}},
+        ])
+    );
+
+    let status = dispatch(&mut app, &handler, "status", &json!({})).unwrap();
+    assert_eq!(status["dirty"], true);
+    assert_eq!(status["mode"], "NORMAL");
+
+    dispatch(&mut app, &handler, "save", &json!({})).unwrap();
+    assert!(!app.focused().is_dirty());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("app.yaml")).unwrap(),
+        "name: '42'\nspec:\n  replicas: 5\n"
+    );
+}
+
+#[test]
+fn test_errors() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app_for(&dir, "a: 1\n");
+    let handler = InputHandler::new();
+
+    let err = dispatch(&mut app, &handler, "frobnicate", &json!({})).unwrap_err();
+    assert_eq!(err.code, -32601);
+    let err = dispatch(&mut app, &handler, "goto", &json!({})).unwrap_err();
+    assert_eq!(err.code, -32602);
+    let err = dispatch(&mut app, &handler, "goto", &json!({"path": "$.b"})).unwrap_err();
+    assert_eq!(err.code, -32000);
+    assert!(err.message.contains("Not found"));
+    let params = json!({"path": "$.a", "value": [1]});
+    assert!(dispatch(&mut app, &handler, "set-value", &params).is_err());
+}
+
+#[test]
+fn test_open() {
+    let dir = TempDir::new().unwrap();
+    let mut app = app_for(&dir, "a: 1\n");
+    let handler = InputHandler::new();
+    let other = dir.path().join("other.yaml");
+    fs::write(&other, "b: 2\n").unwrap();
+
+    let params = json!({"file": other.to_string_lossy()});
+    assert_eq!(
+        dispatch(&mut app, &handler, "open", &params).unwrap(),
+        json!({"buffer": 2})
+    );
+    assert_eq!(app.buffers().len(), 2);
+    let status = dispatch(&mut app, &handler, "status", &json!({})).unwrap();
+    assert_eq!(status["file"], json!(other.to_string_lossy()));
+}
@@ -0,0 +1,58 @@
+//! Tests for safe mode (`--clean`).
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::config::trust::TrustStore;
+use yamlquill::config::Config;
+use yamlquill::editor::notes::notes_file_path;
+use yamlquill::editor::state::EditorState;
+use yamlquill::file::loader::load_yaml_file;
+
+/// Opens `file` the way `main` does, in safe mode or not.
+fn open(file: &std::path::Path, safe: bool) -> EditorState {
+    let mut state = EditorState::new_with_default_theme(load_yaml_file(file).unwrap());
+    state.set_filename(file.display().to_string());
+    state.set_safe_mode(safe);
+    let mut store = TrustStore::new();
+    store.trust(file.parent().unwrap(), None);
+    state.set_trust_store(store);
+    state.load_buffer_settings();
+    state.load_notes();
+    state
+}
+
+#[test]
+fn test_safe_mode_ignores_modelines_and_notes() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("app.yaml");
+    fs::write(&file, "# yamlquill: nonumber\nname: app\n").unwrap();
+    let mut normal = open(&file, false);
+    assert!(!normal.show_line_numbers());
+    normal.set_note("check").unwrap();
+    assert!(!open(&file, false).notes().is_empty());
+
+    let mut safe = open(&file, true);
+    assert!(safe.is_safe_mode());
+    assert!(safe.show_line_numbers());
+    assert!(safe.notes().is_empty());
+
+    // Notes made in safe mode last for the session only
+    let before = fs::read_to_string(notes_file_path(&file)).unwrap();
+    safe.set_note("temporary").unwrap();
+    assert!(!safe.notes().is_empty());
+    assert_eq!(fs::read_to_string(notes_file_path(&file)).unwrap(), before);
+}
+
+#[test]
+fn test_safe_mode_refuses_set_save() {
+    let mut state = EditorState::new_with_default_theme(yamlquill::document::tree::YamlTree::new(
+        yamlquill::document::parser::parse_yaml_auto("a: 1\n").unwrap(),
+    ));
+    state.set_safe_mode(true);
+    state.apply_config(&Config::default());
+    let error = state.save_config().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Settings aren't saved in safe mode (--clean)"
+    );
+}
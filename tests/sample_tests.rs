@@ -0,0 +1,78 @@
+//! Tests for `:sample` record sampling.
+
+use yamlquill::document::node::YamlValue;
+use yamlquill::editor::sample::{Sample, SampleMode};
+use yamlquill::editor::state::MessageLevel;
+use yamlquill::harness::Harness;
+
+fn records(count: usize) -> String {
+    (0..count).map(|i| format!("---\nid: {}\n", i)).collect()
+}
+
+fn record_count(h: &Harness) -> usize {
+    match h.state().tree().root().value() {
+        YamlValue::MultiDoc(records) => records.len(),
+        _ => panic!("expected multi-document root"),
+    }
+}
+
+#[test]
+fn test_sample_shows_subset_read_only_and_restores() {
+    let mut h = Harness::new(&records(50)).unwrap().with_size(100, 20);
+    h.keys(":sample 10<CR>").unwrap();
+    assert_eq!(record_count(&h), 10);
+    let info = h.state().sample_info().unwrap();
+    assert_eq!((info.shown, info.total), (10, 50));
+    assert!(h.screen().unwrap().contains("[Sample: random 10/50]"));
+
+    // Edits are refused, and :view can't lift that
+    h.keys("dd").unwrap();
+    assert_eq!(record_count(&h), 10);
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text.contains("Sampled buffer is read-only")));
+    h.keys(":view<CR>").unwrap();
+    assert!(h.state().is_read_only());
+
+    h.keys(":sample off<CR>").unwrap();
+    assert_eq!(record_count(&h), 50);
+    assert!(h.state().sample_info().is_none());
+    assert!(!h.state().is_read_only());
+    assert_eq!(h.state().message().unwrap().text, "Loaded all 50 records");
+}
+
+#[test]
+fn test_head_and_tail_samples_resample_the_full_set() {
+    let mut h = Harness::new(&records(20)).unwrap();
+    h.keys(":sample head 3<CR>").unwrap();
+    assert_eq!(
+        h.document().unwrap().trim_end(),
+        "---\nid: 0\n---\nid: 1\n---\nid: 2"
+    );
+
+    h.keys(":sample tail 2<CR>").unwrap();
+    assert_eq!(h.document().unwrap().trim_end(), "---\nid: 18\n---\nid: 19");
+    assert_eq!(h.state().sample_info().unwrap().total, 20);
+}
+
+#[test]
+fn test_apply_sample_errors() {
+    let mut h = Harness::new("a: 1\n").unwrap();
+    h.keys(":sample 5<CR>").unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert_eq!(
+        message.text,
+        "Sampling needs a multi-document or JSONL file"
+    );
+
+    let mut h = Harness::new(&records(5)).unwrap();
+    h.keys("ldd").unwrap();
+    let sample = Sample {
+        mode: SampleMode::Head,
+        count: 2,
+    };
+    let err = h.state_mut().apply_sample(sample, 1).unwrap_err();
+    assert_eq!(err.to_string(), "Save or undo your changes before sampling");
+}
@@ -0,0 +1,94 @@
+//! Integration tests for `:validate` JSON Schema support in the editor.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::diagnostics::Severity;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+
+const SCHEMA: &str = r#"{
+  "$schema": "http://json-schema.org/draft-07/schema#",
+  "type": "object",
+  "required": ["name", "replicas"],
+  "properties": {
+    "name": {"type": "string"},
+    "replicas": {"type": "integer", "minimum": 1}
+  }
+}"#;
+
+fn state_in(dir: &TempDir, yaml: &str) -> EditorState {
+    let file = dir.path().join("deploy.yaml");
+    fs::write(&file, yaml).unwrap();
+    fs::write(dir.path().join("schema.json"), SCHEMA).unwrap();
+
+    let tree = YamlTree::with_source(parse_yaml_auto(yaml).unwrap(), Some(yaml.to_string()));
+    let mut state = EditorState::new_with_default_theme(tree);
+    state.set_filename(file.to_string_lossy().to_string());
+    state
+}
+
+#[test]
+fn test_validate_valid_document() {
+    let dir = TempDir::new().unwrap();
+    let mut state = state_in(&dir, "name: web\nreplicas: 2\n");
+
+    let count = state.validate_schema("schema.json").unwrap();
+
+    assert_eq!(count, 0);
+    assert!(state.diagnostics().is_empty());
+    assert_eq!(state.message().unwrap().level, MessageLevel::Info);
+    assert!(state.message().unwrap().text.contains("is valid"));
+}
+
+#[test]
+fn test_validate_reports_diagnostics_and_markers() {
+    let dir = TempDir::new().unwrap();
+    let mut state = state_in(&dir, "name: web\nreplicas: 0\n");
+
+    let count = state.validate_schema("schema.json").unwrap();
+
+    assert_eq!(count, 1);
+    assert_eq!(
+        state.tree_view().diagnostic_markers().get(&vec![1]),
+        Some(&Severity::Error)
+    );
+    let message = state.message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert!(message.text.contains("1 error"));
+    assert!(message.text.contains("$.replicas"));
+}
+
+#[test]
+fn test_revalidating_replaces_previous_results() {
+    let dir = TempDir::new().unwrap();
+    let mut state = state_in(&dir, "replicas: 0\n");
+    assert_eq!(state.validate_schema("schema.json").unwrap(), 2);
+
+    let passing = "{}";
+    fs::write(dir.path().join("empty.json"), passing).unwrap();
+    assert_eq!(state.validate_schema("empty.json").unwrap(), 0);
+
+    assert!(state.diagnostics().is_empty());
+    assert!(state.tree_view().diagnostic_markers().is_empty());
+}
+
+#[test]
+fn test_validate_missing_schema_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    let mut state = state_in(&dir, "name: web\n");
+
+    assert!(state.validate_schema("nope.json").is_err());
+    assert!(state.diagnostics().is_empty());
+}
+
+#[test]
+fn test_reload_clears_diagnostics() {
+    let dir = TempDir::new().unwrap();
+    let mut state = state_in(&dir, "replicas: 0\n");
+    state.validate_schema("schema.json").unwrap();
+    assert!(!state.diagnostics().is_empty());
+
+    state.reload_tree(YamlTree::new(parse_yaml_auto("a: 1\n").unwrap()));
+    assert!(state.diagnostics().is_empty());
+}
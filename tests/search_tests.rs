@@ -0,0 +1,107 @@
+//! Tests for regex and key-glob search.
+
+use yamlquill::editor::state::MessageLevel;
+use yamlquill::harness::Harness;
+
+const DEPLOYMENT: &str = "\
+spec:
+  web:
+    image: nginx:1.25
+    port: 80
+  worker:
+    image: busybox
+    port: 8080
+template:
+  spec:
+    sidecar:
+      image: envoy
+";
+
+#[test]
+fn test_regex_search_with_prefix() {
+    let mut h = Harness::new(DEPLOYMENT).unwrap();
+    h.keys(r"/\v^(nginx|envoy)<CR>").unwrap();
+    assert_eq!(h.state().search_results_info(), Some((1, 2)));
+    assert_eq!(h.state().cursor().path(), &[0, 0, 0]);
+
+    // Without the prefix the same text is a literal substring
+    h.keys("/nginx|envoy<CR>").unwrap();
+    assert_eq!(h.state().search_results_info(), None);
+}
+
+#[test]
+fn test_regexsearch_option() {
+    let mut h = Harness::new(DEPLOYMENT).unwrap();
+    h.keys(":set regexsearch<CR>").unwrap();
+    assert!(h.state().regex_search());
+    h.keys(r"/^\w+x$<CR>").unwrap();
+    assert_eq!(h.state().search_results_info(), Some((1, 1)));
+
+    // \V forces a literal match
+    h.keys(r"/\V^\w<CR>").unwrap();
+    assert_eq!(h.state().search_results_info(), None);
+}
+
+#[test]
+fn test_key_glob_search() {
+    let mut h = Harness::new(DEPLOYMENT).unwrap();
+    h.keys(r"/\gspec.*.image<CR>").unwrap();
+    assert_eq!(h.state().search_results_info(), Some((1, 3)));
+
+    h.keys(r"/\g.spec.*.image<CR>").unwrap();
+    assert_eq!(h.state().search_results_info(), Some((1, 2)));
+    h.keys("n").unwrap();
+    assert_eq!(h.state().cursor().path(), &[0, 1, 0]);
+
+    h.keys(r"/\gspec.**.port<CR>").unwrap();
+    assert_eq!(h.state().search_results_info(), Some((1, 2)));
+}
+
+#[test]
+fn test_invalid_pattern_is_reported() {
+    let mut h = Harness::new(DEPLOYMENT).unwrap();
+    h.keys(r"/\v(nginx").unwrap();
+    let screen = h.screen().unwrap();
+    assert!(screen.contains("Invalid regex"), "{}", screen);
+
+    h.keys("<CR>").unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert!(message.text.contains("Invalid regex"), "{}", message.text);
+    assert_eq!(h.state().search_results_info(), None);
+
+    h.keys(r"/\ga..b<CR>").unwrap();
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text.starts_with("Invalid key glob")));
+}
+
+#[test]
+fn test_search_finds_and_reveals_matches_in_collapsed_nodes() {
+    let mut h = Harness::new(DEPLOYMENT).unwrap();
+    h.keys("hjh").unwrap();
+    assert_eq!(h.state().tree_view().lines().len(), 2);
+
+    h.keys("/envoy<CR>").unwrap();
+    assert_eq!(h.state().search_results_info(), Some((1, 1)));
+    assert_eq!(h.state().cursor().path(), &[1, 0, 0, 0]);
+    let (row, _) = h.state().cursor_position();
+    assert!(row > 0, "cursor line is shown");
+
+    h.keys("/busybox<CR>").unwrap();
+    assert_eq!(h.state().cursor().path(), &[0, 1, 0]);
+    assert!(h.state().cursor_position().0 > 0);
+}
+
+#[test]
+fn test_nosearchcollapsed_searches_visible_lines_only() {
+    let mut h = Harness::new(DEPLOYMENT).unwrap();
+    h.keys(":set nosearchcollapsed<CR>").unwrap();
+    assert!(!h.state().search_collapsed());
+    h.keys("h").unwrap();
+
+    h.keys("/image<CR>").unwrap();
+    assert_eq!(h.state().search_results_info(), Some((1, 1)));
+    assert_eq!(h.state().cursor().path(), &[1, 0, 0, 0]);
+}
@@ -0,0 +1,62 @@
+//! Tests for semantic version helpers (`:bump`, version field checks, `K`).
+
+use yamlquill::harness::Harness;
+
+const CHART: &str = "\
+apiVersion: v2
+name: app
+version: 1.4.2
+appVersion: v2.0.0-rc.1
+";
+
+fn message(h: &Harness) -> Option<String> {
+    h.state().message().map(|m| m.text.clone())
+}
+
+#[test]
+fn test_bump_version_under_cursor() {
+    let mut h = Harness::new(CHART).unwrap();
+    h.keys(":path $.version<CR>:bump minor<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Bumped to 1.5.0"));
+    assert!(h.document().unwrap().contains("version: 1.5.0\n"));
+
+    // A pre-release is released, keeping the v
+    h.keys(":path $.appVersion<CR>:bump major<CR>").unwrap();
+    assert!(h.document().unwrap().contains("appVersion: v2.0.0\n"));
+
+    h.keys("uu").unwrap();
+    assert_eq!(h.document().unwrap(), CHART);
+
+    h.keys(":path $.name<CR>:bump patch<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Not a semantic version"));
+    h.keys(":bump<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Usage: :bump major|minor|patch")
+    );
+}
+
+#[test]
+fn test_version_fields_are_checked() {
+    let mut h = Harness::new("name: app\nversion: \"1.4\"\n").unwrap();
+    let diagnostics = h.state().diagnostics().items();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "1.4 is not a semantic version: expected MAJOR.MINOR.PATCH, found 2 numbers"
+    );
+
+    h.keys("jK").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Not a semantic version: expected MAJOR.MINOR.PATCH, found 2 numbers")
+    );
+
+    h.keys("e<End>.0<CR>").unwrap();
+    assert!(h.state().diagnostics().is_empty());
+    h.keys("K").unwrap();
+    let explanation = h.state().explanation().unwrap();
+    assert_eq!(explanation.title, "Semantic version 1.4.0");
+    assert_eq!(explanation.lines[0], "Major 1, minor 4, patch 0");
+    assert_eq!(explanation.lines.last().unwrap(), "  major  2.0.0");
+}
@@ -0,0 +1,62 @@
+//! Integration tests for the read-only live view (`:share`).
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use termion::event::{Event, Key};
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::input::InputHandler;
+
+fn command(handler: &mut InputHandler, state: &mut EditorState, command: &str) {
+    for c in format!(":{}\n", command).chars() {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), state)
+            .unwrap();
+    }
+}
+
+/// Fetches `url` (`http://host:port/path`) and returns the whole response.
+fn fetch(url: &str) -> String {
+    let rest = url.strip_prefix("http://").unwrap();
+    let (addr, path) = rest.split_at(rest.find('/').unwrap());
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\n\r\n", path).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn test_share_serves_current_view() {
+    let tree = YamlTree::new(parse_yaml_auto("name: web\nreplicas: 3\n").unwrap());
+    let mut state = EditorState::new_with_default_theme(tree);
+    state.set_filename("app.yaml".to_string());
+    let mut handler = InputHandler::new();
+
+    command(&mut handler, &mut state, "share");
+    let url = state.share_url().unwrap();
+    assert!(state.message().unwrap().text.contains(&url));
+    let page = fetch(&url);
+    assert!(page.contains("<title>app.yaml (read-only)</title>"));
+    assert!(page.contains("replicas"));
+
+    // Asking again shows the running share
+    command(&mut handler, &mut state, "share");
+    assert_eq!(state.message().unwrap().text, format!("Sharing at {}", url));
+
+    command(&mut handler, &mut state, "share stop");
+    assert!(state.share_url().is_none());
+    command(&mut handler, &mut state, "share stop");
+    assert_eq!(state.message().unwrap().text, "Not sharing");
+}
+
+#[test]
+fn test_share_bad_address() {
+    let tree = YamlTree::new(parse_yaml_auto("a: 1\n").unwrap());
+    let mut state = EditorState::new_with_default_theme(tree);
+    let mut handler = InputHandler::new();
+    command(&mut handler, &mut state, "share not-an-address");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Error);
+    assert!(state.share_url().is_none());
+}
@@ -0,0 +1,99 @@
+//! Integration tests for sorting mapping keys (`:sort`, `gs`).
+
+use termion::event::{Event, Key};
+use yamlquill::document::node::YamlValue;
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::input::InputHandler;
+
+fn state_for(yaml: &str) -> EditorState {
+    EditorState::new_with_default_theme(YamlTree::new(parse_yaml_auto(yaml).unwrap()))
+}
+
+fn keys(handler: &mut InputHandler, state: &mut EditorState, keys: &str) {
+    for c in keys.chars() {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), state)
+            .unwrap();
+    }
+}
+
+fn keys_at(state: &EditorState, path: &[usize]) -> Vec<String> {
+    match state.tree().get_node(path).unwrap().value() {
+        YamlValue::Object(entries) => entries.keys().cloned().collect(),
+        _ => panic!("not a mapping"),
+    }
+}
+
+#[test]
+fn test_sort_containing_mapping() {
+    let mut state = state_for("zeta: 1\nalpha: 2\nMid: 3\n");
+    let mut handler = InputHandler::new();
+
+    // On a scalar the containing mapping is sorted, and the cursor follows its key
+    assert_eq!(state.cursor().path(), &[0]);
+    keys(&mut handler, &mut state, "gs");
+    assert_eq!(keys_at(&state, &[]), ["alpha", "Mid", "zeta"]);
+    assert_eq!(state.cursor().path(), &[2]);
+    assert_eq!(state.message().unwrap().text, "Sorted keys");
+
+    // One undo step restores the original order
+    assert!(state.undo());
+    assert_eq!(keys_at(&state, &[]), ["zeta", "alpha", "Mid"]);
+
+    keys(&mut handler, &mut state, ":sort!\n");
+    assert_eq!(keys_at(&state, &[]), ["zeta", "Mid", "alpha"]);
+}
+
+#[test]
+fn test_sort_recursive() {
+    let mut state = state_for("b:\n  y: 1\n  x: 2\na:\n  - d: 1\n    c: 2\n");
+    let mut handler = InputHandler::new();
+
+    // On an expanded mapping only its own keys are sorted
+    keys(&mut handler, &mut state, ":sort\n");
+    assert_eq!(keys_at(&state, &[]), ["b", "a"]);
+    assert_eq!(keys_at(&state, &[0]), ["x", "y"]);
+    assert!(state.undo());
+
+    // Collapsed, the containing (root) mapping is sorted, recursively
+    keys(&mut handler, &mut state, "h:sort r\n");
+    assert_eq!(state.message().unwrap().text, "Sorted 3 mappings");
+    assert_eq!(keys_at(&state, &[]), ["a", "b"]);
+    assert_eq!(keys_at(&state, &[1]), ["x", "y"]);
+    assert_eq!(keys_at(&state, &[0, 0]), ["c", "d"]);
+
+    keys(&mut handler, &mut state, ":sort r\n");
+    assert_eq!(state.message().unwrap().text, "Already sorted");
+}
+
+#[test]
+fn test_comments_move_with_their_key() {
+    let mut state = state_for("# about b\nb: 1\n# about a\na: 2\n");
+    let mut handler = InputHandler::new();
+    keys(&mut handler, &mut state, ":sort\n");
+
+    let order: Vec<String> = match state.tree().root().value() {
+        YamlValue::Object(entries) => entries
+            .values()
+            .map(|node| match node.value() {
+                YamlValue::Comment(comment) => comment.content().trim().to_string(),
+                value => value.to_string(),
+            })
+            .collect(),
+        _ => panic!("not a mapping"),
+    };
+    assert_eq!(order, ["about a", "2", "about b", "1"]);
+}
+
+#[test]
+fn test_sort_outside_mapping() {
+    let mut state = state_for("- b\n- a\n");
+    let mut handler = InputHandler::new();
+    keys(&mut handler, &mut state, ":sort\n");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Error);
+
+    keys(&mut handler, &mut state, ":sort x\n");
+    assert_eq!(state.message().unwrap().text, "Usage: :sort[!] [r]");
+}
@@ -0,0 +1,119 @@
+//! Integration tests for writing only staged changes (`:stage`, `:w --staged`).
+
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use termion::event::{Event, Key};
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::file::loader::load_yaml_file;
+use yamlquill::input::InputHandler;
+
+/// Opens `disk` from a file, then edits the buffer with `:path-set` commands.
+fn open_and_edit(dir: &TempDir, disk: &str, edits: &[&str]) -> (EditorState, PathBuf) {
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, disk).unwrap();
+    let mut state = EditorState::new_with_default_theme(load_yaml_file(&path).unwrap());
+    state.set_filename(path.display().to_string());
+    state.record_disk_state();
+    let mut handler = InputHandler::new();
+    for edit in edits {
+        command(&mut handler, &mut state, edit);
+    }
+    (state, path)
+}
+
+fn command(handler: &mut InputHandler, state: &mut EditorState, command: &str) {
+    for c in format!(":{}\n", command).chars() {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), state)
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_write_only_staged_changes() {
+    let dir = TempDir::new().unwrap();
+    let (mut state, path) = open_and_edit(
+        &dir,
+        "app:\n  replicas: 1\n  image: nginx:1.0\ndb:\n  size: 10\n",
+        &[
+            "path-set $.app.replicas 3",
+            "path-set $.app.image nginx:2.0",
+            "path-set $.db.size 20",
+        ],
+    );
+    let mut handler = InputHandler::new();
+
+    command(&mut handler, &mut state, "stage $.app");
+    assert_eq!(state.message().unwrap().text, "Staged 2 changes");
+
+    command(&mut handler, &mut state, "w --staged");
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "app:\n  replicas: 3\n  image: nginx:2.0\ndb:\n  size: 10\n"
+    );
+    assert!(state
+        .message()
+        .unwrap()
+        .text
+        .ends_with("2 staged changes, 1 left unsaved"));
+    // The unstaged change is still in the buffer
+    assert!(state.is_dirty());
+    assert!(state.staged().is_empty());
+
+    // Staging the rest and writing leaves a clean buffer
+    command(&mut handler, &mut state, "stage $.db.size");
+    command(&mut handler, &mut state, "w --staged");
+    assert!(!state.is_dirty());
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "app:\n  replicas: 3\n  image: nginx:2.0\ndb:\n  size: 20\n"
+    );
+}
+
+#[test]
+fn test_stage_at_cursor_and_removals() {
+    let dir = TempDir::new().unwrap();
+    let (mut state, path) = open_and_edit(
+        &dir,
+        "name: web\nports:\n  - 80\n  - 443\ndebug: true\n",
+        &[
+            "path-delete $.debug",
+            "path-delete $.ports[0]",
+            "path-set $.name api",
+        ],
+    );
+    let mut handler = InputHandler::new();
+
+    // The cursor is on "name"
+    command(&mut handler, &mut state, "stage");
+    assert_eq!(state.message().unwrap().text, "Staged 1 change");
+    // Removed keys are staged through their parent
+    command(&mut handler, &mut state, "stage $");
+    assert_eq!(state.message().unwrap().text, "Staged 2 changes");
+    command(&mut handler, &mut state, "unstage");
+    assert!(state.staged().is_empty());
+
+    // Unstaged removals are put back in the file
+    command(&mut handler, &mut state, "stage $.name");
+    command(&mut handler, &mut state, "w --staged");
+    let written: serde_yaml::Value =
+        serde_yaml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let expected: serde_yaml::Value =
+        serde_yaml::from_str("name: api\nports: [80, 443]\ndebug: true\n").unwrap();
+    assert_eq!(written, expected);
+}
+
+#[test]
+fn test_staging_errors() {
+    let dir = TempDir::new().unwrap();
+    let (mut state, _) = open_and_edit(&dir, "a: 1\nb: 2\n", &["path-set $.b 3"]);
+    let mut handler = InputHandler::new();
+
+    command(&mut handler, &mut state, "w --staged");
+    assert_eq!(state.message().unwrap().text, "Nothing staged (use :stage)");
+
+    command(&mut handler, &mut state, "stage");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Warning);
+    assert!(state.staged().is_empty());
+}
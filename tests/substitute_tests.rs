@@ -0,0 +1,109 @@
+//! Integration tests for `:%s` search and replace.
+
+use termion::event::{Event, Key};
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::input::InputHandler;
+
+const DEPLOY: &str = "\
+name: web
+image: registry.old.example.com/web:1.2
+sidecar:
+  image: registry.old.example.com/proxy:0.9
+old_flag: true
+";
+
+fn state_for(yaml: &str) -> EditorState {
+    EditorState::new_with_default_theme(YamlTree::new(parse_yaml_auto(yaml).unwrap()))
+}
+
+fn type_command(handler: &mut InputHandler, state: &mut EditorState, command: &str) {
+    for c in format!(":{}\n", command).chars() {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), state)
+            .unwrap();
+    }
+}
+
+fn value_at(state: &EditorState, path: &[usize]) -> String {
+    state.tree().get_node(path).unwrap().value().to_string()
+}
+
+#[test]
+fn test_substitute_values_single_undo() {
+    let mut state = state_for(DEPLOY);
+    let mut handler = InputHandler::new();
+
+    type_command(&mut handler, &mut state, r"%s/registry\.old/registry.new/");
+    assert_eq!(value_at(&state, &[1]), "registry.new.example.com/web:1.2");
+    assert_eq!(
+        value_at(&state, &[2, 0]),
+        "registry.new.example.com/proxy:0.9"
+    );
+    assert_eq!(state.message().unwrap().text, "2 substitutions on 2 nodes");
+    // Cursor moves to the first change
+    assert_eq!(state.cursor().path(), &[1]);
+
+    assert!(state.undo());
+    assert_eq!(value_at(&state, &[1]), "registry.old.example.com/web:1.2");
+    assert_eq!(
+        value_at(&state, &[2, 0]),
+        "registry.old.example.com/proxy:0.9"
+    );
+}
+
+#[test]
+fn test_substitute_captures_and_global() {
+    let mut state = state_for("tags: a-1 b-2 c-3\n");
+    let mut handler = InputHandler::new();
+
+    type_command(&mut handler, &mut state, r"%s/(\w)-(\d)/\2\1/g");
+    assert_eq!(value_at(&state, &[0]), "1a 2b 3c");
+    assert_eq!(state.message().unwrap().text, "3 substitutions on 1 node");
+}
+
+#[test]
+fn test_substitute_keys_with_flag() {
+    let mut state = state_for(DEPLOY);
+    let mut handler = InputHandler::new();
+
+    // Without `k`, keys are left alone
+    type_command(&mut handler, &mut state, "%s/old_/new_/");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Error);
+    assert!(!state.is_dirty());
+
+    type_command(&mut handler, &mut state, "%s/old_/new_/k");
+    let root = state.tree().root();
+    let yamlquill::document::node::YamlValue::Object(entries) = root.value() else {
+        panic!("expected a mapping");
+    };
+    assert_eq!(entries.get_index(3).unwrap().0, "new_flag");
+}
+
+#[test]
+fn test_substitute_count_only_and_case() {
+    let mut state = state_for(DEPLOY);
+    let mut handler = InputHandler::new();
+
+    type_command(&mut handler, &mut state, "%s/OLD/new/gin");
+    assert_eq!(state.message().unwrap().text, "2 matches on 2 nodes");
+    assert!(!state.is_dirty());
+
+    type_command(&mut handler, &mut state, "%s/OLD/new/g");
+    assert_eq!(state.message().unwrap().level, MessageLevel::Error);
+
+    type_command(&mut handler, &mut state, "%s/(unclosed/x/");
+    assert!(state.message().unwrap().text.contains("Invalid regex"));
+}
+
+#[test]
+fn test_substitute_key_clash_is_skipped() {
+    let mut state = state_for("a_x: 1\nb_x: 2\n");
+    let mut handler = InputHandler::new();
+
+    type_command(&mut handler, &mut state, "%s/^b/a/k");
+    let message = state.message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert!(message.text.contains("already exist"));
+}
@@ -0,0 +1,66 @@
+//! Tests for explicit YAML tags (`!Ref`, `!!str`) and the `:tag` command.
+
+use yamlquill::document::node::{YamlString, YamlValue};
+use yamlquill::editor::state::MessageLevel;
+use yamlquill::harness::Harness;
+
+const TEMPLATE: &str = "\
+Resources:
+  Bucket:
+    Type: AWS::S3::Bucket
+    Properties:
+      BucketName: !Sub '${AWS::StackName}-logs'
+      Arn: !GetAtt Role.Arn
+Outputs:
+  Name: !Ref Bucket
+  Port: !!str 80
+  Url: !Join [':', [a, b]]
+";
+
+#[test]
+fn test_tags_survive_an_unmodified_save() {
+    let h = Harness::new(TEMPLATE).unwrap();
+    assert_eq!(h.document().unwrap(), TEMPLATE);
+    let port = h.state().tree().get_node(&[1, 1]).unwrap();
+    assert_eq!(port.tag(), Some("!!str"));
+    assert_eq!(
+        port.value(),
+        &YamlValue::String(YamlString::Plain("80".to_string()))
+    );
+}
+
+#[test]
+fn test_tags_survive_editing_their_section() {
+    let mut h = Harness::new(TEMPLATE).unwrap().with_size(80, 20);
+    h.keys(":path $.Outputs.Name<CR>").unwrap();
+    assert_eq!(h.state().cursor().path(), &[1, 0]);
+    h.keys("e<BS><BS><BS><BS><BS><BS>Queue<CR>").unwrap();
+    let doc = h.document().unwrap();
+    assert!(doc.contains("Name: !Ref Queue\n"), "{}", doc);
+    assert!(doc.contains("Port: !!str '80'\n"), "{}", doc);
+    assert!(doc.contains("Url: !Join [':', [a, b]]\n"), "{}", doc);
+    assert!(doc.contains("!GetAtt Role.Arn"), "{}", doc);
+}
+
+#[test]
+fn test_tag_command() {
+    let mut h = Harness::new("name: Bucket\n").unwrap().with_size(40, 5);
+    h.keys(":tag<CR>").unwrap();
+    assert!(h.state().message().unwrap().text.starts_with("No tag"));
+
+    h.keys(":tag !Ref<CR>").unwrap();
+    assert_eq!(h.state().message().unwrap().text, "Tagged !Ref");
+    assert_eq!(h.document().unwrap(), "name: !Ref Bucket\n");
+    assert!(h.screen().unwrap().contains("name: !Ref Bucket"));
+
+    h.keys(":tag Ref<CR>").unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert!(message.text.starts_with("Invalid tag 'Ref'"));
+
+    h.keys(":notag<CR>").unwrap();
+    assert_eq!(h.document().unwrap(), "name: Bucket\n");
+
+    h.keys("u").unwrap();
+    assert_eq!(h.document().unwrap(), "name: !Ref Bucket\n");
+}
@@ -0,0 +1,131 @@
+//! Integration tests for the `:tasks` TODO/FIXME panel.
+
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use std::fs;
+use tempfile::TempDir;
+use termion::event::{Event, Key};
+use yamlquill::editor::state::{EditorState, MessageLevel};
+use yamlquill::editor::tasks::TaskSource;
+use yamlquill::file::loader::load_yaml_file;
+use yamlquill::input::InputHandler;
+use yamlquill::theme::get_builtin_theme;
+use yamlquill::ui::UI;
+
+const CONFIG: &str = "\
+# TODO: split into per-env files
+name: web
+ports:
+  - 80
+  # FIXME: 8443 is blocked by the firewall
+  - 8443
+replicas: 3
+";
+
+fn type_command(handler: &mut InputHandler, state: &mut EditorState, command: &str) {
+    for c in format!(":{}\n", command).chars() {
+        handler
+            .handle_event(Event::Key(Key::Char(c)), state)
+            .unwrap();
+    }
+}
+
+fn press(handler: &mut InputHandler, state: &mut EditorState, key: Key) {
+    handler.handle_event(Event::Key(key), state).unwrap();
+}
+
+fn open_config(dir: &TempDir) -> EditorState {
+    let path = dir.path().join("app.yaml");
+    fs::write(&path, CONFIG).unwrap();
+    let mut state = EditorState::new_with_default_theme(load_yaml_file(&path).unwrap());
+    state.set_filename(path.display().to_string());
+    state
+}
+
+#[test]
+fn test_tasks_from_comments_and_notes() {
+    let dir = TempDir::new().unwrap();
+    let mut state = open_config(&dir);
+    let mut handler = InputHandler::new();
+
+    // A note on `replicas` counts too
+    let replicas = state
+        .tree_view()
+        .lines()
+        .iter()
+        .find(|l| l.key.as_deref() == Some("replicas"))
+        .unwrap()
+        .path
+        .clone();
+    state.cursor_mut().set_path(replicas.clone());
+    type_command(
+        &mut handler,
+        &mut state,
+        "note TODO confirm with capacity plan",
+    );
+
+    type_command(&mut handler, &mut state, "tasks");
+    let panel = state.task_list().unwrap();
+    let found: Vec<_> = panel.tasks.iter().map(|t| (t.marker, t.source)).collect();
+    assert_eq!(
+        found,
+        [
+            ("TODO", TaskSource::Comment),
+            ("FIXME", TaskSource::Comment),
+            ("TODO", TaskSource::Note),
+        ]
+    );
+
+    let ui = UI::new(get_builtin_theme("default-dark").unwrap());
+    let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+    ui.render(&mut terminal, &mut state).unwrap();
+    let text: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect();
+    assert!(text.contains("Tasks (3)"));
+    assert!(text.contains("8443 is blocked"));
+
+    // Third entry jumps to the annotated node
+    press(&mut handler, &mut state, Key::Char('k'));
+    press(&mut handler, &mut state, Key::Char('\n'));
+    assert!(state.task_list().is_none());
+    assert_eq!(state.cursor().path(), replicas.as_slice());
+}
+
+#[test]
+fn test_task_jump_expands_collapsed_parent() {
+    let dir = TempDir::new().unwrap();
+    let mut state = open_config(&dir);
+    let mut handler = InputHandler::new();
+
+    type_command(&mut handler, &mut state, "tasks");
+    let fixme = state.task_list().unwrap().tasks[1].path.clone();
+    press(&mut handler, &mut state, Key::Esc);
+    assert!(state.task_list().is_none());
+
+    // Collapse `ports`, then jump into it
+    state.cursor_mut().set_path(fixme[..1].to_vec());
+    state.toggle_expand_at_cursor();
+    assert!(!state.tree_view().lines().iter().any(|l| l.path == fixme));
+
+    type_command(&mut handler, &mut state, "tasks");
+    press(&mut handler, &mut state, Key::Char('j'));
+    press(&mut handler, &mut state, Key::Char('\n'));
+    assert_eq!(state.cursor().path(), fixme.as_slice());
+    assert!(state.tree_view().lines().iter().any(|l| l.path == fixme));
+}
+
+#[test]
+fn test_no_tasks() {
+    let mut state = EditorState::new_with_default_theme(yamlquill::document::tree::YamlTree::new(
+        yamlquill::document::parser::parse_yaml_auto("# nothing to do\na: 1\n").unwrap(),
+    ));
+    let mut handler = InputHandler::new();
+    type_command(&mut handler, &mut state, "tasks");
+    assert!(state.task_list().is_none());
+    assert_eq!(state.message().unwrap().level, MessageLevel::Info);
+}
@@ -1,5 +1,7 @@
 use ratatui::style::Color;
-use yamlquill::theme::{colors::ThemeColors, get_builtin_theme};
+use yamlquill::theme::{
+    colors::ThemeColors, get_builtin_theme, list_themes_in, load_theme_file, load_theme_from,
+};
 
 // Tests for get_builtin_theme function
 
@@ -164,3 +166,75 @@ fn test_both_themes_have_different_cursor_colors() {
     assert_eq!(dark.colors.cursor, Color::LightBlue);
     assert_eq!(light.colors.cursor, Color::Rgb(82, 139, 255));
 }
+
+// Tests for user themes loaded from TOML files
+
+#[test]
+fn test_user_theme_extends_builtin_and_overrides_colors() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("my-nord.toml");
+    std::fs::write(
+        &path,
+        "extends = \"nord\"\nkey = \"#88c0d0\"\nstring = \"LightGreen\"\ncursor = \"208\"\n",
+    )
+    .unwrap();
+
+    let theme = load_theme_file(&path).unwrap();
+    let nord = get_builtin_theme("nord").unwrap();
+    assert_eq!(theme.name, "my-nord");
+    assert_eq!(theme.colors.key, Color::Rgb(0x88, 0xc0, 0xd0));
+    assert_eq!(theme.colors.string, Color::LightGreen);
+    assert_eq!(theme.colors.cursor, Color::Indexed(208));
+    assert_eq!(theme.colors.background, nord.colors.background);
+}
+
+#[test]
+fn test_user_theme_defaults_to_default_dark_base() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("mine.toml"), "null = \"Magenta\"\n").unwrap();
+
+    let theme = load_theme_from(Some(dir.path()), "mine").unwrap();
+    let base = ThemeColors::default_dark();
+    assert_eq!(theme.colors.null, Color::Magenta);
+    assert_eq!(theme.colors.key, base.key);
+}
+
+#[test]
+fn test_user_theme_rejects_bad_colors_and_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let bad_color = dir.path().join("bad-color.toml");
+    std::fs::write(&bad_color, "key = \"not-a-color\"\n").unwrap();
+    let err = format!("{:#}", load_theme_file(&bad_color).unwrap_err());
+    assert!(err.contains("invalid color 'not-a-color'"), "{}", err);
+
+    let bad_key = dir.path().join("bad-key.toml");
+    std::fs::write(&bad_key, "keys = \"Red\"\n").unwrap();
+    assert!(load_theme_file(&bad_key).is_err());
+
+    let bad_base = dir.path().join("bad-base.toml");
+    std::fs::write(&bad_base, "extends = \"nope\"\n").unwrap();
+    let err = format!("{:#}", load_theme_file(&bad_base).unwrap_err());
+    assert!(err.contains("unknown base theme 'nope'"), "{}", err);
+}
+
+#[test]
+fn test_list_themes_includes_user_themes() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("zebra.toml"), "").unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+    let themes = list_themes_in(Some(dir.path()));
+    assert!(themes.contains(&"zebra".to_string()));
+    assert!(themes.contains(&"default-dark".to_string()));
+    assert!(!themes.contains(&"notes".to_string()));
+    assert!(load_theme_from(Some(dir.path()), "missing").is_err());
+}
+
+#[test]
+fn test_theme_colors_round_trip_through_toml() {
+    let colors = get_builtin_theme("dracula").unwrap().colors;
+    let text = toml::to_string(&colors).unwrap();
+    let parsed: ThemeColors = toml::from_str(&text).unwrap();
+    assert_eq!(parsed.key, colors.key);
+    assert_eq!(parsed.visual_selection_bg, colors.visual_selection_bg);
+}
@@ -0,0 +1,97 @@
+//! Integration tests for trust-gated project configs and modelines.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::config::trust::TrustStore;
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::{EditorState, MessageLevel};
+
+fn state_in(dir: &TempDir, yaml: &str, store: TrustStore) -> EditorState {
+    let file = dir.path().join("values.yaml");
+    fs::write(&file, yaml).unwrap();
+
+    let tree = YamlTree::with_source(parse_yaml_auto(yaml).unwrap(), Some(yaml.to_string()));
+    let mut state = EditorState::new_with_default_theme(tree);
+    state.set_filename(file.to_string_lossy().to_string());
+    state.set_trust_store(store);
+    state
+}
+
+#[test]
+fn test_untrusted_project_config_is_ignored() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join(".yamlquill.toml"), "indent_size = 4\n").unwrap();
+    let mut state = state_in(&dir, "a: 1\n", TrustStore::new());
+
+    state.load_buffer_settings();
+
+    assert_eq!(state.indent_size(), 2);
+    assert!(state.project_config().is_none());
+    let message = state.message().unwrap();
+    assert_eq!(message.level, MessageLevel::Warning);
+    assert!(message.text.contains(".yamlquill.toml"));
+}
+
+#[test]
+fn test_untrusted_modeline_is_ignored() {
+    let dir = TempDir::new().unwrap();
+    let mut state = state_in(&dir, "# yamlquill: indent=4\na: 1\n", TrustStore::new());
+
+    state.load_buffer_settings();
+
+    assert_eq!(state.indent_size(), 2);
+    assert!(state.modeline().is_none());
+    assert!(state.message().unwrap().text.contains("modeline"));
+}
+
+#[test]
+fn test_trust_applies_project_config_and_modeline() {
+    let dir = TempDir::new().unwrap();
+    fs::write(
+        dir.path().join(".yamlquill.toml"),
+        "show_line_numbers = false\n",
+    )
+    .unwrap();
+    let mut state = state_in(&dir, "# yamlquill: indent=4\na: 1\n", TrustStore::new());
+    state.load_buffer_settings();
+
+    state.trust_current_location().unwrap();
+
+    assert!(state.project_config().is_some());
+    assert!(!state.show_line_numbers());
+    assert_eq!(state.indent_size(), 4);
+}
+
+#[test]
+fn test_changed_project_config_needs_trust_again() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join(".yamlquill.toml");
+    fs::write(&config, "indent_size = 4\n").unwrap();
+    let mut store = TrustStore::new();
+    store.trust(dir.path(), Some("indent_size = 4\n"));
+    let mut state = state_in(&dir, "a: 1\n", store);
+
+    state.load_buffer_settings();
+    assert_eq!(state.indent_size(), 4);
+
+    fs::write(&config, "indent_size = 8\n").unwrap();
+    state.load_buffer_settings();
+    assert_eq!(state.indent_size(), 2);
+    assert_eq!(state.message().unwrap().level, MessageLevel::Warning);
+}
+
+#[test]
+fn test_untrust_reverts_settings() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join(".yamlquill.toml"), "indent_size = 4\n").unwrap();
+    let mut state = state_in(&dir, "a: 1\n", TrustStore::new());
+    state.trust_current_location().unwrap();
+    assert_eq!(state.indent_size(), 4);
+
+    assert!(state.untrust_current_location().unwrap().is_some());
+
+    assert_eq!(state.indent_size(), 2);
+    assert!(state.project_config().is_none());
+    assert!(state.untrust_current_location().unwrap().is_none());
+}
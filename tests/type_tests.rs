@@ -0,0 +1,66 @@
+//! Tests for type badges and changing a scalar's type.
+
+use yamlquill::editor::state::MessageLevel;
+use yamlquill::harness::Harness;
+
+const SERVICE: &str = "\
+port: \"80\"
+ratio: 0.5
+debug: \"yes\"
+name: web
+tags: [a, b]
+";
+
+#[test]
+fn test_type_badges() {
+    let mut h = Harness::new(SERVICE).unwrap().with_size(60, 10);
+    let screen = h.screen().unwrap();
+    assert!(!screen.contains(" str"), "{}", screen);
+
+    h.keys(":set typebadges<CR>").unwrap();
+    let screen = h.screen().unwrap();
+    let lines: Vec<&str> = screen.lines().collect();
+    assert!(lines[0].trim_end().ends_with("80 str"), "{}", screen);
+    assert!(lines[1].trim_end().ends_with("0.5 float"), "{}", screen);
+    assert!(lines[4].trim_end().ends_with("seq"), "{}", screen);
+}
+
+#[test]
+fn test_change_string_to_int() {
+    let mut h = Harness::new(SERVICE).unwrap();
+    h.keys("gti").unwrap();
+    assert_eq!(h.state().message().unwrap().text, "Changed to int");
+    assert!(h.document().unwrap().starts_with("port: 80\n"));
+
+    // Back to a string, which has to be quoted to stay one
+    h.keys(":type str<CR>").unwrap();
+    assert!(h.document().unwrap().starts_with("port: '80'\n"));
+
+    h.keys("uu").unwrap();
+    assert_eq!(h.document().unwrap(), SERVICE);
+}
+
+#[test]
+fn test_change_type_validates() {
+    let mut h = Harness::new(SERVICE).unwrap();
+    h.keys("jjjgti").unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.text, "Can't convert 'web' to int");
+    assert_eq!(message.level, MessageLevel::Error);
+    assert_eq!(h.document().unwrap(), SERVICE);
+
+    h.keys("kgtb").unwrap();
+    assert!(h.document().unwrap().contains("debug: true\n"));
+
+    h.keys("jjgts").unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "Only scalar values can change type"
+    );
+
+    h.keys(":type map<CR>").unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "Usage: :type str|int|float|bool|null"
+    );
+}
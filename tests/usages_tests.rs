@@ -0,0 +1,81 @@
+//! Tests for `:usages` on a scalar value.
+
+use std::fs;
+use tempfile::TempDir;
+use yamlquill::harness::Harness;
+
+const SERVICE: &str = "\
+port: 8080
+service:
+  targetPort: 8080
+env:
+  PORT: '8080'
+name: api
+";
+
+fn message(h: &Harness) -> Option<String> {
+    h.state().message().map(|m| m.text.clone())
+}
+
+fn locations(h: &Harness) -> Vec<String> {
+    h.state()
+        .usages_panel()
+        .map(|panel| panel.usages.iter().map(|u| u.location.clone()).collect())
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_usages_lists_other_uses_in_the_document() {
+    let mut h = Harness::new(SERVICE).unwrap();
+    h.keys(":usages<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("2 other uses of 8080"));
+    assert_eq!(locations(&h), vec![".service.targetPort", ".env.PORT"]);
+
+    // Enter jumps to the selected use and closes the panel
+    h.keys("j<CR>").unwrap();
+    assert!(h.state().usages_panel().is_none());
+    assert_eq!(h.state().cursor().path(), &[2, 0]);
+
+    h.keys(":path $.name<CR>:usages<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("No other uses of api"));
+    assert!(h.state().usages_panel().is_none());
+
+    h.keys(":path $.service<CR>:usages value<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Not on a scalar value"));
+}
+
+#[test]
+fn test_usages_project_searches_yaml_files() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join(".git")).unwrap();
+    fs::create_dir_all(dir.path().join("charts/api")).unwrap();
+    fs::create_dir_all(dir.path().join(".cache")).unwrap();
+    fs::write(dir.path().join("service.yaml"), SERVICE).unwrap();
+    fs::write(
+        dir.path().join("charts/api/values.yml"),
+        "ports:\n- 80\n- 8080\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("notes.txt"), "8080\n").unwrap();
+    fs::write(dir.path().join(".cache/old.yaml"), "port: 8080\n").unwrap();
+
+    let mut h = Harness::open(dir.path().join("service.yaml")).unwrap();
+    h.keys(":usages project<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("3 other uses of 8080"));
+    assert_eq!(
+        locations(&h),
+        vec![
+            ".service.targetPort",
+            ".env.PORT",
+            "charts/api/values.yml:.ports[1]"
+        ]
+    );
+
+    // Selecting a use in another file opens it at that node
+    h.keys("jj<CR>").unwrap();
+    assert!(h
+        .state()
+        .filename()
+        .is_some_and(|name| name.ends_with("values.yml")));
+    assert_eq!(h.state().cursor().path(), &[0, 1]);
+}
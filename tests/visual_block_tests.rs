@@ -0,0 +1,100 @@
+//! Tests for visual block (column) yank and paste.
+
+use yamlquill::harness::Harness;
+
+const HOSTS: &str = "\
+hosts:
+- name: web
+  region: us-east
+- name: db
+  region: eu-west
+- name: cache
+";
+
+#[test]
+fn test_visual_block_selects_same_key_across_elements() {
+    let mut h = Harness::new(HOSTS).unwrap();
+    // hosts[0].name
+    h.keys("jj<C-v>").unwrap();
+    assert!(h.state().is_visual_block());
+    h.keys("jjj").unwrap();
+
+    // The block holds `name` from the first two elements, not `region`
+    assert_eq!(
+        h.state().visual_selection(),
+        &[vec![0, 0, 0], vec![0, 1, 0]]
+    );
+    assert!(h.screen().unwrap().contains("VISUAL BLOCK"));
+
+    h.keys("<Esc>").unwrap();
+    assert!(!h.state().is_visual_block());
+}
+
+#[test]
+fn test_visual_block_needs_key_of_list_element() {
+    let mut h = Harness::new(HOSTS).unwrap();
+    h.keys("j<C-v>").unwrap();
+    assert!(!h.state().is_visual_block());
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text.contains("Visual block needs")));
+}
+
+#[test]
+fn test_column_yank_pastes_element_by_element() {
+    let mut h = Harness::new(
+        "\
+src:
+- name: a
+- name: b
+dst:
+- id: 1
+- id: 2
+  name: old
+",
+    )
+    .unwrap();
+    // Yank `name` from both src elements
+    h.keys("jj<C-v>jjy").unwrap();
+    assert!(h.state().get_unnamed_register().column);
+    assert_eq!(h.state().get_unnamed_register().nodes.len(), 2);
+
+    // Paste with the cursor anywhere in dst[0]
+    h.keys("Gkkk").unwrap();
+    assert_eq!(h.state().cursor().path(), &[1, 0, 0]);
+    h.keys("p").unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "\
+src:
+- name: a
+- name: b
+dst:
+- id: 1
+  name: a
+- id: 2
+  name: b
+"
+    );
+
+    // One undo step reverts the whole column
+    h.keys("u").unwrap();
+    assert!(h.document().unwrap().contains("name: old"));
+}
+
+#[test]
+fn test_column_paste_over_block_copies_value_down() {
+    let mut h = Harness::new(HOSTS).unwrap();
+    // Yank the first region as a one-value column
+    h.keys("jjj<C-v>y").unwrap();
+
+    // Select region across all three elements and paste over it
+    h.keys("<C-v>jjjjjp").unwrap();
+    let doc = h.document().unwrap();
+    assert_eq!(doc.matches("region: us-east").count(), 3, "{}", doc);
+    assert!(h
+        .state()
+        .message()
+        .is_some_and(|m| m.text == "Pasted 'region' into 3 elements"));
+}
@@ -0,0 +1,64 @@
+//! Tests for visual `c` (change values) and `r` (rename keys).
+
+use yamlquill::harness::Harness;
+
+const SERVICE: &str = "\
+name: api
+replicas: 2
+ports:
+- 80
+- 443
+";
+
+fn message(h: &Harness) -> Option<String> {
+    h.state().message().map(|m| m.text.clone())
+}
+
+#[test]
+fn test_change_sets_every_selected_scalar() {
+    let mut h = Harness::new(SERVICE).unwrap();
+    // Select replicas and the ports list; the list's items are changed too
+    h.keys("jVjc8080<CR>").unwrap();
+    assert_eq!(
+        h.document().unwrap().trim_end(),
+        "\
+name: api
+replicas: 8080
+ports:
+- 8080
+- 8080"
+    );
+    assert_eq!(message(&h).as_deref(), Some("Changed 3 values"));
+    assert!(h.state().visual_selection().is_empty());
+
+    // The whole change is one undo step
+    h.keys("u").unwrap();
+    assert_eq!(h.document().unwrap(), SERVICE);
+}
+
+#[test]
+fn test_rename_applies_template_to_selected_keys() {
+    let mut h = Harness::new(SERVICE).unwrap();
+    h.keys("Vjrsvc_{}<CR>").unwrap();
+    let doc = h.document().unwrap();
+    assert!(
+        doc.starts_with("svc_name: api\nsvc_replicas: 2\nports:\n"),
+        "{}",
+        doc
+    );
+    assert_eq!(message(&h).as_deref(), Some("Renamed 2 keys"));
+
+    h.keys("u").unwrap();
+    assert_eq!(h.document().unwrap(), SERVICE);
+}
+
+#[test]
+fn test_rename_refuses_clashes_and_bad_templates() {
+    let mut h = Harness::new("a: 1\nb: 2\nb_old: 3\n").unwrap();
+    h.keys("Vjr{}_old<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Key 'b_old' already exists"));
+    assert_eq!(h.document().unwrap(), "a: 1\nb: 2\nb_old: 3\n");
+
+    h.keys("Vrold<CR>").unwrap();
+    assert!(message(&h).is_some_and(|m| m.starts_with("Use {} for the old key")));
+}
@@ -0,0 +1,58 @@
+//! Tests for watch expressions and the watch panel.
+
+use yamlquill::editor::state::MessageLevel;
+use yamlquill::harness::Harness;
+
+const VALUES: &str = "\
+server:
+  replicas: 3
+  image: nginx:1.25
+worker:
+  replicas: 2
+  image: nginx:1.25
+";
+
+#[test]
+fn test_watch_follows_edits() {
+    let mut h = Harness::new(VALUES).unwrap().with_size(70, 16);
+    h.keys(":watch $..replicas<CR>").unwrap();
+    assert_eq!(
+        h.state().message().unwrap().text,
+        "Watching $..replicas as 1"
+    );
+    let screen = h.screen().unwrap();
+    assert!(
+        screen.contains("1 $..replicas  2 matches: 3, 2"),
+        "{}",
+        screen
+    );
+
+    h.keys("je<BS>5<CR>").unwrap();
+    let watch = &h.state().watches()[0];
+    assert_eq!(watch.value(), "2 matches: 5, 2");
+    assert_eq!(watch.previous(), Some("2 matches: 3, 2"));
+    assert!(h.screen().unwrap().contains("(was 2 matches: 3, 2)"));
+
+    h.keys("u").unwrap();
+    assert_eq!(h.state().watches()[0].value(), "2 matches: 3, 2");
+}
+
+#[test]
+fn test_watch_cursor_path_and_unwatch() {
+    let mut h = Harness::new(VALUES).unwrap();
+    h.keys("jj:watch<CR>").unwrap();
+    assert_eq!(h.state().watches()[0].value(), "nginx:1.25");
+
+    h.keys(":%s/1.25/1.27/g<CR>").unwrap();
+    assert_eq!(h.state().watches()[0].value(), "nginx:1.27");
+
+    h.keys(":watch $[<CR>").unwrap();
+    let message = h.state().message().unwrap();
+    assert_eq!(message.level, MessageLevel::Error);
+    assert!(message.text.starts_with("Invalid YAMLPath"));
+
+    h.keys(":unwatch 2<CR>").unwrap();
+    assert_eq!(h.state().message().unwrap().text, "No watch 2");
+    h.keys(":unwatch 1<CR>").unwrap();
+    assert!(h.state().watches().is_empty());
+}
@@ -0,0 +1,93 @@
+//! Integration tests for split windows (`:vsplit`, `Ctrl-w`).
+
+use std::fs;
+use tempfile::TempDir;
+use termion::event::{Event, Key};
+use yamlquill::app::App;
+use yamlquill::document::parser::parse_yaml_auto;
+use yamlquill::document::tree::YamlTree;
+use yamlquill::editor::state::EditorState;
+use yamlquill::input::InputHandler;
+
+fn send(handler: &mut InputHandler, app: &mut App, keys: &[Key]) {
+    for &key in keys {
+        handler
+            .handle_event(Event::Key(key), app.focused_mut())
+            .unwrap();
+        app.handle_pending();
+    }
+}
+
+fn command(handler: &mut InputHandler, app: &mut App, cmd: &str) {
+    let mut keys = vec![Key::Char(':')];
+    keys.extend(cmd.chars().map(Key::Char));
+    keys.push(Key::Char('\n'));
+    send(handler, app, &keys);
+}
+
+fn app_with(yaml: &str) -> App {
+    let tree = YamlTree::new(parse_yaml_auto(yaml).unwrap());
+    let mut state = EditorState::new_with_default_theme(tree);
+    state.set_filename("left.yaml".to_string());
+    App::new(state)
+}
+
+#[test]
+fn test_vsplit_opens_file_in_new_pane() {
+    let dir = TempDir::new().unwrap();
+    let right = dir.path().join("right.yaml");
+    fs::write(&right, "port: 8080\n").unwrap();
+
+    let mut handler = InputHandler::new();
+    let mut app = app_with("port: 80\n");
+    command(
+        &mut handler,
+        &mut app,
+        &format!("vsplit {}", right.display()),
+    );
+
+    assert_eq!(app.windows().len(), 2);
+    assert_eq!(app.windows().focus_index(), 1);
+    assert_eq!(app.focused().filename(), Some(right.to_str().unwrap()));
+    assert_eq!(app.buffers()[0].filename(), Some("left.yaml"));
+}
+
+#[test]
+fn test_vsplit_missing_file_reports_error() {
+    let mut handler = InputHandler::new();
+    let mut app = app_with("port: 80\n");
+    command(&mut handler, &mut app, "vsplit /nonexistent/nope.yaml");
+
+    assert_eq!(app.windows().len(), 1);
+    assert!(app.focused().message().is_some());
+}
+
+#[test]
+fn test_ctrl_w_switches_focus_and_edits_stay_per_pane() {
+    let dir = TempDir::new().unwrap();
+    let right = dir.path().join("right.yaml");
+    fs::write(&right, "a: 1\nb: 2\n").unwrap();
+
+    let mut handler = InputHandler::new();
+    let mut app = app_with("x: 1\ny: 2\n");
+    command(
+        &mut handler,
+        &mut app,
+        &format!("vsplit {}", right.display()),
+    );
+
+    // Move down in the right pane only
+    send(&mut handler, &mut app, &[Key::Char('j')]);
+    assert_eq!(app.focused().cursor().path(), &[1]);
+
+    send(&mut handler, &mut app, &[Key::Ctrl('w'), Key::Char('h')]);
+    assert_eq!(app.windows().focus_index(), 0);
+    assert_eq!(app.focused().cursor().path(), &[0]);
+
+    send(&mut handler, &mut app, &[Key::Ctrl('w'), Key::Char('w')]);
+    assert_eq!(app.windows().focus_index(), 1);
+
+    command(&mut handler, &mut app, "close");
+    assert_eq!(app.windows().len(), 1);
+    assert_eq!(app.focused().filename(), Some("left.yaml"));
+}
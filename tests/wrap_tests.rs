@@ -0,0 +1,118 @@
+//! Tests for wrapping a node in a new list (`g[`) or mapping (`g{`), and
+//! for unwrapping (`g]`) and splicing containers.
+
+use yamlquill::harness::Harness;
+
+fn message(h: &Harness) -> Option<String> {
+    h.state().message().map(|m| m.text.clone())
+}
+
+#[test]
+fn test_wrap_array_turns_a_value_into_a_list() {
+    let yaml = "name: api\nport: 80\n";
+    let mut h = Harness::new(yaml).unwrap();
+    h.keys("jg[").unwrap();
+    assert_eq!(h.document().unwrap(), "name: api\nport:\n- 80\n");
+    assert_eq!(message(&h).as_deref(), Some("Wrapped in a list"));
+    assert_eq!(h.state().cursor().path(), &[1, 0]);
+
+    // The wrapped node can be wrapped again
+    h.keys(":wrap-array<CR>").unwrap();
+    assert_eq!(h.document().unwrap(), "name: api\nport:\n- - 80\n");
+
+    h.keys("uu").unwrap();
+    assert_eq!(h.document().unwrap(), yaml);
+}
+
+#[test]
+fn test_wrap_object_nests_under_a_key() {
+    let mut h = Harness::new("images:\n- nginx\n- redis\n").unwrap();
+    h.keys(":path $.images[1]<CR>g{image<CR>").unwrap();
+    assert_eq!(h.document().unwrap(), "images:\n- nginx\n- image: redis\n");
+    assert_eq!(message(&h).as_deref(), Some("Wrapped under image"));
+    assert_eq!(h.state().cursor().path(), &[0, 1, 0]);
+
+    // A whole mapping can be nested too
+    let mut h = Harness::new("db:\n  host: a\n  port: 5432\n").unwrap();
+    h.keys(":wrap-object primary<CR>").unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "db:\n  primary:\n    host: a\n    port: 5432\n"
+    );
+}
+
+#[test]
+fn test_wrap_errors() {
+    let mut h = Harness::new("a: 1\n").unwrap();
+    h.keys(":wrap-object<CR>").unwrap();
+    assert_eq!(message(&h).as_deref(), Some("Usage: :wrap-object <key>"));
+    assert_eq!(h.document().unwrap(), "a: 1\n");
+}
+
+#[test]
+fn test_unwrap_undoes_wrapping() {
+    let yaml = "name: api\nport: 80\nimages:\n- nginx\n";
+    let mut h = Harness::new(yaml).unwrap();
+    h.keys("jg[g]").unwrap();
+    assert_eq!(h.document().unwrap(), yaml);
+    assert_eq!(message(&h).as_deref(), Some("Unwrapped"));
+
+    h.keys(":path $.images[0]<CR>g{image<CR>g]").unwrap();
+    assert_eq!(h.document().unwrap(), yaml);
+
+    // On a one-item list itself, that list is replaced by its item
+    h.keys(":path $.images<CR>:unwrap<CR>").unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "name: api\nport: 80\nimages: nginx\n"
+    );
+
+    h.keys(":path $<CR>:unwrap<CR>").unwrap();
+    assert!(message(&h).is_some_and(|m| m.starts_with("Only a container with one child")));
+}
+
+#[test]
+fn test_splice_moves_children_into_parent() {
+    let mut h = Harness::new("name: api\nlimits:\n  cpu: 1\n  memory: 2Gi\nport: 80\n").unwrap();
+    h.keys(":path $.limits<CR>:splice<CR>").unwrap();
+    assert_eq!(
+        h.document().unwrap(),
+        "name: api\ncpu: 1\nmemory: 2Gi\nport: 80\n"
+    );
+    assert_eq!(message(&h).as_deref(), Some("Spliced 2 children"));
+    assert_eq!(h.state().cursor().path(), &[1]);
+
+    // Lists splice into lists, and entries become one-key items
+    let mut h = Harness::new("- a\n- [b, c]\n- {d: 1, e: 2}\n").unwrap();
+    h.keys(":path $[1]<CR>:splice<CR>:path $[3]<CR>:splice<CR>")
+        .unwrap();
+    assert_eq!(
+        h.document().unwrap().trim_end(),
+        "- a\n- b\n- c\n- d: 1\n- e: 2"
+    );
+
+    h.keys("uu").unwrap();
+    assert_eq!(
+        h.document().unwrap().trim_end(),
+        "- a\n- [b, c]\n- {d: 1, e: 2}"
+    );
+}
+
+#[test]
+fn test_splice_refuses_key_clashes() {
+    let yaml = "port: 80\nweb:\n  port: 8080\n";
+    let mut h = Harness::new(yaml).unwrap();
+    h.keys(":path $.web<CR>:splice<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Key 'port' already exists there")
+    );
+    assert_eq!(h.document().unwrap(), yaml);
+
+    let mut h = Harness::new("a:\n- 1\n- 2\n").unwrap();
+    h.keys(":path $.a<CR>:splice<CR>").unwrap();
+    assert_eq!(
+        message(&h).as_deref(),
+        Some("Only list items with one key can be spliced into a mapping")
+    );
+}
@@ -77,9 +77,9 @@ fn test_integer_display() {
 
 #[test]
 fn test_float_display() {
-    let node = YamlNode::new(YamlValue::Number(YamlNumber::Float(3.14)));
+    let node = YamlNode::new(YamlValue::Number(YamlNumber::Float(2.75)));
     let preview = format_collapsed_preview(&node, 100);
-    assert_eq!(preview, "3.14", "Float should display with decimal point");
+    assert_eq!(preview, "2.75", "Float should display with decimal point");
 }
 
 #[test]
@@ -179,7 +179,7 @@ fn test_tree_view_shows_number_types() {
     );
     obj.insert(
         "float".to_string(),
-        YamlNode::new(YamlValue::Number(YamlNumber::Float(3.14))),
+        YamlNode::new(YamlValue::Number(YamlNumber::Float(2.75))),
     );
 
     let tree = YamlTree::new(YamlNode::new(YamlValue::Object(obj)));
@@ -195,7 +195,7 @@ fn test_tree_view_shows_number_types() {
 
     // Check float
     assert_eq!(lines[1].key, Some("float".to_string()));
-    assert_eq!(lines[1].value_preview, "3.14");
+    assert_eq!(lines[1].value_preview, "2.75");
 }
 
 #[test]